@@ -1,13 +1,16 @@
 //! DdcStaking pallet benchmarking.
 
-use ddc_primitives::{ClusterGovParams, ClusterId, ClusterParams, NodePubKey};
+use ddc_primitives::{
+	ClusterGovParams, ClusterId, ClusterParams, ClusterPricingTiers, NodePubKey, PricingTier,
+	MAX_CLUSTER_REGIONS, MAX_PRICING_TIERS, MAX_REGION_LEN,
+};
 pub use frame_benchmarking::{
 	account, benchmarks, impl_benchmark_test_suite, whitelist_account, whitelisted_caller,
 	BenchmarkError,
 };
 use frame_system::RawOrigin;
 use sp_core::crypto::UncheckedFrom;
-use sp_runtime::{AccountId32, Perquintill};
+use sp_runtime::{AccountId32, FixedU128, Perquintill};
 use sp_std::prelude::*;
 use testing_utils::*;
 
@@ -36,6 +39,7 @@ benchmarks! {
 			unit_per_mb_streamed: 10,
 			unit_per_put_request: 10,
 			unit_per_get_request: 10,
+			billing_period: 2_630_016,
 		};
 	}: _(RawOrigin::Root, cluster_id, user.clone(), user, cluster_params, cluster_gov_params)
 	verify {
@@ -99,12 +103,73 @@ benchmarks! {
 			unit_per_mb_streamed: 1,
 			unit_per_put_request: 1,
 			unit_per_get_request: 1,
+			billing_period: 2_630_016,
 		};
 	}: _(RawOrigin::Root, cluster_id, new_cluster_gov_params.clone())
 	verify {
 		assert_eq!(ClustersGovParams::<T>::try_get(cluster_id).unwrap(), new_cluster_gov_params);
 	}
 
+	set_customer_discount {
+		let cluster_id = ClusterId::from([1; 20]);
+		let user = account::<T::AccountId>("user", USER_SEED, 0u32);
+		let customer = account::<T::AccountId>("customer", USER_SEED_2, 0u32);
+		let _ = config_cluster::<T>(user.clone(), cluster_id);
+		let discount = Perquintill::from_percent(10);
+	}: _(RawOrigin::Signed(user), cluster_id, customer.clone(), discount)
+	verify {
+		assert_eq!(CustomerDiscounts::<T>::get(cluster_id, customer), Some(discount));
+	}
+
+	set_cluster_capabilities {
+		let cluster_id = ClusterId::from([1; 20]);
+		let user = account::<T::AccountId>("user", USER_SEED, 0u32);
+		let _ = config_cluster::<T>(user, cluster_id);
+		let cluster_capabilities = ClusterCapabilities {
+			default_replication_factor: 3,
+			max_replication_factor: 5,
+			regions: BoundedVec::truncate_from(
+				(0..MAX_CLUSTER_REGIONS)
+					.map(|i| BoundedVec::truncate_from([i as u8; MAX_REGION_LEN as usize].to_vec()))
+					.collect(),
+			),
+			cold_storage_discount: Some(Perquintill::from_percent(50)),
+		};
+	}: _(RawOrigin::Root, cluster_id, cluster_capabilities.clone())
+	verify {
+		assert_eq!(ClustersCapabilities::<T>::get(cluster_id), Some(cluster_capabilities));
+	}
+
+	set_cluster_asset_rate {
+		let cluster_id = ClusterId::from([1; 20]);
+		let user = account::<T::AccountId>("user", USER_SEED, 0u32);
+		let _ = config_cluster::<T>(user, cluster_id);
+		let rate = FixedU128::from_rational(3, 2);
+	}: _(RawOrigin::Root, cluster_id, 1, Some(rate))
+	verify {
+		assert_eq!(ClustersAssetRates::<T>::get(cluster_id, 1), Some(rate));
+	}
+
+	set_cluster_pricing_tiers {
+		let cluster_id = ClusterId::from([1; 20]);
+		let user = account::<T::AccountId>("user", USER_SEED, 0u32);
+		let _ = config_cluster::<T>(user, cluster_id);
+		let tiers = BoundedVec::truncate_from(
+			(1..=MAX_PRICING_TIERS as u64)
+				.map(|threshold| PricingTier { threshold, unit_price: 1 })
+				.collect(),
+		);
+		let pricing_tiers = ClusterPricingTiers {
+			streamed: tiers.clone(),
+			stored: tiers.clone(),
+			puts: tiers.clone(),
+			gets: tiers,
+		};
+	}: _(RawOrigin::Root, cluster_id, pricing_tiers.clone())
+	verify {
+		assert_eq!(ClustersPricingTiers::<T>::get(cluster_id), Some(pricing_tiers));
+	}
+
 	impl_benchmark_test_suite!(
 		DdcClusters,
 		crate::mock::ExtBuilder.build(),
@@ -6,6 +6,15 @@
 //! - [`Call`]
 //! - [`Pallet`]
 //!
+//! ## Pricing
+//!
+//! The usage in a cluster is priced by the flat `unit_per_*` prices of its
+//! `ClusterGovParams`. Governance may override them above usage thresholds with the tiered
+//! prices set by `set_cluster_pricing_tiers`, up to `MAX_PRICING_TIERS` tiers per usage type.
+//! The tiers are kept in the separate `ClustersPricingTiers` map rather than in an extended
+//! `ClusterGovParams`, so the gov params of the existing clusters need no migration and a
+//! cluster without tiers is priced flat.
+//!
 //! ## GenesisConfig
 //!
 //! The DDC Clusters pallet depends on the [`GenesisConfig`]. The
@@ -28,11 +37,14 @@ mod tests;
 
 use ddc_primitives::{
 	traits::{
-		cluster::{ClusterCreator, ClusterVisitor, ClusterVisitorError},
+		cluster::{
+			ClusterCreator, ClusterVisitor, ClusterVisitorError, OnClusterBondingParamsChanged,
+		},
 		staking::{StakerCreator, StakingVisitor, StakingVisitorError},
 	},
-	ClusterBondingParams, ClusterFeesParams, ClusterGovParams, ClusterId, ClusterParams,
-	ClusterPricingParams, NodePubKey, NodeType,
+	AssetId, ClusterBondingParams, ClusterCapabilities, ClusterFeesParams, ClusterGovParams,
+	ClusterId, ClusterParams, ClusterPricingParams, ClusterPricingTiers, NodePubKey, NodeType,
+	PricingTier,
 };
 use frame_support::{
 	assert_ok,
@@ -43,7 +55,7 @@ use frame_system::pallet_prelude::*;
 pub use pallet::*;
 use pallet_ddc_nodes::{NodeRepository, NodeTrait};
 use sp_core::crypto::UncheckedFrom;
-use sp_runtime::SaturatedConversion;
+use sp_runtime::{traits::Zero, FixedU128, Perquintill, SaturatedConversion};
 use sp_std::prelude::*;
 
 use crate::{
@@ -52,6 +64,7 @@ use crate::{
 };
 
 pub mod cluster;
+pub mod migration;
 mod node_provider_auth;
 
 /// The balance type of this pallet.
@@ -66,7 +79,7 @@ pub mod pallet {
 
 	/// The current storage version.
 	const STORAGE_VERSION: frame_support::traits::StorageVersion =
-		frame_support::traits::StorageVersion::new(0);
+		frame_support::traits::StorageVersion::new(1);
 
 	#[pallet::pallet]
 	#[pallet::storage_version(STORAGE_VERSION)]
@@ -81,16 +94,35 @@ pub mod pallet {
 		type StakerCreator: StakerCreator<Self, BalanceOf<Self>>;
 		type Currency: LockableCurrency<Self::AccountId, Moment = BlockNumberFor<Self>>;
 		type WeightInfo: WeightInfo;
+		/// Notified when the governance changes the bonding params of a cluster.
+		type OnBondingParamsChanged: OnClusterBondingParamsChanged<Self>;
 	}
 
 	#[pallet::event]
 	#[pallet::generate_deposit(pub(crate) fn deposit_event)]
 	pub enum Event<T: Config> {
+		/// The cluster has been created
 		ClusterCreated { cluster_id: ClusterId },
+		/// The node has been added to the cluster
 		ClusterNodeAdded { cluster_id: ClusterId, node_pub_key: NodePubKey },
+		/// The node has been removed from the cluster
 		ClusterNodeRemoved { cluster_id: ClusterId, node_pub_key: NodePubKey },
+		/// The cluster params have been updated by the cluster manager
 		ClusterParamsSet { cluster_id: ClusterId },
+		/// The cluster gov params have been updated by governance
 		ClusterGovParamsSet { cluster_id: ClusterId },
+		/// The discount of the customer on the cluster usage charges has been set
+		CustomerDiscountSet {
+			cluster_id: ClusterId,
+			customer_id: T::AccountId,
+			discount: Perquintill,
+		},
+		/// The bucket metadata supported by the cluster have been set
+		ClusterCapabilitiesSet { cluster_id: ClusterId },
+		/// The rate of the asset accepted for the cluster usage has been set
+		ClusterAssetRateSet { cluster_id: ClusterId, asset_id: AssetId, rate: Option<FixedU128> },
+		/// The pricing tiers of the cluster have been set by governance
+		ClusterPricingTiersSet { cluster_id: ClusterId },
 	}
 
 	#[pallet::error]
@@ -111,6 +143,16 @@ pub mod pallet {
 		NodeAuthContractCallFailed,
 		NodeAuthContractDeployFailed,
 		NodeAuthNodeAuthorizationNotSuccessful,
+		/// Billing period of the cluster can not be zero.
+		InvalidBillingPeriod,
+		/// Pricing tiers should have ascending non-zero thresholds and not exceed the limit.
+		InvalidPricingTiers,
+		/// Default replication factor should be between one and the maximum one.
+		InvalidReplicationFactor,
+		/// Asset rate can not be zero.
+		InvalidAssetRate,
+		/// Node stake is below the bond size of the cluster.
+		NodeStakeIsUnderBonded,
 	}
 
 	#[pallet::storage]
@@ -123,6 +165,36 @@ pub mod pallet {
 	pub type ClustersGovParams<T: Config> =
 		StorageMap<_, Twox64Concat, ClusterId, ClusterGovParams<BalanceOf<T>, BlockNumberFor<T>>>;
 
+	/// Pricing tiers of the cluster usage, set by governance
+	#[pallet::storage]
+	#[pallet::getter(fn clusters_pricing_tiers)]
+	pub type ClustersPricingTiers<T: Config> =
+		StorageMap<_, Twox64Concat, ClusterId, ClusterPricingTiers>;
+
+	/// Discount of the customer on the usage charges in the cluster, set by the cluster manager
+	#[pallet::storage]
+	#[pallet::getter(fn customer_discounts)]
+	pub type CustomerDiscounts<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		ClusterId,
+		Blake2_128Concat,
+		T::AccountId,
+		Perquintill,
+	>;
+
+	/// Bucket metadata supported by the cluster, set by governance
+	#[pallet::storage]
+	#[pallet::getter(fn clusters_capabilities)]
+	pub type ClustersCapabilities<T: Config> =
+		StorageMap<_, Twox64Concat, ClusterId, ClusterCapabilities>;
+
+	/// Amount of the asset charged per unit of the native currency, set by governance
+	#[pallet::storage]
+	#[pallet::getter(fn clusters_asset_rates)]
+	pub type ClustersAssetRates<T: Config> =
+		StorageDoubleMap<_, Twox64Concat, ClusterId, Twox64Concat, AssetId, FixedU128>;
+
 	#[pallet::storage]
 	#[pallet::getter(fn clusters_nodes)]
 	pub type ClustersNodes<T: Config> = StorageDoubleMap<
@@ -315,9 +387,120 @@ pub mod pallet {
 			ensure_root(origin)?; // requires Governance approval
 			let _cluster =
 				Clusters::<T>::try_get(cluster_id).map_err(|_| Error::<T>::ClusterDoesNotExist)?;
+			ensure!(cluster_gov_params.billing_period > 0, Error::<T>::InvalidBillingPeriod);
+			let old_bonding_params =
+				<Self as ClusterVisitor<T>>::get_bonding_params(&cluster_id).ok();
 			ClustersGovParams::<T>::insert(cluster_id, cluster_gov_params);
 			Self::deposit_event(Event::<T>::ClusterGovParamsSet { cluster_id });
 
+			// Staking schedules a check of the stakes of the nodes in the cluster against the new
+			// bond size.
+			let bonding_params = <Self as ClusterVisitor<T>>::get_bonding_params(&cluster_id)
+				.map_err(|_| Error::<T>::ClusterDoesNotExist)?;
+			if old_bonding_params.as_ref() != Some(&bonding_params) {
+				T::OnBondingParamsChanged::on_bonding_params_changed(&cluster_id, &bonding_params);
+			}
+
+			Ok(())
+		}
+
+		// Sets the discount of the customer on the cluster usage charges, zero discount removes it
+		#[pallet::call_index(5)]
+		#[pallet::weight(<T as pallet::Config>::WeightInfo::set_customer_discount())]
+		pub fn set_customer_discount(
+			origin: OriginFor<T>,
+			cluster_id: ClusterId,
+			customer_id: T::AccountId,
+			discount: Perquintill,
+		) -> DispatchResult {
+			let caller_id = ensure_signed(origin)?;
+			let cluster =
+				Clusters::<T>::try_get(cluster_id).map_err(|_| Error::<T>::ClusterDoesNotExist)?;
+			ensure!(cluster.manager_id == caller_id, Error::<T>::OnlyClusterManager);
+
+			if discount.is_zero() {
+				CustomerDiscounts::<T>::remove(cluster_id, &customer_id);
+			} else {
+				CustomerDiscounts::<T>::insert(cluster_id, &customer_id, discount);
+			}
+			Self::deposit_event(Event::<T>::CustomerDiscountSet {
+				cluster_id,
+				customer_id,
+				discount,
+			});
+
+			Ok(())
+		}
+
+		// Sets the replication, regions and storage classes the buckets of the cluster can use
+		#[pallet::call_index(6)]
+		#[pallet::weight(<T as pallet::Config>::WeightInfo::set_cluster_capabilities())]
+		pub fn set_cluster_capabilities(
+			origin: OriginFor<T>,
+			cluster_id: ClusterId,
+			cluster_capabilities: ClusterCapabilities,
+		) -> DispatchResult {
+			ensure_root(origin)?; // requires Governance approval
+			ensure!(Clusters::<T>::contains_key(cluster_id), Error::<T>::ClusterDoesNotExist);
+			ensure!(
+				cluster_capabilities.max_replication_factor == 0 ||
+					(1..=cluster_capabilities.max_replication_factor)
+						.contains(&cluster_capabilities.default_replication_factor),
+				Error::<T>::InvalidReplicationFactor
+			);
+			ClustersCapabilities::<T>::insert(cluster_id, cluster_capabilities);
+			Self::deposit_event(Event::<T>::ClusterCapabilitiesSet { cluster_id });
+
+			Ok(())
+		}
+
+		// Sets the rate the customers paying with the asset are charged at, or stops accepting
+		// the asset if no rate is provided
+		#[pallet::call_index(7)]
+		#[pallet::weight(<T as pallet::Config>::WeightInfo::set_cluster_asset_rate())]
+		pub fn set_cluster_asset_rate(
+			origin: OriginFor<T>,
+			cluster_id: ClusterId,
+			asset_id: AssetId,
+			rate: Option<FixedU128>,
+		) -> DispatchResult {
+			ensure_root(origin)?; // requires Governance approval
+			ensure!(Clusters::<T>::contains_key(cluster_id), Error::<T>::ClusterDoesNotExist);
+			match rate {
+				Some(rate) => {
+					ensure!(!rate.is_zero(), Error::<T>::InvalidAssetRate);
+					ClustersAssetRates::<T>::insert(cluster_id, asset_id, rate);
+				},
+				None => ClustersAssetRates::<T>::remove(cluster_id, asset_id),
+			}
+			Self::deposit_event(Event::<T>::ClusterAssetRateSet { cluster_id, asset_id, rate });
+
+			Ok(())
+		}
+
+		// Sets the tiered prices of the cluster usage, overriding the flat prices of the gov
+		// params above the tiers thresholds
+		#[pallet::call_index(8)]
+		#[pallet::weight(<T as pallet::Config>::WeightInfo::set_cluster_pricing_tiers())]
+		pub fn set_cluster_pricing_tiers(
+			origin: OriginFor<T>,
+			cluster_id: ClusterId,
+			pricing_tiers: ClusterPricingTiers,
+		) -> DispatchResult {
+			ensure_root(origin)?; // requires Governance approval
+			ensure!(Clusters::<T>::contains_key(cluster_id), Error::<T>::ClusterDoesNotExist);
+			let ClusterPricingTiers { streamed, stored, puts, gets } = &pricing_tiers;
+			for tiers in [streamed, stored, puts, gets] {
+				ensure!(Self::are_valid_tiers(tiers), Error::<T>::InvalidPricingTiers);
+			}
+
+			if pricing_tiers == ClusterPricingTiers::default() {
+				ClustersPricingTiers::<T>::remove(cluster_id);
+			} else {
+				ClustersPricingTiers::<T>::insert(cluster_id, pricing_tiers);
+			}
+			Self::deposit_event(Event::<T>::ClusterPricingTiersSet { cluster_id });
+
 			Ok(())
 		}
 	}
@@ -334,6 +517,7 @@ pub mod pallet {
 				Cluster::new(cluster_id, cluster_manager_id, cluster_reserve_id, cluster_params)
 					.map_err(Into::<Error<T>>::into)?;
 			ensure!(!Clusters::<T>::contains_key(cluster_id), Error::<T>::ClusterAlreadyExists);
+			ensure!(cluster_gov_params.billing_period > 0, Error::<T>::InvalidBillingPeriod);
 
 			Clusters::<T>::insert(cluster_id, cluster);
 			ClustersGovParams::<T>::insert(cluster_id, cluster_gov_params);
@@ -341,6 +525,11 @@ pub mod pallet {
 
 			Ok(())
 		}
+
+		fn are_valid_tiers(tiers: &[PricingTier]) -> bool {
+			tiers.first().map_or(true, |tier| tier.threshold > 0) &&
+				tiers.windows(2).all(|pair| pair[0].threshold < pair[1].threshold)
+		}
 	}
 
 	impl<T: Config> ClusterVisitor<T> for Pallet<T> {
@@ -372,6 +561,8 @@ pub mod pallet {
 				unit_per_mb_streamed: cluster_gov_params.unit_per_mb_streamed,
 				unit_per_put_request: cluster_gov_params.unit_per_put_request,
 				unit_per_get_request: cluster_gov_params.unit_per_get_request,
+				billing_period: cluster_gov_params.billing_period,
+				pricing_tiers: ClustersPricingTiers::<T>::get(cluster_id).unwrap_or_default(),
 			})
 		}
 
@@ -388,6 +579,13 @@ pub mod pallet {
 			})
 		}
 
+		fn get_customer_discount(
+			cluster_id: &ClusterId,
+			customer_id: &T::AccountId,
+		) -> Perquintill {
+			CustomerDiscounts::<T>::get(cluster_id, customer_id).unwrap_or_default()
+		}
+
 		fn get_reserve_account_id(
 			cluster_id: &ClusterId,
 		) -> Result<T::AccountId, ClusterVisitorError> {
@@ -429,6 +627,17 @@ pub mod pallet {
 				storage_unbonding_delay: cluster_gov_params.storage_unbonding_delay,
 			})
 		}
+
+		fn get_capabilities(
+			cluster_id: &ClusterId,
+		) -> Result<ClusterCapabilities, ClusterVisitorError> {
+			Self::ensure_cluster(cluster_id)?;
+			Ok(ClustersCapabilities::<T>::get(cluster_id).unwrap_or_default())
+		}
+
+		fn get_asset_rate(cluster_id: &ClusterId, asset_id: AssetId) -> Option<FixedU128> {
+			ClustersAssetRates::<T>::get(cluster_id, asset_id)
+		}
 	}
 
 	impl<T: Config> ClusterManager<T> for Pallet<T> {
@@ -505,6 +714,7 @@ pub mod pallet {
 			match error {
 				StakingVisitorError::NodeStakeDoesNotExist => Error::<T>::NodeHasNoActivatedStake,
 				StakingVisitorError::NodeStakeIsInBadState => Error::<T>::NodeStakeIsInvalid,
+				StakingVisitorError::NodeStakeIsUnderBonded => Error::<T>::NodeStakeIsUnderBonded,
 			}
 		}
 	}
@@ -0,0 +1,136 @@
+#[cfg(feature = "try-runtime")]
+use frame_support::ensure;
+use frame_support::{
+	storage_alias,
+	traits::{Get, GetStorageVersion, OnRuntimeUpgrade, StorageVersion},
+	weights::Weight,
+};
+use log::info;
+use sp_runtime::Perquintill;
+
+use super::*;
+
+const LOG_TARGET: &str = "ddc-clusters";
+
+/// Billing period the storage prices were applied to before it became configurable, i.e.
+/// 30.44 days.
+pub const DEFAULT_BILLING_PERIOD: u64 = 2_630_016;
+
+pub mod v0 {
+	use frame_support::pallet_prelude::*;
+
+	use super::*;
+
+	#[derive(Clone, Encode, Decode, RuntimeDebug, TypeInfo, PartialEq)]
+	pub struct ClusterGovParams<Balance, BlockNumber> {
+		pub treasury_share: Perquintill,
+		pub validators_share: Perquintill,
+		pub cluster_reserve_share: Perquintill,
+		pub storage_bond_size: Balance,
+		pub storage_chill_delay: BlockNumber,
+		pub storage_unbonding_delay: BlockNumber,
+		pub unit_per_mb_stored: u128,
+		pub unit_per_mb_streamed: u128,
+		pub unit_per_put_request: u128,
+		pub unit_per_get_request: u128,
+	}
+
+	pub type ClusterGovParamsOf<T> = ClusterGovParams<BalanceOf<T>, BlockNumberFor<T>>;
+
+	#[storage_alias]
+	pub(super) type ClustersGovParams<T: Config> =
+		StorageMap<crate::Pallet<T>, Twox64Concat, ClusterId, ClusterGovParamsOf<T>>;
+}
+
+// Migrate to configurable billing period
+pub fn migrate_to_v1<T: Config>() -> Weight {
+	let on_chain_version = Pallet::<T>::on_chain_storage_version();
+	if on_chain_version == 0 {
+		let count = v0::ClustersGovParams::<T>::iter().count() as u64;
+		info!(
+			target: LOG_TARGET,
+			" >>> Updating DDC Clusters storage. Migrating {} clusters gov params...", count
+		);
+
+		ClustersGovParams::<T>::translate::<v0::ClusterGovParamsOf<T>, _>(
+			|cluster_id: ClusterId, params: v0::ClusterGovParamsOf<T>| {
+				info!(
+					target: LOG_TARGET,
+					"     Migrating gov params for cluster {:?}...", cluster_id
+				);
+
+				Some(ClusterGovParams {
+					treasury_share: params.treasury_share,
+					validators_share: params.validators_share,
+					cluster_reserve_share: params.cluster_reserve_share,
+					storage_bond_size: params.storage_bond_size,
+					storage_chill_delay: params.storage_chill_delay,
+					storage_unbonding_delay: params.storage_unbonding_delay,
+					unit_per_mb_stored: params.unit_per_mb_stored,
+					unit_per_mb_streamed: params.unit_per_mb_streamed,
+					unit_per_put_request: params.unit_per_put_request,
+					unit_per_get_request: params.unit_per_get_request,
+					billing_period: DEFAULT_BILLING_PERIOD,
+				})
+			},
+		);
+
+		// Update storage version.
+		StorageVersion::new(1).put::<Pallet<T>>();
+		info!(
+			target: LOG_TARGET,
+			" <<< DDC Clusters storage updated! Migrated {} clusters gov params ✅", count
+		);
+
+		T::DbWeight::get().reads_writes(count * 2 + 1, count + 1)
+	} else {
+		info!(target: LOG_TARGET, " >>> Unused migration!");
+		T::DbWeight::get().reads(1)
+	}
+}
+
+pub struct MigrateToV1<T>(sp_std::marker::PhantomData<T>);
+impl<T: Config> OnRuntimeUpgrade for MigrateToV1<T> {
+	fn on_runtime_upgrade() -> Weight {
+		migrate_to_v1::<T>()
+	}
+
+	#[cfg(feature = "try-runtime")]
+	fn pre_upgrade() -> Result<Vec<u8>, &'static str> {
+		let prev_count = v0::ClustersGovParams::<T>::iter().count();
+
+		Ok((prev_count as u64).encode())
+	}
+
+	#[cfg(feature = "try-runtime")]
+	fn post_upgrade(prev_state: Vec<u8>) -> Result<(), &'static str> {
+		let prev_count: u64 =
+			Decode::decode(&mut &prev_state[..]).expect("pre_upgrade provides a valid state; qed");
+
+		let post_count = ClustersGovParams::<T>::iter().count() as u64;
+		ensure!(
+			prev_count == post_count,
+			"the clusters gov params count before and after the migration should be the same"
+		);
+
+		let current_version = Pallet::<T>::current_storage_version();
+		let on_chain_version = Pallet::<T>::on_chain_storage_version();
+
+		frame_support::ensure!(current_version == 1, "must_upgrade");
+		ensure!(
+			current_version == on_chain_version,
+			"after migration, the current_version and on_chain_version should be the same"
+		);
+
+		ClustersGovParams::<T>::iter().try_for_each(
+			|(_id, params)| -> Result<(), &'static str> {
+				ensure!(
+					params.billing_period == DEFAULT_BILLING_PERIOD,
+					"At this point all the clusters should have the default billing period"
+				);
+				Ok(())
+			},
+		)?;
+		Ok(())
+	}
+}
@@ -3,8 +3,11 @@
 #![allow(dead_code)]
 
 use ddc_primitives::{
-	traits::staking::{StakerCreator, StakingVisitor, StakingVisitorError},
-	ClusterId, NodePubKey,
+	traits::{
+		cluster::OnClusterBondingParamsChanged,
+		staking::{StakerCreator, StakingVisitor, StakingVisitorError},
+	},
+	ClusterBondingParams, ClusterId, NodePubKey,
 };
 use frame_support::{
 	construct_runtime, parameter_types,
@@ -133,6 +136,7 @@ where
 
 parameter_types! {
 	pub static ExistentialDeposit: Balance = 1;
+	pub static BondingParamsChanges: Vec<(ClusterId, ClusterBondingParams<BlockNumber>)> = vec![];
 }
 
 impl frame_system::Config for Test {
@@ -197,12 +201,14 @@ impl crate::pallet::Config for Test {
 	type StakingVisitor = TestStakingVisitor;
 	type StakerCreator = TestStaker;
 	type WeightInfo = ();
+	type OnBondingParamsChanged = TestBondingParamsObserver;
 }
 
 pub(crate) type DdcStakingCall = crate::Call<Test>;
 pub(crate) type TestRuntimeCall = <Test as frame_system::Config>::RuntimeCall;
 pub struct TestStakingVisitor;
 pub struct TestStaker;
+pub struct TestBondingParamsObserver;
 
 impl<T: Config> StakingVisitor<T> for TestStakingVisitor {
 	fn has_activated_stake(
@@ -231,6 +237,15 @@ impl<T: Config> StakerCreator<T, BalanceOf<T>> for TestStaker {
 	}
 }
 
+impl OnClusterBondingParamsChanged<Test> for TestBondingParamsObserver {
+	fn on_bonding_params_changed(
+		cluster_id: &ClusterId,
+		bonding_params: &ClusterBondingParams<BlockNumber>,
+	) {
+		BondingParamsChanges::mutate(|changes| changes.push((*cluster_id, bonding_params.clone())));
+	}
+}
+
 pub struct ExtBuilder;
 
 impl ExtBuilder {
@@ -260,6 +275,7 @@ impl ExtBuilder {
 			unit_per_mb_streamed: 10,
 			unit_per_put_request: 10,
 			unit_per_get_request: 10,
+			billing_period: 2_630_016,
 		};
 
 		let node_pub_key = NodePubKey::StoragePubKey(AccountId::from([0; 32]));
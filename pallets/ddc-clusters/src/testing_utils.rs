@@ -32,6 +32,7 @@ where
 		unit_per_mb_streamed: 10,
 		unit_per_put_request: 10,
 		unit_per_get_request: 10,
+		billing_period: 2_630_016,
 	};
 
 	let _ = DdcClusters::<T>::create_cluster(
@@ -74,6 +75,7 @@ where
 		unit_per_mb_streamed: 10,
 		unit_per_put_request: 10,
 		unit_per_get_request: 10,
+		billing_period: 2_630_016,
 	};
 
 	let _ = DdcClusters::<T>::create_cluster(
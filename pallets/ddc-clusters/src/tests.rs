@@ -1,14 +1,14 @@
 //! Tests for the module.
 
 use ddc_primitives::{
-	traits::cluster::ClusterManager, ClusterBondingParams, ClusterFeesParams, ClusterId,
-	ClusterParams, ClusterPricingParams, NodeParams, NodePubKey, StorageNodeMode,
-	StorageNodeParams,
+	traits::cluster::ClusterManager, ClusterBondingParams, ClusterCapabilities, ClusterFeesParams,
+	ClusterId, ClusterParams, ClusterPricingParams, ClusterPricingTiers, NodeParams, NodePubKey,
+	PricingTier, StorageNodeMode, StorageNodeParams,
 };
 use frame_support::{assert_noop, assert_ok, error::BadOrigin};
 use frame_system::Config;
 use hex_literal::hex;
-use sp_runtime::{traits::Hash, Perquintill};
+use sp_runtime::{traits::Hash, FixedU128, Perquintill};
 
 use super::{mock::*, *};
 
@@ -33,6 +33,7 @@ fn create_cluster_works() {
 			unit_per_mb_streamed: 10,
 			unit_per_put_request: 10,
 			unit_per_get_request: 10,
+			billing_period: 2_630_016,
 		};
 
 		// Creating cluster not with root signature should fail
@@ -102,6 +103,7 @@ fn create_cluster_works() {
 			created_cluster_gov_params.unit_per_get_request,
 			cluster_gov_params.unit_per_get_request
 		);
+		assert_eq!(created_cluster_gov_params.billing_period, cluster_gov_params.billing_period);
 
 		// Creating cluster with same id should fail
 		assert_noop!(
@@ -164,6 +166,7 @@ fn add_and_delete_node_works() {
 				unit_per_mb_streamed: 10,
 				unit_per_put_request: 10,
 				unit_per_get_request: 10,
+				billing_period: 2_630_016,
 			}
 		));
 
@@ -384,6 +387,7 @@ fn set_cluster_params_works() {
 				unit_per_mb_streamed: 10,
 				unit_per_put_request: 10,
 				unit_per_get_request: 10,
+				billing_period: 2_630_016,
 			}
 		));
 
@@ -432,6 +436,7 @@ fn set_cluster_gov_params_works() {
 			unit_per_mb_streamed: 10,
 			unit_per_put_request: 10,
 			unit_per_get_request: 10,
+			billing_period: 2_630_016,
 		};
 
 		// Cluster doesn't exist
@@ -473,14 +478,46 @@ fn set_cluster_gov_params_works() {
 			unit_per_mb_streamed: 100,
 			unit_per_put_request: 100,
 			unit_per_get_request: 100,
+			billing_period: 86_400,
 		};
 
+		assert_noop!(
+			DdcClusters::set_cluster_gov_params(
+				RuntimeOrigin::root(),
+				cluster_id,
+				ClusterGovParams { billing_period: 0, ..updated_gov_params.clone() }
+			),
+			Error::<Test>::InvalidBillingPeriod
+		);
+		assert!(BondingParamsChanges::get().is_empty());
+
 		assert_ok!(DdcClusters::set_cluster_gov_params(
 			RuntimeOrigin::root(),
 			cluster_id,
 			updated_gov_params.clone()
 		));
 
+		// Staking is notified about the new bonding params
+		assert_eq!(
+			BondingParamsChanges::get(),
+			vec![(
+				cluster_id,
+				ClusterBondingParams {
+					storage_bond_size: 1000,
+					storage_chill_delay: 500,
+					storage_unbonding_delay: 500,
+				}
+			)]
+		);
+
+		// Setting the same bonding params again does not notify staking
+		assert_ok!(DdcClusters::set_cluster_gov_params(
+			RuntimeOrigin::root(),
+			cluster_id,
+			ClusterGovParams { unit_per_mb_stored: 200, ..updated_gov_params.clone() }
+		));
+		assert_eq!(BondingParamsChanges::get().len(), 1);
+
 		let updated_cluster_gov_params = DdcClusters::clusters_gov_params(cluster_id).unwrap();
 		assert_eq!(updated_cluster_gov_params.treasury_share, updated_gov_params.treasury_share);
 		assert_eq!(
@@ -519,13 +556,410 @@ fn set_cluster_gov_params_works() {
 			updated_cluster_gov_params.unit_per_get_request,
 			updated_gov_params.unit_per_get_request
 		);
+		assert_eq!(updated_cluster_gov_params.billing_period, updated_gov_params.billing_period);
 
 		// Checking that event was emitted
-		assert_eq!(System::events().len(), 2);
+		assert_eq!(System::events().len(), 3);
 		System::assert_last_event(Event::ClusterGovParamsSet { cluster_id }.into())
 	})
 }
 
+#[test]
+fn set_customer_discount_works() {
+	ExtBuilder.build_and_execute(|| {
+		System::set_block_number(1);
+
+		let cluster_id = ClusterId::from([1; 20]);
+		let cluster_manager_id = AccountId::from([1; 32]);
+		let cluster_reserve_id = AccountId::from([2; 32]);
+		let auth_contract = AccountId::from([3; 32]);
+		let customer_id = AccountId::from([4; 32]);
+		let discount = Perquintill::from_percent(15);
+
+		// Cluster doesn't exist
+		assert_noop!(
+			DdcClusters::set_customer_discount(
+				RuntimeOrigin::signed(cluster_manager_id.clone()),
+				cluster_id,
+				customer_id.clone(),
+				discount
+			),
+			Error::<Test>::ClusterDoesNotExist
+		);
+
+		assert_ok!(DdcClusters::create_cluster(
+			RuntimeOrigin::root(),
+			cluster_id,
+			cluster_manager_id.clone(),
+			cluster_reserve_id.clone(),
+			ClusterParams { node_provider_auth_contract: Some(auth_contract) },
+			ClusterGovParams {
+				treasury_share: Perquintill::from_float(0.05),
+				validators_share: Perquintill::from_float(0.01),
+				cluster_reserve_share: Perquintill::from_float(0.02),
+				storage_bond_size: 100,
+				storage_chill_delay: 50,
+				storage_unbonding_delay: 50,
+				unit_per_mb_stored: 10,
+				unit_per_mb_streamed: 10,
+				unit_per_put_request: 10,
+				unit_per_get_request: 10,
+				billing_period: 2_630_016,
+			}
+		));
+
+		// Only the cluster manager can set discounts
+		assert_noop!(
+			DdcClusters::set_customer_discount(
+				RuntimeOrigin::signed(cluster_reserve_id),
+				cluster_id,
+				customer_id.clone(),
+				discount
+			),
+			Error::<Test>::OnlyClusterManager
+		);
+
+		assert_ok!(DdcClusters::set_customer_discount(
+			RuntimeOrigin::signed(cluster_manager_id.clone()),
+			cluster_id,
+			customer_id.clone(),
+			discount
+		));
+		assert_eq!(DdcClusters::customer_discounts(cluster_id, &customer_id), Some(discount));
+		assert_eq!(
+			<DdcClusters as ClusterVisitor<Test>>::get_customer_discount(&cluster_id, &customer_id),
+			discount
+		);
+		System::assert_last_event(
+			Event::CustomerDiscountSet { cluster_id, customer_id: customer_id.clone(), discount }
+				.into(),
+		);
+
+		// Zero discount removes the entry
+		assert_ok!(DdcClusters::set_customer_discount(
+			RuntimeOrigin::signed(cluster_manager_id),
+			cluster_id,
+			customer_id.clone(),
+			Perquintill::zero()
+		));
+		assert_eq!(DdcClusters::customer_discounts(cluster_id, &customer_id), None);
+		assert_eq!(
+			<DdcClusters as ClusterVisitor<Test>>::get_customer_discount(&cluster_id, &customer_id),
+			Perquintill::zero()
+		);
+	})
+}
+
+#[test]
+fn set_cluster_capabilities_works() {
+	ExtBuilder.build_and_execute(|| {
+		System::set_block_number(1);
+
+		let cluster_id = ClusterId::from([1; 20]);
+		let cluster_manager_id = AccountId::from([1; 32]);
+		let cluster_reserve_id = AccountId::from([2; 32]);
+		let auth_contract = AccountId::from([3; 32]);
+		let cluster_capabilities = ClusterCapabilities {
+			default_replication_factor: 3,
+			max_replication_factor: 5,
+			regions: BoundedVec::truncate_from(vec![BoundedVec::truncate_from(b"eu".to_vec())]),
+			cold_storage_discount: Some(Perquintill::from_percent(40)),
+		};
+
+		// Cluster doesn't exist
+		assert_noop!(
+			DdcClusters::set_cluster_capabilities(
+				RuntimeOrigin::root(),
+				cluster_id,
+				cluster_capabilities.clone()
+			),
+			Error::<Test>::ClusterDoesNotExist
+		);
+		assert_eq!(
+			<DdcClusters as ClusterVisitor<Test>>::get_capabilities(&cluster_id),
+			Err(ClusterVisitorError::ClusterDoesNotExist)
+		);
+
+		assert_ok!(DdcClusters::create_cluster(
+			RuntimeOrigin::root(),
+			cluster_id,
+			cluster_manager_id.clone(),
+			cluster_reserve_id,
+			ClusterParams { node_provider_auth_contract: Some(auth_contract) },
+			ClusterGovParams {
+				treasury_share: Perquintill::from_float(0.05),
+				validators_share: Perquintill::from_float(0.01),
+				cluster_reserve_share: Perquintill::from_float(0.02),
+				storage_bond_size: 100,
+				storage_chill_delay: 50,
+				storage_unbonding_delay: 50,
+				unit_per_mb_stored: 10,
+				unit_per_mb_streamed: 10,
+				unit_per_put_request: 10,
+				unit_per_get_request: 10,
+				billing_period: 2_630_016,
+			}
+		));
+
+		// Clusters support no bucket metadata by default
+		assert_eq!(
+			<DdcClusters as ClusterVisitor<Test>>::get_capabilities(&cluster_id),
+			Ok(ClusterCapabilities::default())
+		);
+
+		// Requires Governance approval
+		assert_noop!(
+			DdcClusters::set_cluster_capabilities(
+				RuntimeOrigin::signed(cluster_manager_id),
+				cluster_id,
+				cluster_capabilities.clone()
+			),
+			BadOrigin
+		);
+
+		// Default replication factor should be within the maximum one
+		assert_noop!(
+			DdcClusters::set_cluster_capabilities(
+				RuntimeOrigin::root(),
+				cluster_id,
+				ClusterCapabilities {
+					default_replication_factor: 6,
+					..cluster_capabilities.clone()
+				}
+			),
+			Error::<Test>::InvalidReplicationFactor
+		);
+
+		assert_ok!(DdcClusters::set_cluster_capabilities(
+			RuntimeOrigin::root(),
+			cluster_id,
+			cluster_capabilities.clone()
+		));
+		System::assert_last_event(Event::ClusterCapabilitiesSet { cluster_id }.into());
+		assert_eq!(
+			<DdcClusters as ClusterVisitor<Test>>::get_capabilities(&cluster_id),
+			Ok(cluster_capabilities)
+		);
+	})
+}
+
+#[test]
+fn set_cluster_asset_rate_works() {
+	ExtBuilder.build_and_execute(|| {
+		System::set_block_number(1);
+
+		let cluster_id = ClusterId::from([1; 20]);
+		let cluster_manager_id = AccountId::from([1; 32]);
+		let cluster_reserve_id = AccountId::from([2; 32]);
+		let auth_contract = AccountId::from([3; 32]);
+		let asset_id = 1;
+		let rate = FixedU128::from_rational(3, 2);
+
+		// Cluster doesn't exist
+		assert_noop!(
+			DdcClusters::set_cluster_asset_rate(
+				RuntimeOrigin::root(),
+				cluster_id,
+				asset_id,
+				Some(rate)
+			),
+			Error::<Test>::ClusterDoesNotExist
+		);
+
+		assert_ok!(DdcClusters::create_cluster(
+			RuntimeOrigin::root(),
+			cluster_id,
+			cluster_manager_id.clone(),
+			cluster_reserve_id,
+			ClusterParams { node_provider_auth_contract: Some(auth_contract) },
+			ClusterGovParams {
+				treasury_share: Perquintill::from_float(0.05),
+				validators_share: Perquintill::from_float(0.01),
+				cluster_reserve_share: Perquintill::from_float(0.02),
+				storage_bond_size: 100,
+				storage_chill_delay: 50,
+				storage_unbonding_delay: 50,
+				unit_per_mb_stored: 10,
+				unit_per_mb_streamed: 10,
+				unit_per_put_request: 10,
+				unit_per_get_request: 10,
+				billing_period: 2_630_016,
+			}
+		));
+
+		// Clusters accept no assets by default
+		assert_eq!(
+			<DdcClusters as ClusterVisitor<Test>>::get_asset_rate(&cluster_id, asset_id),
+			None
+		);
+
+		// Requires Governance approval
+		assert_noop!(
+			DdcClusters::set_cluster_asset_rate(
+				RuntimeOrigin::signed(cluster_manager_id),
+				cluster_id,
+				asset_id,
+				Some(rate)
+			),
+			BadOrigin
+		);
+
+		assert_noop!(
+			DdcClusters::set_cluster_asset_rate(
+				RuntimeOrigin::root(),
+				cluster_id,
+				asset_id,
+				Some(FixedU128::zero())
+			),
+			Error::<Test>::InvalidAssetRate
+		);
+
+		assert_ok!(DdcClusters::set_cluster_asset_rate(
+			RuntimeOrigin::root(),
+			cluster_id,
+			asset_id,
+			Some(rate)
+		));
+		System::assert_last_event(
+			Event::ClusterAssetRateSet { cluster_id, asset_id, rate: Some(rate) }.into(),
+		);
+		assert_eq!(
+			<DdcClusters as ClusterVisitor<Test>>::get_asset_rate(&cluster_id, asset_id),
+			Some(rate)
+		);
+
+		assert_ok!(DdcClusters::set_cluster_asset_rate(
+			RuntimeOrigin::root(),
+			cluster_id,
+			asset_id,
+			None
+		));
+		assert_eq!(
+			<DdcClusters as ClusterVisitor<Test>>::get_asset_rate(&cluster_id, asset_id),
+			None
+		);
+	})
+}
+
+#[test]
+fn set_cluster_pricing_tiers_works() {
+	ExtBuilder.build_and_execute(|| {
+		System::set_block_number(1);
+
+		let cluster_id = ClusterId::from([1; 20]);
+		let cluster_manager_id = AccountId::from([1; 32]);
+		let cluster_reserve_id = AccountId::from([2; 32]);
+		let auth_contract = AccountId::from([3; 32]);
+		let pricing_tiers = ClusterPricingTiers {
+			streamed: BoundedVec::truncate_from(vec![
+				PricingTier { threshold: 1_000, unit_price: 8 },
+				PricingTier { threshold: 10_000, unit_price: 5 },
+			]),
+			stored: BoundedVec::truncate_from(vec![PricingTier {
+				threshold: 1_000,
+				unit_price: 9,
+			}]),
+			puts: Default::default(),
+			gets: Default::default(),
+		};
+
+		// Cluster doesn't exist
+		assert_noop!(
+			DdcClusters::set_cluster_pricing_tiers(
+				RuntimeOrigin::root(),
+				cluster_id,
+				pricing_tiers.clone()
+			),
+			Error::<Test>::ClusterDoesNotExist
+		);
+
+		assert_ok!(DdcClusters::create_cluster(
+			RuntimeOrigin::root(),
+			cluster_id,
+			cluster_manager_id.clone(),
+			cluster_reserve_id,
+			ClusterParams { node_provider_auth_contract: Some(auth_contract) },
+			ClusterGovParams {
+				treasury_share: Perquintill::from_float(0.05),
+				validators_share: Perquintill::from_float(0.01),
+				cluster_reserve_share: Perquintill::from_float(0.02),
+				storage_bond_size: 100,
+				storage_chill_delay: 50,
+				storage_unbonding_delay: 50,
+				unit_per_mb_stored: 10,
+				unit_per_mb_streamed: 10,
+				unit_per_put_request: 10,
+				unit_per_get_request: 10,
+				billing_period: 2_630_016,
+			}
+		));
+
+		// Clusters have flat prices by default
+		assert_eq!(
+			<DdcClusters as ClusterVisitor<Test>>::get_pricing_params(&cluster_id)
+				.unwrap()
+				.pricing_tiers,
+			ClusterPricingTiers::default()
+		);
+
+		// Requires Governance approval
+		assert_noop!(
+			DdcClusters::set_cluster_pricing_tiers(
+				RuntimeOrigin::signed(cluster_manager_id),
+				cluster_id,
+				pricing_tiers.clone()
+			),
+			BadOrigin
+		);
+
+		// Tiers thresholds should be ascending and non-zero
+		for invalid_tiers in [
+			ClusterPricingTiers {
+				streamed: BoundedVec::truncate_from(vec![
+					PricingTier { threshold: 10_000, unit_price: 5 },
+					PricingTier { threshold: 1_000, unit_price: 8 },
+				]),
+				..Default::default()
+			},
+			ClusterPricingTiers {
+				gets: BoundedVec::truncate_from(vec![PricingTier { threshold: 0, unit_price: 5 }]),
+				..Default::default()
+			},
+		] {
+			assert_noop!(
+				DdcClusters::set_cluster_pricing_tiers(
+					RuntimeOrigin::root(),
+					cluster_id,
+					invalid_tiers
+				),
+				Error::<Test>::InvalidPricingTiers
+			);
+		}
+
+		assert_ok!(DdcClusters::set_cluster_pricing_tiers(
+			RuntimeOrigin::root(),
+			cluster_id,
+			pricing_tiers.clone()
+		));
+		System::assert_last_event(Event::ClusterPricingTiersSet { cluster_id }.into());
+		assert_eq!(DdcClusters::clusters_pricing_tiers(cluster_id), Some(pricing_tiers.clone()));
+		assert_eq!(
+			<DdcClusters as ClusterVisitor<Test>>::get_pricing_params(&cluster_id)
+				.unwrap()
+				.pricing_tiers,
+			pricing_tiers
+		);
+
+		// Empty tiers restore the flat prices
+		assert_ok!(DdcClusters::set_cluster_pricing_tiers(
+			RuntimeOrigin::root(),
+			cluster_id,
+			ClusterPricingTiers::default()
+		));
+		assert_eq!(DdcClusters::clusters_pricing_tiers(cluster_id), None);
+	})
+}
+
 #[test]
 fn cluster_visitor_works() {
 	ExtBuilder.build_and_execute(|| {
@@ -547,6 +981,7 @@ fn cluster_visitor_works() {
 			unit_per_mb_streamed: 10,
 			unit_per_put_request: 10,
 			unit_per_get_request: 10,
+			billing_period: 2_630_016,
 		};
 
 		// Creating 1 cluster should work fine
@@ -579,6 +1014,8 @@ fn cluster_visitor_works() {
 				unit_per_mb_streamed: 10,
 				unit_per_put_request: 10,
 				unit_per_get_request: 10,
+				billing_period: 2_630_016,
+				pricing_tiers: Default::default(),
 			}
 		);
 
@@ -656,6 +1093,7 @@ fn cluster_creator_works() {
 			unit_per_mb_streamed: 10,
 			unit_per_put_request: 10,
 			unit_per_get_request: 10,
+			billing_period: 2_630_016,
 		};
 
 		assert_ok!(<DdcClusters as ClusterCreator<Test, BalanceOf<Test>>>::create_new_cluster(
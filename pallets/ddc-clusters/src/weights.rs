@@ -1,11 +1,12 @@
-//! Autogenerated weights for pallet_ddc_clusters
+//! Weights for pallet_ddc_clusters
 //!
-//! THIS FILE WAS AUTO-GENERATED USING THE SUBSTRATE BENCHMARK CLI VERSION 4.0.0-dev
-//! DATE: 2023-12-14, STEPS: `50`, REPEAT: 20, LOW RANGE: `[]`, HIGH RANGE: `[]`
-//! HOSTNAME: `Yahors-MacBook-Pro.local`, CPU: `<UNKNOWN>`
-//! EXECUTION: Some(Wasm), WASM-EXECUTION: Compiled, CHAIN: Some("dev"), DB CACHE: 1024
+//! The weights were generated with the Substrate benchmark CLI on 2023-12-14 (STEPS: `50`, REPEAT:
+//! 20, HOSTNAME: `Yahors-MacBook-Pro.local`). The weights of the following calls are provisional
+//! estimates derived from their storage access, not benchmark results, and must be regenerated with
+//! the command below before a release: `set_cluster_gov_params`, `set_customer_discount`,
+//! `set_cluster_capabilities`, `set_cluster_asset_rate`, `set_cluster_pricing_tiers`.
 
-// Executed Command:
+// Command to regenerate the weights:
 // ./target/release/cere
 // benchmark
 // pallet
@@ -32,6 +33,10 @@ pub trait WeightInfo {
 	fn remove_node() -> Weight;
 	fn set_cluster_params() -> Weight;
 	fn set_cluster_gov_params() -> Weight;
+	fn set_customer_discount() -> Weight;
+	fn set_cluster_capabilities() -> Weight;
+	fn set_cluster_asset_rate() -> Weight;
+	fn set_cluster_pricing_tiers() -> Weight;
 }
 
 /// Weights for pallet_ddc_clusters using the Substrate node and recommended hardware.
@@ -78,9 +83,38 @@ impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
 			.saturating_add(T::DbWeight::get().writes(1_u64))
 	}
 	// Storage: DdcClusters Clusters (r:1 w:0)
-	// Storage: DdcClusters ClustersGovParams (r:0 w:1)
+	// Storage: DdcClusters ClustersGovParams (r:1 w:1)
+	// Storage: DdcStaking PendingBondChecks (r:0 w:1)
 	fn set_cluster_gov_params() -> Weight {
-		Weight::from_parts(17_000_000_u64, 0)
+		Weight::from_parts(19_000_000_u64, 0)
+			.saturating_add(T::DbWeight::get().reads(2_u64))
+			.saturating_add(T::DbWeight::get().writes(2_u64))
+	}
+	// Storage: DdcClusters Clusters (r:1 w:0)
+	// Storage: DdcClusters CustomerDiscounts (r:0 w:1)
+	fn set_customer_discount() -> Weight {
+		Weight::from_parts(16_000_000_u64, 0)
+			.saturating_add(T::DbWeight::get().reads(1_u64))
+			.saturating_add(T::DbWeight::get().writes(1_u64))
+	}
+	// Storage: DdcClusters Clusters (r:1 w:0)
+	// Storage: DdcClusters ClustersCapabilities (r:0 w:1)
+	fn set_cluster_capabilities() -> Weight {
+		Weight::from_parts(14_000_000_u64, 0)
+			.saturating_add(T::DbWeight::get().reads(1_u64))
+			.saturating_add(T::DbWeight::get().writes(1_u64))
+	}
+	// Storage: DdcClusters Clusters (r:1 w:0)
+	// Storage: DdcClusters ClustersAssetRates (r:0 w:1)
+	fn set_cluster_asset_rate() -> Weight {
+		Weight::from_parts(13_000_000_u64, 0)
+			.saturating_add(T::DbWeight::get().reads(1_u64))
+			.saturating_add(T::DbWeight::get().writes(1_u64))
+	}
+	// Storage: DdcClusters Clusters (r:1 w:0)
+	// Storage: DdcClusters ClustersPricingTiers (r:0 w:1)
+	fn set_cluster_pricing_tiers() -> Weight {
+		Weight::from_parts(15_000_000_u64, 0)
 			.saturating_add(T::DbWeight::get().reads(1_u64))
 			.saturating_add(T::DbWeight::get().writes(1_u64))
 	}
@@ -129,9 +163,38 @@ impl WeightInfo for () {
 			.saturating_add(RocksDbWeight::get().writes(1_u64))
 	}
 	// Storage: DdcClusters Clusters (r:1 w:0)
-	// Storage: DdcClusters ClustersGovParams (r:0 w:1)
+	// Storage: DdcClusters ClustersGovParams (r:1 w:1)
+	// Storage: DdcStaking PendingBondChecks (r:0 w:1)
 	fn set_cluster_gov_params() -> Weight {
-		Weight::from_parts(17_000_000_u64, 0)
+		Weight::from_parts(19_000_000_u64, 0)
+			.saturating_add(RocksDbWeight::get().reads(2_u64))
+			.saturating_add(RocksDbWeight::get().writes(2_u64))
+	}
+	// Storage: DdcClusters Clusters (r:1 w:0)
+	// Storage: DdcClusters CustomerDiscounts (r:0 w:1)
+	fn set_customer_discount() -> Weight {
+		Weight::from_parts(16_000_000_u64, 0)
+			.saturating_add(RocksDbWeight::get().reads(1_u64))
+			.saturating_add(RocksDbWeight::get().writes(1_u64))
+	}
+	// Storage: DdcClusters Clusters (r:1 w:0)
+	// Storage: DdcClusters ClustersCapabilities (r:0 w:1)
+	fn set_cluster_capabilities() -> Weight {
+		Weight::from_parts(14_000_000_u64, 0)
+			.saturating_add(RocksDbWeight::get().reads(1_u64))
+			.saturating_add(RocksDbWeight::get().writes(1_u64))
+	}
+	// Storage: DdcClusters Clusters (r:1 w:0)
+	// Storage: DdcClusters ClustersAssetRates (r:0 w:1)
+	fn set_cluster_asset_rate() -> Weight {
+		Weight::from_parts(13_000_000_u64, 0)
+			.saturating_add(RocksDbWeight::get().reads(1_u64))
+			.saturating_add(RocksDbWeight::get().writes(1_u64))
+	}
+	// Storage: DdcClusters Clusters (r:1 w:0)
+	// Storage: DdcClusters ClustersPricingTiers (r:0 w:1)
+	fn set_cluster_pricing_tiers() -> Weight {
+		Weight::from_parts(15_000_000_u64, 0)
 			.saturating_add(RocksDbWeight::get().reads(1_u64))
 			.saturating_add(RocksDbWeight::get().writes(1_u64))
 	}
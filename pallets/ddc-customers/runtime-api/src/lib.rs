@@ -0,0 +1,22 @@
+//! Runtime API definition for the DDC Customers pallet.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::Codec;
+use ddc_primitives::BucketId;
+pub use pallet_ddc_customers::{BucketGrant, BucketPermission};
+use sp_std::prelude::*;
+
+sp_api::decl_runtime_apis! {
+	pub trait DdcCustomersApi<AccountId, Balance, BlockNumber>
+	where
+		AccountId: Codec,
+		Balance: Codec,
+		BlockNumber: Codec,
+	{
+		/// Permission of the account on the bucket, `Admin` for the bucket owner.
+		fn bucket_access(bucket_id: BucketId, account_id: AccountId) -> Option<BucketPermission>;
+		/// Unexpired grants of the bucket, by grantee.
+		fn bucket_grants(bucket_id: BucketId) -> Vec<(AccountId, BucketGrant<BlockNumber, Balance>)>;
+	}
+}
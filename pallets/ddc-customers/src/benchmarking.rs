@@ -3,7 +3,7 @@
 
 use ddc_primitives::{ClusterGovParams, ClusterId, ClusterParams};
 use frame_benchmarking::{account, benchmarks, whitelist_account};
-use frame_support::traits::Currency;
+use frame_support::traits::{fungibles, Currency};
 use sp_runtime::Perquintill;
 use sp_std::prelude::*;
 
@@ -16,6 +16,18 @@ pub type BalanceOf<T> =
 use frame_system::{Pallet as System, RawOrigin};
 
 const USER_SEED: u32 = 999666;
+const ASSET_ID: AssetId = 1;
+
+fn fund_asset<T: Config>(user: &T::AccountId, amount: BalanceOf<T>) {
+	let min_balance = <T as pallet::Config>::Currency::minimum_balance();
+	let _ = <T::Assets as fungibles::Create<T::AccountId>>::create(
+		ASSET_ID,
+		user.clone(),
+		true,
+		min_balance,
+	);
+	let _ = <T::Assets as fungibles::Mutate<T::AccountId>>::mint_into(ASSET_ID, user, amount);
+}
 
 benchmarks! {
 	create_bucket {
@@ -32,6 +44,7 @@ benchmarks! {
 			unit_per_mb_streamed: 10,
 			unit_per_put_request: 10,
 			unit_per_get_request: 10,
+			billing_period: 2_630_016,
 		};
 
 		let _ = <T as pallet::Config>::ClusterCreator::create_new_cluster(
@@ -42,8 +55,13 @@ benchmarks! {
 			cluster_gov_params
 		);
 
+		let balance = <T as pallet::Config>::Currency::minimum_balance() * 100u32.into();
+		let _ = <T as pallet::Config>::Currency::make_free_balance_be(&user, balance + T::BucketDeposit::get());
+
 		let bucket_params = BucketParams {
-			is_public: false
+			is_public: false,
+			budget: None,
+			metadata: Default::default(),
 		};
 
 		whitelist_account!(user);
@@ -157,6 +175,7 @@ benchmarks! {
 			cluster_id,
 			is_public: false,
 			is_removed: false,
+			metadata: Default::default(),
 		};
 
 		<BucketsCount<T>>::set(bucket_id);
@@ -165,7 +184,9 @@ benchmarks! {
 		whitelist_account!(user);
 
 		let bucket_params = BucketParams {
-			is_public: true
+			is_public: true,
+			budget: Some(1_000_000),
+			metadata: Default::default(),
 		};
 
 	}: _(RawOrigin::Signed(user), bucket_id, bucket_params)
@@ -185,6 +206,7 @@ benchmarks! {
 			cluster_id,
 			is_public: false,
 			is_removed: false,
+			metadata: Default::default(),
 		};
 
 		<BucketsCount<T>>::set(bucket_id);
@@ -195,6 +217,406 @@ benchmarks! {
 	verify {
 		let bucket = <Buckets<T>>::get(bucket_id).unwrap();
 		assert!(bucket.is_removed);
+		assert!(<RemovedBuckets<T>>::contains_key(bucket_id));
+		assert_eq!(<ReapingQueueRange<T>>::get(), (0, 1));
+	}
+
+	allocate_to_bucket {
+		let cluster_id = ClusterId::from([1; 20]);
+		let user = account::<T::AccountId>("user", USER_SEED, 0u32);
+		let balance = <T as pallet::Config>::Currency::minimum_balance() * 200u32.into();
+		let _ = <T as pallet::Config>::Currency::make_free_balance_be(&user, balance);
+		let amount = <T as pallet::Config>::Currency::minimum_balance() * 50u32.into();
+
+		let _ = DdcCustomers::<T>::deposit(RawOrigin::Signed(user.clone()).into(), amount);
+
+		let bucket_id = 1;
+		let bucket = Bucket {
+			bucket_id,
+			owner_id: user.clone(),
+			cluster_id,
+			is_public: false,
+			is_removed: false,
+			metadata: Default::default(),
+		};
+
+		<BucketsCount<T>>::set(bucket_id);
+		<Buckets<T>>::insert(bucket_id, bucket);
+		<BucketBudgets<T>>::insert(bucket_id, BucketBudget { limit: amount, allocated: Zero::zero() });
+
+		// Worst case scenario, the allocation is spent from a grant's allowance
+		let grantee = account::<T::AccountId>("grantee", USER_SEED, 0u32);
+		<BucketGrants<T>>::insert(bucket_id, grantee.clone(), BucketGrant {
+			permission: BucketPermission::Write,
+			expires_at: 1_000u32.into(),
+			allowance: amount,
+		});
+
+		whitelist_account!(grantee);
+	}: _(RawOrigin::Signed(grantee.clone()), bucket_id, amount)
+	verify {
+		let budget = <BucketBudgets<T>>::get(bucket_id).unwrap();
+		assert_eq!(budget.allocated, amount);
+		assert!(<BucketGrants<T>>::get(bucket_id, grantee).unwrap().allowance.is_zero());
+	}
+
+	release_bucket_allocation {
+		let cluster_id = ClusterId::from([1; 20]);
+		let user = account::<T::AccountId>("user", USER_SEED, 0u32);
+		let balance = <T as pallet::Config>::Currency::minimum_balance() * 200u32.into();
+		let _ = <T as pallet::Config>::Currency::make_free_balance_be(&user, balance);
+		let amount = <T as pallet::Config>::Currency::minimum_balance() * 50u32.into();
+
+		let _ = DdcCustomers::<T>::deposit(RawOrigin::Signed(user.clone()).into(), amount);
+
+		let bucket_id = 1;
+		let bucket = Bucket {
+			bucket_id,
+			owner_id: user.clone(),
+			cluster_id,
+			is_public: false,
+			is_removed: false,
+			metadata: Default::default(),
+		};
+
+		<BucketsCount<T>>::set(bucket_id);
+		<Buckets<T>>::insert(bucket_id, bucket);
+		<BucketBudgets<T>>::insert(bucket_id, BucketBudget { limit: amount, allocated: Zero::zero() });
+		let _ = DdcCustomers::<T>::allocate_to_bucket(RawOrigin::Signed(user.clone()).into(), bucket_id, amount);
+
+		whitelist_account!(user);
+	}: _(RawOrigin::Signed(user), bucket_id, amount)
+	verify {
+		let budget = <BucketBudgets<T>>::get(bucket_id).unwrap();
+		assert!(budget.allocated.is_zero());
+	}
+
+	grant_bucket_access {
+		let cluster_id = ClusterId::from([1; 20]);
+		let user = account::<T::AccountId>("user", USER_SEED, 0u32);
+		let grantee = account::<T::AccountId>("grantee", USER_SEED, 0u32);
+
+		let bucket_id = 1;
+		let bucket = Bucket {
+			bucket_id,
+			owner_id: user.clone(),
+			cluster_id,
+			is_public: false,
+			is_removed: false,
+			metadata: Default::default(),
+		};
+
+		<BucketsCount<T>>::set(bucket_id);
+		<Buckets<T>>::insert(bucket_id, bucket);
+		let allowance = <T as pallet::Config>::Currency::minimum_balance() * 50u32.into();
+
+		whitelist_account!(user);
+	}: _(RawOrigin::Signed(user), bucket_id, grantee.clone(), BucketPermission::Write, 1_000u32.into(), allowance)
+	verify {
+		assert!(<BucketGrants<T>>::contains_key(bucket_id, grantee));
+	}
+
+	// Worst case scenario, an admin revokes a write grant
+	revoke_bucket_access {
+		let cluster_id = ClusterId::from([1; 20]);
+		let user = account::<T::AccountId>("user", USER_SEED, 0u32);
+		let admin = account::<T::AccountId>("admin", USER_SEED, 0u32);
+		let grantee = account::<T::AccountId>("grantee", USER_SEED, 0u32);
+
+		let bucket_id = 1;
+		let bucket = Bucket {
+			bucket_id,
+			owner_id: user,
+			cluster_id,
+			is_public: false,
+			is_removed: false,
+			metadata: Default::default(),
+		};
+
+		<BucketsCount<T>>::set(bucket_id);
+		<Buckets<T>>::insert(bucket_id, bucket);
+		<BucketGrants<T>>::insert(bucket_id, admin.clone(), BucketGrant {
+			permission: BucketPermission::Admin,
+			expires_at: 1_000u32.into(),
+			allowance: Zero::zero(),
+		});
+		<BucketGrants<T>>::insert(bucket_id, grantee.clone(), BucketGrant {
+			permission: BucketPermission::Write,
+			expires_at: 1_000u32.into(),
+			allowance: Zero::zero(),
+		});
+		<BucketGrantsCount<T>>::insert(bucket_id, 2);
+
+		whitelist_account!(admin);
+	}: _(RawOrigin::Signed(admin), bucket_id, grantee.clone())
+	verify {
+		assert!(!<BucketGrants<T>>::contains_key(bucket_id, grantee));
+	}
+
+	offer_bucket_transfer {
+		let cluster_id = ClusterId::from([1; 20]);
+		let user = account::<T::AccountId>("user", USER_SEED, 0u32);
+		let new_owner = account::<T::AccountId>("user", USER_SEED, 1u32);
+
+		let bucket_id = 1;
+		let bucket = Bucket {
+			bucket_id,
+			owner_id: user.clone(),
+			cluster_id,
+			is_public: false,
+			is_removed: false,
+			metadata: Default::default(),
+		};
+
+		<BucketsCount<T>>::set(bucket_id);
+		<Buckets<T>>::insert(bucket_id, bucket);
+
+		whitelist_account!(user);
+	}: _(RawOrigin::Signed(user), bucket_id, new_owner.clone())
+	verify {
+		assert_eq!(<BucketTransfers<T>>::get(bucket_id), Some(new_owner));
+	}
+
+	cancel_bucket_transfer {
+		let cluster_id = ClusterId::from([1; 20]);
+		let user = account::<T::AccountId>("user", USER_SEED, 0u32);
+		let new_owner = account::<T::AccountId>("user", USER_SEED, 1u32);
+
+		let bucket_id = 1;
+		let bucket = Bucket {
+			bucket_id,
+			owner_id: user.clone(),
+			cluster_id,
+			is_public: false,
+			is_removed: false,
+			metadata: Default::default(),
+		};
+
+		<BucketsCount<T>>::set(bucket_id);
+		<Buckets<T>>::insert(bucket_id, bucket);
+		<BucketTransfers<T>>::insert(bucket_id, new_owner);
+
+		whitelist_account!(user);
+	}: _(RawOrigin::Signed(user), bucket_id)
+	verify {
+		assert!(!<BucketTransfers<T>>::contains_key(bucket_id));
+	}
+
+	// Worst case scenario, all the grants of the previous owner are revoked
+	accept_bucket_transfer {
+		let cluster_id = ClusterId::from([1; 20]);
+		let user = account::<T::AccountId>("user", USER_SEED, 0u32);
+		let new_owner = account::<T::AccountId>("user", USER_SEED, 1u32);
+
+		let bucket_id = 1;
+		let bucket = Bucket {
+			bucket_id,
+			owner_id: user,
+			cluster_id,
+			is_public: false,
+			is_removed: false,
+			metadata: Default::default(),
+		};
+
+		<BucketsCount<T>>::set(bucket_id);
+		<Buckets<T>>::insert(bucket_id, bucket);
+		<BucketTransfers<T>>::insert(bucket_id, new_owner.clone());
+
+		for k in 0 .. MaxBucketGrants::get() {
+			let grantee = account::<T::AccountId>("grantee", USER_SEED, k);
+			<BucketGrants<T>>::insert(bucket_id, grantee, BucketGrant {
+				permission: BucketPermission::Read,
+				expires_at: 1_000u32.into(),
+				allowance: Zero::zero(),
+			});
+		}
+		<BucketGrantsCount<T>>::insert(bucket_id, MaxBucketGrants::get());
+
+		whitelist_account!(new_owner);
+	}: _(RawOrigin::Signed(new_owner.clone()), bucket_id)
+	verify {
+		let bucket = <Buckets<T>>::get(bucket_id).unwrap();
+		assert_eq!(bucket.owner_id, new_owner);
+		assert_eq!(<BucketGrantsCount<T>>::get(bucket_id), 0);
+	}
+
+	migrate_bucket {
+		let cluster_id = ClusterId::from([1; 20]);
+		let new_cluster_id = ClusterId::from([2; 20]);
+		let user = account::<T::AccountId>("user", USER_SEED, 0u32);
+		let cluster_gov_params: ClusterGovParams<BalanceOf<T>, BlockNumberFor<T>> = ClusterGovParams {
+			treasury_share: Perquintill::default(),
+			validators_share: Perquintill::default(),
+			cluster_reserve_share: Perquintill::default(),
+			storage_bond_size: 100u32.into(),
+			storage_chill_delay: 50u32.into(),
+			storage_unbonding_delay: 50u32.into(),
+			unit_per_mb_stored: 10,
+			unit_per_mb_streamed: 10,
+			unit_per_put_request: 10,
+			unit_per_get_request: 10,
+			billing_period: 2_630_016,
+		};
+
+		let _ = <T as pallet::Config>::ClusterCreator::create_new_cluster(
+			new_cluster_id,
+			user.clone(),
+			user.clone(),
+			ClusterParams { node_provider_auth_contract: Some(user.clone()) },
+			cluster_gov_params
+		);
+
+		let bucket_id = 1;
+		let bucket = Bucket {
+			bucket_id,
+			owner_id: user,
+			cluster_id,
+			is_public: false,
+			is_removed: false,
+			metadata: Default::default(),
+		};
+
+		<BucketsCount<T>>::set(bucket_id);
+		<Buckets<T>>::insert(bucket_id, bucket);
+	}: _(RawOrigin::Root, bucket_id, new_cluster_id)
+	verify {
+		let bucket = <Buckets<T>>::get(bucket_id).unwrap();
+		assert_eq!(bucket.cluster_id, new_cluster_id);
+	}
+
+	reap_bucket {
+		let cluster_id = ClusterId::from([1; 20]);
+		let user = account::<T::AccountId>("user", USER_SEED, 0u32);
+		let reaper = account::<T::AccountId>("user", USER_SEED, 1u32);
+		let amount = <T as pallet::Config>::Currency::minimum_balance() * 50u32.into();
+		let deposit = <T as pallet::Config>::Currency::minimum_balance() * 10u32.into();
+		let _ = <T as pallet::Config>::Currency::make_free_balance_be(&DdcCustomers::<T>::account_id(), amount * 2u32.into() + deposit);
+
+		let bucket_id = 1;
+		let bucket = Bucket {
+			bucket_id,
+			owner_id: user.clone(),
+			cluster_id,
+			is_public: false,
+			is_removed: true,
+			metadata: Default::default(),
+		};
+
+		<BucketsCount<T>>::set(bucket_id);
+		<Buckets<T>>::insert(bucket_id, bucket);
+		<RemovedBuckets<T>>::insert(bucket_id, RemovedBucket {
+			reap_at: System::<T>::block_number(),
+			billed_era: None,
+		});
+		T::PayoutVisitor::set_last_finalized_era(&cluster_id, 1);
+		<BucketBudgets<T>>::insert(bucket_id, BucketBudget { limit: amount, allocated: amount });
+		<BucketDeposits<T>>::insert(bucket_id, (user.clone(), deposit));
+
+		for k in 0 .. MaxBucketGrants::get() {
+			let grantee = account::<T::AccountId>("grantee", USER_SEED, k);
+			<BucketGrants<T>>::insert(bucket_id, grantee, BucketGrant {
+				permission: BucketPermission::Read,
+				expires_at: 1_000u32.into(),
+				allowance: Zero::zero(),
+			});
+		}
+		<BucketGrantsCount<T>>::insert(bucket_id, MaxBucketGrants::get());
+
+		whitelist_account!(reaper);
+	}: _(RawOrigin::Signed(reaper), bucket_id)
+	verify {
+		assert!(<Buckets<T>>::get(bucket_id).is_none());
+		assert_eq!(DdcCustomers::<T>::ledger(&user).unwrap().active, amount);
+		assert_eq!(<T as pallet::Config>::Currency::free_balance(&user), deposit);
+	}
+
+	subscribe_top_up {
+		let user = account::<T::AccountId>("user", USER_SEED, 0u32);
+		let funder = account::<T::AccountId>("user", USER_SEED, 1u32);
+		let balance = <T as pallet::Config>::Currency::minimum_balance() * 100u32.into();
+		let _ = <T as pallet::Config>::Currency::make_free_balance_be(&user, balance);
+		let amount = <T as pallet::Config>::Currency::minimum_balance() * 50u32.into();
+
+		let _ = DdcCustomers::<T>::deposit(RawOrigin::Signed(user.clone()).into(), amount);
+
+		whitelist_account!(funder);
+	}: _(RawOrigin::Signed(funder.clone()), user.clone(), amount, amount)
+	verify {
+		assert_eq!(DdcCustomers::<T>::top_up_subscription(&user).unwrap().funder, funder);
+	}
+
+	cancel_top_up {
+		let user = account::<T::AccountId>("user", USER_SEED, 0u32);
+		let amount = <T as pallet::Config>::Currency::minimum_balance() * 50u32.into();
+
+		<TopUpSubscriptions<T>>::insert(&user, TopUpSubscription {
+			funder: user.clone(),
+			threshold: amount,
+			amount,
+		});
+
+		whitelist_account!(user);
+	}: _(RawOrigin::Signed(user.clone()), user.clone())
+	verify {
+		assert!(DdcCustomers::<T>::top_up_subscription(&user).is_none());
+	}
+
+	deposit_asset {
+		let user = account::<T::AccountId>("user", USER_SEED, 0u32);
+		let balance = <T as pallet::Config>::Currency::minimum_balance() * 100u32.into();
+		fund_asset::<T>(&user, balance);
+		let amount = <T as pallet::Config>::Currency::minimum_balance() * 50u32.into();
+
+		whitelist_account!(user);
+	}: _(RawOrigin::Signed(user.clone()), ASSET_ID, amount)
+	verify {
+		assert_eq!(DdcCustomers::<T>::asset_ledger(&user, ASSET_ID).unwrap().active, amount);
+	}
+
+	unlock_asset_deposit {
+		let user = account::<T::AccountId>("user", USER_SEED, 0u32);
+		let balance = <T as pallet::Config>::Currency::minimum_balance() * 200u32.into();
+		fund_asset::<T>(&user, balance);
+		let amount = <T as pallet::Config>::Currency::minimum_balance() * 50u32.into();
+
+		let _ = DdcCustomers::<T>::deposit_asset(RawOrigin::Signed(user.clone()).into(), ASSET_ID, amount);
+
+		whitelist_account!(user);
+	}: _(RawOrigin::Signed(user.clone()), ASSET_ID, amount)
+	verify {
+		assert_eq!(DdcCustomers::<T>::asset_ledger(&user, ASSET_ID).unwrap().unlocking.len(), 1);
+	}
+
+	withdraw_unlocked_asset_deposit {
+		System::<T>::set_block_number(1u32.into());
+
+		let user = account::<T::AccountId>("user", USER_SEED, 0u32);
+		let balance = <T as pallet::Config>::Currency::minimum_balance() * 200u32.into();
+		fund_asset::<T>(&user, balance);
+		let amount = <T as pallet::Config>::Currency::minimum_balance() * 50u32.into();
+
+		let _ = DdcCustomers::<T>::deposit_asset(RawOrigin::Signed(user.clone()).into(), ASSET_ID, amount);
+		let _ = DdcCustomers::<T>::set_payment_asset(RawOrigin::Signed(user.clone()).into(), Some(ASSET_ID));
+		let _ = DdcCustomers::<T>::unlock_asset_deposit(RawOrigin::Signed(user.clone()).into(), ASSET_ID, amount);
+
+		System::<T>::set_block_number(5256001u32.into());
+
+		whitelist_account!(user);
+	}: _(RawOrigin::Signed(user.clone()), ASSET_ID)
+	verify {
+		assert!(DdcCustomers::<T>::asset_ledger(&user, ASSET_ID).is_none());
+		assert!(DdcCustomers::<T>::payment_asset(&user).is_none());
+	}
+
+	set_payment_asset {
+		let user = account::<T::AccountId>("user", USER_SEED, 0u32);
+
+		<AssetLedgers<T>>::insert(&user, ASSET_ID, AccountsLedger::<T>::default_from(user.clone()));
+
+		whitelist_account!(user);
+	}: _(RawOrigin::Signed(user.clone()), Some(ASSET_ID))
+	verify {
+		assert_eq!(DdcCustomers::<T>::payment_asset(&user), Some(ASSET_ID));
 	}
 
 	impl_benchmark_test_suite!(
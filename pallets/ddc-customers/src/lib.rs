@@ -1,3 +1,35 @@
+//! # DDC Customers Pallet
+//!
+//! The DDC Customers pallet is used to manage the deposits and buckets of DDC customers
+//!
+//! - [`Config`]
+//! - [`Call`]
+//! - [`Pallet`]
+//!
+//! ## Buckets
+//!
+//! A bucket takes a refundable `BucketDeposit` and carries metadata (replication factor,
+//! region, storage class and retention) validated against the capabilities of its cluster. A
+//! bucket with a budget is charged only from its own allocation, funded with
+//! `allocate_to_bucket` and returned with `release_bucket_allocation`. The owner grants
+//! expiring read, write and admin access to other accounts, and writers may allocate the owner
+//! deposit to the bucket within their allowance.
+//!
+//! A bucket changes owner in two steps with `offer_bucket_transfer` and
+//! `accept_bucket_transfer`, and governance moves it to another cluster with `migrate_bucket`.
+//! Neither happens while a billing report of the cluster is open, and an indebted bucket keeps
+//! its owner. A removed bucket is kept for the `BucketGracePeriod` and until its cluster
+//! finalizes billing of a later era, and is then erased from the reaping queue on idle or with
+//! `reap_bucket`, which refunds the deposit.
+//!
+//! ## Deposits
+//!
+//! Charges take the active deposit first and then the unlocking chunks, newest first, and
+//! reduce the ledger total. An owner may subscribe to an automatic top-up of the deposit,
+//! funded by itself or another account whenever a charge drops the active deposit below the
+//! threshold. Deposits of `pallet-assets` assets are managed with `deposit_asset`,
+//! `unlock_asset_deposit` and `withdraw_unlocked_asset_deposit`, and the owner chooses the
+//! asset it is charged in with `set_payment_asset`.
 #![cfg_attr(not(feature = "std"), no_std)]
 #![recursion_limit = "256"]
 
@@ -16,13 +48,18 @@ use codec::{Decode, Encode};
 use ddc_primitives::{
 	traits::{
 		cluster::{ClusterCreator, ClusterVisitor},
-		customer::{CustomerCharger, CustomerDepositor},
+		customer::{BucketVisitor, BucketVisitorError, CustomerCharger, CustomerDepositor},
+		payout::PayoutVisitor,
 	},
-	BucketId, ClusterId,
+	AssetId, BucketId, BucketMetadata, ClusterId, DdcEra, PaymentSource, StorageClass,
 };
 use frame_support::{
 	parameter_types,
-	traits::{Currency, DefensiveSaturating, ExistenceRequirement},
+	traits::{
+		fungibles,
+		tokens::{Fortitude, Preservation},
+		Currency, DefensiveSaturating, ExistenceRequirement,
+	},
 	BoundedVec, Deserialize, PalletId, Serialize,
 };
 use frame_system::pallet_prelude::*;
@@ -44,6 +81,8 @@ pub type BalanceOf<T> =
 parameter_types! {
 	/// A limit to the number of pending unlocks an account may have in parallel.
 	pub MaxUnlockingChunks: u32 = 32;
+	/// A limit to the number of accounts a bucket can grant access to.
+	pub MaxBucketGrants: u32 = 32;
 }
 
 /// Just a Balance/BlockNumber tuple to encode when a chunk of funds will be unlocked.
@@ -66,13 +105,73 @@ pub struct Bucket<T: Config> {
 	cluster_id: ClusterId,
 	is_public: bool,
 	is_removed: bool,
+	metadata: BucketMetadata,
 }
 
 #[derive(PartialEq, Eq, Clone, Encode, Decode, RuntimeDebug, TypeInfo)]
 pub struct BucketParams {
 	is_public: bool,
+	/// Hard cap on the bucket's own allocation. A bucket with a budget is charged from its
+	/// allocation only and never from the owner's shared deposit.
+	budget: Option<u128>,
+	/// Placement and retention of the bucket data, supported by the bucket cluster.
+	metadata: BucketMetadata,
+}
+
+#[derive(PartialEq, Eq, Clone, Encode, Decode, RuntimeDebug, TypeInfo)]
+#[scale_info(skip_type_params(T))]
+pub struct BucketBudget<T: Config> {
+	/// The maximum amount that can be allocated to the bucket.
+	pub limit: BalanceOf<T>,
+	/// The amount moved from the owner's deposit to the bucket and not yet charged.
+	pub allocated: BalanceOf<T>,
+}
+
+#[derive(PartialEq, Eq, Clone, Encode, Decode, RuntimeDebug, TypeInfo)]
+#[scale_info(skip_type_params(T))]
+pub struct TopUpSubscription<T: Config> {
+	/// The account the top-up is pulled from, the owner itself or a funding account.
+	pub funder: T::AccountId,
+	/// The active deposit below which the owner's deposit is topped up.
+	pub threshold: BalanceOf<T>,
+	/// The amount added to the owner's deposit on each top-up.
+	pub amount: BalanceOf<T>,
 }
 
+#[derive(PartialEq, Eq, Clone, Encode, Decode, RuntimeDebug, TypeInfo)]
+#[scale_info(skip_type_params(T))]
+pub struct RemovedBucket<T: Config> {
+	/// The block the grace period of the bucket ends at.
+	pub reap_at: BlockNumberFor<T>,
+	/// The last era with a finalized billing report of the bucket cluster at the removal. The
+	/// bucket is kept until a later era is finalized, so that its last usage is billed.
+	pub billed_era: Option<DdcEra>,
+}
+
+/// Level of access delegated to a bucket. Each level includes the lower ones.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Encode, Decode, RuntimeDebug, TypeInfo)]
+pub enum BucketPermission {
+	Read,
+	Write,
+	/// Allows to update the bucket params, manage its allocation and grant read or write access
+	Admin,
+}
+
+/// Access to a bucket granted by its owner to another account.
+#[derive(PartialEq, Eq, Clone, Encode, Decode, RuntimeDebug, TypeInfo)]
+pub struct BucketGrant<BlockNumber, Balance> {
+	pub permission: BucketPermission,
+	/// The block at which the grant stops being valid.
+	pub expires_at: BlockNumber,
+	/// The amount of the owner's deposit the grantee can still allocate to the bucket.
+	pub allowance: Balance,
+}
+
+pub type BucketGrantOf<T> = BucketGrant<BlockNumberFor<T>, BalanceOf<T>>;
+
+/// Amounts charged from the unlocking chunks, by the block the chunk unlocks at.
+pub type UnlockingCharges<T> = Vec<(BlockNumberFor<T>, BalanceOf<T>)>;
+
 #[derive(PartialEq, Eq, Clone, Encode, Decode, RuntimeDebug, TypeInfo)]
 #[scale_info(skip_type_params(T))]
 pub struct AccountsLedger<T: Config> {
@@ -135,7 +234,7 @@ pub mod pallet {
 
 	/// The current storage version.
 	const STORAGE_VERSION: frame_support::traits::StorageVersion =
-		frame_support::traits::StorageVersion::new(1);
+		frame_support::traits::StorageVersion::new(2);
 
 	#[pallet::pallet]
 	#[pallet::storage_version(STORAGE_VERSION)]
@@ -154,6 +253,17 @@ pub mod pallet {
 		type UnlockingDelay: Get<BlockNumberFor<Self>>;
 		type ClusterVisitor: ClusterVisitor<Self>;
 		type ClusterCreator: ClusterCreator<Self, BalanceOf<Self>>;
+		type PayoutVisitor: PayoutVisitor<Self>;
+		/// Refundable deposit taken on bucket creation and returned when the bucket is reaped.
+		#[pallet::constant]
+		type BucketDeposit: Get<BalanceOf<Self>>;
+		/// Number of blocks a removed bucket is kept for, so billing can settle its usage.
+		#[pallet::constant]
+		type BucketGracePeriod: Get<BlockNumberFor<Self>>;
+		/// Fungible assets that can be deposited to pay for the usage instead of the native
+		/// currency.
+		type Assets: fungibles::Mutate<Self::AccountId, AssetId = AssetId, Balance = BalanceOf<Self>>
+			+ fungibles::Create<Self::AccountId>;
 		type WeightInfo: WeightInfo;
 	}
 
@@ -177,6 +287,83 @@ pub mod pallet {
 	#[pallet::getter(fn buckets)]
 	pub type Buckets<T: Config> = StorageMap<_, Twox64Concat, BucketId, Bucket<T>, OptionQuery>;
 
+	/// Map from bucket ID to the bucket's budget and prepaid allocation
+	#[pallet::storage]
+	#[pallet::getter(fn bucket_budgets)]
+	pub type BucketBudgets<T: Config> =
+		StorageMap<_, Twox64Concat, BucketId, BucketBudget<T>, OptionQuery>;
+
+	/// Map from bucket ID and grantee to the access granted by the bucket owner
+	#[pallet::storage]
+	#[pallet::getter(fn bucket_grant)]
+	pub type BucketGrants<T: Config> = StorageDoubleMap<
+		_,
+		Twox64Concat,
+		BucketId,
+		Blake2_128Concat,
+		T::AccountId,
+		BucketGrantOf<T>,
+		OptionQuery,
+	>;
+
+	/// Map from bucket ID to the number of its grants
+	#[pallet::storage]
+	#[pallet::getter(fn bucket_grants_count)]
+	pub type BucketGrantsCount<T: Config> = StorageMap<_, Twox64Concat, BucketId, u32, ValueQuery>;
+
+	/// Map from bucket ID to the account the bucket has been offered to
+	#[pallet::storage]
+	#[pallet::getter(fn bucket_transfer)]
+	pub type BucketTransfers<T: Config> =
+		StorageMap<_, Twox64Concat, BucketId, T::AccountId, OptionQuery>;
+
+	/// Map from bucket ID to the account that paid the bucket deposit and its amount
+	#[pallet::storage]
+	#[pallet::getter(fn bucket_deposit)]
+	pub type BucketDeposits<T: Config> =
+		StorageMap<_, Twox64Concat, BucketId, (T::AccountId, BalanceOf<T>), OptionQuery>;
+
+	/// Map from owner account to its automatic deposit top-up
+	#[pallet::storage]
+	#[pallet::getter(fn top_up_subscription)]
+	pub type TopUpSubscriptions<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::AccountId, TopUpSubscription<T>, OptionQuery>;
+
+	/// Map from removed bucket ID to the conditions its state can be reaped on
+	#[pallet::storage]
+	#[pallet::getter(fn removed_bucket)]
+	pub type RemovedBuckets<T: Config> =
+		StorageMap<_, Twox64Concat, BucketId, RemovedBucket<T>, OptionQuery>;
+
+	/// Queue of removed buckets with the block they are checked for reaping at, in block order
+	#[pallet::storage]
+	#[pallet::getter(fn reaping_queue)]
+	pub type ReapingQueue<T: Config> =
+		StorageMap<_, Twox64Concat, u64, (BucketId, BlockNumberFor<T>), OptionQuery>;
+
+	/// Head and tail indices of the reaping queue
+	#[pallet::storage]
+	pub type ReapingQueueRange<T: Config> = StorageValue<_, (u64, u64), ValueQuery>;
+
+	/// Map from owner account and asset ID to the owner's deposit of the asset
+	#[pallet::storage]
+	#[pallet::getter(fn asset_ledger)]
+	pub type AssetLedgers<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		T::AccountId,
+		Twox64Concat,
+		AssetId,
+		AccountsLedger<T>,
+		OptionQuery,
+	>;
+
+	/// Map from owner account to the asset its usage is charged in
+	#[pallet::storage]
+	#[pallet::getter(fn payment_asset)]
+	pub type PaymentAssets<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::AccountId, AssetId, OptionQuery>;
+
 	#[pallet::event]
 	#[pallet::generate_deposit(pub(crate) fn deposit_event)]
 	pub enum Event<T: Config> {
@@ -199,6 +386,78 @@ pub mod pallet {
 		BucketUpdated { bucket_id: BucketId },
 		/// Bucket with specific id marked as removed
 		BucketRemoved { bucket_id: BucketId },
+		/// Part of the owner's deposit has been allocated to the bucket
+		BucketAllocated { bucket_id: BucketId, owner_id: T::AccountId, amount: BalanceOf<T> },
+		/// Part of the bucket's allocation has been returned to the owner's deposit
+		BucketAllocationReleased {
+			bucket_id: BucketId,
+			owner_id: T::AccountId,
+			amount: BalanceOf<T>,
+		},
+		/// Access to the bucket has been granted to the account
+		BucketAccessGranted {
+			bucket_id: BucketId,
+			grantee: T::AccountId,
+			permission: BucketPermission,
+			expires_at: BlockNumberFor<T>,
+			allowance: BalanceOf<T>,
+		},
+		/// Access to the bucket has been revoked from the account
+		BucketAccessRevoked { bucket_id: BucketId, grantee: T::AccountId },
+		/// The grantee has allocated part of the owner's deposit to the bucket
+		AllowanceSpent {
+			bucket_id: BucketId,
+			grantee: T::AccountId,
+			amount: BalanceOf<T>,
+			remaining: BalanceOf<T>,
+		},
+		/// The bucket owner has offered the bucket to another account
+		BucketTransferOffered { bucket_id: BucketId, from: T::AccountId, to: T::AccountId },
+		/// The bucket owner has withdrawn the offer
+		BucketTransferCancelled { bucket_id: BucketId },
+		/// The bucket has a new owner
+		BucketTransferred { bucket_id: BucketId, from: T::AccountId, to: T::AccountId },
+		/// The bucket has been moved to another cluster
+		BucketMigrated { bucket_id: BucketId, from_cluster: ClusterId, to_cluster: ClusterId },
+		/// The removed bucket has been erased and its deposit refunded
+		BucketReaped { bucket_id: BucketId, depositor: Option<T::AccountId>, refund: BalanceOf<T> },
+		/// The owner's deposit will be topped up automatically
+		TopUpSubscribed {
+			owner_id: T::AccountId,
+			funder: T::AccountId,
+			threshold: BalanceOf<T>,
+			amount: BalanceOf<T>,
+		},
+		/// The automatic top-up of the owner's deposit has been cancelled
+		TopUpCancelled { owner_id: T::AccountId },
+		/// The owner's deposit has been topped up after a charge
+		ToppedUp { owner_id: T::AccountId, funder: T::AccountId, amount: BalanceOf<T> },
+		/// The owner's deposit could not be topped up after a charge
+		TopUpFailed { owner_id: T::AccountId, funder: T::AccountId },
+		/// An account has deposited this amount of the asset
+		AssetDeposited { owner_id: T::AccountId, asset_id: AssetId, amount: BalanceOf<T> },
+		/// An account has initiated unlock for amount of the asset
+		AssetDepositUnlock { owner_id: T::AccountId, asset_id: AssetId, amount: BalanceOf<T> },
+		/// An account has withdrawn the unlocked amount of the asset
+		AssetWithdrawn { owner_id: T::AccountId, asset_id: AssetId, amount: BalanceOf<T> },
+		/// The account has been charged for the usage in the asset
+		AssetCharged {
+			owner_id: T::AccountId,
+			asset_id: AssetId,
+			charged: BalanceOf<T>,
+			expected_to_charge: BalanceOf<T>,
+		},
+		/// The asset the account is charged in has been changed
+		PaymentAssetSet { owner_id: T::AccountId, asset_id: Option<AssetId> },
+		/// Part of the charge has been taken from the funds scheduled for unlocking, by unlock
+		/// block of the chunk, newest first
+		ChargedFromUnlocking {
+			owner_id: T::AccountId,
+			asset_id: Option<AssetId>,
+			chunks: UnlockingCharges<T>,
+		},
+		/// A charge of the account has been refunded to the source it was taken from
+		Refunded { owner_id: T::AccountId, source: PaymentSource, amount: BalanceOf<T> },
 	}
 
 	#[pallet::error]
@@ -231,6 +490,104 @@ pub mod pallet {
 		TransferFailed,
 		/// Bucket is already removed
 		AlreadyRemoved,
+		/// Customer has an outstanding debt in the cluster that is not covered by the deposit
+		CustomerIndebted,
+		/// Bucket has no budget to allocate funds to
+		NoBucketBudget,
+		/// Bucket allocation would exceed the bucket budget
+		BucketBudgetExceeded,
+		/// Grant has to expire in a future block
+		InvalidGrantExpiry,
+		/// Account has no grant for the bucket
+		NoBucketGrant,
+		/// Amount is above the spending allowance of the grant
+		AllowanceExceeded,
+		/// Bucket can not grant access to more accounts
+		TooManyGrants,
+		/// Bucket has to release its allocation first
+		BucketHasAllocation,
+		/// Bucket has not been offered to the account
+		NoBucketTransfer,
+		/// Bucket already belongs to the cluster
+		SameCluster,
+		/// Replication factor is above the maximum one of the cluster
+		UnsupportedReplicationFactor,
+		/// Region is not served by the cluster
+		UnsupportedRegion,
+		/// Storage class is not supported by the cluster
+		UnsupportedStorageClass,
+		/// Retention period can not be zero
+		InvalidTtl,
+		/// Bucket has not been removed
+		BucketNotRemoved,
+		/// Removed bucket is still in its grace period
+		GracePeriodNotOver,
+		/// Owner has no top-up subscription
+		NoTopUpSubscription,
+		/// Owner has no deposit of the asset
+		NoAssetDeposit,
+		/// A billing report of the bucket cluster is open
+		BillingReportOpen,
+		/// Bucket cluster has not finalized billing of an era after the bucket removal
+		RemovalNotBilled,
+		/// Bucket has an outstanding debt in its cluster
+		BucketIndebted,
+	}
+
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		fn on_idle(now: BlockNumberFor<T>, remaining_weight: Weight) -> Weight {
+			let mut consumed_weight = T::DbWeight::get().reads(1);
+			if remaining_weight.any_lt(consumed_weight) {
+				return Weight::zero()
+			}
+
+			let (mut head, mut tail) = <ReapingQueueRange<T>>::get();
+			let (start, end) = (head, tail);
+			let reap_weight = T::WeightInfo::reap_bucket();
+			// buckets requeued in this block are not processed again
+			while head < end {
+				// reserve writes for the updated queue range and a requeued bucket
+				let required_weight = consumed_weight
+					.saturating_add(reap_weight)
+					.saturating_add(T::DbWeight::get().writes(2));
+				if remaining_weight.any_lt(required_weight) {
+					break
+				}
+
+				if let Some((bucket_id, check_at)) = <ReapingQueue<T>>::get(head) {
+					if check_at > now {
+						break
+					}
+					<ReapingQueue<T>>::remove(head);
+					match Self::ensure_reapable(bucket_id) {
+						Ok(()) => {
+							let _ = frame_support::storage::with_storage_layer(|| {
+								Self::do_reap_bucket(bucket_id)
+							});
+						},
+						Err(Error::<T>::RemovalNotBilled) => {
+							let check_at = now.saturating_add(T::BucketGracePeriod::get());
+							<ReapingQueue<T>>::insert(tail, (bucket_id, check_at));
+							tail += 1;
+							consumed_weight =
+								consumed_weight.saturating_add(T::DbWeight::get().writes(1));
+						},
+						// the bucket has been reaped with a call already
+						Err(_) => {},
+					}
+				}
+				consumed_weight = consumed_weight.saturating_add(reap_weight);
+				head += 1;
+			}
+
+			if (head, tail) != (start, end) {
+				<ReapingQueueRange<T>>::put((head, tail));
+				consumed_weight = consumed_weight.saturating_add(T::DbWeight::get().writes(1));
+			}
+
+			consumed_weight
+		}
 	}
 
 	#[pallet::genesis_config]
@@ -307,16 +664,33 @@ pub mod pallet {
 			<T as pallet::Config>::ClusterVisitor::ensure_cluster(&cluster_id)
 				.map_err(|_| Error::<T>::ClusterDoesNotExist)?;
 
+			Self::validate_bucket_metadata(&cluster_id, &bucket_params.metadata)?;
+			Self::ensure_not_indebted(&cluster_id, &bucket_owner)?;
+
+			let deposit = T::BucketDeposit::get();
+			if !deposit.is_zero() {
+				<T as pallet::Config>::Currency::transfer(
+					&bucket_owner,
+					&Self::account_id(),
+					deposit,
+					ExistenceRequirement::KeepAlive,
+				)
+				.map_err(|_| Error::<T>::TransferFailed)?;
+				<BucketDeposits<T>>::insert(cur_bucket_id, (bucket_owner.clone(), deposit));
+			}
+
 			let bucket = Bucket {
 				bucket_id: cur_bucket_id,
 				owner_id: bucket_owner,
 				cluster_id,
 				is_public: bucket_params.is_public,
 				is_removed: false,
+				metadata: bucket_params.metadata,
 			};
 
 			<BucketsCount<T>>::set(cur_bucket_id);
 			<Buckets<T>>::insert(cur_bucket_id, bucket);
+			Self::set_bucket_budget(cur_bucket_id, bucket_params.budget)?;
 
 			Self::deposit_event(Event::<T>::BucketCreated { bucket_id: cur_bucket_id });
 
@@ -388,38 +762,13 @@ pub mod pallet {
 				Error::<T>::NoMoreChunks,
 			);
 
-			let mut value = value.min(ledger.active);
+			let value = Self::schedule_unlock(
+				&mut ledger,
+				value,
+				<T as pallet::Config>::Currency::minimum_balance(),
+			)?;
 
 			if !value.is_zero() {
-				ledger.active =
-					ledger.active.checked_sub(&value).ok_or(Error::<T>::ArithmeticUnderflow)?;
-
-				// Avoid there being a dust balance left in the accounts system.
-				if ledger.active < <T as pallet::Config>::Currency::minimum_balance() {
-					value =
-						value.checked_add(&ledger.active).ok_or(Error::<T>::ArithmeticOverflow)?;
-					ledger.active = Zero::zero();
-				}
-
-				let current_block = <frame_system::Pallet<T>>::block_number();
-				// Note: locking for extra block to allow for accounting
-				// block + configurable value - shouldn't overflow
-				let block = current_block + <T as pallet::Config>::UnlockingDelay::get();
-
-				if let Some(chunk) =
-					ledger.unlocking.last_mut().filter(|chunk| chunk.block == block)
-				{
-					// To keep the chunk count down, we only keep one chunk per era. Since
-					// `unlocking` is a FiFo queue, if a chunk exists for `era` we know that it will
-					// be the last one.
-					chunk.value = chunk.value.defensive_saturating_add(value)
-				} else {
-					ledger
-						.unlocking
-						.try_push(UnlockChunk { value, block })
-						.map_err(|_| Error::<T>::NoMoreChunks)?;
-				};
-
 				<Ledger<T>>::insert(&owner, &ledger);
 
 				Self::deposit_event(Event::<T>::InitialDepositUnlock {
@@ -493,7 +842,8 @@ pub mod pallet {
 
 		/// Sets bucket parameters.
 		///
-		/// The dispatch origin for this call must be _Signed_ by the bucket owner.
+		/// The dispatch origin for this call must be _Signed_ by the bucket owner or an admin of
+		/// the bucket.
 		///
 		/// Emits `BucketUpdated`.
 		#[pallet::call_index(5)]
@@ -503,18 +853,24 @@ pub mod pallet {
 			bucket_id: BucketId,
 			bucket_params: BucketParams,
 		) -> DispatchResult {
-			let owner = ensure_signed(origin)?;
+			let who = ensure_signed(origin)?;
 			let mut bucket = Self::buckets(bucket_id).ok_or(Error::<T>::NoBucketWithId)?;
-			ensure!(bucket.owner_id == owner, Error::<T>::NotBucketOwner);
+			Self::ensure_bucket_access(&bucket, &who, BucketPermission::Admin)?;
+
+			Self::validate_bucket_metadata(&bucket.cluster_id, &bucket_params.metadata)?;
 
 			bucket.is_public = bucket_params.is_public;
+			bucket.metadata = bucket_params.metadata;
 			<Buckets<T>>::insert(bucket_id, bucket);
+			Self::set_bucket_budget(bucket_id, bucket_params.budget)?;
 			Self::deposit_event(Event::<T>::BucketUpdated { bucket_id });
 
 			Ok(())
 		}
 
-		/// Mark existing bucket with specified bucket id as removed
+		/// Mark existing bucket with specified bucket id as removed. The bucket can still be
+		/// billed during the grace period, after which it is reaped once its cluster finalizes
+		/// billing of a later era.
 		///
 		/// Only an owner can remove a bucket
 		#[pallet::call_index(6)]
@@ -522,18 +878,613 @@ pub mod pallet {
 		pub fn remove_bucket(origin: OriginFor<T>, bucket_id: BucketId) -> DispatchResult {
 			let owner = ensure_signed(origin)?;
 
-			<Buckets<T>>::try_mutate(bucket_id, |maybe_bucket| -> DispatchResult {
-				let bucket = maybe_bucket.as_mut().ok_or(Error::<T>::NoBucketWithId)?;
-				ensure!(bucket.owner_id == owner, Error::<T>::NotBucketOwner);
-				ensure!(!bucket.is_removed, Error::<T>::AlreadyRemoved);
+			let cluster_id =
+				<Buckets<T>>::try_mutate(bucket_id, |maybe_bucket| -> Result<_, DispatchError> {
+					let bucket = maybe_bucket.as_mut().ok_or(Error::<T>::NoBucketWithId)?;
+					ensure!(bucket.owner_id == owner, Error::<T>::NotBucketOwner);
+					ensure!(!bucket.is_removed, Error::<T>::AlreadyRemoved);
+
+					// Mark the bucket as removed
+					bucket.is_removed = true;
+
+					Ok(bucket.cluster_id)
+				})?;
+
+			let reap_at = <frame_system::Pallet<T>>::block_number()
+				.saturating_add(T::BucketGracePeriod::get());
+			let billed_era = T::PayoutVisitor::get_last_finalized_era(&cluster_id);
+			<RemovedBuckets<T>>::insert(bucket_id, RemovedBucket { reap_at, billed_era });
+			<ReapingQueueRange<T>>::mutate(|(_, tail)| {
+				<ReapingQueue<T>>::insert(*tail, (bucket_id, reap_at));
+				*tail += 1;
+			});
 
-				// Mark the bucket as removed
-				bucket.is_removed = true;
+			Self::deposit_event(Event::<T>::BucketRemoved { bucket_id });
 
-				Ok(())
-			})?;
+			Ok(())
+		}
 
-			Self::deposit_event(Event::<T>::BucketRemoved { bucket_id });
+		/// Move a portion of the owner's active deposit to the bucket's own allocation, up to the
+		/// bucket budget. The whole active deposit is allocated if the rest would be below the
+		/// minimum balance.
+		///
+		/// The dispatch origin for this call must be _Signed_ by the bucket owner or a grantee
+		/// with write access, in which case the amount is taken from the grant's allowance.
+		///
+		/// Emits `BucketAllocated`.
+		#[pallet::call_index(7)]
+		#[pallet::weight(T::WeightInfo::allocate_to_bucket())]
+		pub fn allocate_to_bucket(
+			origin: OriginFor<T>,
+			bucket_id: BucketId,
+			#[pallet::compact] value: BalanceOf<T>,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let bucket = Self::buckets(bucket_id).ok_or(Error::<T>::NoBucketWithId)?;
+			let grant = Self::ensure_bucket_access(&bucket, &who, BucketPermission::Write)?;
+			ensure!(!bucket.is_removed, Error::<T>::AlreadyRemoved);
+
+			let owner = bucket.owner_id;
+			let mut budget = Self::bucket_budgets(bucket_id).ok_or(Error::<T>::NoBucketBudget)?;
+			let mut ledger = Self::ledger(&owner).ok_or(Error::<T>::NotOwner)?;
+
+			// Avoid there being a dust balance left in the active deposit, as with
+			// `unlock_deposit`.
+			let mut value = value.min(ledger.active);
+			if ledger.active.saturating_sub(value) <
+				<T as pallet::Config>::Currency::minimum_balance()
+			{
+				value = ledger.active;
+			}
+			if let Some(mut grant) = grant {
+				grant.allowance =
+					grant.allowance.checked_sub(&value).ok_or(Error::<T>::AllowanceExceeded)?;
+				<BucketGrants<T>>::insert(bucket_id, &who, &grant);
+				Self::deposit_event(Event::<T>::AllowanceSpent {
+					bucket_id,
+					grantee: who,
+					amount: value,
+					remaining: grant.allowance,
+				});
+			}
+
+			budget.allocated =
+				budget.allocated.checked_add(&value).ok_or(Error::<T>::ArithmeticOverflow)?;
+			ensure!(budget.allocated <= budget.limit, Error::<T>::BucketBudgetExceeded);
+
+			ledger.active =
+				ledger.active.checked_sub(&value).ok_or(Error::<T>::ArithmeticUnderflow)?;
+			ledger.total =
+				ledger.total.checked_sub(&value).ok_or(Error::<T>::ArithmeticUnderflow)?;
+
+			<Ledger<T>>::insert(&owner, &ledger);
+			<BucketBudgets<T>>::insert(bucket_id, budget);
+
+			Self::deposit_event(Event::<T>::BucketAllocated {
+				bucket_id,
+				owner_id: owner,
+				amount: value,
+			});
+
+			Ok(())
+		}
+
+		/// Return a portion of the bucket's allocation back to the owner's active deposit.
+		///
+		/// The dispatch origin for this call must be _Signed_ by the bucket owner or an admin of
+		/// the bucket.
+		///
+		/// Emits `BucketAllocationReleased`.
+		#[pallet::call_index(8)]
+		#[pallet::weight(T::WeightInfo::release_bucket_allocation())]
+		pub fn release_bucket_allocation(
+			origin: OriginFor<T>,
+			bucket_id: BucketId,
+			#[pallet::compact] value: BalanceOf<T>,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let bucket = Self::buckets(bucket_id).ok_or(Error::<T>::NoBucketWithId)?;
+			Self::ensure_bucket_access(&bucket, &who, BucketPermission::Admin)?;
+
+			let owner = bucket.owner_id;
+			let mut budget = Self::bucket_budgets(bucket_id).ok_or(Error::<T>::NoBucketBudget)?;
+			let value = value.min(budget.allocated);
+			budget.allocated =
+				budget.allocated.checked_sub(&value).ok_or(Error::<T>::ArithmeticUnderflow)?;
+
+			Self::return_to_ledger(&owner, value)?;
+			<BucketBudgets<T>>::insert(bucket_id, budget);
+
+			Self::deposit_event(Event::<T>::BucketAllocationReleased {
+				bucket_id,
+				owner_id: owner,
+				amount: value,
+			});
+
+			Ok(())
+		}
+
+		/// Grant the account access to the bucket until the `expires_at` block, optionally with
+		/// an allowance to allocate the owner's deposit to the bucket. Replaces the existing grant
+		/// of the account.
+		///
+		/// The dispatch origin for this call must be _Signed_ by the bucket owner, or by an admin
+		/// of the bucket for read and write access.
+		///
+		/// Emits `BucketAccessGranted`.
+		#[pallet::call_index(9)]
+		#[pallet::weight(T::WeightInfo::grant_bucket_access())]
+		pub fn grant_bucket_access(
+			origin: OriginFor<T>,
+			bucket_id: BucketId,
+			grantee: T::AccountId,
+			permission: BucketPermission,
+			expires_at: BlockNumberFor<T>,
+			allowance: BalanceOf<T>,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let bucket = Self::buckets(bucket_id).ok_or(Error::<T>::NoBucketWithId)?;
+			let grant = Self::ensure_bucket_access(&bucket, &who, BucketPermission::Admin)?;
+			ensure!(!bucket.is_removed, Error::<T>::AlreadyRemoved);
+			// Only the owner manages admins
+			ensure!(
+				grant.is_none() || permission < BucketPermission::Admin,
+				Error::<T>::Unauthorised
+			);
+			ensure!(grantee != bucket.owner_id, Error::<T>::Unauthorised);
+			ensure!(
+				expires_at > <frame_system::Pallet<T>>::block_number(),
+				Error::<T>::InvalidGrantExpiry
+			);
+
+			if !<BucketGrants<T>>::contains_key(bucket_id, &grantee) {
+				<BucketGrantsCount<T>>::try_mutate(bucket_id, |count| -> DispatchResult {
+					ensure!(*count < MaxBucketGrants::get(), Error::<T>::TooManyGrants);
+					*count += 1;
+					Ok(())
+				})?;
+			}
+
+			<BucketGrants<T>>::insert(
+				bucket_id,
+				&grantee,
+				BucketGrant { permission, expires_at, allowance },
+			);
+
+			Self::deposit_event(Event::<T>::BucketAccessGranted {
+				bucket_id,
+				grantee,
+				permission,
+				expires_at,
+				allowance,
+			});
+
+			Ok(())
+		}
+
+		/// Revoke the access of the account to the bucket.
+		///
+		/// The dispatch origin for this call must be _Signed_ by the bucket owner, by an admin of
+		/// the bucket for read and write access, or by the grantee itself. Expired grants can be
+		/// removed by anyone.
+		///
+		/// Emits `BucketAccessRevoked`.
+		#[pallet::call_index(10)]
+		#[pallet::weight(T::WeightInfo::revoke_bucket_access())]
+		pub fn revoke_bucket_access(
+			origin: OriginFor<T>,
+			bucket_id: BucketId,
+			grantee: T::AccountId,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let bucket = Self::buckets(bucket_id).ok_or(Error::<T>::NoBucketWithId)?;
+			let revoked =
+				Self::bucket_grant(bucket_id, &grantee).ok_or(Error::<T>::NoBucketGrant)?;
+
+			let is_expired = revoked.expires_at <= <frame_system::Pallet<T>>::block_number();
+			if who != grantee && !is_expired {
+				let grant = Self::ensure_bucket_access(&bucket, &who, BucketPermission::Admin)?;
+				// Only the owner manages admins
+				ensure!(
+					grant.is_none() || revoked.permission < BucketPermission::Admin,
+					Error::<T>::Unauthorised
+				);
+			}
+
+			<BucketGrants<T>>::remove(bucket_id, &grantee);
+			<BucketGrantsCount<T>>::mutate(bucket_id, |count| *count = count.saturating_sub(1));
+
+			Self::deposit_event(Event::<T>::BucketAccessRevoked { bucket_id, grantee });
+
+			Ok(())
+		}
+
+		/// Offer the bucket to another account, which becomes the owner once it accepts the
+		/// offer. Replaces the previous offer.
+		///
+		/// The dispatch origin for this call must be _Signed_ by the bucket owner.
+		///
+		/// Emits `BucketTransferOffered`.
+		#[pallet::call_index(11)]
+		#[pallet::weight(T::WeightInfo::offer_bucket_transfer())]
+		pub fn offer_bucket_transfer(
+			origin: OriginFor<T>,
+			bucket_id: BucketId,
+			new_owner: T::AccountId,
+		) -> DispatchResult {
+			let owner = ensure_signed(origin)?;
+			let bucket = Self::buckets(bucket_id).ok_or(Error::<T>::NoBucketWithId)?;
+			ensure!(bucket.owner_id == owner, Error::<T>::NotBucketOwner);
+			ensure!(!bucket.is_removed, Error::<T>::AlreadyRemoved);
+			ensure!(new_owner != owner, Error::<T>::Unauthorised);
+
+			<BucketTransfers<T>>::insert(bucket_id, &new_owner);
+
+			Self::deposit_event(Event::<T>::BucketTransferOffered {
+				bucket_id,
+				from: owner,
+				to: new_owner,
+			});
+
+			Ok(())
+		}
+
+		/// Withdraw the pending offer of the bucket.
+		///
+		/// The dispatch origin for this call must be _Signed_ by the bucket owner.
+		///
+		/// Emits `BucketTransferCancelled`.
+		#[pallet::call_index(12)]
+		#[pallet::weight(T::WeightInfo::cancel_bucket_transfer())]
+		pub fn cancel_bucket_transfer(origin: OriginFor<T>, bucket_id: BucketId) -> DispatchResult {
+			let owner = ensure_signed(origin)?;
+			let bucket = Self::buckets(bucket_id).ok_or(Error::<T>::NoBucketWithId)?;
+			ensure!(bucket.owner_id == owner, Error::<T>::NotBucketOwner);
+			ensure!(<BucketTransfers<T>>::contains_key(bucket_id), Error::<T>::NoBucketTransfer);
+
+			<BucketTransfers<T>>::remove(bucket_id);
+
+			Self::deposit_event(Event::<T>::BucketTransferCancelled { bucket_id });
+
+			Ok(())
+		}
+
+		/// Accept the offered bucket. The grants of the previous owner are revoked, and the
+		/// bucket allocation has to be released by the previous owner beforehand. The bucket can
+		/// not be transferred while a billing report of its cluster is open or while it has an
+		/// outstanding debt.
+		///
+		/// The dispatch origin for this call must be _Signed_ by the account the bucket has been
+		/// offered to.
+		///
+		/// Emits `BucketTransferred`.
+		#[pallet::call_index(13)]
+		#[pallet::weight(T::WeightInfo::accept_bucket_transfer())]
+		pub fn accept_bucket_transfer(origin: OriginFor<T>, bucket_id: BucketId) -> DispatchResult {
+			let new_owner = ensure_signed(origin)?;
+			let mut bucket = Self::buckets(bucket_id).ok_or(Error::<T>::NoBucketWithId)?;
+			ensure!(
+				Self::bucket_transfer(bucket_id).as_ref() == Some(&new_owner),
+				Error::<T>::NoBucketTransfer
+			);
+			ensure!(!bucket.is_removed, Error::<T>::AlreadyRemoved);
+			ensure!(
+				Self::bucket_budgets(bucket_id).map_or(true, |budget| budget.allocated.is_zero()),
+				Error::<T>::BucketHasAllocation
+			);
+			// the debt of the bucket stays with the owner it was incurred by
+			ensure!(
+				T::PayoutVisitor::get_bucket_debt(&bucket.cluster_id, bucket_id).is_zero(),
+				Error::<T>::BucketIndebted
+			);
+			Self::ensure_not_indebted(&bucket.cluster_id, &new_owner)?;
+			Self::ensure_no_open_billing_report(&bucket.cluster_id)?;
+
+			let _ = <BucketGrants<T>>::clear_prefix(bucket_id, MaxBucketGrants::get(), None);
+			<BucketGrantsCount<T>>::remove(bucket_id);
+			<BucketTransfers<T>>::remove(bucket_id);
+
+			let previous_owner = sp_std::mem::replace(&mut bucket.owner_id, new_owner.clone());
+			<Buckets<T>>::insert(bucket_id, bucket);
+
+			Self::deposit_event(Event::<T>::BucketTransferred {
+				bucket_id,
+				from: previous_owner,
+				to: new_owner,
+			});
+
+			Ok(())
+		}
+
+		/// Move the bucket to another cluster, e.g. when its cluster is decommissioned. The bucket
+		/// can not be migrated while a billing report of either cluster is open.
+		///
+		/// The dispatch origin for this call must be _Root_.
+		///
+		/// Emits `BucketMigrated`.
+		#[pallet::call_index(14)]
+		#[pallet::weight(T::WeightInfo::migrate_bucket())]
+		pub fn migrate_bucket(
+			origin: OriginFor<T>,
+			bucket_id: BucketId,
+			cluster_id: ClusterId,
+		) -> DispatchResult {
+			ensure_root(origin)?; // requires Governance approval
+			let mut bucket = Self::buckets(bucket_id).ok_or(Error::<T>::NoBucketWithId)?;
+			ensure!(!bucket.is_removed, Error::<T>::AlreadyRemoved);
+			ensure!(bucket.cluster_id != cluster_id, Error::<T>::SameCluster);
+			Self::ensure_no_open_billing_report(&bucket.cluster_id)?;
+			Self::ensure_no_open_billing_report(&cluster_id)?;
+
+			<T as pallet::Config>::ClusterVisitor::ensure_cluster(&cluster_id)
+				.map_err(|_| Error::<T>::ClusterDoesNotExist)?;
+			Self::validate_bucket_metadata(&cluster_id, &bucket.metadata)?;
+
+			let from_cluster = sp_std::mem::replace(&mut bucket.cluster_id, cluster_id);
+			<Buckets<T>>::insert(bucket_id, bucket);
+
+			Self::deposit_event(Event::<T>::BucketMigrated {
+				bucket_id,
+				from_cluster,
+				to_cluster: cluster_id,
+			});
+
+			Ok(())
+		}
+
+		/// Erase a removed bucket whose grace period is over and whose cluster finalized billing of
+		/// an era after the removal, returning its remaining allocation to the owner's deposit and
+		/// the bucket deposit to the account that paid it.
+		///
+		/// The dispatch origin for this call must be _Signed_ by any account.
+		///
+		/// Emits `BucketReaped`.
+		#[pallet::call_index(15)]
+		#[pallet::weight(T::WeightInfo::reap_bucket())]
+		pub fn reap_bucket(origin: OriginFor<T>, bucket_id: BucketId) -> DispatchResult {
+			ensure_signed(origin)?;
+			Self::ensure_reapable(bucket_id)?;
+
+			Self::do_reap_bucket(bucket_id)
+		}
+
+		/// Top up the owner's deposit with `amount` whenever a charge drops its active deposit
+		/// below `threshold`. The top-up is pulled from the free balance of the signer, so an
+		/// account can fund the deposit of another owner. Replaces the existing subscription.
+		///
+		/// The dispatch origin for this call must be _Signed_ by the owner, or by the funder of
+		/// the existing subscription if any.
+		///
+		/// Emits `TopUpSubscribed`.
+		#[pallet::call_index(16)]
+		#[pallet::weight(T::WeightInfo::subscribe_top_up())]
+		pub fn subscribe_top_up(
+			origin: OriginFor<T>,
+			owner: T::AccountId,
+			#[pallet::compact] threshold: BalanceOf<T>,
+			#[pallet::compact] amount: BalanceOf<T>,
+		) -> DispatchResult {
+			let funder = ensure_signed(origin)?;
+			ensure!(<Ledger<T>>::contains_key(&owner), Error::<T>::NotOwner);
+			ensure!(
+				amount >= <T as pallet::Config>::Currency::minimum_balance(),
+				Error::<T>::InsufficientDeposit
+			);
+			if let Some(subscription) = Self::top_up_subscription(&owner) {
+				ensure!(funder == owner || funder == subscription.funder, Error::<T>::Unauthorised);
+			}
+
+			<TopUpSubscriptions<T>>::insert(
+				&owner,
+				TopUpSubscription { funder: funder.clone(), threshold, amount },
+			);
+			Self::deposit_event(Event::<T>::TopUpSubscribed {
+				owner_id: owner,
+				funder,
+				threshold,
+				amount,
+			});
+
+			Ok(())
+		}
+
+		/// Stop topping up the owner's deposit.
+		///
+		/// The dispatch origin for this call must be _Signed_ by the owner or the funder.
+		///
+		/// Emits `TopUpCancelled`.
+		#[pallet::call_index(17)]
+		#[pallet::weight(T::WeightInfo::cancel_top_up())]
+		pub fn cancel_top_up(origin: OriginFor<T>, owner: T::AccountId) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let subscription =
+				Self::top_up_subscription(&owner).ok_or(Error::<T>::NoTopUpSubscription)?;
+			ensure!(who == owner || who == subscription.funder, Error::<T>::Unauthorised);
+
+			<TopUpSubscriptions<T>>::remove(&owner);
+			Self::deposit_event(Event::<T>::TopUpCancelled { owner_id: owner });
+
+			Ok(())
+		}
+
+		/// Lock up `value` of the owner's balance of the asset, adding it to the owner's deposit
+		/// of the asset.
+		///
+		/// The resulting deposit must be more than the `minimum_balance` of the asset.
+		///
+		/// The dispatch origin for this call must be _Signed_ by the owner account.
+		///
+		/// Emits `AssetDeposited`.
+		#[pallet::call_index(18)]
+		#[pallet::weight(T::WeightInfo::deposit_asset())]
+		pub fn deposit_asset(
+			origin: OriginFor<T>,
+			asset_id: AssetId,
+			#[pallet::compact] value: BalanceOf<T>,
+		) -> DispatchResult {
+			let owner = ensure_signed(origin)?;
+			let mut ledger = Self::asset_ledger(&owner, asset_id)
+				.unwrap_or_else(|| AccountsLedger::default_from(owner.clone()));
+
+			let owner_balance = <T::Assets as fungibles::Inspect<T::AccountId>>::reducible_balance(
+				asset_id,
+				&owner,
+				Preservation::Expendable,
+				Fortitude::Polite,
+			);
+			let value = value.min(owner_balance);
+			ledger.total =
+				ledger.total.checked_add(&value).ok_or(Error::<T>::ArithmeticOverflow)?;
+			ledger.active =
+				ledger.active.checked_add(&value).ok_or(Error::<T>::ArithmeticOverflow)?;
+
+			// Reject a deposit which is considered to be _dust_.
+			ensure!(
+				ledger.active >=
+					<T::Assets as fungibles::Inspect<T::AccountId>>::minimum_balance(asset_id),
+				Error::<T>::InsufficientDeposit
+			);
+
+			<T::Assets as fungibles::Mutate<T::AccountId>>::transfer(
+				asset_id,
+				&owner,
+				&Self::account_id(),
+				value,
+				Preservation::Expendable,
+			)
+			.map_err(|_| Error::<T>::TransferFailed)?;
+			<AssetLedgers<T>>::insert(&owner, asset_id, &ledger);
+
+			Self::deposit_event(Event::<T>::AssetDeposited {
+				owner_id: owner,
+				asset_id,
+				amount: value,
+			});
+
+			Ok(())
+		}
+
+		/// Schedule a portion of the owner's deposit of the asset to be unlocked ready for
+		/// transfer out after the lock period ends.
+		///
+		/// The dispatch origin for this call must be _Signed_ by the owner.
+		///
+		/// Emits `AssetDepositUnlock`.
+		///
+		/// See also [`Call::withdraw_unlocked_asset_deposit`].
+		#[pallet::call_index(19)]
+		#[pallet::weight(T::WeightInfo::unlock_asset_deposit())]
+		pub fn unlock_asset_deposit(
+			origin: OriginFor<T>,
+			asset_id: AssetId,
+			#[pallet::compact] value: BalanceOf<T>,
+		) -> DispatchResult {
+			let owner = ensure_signed(origin)?;
+			let mut ledger =
+				Self::asset_ledger(&owner, asset_id).ok_or(Error::<T>::NoAssetDeposit)?;
+			ensure!(
+				ledger.unlocking.len() < MaxUnlockingChunks::get() as usize,
+				Error::<T>::NoMoreChunks,
+			);
+
+			let value = Self::schedule_unlock(
+				&mut ledger,
+				value,
+				<T::Assets as fungibles::Inspect<T::AccountId>>::minimum_balance(asset_id),
+			)?;
+
+			if !value.is_zero() {
+				<AssetLedgers<T>>::insert(&owner, asset_id, &ledger);
+
+				Self::deposit_event(Event::<T>::AssetDepositUnlock {
+					owner_id: owner,
+					asset_id,
+					amount: value,
+				});
+			}
+			Ok(())
+		}
+
+		/// Transfer the unlocked chunks of the owner's deposit of the asset back to the owner.
+		///
+		/// The dispatch origin for this call must be _Signed_ by the owner.
+		///
+		/// Emits `AssetWithdrawn`.
+		///
+		/// See also [`Call::unlock_asset_deposit`].
+		#[pallet::call_index(20)]
+		#[pallet::weight(T::WeightInfo::withdraw_unlocked_asset_deposit())]
+		pub fn withdraw_unlocked_asset_deposit(
+			origin: OriginFor<T>,
+			asset_id: AssetId,
+		) -> DispatchResult {
+			let owner = ensure_signed(origin)?;
+			let mut ledger =
+				Self::asset_ledger(&owner, asset_id).ok_or(Error::<T>::NoAssetDeposit)?;
+			let old_total = ledger.total;
+			ledger = ledger.consolidate_unlocked(<frame_system::Pallet<T>>::block_number());
+
+			if ledger.unlocking.is_empty() &&
+				ledger.active <
+					<T::Assets as fungibles::Inspect<T::AccountId>>::minimum_balance(asset_id)
+			{
+				<AssetLedgers<T>>::remove(&owner, asset_id);
+				<PaymentAssets<T>>::mutate_exists(&owner, |payment_asset| {
+					if *payment_asset == Some(asset_id) {
+						*payment_asset = None;
+					}
+				});
+			} else {
+				<AssetLedgers<T>>::insert(&owner, asset_id, &ledger);
+			}
+
+			if ledger.total < old_total {
+				let value =
+					old_total.checked_sub(&ledger.total).ok_or(Error::<T>::ArithmeticUnderflow)?;
+
+				<T::Assets as fungibles::Mutate<T::AccountId>>::transfer(
+					asset_id,
+					&Self::account_id(),
+					&owner,
+					value,
+					Preservation::Expendable,
+				)?;
+				Self::deposit_event(Event::<T>::AssetWithdrawn {
+					owner_id: owner,
+					asset_id,
+					amount: value,
+				});
+			}
+
+			Ok(())
+		}
+
+		/// Charge the owner's usage in the asset, from the owner's deposit of the asset, in the
+		/// clusters that accept it. The usage is charged in the native currency if no asset is
+		/// provided.
+		///
+		/// The dispatch origin for this call must be _Signed_ by the owner.
+		///
+		/// Emits `PaymentAssetSet`.
+		#[pallet::call_index(21)]
+		#[pallet::weight(T::WeightInfo::set_payment_asset())]
+		pub fn set_payment_asset(
+			origin: OriginFor<T>,
+			asset_id: Option<AssetId>,
+		) -> DispatchResult {
+			let owner = ensure_signed(origin)?;
+
+			match asset_id {
+				Some(asset_id) => {
+					ensure!(
+						<AssetLedgers<T>>::contains_key(&owner, asset_id),
+						Error::<T>::NoAssetDeposit
+					);
+					<PaymentAssets<T>>::insert(&owner, asset_id);
+				},
+				None => <PaymentAssets<T>>::remove(&owner),
+			}
+			Self::deposit_event(Event::<T>::PaymentAssetSet { owner_id: owner, asset_id });
 
 			Ok(())
 		}
@@ -553,15 +1504,16 @@ pub mod pallet {
 
 		/// Update the ledger for a owner.
 		///
-		/// This will also deposit the funds to pallet.
+		/// This will also deposit the `value` added to the ledger to pallet.
 		fn update_ledger_and_deposit(
 			owner: &T::AccountId,
 			ledger: &AccountsLedger<T>,
+			value: BalanceOf<T>,
 		) -> DispatchResult {
 			<T as pallet::Config>::Currency::transfer(
 				owner,
 				&Self::account_id(),
-				ledger.total,
+				value,
 				ExistenceRequirement::AllowDeath,
 			)?;
 			<Ledger<T>>::insert(owner, ledger);
@@ -577,87 +1529,364 @@ pub mod pallet {
 		/// - after a `withdraw_unlocked_deposit()` call that frees all of a owner's locked balance.
 		fn kill_owner(owner: &T::AccountId) -> DispatchResult {
 			<Ledger<T>>::remove(owner);
+			<TopUpSubscriptions<T>>::remove(owner);
 
 			frame_system::Pallet::<T>::dec_consumers(owner);
 
 			Ok(())
 		}
 
-		/// Charge funds that were scheduled for unlocking.
+		/// Top up the owner's deposit if the charge has dropped it below the subscription
+		/// threshold. A failed top-up does not fail the charge.
+		fn top_up(owner: &T::AccountId, active: BalanceOf<T>) {
+			let subscription = match Self::top_up_subscription(owner) {
+				Some(subscription) if active < subscription.threshold => subscription,
+				_ => return,
+			};
+
+			let result = frame_support::storage::with_storage_layer(|| -> DispatchResult {
+				if subscription.funder != *owner {
+					<T as pallet::Config>::Currency::transfer(
+						&subscription.funder,
+						owner,
+						subscription.amount,
+						ExistenceRequirement::KeepAlive,
+					)?;
+				}
+				<Self as CustomerDepositor<T>>::deposit_extra(
+					owner.clone(),
+					subscription.amount.saturated_into(),
+				)
+			});
+
+			match result {
+				Ok(()) => Self::deposit_event(Event::<T>::ToppedUp {
+					owner_id: owner.clone(),
+					funder: subscription.funder,
+					amount: subscription.amount,
+				}),
+				Err(_) => Self::deposit_event(Event::<T>::TopUpFailed {
+					owner_id: owner.clone(),
+					funder: subscription.funder,
+				}),
+			}
+		}
+
+		/// Credit the owner's deposit with funds already held by the pallet.
+		fn return_to_ledger(owner: &T::AccountId, value: BalanceOf<T>) -> DispatchResult {
+			let mut ledger = match Self::ledger(owner) {
+				Some(ledger) => ledger,
+				None => {
+					// The owner may have withdrawn the whole deposit in the meantime
+					frame_system::Pallet::<T>::inc_consumers(owner)
+						.map_err(|_| Error::<T>::BadState)?;
+					AccountsLedger::default_from(owner.clone())
+				},
+			};
+			ledger.active =
+				ledger.active.checked_add(&value).ok_or(Error::<T>::ArithmeticOverflow)?;
+			ledger.total =
+				ledger.total.checked_add(&value).ok_or(Error::<T>::ArithmeticOverflow)?;
+			<Ledger<T>>::insert(owner, &ledger);
+
+			Ok(())
+		}
+
+		/// Ensures the grace period of the removed bucket is over and its last usage is billed.
+		fn ensure_reapable(bucket_id: BucketId) -> Result<(), Error<T>> {
+			let removed = Self::removed_bucket(bucket_id).ok_or(Error::<T>::BucketNotRemoved)?;
+			ensure!(
+				removed.reap_at <= <frame_system::Pallet<T>>::block_number(),
+				Error::<T>::GracePeriodNotOver
+			);
+			let bucket = Self::buckets(bucket_id).ok_or(Error::<T>::NoBucketWithId)?;
+			ensure!(
+				T::PayoutVisitor::get_last_finalized_era(&bucket.cluster_id) > removed.billed_era,
+				Error::<T>::RemovalNotBilled
+			);
+
+			Ok(())
+		}
+
+		/// Erase the removed bucket with all of its state and refund its deposit.
+		fn do_reap_bucket(bucket_id: BucketId) -> DispatchResult {
+			let bucket = Self::buckets(bucket_id).ok_or(Error::<T>::NoBucketWithId)?;
+			ensure!(bucket.is_removed, Error::<T>::BucketNotRemoved);
+
+			let (depositor, refund) = match <BucketDeposits<T>>::take(bucket_id) {
+				Some((depositor, deposit)) => {
+					<T as pallet::Config>::Currency::transfer(
+						&Self::account_id(),
+						&depositor,
+						deposit,
+						ExistenceRequirement::AllowDeath,
+					)?;
+					(Some(depositor), deposit)
+				},
+				None => (None, Zero::zero()),
+			};
+
+			// Refund first, the ledger can only be recreated for an existing owner account
+			if let Some(budget) = <BucketBudgets<T>>::take(bucket_id) {
+				if !budget.allocated.is_zero() {
+					Self::return_to_ledger(&bucket.owner_id, budget.allocated)?;
+				}
+			}
+
+			let _ = <BucketGrants<T>>::clear_prefix(bucket_id, MaxBucketGrants::get(), None);
+			<BucketGrantsCount<T>>::remove(bucket_id);
+			<BucketTransfers<T>>::remove(bucket_id);
+			<RemovedBuckets<T>>::remove(bucket_id);
+			<Buckets<T>>::remove(bucket_id);
+
+			Self::deposit_event(Event::<T>::BucketReaped { bucket_id, depositor, refund });
+
+			Ok(())
+		}
+
+		/// Ensures the cluster supports the metadata of its bucket.
+		fn validate_bucket_metadata(
+			cluster_id: &ClusterId,
+			metadata: &BucketMetadata,
+		) -> DispatchResult {
+			let capabilities = <T as pallet::Config>::ClusterVisitor::get_capabilities(cluster_id)
+				.map_err(|_| Error::<T>::ClusterDoesNotExist)?;
+
+			if let Some(replication_factor) = metadata.replication_factor {
+				ensure!(
+					(1..=capabilities.max_replication_factor).contains(&replication_factor),
+					Error::<T>::UnsupportedReplicationFactor
+				);
+			}
+			if let Some(region) = &metadata.region {
+				ensure!(capabilities.regions.contains(region), Error::<T>::UnsupportedRegion);
+			}
+			ensure!(
+				metadata.storage_class == StorageClass::Hot ||
+					capabilities.cold_storage_discount.is_some(),
+				Error::<T>::UnsupportedStorageClass
+			);
+			ensure!(metadata.ttl != Some(0), Error::<T>::InvalidTtl);
+
+			Ok(())
+		}
+
+		/// Debtors have to top up their deposit to cover the debt in the cluster first. The debts
+		/// of the buckets charged from their own allocation are not covered by the deposit and have
+		/// to be repaid from the allocations.
+		fn ensure_not_indebted(cluster_id: &ClusterId, owner: &T::AccountId) -> DispatchResult {
+			let debt = T::PayoutVisitor::get_customer_debt(cluster_id, owner);
+			if !debt.is_zero() {
+				let active = Self::ledger(owner).map(|ledger| ledger.active).unwrap_or_default();
+				ensure!(active.saturated_into::<u128>() >= debt, Error::<T>::CustomerIndebted);
+			}
+			ensure!(
+				T::PayoutVisitor::get_buckets_debt(cluster_id, owner).is_zero(),
+				Error::<T>::CustomerIndebted
+			);
+
+			Ok(())
+		}
+
+		/// The buckets of a cluster keep their owner and cluster while a billing report of the
+		/// cluster is open, so that its charges and refunds reach the accounts that used them.
+		fn ensure_no_open_billing_report(cluster_id: &ClusterId) -> DispatchResult {
+			ensure!(
+				!T::PayoutVisitor::has_open_billing_report(cluster_id),
+				Error::<T>::BillingReportOpen
+			);
+
+			Ok(())
+		}
+
+		/// Ensures the account is the bucket owner or holds an unexpired grant with at least the
+		/// given permission. Returns the grant, which is `None` for the owner.
+		fn ensure_bucket_access(
+			bucket: &Bucket<T>,
+			who: &T::AccountId,
+			permission: BucketPermission,
+		) -> Result<Option<BucketGrantOf<T>>, Error<T>> {
+			if bucket.owner_id == *who {
+				return Ok(None)
+			}
+
+			let grant =
+				Self::bucket_grant(bucket.bucket_id, who).ok_or(Error::<T>::NotBucketOwner)?;
+			ensure!(
+				grant.permission >= permission &&
+					grant.expires_at > <frame_system::Pallet<T>>::block_number(),
+				Error::<T>::NotBucketOwner
+			);
+
+			Ok(Some(grant))
+		}
+
+		/// The permission the account has on the bucket, if any.
+		pub fn bucket_access(bucket_id: BucketId, who: &T::AccountId) -> Option<BucketPermission> {
+			let bucket = Self::buckets(bucket_id)?;
+			if bucket.owner_id == *who {
+				return Some(BucketPermission::Admin)
+			}
+
+			Self::bucket_grant(bucket_id, who)
+				.filter(|grant| grant.expires_at > <frame_system::Pallet<T>>::block_number())
+				.map(|grant| grant.permission)
+		}
+
+		/// Unexpired grants of the bucket, by grantee.
+		pub fn bucket_grants(bucket_id: BucketId) -> Vec<(T::AccountId, BucketGrantOf<T>)> {
+			let now = <frame_system::Pallet<T>>::block_number();
+			BucketGrants::<T>::iter_prefix(bucket_id)
+				.filter(|(_, grant)| grant.expires_at > now)
+				.collect()
+		}
+
+		/// Set the budget of the bucket, keeping its current allocation.
 		///
-		/// Returns the updated ledger, and the amount actually charged.
-		fn charge_unlocking(
-			mut ledger: AccountsLedger<T>,
+		/// The budget can not be lowered below the allocation, nor removed while the bucket
+		/// still holds an allocation.
+		fn set_bucket_budget(bucket_id: BucketId, budget: Option<u128>) -> DispatchResult {
+			let allocated = Self::bucket_budgets(bucket_id)
+				.map(|budget| budget.allocated)
+				.unwrap_or_default();
+
+			if let Some(limit) = budget {
+				let limit = limit.saturated_into::<BalanceOf<T>>();
+				ensure!(allocated <= limit, Error::<T>::BucketBudgetExceeded);
+				<BucketBudgets<T>>::insert(bucket_id, BucketBudget { limit, allocated });
+			} else {
+				ensure!(allocated.is_zero(), Error::<T>::BucketBudgetExceeded);
+				<BucketBudgets<T>>::remove(bucket_id);
+			}
+
+			Ok(())
+		}
+
+		/// Move up to `value` of the active deposit to a chunk unlocked after the unlocking
+		/// delay. The whole active deposit is unlocked if the rest would be below the minimum
+		/// balance.
+		///
+		/// Returns the amount actually scheduled for unlocking.
+		fn schedule_unlock(
+			ledger: &mut AccountsLedger<T>,
 			value: BalanceOf<T>,
-		) -> Result<(AccountsLedger<T>, BalanceOf<T>), Error<T>> {
-			let mut unlocking_balance = BalanceOf::<T>::zero();
-
-			while let Some(last) = ledger.unlocking.last_mut() {
-				let temp = unlocking_balance
-					.checked_add(&last.value)
-					.ok_or(Error::<T>::ArithmeticOverflow)?;
-				if temp <= value {
-					unlocking_balance = temp;
-					ledger.unlocking.pop();
-				} else {
-					let diff = value
-						.checked_sub(&unlocking_balance)
-						.ok_or(Error::<T>::ArithmeticUnderflow)?;
+			minimum_balance: BalanceOf<T>,
+		) -> Result<BalanceOf<T>, Error<T>> {
+			let mut value = value.min(ledger.active);
+			if value.is_zero() {
+				return Ok(value)
+			}
 
-					unlocking_balance = unlocking_balance
-						.checked_add(&diff)
-						.ok_or(Error::<T>::ArithmeticOverflow)?;
-					last.value =
-						last.value.checked_sub(&diff).ok_or(Error::<T>::ArithmeticUnderflow)?;
-				}
+			ledger.active =
+				ledger.active.checked_sub(&value).ok_or(Error::<T>::ArithmeticUnderflow)?;
 
-				if unlocking_balance >= value {
-					break
+			// Avoid there being a dust balance left in the accounts system.
+			if ledger.active < minimum_balance {
+				value = value.checked_add(&ledger.active).ok_or(Error::<T>::ArithmeticOverflow)?;
+				ledger.active = Zero::zero();
+			}
+
+			let current_block = <frame_system::Pallet<T>>::block_number();
+			// Note: locking for extra block to allow for accounting
+			// block + configurable value - shouldn't overflow
+			let block = current_block + <T as pallet::Config>::UnlockingDelay::get();
+
+			if let Some(chunk) = ledger.unlocking.last_mut().filter(|chunk| chunk.block == block) {
+				// To keep the chunk count down, we only keep one chunk per era. Since
+				// `unlocking` is a FiFo queue, if a chunk exists for `era` we know that it will
+				// be the last one.
+				chunk.value = chunk.value.defensive_saturating_add(value)
+			} else {
+				ledger
+					.unlocking
+					.try_push(UnlockChunk { value, block })
+					.map_err(|_| Error::<T>::NoMoreChunks)?;
+			};
+
+			Ok(value)
+		}
+
+		/// Charge the ledger up to `value`, from the active funds first and then from the funds
+		/// scheduled for unlocking, newest chunk first.
+		///
+		/// Returns the updated ledger, the amount actually charged and the amount charged from
+		/// each unlocking chunk by its unlock block.
+		fn charge_ledger(
+			mut ledger: AccountsLedger<T>,
+			value: BalanceOf<T>,
+		) -> Result<(AccountsLedger<T>, BalanceOf<T>, UnlockingCharges<T>), Error<T>> {
+			let mut charged = ledger.active.min(value);
+			ledger.active =
+				ledger.active.checked_sub(&charged).ok_or(Error::<T>::ArithmeticUnderflow)?;
+
+			let mut unlocking_charges = Vec::new();
+			while charged < value {
+				let chunk = match ledger.unlocking.last_mut() {
+					Some(chunk) => chunk,
+					None => break,
+				};
+				let remaining =
+					value.checked_sub(&charged).ok_or(Error::<T>::ArithmeticUnderflow)?;
+				let chunk_charge = chunk.value.min(remaining);
+				chunk.value = chunk
+					.value
+					.checked_sub(&chunk_charge)
+					.ok_or(Error::<T>::ArithmeticUnderflow)?;
+				unlocking_charges.push((chunk.block, chunk_charge));
+				if chunk.value.is_zero() {
+					ledger.unlocking.pop();
 				}
+				charged =
+					charged.checked_add(&chunk_charge).ok_or(Error::<T>::ArithmeticOverflow)?;
 			}
 
-			Ok((ledger, unlocking_balance))
+			ledger.total =
+				ledger.total.checked_sub(&charged).ok_or(Error::<T>::ArithmeticUnderflow)?;
+
+			Ok((ledger, charged, unlocking_charges))
 		}
 	}
 
 	impl<T: Config> CustomerCharger<T> for Pallet<T> {
 		fn charge_content_owner(
+			bucket_id: Option<BucketId>,
 			content_owner: T::AccountId,
 			billing_vault: T::AccountId,
 			amount: u128,
 		) -> Result<u128, DispatchError> {
-			let actually_charged: BalanceOf<T>;
-			let mut ledger = Self::ledger(&content_owner).ok_or(Error::<T>::NotOwner)?;
 			let amount_to_deduct = amount.saturated_into::<BalanceOf<T>>();
 
-			if ledger.active >= amount_to_deduct {
-				actually_charged = amount_to_deduct;
-				ledger.active = ledger
-					.active
-					.checked_sub(&amount_to_deduct)
-					.ok_or(Error::<T>::ArithmeticUnderflow)?;
-				ledger.total = ledger
-					.total
-					.checked_sub(&amount_to_deduct)
-					.ok_or(Error::<T>::ArithmeticUnderflow)?;
-			} else {
-				let diff = amount_to_deduct
-					.checked_sub(&ledger.active)
+			// Buckets with a budget are isolated from the shared deposit
+			if let Some((bucket_id, mut budget)) =
+				bucket_id.and_then(|id| Self::bucket_budgets(id).map(|budget| (id, budget)))
+			{
+				let actually_charged = budget.allocated.min(amount_to_deduct);
+				budget.allocated = budget
+					.allocated
+					.checked_sub(&actually_charged)
 					.ok_or(Error::<T>::ArithmeticUnderflow)?;
 
-				actually_charged = ledger.active;
-				ledger.total = ledger
-					.total
-					.checked_sub(&ledger.active)
-					.ok_or(Error::<T>::ArithmeticUnderflow)?;
-				ledger.active = BalanceOf::<T>::zero();
+				<T as pallet::Config>::Currency::transfer(
+					&Self::account_id(),
+					&billing_vault,
+					actually_charged,
+					ExistenceRequirement::AllowDeath,
+				)?;
 
-				let (_ledger, charged) = Self::charge_unlocking(ledger, diff)?;
-				ledger = _ledger;
+				<BucketBudgets<T>>::insert(bucket_id, budget); // update state after successful transfer
+				Self::deposit_event(Event::<T>::Charged {
+					owner_id: content_owner,
+					charged: actually_charged,
+					expected_to_charge: amount_to_deduct,
+				});
 
-				actually_charged.checked_add(&charged).ok_or(Error::<T>::ArithmeticUnderflow)?;
+				return Ok(actually_charged.saturated_into::<u128>())
 			}
 
+			let ledger = Self::ledger(&content_owner).ok_or(Error::<T>::NotOwner)?;
+			let (ledger, actually_charged, unlocking_charges) =
+				Self::charge_ledger(ledger, amount_to_deduct)?;
+
 			<T as pallet::Config>::Currency::transfer(
 				&Self::account_id(),
 				&billing_vault,
@@ -666,14 +1895,165 @@ pub mod pallet {
 			)?;
 
 			<Ledger<T>>::insert(&content_owner, &ledger); // update state after successful transfer
+			if !unlocking_charges.is_empty() {
+				Self::deposit_event(Event::<T>::ChargedFromUnlocking {
+					owner_id: content_owner.clone(),
+					asset_id: None,
+					chunks: unlocking_charges,
+				});
+			}
 			Self::deposit_event(Event::<T>::Charged {
+				owner_id: content_owner.clone(),
+				charged: actually_charged,
+				expected_to_charge: amount_to_deduct,
+			});
+
+			Self::top_up(&content_owner, ledger.active);
+
+			Ok(actually_charged.saturated_into::<u128>())
+		}
+
+		fn get_payment_source(
+			bucket_id: Option<BucketId>,
+			content_owner: &T::AccountId,
+		) -> PaymentSource {
+			// Buckets with a budget are charged from their allocation
+			match bucket_id.filter(|id| <BucketBudgets<T>>::contains_key(id)) {
+				Some(bucket_id) => PaymentSource::BucketBudget(bucket_id),
+				None => Self::payment_asset(content_owner)
+					.map_or(PaymentSource::Deposit, PaymentSource::Asset),
+			}
+		}
+
+		fn charge_content_owner_asset(
+			content_owner: T::AccountId,
+			payee: T::AccountId,
+			asset_id: AssetId,
+			amount: u128,
+		) -> Result<u128, DispatchError> {
+			let amount_to_deduct = amount.saturated_into::<BalanceOf<T>>();
+			let ledger =
+				Self::asset_ledger(&content_owner, asset_id).ok_or(Error::<T>::NoAssetDeposit)?;
+			let (ledger, actually_charged, unlocking_charges) =
+				Self::charge_ledger(ledger, amount_to_deduct)?;
+
+			<T::Assets as fungibles::Mutate<T::AccountId>>::transfer(
+				asset_id,
+				&Self::account_id(),
+				&payee,
+				actually_charged,
+				Preservation::Expendable,
+			)?;
+
+			<AssetLedgers<T>>::insert(&content_owner, asset_id, &ledger); // update state after successful transfer
+			if !unlocking_charges.is_empty() {
+				Self::deposit_event(Event::<T>::ChargedFromUnlocking {
+					owner_id: content_owner.clone(),
+					asset_id: Some(asset_id),
+					chunks: unlocking_charges,
+				});
+			}
+			Self::deposit_event(Event::<T>::AssetCharged {
 				owner_id: content_owner,
+				asset_id,
 				charged: actually_charged,
 				expected_to_charge: amount_to_deduct,
 			});
 
 			Ok(actually_charged.saturated_into::<u128>())
 		}
+
+		fn refund_content_owner(
+			source: PaymentSource,
+			content_owner: T::AccountId,
+			payer: T::AccountId,
+			amount: u128,
+		) -> DispatchResult {
+			let amount_to_refund = amount.saturated_into::<BalanceOf<T>>();
+
+			match source {
+				PaymentSource::Deposit => {
+					let mut ledger = Self::ledger(&content_owner).ok_or(Error::<T>::NotOwner)?;
+					ledger.total = ledger
+						.total
+						.checked_add(&amount_to_refund)
+						.ok_or(Error::<T>::ArithmeticOverflow)?;
+					ledger.active = ledger
+						.active
+						.checked_add(&amount_to_refund)
+						.ok_or(Error::<T>::ArithmeticOverflow)?;
+
+					<T as pallet::Config>::Currency::transfer(
+						&payer,
+						&Self::account_id(),
+						amount_to_refund,
+						ExistenceRequirement::AllowDeath,
+					)?;
+
+					<Ledger<T>>::insert(&content_owner, &ledger); // update state after successful transfer
+				},
+				PaymentSource::BucketBudget(bucket_id) => {
+					let mut budget =
+						Self::bucket_budgets(bucket_id).ok_or(Error::<T>::NoBucketBudget)?;
+					budget.allocated = budget
+						.allocated
+						.checked_add(&amount_to_refund)
+						.ok_or(Error::<T>::ArithmeticOverflow)?;
+
+					<T as pallet::Config>::Currency::transfer(
+						&payer,
+						&Self::account_id(),
+						amount_to_refund,
+						ExistenceRequirement::AllowDeath,
+					)?;
+
+					<BucketBudgets<T>>::insert(bucket_id, budget); // update state after successful transfer
+				},
+				PaymentSource::Asset(asset_id) =>
+					match Self::asset_ledger(&content_owner, asset_id) {
+						Some(mut ledger) => {
+							ledger.total = ledger
+								.total
+								.checked_add(&amount_to_refund)
+								.ok_or(Error::<T>::ArithmeticOverflow)?;
+							ledger.active = ledger
+								.active
+								.checked_add(&amount_to_refund)
+								.ok_or(Error::<T>::ArithmeticOverflow)?;
+
+							<T::Assets as fungibles::Mutate<T::AccountId>>::transfer(
+								asset_id,
+								&payer,
+								&Self::account_id(),
+								amount_to_refund,
+								Preservation::Expendable,
+							)?;
+
+							// update state after successful transfer
+							<AssetLedgers<T>>::insert(&content_owner, asset_id, &ledger);
+						},
+						// the deposit of the asset has been withdrawn, the owner is refunded
+						// directly
+						None => {
+							<T::Assets as fungibles::Mutate<T::AccountId>>::transfer(
+								asset_id,
+								&payer,
+								&content_owner,
+								amount_to_refund,
+								Preservation::Expendable,
+							)?;
+						},
+					},
+			}
+
+			Self::deposit_event(Event::<T>::Refunded {
+				owner_id: content_owner,
+				source,
+				amount: amount_to_refund,
+			});
+
+			Ok(())
+		}
 	}
 
 	impl<T: Config> CustomerDepositor<T> for Pallet<T> {
@@ -700,7 +2080,7 @@ pub mod pallet {
 				unlocking: Default::default(),
 			};
 
-			Self::update_ledger_and_deposit(&owner, &item)
+			Self::update_ledger_and_deposit(&owner, &item, value)
 				.map_err(|_| Error::<T>::TransferFailed)?;
 			Self::deposit_event(Event::<T>::Deposited { owner_id: owner, amount: value });
 
@@ -724,11 +2104,45 @@ pub mod pallet {
 				Error::<T>::InsufficientDeposit
 			);
 
-			Self::update_ledger_and_deposit(&owner, &ledger)
+			Self::update_ledger_and_deposit(&owner, &ledger, extra)
 				.map_err(|_| Error::<T>::TransferFailed)?;
 			Self::deposit_event(Event::<T>::Deposited { owner_id: owner, amount: extra });
 
 			Ok(())
 		}
 	}
+
+	impl<T: Config> BucketVisitor<T> for Pallet<T> {
+		fn ensure_bucket_owner(
+			cluster_id: &ClusterId,
+			bucket_id: BucketId,
+			owner_id: &T::AccountId,
+		) -> Result<(), BucketVisitorError> {
+			let bucket = Self::buckets(bucket_id).ok_or(BucketVisitorError::NoBucketWithId)?;
+			ensure!(bucket.owner_id == *owner_id, BucketVisitorError::NotBucketOwner);
+			ensure!(bucket.cluster_id == *cluster_id, BucketVisitorError::IncorrectClusterId);
+
+			Ok(())
+		}
+
+		fn get_bucket_metadata(bucket_id: BucketId) -> Result<BucketMetadata, BucketVisitorError> {
+			let bucket = Self::buckets(bucket_id).ok_or(BucketVisitorError::NoBucketWithId)?;
+
+			Ok(bucket.metadata)
+		}
+
+		#[cfg(feature = "runtime-benchmarks")]
+		fn create_bucket(
+			cluster_id: &ClusterId,
+			owner_id: T::AccountId,
+		) -> Result<BucketId, DispatchError> {
+			Self::create_bucket(
+				frame_system::RawOrigin::Signed(owner_id).into(),
+				*cluster_id,
+				BucketParams { is_public: false, budget: None, metadata: Default::default() },
+			)?;
+
+			Ok(Self::buckets_count())
+		}
+	}
 }
@@ -1,23 +1,27 @@
 //! Test utilities
 
 use ddc_primitives::{
-	traits::cluster::{
-		ClusterCreator, ClusterManager, ClusterManagerError, ClusterVisitor, ClusterVisitorError,
+	traits::{
+		cluster::{
+			ClusterCreator, ClusterManager, ClusterManagerError, ClusterVisitor,
+			ClusterVisitorError,
+		},
+		payout::PayoutVisitor,
 	},
-	ClusterBondingParams, ClusterFeesParams, ClusterGovParams, ClusterId, ClusterParams,
-	ClusterPricingParams, NodePubKey, NodeType,
+	AssetId, ClusterBondingParams, ClusterCapabilities, ClusterFeesParams, ClusterGovParams,
+	ClusterId, ClusterParams, ClusterPricingParams, DdcEra, NodePubKey, NodeType,
 };
 use frame_support::{
 	construct_runtime, parameter_types,
-	traits::{ConstU32, ConstU64, Everything},
+	traits::{AsEnsureOriginWithArg, ConstU128, ConstU32, ConstU64, Everything},
 	weights::constants::RocksDbWeight,
 };
-use frame_system::mocking::MockBlock;
+use frame_system::{mocking::MockBlock, EnsureRoot, EnsureSigned};
 use sp_core::H256;
 use sp_io::TestExternalities;
 use sp_runtime::{
 	traits::{BlakeTwo256, IdentityLookup},
-	BuildStorage, DispatchResult, Perquintill,
+	BuildStorage, DispatchResult, FixedU128, Perquintill,
 };
 
 use crate::{self as pallet_ddc_customers, *};
@@ -35,6 +39,7 @@ construct_runtime!(
 		System: frame_system::{Pallet, Call, Config<T>, Storage, Event<T>},
 		Timestamp: pallet_timestamp::{Pallet, Call, Storage, Inherent},
 		Balances: pallet_balances::{Pallet, Call, Storage, Config<T>, Event<T>},
+		Assets: pallet_assets::{Pallet, Call, Storage, Config<T>, Event<T>},
 		DdcCustomers: pallet_ddc_customers::{Pallet, Call, Storage, Config<T>, Event<T>},
 	}
 );
@@ -85,6 +90,29 @@ impl pallet_balances::Config for Test {
 	type RuntimeHoldReason = ();
 }
 
+impl pallet_assets::Config for Test {
+	type RuntimeEvent = RuntimeEvent;
+	type Balance = Balance;
+	type RemoveItemsLimit = ConstU32<1000>;
+	type AssetId = AssetId;
+	type AssetIdParameter = codec::Compact<AssetId>;
+	type Currency = Balances;
+	type CreateOrigin = AsEnsureOriginWithArg<EnsureSigned<AccountId>>;
+	type ForceOrigin = EnsureRoot<AccountId>;
+	type AssetDeposit = ConstU128<1>;
+	type AssetAccountDeposit = ConstU128<1>;
+	type MetadataDepositBase = ConstU128<1>;
+	type MetadataDepositPerByte = ConstU128<1>;
+	type ApprovalDeposit = ConstU128<1>;
+	type StringLimit = ConstU32<50>;
+	type Freezer = ();
+	type Extra = ();
+	type CallbackHandle = ();
+	type WeightInfo = ();
+	#[cfg(feature = "runtime-benchmarks")]
+	type BenchmarkHelper = ();
+}
+
 impl pallet_timestamp::Config for Test {
 	type Moment = u64;
 	type OnTimestampSet = ();
@@ -95,6 +123,14 @@ impl pallet_timestamp::Config for Test {
 parameter_types! {
 	pub const DdcCustomersPalletId: PalletId = PalletId(*b"accounts"); // DDC maintainer's stake
 	pub const UnlockingDelay: BlockNumber = 10u64; // 10 blocks for test
+	pub static CustomerDebt: u128 = 0;
+	pub static BucketDebt: u128 = 0;
+	pub static OpenBillingClusters: Vec<ClusterId> = Vec::new();
+	pub static LastFinalizedEra: Option<DdcEra> = None;
+	pub static BucketDeposit: Balance = 0;
+	pub const BucketGracePeriod: BlockNumber = 10u64; // 10 blocks for test
+	pub static TestCapabilities: ClusterCapabilities = ClusterCapabilities::default();
+	pub const StableAssetId: AssetId = 1;
 }
 
 impl crate::pallet::Config for Test {
@@ -104,6 +140,10 @@ impl crate::pallet::Config for Test {
 	type RuntimeEvent = RuntimeEvent;
 	type ClusterVisitor = TestClusterVisitor;
 	type ClusterCreator = TestClusterCreator;
+	type PayoutVisitor = TestPayoutVisitor;
+	type BucketDeposit = BucketDeposit;
+	type BucketGracePeriod = BucketGracePeriod;
+	type Assets = Assets;
 	type WeightInfo = ();
 }
 
@@ -139,6 +179,8 @@ impl<T: Config> ClusterVisitor<T> for TestClusterVisitor {
 			unit_per_mb_streamed: 2,
 			unit_per_put_request: 3,
 			unit_per_get_request: 4,
+			billing_period: 2_630_016,
+			pricing_tiers: Default::default(),
 		})
 	}
 
@@ -150,6 +192,20 @@ impl<T: Config> ClusterVisitor<T> for TestClusterVisitor {
 		})
 	}
 
+	fn get_customer_discount(_cluster_id: &ClusterId, _customer_id: &T::AccountId) -> Perquintill {
+		Perquintill::zero()
+	}
+
+	fn get_capabilities(
+		_cluster_id: &ClusterId,
+	) -> Result<ClusterCapabilities, ClusterVisitorError> {
+		Ok(TestCapabilities::get())
+	}
+
+	fn get_asset_rate(_cluster_id: &ClusterId, _asset_id: AssetId) -> Option<FixedU128> {
+		None
+	}
+
 	fn get_reserve_account_id(
 		_cluster_id: &ClusterId,
 	) -> Result<T::AccountId, ClusterVisitorError> {
@@ -213,6 +269,34 @@ impl<T: Config> ClusterCreator<T, Balance> for TestClusterCreator {
 	}
 }
 
+pub struct TestPayoutVisitor;
+impl<T: Config> PayoutVisitor<T> for TestPayoutVisitor {
+	fn get_customer_debt(_cluster_id: &ClusterId, _customer_id: &T::AccountId) -> u128 {
+		CustomerDebt::get()
+	}
+
+	fn get_bucket_debt(_cluster_id: &ClusterId, _bucket_id: BucketId) -> u128 {
+		BucketDebt::get()
+	}
+
+	fn get_buckets_debt(_cluster_id: &ClusterId, _owner_id: &T::AccountId) -> u128 {
+		BucketDebt::get()
+	}
+
+	fn has_open_billing_report(cluster_id: &ClusterId) -> bool {
+		OpenBillingClusters::get().contains(cluster_id)
+	}
+
+	fn get_last_finalized_era(_cluster_id: &ClusterId) -> Option<DdcEra> {
+		LastFinalizedEra::get()
+	}
+
+	#[cfg(feature = "runtime-benchmarks")]
+	fn set_last_finalized_era(_cluster_id: &ClusterId, era: DdcEra) {
+		LastFinalizedEra::set(Some(era));
+	}
+}
+
 pub struct ExtBuilder;
 
 impl ExtBuilder {
@@ -226,6 +310,13 @@ impl ExtBuilder {
 		}
 		.assimilate_storage(&mut storage);
 
+		let _assets_genesis = pallet_assets::GenesisConfig::<Test> {
+			assets: vec![(StableAssetId::get(), 1, true, 1)],
+			metadata: Default::default(),
+			accounts: vec![(StableAssetId::get(), 3, 1000)],
+		}
+		.assimilate_storage(&mut storage);
+
 		let _customer_genesis = pallet_ddc_customers::GenesisConfig::<Test> {
 			feeder_account: None,
 			buckets: Default::default(),
@@ -1,7 +1,12 @@
 //! Tests for the module.
 
-use ddc_primitives::ClusterId;
-use frame_support::{assert_noop, assert_ok};
+use ddc_primitives::{BucketMetadata, ClusterCapabilities, ClusterId, Region, StorageClass};
+use frame_support::{assert_noop, assert_ok, traits::Hooks, weights::Weight};
+use rand_chacha::{
+	rand_core::{RngCore, SeedableRng},
+	ChaCha8Rng,
+};
+use sp_runtime::{DispatchError, Perquintill};
 
 use super::{mock::*, *};
 
@@ -12,7 +17,8 @@ fn create_bucket_works() {
 
 		let cluster_id = ClusterId::from([1; 20]);
 		let account_1 = 1;
-		let bucket_params = BucketParams { is_public: false };
+		let bucket_params =
+			BucketParams { is_public: false, budget: None, metadata: Default::default() };
 
 		// Bucket created
 		assert_ok!(DdcCustomers::create_bucket(
@@ -31,6 +37,7 @@ fn create_bucket_works() {
 				cluster_id,
 				is_public: bucket_params.is_public,
 				is_removed: false,
+				metadata: Default::default(),
 			})
 		);
 
@@ -40,6 +47,100 @@ fn create_bucket_works() {
 	})
 }
 
+#[test]
+fn ensure_bucket_owner_works() {
+	ExtBuilder.build_and_execute(|| {
+		System::set_block_number(1);
+
+		let cluster_id = ClusterId::from([1; 20]);
+		let account_1 = 1;
+		let account_2 = 2;
+
+		assert_ok!(DdcCustomers::create_bucket(
+			RuntimeOrigin::signed(account_1),
+			cluster_id,
+			BucketParams { is_public: false, budget: None, metadata: Default::default() }
+		));
+
+		assert_ok!(<DdcCustomers as BucketVisitor<Test>>::ensure_bucket_owner(
+			&cluster_id,
+			1,
+			&account_1
+		));
+		assert_eq!(
+			<DdcCustomers as BucketVisitor<Test>>::ensure_bucket_owner(&cluster_id, 2, &account_1),
+			Err(BucketVisitorError::NoBucketWithId)
+		);
+		assert_eq!(
+			<DdcCustomers as BucketVisitor<Test>>::ensure_bucket_owner(&cluster_id, 1, &account_2),
+			Err(BucketVisitorError::NotBucketOwner)
+		);
+		assert_eq!(
+			<DdcCustomers as BucketVisitor<Test>>::ensure_bucket_owner(
+				&ClusterId::from([2; 20]),
+				1,
+				&account_1
+			),
+			Err(BucketVisitorError::IncorrectClusterId)
+		);
+	})
+}
+
+#[test]
+fn create_bucket_fails_for_indebted_customer() {
+	ExtBuilder.build_and_execute(|| {
+		System::set_block_number(1);
+
+		let cluster_id = ClusterId::from([1; 20]);
+		let account_1 = 1;
+		let bucket_params =
+			BucketParams { is_public: false, budget: None, metadata: Default::default() };
+		CustomerDebt::set(50);
+
+		// Debt is not covered by the deposit
+		assert_noop!(
+			DdcCustomers::create_bucket(
+				RuntimeOrigin::signed(account_1),
+				cluster_id,
+				bucket_params.clone()
+			),
+			Error::<Test>::CustomerIndebted
+		);
+
+		assert_ok!(DdcCustomers::deposit(RuntimeOrigin::signed(account_1), 40_u128));
+		assert_noop!(
+			DdcCustomers::create_bucket(
+				RuntimeOrigin::signed(account_1),
+				cluster_id,
+				bucket_params.clone()
+			),
+			Error::<Test>::CustomerIndebted
+		);
+
+		// Topped up deposit covers the debt
+		assert_ok!(DdcCustomers::deposit_extra(RuntimeOrigin::signed(account_1), 10_u128));
+
+		// The debts of the buckets charged from their allocation are not covered by the deposit
+		BucketDebt::set(10);
+		assert_noop!(
+			DdcCustomers::create_bucket(
+				RuntimeOrigin::signed(account_1),
+				cluster_id,
+				bucket_params.clone()
+			),
+			Error::<Test>::CustomerIndebted
+		);
+		BucketDebt::set(0);
+
+		assert_ok!(DdcCustomers::create_bucket(
+			RuntimeOrigin::signed(account_1),
+			cluster_id,
+			bucket_params
+		));
+		assert_eq!(DdcCustomers::buckets_count(), 1);
+	})
+}
+
 #[test]
 fn create_two_buckets_works() {
 	ExtBuilder.build_and_execute(|| {
@@ -47,8 +148,10 @@ fn create_two_buckets_works() {
 
 		let cluster_id = ClusterId::from([1; 20]);
 		let account_1 = 1;
-		let bucket_1_params = BucketParams { is_public: false };
-		let bucket_2_params = BucketParams { is_public: true };
+		let bucket_1_params =
+			BucketParams { is_public: false, budget: None, metadata: Default::default() };
+		let bucket_2_params =
+			BucketParams { is_public: true, budget: None, metadata: Default::default() };
 
 		// Buckets created
 		assert_ok!(DdcCustomers::create_bucket(
@@ -76,6 +179,7 @@ fn create_two_buckets_works() {
 				cluster_id,
 				is_public: bucket_1_params.is_public,
 				is_removed: false,
+				metadata: Default::default(),
 			})
 		);
 		assert_eq!(
@@ -86,6 +190,7 @@ fn create_two_buckets_works() {
 				cluster_id,
 				is_public: bucket_2_params.is_public,
 				is_removed: false,
+				metadata: Default::default(),
 			})
 		);
 	})
@@ -156,6 +261,9 @@ fn deposit_and_deposit_extra_works() {
 			})
 		);
 
+		// Only the extra amount is deposited
+		assert_eq!(Balances::free_balance(account_1), 100 - amount1 - amount2);
+
 		// Checking that event was emitted
 		System::assert_last_event(Event::Deposited { owner_id: account_1, amount: amount2 }.into());
 	})
@@ -195,7 +303,7 @@ fn charge_content_owner_works() {
 
 		// successful transfer
 		let charge1 = 10;
-		let charged = DdcCustomers::charge_content_owner(account_3, vault, charge1).unwrap();
+		let charged = DdcCustomers::charge_content_owner(None, account_3, vault, charge1).unwrap();
 		assert_eq!(charge1, charged);
 
 		let vault_balance = Balances::free_balance(vault);
@@ -225,7 +333,8 @@ fn charge_content_owner_works() {
 
 		// failed transfer
 		let charge2 = 100u128;
-		let charge_result = DdcCustomers::charge_content_owner(account_3, vault, charge2).unwrap();
+		let charge_result =
+			DdcCustomers::charge_content_owner(None, account_3, vault, charge2).unwrap();
 		assert_eq!(
 			DdcCustomers::ledger(account_3),
 			Some(AccountsLedger {
@@ -270,6 +379,127 @@ fn charge_content_owner_works() {
 	})
 }
 
+#[test]
+fn charge_content_owner_charges_unlocking_newest_first() {
+	ExtBuilder.build_and_execute(|| {
+		System::set_block_number(1);
+
+		let owner: u128 = 3;
+		let vault: u128 = 4;
+
+		assert_ok!(DdcCustomers::deposit(RuntimeOrigin::signed(owner), 100));
+		assert_ok!(DdcCustomers::unlock_deposit(RuntimeOrigin::signed(owner), 20));
+		System::set_block_number(2);
+		assert_ok!(DdcCustomers::unlock_deposit(RuntimeOrigin::signed(owner), 30));
+
+		// Active funds first, then the newest chunk
+		assert_eq!(DdcCustomers::charge_content_owner(None, owner, vault, 70), Ok(70));
+		System::assert_has_event(
+			Event::ChargedFromUnlocking { owner_id: owner, asset_id: None, chunks: vec![(12, 20)] }
+				.into(),
+		);
+		System::assert_last_event(
+			Event::Charged { owner_id: owner, charged: 70, expected_to_charge: 70 }.into(),
+		);
+		assert_eq!(
+			DdcCustomers::ledger(owner),
+			Some(AccountsLedger {
+				owner,
+				total: 30,
+				active: 0,
+				unlocking: BoundedVec::try_from(vec![
+					UnlockChunk { value: 20, block: 11 },
+					UnlockChunk { value: 10, block: 12 },
+				])
+				.unwrap(),
+			})
+		);
+
+		// Exhausted chunks are removed
+		assert_eq!(DdcCustomers::charge_content_owner(None, owner, vault, 25), Ok(25));
+		System::assert_has_event(
+			Event::ChargedFromUnlocking {
+				owner_id: owner,
+				asset_id: None,
+				chunks: vec![(12, 10), (11, 15)],
+			}
+			.into(),
+		);
+		assert_eq!(
+			DdcCustomers::ledger(owner),
+			Some(AccountsLedger {
+				owner,
+				total: 5,
+				active: 0,
+				unlocking: BoundedVec::try_from(vec![UnlockChunk { value: 5, block: 11 }]).unwrap(),
+			})
+		);
+
+		assert_eq!(DdcCustomers::charge_content_owner(None, owner, vault, 10), Ok(5));
+		System::assert_last_event(
+			Event::Charged { owner_id: owner, charged: 5, expected_to_charge: 10 }.into(),
+		);
+		assert_eq!(Balances::free_balance(vault), 100);
+		assert_eq!(
+			DdcCustomers::ledger(owner),
+			Some(AccountsLedger { owner, total: 0, active: 0, unlocking: Default::default() })
+		);
+	})
+}
+
+#[test]
+fn charging_keeps_ledger_total_consistent() {
+	for seed in 0..50 {
+		ExtBuilder.build_and_execute(|| {
+			let mut rng = ChaCha8Rng::seed_from_u64(seed);
+			let owner: u128 = 3;
+			let vault: u128 = 4;
+			let mut block = 1;
+			let mut charged_total = 0;
+			System::set_block_number(block);
+
+			assert_ok!(DdcCustomers::deposit(RuntimeOrigin::signed(owner), 100));
+			for _ in 0..40 {
+				let value = (rng.next_u64() % 60) as u128;
+				match rng.next_u64() % 4 {
+					0 => {
+						let _ = DdcCustomers::deposit_extra(RuntimeOrigin::signed(owner), value);
+					},
+					1 => {
+						let _ = DdcCustomers::unlock_deposit(RuntimeOrigin::signed(owner), value);
+					},
+					2 => {
+						let ledger = DdcCustomers::ledger(owner).unwrap();
+						let charged =
+							DdcCustomers::charge_content_owner(None, owner, vault, value).unwrap();
+						assert_eq!(charged, value.min(ledger.total));
+						charged_total += charged;
+					},
+					_ => {
+						block += rng.next_u64() % 8;
+						System::set_block_number(block);
+						let _ =
+							DdcCustomers::withdraw_unlocked_deposit(RuntimeOrigin::signed(owner));
+					},
+				}
+
+				let ledger = match DdcCustomers::ledger(owner) {
+					Some(ledger) => ledger,
+					None => break,
+				};
+				let unlocking: u128 = ledger.unlocking.iter().map(|chunk| chunk.value).sum();
+				assert_eq!(ledger.total, ledger.active + unlocking);
+				assert!(ledger.unlocking.iter().all(|chunk| !chunk.value.is_zero()));
+				assert_eq!(
+					Balances::free_balance(DdcCustomers::account_id()),
+					Balances::minimum_balance() + ledger.total
+				);
+			}
+			assert_eq!(Balances::free_balance(vault), charged_total);
+		});
+	}
+}
+
 #[test]
 fn unlock_and_withdraw_deposit_works() {
 	ExtBuilder.build_and_execute(|| {
@@ -343,7 +573,8 @@ fn set_bucket_params_works() {
 
 		let cluster_id = ClusterId::from([1; 20]);
 		let bucket_owner = 1;
-		let bucket_params = BucketParams { is_public: false };
+		let bucket_params =
+			BucketParams { is_public: false, budget: None, metadata: Default::default() };
 
 		// Bucket created
 		assert_ok!(DdcCustomers::create_bucket(
@@ -357,7 +588,8 @@ fn set_bucket_params_works() {
 		System::assert_last_event(Event::BucketCreated { bucket_id: 1u64 }.into());
 
 		let bucket_id = 1;
-		let update_bucket_params = BucketParams { is_public: true };
+		let update_bucket_params =
+			BucketParams { is_public: true, budget: None, metadata: Default::default() };
 		assert_ok!(DdcCustomers::set_bucket_params(
 			RuntimeOrigin::signed(bucket_owner),
 			bucket_id,
@@ -373,6 +605,7 @@ fn set_bucket_params_works() {
 				cluster_id,
 				is_public: update_bucket_params.is_public,
 				is_removed: false,
+				metadata: Default::default(),
 			})
 		);
 
@@ -389,7 +622,8 @@ fn set_bucket_params_checks_work() {
 
 		let cluster_id = ClusterId::from([1; 20]);
 		let bucket_owner = 1;
-		let bucket_params = BucketParams { is_public: false };
+		let bucket_params =
+			BucketParams { is_public: false, budget: None, metadata: Default::default() };
 
 		// Bucket created
 		assert_ok!(DdcCustomers::create_bucket(
@@ -408,7 +642,7 @@ fn set_bucket_params_checks_work() {
 			DdcCustomers::set_bucket_params(
 				RuntimeOrigin::signed(bucket_owner),
 				non_existent_bucket_id,
-				BucketParams { is_public: true }
+				BucketParams { is_public: true, budget: None, metadata: Default::default() }
 			),
 			Error::<Test>::NoBucketWithId
 		);
@@ -418,7 +652,7 @@ fn set_bucket_params_checks_work() {
 			DdcCustomers::set_bucket_params(
 				RuntimeOrigin::signed(not_bucket_owner_id),
 				bucket_id,
-				BucketParams { is_public: true }
+				BucketParams { is_public: true, budget: None, metadata: Default::default() }
 			),
 			Error::<Test>::NotBucketOwner
 		);
@@ -435,7 +669,8 @@ fn remove_bucket_works() {
 		let account_2 = 2;
 		let bucket_id_1 = 1;
 		let bucket_id_2 = 2;
-		let bucket_params = BucketParams { is_public: false };
+		let bucket_params =
+			BucketParams { is_public: false, budget: None, metadata: Default::default() };
 
 		// Bucket created
 		assert_ok!(DdcCustomers::create_bucket(
@@ -466,6 +701,7 @@ fn remove_bucket_works() {
 				cluster_id,
 				is_public: bucket_params.is_public,
 				is_removed: false,
+				metadata: Default::default(),
 			})
 		);
 
@@ -496,3 +732,1185 @@ fn remove_bucket_works() {
 		);
 	})
 }
+
+#[test]
+fn bucket_budget_isolates_charges() {
+	ExtBuilder.build_and_execute(|| {
+		System::set_block_number(1);
+
+		let cluster_id = ClusterId::from([1; 20]);
+		let account_3: u128 = 3;
+		let vault: u128 = 4;
+		let deposit = 100_u128;
+		let budgeted_bucket_id = 1;
+		let shared_bucket_id = 2;
+
+		assert_ok!(DdcCustomers::deposit(RuntimeOrigin::signed(account_3), deposit));
+		assert_ok!(DdcCustomers::create_bucket(
+			RuntimeOrigin::signed(account_3),
+			cluster_id,
+			BucketParams { is_public: false, budget: Some(30), metadata: Default::default() }
+		));
+		assert_ok!(DdcCustomers::create_bucket(
+			RuntimeOrigin::signed(account_3),
+			cluster_id,
+			BucketParams { is_public: false, budget: None, metadata: Default::default() }
+		));
+
+		// Only buckets with a budget accept allocations, up to the budget
+		assert_noop!(
+			DdcCustomers::allocate_to_bucket(
+				RuntimeOrigin::signed(account_3),
+				shared_bucket_id,
+				10
+			),
+			Error::<Test>::NoBucketBudget
+		);
+		assert_noop!(
+			DdcCustomers::allocate_to_bucket(
+				RuntimeOrigin::signed(account_3),
+				budgeted_bucket_id,
+				31
+			),
+			Error::<Test>::BucketBudgetExceeded
+		);
+		assert_ok!(DdcCustomers::allocate_to_bucket(
+			RuntimeOrigin::signed(account_3),
+			budgeted_bucket_id,
+			30
+		));
+		System::assert_last_event(
+			Event::BucketAllocated {
+				bucket_id: budgeted_bucket_id,
+				owner_id: account_3,
+				amount: 30,
+			}
+			.into(),
+		);
+		assert_eq!(
+			DdcCustomers::bucket_budgets(budgeted_bucket_id),
+			Some(BucketBudget { limit: 30, allocated: 30 })
+		);
+		assert_eq!(DdcCustomers::ledger(account_3).unwrap().active, deposit - 30);
+
+		// The budget can not be lowered below the allocation
+		assert_noop!(
+			DdcCustomers::set_bucket_params(
+				RuntimeOrigin::signed(account_3),
+				budgeted_bucket_id,
+				BucketParams { is_public: false, budget: Some(20), metadata: Default::default() }
+			),
+			Error::<Test>::BucketBudgetExceeded
+		);
+
+		// Charging the budgeted bucket stops at its allocation
+		let charged =
+			DdcCustomers::charge_content_owner(Some(budgeted_bucket_id), account_3, vault, 50)
+				.unwrap();
+		assert_eq!(charged, 30);
+		assert_eq!(Balances::free_balance(vault), 30);
+		assert_eq!(
+			DdcCustomers::bucket_budgets(budgeted_bucket_id),
+			Some(BucketBudget { limit: 30, allocated: 0 })
+		);
+		assert_eq!(DdcCustomers::ledger(account_3).unwrap().active, deposit - 30);
+
+		// Charging the shared bucket can not touch the budgeted allocation
+		assert_ok!(DdcCustomers::allocate_to_bucket(
+			RuntimeOrigin::signed(account_3),
+			budgeted_bucket_id,
+			20
+		));
+		let charged =
+			DdcCustomers::charge_content_owner(Some(shared_bucket_id), account_3, vault, 100)
+				.unwrap();
+		assert_eq!(charged, deposit - 50);
+		assert_eq!(DdcCustomers::ledger(account_3).unwrap().active, 0);
+		assert_eq!(
+			DdcCustomers::bucket_budgets(budgeted_bucket_id),
+			Some(BucketBudget { limit: 30, allocated: 20 })
+		);
+
+		// The allocation can be returned to the shared deposit
+		assert_ok!(DdcCustomers::release_bucket_allocation(
+			RuntimeOrigin::signed(account_3),
+			budgeted_bucket_id,
+			50
+		));
+		System::assert_last_event(
+			Event::BucketAllocationReleased {
+				bucket_id: budgeted_bucket_id,
+				owner_id: account_3,
+				amount: 20,
+			}
+			.into(),
+		);
+		assert_eq!(DdcCustomers::ledger(account_3).unwrap().active, 20);
+		assert_eq!(DdcCustomers::ledger(account_3).unwrap().total, 20);
+
+		// Removing the budget makes the bucket charge the shared deposit again
+		assert_ok!(DdcCustomers::set_bucket_params(
+			RuntimeOrigin::signed(account_3),
+			budgeted_bucket_id,
+			BucketParams { is_public: false, budget: None, metadata: Default::default() }
+		));
+		assert_eq!(DdcCustomers::bucket_budgets(budgeted_bucket_id), None);
+		let charged =
+			DdcCustomers::charge_content_owner(Some(budgeted_bucket_id), account_3, vault, 5)
+				.unwrap();
+		assert_eq!(charged, 5);
+		assert_eq!(DdcCustomers::ledger(account_3).unwrap().active, 15);
+	})
+}
+
+#[test]
+fn allocate_to_bucket_leaves_no_dust() {
+	ExtBuilder.build_and_execute(|| {
+		System::set_block_number(1);
+		ExistentialDeposit::set(10);
+
+		let cluster_id = ClusterId::from([1; 20]);
+		let account_3: u128 = 3;
+		let deposit = 100_u128;
+		let bucket_id = 1;
+
+		assert_ok!(DdcCustomers::deposit(RuntimeOrigin::signed(account_3), deposit));
+		assert_ok!(DdcCustomers::create_bucket(
+			RuntimeOrigin::signed(account_3),
+			cluster_id,
+			BucketParams { is_public: false, budget: Some(deposit), metadata: Default::default() }
+		));
+
+		// The rest of the active deposit below the minimum balance is allocated as well
+		assert_ok!(DdcCustomers::allocate_to_bucket(
+			RuntimeOrigin::signed(account_3),
+			bucket_id,
+			95
+		));
+		System::assert_last_event(
+			Event::BucketAllocated { bucket_id, owner_id: account_3, amount: deposit }.into(),
+		);
+		assert_eq!(
+			DdcCustomers::bucket_budgets(bucket_id),
+			Some(BucketBudget { limit: deposit, allocated: deposit })
+		);
+		assert_eq!(DdcCustomers::ledger(account_3).unwrap().active, 0);
+	})
+}
+
+#[test]
+fn bucket_grants_are_bounded() {
+	ExtBuilder.build_and_execute(|| {
+		System::set_block_number(1);
+
+		let cluster_id = ClusterId::from([1; 20]);
+		let owner: u128 = 3;
+		let bucket_id = 1;
+		let expires_at = 10;
+
+		assert_ok!(DdcCustomers::create_bucket(
+			RuntimeOrigin::signed(owner),
+			cluster_id,
+			BucketParams { is_public: false, budget: None }
+		));
+
+		for grantee in 0..MaxBucketGrants::get() {
+			assert_ok!(DdcCustomers::grant_bucket_access(
+				RuntimeOrigin::signed(owner),
+				bucket_id,
+				100 + grantee as u128,
+				BucketPermission::Read,
+				expires_at,
+				0
+			));
+		}
+		assert_eq!(DdcCustomers::bucket_grants_count(bucket_id), MaxBucketGrants::get());
+
+		assert_noop!(
+			DdcCustomers::grant_bucket_access(
+				RuntimeOrigin::signed(owner),
+				bucket_id,
+				99,
+				BucketPermission::Read,
+				expires_at,
+				0
+			),
+			Error::<Test>::TooManyGrants
+		);
+
+		// Updating an existing grant does not take a new slot
+		assert_ok!(DdcCustomers::grant_bucket_access(
+			RuntimeOrigin::signed(owner),
+			bucket_id,
+			100,
+			BucketPermission::Write,
+			expires_at,
+			5
+		));
+		assert_eq!(DdcCustomers::bucket_grants_count(bucket_id), MaxBucketGrants::get());
+
+		assert_ok!(DdcCustomers::revoke_bucket_access(
+			RuntimeOrigin::signed(owner),
+			bucket_id,
+			100
+		));
+		assert_eq!(DdcCustomers::bucket_grants_count(bucket_id), MaxBucketGrants::get() - 1);
+		assert_ok!(DdcCustomers::grant_bucket_access(
+			RuntimeOrigin::signed(owner),
+			bucket_id,
+			99,
+			BucketPermission::Read,
+			expires_at,
+			0
+		));
+	})
+}
+
+#[test]
+fn bucket_access_grants_work() {
+	ExtBuilder.build_and_execute(|| {
+		System::set_block_number(1);
+
+		let cluster_id = ClusterId::from([1; 20]);
+		let owner: u128 = 3;
+		let admin: u128 = 1;
+		let backend: u128 = 2;
+		let bucket_id = 1;
+		let expires_at = 10;
+
+		assert_ok!(DdcCustomers::deposit(RuntimeOrigin::signed(owner), 100));
+		assert_ok!(DdcCustomers::create_bucket(
+			RuntimeOrigin::signed(owner),
+			cluster_id,
+			BucketParams { is_public: false, budget: Some(50), metadata: Default::default() }
+		));
+
+		// Only the owner or an admin can grant access
+		assert_noop!(
+			DdcCustomers::grant_bucket_access(
+				RuntimeOrigin::signed(backend),
+				bucket_id,
+				backend,
+				BucketPermission::Write,
+				expires_at,
+				0
+			),
+			Error::<Test>::NotBucketOwner
+		);
+		assert_noop!(
+			DdcCustomers::grant_bucket_access(
+				RuntimeOrigin::signed(owner),
+				bucket_id,
+				admin,
+				BucketPermission::Admin,
+				1,
+				0
+			),
+			Error::<Test>::InvalidGrantExpiry
+		);
+
+		assert_ok!(DdcCustomers::grant_bucket_access(
+			RuntimeOrigin::signed(owner),
+			bucket_id,
+			admin,
+			BucketPermission::Admin,
+			expires_at,
+			0
+		));
+		System::assert_last_event(
+			Event::BucketAccessGranted {
+				bucket_id,
+				grantee: admin,
+				permission: BucketPermission::Admin,
+				expires_at,
+				allowance: 0,
+			}
+			.into(),
+		);
+
+		// Admins can grant read and write access, but can not appoint other admins
+		assert_noop!(
+			DdcCustomers::grant_bucket_access(
+				RuntimeOrigin::signed(admin),
+				bucket_id,
+				backend,
+				BucketPermission::Admin,
+				expires_at,
+				0
+			),
+			Error::<Test>::Unauthorised
+		);
+		assert_ok!(DdcCustomers::grant_bucket_access(
+			RuntimeOrigin::signed(admin),
+			bucket_id,
+			backend,
+			BucketPermission::Write,
+			expires_at,
+			20
+		));
+		assert_ok!(DdcCustomers::set_bucket_params(
+			RuntimeOrigin::signed(admin),
+			bucket_id,
+			BucketParams { is_public: true, budget: Some(50), metadata: Default::default() }
+		));
+
+		assert_eq!(DdcCustomers::bucket_access(bucket_id, &owner), Some(BucketPermission::Admin));
+		assert_eq!(DdcCustomers::bucket_access(bucket_id, &backend), Some(BucketPermission::Write));
+		assert_eq!(DdcCustomers::bucket_access(bucket_id, &4), None);
+		assert_eq!(DdcCustomers::bucket_grants(bucket_id).len(), 2);
+
+		// Writers can not administer the bucket
+		assert_noop!(
+			DdcCustomers::set_bucket_params(
+				RuntimeOrigin::signed(backend),
+				bucket_id,
+				BucketParams { is_public: false, budget: Some(50), metadata: Default::default() }
+			),
+			Error::<Test>::NotBucketOwner
+		);
+
+		// Writers spend the owner's deposit within their allowance
+		assert_noop!(
+			DdcCustomers::allocate_to_bucket(RuntimeOrigin::signed(backend), bucket_id, 21),
+			Error::<Test>::AllowanceExceeded
+		);
+		assert_ok!(DdcCustomers::allocate_to_bucket(RuntimeOrigin::signed(backend), bucket_id, 15));
+		System::assert_has_event(
+			Event::AllowanceSpent { bucket_id, grantee: backend, amount: 15, remaining: 5 }.into(),
+		);
+		assert_eq!(DdcCustomers::ledger(owner).unwrap().active, 85);
+		assert_eq!(DdcCustomers::bucket_budgets(bucket_id).unwrap().allocated, 15);
+		assert_eq!(DdcCustomers::bucket_grant(bucket_id, backend).unwrap().allowance, 5);
+
+		// Admins can not revoke other admins
+		assert_ok!(DdcCustomers::grant_bucket_access(
+			RuntimeOrigin::signed(owner),
+			bucket_id,
+			4,
+			BucketPermission::Admin,
+			expires_at,
+			0
+		));
+		assert_noop!(
+			DdcCustomers::revoke_bucket_access(RuntimeOrigin::signed(admin), bucket_id, 4),
+			Error::<Test>::Unauthorised
+		);
+		assert_ok!(DdcCustomers::revoke_bucket_access(RuntimeOrigin::signed(4), bucket_id, 4));
+
+		assert_ok!(DdcCustomers::revoke_bucket_access(
+			RuntimeOrigin::signed(admin),
+			bucket_id,
+			backend
+		));
+		System::assert_last_event(
+			Event::BucketAccessRevoked { bucket_id, grantee: backend }.into(),
+		);
+		assert_noop!(
+			DdcCustomers::allocate_to_bucket(RuntimeOrigin::signed(backend), bucket_id, 1),
+			Error::<Test>::NotBucketOwner
+		);
+
+		// Expired grants give no access and can be removed by anyone
+		System::set_block_number(expires_at);
+		assert_eq!(DdcCustomers::bucket_access(bucket_id, &admin), None);
+		assert!(DdcCustomers::bucket_grants(bucket_id).is_empty());
+		assert_noop!(
+			DdcCustomers::set_bucket_params(
+				RuntimeOrigin::signed(admin),
+				bucket_id,
+				BucketParams { is_public: false, budget: Some(50), metadata: Default::default() }
+			),
+			Error::<Test>::NotBucketOwner
+		);
+		assert_ok!(DdcCustomers::revoke_bucket_access(
+			RuntimeOrigin::signed(backend),
+			bucket_id,
+			admin
+		));
+		assert_eq!(DdcCustomers::bucket_grant(bucket_id, admin), None);
+	})
+}
+
+#[test]
+fn bucket_transfer_works() {
+	ExtBuilder.build_and_execute(|| {
+		System::set_block_number(1);
+
+		let cluster_id = ClusterId::from([1; 20]);
+		let owner: u128 = 1;
+		let new_owner: u128 = 2;
+		let grantee: u128 = 3;
+		let bucket_id = 1;
+
+		assert_ok!(DdcCustomers::create_bucket(
+			RuntimeOrigin::signed(owner),
+			cluster_id,
+			BucketParams { is_public: false, budget: None, metadata: Default::default() }
+		));
+		assert_ok!(DdcCustomers::grant_bucket_access(
+			RuntimeOrigin::signed(owner),
+			bucket_id,
+			grantee,
+			BucketPermission::Admin,
+			10,
+			0
+		));
+
+		// Nothing to accept without an offer
+		assert_noop!(
+			DdcCustomers::accept_bucket_transfer(RuntimeOrigin::signed(new_owner), bucket_id),
+			Error::<Test>::NoBucketTransfer
+		);
+		// Admins can not offer the bucket
+		assert_noop!(
+			DdcCustomers::offer_bucket_transfer(RuntimeOrigin::signed(grantee), bucket_id, grantee),
+			Error::<Test>::NotBucketOwner
+		);
+
+		assert_ok!(DdcCustomers::offer_bucket_transfer(
+			RuntimeOrigin::signed(owner),
+			bucket_id,
+			new_owner
+		));
+		System::assert_last_event(
+			Event::BucketTransferOffered { bucket_id, from: owner, to: new_owner }.into(),
+		);
+
+		// Only the account the bucket has been offered to can accept
+		assert_noop!(
+			DdcCustomers::accept_bucket_transfer(RuntimeOrigin::signed(grantee), bucket_id),
+			Error::<Test>::NoBucketTransfer
+		);
+
+		// Debtors can not take over buckets in the cluster
+		CustomerDebt::set(10);
+		assert_noop!(
+			DdcCustomers::accept_bucket_transfer(RuntimeOrigin::signed(new_owner), bucket_id),
+			Error::<Test>::CustomerIndebted
+		);
+		CustomerDebt::set(0);
+
+		// Indebted buckets keep their owner
+		BucketDebt::set(10);
+		assert_noop!(
+			DdcCustomers::accept_bucket_transfer(RuntimeOrigin::signed(new_owner), bucket_id),
+			Error::<Test>::BucketIndebted
+		);
+		BucketDebt::set(0);
+
+		// The owner is kept while the cluster is billed
+		OpenBillingClusters::set(vec![cluster_id]);
+		assert_noop!(
+			DdcCustomers::accept_bucket_transfer(RuntimeOrigin::signed(new_owner), bucket_id),
+			Error::<Test>::BillingReportOpen
+		);
+		OpenBillingClusters::set(vec![]);
+
+		assert_ok!(DdcCustomers::accept_bucket_transfer(
+			RuntimeOrigin::signed(new_owner),
+			bucket_id
+		));
+		System::assert_last_event(
+			Event::BucketTransferred { bucket_id, from: owner, to: new_owner }.into(),
+		);
+
+		assert_eq!(
+			DdcCustomers::buckets(bucket_id),
+			Some(Bucket {
+				bucket_id,
+				owner_id: new_owner,
+				cluster_id,
+				is_public: false,
+				is_removed: false,
+				metadata: Default::default(),
+			})
+		);
+		assert_eq!(DdcCustomers::bucket_transfer(bucket_id), None);
+		// Grants of the previous owner are revoked
+		assert_eq!(DdcCustomers::bucket_grant(bucket_id, grantee), None);
+		assert_eq!(DdcCustomers::bucket_grants_count(bucket_id), 0);
+		assert_eq!(DdcCustomers::bucket_access(bucket_id, &owner), None);
+
+		// The offer can be cancelled by the owner
+		assert_ok!(DdcCustomers::offer_bucket_transfer(
+			RuntimeOrigin::signed(new_owner),
+			bucket_id,
+			owner
+		));
+		assert_ok!(DdcCustomers::cancel_bucket_transfer(
+			RuntimeOrigin::signed(new_owner),
+			bucket_id
+		));
+		System::assert_last_event(Event::BucketTransferCancelled { bucket_id }.into());
+		assert_noop!(
+			DdcCustomers::accept_bucket_transfer(RuntimeOrigin::signed(owner), bucket_id),
+			Error::<Test>::NoBucketTransfer
+		);
+	})
+}
+
+#[test]
+fn bucket_transfer_requires_released_allocation() {
+	ExtBuilder.build_and_execute(|| {
+		System::set_block_number(1);
+
+		let cluster_id = ClusterId::from([1; 20]);
+		let owner: u128 = 3;
+		let new_owner: u128 = 2;
+		let bucket_id = 1;
+
+		assert_ok!(DdcCustomers::deposit(RuntimeOrigin::signed(owner), 100));
+		assert_ok!(DdcCustomers::create_bucket(
+			RuntimeOrigin::signed(owner),
+			cluster_id,
+			BucketParams { is_public: false, budget: Some(50), metadata: Default::default() }
+		));
+		assert_ok!(DdcCustomers::allocate_to_bucket(RuntimeOrigin::signed(owner), bucket_id, 50));
+		assert_ok!(DdcCustomers::offer_bucket_transfer(
+			RuntimeOrigin::signed(owner),
+			bucket_id,
+			new_owner
+		));
+
+		assert_noop!(
+			DdcCustomers::accept_bucket_transfer(RuntimeOrigin::signed(new_owner), bucket_id),
+			Error::<Test>::BucketHasAllocation
+		);
+
+		assert_ok!(DdcCustomers::release_bucket_allocation(
+			RuntimeOrigin::signed(owner),
+			bucket_id,
+			50
+		));
+		assert_ok!(DdcCustomers::accept_bucket_transfer(
+			RuntimeOrigin::signed(new_owner),
+			bucket_id
+		));
+		assert_eq!(DdcCustomers::ledger(owner).unwrap().active, 100);
+	})
+}
+
+#[test]
+fn migrate_bucket_works() {
+	ExtBuilder.build_and_execute(|| {
+		System::set_block_number(1);
+
+		let cluster_id = ClusterId::from([1; 20]);
+		let new_cluster_id = ClusterId::from([2; 20]);
+		let owner: u128 = 1;
+		let bucket_id = 1;
+
+		assert_ok!(DdcCustomers::create_bucket(
+			RuntimeOrigin::signed(owner),
+			cluster_id,
+			BucketParams { is_public: false, budget: None, metadata: Default::default() }
+		));
+
+		assert_noop!(
+			DdcCustomers::migrate_bucket(RuntimeOrigin::signed(owner), bucket_id, new_cluster_id),
+			DispatchError::BadOrigin
+		);
+		assert_noop!(
+			DdcCustomers::migrate_bucket(RuntimeOrigin::root(), bucket_id, cluster_id),
+			Error::<Test>::SameCluster
+		);
+
+		// The cluster is kept while either cluster is billed
+		for billed_cluster_id in [cluster_id, new_cluster_id] {
+			OpenBillingClusters::set(vec![billed_cluster_id]);
+			assert_noop!(
+				DdcCustomers::migrate_bucket(RuntimeOrigin::root(), bucket_id, new_cluster_id),
+				Error::<Test>::BillingReportOpen
+			);
+		}
+		OpenBillingClusters::set(vec![]);
+
+		assert_ok!(DdcCustomers::migrate_bucket(RuntimeOrigin::root(), bucket_id, new_cluster_id));
+		System::assert_last_event(
+			Event::BucketMigrated {
+				bucket_id,
+				from_cluster: cluster_id,
+				to_cluster: new_cluster_id,
+			}
+			.into(),
+		);
+
+		assert_ok!(<DdcCustomers as BucketVisitor<Test>>::ensure_bucket_owner(
+			&new_cluster_id,
+			bucket_id,
+			&owner
+		));
+		assert_eq!(
+			<DdcCustomers as BucketVisitor<Test>>::ensure_bucket_owner(
+				&cluster_id,
+				bucket_id,
+				&owner
+			),
+			Err(BucketVisitorError::IncorrectClusterId)
+		);
+	})
+}
+
+#[test]
+fn bucket_metadata_is_validated_against_cluster_capabilities() {
+	ExtBuilder.build_and_execute(|| {
+		System::set_block_number(1);
+
+		let cluster_id = ClusterId::from([1; 20]);
+		let owner: u128 = 1;
+		let bucket_id = 1;
+		let region: Region = b"eu-central".to_vec().try_into().unwrap();
+		let metadata = BucketMetadata {
+			replication_factor: Some(3),
+			region: Some(region.clone()),
+			storage_class: StorageClass::Cold,
+			ttl: Some(86_400),
+		};
+
+		// Default capabilities support no custom placement
+		assert_noop!(
+			DdcCustomers::create_bucket(
+				RuntimeOrigin::signed(owner),
+				cluster_id,
+				BucketParams { is_public: false, budget: None, metadata: metadata.clone() }
+			),
+			Error::<Test>::UnsupportedReplicationFactor
+		);
+
+		TestCapabilities::set(ClusterCapabilities {
+			default_replication_factor: 2,
+			max_replication_factor: 3,
+			regions: Default::default(),
+			cold_storage_discount: None,
+		});
+		assert_noop!(
+			DdcCustomers::create_bucket(
+				RuntimeOrigin::signed(owner),
+				cluster_id,
+				BucketParams { is_public: false, budget: None, metadata: metadata.clone() }
+			),
+			Error::<Test>::UnsupportedRegion
+		);
+
+		TestCapabilities::set(ClusterCapabilities {
+			default_replication_factor: 2,
+			max_replication_factor: 3,
+			regions: vec![region.clone()].try_into().unwrap(),
+			cold_storage_discount: None,
+		});
+		assert_noop!(
+			DdcCustomers::create_bucket(
+				RuntimeOrigin::signed(owner),
+				cluster_id,
+				BucketParams { is_public: false, budget: None, metadata: metadata.clone() }
+			),
+			Error::<Test>::UnsupportedStorageClass
+		);
+
+		TestCapabilities::set(ClusterCapabilities {
+			default_replication_factor: 2,
+			max_replication_factor: 3,
+			regions: vec![region].try_into().unwrap(),
+			cold_storage_discount: Some(Perquintill::from_percent(40)),
+		});
+		assert_noop!(
+			DdcCustomers::create_bucket(
+				RuntimeOrigin::signed(owner),
+				cluster_id,
+				BucketParams {
+					is_public: false,
+					budget: None,
+					metadata: BucketMetadata { ttl: Some(0), ..metadata.clone() }
+				}
+			),
+			Error::<Test>::InvalidTtl
+		);
+
+		assert_ok!(DdcCustomers::create_bucket(
+			RuntimeOrigin::signed(owner),
+			cluster_id,
+			BucketParams { is_public: false, budget: None, metadata: metadata.clone() }
+		));
+		assert_eq!(
+			<DdcCustomers as BucketVisitor<Test>>::get_bucket_metadata(bucket_id),
+			Ok(metadata.clone())
+		);
+
+		assert_noop!(
+			DdcCustomers::set_bucket_params(
+				RuntimeOrigin::signed(owner),
+				bucket_id,
+				BucketParams {
+					is_public: false,
+					budget: None,
+					metadata: BucketMetadata { replication_factor: Some(4), ..metadata }
+				}
+			),
+			Error::<Test>::UnsupportedReplicationFactor
+		);
+		assert_ok!(DdcCustomers::set_bucket_params(
+			RuntimeOrigin::signed(owner),
+			bucket_id,
+			BucketParams { is_public: false, budget: None, metadata: Default::default() }
+		));
+		assert_eq!(
+			<DdcCustomers as BucketVisitor<Test>>::get_bucket_metadata(bucket_id),
+			Ok(Default::default())
+		);
+	})
+}
+
+#[test]
+fn reap_bucket_works() {
+	ExtBuilder.build_and_execute(|| {
+		System::set_block_number(1);
+
+		let cluster_id = ClusterId::from([1; 20]);
+		let owner: u128 = 1;
+		let grantee: u128 = 2;
+		let reaper: u128 = 3;
+		let bucket_id = 1;
+		BucketDeposit::set(10);
+
+		assert_ok!(DdcCustomers::create_bucket(
+			RuntimeOrigin::signed(owner),
+			cluster_id,
+			BucketParams { is_public: false, budget: Some(30), metadata: Default::default() }
+		));
+		assert_eq!(DdcCustomers::bucket_deposit(bucket_id), Some((owner, 10)));
+		assert_eq!(Balances::free_balance(owner), 90);
+
+		assert_ok!(DdcCustomers::deposit(RuntimeOrigin::signed(owner), 50));
+		assert_ok!(DdcCustomers::allocate_to_bucket(RuntimeOrigin::signed(owner), bucket_id, 20));
+		assert_ok!(DdcCustomers::grant_bucket_access(
+			RuntimeOrigin::signed(owner),
+			bucket_id,
+			grantee,
+			BucketPermission::Read,
+			100,
+			0
+		));
+
+		assert_noop!(
+			DdcCustomers::reap_bucket(RuntimeOrigin::signed(reaper), bucket_id),
+			Error::<Test>::BucketNotRemoved
+		);
+
+		LastFinalizedEra::set(Some(1));
+		assert_ok!(DdcCustomers::remove_bucket(RuntimeOrigin::signed(owner), bucket_id));
+		assert_eq!(
+			DdcCustomers::removed_bucket(bucket_id),
+			Some(RemovedBucket { reap_at: 11, billed_era: Some(1) })
+		);
+
+		// The bucket can still be billed during the grace period
+		System::set_block_number(10);
+		assert_ok!(<DdcCustomers as BucketVisitor<Test>>::ensure_bucket_owner(
+			&cluster_id,
+			bucket_id,
+			&owner
+		));
+		assert_noop!(
+			DdcCustomers::reap_bucket(RuntimeOrigin::signed(reaper), bucket_id),
+			Error::<Test>::GracePeriodNotOver
+		);
+
+		// The era the bucket was last used in has to be billed
+		System::set_block_number(11);
+		assert_noop!(
+			DdcCustomers::reap_bucket(RuntimeOrigin::signed(reaper), bucket_id),
+			Error::<Test>::RemovalNotBilled
+		);
+
+		LastFinalizedEra::set(Some(2));
+		assert_ok!(DdcCustomers::reap_bucket(RuntimeOrigin::signed(reaper), bucket_id));
+		System::assert_last_event(
+			Event::BucketReaped { bucket_id, depositor: Some(owner), refund: 10 }.into(),
+		);
+
+		assert_eq!(DdcCustomers::buckets(bucket_id), None);
+		assert_eq!(DdcCustomers::removed_bucket(bucket_id), None);
+		assert_eq!(DdcCustomers::bucket_deposit(bucket_id), None);
+		assert_eq!(DdcCustomers::bucket_budgets(bucket_id), None);
+		assert_eq!(DdcCustomers::bucket_grant(bucket_id, grantee), None);
+		assert_eq!(DdcCustomers::bucket_grants_count(bucket_id), 0);
+		assert_eq!(DdcCustomers::ledger(owner).unwrap().active, 50);
+		assert_eq!(Balances::free_balance(owner), 50);
+
+		assert_noop!(
+			DdcCustomers::reap_bucket(RuntimeOrigin::signed(reaper), bucket_id),
+			Error::<Test>::BucketNotRemoved
+		);
+	})
+}
+
+#[test]
+fn removed_buckets_are_reaped_on_idle() {
+	ExtBuilder.build_and_execute(|| {
+		System::set_block_number(1);
+
+		let cluster_id = ClusterId::from([1; 20]);
+		let owner: u128 = 1;
+		BucketDeposit::set(10);
+
+		for bucket_id in 1..=2 {
+			assert_ok!(DdcCustomers::create_bucket(
+				RuntimeOrigin::signed(owner),
+				cluster_id,
+				BucketParams { is_public: false, budget: None, metadata: Default::default() }
+			));
+			assert_ok!(DdcCustomers::remove_bucket(RuntimeOrigin::signed(owner), bucket_id));
+		}
+		assert_eq!(Balances::free_balance(owner), 80);
+
+		// Nothing to reap during the grace period
+		DdcCustomers::on_idle(10, Weight::MAX);
+		assert_eq!(DdcCustomers::buckets(1).map(|bucket| bucket.is_removed), Some(true));
+		assert_eq!(DdcCustomers::buckets(2).map(|bucket| bucket.is_removed), Some(true));
+
+		// Not enough weight to reap a bucket
+		assert_eq!(DdcCustomers::on_idle(11, Weight::zero()), Weight::zero());
+		assert!(DdcCustomers::removed_bucket(1).is_some());
+
+		let db_weight = <Test as frame_system::Config>::DbWeight::get();
+		let reap_weight = <() as WeightInfo>::reap_bucket();
+
+		// Buckets are checked again after the next grace period until their last era is billed
+		let weight = DdcCustomers::on_idle(11, Weight::MAX);
+		assert_eq!(
+			weight,
+			db_weight.reads(1) + (reap_weight + db_weight.writes(1)) * 2 + db_weight.writes(1)
+		);
+		assert!(DdcCustomers::removed_bucket(1).is_some());
+		assert_eq!(ReapingQueueRange::<Test>::get(), (2, 4));
+		assert_eq!(DdcCustomers::reaping_queue(2), Some((1, 21)));
+		assert_eq!(DdcCustomers::reaping_queue(3), Some((2, 21)));
+
+		LastFinalizedEra::set(Some(1));
+		assert_eq!(DdcCustomers::on_idle(20, Weight::MAX), db_weight.reads(1));
+		assert!(DdcCustomers::removed_bucket(1).is_some());
+
+		let weight = DdcCustomers::on_idle(21, Weight::MAX);
+		assert_eq!(weight, db_weight.reads(1) + reap_weight * 2 + db_weight.writes(1));
+		assert_eq!(ReapingQueueRange::<Test>::get(), (4, 4));
+		assert_eq!(DdcCustomers::buckets(1), None);
+		assert_eq!(DdcCustomers::buckets(2), None);
+		assert_eq!(Balances::free_balance(owner), 100);
+	})
+}
+
+#[test]
+fn top_up_subscription_works() {
+	ExtBuilder.build_and_execute(|| {
+		System::set_block_number(1);
+
+		let owner: u128 = 3;
+		let funder: u128 = 1;
+		let stranger: u128 = 2;
+		let vault: u128 = 4;
+
+		assert_noop!(
+			DdcCustomers::subscribe_top_up(RuntimeOrigin::signed(owner), owner, 50, 30),
+			Error::<Test>::NotOwner
+		);
+		assert_ok!(DdcCustomers::deposit(RuntimeOrigin::signed(owner), 100));
+		assert_noop!(
+			DdcCustomers::subscribe_top_up(RuntimeOrigin::signed(owner), owner, 50, 0),
+			Error::<Test>::InsufficientDeposit
+		);
+		assert_ok!(DdcCustomers::subscribe_top_up(RuntimeOrigin::signed(owner), owner, 50, 30));
+		System::assert_last_event(
+			Event::TopUpSubscribed { owner_id: owner, funder: owner, threshold: 50, amount: 30 }
+				.into(),
+		);
+
+		// Active deposit stays above the threshold
+		assert_ok!(DdcCustomers::charge_content_owner(None, owner, vault, 40));
+		assert_eq!(DdcCustomers::ledger(owner).unwrap().active, 60);
+
+		// Active deposit drops below the threshold and is topped up from the owner's balance
+		assert_ok!(DdcCustomers::charge_content_owner(None, owner, vault, 20));
+		System::assert_last_event(
+			Event::ToppedUp { owner_id: owner, funder: owner, amount: 30 }.into(),
+		);
+		assert_eq!(DdcCustomers::ledger(owner).unwrap().active, 70);
+		assert_eq!(Balances::free_balance(owner), 1000 - 100 - 30);
+
+		// Only the owner or the funder can replace the subscription
+		assert_noop!(
+			DdcCustomers::subscribe_top_up(RuntimeOrigin::signed(funder), owner, 80, 20),
+			Error::<Test>::Unauthorised
+		);
+		assert_ok!(DdcCustomers::cancel_top_up(RuntimeOrigin::signed(owner), owner));
+		System::assert_last_event(Event::TopUpCancelled { owner_id: owner }.into());
+
+		// Another account funds the top-up
+		assert_ok!(DdcCustomers::subscribe_top_up(RuntimeOrigin::signed(funder), owner, 80, 20));
+		assert_ok!(DdcCustomers::charge_content_owner(None, owner, vault, 1));
+		System::assert_last_event(Event::ToppedUp { owner_id: owner, funder, amount: 20 }.into());
+		assert_eq!(DdcCustomers::ledger(owner).unwrap().active, 89);
+		assert_eq!(Balances::free_balance(funder), 80);
+		assert_eq!(Balances::free_balance(owner), 1000 - 100 - 30);
+
+		// A failed top-up does not fail the charge
+		assert_ok!(DdcCustomers::subscribe_top_up(RuntimeOrigin::signed(funder), owner, 80, 200));
+		assert_eq!(DdcCustomers::charge_content_owner(None, owner, vault, 10), Ok(10));
+		System::assert_last_event(Event::TopUpFailed { owner_id: owner, funder }.into());
+		assert_eq!(DdcCustomers::ledger(owner).unwrap().active, 79);
+		assert_eq!(Balances::free_balance(funder), 80);
+
+		assert_noop!(
+			DdcCustomers::cancel_top_up(RuntimeOrigin::signed(stranger), owner),
+			Error::<Test>::Unauthorised
+		);
+		assert_ok!(DdcCustomers::cancel_top_up(RuntimeOrigin::signed(funder), owner));
+		assert_noop!(
+			DdcCustomers::cancel_top_up(RuntimeOrigin::signed(owner), owner),
+			Error::<Test>::NoTopUpSubscription
+		);
+	})
+}
+
+#[test]
+fn deposit_asset_and_withdraw_works() {
+	ExtBuilder.build_and_execute(|| {
+		System::set_block_number(1);
+
+		let owner: u128 = 3;
+		let asset_id = StableAssetId::get();
+
+		assert_noop!(
+			DdcCustomers::deposit_asset(RuntimeOrigin::signed(owner), asset_id, 0),
+			Error::<Test>::InsufficientDeposit
+		);
+		assert_ok!(DdcCustomers::deposit_asset(RuntimeOrigin::signed(owner), asset_id, 100));
+		System::assert_last_event(
+			Event::AssetDeposited { owner_id: owner, asset_id, amount: 100 }.into(),
+		);
+		assert_ok!(DdcCustomers::deposit_asset(RuntimeOrigin::signed(owner), asset_id, 50));
+		assert_eq!(
+			DdcCustomers::asset_ledger(owner, asset_id),
+			Some(AccountsLedger { owner, total: 150, active: 150, unlocking: Default::default() })
+		);
+		assert_eq!(Assets::balance(asset_id, owner), 850);
+		assert_eq!(Assets::balance(asset_id, DdcCustomers::account_id()), 150);
+		// The native deposit is not affected
+		assert_eq!(DdcCustomers::ledger(owner), None);
+
+		assert_noop!(
+			DdcCustomers::unlock_asset_deposit(RuntimeOrigin::signed(1), asset_id, 50),
+			Error::<Test>::NoAssetDeposit
+		);
+		assert_ok!(DdcCustomers::unlock_asset_deposit(RuntimeOrigin::signed(owner), asset_id, 50));
+		System::assert_last_event(
+			Event::AssetDepositUnlock { owner_id: owner, asset_id, amount: 50 }.into(),
+		);
+
+		// Still locked
+		assert_ok!(DdcCustomers::withdraw_unlocked_asset_deposit(
+			RuntimeOrigin::signed(owner),
+			asset_id
+		));
+		assert_eq!(Assets::balance(asset_id, owner), 850);
+
+		System::set_block_number(11);
+		assert_ok!(DdcCustomers::withdraw_unlocked_asset_deposit(
+			RuntimeOrigin::signed(owner),
+			asset_id
+		));
+		System::assert_last_event(
+			Event::AssetWithdrawn { owner_id: owner, asset_id, amount: 50 }.into(),
+		);
+		assert_eq!(Assets::balance(asset_id, owner), 900);
+		assert_eq!(
+			DdcCustomers::asset_ledger(owner, asset_id),
+			Some(AccountsLedger { owner, total: 100, active: 100, unlocking: Default::default() })
+		);
+
+		// Withdrawing the whole deposit removes the ledger and the payment asset
+		assert_ok!(DdcCustomers::set_payment_asset(RuntimeOrigin::signed(owner), Some(asset_id)));
+		assert_ok!(DdcCustomers::unlock_asset_deposit(RuntimeOrigin::signed(owner), asset_id, 100));
+		System::set_block_number(21);
+		assert_ok!(DdcCustomers::withdraw_unlocked_asset_deposit(
+			RuntimeOrigin::signed(owner),
+			asset_id
+		));
+		assert_eq!(Assets::balance(asset_id, owner), 1000);
+		assert_eq!(DdcCustomers::asset_ledger(owner, asset_id), None);
+		assert_eq!(DdcCustomers::payment_asset(owner), None);
+	})
+}
+
+#[test]
+fn set_payment_asset_works() {
+	ExtBuilder.build_and_execute(|| {
+		System::set_block_number(1);
+
+		let owner: u128 = 3;
+		let asset_id = StableAssetId::get();
+		let cluster_id = ClusterId::from([1; 20]);
+
+		assert_noop!(
+			DdcCustomers::set_payment_asset(RuntimeOrigin::signed(owner), Some(asset_id)),
+			Error::<Test>::NoAssetDeposit
+		);
+		assert_ok!(DdcCustomers::deposit_asset(RuntimeOrigin::signed(owner), asset_id, 100));
+		assert_ok!(DdcCustomers::set_payment_asset(RuntimeOrigin::signed(owner), Some(asset_id)));
+		System::assert_last_event(
+			Event::PaymentAssetSet { owner_id: owner, asset_id: Some(asset_id) }.into(),
+		);
+		assert_eq!(DdcCustomers::get_payment_source(None, &owner), PaymentSource::Asset(asset_id));
+
+		// Buckets with a budget are charged from their allocation in the native currency
+		assert_ok!(DdcCustomers::create_bucket(
+			RuntimeOrigin::signed(owner),
+			cluster_id,
+			BucketParams { is_public: false, budget: None, metadata: Default::default() }
+		));
+		assert_ok!(DdcCustomers::create_bucket(
+			RuntimeOrigin::signed(owner),
+			cluster_id,
+			BucketParams { is_public: false, budget: Some(100), metadata: Default::default() }
+		));
+		assert_eq!(
+			DdcCustomers::get_payment_source(Some(1), &owner),
+			PaymentSource::Asset(asset_id)
+		);
+		assert_eq!(
+			DdcCustomers::get_payment_source(Some(2), &owner),
+			PaymentSource::BucketBudget(2)
+		);
+
+		assert_ok!(DdcCustomers::set_payment_asset(RuntimeOrigin::signed(owner), None));
+		System::assert_last_event(
+			Event::PaymentAssetSet { owner_id: owner, asset_id: None }.into(),
+		);
+		assert_eq!(DdcCustomers::get_payment_source(None, &owner), PaymentSource::Deposit);
+	})
+}
+
+#[test]
+fn charge_content_owner_asset_works() {
+	ExtBuilder.build_and_execute(|| {
+		System::set_block_number(1);
+
+		let owner: u128 = 3;
+		let payee: u128 = 1;
+		let asset_id = StableAssetId::get();
+
+		assert_noop!(
+			DdcCustomers::charge_content_owner_asset(owner, payee, asset_id, 10),
+			Error::<Test>::NoAssetDeposit
+		);
+
+		assert_ok!(DdcCustomers::deposit_asset(RuntimeOrigin::signed(owner), asset_id, 100));
+		assert_ok!(DdcCustomers::unlock_asset_deposit(RuntimeOrigin::signed(owner), asset_id, 40));
+
+		assert_eq!(DdcCustomers::charge_content_owner_asset(owner, payee, asset_id, 50), Ok(50));
+		System::assert_last_event(
+			Event::AssetCharged { owner_id: owner, asset_id, charged: 50, expected_to_charge: 50 }
+				.into(),
+		);
+		assert_eq!(Assets::balance(asset_id, payee), 50);
+		assert_eq!(DdcCustomers::asset_ledger(owner, asset_id).unwrap().active, 10);
+
+		// Funds scheduled for unlocking are charged once the active deposit is exhausted
+		assert_eq!(DdcCustomers::charge_content_owner_asset(owner, payee, asset_id, 80), Ok(50));
+		System::assert_last_event(
+			Event::AssetCharged { owner_id: owner, asset_id, charged: 50, expected_to_charge: 80 }
+				.into(),
+		);
+		assert_eq!(Assets::balance(asset_id, payee), 100);
+		assert_eq!(
+			DdcCustomers::asset_ledger(owner, asset_id),
+			Some(AccountsLedger { owner, total: 0, active: 0, unlocking: Default::default() })
+		);
+		// The native deposit is not charged
+		assert_eq!(Balances::free_balance(payee), 100);
+	})
+}
+
+#[test]
+fn refund_content_owner_works() {
+	ExtBuilder.build_and_execute(|| {
+		System::set_block_number(1);
+
+		let cluster_id = ClusterId::from([1; 20]);
+		let owner: u128 = 3;
+		let vault: u128 = 4;
+		let asset_id = StableAssetId::get();
+		let bucket_id = 1;
+
+		assert_ok!(DdcCustomers::deposit(RuntimeOrigin::signed(owner), 100));
+		assert_ok!(DdcCustomers::deposit_asset(RuntimeOrigin::signed(owner), asset_id, 100));
+		assert_ok!(DdcCustomers::create_bucket(
+			RuntimeOrigin::signed(owner),
+			cluster_id,
+			BucketParams { is_public: false, budget: Some(30), metadata: Default::default() }
+		));
+		assert_ok!(DdcCustomers::allocate_to_bucket(RuntimeOrigin::signed(owner), bucket_id, 30));
+
+		assert_eq!(DdcCustomers::charge_content_owner(None, owner, vault, 40), Ok(40));
+		assert_eq!(DdcCustomers::charge_content_owner(Some(bucket_id), owner, vault, 20), Ok(20));
+		assert_eq!(DdcCustomers::charge_content_owner_asset(owner, vault, asset_id, 50), Ok(50));
+
+		// Every charge is refunded to the source it was taken from
+		assert_ok!(DdcCustomers::refund_content_owner(PaymentSource::Deposit, owner, vault, 40));
+		System::assert_last_event(
+			Event::Refunded { owner_id: owner, source: PaymentSource::Deposit, amount: 40 }.into(),
+		);
+		assert_eq!(DdcCustomers::ledger(owner).unwrap().active, 70);
+
+		assert_ok!(DdcCustomers::refund_content_owner(
+			PaymentSource::BucketBudget(bucket_id),
+			owner,
+			vault,
+			20
+		));
+		assert_eq!(
+			DdcCustomers::bucket_budgets(bucket_id),
+			Some(BucketBudget { limit: 30, allocated: 30 })
+		);
+
+		assert_ok!(DdcCustomers::refund_content_owner(
+			PaymentSource::Asset(asset_id),
+			owner,
+			vault,
+			50
+		));
+		assert_eq!(DdcCustomers::asset_ledger(owner, asset_id).unwrap().active, 100);
+		assert_eq!(Balances::free_balance(vault), 0);
+		assert_eq!(Assets::balance(asset_id, vault), 0);
+
+		// A charge in the asset is refunded to the owner account once the deposit is withdrawn
+		assert_eq!(DdcCustomers::charge_content_owner_asset(owner, vault, asset_id, 50), Ok(50));
+		assert_ok!(DdcCustomers::unlock_asset_deposit(RuntimeOrigin::signed(owner), asset_id, 50));
+		System::set_block_number(11);
+		assert_ok!(DdcCustomers::withdraw_unlocked_asset_deposit(
+			RuntimeOrigin::signed(owner),
+			asset_id
+		));
+		assert_eq!(DdcCustomers::asset_ledger(owner, asset_id), None);
+		assert_eq!(Assets::balance(asset_id, owner), 950);
+
+		assert_ok!(DdcCustomers::refund_content_owner(
+			PaymentSource::Asset(asset_id),
+			owner,
+			vault,
+			50
+		));
+		assert_eq!(DdcCustomers::asset_ledger(owner, asset_id), None);
+		assert_eq!(Assets::balance(asset_id, owner), 1000);
+		assert_eq!(Assets::balance(asset_id, vault), 0);
+
+		// A source the account does not have can not be refunded
+		assert_noop!(
+			DdcCustomers::refund_content_owner(PaymentSource::BucketBudget(2), owner, vault, 10),
+			Error::<Test>::NoBucketBudget
+		);
+		assert_noop!(
+			DdcCustomers::refund_content_owner(PaymentSource::Deposit, 1, vault, 10),
+			Error::<Test>::NotOwner
+		);
+	})
+}
@@ -1,11 +1,15 @@
-//! Autogenerated weights for pallet_ddc_customers
+//! Weights for pallet_ddc_customers
 //!
-//! THIS FILE WAS AUTO-GENERATED USING THE SUBSTRATE BENCHMARK CLI VERSION 4.0.0-dev
-//! DATE: 2024-02-18, STEPS: `50`, REPEAT: 50, LOW RANGE: `[]`, HIGH RANGE: `[]`
-//! HOSTNAME: `bench`, CPU: `DO-Premium-AMD`
-//! EXECUTION: Some(Wasm), WASM-EXECUTION: Compiled, CHAIN: Some("dev"), DB CACHE: 1024
+//! The weights were generated with the Substrate benchmark CLI on 2024-02-18 (STEPS: `50`, REPEAT:
+//! 50, HOSTNAME: `bench`). The weights of the following calls are provisional estimates derived
+//! from their storage access, not benchmark results, and must be regenerated with the command below
+//! before a release: `create_bucket`, `set_bucket_params`, `remove_bucket`, `allocate_to_bucket`,
+//! `release_bucket_allocation`, `grant_bucket_access`, `revoke_bucket_access`,
+//! `offer_bucket_transfer`, `cancel_bucket_transfer`, `accept_bucket_transfer`, `migrate_bucket`,
+//! `reap_bucket`, `subscribe_top_up`, `cancel_top_up`, `deposit_asset`, `unlock_asset_deposit`,
+//! `withdraw_unlocked_asset_deposit`, `set_payment_asset`.
 
-// Executed Command:
+// Command to regenerate the weights:
 // ./target/release/cere
 // benchmark
 // pallet
@@ -35,6 +39,21 @@ pub trait WeightInfo {
 	fn withdraw_unlocked_deposit_kill() -> Weight;
 	fn set_bucket_params() -> Weight;
 	fn remove_bucket() -> Weight;
+	fn allocate_to_bucket() -> Weight;
+	fn release_bucket_allocation() -> Weight;
+	fn grant_bucket_access() -> Weight;
+	fn revoke_bucket_access() -> Weight;
+	fn offer_bucket_transfer() -> Weight;
+	fn cancel_bucket_transfer() -> Weight;
+	fn accept_bucket_transfer() -> Weight;
+	fn migrate_bucket() -> Weight;
+	fn reap_bucket() -> Weight;
+	fn subscribe_top_up() -> Weight;
+	fn cancel_top_up() -> Weight;
+	fn deposit_asset() -> Weight;
+	fn unlock_asset_deposit() -> Weight;
+	fn withdraw_unlocked_asset_deposit() -> Weight;
+	fn set_payment_asset() -> Weight;
 }
 
 /// Weights for pallet_ddc_customers using the Substrate node and recommended hardware.
@@ -44,12 +63,24 @@ impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
 	// Proof Skipped: DdcCustomers BucketsCount (max_values: Some(1), max_size: None, mode: Measured)
 	// Storage: DdcClusters Clusters (r:1 w:0)
 	// Proof Skipped: DdcClusters Clusters (max_values: None, max_size: None, mode: Measured)
+	// Storage: DdcPayouts DebtorCustomers (r:1 w:0)
+	// Proof Skipped: DdcPayouts DebtorCustomers (max_values: None, max_size: None, mode: Measured)
+	// Storage: DdcPayouts DebtorBucketOwners (r:1 w:0)
+	// Proof Skipped: DdcPayouts DebtorBucketOwners (max_values: None, max_size: None, mode: Measured)
+	// Storage: DdcCustomers BucketBudgets (r:1 w:1)
+	// Proof Skipped: DdcCustomers BucketBudgets (max_values: None, max_size: None, mode: Measured)
 	// Storage: DdcCustomers Buckets (r:0 w:1)
 	// Proof Skipped: DdcCustomers Buckets (max_values: None, max_size: None, mode: Measured)
+	// Storage: DdcClusters ClustersCapabilities (r:1 w:0)
+	// Proof Skipped: DdcClusters ClustersCapabilities (max_values: None, max_size: None, mode: Measured)
+	// Storage: System Account (r:1 w:1)
+	// Proof: System Account (max_values: None, max_size: Some(128), added: 2603, mode: MaxEncodedLen)
+	// Storage: DdcCustomers BucketDeposits (r:0 w:1)
+	// Proof Skipped: DdcCustomers BucketDeposits (max_values: None, max_size: None, mode: Measured)
 	fn create_bucket() -> Weight {
-		Weight::from_parts(39_599_000_u64, 0)
-			.saturating_add(T::DbWeight::get().reads(2_u64))
-			.saturating_add(T::DbWeight::get().writes(2_u64))
+		Weight::from_parts(71_934_000_u64, 0)
+			.saturating_add(T::DbWeight::get().reads(7_u64))
+			.saturating_add(T::DbWeight::get().writes(5_u64))
 	}
 	// Storage: DdcCustomers Ledger (r:1 w:1)
 	// Proof Skipped: DdcCustomers Ledger (max_values: None, max_size: None, mode: Measured)
@@ -96,15 +127,212 @@ impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
 	}
 	// Storage: DdcCustomers Buckets (r:1 w:1)
 	// Proof Skipped: DdcCustomers Buckets (max_values: None, max_size: None, mode: Measured)
+	// Storage: DdcCustomers BucketGrants (r:1 w:0)
+	// Proof Skipped: DdcCustomers BucketGrants (max_values: None, max_size: None, mode: Measured)
+	// Storage: DdcCustomers BucketBudgets (r:1 w:1)
+	// Proof Skipped: DdcCustomers BucketBudgets (max_values: None, max_size: None, mode: Measured)
+	// Storage: DdcClusters ClustersCapabilities (r:1 w:0)
+	// Proof Skipped: DdcClusters ClustersCapabilities (max_values: None, max_size: None, mode: Measured)
 	fn set_bucket_params() -> Weight {
-		Weight::from_parts(31_820_000_u64, 0)
+		Weight::from_parts(37_208_000_u64, 0)
+			.saturating_add(T::DbWeight::get().reads(4_u64))
+			.saturating_add(T::DbWeight::get().writes(2_u64))
+	}
+	// Storage: DdcCustomers Buckets (r:1 w:1)
+	// Proof Skipped: DdcCustomers Buckets (max_values: None, max_size: None, mode: Measured)
+	// Storage: DdcPayouts LastFinalizedEras (r:1 w:0)
+	// Proof Skipped: DdcPayouts LastFinalizedEras (max_values: None, max_size: None, mode: Measured)
+	// Storage: DdcCustomers ReapingQueueRange (r:1 w:1)
+	// Proof Skipped: DdcCustomers ReapingQueueRange (max_values: Some(1), max_size: None, mode: Measured)
+	// Storage: DdcCustomers RemovedBuckets (r:0 w:1)
+	// Proof Skipped: DdcCustomers RemovedBuckets (max_values: None, max_size: None, mode: Measured)
+	// Storage: DdcCustomers ReapingQueue (r:0 w:1)
+	// Proof Skipped: DdcCustomers ReapingQueue (max_values: None, max_size: None, mode: Measured)
+	fn remove_bucket() -> Weight {
+		Weight::from_parts(44_935_000_u64, 0)
+			.saturating_add(T::DbWeight::get().reads(3_u64))
+			.saturating_add(T::DbWeight::get().writes(4_u64))
+	}
+	// Storage: DdcCustomers Buckets (r:1 w:0)
+	// Proof Skipped: DdcCustomers Buckets (max_values: None, max_size: None, mode: Measured)
+	// Storage: DdcCustomers BucketGrants (r:1 w:1)
+	// Proof Skipped: DdcCustomers BucketGrants (max_values: None, max_size: None, mode: Measured)
+	// Storage: DdcCustomers BucketBudgets (r:1 w:1)
+	// Proof Skipped: DdcCustomers BucketBudgets (max_values: None, max_size: None, mode: Measured)
+	// Storage: DdcCustomers Ledger (r:1 w:1)
+	// Proof Skipped: DdcCustomers Ledger (max_values: None, max_size: None, mode: Measured)
+	fn allocate_to_bucket() -> Weight {
+		Weight::from_parts(49_874_000_u64, 0)
+			.saturating_add(T::DbWeight::get().reads(4_u64))
+			.saturating_add(T::DbWeight::get().writes(3_u64))
+	}
+	// Storage: DdcCustomers Buckets (r:1 w:0)
+	// Proof Skipped: DdcCustomers Buckets (max_values: None, max_size: None, mode: Measured)
+	// Storage: DdcCustomers BucketGrants (r:1 w:0)
+	// Proof Skipped: DdcCustomers BucketGrants (max_values: None, max_size: None, mode: Measured)
+	// Storage: DdcCustomers BucketBudgets (r:1 w:1)
+	// Proof Skipped: DdcCustomers BucketBudgets (max_values: None, max_size: None, mode: Measured)
+	// Storage: DdcCustomers Ledger (r:1 w:1)
+	// Proof Skipped: DdcCustomers Ledger (max_values: None, max_size: None, mode: Measured)
+	fn release_bucket_allocation() -> Weight {
+		Weight::from_parts(44_512_000_u64, 0)
+			.saturating_add(T::DbWeight::get().reads(4_u64))
+			.saturating_add(T::DbWeight::get().writes(2_u64))
+	}
+	// Storage: DdcCustomers Buckets (r:1 w:0)
+	// Proof Skipped: DdcCustomers Buckets (max_values: None, max_size: None, mode: Measured)
+	// Storage: DdcCustomers BucketGrants (r:1 w:1)
+	// Proof Skipped: DdcCustomers BucketGrants (max_values: None, max_size: None, mode: Measured)
+	// Storage: DdcCustomers BucketGrantsCount (r:1 w:1)
+	// Proof Skipped: DdcCustomers BucketGrantsCount (max_values: None, max_size: None, mode: Measured)
+	fn grant_bucket_access() -> Weight {
+		Weight::from_parts(38_725_000_u64, 0)
+			.saturating_add(T::DbWeight::get().reads(3_u64))
+			.saturating_add(T::DbWeight::get().writes(2_u64))
+	}
+	// Storage: DdcCustomers Buckets (r:1 w:0)
+	// Proof Skipped: DdcCustomers Buckets (max_values: None, max_size: None, mode: Measured)
+	// Storage: DdcCustomers BucketGrants (r:2 w:1)
+	// Proof Skipped: DdcCustomers BucketGrants (max_values: None, max_size: None, mode: Measured)
+	// Storage: DdcCustomers BucketGrantsCount (r:1 w:1)
+	// Proof Skipped: DdcCustomers BucketGrantsCount (max_values: None, max_size: None, mode: Measured)
+	fn revoke_bucket_access() -> Weight {
+		Weight::from_parts(41_660_000_u64, 0)
+			.saturating_add(T::DbWeight::get().reads(4_u64))
+			.saturating_add(T::DbWeight::get().writes(2_u64))
+	}
+	// Storage: DdcCustomers Buckets (r:1 w:0)
+	// Proof Skipped: DdcCustomers Buckets (max_values: None, max_size: None, mode: Measured)
+	// Storage: DdcCustomers BucketTransfers (r:0 w:1)
+	// Proof Skipped: DdcCustomers BucketTransfers (max_values: None, max_size: None, mode: Measured)
+	fn offer_bucket_transfer() -> Weight {
+		Weight::from_parts(30_472_000_u64, 0)
 			.saturating_add(T::DbWeight::get().reads(1_u64))
 			.saturating_add(T::DbWeight::get().writes(1_u64))
 	}
+	// Storage: DdcCustomers Buckets (r:1 w:0)
+	// Proof Skipped: DdcCustomers Buckets (max_values: None, max_size: None, mode: Measured)
+	// Storage: DdcCustomers BucketTransfers (r:1 w:1)
+	// Proof Skipped: DdcCustomers BucketTransfers (max_values: None, max_size: None, mode: Measured)
+	fn cancel_bucket_transfer() -> Weight {
+		Weight::from_parts(31_905_000_u64, 0)
+			.saturating_add(T::DbWeight::get().reads(2_u64))
+			.saturating_add(T::DbWeight::get().writes(1_u64))
+	}
 	// Storage: DdcCustomers Buckets (r:1 w:1)
 	// Proof Skipped: DdcCustomers Buckets (max_values: None, max_size: None, mode: Measured)
-	fn remove_bucket() -> Weight {
-		Weight::from_parts(34_070_000_u64, 0)
+	// Storage: DdcCustomers BucketTransfers (r:1 w:1)
+	// Proof Skipped: DdcCustomers BucketTransfers (max_values: None, max_size: None, mode: Measured)
+	// Storage: DdcCustomers BucketBudgets (r:1 w:0)
+	// Proof Skipped: DdcCustomers BucketBudgets (max_values: None, max_size: None, mode: Measured)
+	// Storage: DdcPayouts DebtorBuckets (r:1 w:0)
+	// Proof Skipped: DdcPayouts DebtorBuckets (max_values: None, max_size: None, mode: Measured)
+	// Storage: DdcPayouts DebtorCustomers (r:1 w:0)
+	// Proof Skipped: DdcPayouts DebtorCustomers (max_values: None, max_size: None, mode: Measured)
+	// Storage: DdcPayouts DebtorBucketOwners (r:1 w:0)
+	// Proof Skipped: DdcPayouts DebtorBucketOwners (max_values: None, max_size: None, mode: Measured)
+	// Storage: DdcPayouts ActiveBillingReports (r:1 w:0)
+	// Proof Skipped: DdcPayouts ActiveBillingReports (max_values: None, max_size: None, mode: Measured)
+	// Storage: DdcCustomers BucketGrants (r:0 w:32)
+	// Proof Skipped: DdcCustomers BucketGrants (max_values: None, max_size: None, mode: Measured)
+	// Storage: DdcCustomers BucketGrantsCount (r:0 w:1)
+	// Proof Skipped: DdcCustomers BucketGrantsCount (max_values: None, max_size: None, mode: Measured)
+	fn accept_bucket_transfer() -> Weight {
+		Weight::from_parts(308_172_000_u64, 0)
+			.saturating_add(T::DbWeight::get().reads(7_u64))
+			.saturating_add(T::DbWeight::get().writes(35_u64))
+	}
+	// Storage: DdcCustomers Buckets (r:1 w:1)
+	// Proof Skipped: DdcCustomers Buckets (max_values: None, max_size: None, mode: Measured)
+	// Storage: DdcClusters Clusters (r:1 w:0)
+	// Proof Skipped: DdcClusters Clusters (max_values: None, max_size: None, mode: Measured)
+	// Storage: DdcClusters ClustersCapabilities (r:1 w:0)
+	// Proof Skipped: DdcClusters ClustersCapabilities (max_values: None, max_size: None, mode: Measured)
+	// Storage: DdcPayouts ActiveBillingReports (r:2 w:0)
+	// Proof Skipped: DdcPayouts ActiveBillingReports (max_values: None, max_size: None, mode: Measured)
+	fn migrate_bucket() -> Weight {
+		Weight::from_parts(47_206_000_u64, 0)
+			.saturating_add(T::DbWeight::get().reads(5_u64))
+			.saturating_add(T::DbWeight::get().writes(1_u64))
+	}
+	// Storage: DdcCustomers RemovedBuckets (r:1 w:1)
+	// Proof Skipped: DdcCustomers RemovedBuckets (max_values: None, max_size: None, mode: Measured)
+	// Storage: DdcCustomers Buckets (r:1 w:1)
+	// Proof Skipped: DdcCustomers Buckets (max_values: None, max_size: None, mode: Measured)
+	// Storage: DdcCustomers BucketBudgets (r:1 w:1)
+	// Proof Skipped: DdcCustomers BucketBudgets (max_values: None, max_size: None, mode: Measured)
+	// Storage: DdcCustomers Ledger (r:1 w:1)
+	// Proof Skipped: DdcCustomers Ledger (max_values: None, max_size: None, mode: Measured)
+	// Storage: DdcCustomers BucketDeposits (r:1 w:1)
+	// Proof Skipped: DdcCustomers BucketDeposits (max_values: None, max_size: None, mode: Measured)
+	// Storage: System Account (r:2 w:2)
+	// Proof: System Account (max_values: None, max_size: Some(128), added: 2603, mode: MaxEncodedLen)
+	// Storage: DdcCustomers BucketGrants (r:0 w:32)
+	// Proof Skipped: DdcCustomers BucketGrants (max_values: None, max_size: None, mode: Measured)
+	// Storage: DdcCustomers BucketGrantsCount (r:0 w:1)
+	// Proof Skipped: DdcCustomers BucketGrantsCount (max_values: None, max_size: None, mode: Measured)
+	// Storage: DdcCustomers BucketTransfers (r:0 w:1)
+	// Proof Skipped: DdcCustomers BucketTransfers (max_values: None, max_size: None, mode: Measured)
+	// Storage: DdcPayouts LastFinalizedEras (r:1 w:0)
+	// Proof Skipped: DdcPayouts LastFinalizedEras (max_values: None, max_size: None, mode: Measured)
+	fn reap_bucket() -> Weight {
+		Weight::from_parts(394_817_000_u64, 0)
+			.saturating_add(T::DbWeight::get().reads(8_u64))
+			.saturating_add(T::DbWeight::get().writes(41_u64))
+	}
+	// Storage: DdcCustomers Ledger (r:1 w:0)
+	// Proof Skipped: DdcCustomers Ledger (max_values: None, max_size: None, mode: Measured)
+	// Storage: DdcCustomers TopUpSubscriptions (r:1 w:1)
+	// Proof Skipped: DdcCustomers TopUpSubscriptions (max_values: None, max_size: None, mode: Measured)
+	fn subscribe_top_up() -> Weight {
+		Weight::from_parts(31_486_000_u64, 0)
+			.saturating_add(T::DbWeight::get().reads(2_u64))
+			.saturating_add(T::DbWeight::get().writes(1_u64))
+	}
+	// Storage: DdcCustomers TopUpSubscriptions (r:1 w:1)
+	// Proof Skipped: DdcCustomers TopUpSubscriptions (max_values: None, max_size: None, mode: Measured)
+	fn cancel_top_up() -> Weight {
+		Weight::from_parts(24_903_000_u64, 0)
+			.saturating_add(T::DbWeight::get().reads(1_u64))
+			.saturating_add(T::DbWeight::get().writes(1_u64))
+	}
+	// Storage: DdcCustomers AssetLedgers (r:1 w:1)
+	// Proof Skipped: DdcCustomers AssetLedgers (max_values: None, max_size: None, mode: Measured)
+	// Storage: Assets Asset (r:1 w:1)
+	// Proof: Assets Asset (max_values: None, max_size: Some(210), added: 2685, mode: MaxEncodedLen)
+	// Storage: Assets Account (r:2 w:2)
+	// Proof: Assets Account (max_values: None, max_size: Some(134), added: 2609, mode: MaxEncodedLen)
+	fn deposit_asset() -> Weight {
+		Weight::from_parts(68_413_000_u64, 0)
+			.saturating_add(T::DbWeight::get().reads(4_u64))
+			.saturating_add(T::DbWeight::get().writes(4_u64))
+	}
+	// Storage: DdcCustomers AssetLedgers (r:1 w:1)
+	// Proof Skipped: DdcCustomers AssetLedgers (max_values: None, max_size: None, mode: Measured)
+	fn unlock_asset_deposit() -> Weight {
+		Weight::from_parts(27_612_000_u64, 0)
+			.saturating_add(T::DbWeight::get().reads(1_u64))
+			.saturating_add(T::DbWeight::get().writes(1_u64))
+	}
+	// Storage: DdcCustomers AssetLedgers (r:1 w:1)
+	// Proof Skipped: DdcCustomers AssetLedgers (max_values: None, max_size: None, mode: Measured)
+	// Storage: DdcCustomers PaymentAssets (r:1 w:1)
+	// Proof Skipped: DdcCustomers PaymentAssets (max_values: None, max_size: None, mode: Measured)
+	// Storage: Assets Asset (r:1 w:1)
+	// Proof: Assets Asset (max_values: None, max_size: Some(210), added: 2685, mode: MaxEncodedLen)
+	// Storage: Assets Account (r:2 w:2)
+	// Proof: Assets Account (max_values: None, max_size: Some(134), added: 2609, mode: MaxEncodedLen)
+	fn withdraw_unlocked_asset_deposit() -> Weight {
+		Weight::from_parts(71_935_000_u64, 0)
+			.saturating_add(T::DbWeight::get().reads(5_u64))
+			.saturating_add(T::DbWeight::get().writes(5_u64))
+	}
+	// Storage: DdcCustomers AssetLedgers (r:1 w:0)
+	// Proof Skipped: DdcCustomers AssetLedgers (max_values: None, max_size: None, mode: Measured)
+	// Storage: DdcCustomers PaymentAssets (r:0 w:1)
+	// Proof Skipped: DdcCustomers PaymentAssets (max_values: None, max_size: None, mode: Measured)
+	fn set_payment_asset() -> Weight {
+		Weight::from_parts(22_178_000_u64, 0)
 			.saturating_add(T::DbWeight::get().reads(1_u64))
 			.saturating_add(T::DbWeight::get().writes(1_u64))
 	}
@@ -116,12 +344,24 @@ impl WeightInfo for () {
 	// Proof Skipped: DdcCustomers BucketsCount (max_values: Some(1), max_size: None, mode: Measured)
 	// Storage: DdcClusters Clusters (r:1 w:0)
 	// Proof Skipped: DdcClusters Clusters (max_values: None, max_size: None, mode: Measured)
+	// Storage: DdcPayouts DebtorCustomers (r:1 w:0)
+	// Proof Skipped: DdcPayouts DebtorCustomers (max_values: None, max_size: None, mode: Measured)
+	// Storage: DdcPayouts DebtorBucketOwners (r:1 w:0)
+	// Proof Skipped: DdcPayouts DebtorBucketOwners (max_values: None, max_size: None, mode: Measured)
+	// Storage: DdcCustomers BucketBudgets (r:1 w:1)
+	// Proof Skipped: DdcCustomers BucketBudgets (max_values: None, max_size: None, mode: Measured)
 	// Storage: DdcCustomers Buckets (r:0 w:1)
 	// Proof Skipped: DdcCustomers Buckets (max_values: None, max_size: None, mode: Measured)
+	// Storage: DdcClusters ClustersCapabilities (r:1 w:0)
+	// Proof Skipped: DdcClusters ClustersCapabilities (max_values: None, max_size: None, mode: Measured)
+	// Storage: System Account (r:1 w:1)
+	// Proof: System Account (max_values: None, max_size: Some(128), added: 2603, mode: MaxEncodedLen)
+	// Storage: DdcCustomers BucketDeposits (r:0 w:1)
+	// Proof Skipped: DdcCustomers BucketDeposits (max_values: None, max_size: None, mode: Measured)
 	fn create_bucket() -> Weight {
-		Weight::from_parts(39_599_000_u64, 0)
-			.saturating_add(RocksDbWeight::get().reads(2_u64))
-			.saturating_add(RocksDbWeight::get().writes(2_u64))
+		Weight::from_parts(71_934_000_u64, 0)
+			.saturating_add(RocksDbWeight::get().reads(7_u64))
+			.saturating_add(RocksDbWeight::get().writes(5_u64))
 	}
 	// Storage: DdcCustomers Ledger (r:1 w:1)
 	// Proof Skipped: DdcCustomers Ledger (max_values: None, max_size: None, mode: Measured)
@@ -168,15 +408,212 @@ impl WeightInfo for () {
 	}
 	// Storage: DdcCustomers Buckets (r:1 w:1)
 	// Proof Skipped: DdcCustomers Buckets (max_values: None, max_size: None, mode: Measured)
+	// Storage: DdcCustomers BucketGrants (r:1 w:0)
+	// Proof Skipped: DdcCustomers BucketGrants (max_values: None, max_size: None, mode: Measured)
+	// Storage: DdcCustomers BucketBudgets (r:1 w:1)
+	// Proof Skipped: DdcCustomers BucketBudgets (max_values: None, max_size: None, mode: Measured)
+	// Storage: DdcClusters ClustersCapabilities (r:1 w:0)
+	// Proof Skipped: DdcClusters ClustersCapabilities (max_values: None, max_size: None, mode: Measured)
 	fn set_bucket_params() -> Weight {
-		Weight::from_parts(31_820_000_u64, 0)
+		Weight::from_parts(37_208_000_u64, 0)
+			.saturating_add(RocksDbWeight::get().reads(4_u64))
+			.saturating_add(RocksDbWeight::get().writes(2_u64))
+	}
+	// Storage: DdcCustomers Buckets (r:1 w:1)
+	// Proof Skipped: DdcCustomers Buckets (max_values: None, max_size: None, mode: Measured)
+	// Storage: DdcPayouts LastFinalizedEras (r:1 w:0)
+	// Proof Skipped: DdcPayouts LastFinalizedEras (max_values: None, max_size: None, mode: Measured)
+	// Storage: DdcCustomers ReapingQueueRange (r:1 w:1)
+	// Proof Skipped: DdcCustomers ReapingQueueRange (max_values: Some(1), max_size: None, mode: Measured)
+	// Storage: DdcCustomers RemovedBuckets (r:0 w:1)
+	// Proof Skipped: DdcCustomers RemovedBuckets (max_values: None, max_size: None, mode: Measured)
+	// Storage: DdcCustomers ReapingQueue (r:0 w:1)
+	// Proof Skipped: DdcCustomers ReapingQueue (max_values: None, max_size: None, mode: Measured)
+	fn remove_bucket() -> Weight {
+		Weight::from_parts(44_935_000_u64, 0)
+			.saturating_add(RocksDbWeight::get().reads(3_u64))
+			.saturating_add(RocksDbWeight::get().writes(4_u64))
+	}
+	// Storage: DdcCustomers Buckets (r:1 w:0)
+	// Proof Skipped: DdcCustomers Buckets (max_values: None, max_size: None, mode: Measured)
+	// Storage: DdcCustomers BucketGrants (r:1 w:1)
+	// Proof Skipped: DdcCustomers BucketGrants (max_values: None, max_size: None, mode: Measured)
+	// Storage: DdcCustomers BucketBudgets (r:1 w:1)
+	// Proof Skipped: DdcCustomers BucketBudgets (max_values: None, max_size: None, mode: Measured)
+	// Storage: DdcCustomers Ledger (r:1 w:1)
+	// Proof Skipped: DdcCustomers Ledger (max_values: None, max_size: None, mode: Measured)
+	fn allocate_to_bucket() -> Weight {
+		Weight::from_parts(49_874_000_u64, 0)
+			.saturating_add(RocksDbWeight::get().reads(4_u64))
+			.saturating_add(RocksDbWeight::get().writes(3_u64))
+	}
+	// Storage: DdcCustomers Buckets (r:1 w:0)
+	// Proof Skipped: DdcCustomers Buckets (max_values: None, max_size: None, mode: Measured)
+	// Storage: DdcCustomers BucketGrants (r:1 w:0)
+	// Proof Skipped: DdcCustomers BucketGrants (max_values: None, max_size: None, mode: Measured)
+	// Storage: DdcCustomers BucketBudgets (r:1 w:1)
+	// Proof Skipped: DdcCustomers BucketBudgets (max_values: None, max_size: None, mode: Measured)
+	// Storage: DdcCustomers Ledger (r:1 w:1)
+	// Proof Skipped: DdcCustomers Ledger (max_values: None, max_size: None, mode: Measured)
+	fn release_bucket_allocation() -> Weight {
+		Weight::from_parts(44_512_000_u64, 0)
+			.saturating_add(RocksDbWeight::get().reads(4_u64))
+			.saturating_add(RocksDbWeight::get().writes(2_u64))
+	}
+	// Storage: DdcCustomers Buckets (r:1 w:0)
+	// Proof Skipped: DdcCustomers Buckets (max_values: None, max_size: None, mode: Measured)
+	// Storage: DdcCustomers BucketGrants (r:1 w:1)
+	// Proof Skipped: DdcCustomers BucketGrants (max_values: None, max_size: None, mode: Measured)
+	// Storage: DdcCustomers BucketGrantsCount (r:1 w:1)
+	// Proof Skipped: DdcCustomers BucketGrantsCount (max_values: None, max_size: None, mode: Measured)
+	fn grant_bucket_access() -> Weight {
+		Weight::from_parts(38_725_000_u64, 0)
+			.saturating_add(RocksDbWeight::get().reads(3_u64))
+			.saturating_add(RocksDbWeight::get().writes(2_u64))
+	}
+	// Storage: DdcCustomers Buckets (r:1 w:0)
+	// Proof Skipped: DdcCustomers Buckets (max_values: None, max_size: None, mode: Measured)
+	// Storage: DdcCustomers BucketGrants (r:2 w:1)
+	// Proof Skipped: DdcCustomers BucketGrants (max_values: None, max_size: None, mode: Measured)
+	// Storage: DdcCustomers BucketGrantsCount (r:1 w:1)
+	// Proof Skipped: DdcCustomers BucketGrantsCount (max_values: None, max_size: None, mode: Measured)
+	fn revoke_bucket_access() -> Weight {
+		Weight::from_parts(41_660_000_u64, 0)
+			.saturating_add(RocksDbWeight::get().reads(4_u64))
+			.saturating_add(RocksDbWeight::get().writes(2_u64))
+	}
+	// Storage: DdcCustomers Buckets (r:1 w:0)
+	// Proof Skipped: DdcCustomers Buckets (max_values: None, max_size: None, mode: Measured)
+	// Storage: DdcCustomers BucketTransfers (r:0 w:1)
+	// Proof Skipped: DdcCustomers BucketTransfers (max_values: None, max_size: None, mode: Measured)
+	fn offer_bucket_transfer() -> Weight {
+		Weight::from_parts(30_472_000_u64, 0)
 			.saturating_add(RocksDbWeight::get().reads(1_u64))
 			.saturating_add(RocksDbWeight::get().writes(1_u64))
 	}
+	// Storage: DdcCustomers Buckets (r:1 w:0)
+	// Proof Skipped: DdcCustomers Buckets (max_values: None, max_size: None, mode: Measured)
+	// Storage: DdcCustomers BucketTransfers (r:1 w:1)
+	// Proof Skipped: DdcCustomers BucketTransfers (max_values: None, max_size: None, mode: Measured)
+	fn cancel_bucket_transfer() -> Weight {
+		Weight::from_parts(31_905_000_u64, 0)
+			.saturating_add(RocksDbWeight::get().reads(2_u64))
+			.saturating_add(RocksDbWeight::get().writes(1_u64))
+	}
 	// Storage: DdcCustomers Buckets (r:1 w:1)
 	// Proof Skipped: DdcCustomers Buckets (max_values: None, max_size: None, mode: Measured)
-	fn remove_bucket() -> Weight {
-		Weight::from_parts(34_070_000_u64, 0)
+	// Storage: DdcCustomers BucketTransfers (r:1 w:1)
+	// Proof Skipped: DdcCustomers BucketTransfers (max_values: None, max_size: None, mode: Measured)
+	// Storage: DdcCustomers BucketBudgets (r:1 w:0)
+	// Proof Skipped: DdcCustomers BucketBudgets (max_values: None, max_size: None, mode: Measured)
+	// Storage: DdcPayouts DebtorBuckets (r:1 w:0)
+	// Proof Skipped: DdcPayouts DebtorBuckets (max_values: None, max_size: None, mode: Measured)
+	// Storage: DdcPayouts DebtorCustomers (r:1 w:0)
+	// Proof Skipped: DdcPayouts DebtorCustomers (max_values: None, max_size: None, mode: Measured)
+	// Storage: DdcPayouts DebtorBucketOwners (r:1 w:0)
+	// Proof Skipped: DdcPayouts DebtorBucketOwners (max_values: None, max_size: None, mode: Measured)
+	// Storage: DdcPayouts ActiveBillingReports (r:1 w:0)
+	// Proof Skipped: DdcPayouts ActiveBillingReports (max_values: None, max_size: None, mode: Measured)
+	// Storage: DdcCustomers BucketGrants (r:0 w:32)
+	// Proof Skipped: DdcCustomers BucketGrants (max_values: None, max_size: None, mode: Measured)
+	// Storage: DdcCustomers BucketGrantsCount (r:0 w:1)
+	// Proof Skipped: DdcCustomers BucketGrantsCount (max_values: None, max_size: None, mode: Measured)
+	fn accept_bucket_transfer() -> Weight {
+		Weight::from_parts(308_172_000_u64, 0)
+			.saturating_add(RocksDbWeight::get().reads(7_u64))
+			.saturating_add(RocksDbWeight::get().writes(35_u64))
+	}
+	// Storage: DdcCustomers Buckets (r:1 w:1)
+	// Proof Skipped: DdcCustomers Buckets (max_values: None, max_size: None, mode: Measured)
+	// Storage: DdcClusters Clusters (r:1 w:0)
+	// Proof Skipped: DdcClusters Clusters (max_values: None, max_size: None, mode: Measured)
+	// Storage: DdcClusters ClustersCapabilities (r:1 w:0)
+	// Proof Skipped: DdcClusters ClustersCapabilities (max_values: None, max_size: None, mode: Measured)
+	// Storage: DdcPayouts ActiveBillingReports (r:2 w:0)
+	// Proof Skipped: DdcPayouts ActiveBillingReports (max_values: None, max_size: None, mode: Measured)
+	fn migrate_bucket() -> Weight {
+		Weight::from_parts(47_206_000_u64, 0)
+			.saturating_add(RocksDbWeight::get().reads(5_u64))
+			.saturating_add(RocksDbWeight::get().writes(1_u64))
+	}
+	// Storage: DdcCustomers RemovedBuckets (r:1 w:1)
+	// Proof Skipped: DdcCustomers RemovedBuckets (max_values: None, max_size: None, mode: Measured)
+	// Storage: DdcCustomers Buckets (r:1 w:1)
+	// Proof Skipped: DdcCustomers Buckets (max_values: None, max_size: None, mode: Measured)
+	// Storage: DdcCustomers BucketBudgets (r:1 w:1)
+	// Proof Skipped: DdcCustomers BucketBudgets (max_values: None, max_size: None, mode: Measured)
+	// Storage: DdcCustomers Ledger (r:1 w:1)
+	// Proof Skipped: DdcCustomers Ledger (max_values: None, max_size: None, mode: Measured)
+	// Storage: DdcCustomers BucketDeposits (r:1 w:1)
+	// Proof Skipped: DdcCustomers BucketDeposits (max_values: None, max_size: None, mode: Measured)
+	// Storage: System Account (r:2 w:2)
+	// Proof: System Account (max_values: None, max_size: Some(128), added: 2603, mode: MaxEncodedLen)
+	// Storage: DdcCustomers BucketGrants (r:0 w:32)
+	// Proof Skipped: DdcCustomers BucketGrants (max_values: None, max_size: None, mode: Measured)
+	// Storage: DdcCustomers BucketGrantsCount (r:0 w:1)
+	// Proof Skipped: DdcCustomers BucketGrantsCount (max_values: None, max_size: None, mode: Measured)
+	// Storage: DdcCustomers BucketTransfers (r:0 w:1)
+	// Proof Skipped: DdcCustomers BucketTransfers (max_values: None, max_size: None, mode: Measured)
+	// Storage: DdcPayouts LastFinalizedEras (r:1 w:0)
+	// Proof Skipped: DdcPayouts LastFinalizedEras (max_values: None, max_size: None, mode: Measured)
+	fn reap_bucket() -> Weight {
+		Weight::from_parts(394_817_000_u64, 0)
+			.saturating_add(RocksDbWeight::get().reads(8_u64))
+			.saturating_add(RocksDbWeight::get().writes(41_u64))
+	}
+	// Storage: DdcCustomers Ledger (r:1 w:0)
+	// Proof Skipped: DdcCustomers Ledger (max_values: None, max_size: None, mode: Measured)
+	// Storage: DdcCustomers TopUpSubscriptions (r:1 w:1)
+	// Proof Skipped: DdcCustomers TopUpSubscriptions (max_values: None, max_size: None, mode: Measured)
+	fn subscribe_top_up() -> Weight {
+		Weight::from_parts(31_486_000_u64, 0)
+			.saturating_add(RocksDbWeight::get().reads(2_u64))
+			.saturating_add(RocksDbWeight::get().writes(1_u64))
+	}
+	// Storage: DdcCustomers TopUpSubscriptions (r:1 w:1)
+	// Proof Skipped: DdcCustomers TopUpSubscriptions (max_values: None, max_size: None, mode: Measured)
+	fn cancel_top_up() -> Weight {
+		Weight::from_parts(24_903_000_u64, 0)
+			.saturating_add(RocksDbWeight::get().reads(1_u64))
+			.saturating_add(RocksDbWeight::get().writes(1_u64))
+	}
+	// Storage: DdcCustomers AssetLedgers (r:1 w:1)
+	// Proof Skipped: DdcCustomers AssetLedgers (max_values: None, max_size: None, mode: Measured)
+	// Storage: Assets Asset (r:1 w:1)
+	// Proof: Assets Asset (max_values: None, max_size: Some(210), added: 2685, mode: MaxEncodedLen)
+	// Storage: Assets Account (r:2 w:2)
+	// Proof: Assets Account (max_values: None, max_size: Some(134), added: 2609, mode: MaxEncodedLen)
+	fn deposit_asset() -> Weight {
+		Weight::from_parts(68_413_000_u64, 0)
+			.saturating_add(RocksDbWeight::get().reads(4_u64))
+			.saturating_add(RocksDbWeight::get().writes(4_u64))
+	}
+	// Storage: DdcCustomers AssetLedgers (r:1 w:1)
+	// Proof Skipped: DdcCustomers AssetLedgers (max_values: None, max_size: None, mode: Measured)
+	fn unlock_asset_deposit() -> Weight {
+		Weight::from_parts(27_612_000_u64, 0)
+			.saturating_add(RocksDbWeight::get().reads(1_u64))
+			.saturating_add(RocksDbWeight::get().writes(1_u64))
+	}
+	// Storage: DdcCustomers AssetLedgers (r:1 w:1)
+	// Proof Skipped: DdcCustomers AssetLedgers (max_values: None, max_size: None, mode: Measured)
+	// Storage: DdcCustomers PaymentAssets (r:1 w:1)
+	// Proof Skipped: DdcCustomers PaymentAssets (max_values: None, max_size: None, mode: Measured)
+	// Storage: Assets Asset (r:1 w:1)
+	// Proof: Assets Asset (max_values: None, max_size: Some(210), added: 2685, mode: MaxEncodedLen)
+	// Storage: Assets Account (r:2 w:2)
+	// Proof: Assets Account (max_values: None, max_size: Some(134), added: 2609, mode: MaxEncodedLen)
+	fn withdraw_unlocked_asset_deposit() -> Weight {
+		Weight::from_parts(71_935_000_u64, 0)
+			.saturating_add(RocksDbWeight::get().reads(5_u64))
+			.saturating_add(RocksDbWeight::get().writes(5_u64))
+	}
+	// Storage: DdcCustomers AssetLedgers (r:1 w:0)
+	// Proof Skipped: DdcCustomers AssetLedgers (max_values: None, max_size: None, mode: Measured)
+	// Storage: DdcCustomers PaymentAssets (r:0 w:1)
+	// Proof Skipped: DdcCustomers PaymentAssets (max_values: None, max_size: None, mode: Measured)
+	fn set_payment_asset() -> Weight {
+		Weight::from_parts(22_178_000_u64, 0)
 			.saturating_add(RocksDbWeight::get().reads(1_u64))
 			.saturating_add(RocksDbWeight::get().writes(1_u64))
 	}
@@ -0,0 +1,247 @@
+//! RPC interface for the DDC Payouts pallet.
+
+use std::sync::Arc;
+
+use codec::Codec;
+use ddc_primitives::{ClusterId, DdcEra};
+use jsonrpsee::{
+	core::{async_trait, RpcResult},
+	proc_macros::rpc,
+	types::error::{CallError, ErrorObject},
+};
+pub use pallet_ddc_payouts_runtime_api::DdcPayoutsApi as DdcPayoutsRuntimeApi;
+use pallet_ddc_payouts_runtime_api::{BillingReportInfo, CustomerCharge, NodeUsage};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_rpc::number::NumberOrHex;
+use sp_runtime::traits::Block as BlockT;
+
+#[rpc(client, server)]
+pub trait DdcPayoutsApi<BlockHash, AccountId> {
+	/// Progress of the billing report of the cluster for the era, if it is active.
+	#[method(name = "ddcPayouts_getBillingReport")]
+	fn billing_report(
+		&self,
+		cluster_id: ClusterId,
+		era: DdcEra,
+		at: Option<BlockHash>,
+	) -> RpcResult<Option<BillingReport>>;
+
+	/// Progress of all the active billing reports of the cluster.
+	#[method(name = "ddcPayouts_getBillingReports")]
+	fn billing_reports(
+		&self,
+		cluster_id: ClusterId,
+		at: Option<BlockHash>,
+	) -> RpcResult<Vec<BillingReport>>;
+
+	/// Outstanding debt of the customer in the cluster.
+	#[method(name = "ddcPayouts_getCustomerDebt")]
+	fn customer_debt(
+		&self,
+		cluster_id: ClusterId,
+		customer_id: AccountId,
+		at: Option<BlockHash>,
+	) -> RpcResult<NumberOrHex>;
+
+	/// Outstanding debts of all the customers in the cluster.
+	#[method(name = "ddcPayouts_getCustomersDebts")]
+	fn customers_debts(
+		&self,
+		cluster_id: ClusterId,
+		at: Option<BlockHash>,
+	) -> RpcResult<Vec<CustomerDebt<AccountId>>>;
+}
+
+/// Charges of the customers by usage type.
+#[derive(Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Charge {
+	pub transfer: NumberOrHex,
+	pub storage: NumberOrHex,
+	pub puts: NumberOrHex,
+	pub gets: NumberOrHex,
+}
+
+impl From<CustomerCharge> for Charge {
+	fn from(charge: CustomerCharge) -> Self {
+		Self {
+			transfer: charge.transfer.into(),
+			storage: charge.storage.into(),
+			puts: charge.puts.into(),
+			gets: charge.gets.into(),
+		}
+	}
+}
+
+/// Usage of the nodes by usage type.
+#[derive(Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Usage {
+	pub transferred_bytes: u64,
+	pub stored_bytes: u64,
+	pub number_of_puts: u64,
+	pub number_of_gets: u64,
+}
+
+impl From<NodeUsage> for Usage {
+	fn from(usage: NodeUsage) -> Self {
+		Self {
+			transferred_bytes: usage.transferred_bytes,
+			stored_bytes: usage.stored_bytes,
+			number_of_puts: usage.number_of_puts,
+			number_of_gets: usage.number_of_gets,
+		}
+	}
+}
+
+/// Billing report progress, `state` is the numeric billing report state used by the DAC.
+#[derive(Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BillingReport {
+	pub era: DdcEra,
+	pub state: u8,
+	pub start_era: i64,
+	pub end_era: i64,
+	pub total_customer_charge: Charge,
+	pub total_distributed_reward: NumberOrHex,
+	pub total_node_usage: Usage,
+	pub charging_max_batch_index: u16,
+	pub charging_missing_batches: Vec<u16>,
+	pub rewarding_max_batch_index: u16,
+	pub rewarding_missing_batches: Vec<u16>,
+}
+
+impl BillingReport {
+	fn new(era: DdcEra, info: BillingReportInfo) -> Self {
+		Self {
+			era,
+			state: info.state as u8,
+			start_era: info.start_era,
+			end_era: info.end_era,
+			total_customer_charge: info.total_customer_charge.into(),
+			total_distributed_reward: info.total_distributed_reward.into(),
+			total_node_usage: info.total_node_usage.into(),
+			charging_max_batch_index: info.charging_max_batch_index,
+			charging_missing_batches: info.charging_missing_batches,
+			rewarding_max_batch_index: info.rewarding_max_batch_index,
+			rewarding_missing_batches: info.rewarding_missing_batches,
+		}
+	}
+}
+
+/// Outstanding debt of the customer.
+#[derive(Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CustomerDebt<AccountId> {
+	pub customer_id: AccountId,
+	pub debt: NumberOrHex,
+}
+
+/// Error type of this RPC api.
+pub enum Error {
+	/// The call to runtime failed.
+	RuntimeError,
+}
+
+impl From<Error> for i32 {
+	fn from(e: Error) -> i32 {
+		match e {
+			Error::RuntimeError => 1,
+		}
+	}
+}
+
+/// Provides RPC methods to query the DDC billing reports.
+pub struct DdcPayouts<C, Block> {
+	client: Arc<C>,
+	_marker: std::marker::PhantomData<Block>,
+}
+
+impl<C, Block> DdcPayouts<C, Block> {
+	/// Creates a new instance of the DdcPayouts RPC helper.
+	pub fn new(client: Arc<C>) -> Self {
+		Self { client, _marker: Default::default() }
+	}
+}
+
+fn runtime_error(message: &'static str, e: impl std::fmt::Display) -> jsonrpsee::core::Error {
+	CallError::Custom(ErrorObject::owned(Error::RuntimeError.into(), message, Some(e.to_string())))
+		.into()
+}
+
+#[async_trait]
+impl<C, Block, AccountId> DdcPayoutsApiServer<<Block as BlockT>::Hash, AccountId>
+	for DdcPayouts<C, Block>
+where
+	Block: BlockT,
+	C: ProvideRuntimeApi<Block> + HeaderBackend<Block> + Send + Sync + 'static,
+	C::Api: DdcPayoutsRuntimeApi<Block, AccountId>,
+	AccountId: Codec + Serialize + DeserializeOwned + Send + Sync + 'static,
+{
+	fn billing_report(
+		&self,
+		cluster_id: ClusterId,
+		era: DdcEra,
+		at: Option<Block::Hash>,
+	) -> RpcResult<Option<BillingReport>> {
+		let api = self.client.runtime_api();
+		let at_hash = at.unwrap_or_else(|| self.client.info().best_hash);
+
+		let info = api
+			.billing_report(at_hash, cluster_id, era)
+			.map_err(|e| runtime_error("Unable to query billing report.", e))?;
+
+		Ok(info.map(|info| BillingReport::new(era, info)))
+	}
+
+	fn billing_reports(
+		&self,
+		cluster_id: ClusterId,
+		at: Option<Block::Hash>,
+	) -> RpcResult<Vec<BillingReport>> {
+		let api = self.client.runtime_api();
+		let at_hash = at.unwrap_or_else(|| self.client.info().best_hash);
+
+		let reports = api
+			.billing_reports(at_hash, cluster_id)
+			.map_err(|e| runtime_error("Unable to query billing reports.", e))?;
+
+		Ok(reports.into_iter().map(|(era, info)| BillingReport::new(era, info)).collect())
+	}
+
+	fn customer_debt(
+		&self,
+		cluster_id: ClusterId,
+		customer_id: AccountId,
+		at: Option<Block::Hash>,
+	) -> RpcResult<NumberOrHex> {
+		let api = self.client.runtime_api();
+		let at_hash = at.unwrap_or_else(|| self.client.info().best_hash);
+
+		let debt = api
+			.customer_debt(at_hash, cluster_id, customer_id)
+			.map_err(|e| runtime_error("Unable to query customer debt.", e))?;
+
+		Ok(debt.into())
+	}
+
+	fn customers_debts(
+		&self,
+		cluster_id: ClusterId,
+		at: Option<Block::Hash>,
+	) -> RpcResult<Vec<CustomerDebt<AccountId>>> {
+		let api = self.client.runtime_api();
+		let at_hash = at.unwrap_or_else(|| self.client.info().best_hash);
+
+		let debts = api
+			.customers_debts(at_hash, cluster_id)
+			.map_err(|e| runtime_error("Unable to query customers debts.", e))?;
+
+		Ok(debts
+			.into_iter()
+			.map(|(customer_id, debt)| CustomerDebt { customer_id, debt: debt.into() })
+			.collect())
+	}
+}
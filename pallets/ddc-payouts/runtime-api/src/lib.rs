@@ -0,0 +1,24 @@
+//! Runtime API definition for the DDC Payouts pallet.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::Codec;
+use ddc_primitives::{ClusterId, DdcEra};
+pub use pallet_ddc_payouts::{BillingReportInfo, CustomerCharge, NodeUsage, State};
+use sp_std::prelude::*;
+
+sp_api::decl_runtime_apis! {
+	pub trait DdcPayoutsApi<AccountId>
+	where
+		AccountId: Codec,
+	{
+		/// Progress of the billing report of the cluster for the era, if it is active.
+		fn billing_report(cluster_id: ClusterId, era: DdcEra) -> Option<BillingReportInfo>;
+		/// Progress of all the active billing reports of the cluster, by era.
+		fn billing_reports(cluster_id: ClusterId) -> Vec<(DdcEra, BillingReportInfo)>;
+		/// Outstanding debt of the customer in the cluster.
+		fn customer_debt(cluster_id: ClusterId, customer_id: AccountId) -> u128;
+		/// Outstanding debts of all the customers in the cluster.
+		fn customers_debts(cluster_id: ClusterId) -> Vec<(AccountId, u128)>;
+	}
+}
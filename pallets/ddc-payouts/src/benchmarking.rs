@@ -1,6 +1,8 @@
 //! DdcPayouts pallet benchmarking.
 
-use ddc_primitives::{ClusterGovParams, ClusterId, ClusterParams};
+use ddc_primitives::{
+	ClusterGovParams, ClusterId, ClusterParams, NodePubKey, PaymentSource, StorageNodePubKey,
+};
 pub use frame_benchmarking::{account, benchmarks, whitelist_account};
 use frame_system::RawOrigin;
 use sp_runtime::Perquintill;
@@ -22,7 +24,7 @@ fn create_account<T: Config>(name: &'static str, idx: u32, seed: u32) -> T::Acco
 }
 
 fn authorize_account<T: Config>(account: T::AccountId) {
-	AuthorisedCaller::<T>::put(account);
+	T::ValidatorVisitor::setup_validators(vec![account]);
 }
 
 fn endow_account<T: Config>(account: &T::AccountId, amount: u128) {
@@ -69,6 +71,7 @@ fn create_default_cluster<T: Config>(cluster_id: ClusterId) {
 		unit_per_mb_streamed: CERE,
 		unit_per_put_request: CERE,
 		unit_per_get_request: CERE,
+		billing_period: 2_630_016,
 		..Default::default()
 	};
 
@@ -88,6 +91,8 @@ struct BillingReportParams {
 	total_customer_charge: CustomerCharge,
 	total_distributed_reward: u128,
 	total_node_usage: NodeUsage,
+	customers_merkle_root: H256,
+	nodes_merkle_root: H256,
 	charging_max_batch_index: BatchIndex,
 	charging_processed_batches: BoundedBTreeSet<BatchIndex, MaxBatchesCount>,
 	rewarding_max_batch_index: BatchIndex,
@@ -99,6 +104,13 @@ fn create_billing_report<T: Config>(params: BillingReportParams) {
 	let start_era: i64 = 1_000_000_000;
 	let end_era: i64 = start_era + (30.44 * 24.0 * 3600.0) as i64;
 
+	// the processed batches add up to the committed usage
+	let rewarded_usage = if params.rewarding_processed_batches.is_empty() {
+		NodeUsage::default()
+	} else {
+		params.total_node_usage.clone()
+	};
+
 	let billing_report = BillingReport::<T> {
 		vault,
 		start_era,
@@ -107,10 +119,15 @@ fn create_billing_report<T: Config>(params: BillingReportParams) {
 		total_customer_charge: params.total_customer_charge,
 		total_distributed_reward: params.total_distributed_reward,
 		total_node_usage: params.total_node_usage,
+		customers_merkle_root: params.customers_merkle_root,
+		nodes_merkle_root: params.nodes_merkle_root,
+		total_customer_usage: CustomerUsage::default(),
 		charging_max_batch_index: params.charging_max_batch_index,
 		charging_processed_batches: params.charging_processed_batches,
+		charged_usage: CustomerUsage::default(),
 		rewarding_max_batch_index: params.rewarding_max_batch_index,
 		rewarding_processed_batches: params.rewarding_processed_batches,
+		rewarded_usage,
 	};
 
 	ActiveBillingReports::<T>::insert(params.cluster_id, params.era, billing_report);
@@ -118,12 +135,15 @@ fn create_billing_report<T: Config>(params: BillingReportParams) {
 
 benchmarks! {
 
-	set_authorised_caller {
-		 let dac_account = create_account::<T>("dac_account", 0, 0);
+	set_validator_threshold {
+		let threshold = 3;
+		T::ValidatorVisitor::setup_validators(
+			(0..threshold).map(|i| create_account::<T>("validator", i, i)).collect(),
+		);
 
-	}: _(RawOrigin::Root, dac_account.clone())
+	}: _(RawOrigin::Root, threshold)
 	verify {
-		assert_eq!(AuthorisedCaller::<T>::get(), Some(dac_account));
+		assert_eq!(ValidatorThreshold::<T>::get(), Some(threshold));
 	}
 
 	begin_billing_report {
@@ -138,11 +158,33 @@ benchmarks! {
 		let dac_account = create_dac_account::<T>();
 		whitelist_account!(dac_account);
 
-	}: _(RawOrigin::Signed(dac_account.clone()), cluster_id, era, start_era, end_era)
+		let customers_merkle_root = H256::repeat_byte(1);
+		let nodes_merkle_root = H256::repeat_byte(2);
+		let max_batch_index: BatchIndex = 10;
+		let total_customer_usage = CustomerUsage {
+			transferred_bytes: 200000000, // 200 mb
+			stored_bytes: 100000000, // 100 mb
+			number_of_gets: 10, // 10 gets
+			number_of_puts: 5, // 5 puts
+		};
+		let total_node_usage = NodeUsage {
+			transferred_bytes: 200000000, // 200 mb
+			stored_bytes: 100000000, // 100 mb
+			number_of_gets: 10, // 10 gets
+			number_of_puts: 5, // 5 puts
+		};
+
+	}: _(RawOrigin::Signed(dac_account.clone()), cluster_id, era, start_era, end_era, customers_merkle_root, nodes_merkle_root, max_batch_index, max_batch_index, total_customer_usage.clone(), total_node_usage.clone())
 	verify {
 		assert!(ActiveBillingReports::<T>::contains_key(cluster_id, era));
 		let billing_report = ActiveBillingReports::<T>::get(cluster_id, era).unwrap();
 		assert_eq!(billing_report.state, State::Initialized);
+		assert_eq!(billing_report.customers_merkle_root, customers_merkle_root);
+		assert_eq!(billing_report.nodes_merkle_root, nodes_merkle_root);
+		assert_eq!(billing_report.charging_max_batch_index, max_batch_index);
+		assert_eq!(billing_report.rewarding_max_batch_index, max_batch_index);
+		assert_eq!(billing_report.total_customer_usage, total_customer_usage);
+		assert_eq!(billing_report.total_node_usage, total_node_usage);
 	}
 
 	begin_charging_customers {
@@ -156,6 +198,8 @@ benchmarks! {
 		let charging_processed_batches : BoundedBTreeSet<BatchIndex, MaxBatchesCount> = BoundedBTreeSet::default();
 		let rewarding_max_batch_index = BatchIndex::default();
 		let rewarding_processed_batches : BoundedBTreeSet<BatchIndex, MaxBatchesCount> = BoundedBTreeSet::default();
+		let customers_merkle_root = H256::default();
+		let nodes_merkle_root = H256::default();
 
 		create_default_cluster::<T>(cluster_id);
 		create_billing_report::<T>(BillingReportParams {
@@ -165,6 +209,8 @@ benchmarks! {
 			total_customer_charge,
 			total_distributed_reward,
 			total_node_usage,
+			customers_merkle_root,
+			nodes_merkle_root,
 			charging_max_batch_index,
 			charging_processed_batches,
 			rewarding_max_batch_index,
@@ -174,14 +220,11 @@ benchmarks! {
 		let dac_account = create_dac_account::<T>();
 		whitelist_account!(dac_account);
 
-		let max_batch_index: BatchIndex = 10;
-
-	}: _(RawOrigin::Signed(dac_account.clone()), cluster_id, era, max_batch_index)
+	}: _(RawOrigin::Signed(dac_account.clone()), cluster_id, era)
 	verify {
 		assert!(ActiveBillingReports::<T>::contains_key(cluster_id, era));
 		let billing_report = ActiveBillingReports::<T>::get(cluster_id, era).unwrap();
 		assert_eq!(billing_report.state, State::ChargingCustomers);
-		assert_eq!(billing_report.charging_max_batch_index, max_batch_index);
 	}
 
 	send_charging_customers_batch {
@@ -198,25 +241,12 @@ benchmarks! {
 		let rewarding_max_batch_index = BatchIndex::default();
 		let rewarding_processed_batches : BoundedBTreeSet<BatchIndex, MaxBatchesCount> = BoundedBTreeSet::default();
 
-		let dac_account = create_dac_account::<T>();
-		whitelist_account!(dac_account);
-
 		create_default_cluster::<T>(cluster_id);
-		create_billing_report::<T>(BillingReportParams {
-			cluster_id,
-			era,
-			state,
-			total_customer_charge,
-			total_distributed_reward,
-			total_node_usage,
-			charging_max_batch_index,
-			charging_processed_batches,
-			rewarding_max_batch_index,
-			rewarding_processed_batches,
-		});
+		// repaid debts fund the rewards owed in the cluster first
+		TotalOwedRewards::<T>::insert(cluster_id, b as u128 * CERE);
 
 		let batch_index: BatchIndex = 0;
-		let payers: Vec<(T::AccountId, CustomerUsage)> = (0..b).map(|i| {
+		let payers: Vec<(T::AccountId, BucketId, CustomerUsage)> = (0..b).map(|i| {
 			let customer = create_account::<T>("customer", i, i);
 
 			if b % 2 == 0 {
@@ -226,6 +256,10 @@ benchmarks! {
 				// customer debt path
 				endow_customer::<T>(&customer, 10 * CERE);
 			}
+			let bucket_id = T::BucketVisitor::create_bucket(&cluster_id, customer.clone())
+				.expect("Bucket is not created");
+			// outstanding debt from a previous era is collected first
+			DebtorCustomers::<T>::insert(cluster_id, customer.clone(), CERE);
 
 			let customer_usage = CustomerUsage {
 				transferred_bytes: 200000000, // 200 mb
@@ -234,10 +268,31 @@ benchmarks! {
 				number_of_puts: 5, // 5 puts
 			};
 
-			(customer, customer_usage)
+			(customer, bucket_id, customer_usage)
 		}).collect();
+		let customers_merkle_root = merkle::batch_leaf(batch_index, &payers);
+		let nodes_merkle_root = H256::default();
 
-	}: _(RawOrigin::Signed(dac_account.clone()), cluster_id, era, batch_index, payers)
+		let dac_account = create_dac_account::<T>();
+		whitelist_account!(dac_account);
+
+		create_billing_report::<T>(BillingReportParams {
+			cluster_id,
+			era,
+			state,
+			total_customer_charge,
+			total_distributed_reward,
+			total_node_usage,
+			customers_merkle_root,
+			nodes_merkle_root,
+			charging_max_batch_index,
+			charging_processed_batches,
+			rewarding_max_batch_index,
+			rewarding_processed_batches,
+		});
+
+
+	}: _(RawOrigin::Signed(dac_account.clone()), cluster_id, era, batch_index, payers, vec![])
 	verify {
 		assert!(ActiveBillingReports::<T>::contains_key(cluster_id, era));
 		let billing_report = ActiveBillingReports::<T>::get(cluster_id, era).unwrap();
@@ -262,6 +317,8 @@ benchmarks! {
 		charging_processed_batches.try_insert(0).unwrap();
 		let rewarding_max_batch_index = BatchIndex::default();
 		let rewarding_processed_batches : BoundedBTreeSet<BatchIndex, MaxBatchesCount> = BoundedBTreeSet::default();
+		let customers_merkle_root = H256::default();
+		let nodes_merkle_root = H256::default();
 
 		create_default_cluster::<T>(cluster_id);
 		create_billing_report::<T>(BillingReportParams {
@@ -271,6 +328,8 @@ benchmarks! {
 			total_customer_charge: total_customer_charge.clone(),
 			total_distributed_reward,
 			total_node_usage,
+			customers_merkle_root,
+			nodes_merkle_root,
 			charging_max_batch_index,
 			charging_processed_batches,
 			rewarding_max_batch_index,
@@ -308,6 +367,8 @@ benchmarks! {
 		charging_processed_batches.try_insert(0).unwrap();
 		let rewarding_max_batch_index = BatchIndex::default();
 		let rewarding_processed_batches : BoundedBTreeSet<BatchIndex, MaxBatchesCount> = BoundedBTreeSet::default();
+		let customers_merkle_root = H256::default();
+		let nodes_merkle_root = H256::default();
 
 		create_default_cluster::<T>(cluster_id);
 		create_billing_report::<T>(BillingReportParams {
@@ -317,28 +378,21 @@ benchmarks! {
 			total_customer_charge,
 			total_distributed_reward,
 			total_node_usage,
+			customers_merkle_root,
+			nodes_merkle_root,
 			charging_max_batch_index,
 			charging_processed_batches,
 			rewarding_max_batch_index,
 			rewarding_processed_batches,
 		});
 
-		let max_batch_index: BatchIndex = 10;
-		let total_node_usage = NodeUsage {
-			transferred_bytes: 200000000, // 200 mb
-			stored_bytes: 100000000, // 100 mb
-			number_of_gets: 10, // 10 gets
-			number_of_puts: 5, // 5 puts
-		};
-
 		let dac_account = create_dac_account::<T>();
 		whitelist_account!(dac_account);
 
-	}: _(RawOrigin::Signed(dac_account.clone()), cluster_id, era, max_batch_index, total_node_usage)
+	}: _(RawOrigin::Signed(dac_account.clone()), cluster_id, era)
 	verify {
 		let billing_report = ActiveBillingReports::<T>::get(cluster_id, era).unwrap();
 		assert_eq!(billing_report.state, State::RewardingProviders);
-		assert_eq!(billing_report.rewarding_max_batch_index, max_batch_index);
 	}
 
 	send_rewarding_providers_batch {
@@ -366,6 +420,31 @@ benchmarks! {
 		let rewarding_max_batch_index = 0;
 		let rewarding_processed_batches : BoundedBTreeSet<BatchIndex, MaxBatchesCount> = BoundedBTreeSet::default();
 
+		// rewards owed from previous eras are funded with repaid debts
+		TotalOwedRewards::<T>::insert(cluster_id, b as u128 * CERE);
+		endow_account::<T>(&DdcPayouts::<T>::owed_rewards_account_id(cluster_id), b as u128 * CERE);
+
+		let batch_index: BatchIndex = 0;
+		let payees: Vec<(T::AccountId, NodePubKey, NodeUsage)> = (0..b).map(|i| {
+			let provider = create_account::<T>("provider", i, i);
+			endow_account::<T>(&provider, T::Currency::minimum_balance().saturated_into());
+			let mut node_key = [0u8; 32];
+			node_key[..4].copy_from_slice(&i.to_le_bytes());
+			let node = NodePubKey::StoragePubKey(StorageNodePubKey::new(node_key));
+			// reward owed from a previous era is paid out first
+			OwingProviders::<T>::insert(cluster_id, provider.clone(), CERE);
+			OwingNodes::<T>::insert((cluster_id, provider.clone()), node.clone(), CERE);
+			let node_usage = NodeUsage {
+				transferred_bytes: 200000000, // 200 mb
+				stored_bytes: 100000000, // 100 mb
+				number_of_gets: 10, // 10 gets
+				number_of_puts: 5, // 5 puts
+			};
+			(provider, node, node_usage)
+		}).collect();
+		let customers_merkle_root = H256::default();
+		let nodes_merkle_root = merkle::batch_leaf(batch_index, &payees);
+
 		create_default_cluster::<T>(cluster_id);
 		create_billing_report::<T>(BillingReportParams {
 			cluster_id,
@@ -374,6 +453,8 @@ benchmarks! {
 			total_customer_charge: total_customer_charge.clone(),
 			total_distributed_reward,
 			total_node_usage,
+			customers_merkle_root,
+			nodes_merkle_root,
 			charging_max_batch_index,
 			charging_processed_batches,
 			rewarding_max_batch_index,
@@ -382,25 +463,13 @@ benchmarks! {
 
 		let vault = DdcPayouts::<T>::sub_account_id(cluster_id, era);
 		let total_customer_charge_amount = total_customer_charge.transfer + total_customer_charge.storage + total_customer_charge.gets + total_customer_charge.puts;
-		endow_account::<T>(&vault, total_customer_charge_amount + T::Currency::minimum_balance().saturated_into::<u128>());
+		let total_owed_reward = CERE.saturating_mul(b.into());
+		endow_account::<T>(&vault, total_customer_charge_amount + total_owed_reward + T::Currency::minimum_balance().saturated_into::<u128>());
 
 		let dac_account = create_dac_account::<T>();
 		whitelist_account!(dac_account);
 
-		let batch_index: BatchIndex = 0;
-		let payees: Vec<(T::AccountId, NodeUsage)> = (0..b).map(|i| {
-			let provider = create_account::<T>("provider", i, i);
-			endow_account::<T>(&provider, T::Currency::minimum_balance().saturated_into());
-			let node_usage = NodeUsage {
-				transferred_bytes: 200000000, // 200 mb
-				stored_bytes: 100000000, // 100 mb
-				number_of_gets: 10, // 10 gets
-				number_of_puts: 5, // 5 puts
-			};
-			(provider, node_usage)
-		}).collect();
-
-	}: _(RawOrigin::Signed(dac_account.clone()), cluster_id, era, batch_index, payees)
+	}: _(RawOrigin::Signed(dac_account.clone()), cluster_id, era, batch_index, payees, vec![])
 	verify {
 		assert!(ActiveBillingReports::<T>::contains_key(cluster_id, era));
 		let billing_report = ActiveBillingReports::<T>::get(cluster_id, era).unwrap();
@@ -430,6 +499,8 @@ benchmarks! {
 		charging_processed_batches.try_insert(0).unwrap();
 		let rewarding_max_batch_index = 0;
 		let mut rewarding_processed_batches : BoundedBTreeSet<BatchIndex, MaxBatchesCount> = BoundedBTreeSet::default();
+		let customers_merkle_root = H256::default();
+		let nodes_merkle_root = H256::default();
 		rewarding_processed_batches.try_insert(0).unwrap();
 
 		create_default_cluster::<T>(cluster_id);
@@ -440,6 +511,8 @@ benchmarks! {
 			total_customer_charge,
 			total_distributed_reward,
 			total_node_usage,
+			customers_merkle_root,
+			nodes_merkle_root,
 			charging_max_batch_index,
 			charging_processed_batches,
 			rewarding_max_batch_index,
@@ -478,6 +551,8 @@ benchmarks! {
 		charging_processed_batches.try_insert(0).unwrap();
 		let rewarding_max_batch_index = 0;
 		let mut rewarding_processed_batches : BoundedBTreeSet<BatchIndex, MaxBatchesCount> = BoundedBTreeSet::default();
+		let customers_merkle_root = H256::default();
+		let nodes_merkle_root = H256::default();
 		rewarding_processed_batches.try_insert(0).unwrap();
 
 		create_default_cluster::<T>(cluster_id);
@@ -488,6 +563,8 @@ benchmarks! {
 			total_customer_charge,
 			total_distributed_reward,
 			total_node_usage,
+			customers_merkle_root,
+			nodes_merkle_root,
 			charging_max_batch_index,
 			charging_processed_batches,
 			rewarding_max_batch_index,
@@ -504,4 +581,378 @@ benchmarks! {
 		assert_eq!(billing_report.state, State::Finalized);
 	}
 
+	dispute_billing_report {
+		let cluster_id = ClusterId::from([1; 20]);
+		let era : DdcEra = 1;
+		let state = State::ProvidersRewarded;
+		let total_customer_charge = CustomerCharge {
+			transfer: 200 * CERE, // price for 200 mb
+			storage: 100 * CERE, // price for 100 mb
+			gets: 10 * CERE, // price for 10 gets
+			puts: 5 * CERE, // price for 5 puts
+		};
+		let total_distributed_reward : u128 = total_customer_charge.transfer + total_customer_charge.storage + total_customer_charge.gets + total_customer_charge.puts;
+		let total_node_usage = NodeUsage {
+			transferred_bytes: 200000000, // 200 mb
+			stored_bytes: 100000000, // 100 mb
+			number_of_gets: 10, // 10 gets
+			number_of_puts: 5, // 5 puts
+		};
+		let charging_max_batch_index = 0;
+		let mut charging_processed_batches : BoundedBTreeSet<BatchIndex, MaxBatchesCount> = BoundedBTreeSet::default();
+		charging_processed_batches.try_insert(0).unwrap();
+		let rewarding_max_batch_index = 0;
+		let mut rewarding_processed_batches : BoundedBTreeSet<BatchIndex, MaxBatchesCount> = BoundedBTreeSet::default();
+		let customers_merkle_root = H256::default();
+		let nodes_merkle_root = H256::default();
+		rewarding_processed_batches.try_insert(0).unwrap();
+
+		create_default_cluster::<T>(cluster_id);
+		create_billing_report::<T>(BillingReportParams {
+			cluster_id,
+			era,
+			state,
+			total_customer_charge,
+			total_distributed_reward,
+			total_node_usage,
+			customers_merkle_root,
+			nodes_merkle_root,
+			charging_max_batch_index,
+			charging_processed_batches,
+			rewarding_max_batch_index,
+			rewarding_processed_batches,
+		});
+
+		DisputePeriodEnds::<T>::insert(cluster_id, era, frame_system::Pallet::<T>::block_number() + 1u32.into());
+
+		let disputer = create_account::<T>("disputer", 0, 0);
+		endow_account::<T>(&disputer, 2 * T::DisputeBond::get().saturated_into::<u128>() + T::Currency::minimum_balance().saturated_into::<u128>());
+		whitelist_account!(disputer);
+
+	}: _(RawOrigin::Signed(disputer.clone()), cluster_id, era)
+	verify {
+		assert!(BillingReportDisputes::<T>::contains_key(cluster_id, era));
+	}
+
+	settle_dispute {
+		let cluster_id = ClusterId::from([1; 20]);
+		let era : DdcEra = 1;
+		let state = State::ProvidersRewarded;
+		let total_customer_charge = CustomerCharge {
+			transfer: 200 * CERE, // price for 200 mb
+			storage: 100 * CERE, // price for 100 mb
+			gets: 10 * CERE, // price for 10 gets
+			puts: 5 * CERE, // price for 5 puts
+		};
+		let total_distributed_reward : u128 = total_customer_charge.transfer + total_customer_charge.storage + total_customer_charge.gets + total_customer_charge.puts;
+		let total_node_usage = NodeUsage {
+			transferred_bytes: 200000000, // 200 mb
+			stored_bytes: 100000000, // 100 mb
+			number_of_gets: 10, // 10 gets
+			number_of_puts: 5, // 5 puts
+		};
+		let charging_max_batch_index = 0;
+		let mut charging_processed_batches : BoundedBTreeSet<BatchIndex, MaxBatchesCount> = BoundedBTreeSet::default();
+		charging_processed_batches.try_insert(0).unwrap();
+		let rewarding_max_batch_index = 0;
+		let mut rewarding_processed_batches : BoundedBTreeSet<BatchIndex, MaxBatchesCount> = BoundedBTreeSet::default();
+		let customers_merkle_root = H256::default();
+		let nodes_merkle_root = H256::default();
+		rewarding_processed_batches.try_insert(0).unwrap();
+
+		create_default_cluster::<T>(cluster_id);
+		create_billing_report::<T>(BillingReportParams {
+			cluster_id,
+			era,
+			state,
+			total_customer_charge,
+			total_distributed_reward,
+			total_node_usage,
+			customers_merkle_root,
+			nodes_merkle_root,
+			charging_max_batch_index,
+			charging_processed_batches,
+			rewarding_max_batch_index,
+			rewarding_processed_batches,
+		});
+
+		DisputePeriodEnds::<T>::insert(cluster_id, era, frame_system::Pallet::<T>::block_number() + 1u32.into());
+
+		let disputer = create_account::<T>("disputer", 0, 0);
+		endow_account::<T>(&disputer, 2 * T::DisputeBond::get().saturated_into::<u128>() + T::Currency::minimum_balance().saturated_into::<u128>());
+		whitelist_account!(disputer);
+
+		DdcPayouts::<T>::dispute_billing_report(RawOrigin::Signed(disputer.clone()).into(), cluster_id, era)?;
+
+		// Worst case scenario, every fee is clawed back to roll back the report
+		let fee = 10 * CERE;
+		let existential_deposit = T::Currency::minimum_balance().saturated_into::<u128>();
+		BillingReportFunds::<T>::insert(cluster_id, era, CollectedFunds {
+			collected: total_distributed_reward + 4 * fee,
+			treasury_fee: fee,
+			validators_fee: fee,
+			cluster_reserve_fee: fee,
+			owed_rewards_funded: fee,
+		});
+		ValidatorFeesPots::<T>::insert(cluster_id, era, ValidatorFeesPot {
+			total_fees: fee,
+			total_score: 1,
+			claimed_fees: 0,
+			snapshotted: true,
+		});
+		endow_account::<T>(&T::TreasuryVisitor::get_account_id(), fee + existential_deposit);
+		endow_account::<T>(&T::ClusterVisitor::get_reserve_account_id(&cluster_id).unwrap(), fee + existential_deposit);
+		endow_account::<T>(&DdcPayouts::<T>::validator_fees_pot_account_id(), fee + existential_deposit);
+		endow_account::<T>(&DdcPayouts::<T>::owed_rewards_account_id(cluster_id), fee + existential_deposit);
+
+	}: _(RawOrigin::Root, cluster_id, era, DisputeResolution::Reverted)
+	verify {
+		assert!(!BillingReportDisputes::<T>::contains_key(cluster_id, era));
+		assert!(BillingReportRollbacks::<T>::contains_key(cluster_id, era));
+	}
+
+	claim_owed_reward {
+		let cluster_id = ClusterId::from([1; 20]);
+		let owed_reward: u128 = 10 * CERE;
+
+		let provider = create_account::<T>("provider", 0, 0);
+		endow_account::<T>(&provider, T::Currency::minimum_balance().saturated_into());
+		whitelist_account!(provider);
+		let node = NodePubKey::StoragePubKey(StorageNodePubKey::new([0; 32]));
+		OwingProviders::<T>::insert(cluster_id, provider.clone(), owed_reward);
+		OwingNodes::<T>::insert((cluster_id, provider.clone()), node.clone(), owed_reward);
+		TotalOwedRewards::<T>::insert(cluster_id, owed_reward);
+
+		endow_account::<T>(&DdcPayouts::<T>::owed_rewards_account_id(cluster_id), owed_reward);
+	}: _(RawOrigin::Signed(provider.clone()), cluster_id, node.clone())
+	verify {
+		assert!(!OwingNodes::<T>::contains_key((cluster_id, provider.clone()), node));
+		assert!(!OwingProviders::<T>::contains_key(cluster_id, provider));
+	}
+
+	claim_validator_fees {
+		let cluster_id = ClusterId::from([1; 20]);
+		let era : DdcEra = 1;
+		let validators_fee: u128 = 10 * CERE;
+
+		let staker = T::NominatorsAndValidatorsList::iter().next().expect("Stakers list is empty");
+		let score = T::VoteScoreToU64::convert(T::NominatorsAndValidatorsList::get_score(&staker).expect("Staker has no score"));
+		ValidatorFeesPots::<T>::insert(cluster_id, era, ValidatorFeesPot {
+			total_fees: validators_fee,
+			total_score: score,
+			claimed_fees: 0,
+			snapshotted: true,
+		});
+		ValidatorFeesScores::<T>::insert((cluster_id, era), &staker, score);
+
+		let pot = DdcPayouts::<T>::validator_fees_pot_account_id();
+		let existential_deposit = T::Currency::minimum_balance().saturated_into::<u128>();
+		endow_account::<T>(&pot, validators_fee + existential_deposit);
+
+		let caller = create_account::<T>("caller", 0, 0);
+		whitelist_account!(caller);
+
+	}: _(RawOrigin::Signed(caller), staker.clone(), cluster_id, era)
+	verify {
+		assert!(ValidatorFeesClaims::<T>::contains_key((cluster_id, era), staker));
+	}
+
+	prune_billing_report {
+		let cluster_id = ClusterId::from([1; 20]);
+		let era : DdcEra = 1;
+		let state = State::Finalized;
+		let total_customer_charge = CustomerCharge {
+			transfer: 200 * CERE, // price for 200 mb
+			storage: 100 * CERE, // price for 100 mb
+			gets: 10 * CERE, // price for 10 gets
+			puts: 5 * CERE, // price for 5 puts
+		};
+		let total_distributed_reward : u128 = total_customer_charge.transfer + total_customer_charge.storage + total_customer_charge.gets + total_customer_charge.puts;
+		let total_node_usage = NodeUsage {
+			transferred_bytes: 200000000, // 200 mb
+			stored_bytes: 100000000, // 100 mb
+			number_of_gets: 10, // 10 gets
+			number_of_puts: 5, // 5 puts
+		};
+
+		create_default_cluster::<T>(cluster_id);
+		create_billing_report::<T>(BillingReportParams {
+			cluster_id,
+			era,
+			state,
+			total_customer_charge,
+			total_distributed_reward,
+			total_node_usage,
+			customers_merkle_root: H256::default(),
+			nodes_merkle_root: H256::default(),
+			charging_max_batch_index: 0,
+			charging_processed_batches: BoundedBTreeSet::default(),
+			rewarding_max_batch_index: 0,
+			rewarding_processed_batches: BoundedBTreeSet::default(),
+		});
+		DdcPayouts::<T>::schedule_pruning(cluster_id, era, frame_system::Pallet::<T>::block_number());
+		// Worst case scenario, every batch of both phases has been executed as a proposal
+		for proposal in 0 .. 2 * MaxBatchesCount::get() as u32 + 7 {
+			ExecutedProposals::<T>::insert((cluster_id, era), H256::from_low_u64_be(proposal as u64), ());
+		}
+
+	}: {
+		let _ = PruningQueue::<T>::take(0);
+		DdcPayouts::<T>::prune_billing_report(cluster_id, era);
+	}
+	verify {
+		assert!(!ActiveBillingReports::<T>::contains_key(cluster_id, era));
+		assert!(ArchivedBillingReports::<T>::contains_key(cluster_id, era));
+		assert!(ExecutedProposals::<T>::iter_prefix((cluster_id, era)).next().is_none());
+	}
+
+	abort_billing_report {
+		let cluster_id = ClusterId::from([1; 20]);
+		let era : DdcEra = 1;
+		let state = State::RewardingProviders;
+		let total_customer_charge = CustomerCharge {
+			transfer: 200 * CERE, // price for 200 mb
+			storage: 100 * CERE, // price for 100 mb
+			gets: 10 * CERE, // price for 10 gets
+			puts: 5 * CERE, // price for 5 puts
+		};
+		let total_distributed_reward : u128 = 0;
+		let total_node_usage = NodeUsage::default();
+
+		create_default_cluster::<T>(cluster_id);
+		create_billing_report::<T>(BillingReportParams {
+			cluster_id,
+			era,
+			state,
+			total_customer_charge: total_customer_charge.clone(),
+			total_distributed_reward,
+			total_node_usage,
+			customers_merkle_root: H256::default(),
+			nodes_merkle_root: H256::default(),
+			charging_max_batch_index: 0,
+			charging_processed_batches: BoundedBTreeSet::default(),
+			rewarding_max_batch_index: 0,
+			rewarding_processed_batches: BoundedBTreeSet::default(),
+		});
+
+		// Worst case scenario, every fee and the owed rewards funds are clawed back
+		let fee = 10 * CERE;
+		let existential_deposit = T::Currency::minimum_balance().saturated_into::<u128>();
+		let total_customer_charge_amount = total_customer_charge.transfer + total_customer_charge.storage + total_customer_charge.gets + total_customer_charge.puts;
+		BillingReportFunds::<T>::insert(cluster_id, era, CollectedFunds {
+			collected: total_customer_charge_amount + 4 * fee,
+			treasury_fee: fee,
+			validators_fee: fee,
+			cluster_reserve_fee: fee,
+			owed_rewards_funded: fee,
+		});
+		ValidatorFeesPots::<T>::insert(cluster_id, era, ValidatorFeesPot {
+			total_fees: fee,
+			total_score: 1,
+			claimed_fees: 0,
+			snapshotted: true,
+		});
+		endow_account::<T>(&DdcPayouts::<T>::sub_account_id(cluster_id, era), total_customer_charge_amount + existential_deposit);
+		endow_account::<T>(&T::TreasuryVisitor::get_account_id(), fee + existential_deposit);
+		endow_account::<T>(&T::ClusterVisitor::get_reserve_account_id(&cluster_id).unwrap(), fee + existential_deposit);
+		endow_account::<T>(&DdcPayouts::<T>::validator_fees_pot_account_id(), fee + existential_deposit);
+		endow_account::<T>(&DdcPayouts::<T>::owed_rewards_account_id(cluster_id), fee + existential_deposit);
+
+	}: _(RawOrigin::Root, cluster_id, era)
+	verify {
+		assert_eq!(ActiveBillingReports::<T>::get(cluster_id, era).unwrap().state, State::Aborted);
+		assert!(BillingReportRollbacks::<T>::contains_key(cluster_id, era));
+	}
+
+	roll_back_billing_report {
+		let r in 1 .. MaxBatchSize::get() as u32;
+
+		let cluster_id = ClusterId::from([1; 20]);
+		let era : DdcEra = 1;
+		let state = State::Aborted;
+		let total_customer_charge = CustomerCharge {
+			transfer: 200 * CERE, // price for 200 mb
+			storage: 100 * CERE, // price for 100 mb
+			gets: 10 * CERE, // price for 10 gets
+			puts: 5 * CERE, // price for 5 puts
+		};
+		let total_distributed_reward : u128 = 0;
+		let total_node_usage = NodeUsage::default();
+
+		create_default_cluster::<T>(cluster_id);
+		create_billing_report::<T>(BillingReportParams {
+			cluster_id,
+			era,
+			state,
+			total_customer_charge,
+			total_distributed_reward,
+			total_node_usage,
+			customers_merkle_root: H256::default(),
+			nodes_merkle_root: H256::default(),
+			charging_max_batch_index: 0,
+			charging_processed_batches: BoundedBTreeSet::default(),
+			rewarding_max_batch_index: 0,
+			rewarding_processed_batches: BoundedBTreeSet::default(),
+		});
+
+		// Worst case scenario, every customer is refunded to the deposit it was charged from
+		let charge = 10 * CERE;
+		for i in 0..r {
+			let customer = create_account::<T>("customer", i, i);
+			endow_customer::<T>(&customer, 10 * CERE);
+			DebtorCustomers::<T>::insert(cluster_id, customer.clone(), CERE);
+			ChargedCustomers::<T>::insert((cluster_id, era), customer.clone(), CustomerChargeRecord {
+				charged: charge,
+				debt_repaid: CERE,
+				debt_incurred: CERE,
+				usage: Default::default(),
+				refunded: 0,
+			});
+			ChargedPaymentSources::<T>::insert((cluster_id, era), (customer, PaymentSource::Deposit), PaymentSourceCharge {
+				charged: charge + CERE,
+				asset_charged: 0,
+			});
+		}
+		let refundable = r as u128 * (charge + CERE);
+		BillingReportRollbacks::<T>::insert(cluster_id, era, BillingReportRollback {
+			reason: RollbackReason::Reverted,
+			share: Perquintill::one(),
+			refundable,
+			available: refundable,
+			refund_ratio: Some(Perquintill::one()),
+			unrecovered: 0,
+		});
+
+		let vault = DdcPayouts::<T>::sub_account_id(cluster_id, era);
+		endow_account::<T>(&vault, refundable + T::Currency::minimum_balance().saturated_into::<u128>());
+
+		let caller = create_account::<T>("caller", 0, 0);
+		whitelist_account!(caller);
+
+	}: _(RawOrigin::Signed(caller), cluster_id, era, r)
+	verify {
+		assert!(ChargedPaymentSources::<T>::iter_prefix((cluster_id, era)).next().is_none());
+	}
+
+	snapshot_validator_scores {
+		let s in 1 .. MaxBatchSize::get() as u32;
+
+		let cluster_id = ClusterId::from([1; 20]);
+		let era : DdcEra = 1;
+
+		ValidatorFeesPots::<T>::insert(cluster_id, era, ValidatorFeesPot {
+			total_fees: 10 * CERE,
+			total_score: 0,
+			claimed_fees: 0,
+			snapshotted: false,
+		});
+
+		let caller = create_account::<T>("caller", 0, 0);
+		whitelist_account!(caller);
+
+	}: _(RawOrigin::Signed(caller), cluster_id, era, s)
+	verify {
+		assert!(ValidatorFeesScores::<T>::iter_prefix((cluster_id, era)).next().is_some());
+	}
+
 }
@@ -6,6 +6,45 @@
 //! - [`Call`]
 //! - [`Pallet`]
 //!
+//! ## Billing reports
+//!
+//! A billing report commits to the DAC usage batches of an era with the Merkle roots of the
+//! customers and nodes batches, the batch counts and the usage totals, and every batch is
+//! verified against them. Each transition of the report requires the votes of a threshold of
+//! the active DAC validators, a majority by default.
+//!
+//! Once the providers are rewarded, the report is open to bonded disputes for `DisputePeriod`
+//! blocks, settled by governance. Governance may also abort a report stuck in charging or
+//! rewarding. An adjusted, reverted or aborted report is rolled back with
+//! `roll_back_billing_report`, which claws back the fees and rewards and refunds every charge
+//! to the deposit, bucket budget or asset deposit it was taken from. A reverted era is never
+//! billed again, an aborted one only once refunded in full. Finalized reports are pruned on
+//! idle after `BillingReportRetentionPeriod`, keeping their totals and content hash in
+//! `ArchivedBillingReports`.
+//!
+//! ## Charging
+//!
+//! The usage is charged per bucket, priced by byte-seconds with integer arithmetic against the
+//! cluster billing period, its pricing tiers and the customer discount. The outstanding debt of
+//! a customer is collected before the usage charge of later eras. A bucket transferred since
+//! the era is charged to the payer recorded in the batch, and the usage of a bucket unknown to
+//! the cluster is skipped. A bucket with a budget is charged from its own allocation only, and
+//! its shortfall is kept as the bucket debt in `DebtorBuckets`, which keeps its owner from
+//! creating or taking over buckets in the cluster.
+//!
+//! A customer paying with an asset the cluster accepts is charged the asset equivalent of the
+//! usage, rounded up, to the cluster reserve, which pays the native amount into the vault. The
+//! native deposit is charged instead while the reserve can not pay.
+//!
+//! ## Rewards
+//!
+//! The rewards the vault can not pay are owed to the node providers per node in `OwingNodes`,
+//! funded with the debts later repaid by the customers of the cluster, and paid out with later
+//! eras or claimed with `claim_owed_reward`. The delegators of a node are paid their pro-rata
+//! share of the node reward, less the provider commission. The validators fees are credited to
+//! a pot per era and claimed by the stakers with `claim_validator_fees` against the scores
+//! snapshotted with `snapshot_validator_scores`.
+//!
 //! ## GenesisConfig
 //!
 //! The DDC Payouts pallet depends on the [`GenesisConfig`]. The
@@ -14,6 +53,8 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 #![recursion_limit = "256"]
 
+pub mod merkle;
+pub mod migration;
 pub mod weights;
 use crate::weights::WeightInfo;
 
@@ -29,23 +70,35 @@ use ddc_primitives::{
 	traits::{
 		cluster::{ClusterCreator as ClusterCreatorType, ClusterVisitor as ClusterVisitorType},
 		customer::{
+			BucketVisitor as BucketVisitorType, BucketVisitorError,
 			CustomerCharger as CustomerChargerType, CustomerDepositor as CustomerDepositorType,
 		},
 		pallet::PalletVisitor as PalletVisitorType,
+		payout::PayoutVisitor as PayoutVisitorType,
+		staking::DelegationVisitor as DelegationVisitorType,
+		validator::ValidatorVisitor as ValidatorVisitorType,
 	},
-	ClusterId, DdcEra, MILLICENTS,
+	AssetId, BucketId, ClusterId, DdcEra, NodePubKey, PaymentSource, PricingTier, StorageClass,
+	MILLICENTS,
 };
 use frame_election_provider_support::SortedListProvider;
 use frame_support::{
 	pallet_prelude::*,
 	parameter_types,
 	sp_runtime::SaturatedConversion,
-	traits::{Currency, ExistenceRequirement, LockableCurrency},
+	traits::{
+		BalanceStatus, Currency, ExistenceRequirement, LockableCurrency, ReservableCurrency,
+		WithdrawReasons,
+	},
 	BoundedBTreeSet,
 };
 use frame_system::pallet_prelude::*;
 pub use pallet::*;
-use sp_runtime::{traits::Convert, PerThing, Perquintill};
+use sp_core::H256;
+use sp_runtime::{
+	helpers_128bit::multiply_by_rational_with_rounding, traits::Convert, FixedPointNumber,
+	FixedU128, PerThing, Perquintill, Rounding,
+};
 use sp_std::prelude::*;
 
 type BatchIndex = u16;
@@ -59,6 +112,17 @@ pub struct CustomerUsage {
 	pub number_of_gets: u64,
 }
 
+impl CustomerUsage {
+	fn checked_add(&self, other: &Self) -> Option<Self> {
+		Some(Self {
+			transferred_bytes: self.transferred_bytes.checked_add(other.transferred_bytes)?,
+			stored_bytes: self.stored_bytes.checked_add(other.stored_bytes)?,
+			number_of_puts: self.number_of_puts.checked_add(other.number_of_puts)?,
+			number_of_gets: self.number_of_gets.checked_add(other.number_of_gets)?,
+		})
+	}
+}
+
 /// Stores usage of node provider
 #[derive(PartialEq, Encode, Decode, RuntimeDebug, TypeInfo, Default, Clone)]
 pub struct NodeUsage {
@@ -68,6 +132,17 @@ pub struct NodeUsage {
 	pub number_of_gets: u64,
 }
 
+impl NodeUsage {
+	fn checked_add(&self, other: &Self) -> Option<Self> {
+		Some(Self {
+			transferred_bytes: self.transferred_bytes.checked_add(other.transferred_bytes)?,
+			stored_bytes: self.stored_bytes.checked_add(other.stored_bytes)?,
+			number_of_puts: self.number_of_puts.checked_add(other.number_of_puts)?,
+			number_of_gets: self.number_of_gets.checked_add(other.number_of_gets)?,
+		})
+	}
+}
+
 /// Stores reward in tokens(units) of node provider as per NodeUsage
 #[derive(PartialEq, Encode, Decode, RuntimeDebug, TypeInfo, Default, Clone)]
 pub struct NodeReward {
@@ -94,6 +169,37 @@ pub struct CustomerCharge {
 	pub gets: u128,     // charge in tokens for CustomerUsage::number_of_gets
 }
 
+/// Billing report progress as exposed by the runtime API
+#[derive(PartialEq, Encode, Decode, RuntimeDebug, TypeInfo, Clone)]
+pub struct BillingReportInfo {
+	pub state: State,
+	pub start_era: i64,
+	pub end_era: i64,
+	pub total_customer_charge: CustomerCharge,
+	pub total_distributed_reward: u128,
+	pub total_node_usage: NodeUsage,
+	pub charging_max_batch_index: BatchIndex,
+	// batches not processed yet, empty until charging begins
+	pub charging_missing_batches: Vec<BatchIndex>,
+	pub rewarding_max_batch_index: BatchIndex,
+	// batches not processed yet, empty until rewarding begins
+	pub rewarding_missing_batches: Vec<BatchIndex>,
+}
+
+/// Compact record of a finalized billing report, kept after the report is pruned
+#[derive(PartialEq, Encode, Decode, RuntimeDebug, TypeInfo, Clone)]
+pub struct BillingReportSummary {
+	pub start_era: i64,
+	pub end_era: i64,
+	pub total_customer_charge: CustomerCharge,
+	pub total_distributed_reward: u128,
+	pub total_node_usage: NodeUsage,
+	pub charging_max_batch_index: BatchIndex,
+	pub rewarding_max_batch_index: BatchIndex,
+	// blake2_256 hash of the SCALE encoded finalized report
+	pub content_hash: H256,
+}
+
 /// The balance type of this pallet.
 pub type BalanceOf<T> =
 	<<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
@@ -107,19 +213,24 @@ parameter_types! {
 	pub MaxBatchesCount: u16 = 1000;
 	pub MaxDust: u128 = MILLICENTS;
 	pub MaxBatchSize: u16 = 1000;
+	pub MaxBatchProofLength: u16 = 16;
+	pub MaxValidatorVotes: u32 = 256;
 }
 
 #[frame_support::pallet]
 pub mod pallet {
 	use frame_support::PalletId;
-	use sp_io::hashing::blake2_128;
+	use sp_io::{
+		hashing::{blake2_128, blake2_256},
+		MultiRemovalResults,
+	};
 	use sp_runtime::traits::{AccountIdConversion, Zero};
 
 	use super::*;
 
 	/// The current storage version.
 	const STORAGE_VERSION: frame_support::traits::StorageVersion =
-		frame_support::traits::StorageVersion::new(0);
+		frame_support::traits::StorageVersion::new(3);
 
 	#[pallet::pallet]
 	#[pallet::storage_version(STORAGE_VERSION)]
@@ -131,15 +242,27 @@ pub mod pallet {
 		type RuntimeEvent: From<Event<Self>> + IsType<<Self as frame_system::Config>::RuntimeEvent>;
 		#[pallet::constant]
 		type PalletId: Get<PalletId>;
-		type Currency: LockableCurrency<Self::AccountId, Moment = BlockNumberFor<Self>>;
+		type Currency: LockableCurrency<Self::AccountId, Moment = BlockNumberFor<Self>>
+			+ ReservableCurrency<Self::AccountId>;
 		type CustomerCharger: CustomerChargerType<Self>;
 		type CustomerDepositor: CustomerDepositorType<Self>;
+		type BucketVisitor: BucketVisitorType<Self>;
 		type TreasuryVisitor: PalletVisitorType<Self>;
 		type ClusterVisitor: ClusterVisitorType<Self>;
 		type NominatorsAndValidatorsList: SortedListProvider<Self::AccountId>;
 		type ClusterCreator: ClusterCreatorType<Self, BalanceOf<Self>>;
+		type ValidatorVisitor: ValidatorVisitorType<Self>;
+		type DelegationVisitor: DelegationVisitorType<Self, BalanceOf<Self>>;
 		type WeightInfo: WeightInfo;
 		type VoteScoreToU64: Convert<VoteScoreOf<Self>, u64>;
+		#[pallet::constant]
+		type DisputePeriod: Get<BlockNumberFor<Self>>;
+		#[pallet::constant]
+		type DisputeBond: Get<BalanceOf<Self>>;
+		/// Number of blocks a finalized billing report is kept before it is pruned and only its
+		/// summary remains in the archive.
+		#[pallet::constant]
+		type BillingReportRetentionPeriod: Get<BlockNumberFor<Self>>;
 	}
 
 	#[pallet::event]
@@ -158,6 +281,7 @@ pub mod pallet {
 			era: DdcEra,
 			batch_index: BatchIndex,
 			customer_id: T::AccountId,
+			bucket_id: BucketId,
 			amount: u128,
 		},
 		ChargeFailed {
@@ -165,6 +289,7 @@ pub mod pallet {
 			era: DdcEra,
 			batch_index: BatchIndex,
 			customer_id: T::AccountId,
+			bucket_id: BucketId,
 			charged: u128,
 			expected_to_charge: u128,
 		},
@@ -173,8 +298,24 @@ pub mod pallet {
 			era: DdcEra,
 			batch_index: BatchIndex,
 			customer_id: T::AccountId,
+			bucket_id: BucketId,
 			amount: u128,
 		},
+		DebtPartiallyRepaid {
+			cluster_id: ClusterId,
+			era: DdcEra,
+			batch_index: BatchIndex,
+			customer_id: T::AccountId,
+			repaid: u128,
+			remaining_debt: u128,
+		},
+		DebtRepaid {
+			cluster_id: ClusterId,
+			era: DdcEra,
+			batch_index: BatchIndex,
+			customer_id: T::AccountId,
+			repaid: u128,
+		},
 		ChargingFinished {
 			cluster_id: ClusterId,
 			era: DdcEra,
@@ -194,6 +335,17 @@ pub mod pallet {
 			era: DdcEra,
 			amount: u128,
 		},
+		ValidatorFeesClaimed {
+			cluster_id: ClusterId,
+			era: DdcEra,
+			staker_id: T::AccountId,
+			amount: u128,
+		},
+		ValidatorScoresSnapshotted {
+			cluster_id: ClusterId,
+			era: DdcEra,
+			total_score: u64,
+		},
 		RewardingStarted {
 			cluster_id: ClusterId,
 			era: DdcEra,
@@ -206,6 +358,14 @@ pub mod pallet {
 			rewarded: u128,
 			expected_to_reward: u128,
 		},
+		/// A delegator has been paid its share of the node provider reward
+		DelegatorRewarded {
+			cluster_id: ClusterId,
+			era: DdcEra,
+			node_provider_id: T::AccountId,
+			delegator_id: T::AccountId,
+			rewarded: u128,
+		},
 		NotDistributedReward {
 			cluster_id: ClusterId,
 			era: DdcEra,
@@ -214,6 +374,19 @@ pub mod pallet {
 			expected_reward: u128,
 			distributed_reward: BalanceOf<T>,
 		},
+		OwedRewardPaid {
+			cluster_id: ClusterId,
+			node_provider_id: T::AccountId,
+			paid: u128,
+			remaining: u128,
+		},
+		/// A delegator has been paid its share of the reward owed to the node provider
+		OwedDelegatorRewardPaid {
+			cluster_id: ClusterId,
+			node_provider_id: T::AccountId,
+			delegator_id: T::AccountId,
+			paid: u128,
+		},
 		NotDistributedOverallReward {
 			cluster_id: ClusterId,
 			era: DdcEra,
@@ -228,15 +401,121 @@ pub mod pallet {
 			cluster_id: ClusterId,
 			era: DdcEra,
 		},
-		AuthorisedCaller {
-			authorised_caller: T::AccountId,
+		BillingReportArchived {
+			cluster_id: ClusterId,
+			era: DdcEra,
+			content_hash: H256,
+		},
+		BillingReportAborted {
+			cluster_id: ClusterId,
+			era: DdcEra,
+			state: State,
+		},
+		/// Fees and owed rewards funds paid out of the funds of a billing report being rolled
+		/// back are clawed back to the vault
+		FeesClawedBack {
+			cluster_id: ClusterId,
+			era: DdcEra,
+			treasury_fee: u128,
+			validators_fee: u128,
+			cluster_reserve_fee: u128,
+			owed_rewards_funded: u128,
+		},
+		/// The reward paid with a billing report being rolled back is clawed back from the node
+		/// provider or delegator to the vault
+		RewardClawedBack {
+			cluster_id: ClusterId,
+			era: DdcEra,
+			account_id: T::AccountId,
+			expected: u128,
+			clawed_back: u128,
+		},
+		ChargeRefunded {
+			cluster_id: ClusterId,
+			era: DdcEra,
+			customer_id: T::AccountId,
+			refunded: u128,
+			debt: u128,
+		},
+		/// The charge could not be refunded to the payment source it was taken from, the refund
+		/// is transferred to the customer instead
+		RefundError {
+			cluster_id: ClusterId,
+			era: DdcEra,
+			customer_id: T::AccountId,
+			source: PaymentSource,
+			amount: u128,
+			error: DispatchError,
+		},
+		BillingReportRolledBack {
+			cluster_id: ClusterId,
+			era: DdcEra,
+			refund_ratio: Perquintill,
+		},
+		ValidatorThresholdChanged {
+			threshold: u32,
+		},
+		BillingReportVoted {
+			cluster_id: ClusterId,
+			era: DdcEra,
+			validator: T::AccountId,
+			proposal_hash: H256,
+		},
+		BillingReportDisputed {
+			cluster_id: ClusterId,
+			era: DdcEra,
+			disputer: T::AccountId,
+			bond: BalanceOf<T>,
+		},
+		DisputeSettled {
+			cluster_id: ClusterId,
+			era: DdcEra,
+			disputer: T::AccountId,
+			resolution: DisputeResolution,
 		},
 		ChargeError {
 			cluster_id: ClusterId,
 			era: DdcEra,
 			batch_index: BatchIndex,
 			customer_id: T::AccountId,
+			bucket_id: BucketId,
+			amount: u128,
+			error: DispatchError,
+		},
+		/// The debt of the bucket charged from its own allocation has been restored while the
+		/// billing report is rolled back
+		BucketDebtRolledBack {
+			cluster_id: ClusterId,
+			era: DdcEra,
+			bucket_id: BucketId,
+			debt: u128,
+		},
+		/// The bucket is no longer owned by the customer in the cluster, its usage is charged to
+		/// the deposit of the customer recorded in the batch
+		BucketNotVerified {
+			cluster_id: ClusterId,
+			era: DdcEra,
+			batch_index: BatchIndex,
+			customer_id: T::AccountId,
+			bucket_id: BucketId,
+			error: DispatchError,
+		},
+		/// The cluster reserve could not pay for the asset charge of the customer, the native
+		/// deposit of the customer is charged instead
+		ReserveBalanceTooLow {
+			cluster_id: ClusterId,
+			era: DdcEra,
+			customer_id: T::AccountId,
+			asset_id: AssetId,
 			amount: u128,
+		},
+		/// The bucket does not exist or belongs to another cluster, its usage is not charged
+		BucketSkipped {
+			cluster_id: ClusterId,
+			era: DdcEra,
+			batch_index: BatchIndex,
+			customer_id: T::AccountId,
+			bucket_id: BucketId,
 			error: DispatchError,
 		},
 	}
@@ -257,6 +536,27 @@ pub mod pallet {
 		BatchSizeIsOutOfBounds,
 		ScoreRetrievalError,
 		BadRequest,
+		BatchProofVerificationFailed,
+		InvalidThreshold,
+		AlreadyVoted,
+		ProposalAlreadyExecuted,
+		DisputePeriodElapsed,
+		DisputePeriodNotElapsed,
+		DisputeAlreadyOpened,
+		DisputeDoesNotExist,
+		BillingReportDisputed,
+		NoOwedReward,
+		NotEnoughVaultBalance,
+		NoValidatorFees,
+		ValidatorFeesAlreadyClaimed,
+		ValidatorScoresNotSnapshotted,
+		ValidatorScoresAlreadySnapshotted,
+		NoBucketWithId,
+		NotBucketOwner,
+		IncorrectBucketClusterId,
+		RollbackInProgress,
+		ReserveBalanceTooLow,
+		UsageMismatch,
 	}
 
 	#[pallet::storage]
@@ -270,20 +570,348 @@ pub mod pallet {
 		BillingReport<T>,
 	>;
 
+	/// Number of DAC validators votes required for a billing report proposal to execute.
+	/// A majority of the active DAC validators is required unless set by governance
+	#[pallet::storage]
+	pub type ValidatorThreshold<T: Config> = StorageValue<_, u32>;
+
+	/// Votes of DAC validators for pending billing report proposals.
+	/// The key is the cluster and era of the report and the hash of the proposed call parameters
+	#[pallet::storage]
+	#[pallet::getter(fn billing_report_votes)]
+	pub type BillingReportVotes<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		(ClusterId, DdcEra),
+		Identity,
+		H256,
+		BoundedVec<T::AccountId, MaxValidatorVotes>,
+	>;
+
+	/// Billing report proposals which have been backed by the validators quorum and applied.
+	/// Kept until the report is pruned, so that late votes are not stored
 	#[pallet::storage]
-	#[pallet::getter(fn authorised_caller)]
-	pub type AuthorisedCaller<T: Config> = StorageValue<_, T::AccountId>;
+	#[pallet::getter(fn executed_proposals)]
+	pub type ExecutedProposals<T: Config> =
+		StorageDoubleMap<_, Blake2_128Concat, (ClusterId, DdcEra), Identity, H256, ()>;
 
 	#[pallet::storage]
 	#[pallet::getter(fn debtor_customers)]
 	pub type DebtorCustomers<T: Config> =
 		StorageDoubleMap<_, Blake2_128Concat, ClusterId, Blake2_128Concat, T::AccountId, u128>;
 
+	/// Debts of the buckets charged from their own allocation, collected from the allocation
+	/// only
+	#[pallet::storage]
+	#[pallet::getter(fn debtor_buckets)]
+	pub type DebtorBuckets<T: Config> =
+		StorageDoubleMap<_, Blake2_128Concat, ClusterId, Blake2_128Concat, BucketId, u128>;
+
+	/// Total debt of the buckets charged from their own allocation per bucket owner, so that an
+	/// indebted owner can not create or take over buckets in the cluster
+	#[pallet::storage]
+	#[pallet::getter(fn debtor_bucket_owners)]
+	pub type DebtorBucketOwners<T: Config> =
+		StorageDoubleMap<_, Blake2_128Concat, ClusterId, Blake2_128Concat, T::AccountId, u128>;
+
 	#[pallet::storage]
 	#[pallet::getter(fn owing_providers)]
 	pub type OwingProviders<T: Config> =
 		StorageDoubleMap<_, Blake2_128Concat, ClusterId, Blake2_128Concat, T::AccountId, u128>;
 
+	/// Rewards owed to the node providers of the cluster per node, shared with the delegators of
+	/// the node once paid. The rewards owed before they were tracked per node are not included
+	#[pallet::storage]
+	#[pallet::getter(fn owing_nodes)]
+	pub type OwingNodes<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		(ClusterId, T::AccountId),
+		Blake2_128Concat,
+		NodePubKey,
+		u128,
+	>;
+
+	/// Rewards owed to the node providers of the cluster in total, funded with the debts repaid
+	/// by the customers of the cluster
+	#[pallet::storage]
+	#[pallet::getter(fn total_owed_rewards)]
+	pub type TotalOwedRewards<T: Config> =
+		StorageMap<_, Blake2_128Concat, ClusterId, u128, ValueQuery>;
+
+	/// Validators fees collected for the era of the cluster, to be claimed by the stakers
+	#[pallet::storage]
+	#[pallet::getter(fn validator_fees_pots)]
+	pub type ValidatorFeesPots<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		ClusterId,
+		Blake2_128Concat,
+		DdcEra,
+		ValidatorFeesPot,
+	>;
+
+	/// Validators fees claimed by the stakers from the pot of the cluster and era
+	#[pallet::storage]
+	#[pallet::getter(fn validator_fees_claims)]
+	pub type ValidatorFeesClaims<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		(ClusterId, DdcEra),
+		Blake2_128Concat,
+		T::AccountId,
+		u128,
+	>;
+
+	/// Scores of the stakers snapshotted for the validators fees pot of the cluster and era
+	#[pallet::storage]
+	#[pallet::getter(fn validator_fees_scores)]
+	pub type ValidatorFeesScores<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		(ClusterId, DdcEra),
+		Blake2_128Concat,
+		T::AccountId,
+		u64,
+	>;
+
+	/// Last staker whose score is snapshotted for the validators fees pot of the cluster and era,
+	/// the snapshot continues from the next staker
+	#[pallet::storage]
+	#[pallet::getter(fn validator_scores_snapshot_cursors)]
+	pub type ValidatorScoresSnapshotCursors<T: Config> =
+		StorageDoubleMap<_, Blake2_128Concat, ClusterId, Blake2_128Concat, DdcEra, T::AccountId>;
+
+	/// Latest era with a finalized billing report per cluster
+	#[pallet::storage]
+	#[pallet::getter(fn last_finalized_era)]
+	pub type LastFinalizedEras<T: Config> = StorageMap<_, Blake2_128Concat, ClusterId, DdcEra>;
+
+	/// Block until which a report with rewarded providers can be disputed
+	#[pallet::storage]
+	#[pallet::getter(fn dispute_period_ends)]
+	pub type DisputePeriodEnds<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		ClusterId,
+		Blake2_128Concat,
+		DdcEra,
+		BlockNumberFor<T>,
+	>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn billing_report_disputes)]
+	pub type BillingReportDisputes<T: Config> =
+		StorageDoubleMap<_, Blake2_128Concat, ClusterId, Blake2_128Concat, DdcEra, Dispute<T>>;
+
+	/// Amounts charged to the customers with the billing report, to roll back the report if
+	/// needed
+	#[pallet::storage]
+	#[pallet::getter(fn charged_customers)]
+	pub type ChargedCustomers<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		(ClusterId, DdcEra),
+		Blake2_128Concat,
+		T::AccountId,
+		CustomerChargeRecord,
+	>;
+
+	/// Debts of the buckets charged from their own allocation repaid and incurred with the
+	/// billing report by bucket owner, to roll the report back if needed
+	#[pallet::storage]
+	#[pallet::getter(fn charged_buckets)]
+	pub type ChargedBuckets<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		(ClusterId, DdcEra),
+		Blake2_128Concat,
+		(T::AccountId, BucketId),
+		BucketDebtRecord,
+	>;
+
+	/// Funds collected with the billing report and the fees paid out of them, to roll the report
+	/// back if needed
+	#[pallet::storage]
+	#[pallet::getter(fn billing_report_funds)]
+	pub type BillingReportFunds<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		ClusterId,
+		Blake2_128Concat,
+		DdcEra,
+		CollectedFunds,
+		ValueQuery,
+	>;
+
+	/// Rewards paid to the node providers and their delegators with the billing report, to claw
+	/// them back if the report is rolled back
+	#[pallet::storage]
+	#[pallet::getter(fn rewarded_providers)]
+	pub type RewardedProviders<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		(ClusterId, DdcEra),
+		Blake2_128Concat,
+		T::AccountId,
+		ProviderRewardRecord,
+	>;
+
+	/// Billing reports being rolled back by `roll_back_billing_report` after a settled dispute
+	#[pallet::storage]
+	#[pallet::getter(fn billing_report_rollbacks)]
+	pub type BillingReportRollbacks<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		ClusterId,
+		Blake2_128Concat,
+		DdcEra,
+		BillingReportRollback,
+	>;
+
+	/// Amounts charged to the customers with the billing report by payment source, to refund
+	/// the charges to the sources they were taken from
+	#[pallet::storage]
+	#[pallet::getter(fn charged_payment_sources)]
+	pub type ChargedPaymentSources<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		(ClusterId, DdcEra),
+		Blake2_128Concat,
+		(T::AccountId, PaymentSource),
+		PaymentSourceCharge,
+	>;
+
+	/// Summaries of the finalized billing reports pruned after the retention period
+	#[pallet::storage]
+	#[pallet::getter(fn archived_billing_reports)]
+	pub type ArchivedBillingReports<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		ClusterId,
+		Blake2_128Concat,
+		DdcEra,
+		BillingReportSummary,
+	>;
+
+	/// Finalized billing reports waiting to be pruned, in the order of finalization, together
+	/// with the block from which they can be pruned
+	#[pallet::storage]
+	#[pallet::getter(fn pruning_queue)]
+	pub type PruningQueue<T: Config> =
+		StorageMap<_, Twox64Concat, u64, (ClusterId, DdcEra, BlockNumberFor<T>)>;
+
+	/// Indices of the first and the next free entry of the pruning queue
+	#[pallet::storage]
+	#[pallet::getter(fn pruning_queue_range)]
+	pub type PruningQueueRange<T: Config> = StorageValue<_, (u64, u64), ValueQuery>;
+
+	#[derive(Clone, Encode, Decode, RuntimeDebug, TypeInfo, PartialEq, Default)]
+	pub struct ValidatorFeesPot {
+		pub total_fees: u128,
+		// total score of the stakers snapshotted so far
+		pub total_score: u64,
+		pub claimed_fees: u128,
+		// the scores of all the stakers are snapshotted and the fees can be claimed
+		pub snapshotted: bool,
+	}
+
+	#[derive(Clone, Encode, Decode, RuntimeDebug, TypeInfo, PartialEq, Default)]
+	pub struct CustomerChargeRecord {
+		// charged for the usage in the era
+		pub charged: u128,
+		// outstanding debt from previous eras collected with the report
+		pub debt_repaid: u128,
+		// usage charge the customer could not cover
+		pub debt_incurred: u128,
+		// usage of the customer buckets charged so far, the pricing tiers apply to its total
+		pub usage: CustomerUsage,
+		// refunded to the payment sources of the customer while the report is rolled back
+		pub refunded: u128,
+	}
+
+	#[derive(Clone, Encode, Decode, RuntimeDebug, TypeInfo, PartialEq, Default)]
+	pub struct BucketDebtRecord {
+		// outstanding debt of the bucket collected from its allocation with the report
+		pub debt_repaid: u128,
+		// usage charge the allocation of the bucket could not cover
+		pub debt_incurred: u128,
+	}
+
+	#[derive(Clone, Encode, Decode, RuntimeDebug, TypeInfo, PartialEq, Default)]
+	pub struct PaymentSourceCharge {
+		// charged from the payment source, in the native currency
+		pub charged: u128,
+		// charged from an asset deposit, in the asset
+		pub asset_charged: u128,
+	}
+
+	#[derive(Clone, Encode, Decode, RuntimeDebug, TypeInfo, PartialEq, Default)]
+	pub struct CollectedFunds {
+		// charged to the customers, including the repaid debts
+		pub collected: u128,
+		pub treasury_fee: u128,
+		pub validators_fee: u128,
+		pub cluster_reserve_fee: u128,
+		// repaid debts moved to the funds of the rewards owed in the cluster
+		pub owed_rewards_funded: u128,
+	}
+
+	#[derive(Clone, Encode, Decode, RuntimeDebug, TypeInfo, PartialEq, Default)]
+	pub struct ProviderRewardRecord {
+		// paid out of the vault with the report
+		pub rewarded: u128,
+		// left owed to the node provider with the report
+		pub owed: u128,
+	}
+
+	#[derive(Clone, Encode, Decode, RuntimeDebug, TypeInfo, PartialEq)]
+	pub enum RollbackReason {
+		/// The report totals are lowered by a dispute resolution.
+		Adjusted,
+		/// The report is reverted by a dispute resolution.
+		Reverted,
+		/// The report is aborted by the governance before its providers are rewarded.
+		Aborted,
+	}
+
+	#[derive(Clone, Encode, Decode, RuntimeDebug, TypeInfo, PartialEq)]
+	pub struct BillingReportRollback {
+		pub reason: RollbackReason,
+		// share of the report funds rolled back
+		pub share: Perquintill,
+		// share of the charges to refund to the customers
+		pub refundable: u128,
+		// funds of the report left in the vault and clawed back to it so far
+		pub available: u128,
+		// share of the refundable charges the customers get back, known once every reward is
+		// clawed back
+		pub refund_ratio: Option<Perquintill>,
+		// fees and rewards that could not be clawed back, as they are not transferable
+		pub unrecovered: u128,
+	}
+
+	#[derive(Clone, Encode, Decode, RuntimeDebug, TypeInfo, PartialEq)]
+	#[scale_info(skip_type_params(T))]
+	pub struct Dispute<T: Config> {
+		pub disputer: T::AccountId,
+		pub bond: BalanceOf<T>,
+	}
+
+	#[derive(Clone, Encode, Decode, RuntimeDebug, TypeInfo, PartialEq)]
+	pub enum DisputeResolution {
+		/// The report is correct, the bond of the disputer is slashed to the treasury.
+		Rejected,
+		/// The report totals are lowered by governance, the bond is returned to the disputer.
+		/// The charges are refunded to the customers in the share they are lowered, clawing the
+		/// same share back from the fees and rewards paid with the report.
+		Adjusted { total_customer_charge: CustomerCharge, total_node_usage: NodeUsage },
+		/// The report is rolled back, the bond is returned to the disputer. The fees and rewards
+		/// paid with the report are clawed back to refund the customers and the report is kept
+		/// in the `Aborted` state, so that the era of the cluster is never billed again.
+		Reverted,
+	}
+
 	#[derive(Clone, Encode, Decode, RuntimeDebug, TypeInfo, PartialEq)]
 	#[scale_info(skip_type_params(T))]
 	pub struct BillingReport<T: Config> {
@@ -294,12 +922,18 @@ pub mod pallet {
 		pub total_customer_charge: CustomerCharge,
 		pub total_distributed_reward: u128,
 		pub total_node_usage: NodeUsage,
+		// DAC commitments to the customers and nodes usage batches
+		pub customers_merkle_root: H256,
+		pub nodes_merkle_root: H256,
+		pub total_customer_usage: CustomerUsage,
 		// stage 1
 		pub charging_max_batch_index: BatchIndex,
 		pub charging_processed_batches: BoundedBTreeSet<BatchIndex, MaxBatchesCount>,
+		pub charged_usage: CustomerUsage,
 		// stage 2
 		pub rewarding_max_batch_index: BatchIndex,
 		pub rewarding_processed_batches: BoundedBTreeSet<BatchIndex, MaxBatchesCount>,
+		pub rewarded_usage: NodeUsage,
 	}
 
 	impl<T: pallet::Config> Default for BillingReport<T> {
@@ -312,10 +946,15 @@ pub mod pallet {
 				total_customer_charge: CustomerCharge::default(),
 				total_distributed_reward: Zero::zero(),
 				total_node_usage: NodeUsage::default(),
+				customers_merkle_root: H256::default(),
+				nodes_merkle_root: H256::default(),
+				total_customer_usage: CustomerUsage::default(),
 				charging_max_batch_index: Zero::zero(),
 				charging_processed_batches: BoundedBTreeSet::default(),
+				charged_usage: CustomerUsage::default(),
 				rewarding_max_batch_index: Zero::zero(),
 				rewarding_processed_batches: BoundedBTreeSet::default(),
+				rewarded_usage: NodeUsage::default(),
 			}
 		}
 	}
@@ -332,49 +971,157 @@ pub mod pallet {
 		RewardingProviders = 5,
 		ProvidersRewarded = 6,
 		Finalized = 7,
+		Aborted = 8,
 	}
 
-	#[pallet::call]
-	impl<T: Config> Pallet<T> {
-		#[pallet::call_index(0)]
-		#[pallet::weight(T::WeightInfo::set_authorised_caller())]
-		pub fn set_authorised_caller(
-			origin: OriginFor<T>,
-			authorised_caller: T::AccountId,
-		) -> DispatchResult {
-			ensure_root(origin)?; // requires Governance approval
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		/// Prunes the finalized billing reports whose retention period is over, as long as there
+		/// is enough weight left in the block. The charge and reward records of a report are
+		/// removed page by page before the report is archived.
+		fn on_idle(now: BlockNumberFor<T>, remaining_weight: Weight) -> Weight {
+			let mut consumed_weight = T::DbWeight::get().reads(1);
+			if remaining_weight.any_lt(consumed_weight) {
+				return Weight::zero()
+			}
 
-			AuthorisedCaller::<T>::put(authorised_caller.clone());
+			let (mut head, tail) = PruningQueueRange::<T>::get();
+			let start = head;
+			let prune_weight = T::WeightInfo::prune_billing_report();
+			while head < tail {
+				// reserve a write for the updated queue range
+				let required_weight = consumed_weight
+					.saturating_add(prune_weight)
+					.saturating_add(T::DbWeight::get().writes(1));
+				if remaining_weight.any_lt(required_weight) {
+					break
+				}
 
-			Self::deposit_event(Event::<T>::AuthorisedCaller { authorised_caller });
+				if let Some((cluster_id, era, prunable_at)) = PruningQueue::<T>::get(head) {
+					if prunable_at > now {
+						break
+					}
+					let record_weight = T::DbWeight::get().reads_writes(1, 1);
+					let limit = remaining_weight
+						.saturating_sub(required_weight)
+						.ref_time()
+						.checked_div(record_weight.ref_time())
+						.unwrap_or(u64::MAX)
+						.min(u32::MAX as u64) as u32;
+					let (removed, done) =
+						Self::prune_billing_report_records(cluster_id, era, limit);
+					consumed_weight = consumed_weight
+						.saturating_add(record_weight.saturating_mul(removed as u64));
+					if !done {
+						break
+					}
+					Self::prune_billing_report(cluster_id, era);
+					PruningQueue::<T>::remove(head);
+				}
+				consumed_weight = consumed_weight.saturating_add(prune_weight);
+				head += 1;
+			}
 
-			Ok(())
+			if head != start {
+				PruningQueueRange::<T>::put((head, tail));
+				consumed_weight = consumed_weight.saturating_add(T::DbWeight::get().writes(1));
+			}
+
+			consumed_weight
+		}
+	}
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		/// Set the number of DAC validators votes required to apply a billing report proposal.
+		/// The threshold can not exceed the number of the active DAC validators.
+		#[pallet::call_index(0)]
+		#[pallet::weight(T::WeightInfo::set_validator_threshold())]
+		pub fn set_validator_threshold(origin: OriginFor<T>, threshold: u32) -> DispatchResult {
+			ensure_root(origin)?; // requires Governance approval
+
+			ensure!(
+				threshold > 0 &&
+					threshold <= MaxValidatorVotes::get() &&
+					threshold as usize <= T::ValidatorVisitor::get_active_validators().len(),
+				Error::<T>::InvalidThreshold
+			);
+			ValidatorThreshold::<T>::put(threshold);
+
+			Self::deposit_event(Event::<T>::ValidatorThresholdChanged { threshold });
+
+			Ok(())
 		}
 
+		/// Open a billing report for the `era` of the cluster.
+		///
+		/// `customers_merkle_root` and `nodes_merkle_root` commit to all the customers and nodes
+		/// usage batches the DAC is going to send for this report (see [`merkle`]). Every batch
+		/// must later be accompanied by an inclusion proof against the respective root. The number
+		/// of batches and the usage totals are committed along with the roots, and the batches
+		/// sent must add up to them before charging and rewarding can end.
 		#[pallet::call_index(1)]
 		#[pallet::weight(T::WeightInfo::begin_billing_report())]
+		#[allow(clippy::too_many_arguments)]
 		pub fn begin_billing_report(
 			origin: OriginFor<T>,
 			cluster_id: ClusterId,
 			era: DdcEra,
 			start_era: i64,
 			end_era: i64,
+			customers_merkle_root: H256,
+			nodes_merkle_root: H256,
+			charging_max_batch_index: BatchIndex,
+			rewarding_max_batch_index: BatchIndex,
+			total_customer_usage: CustomerUsage,
+			total_node_usage: NodeUsage,
 		) -> DispatchResult {
 			let caller = ensure_signed(origin)?;
-			ensure!(Self::authorised_caller() == Some(caller), Error::<T>::Unauthorised);
+			if !Self::vote_for_proposal(
+				caller,
+				cluster_id,
+				era,
+				Self::proposal_hash(
+					1,
+					(
+						start_era,
+						end_era,
+						customers_merkle_root,
+						nodes_merkle_root,
+						charging_max_batch_index,
+						rewarding_max_batch_index,
+						&total_customer_usage,
+						&total_node_usage,
+					),
+				),
+			)? {
+				return Ok(())
+			}
 
 			ensure!(
-				ActiveBillingReports::<T>::try_get(cluster_id, era).is_err(),
+				ActiveBillingReports::<T>::try_get(cluster_id, era).is_err() &&
+					!ArchivedBillingReports::<T>::contains_key(cluster_id, era),
 				Error::<T>::NotExpectedState
 			);
 
 			ensure!(end_era > start_era, Error::<T>::BadRequest);
+			ensure!(
+				charging_max_batch_index < MaxBatchesCount::get() &&
+					rewarding_max_batch_index < MaxBatchesCount::get(),
+				Error::<T>::BatchIndexOverflow
+			);
 
 			let billing_report = BillingReport::<T> {
 				vault: Self::account_id(),
 				state: State::Initialized,
 				start_era,
 				end_era,
+				total_node_usage,
+				customers_merkle_root,
+				nodes_merkle_root,
+				total_customer_usage,
+				charging_max_batch_index,
+				rewarding_max_batch_index,
 				..Default::default()
 			};
 			ActiveBillingReports::<T>::insert(cluster_id, era, billing_report);
@@ -390,19 +1137,17 @@ pub mod pallet {
 			origin: OriginFor<T>,
 			cluster_id: ClusterId,
 			era: DdcEra,
-			max_batch_index: BatchIndex,
 		) -> DispatchResult {
 			let caller = ensure_signed(origin)?;
-			ensure!(Self::authorised_caller() == Some(caller), Error::<T>::Unauthorised);
-
-			ensure!(max_batch_index < MaxBatchesCount::get(), Error::<T>::BatchIndexOverflow);
+			if !Self::vote_for_proposal(caller, cluster_id, era, Self::proposal_hash(2, ()))? {
+				return Ok(())
+			}
 
 			let mut billing_report = ActiveBillingReports::<T>::try_get(cluster_id, era)
 				.map_err(|_| Error::<T>::BillingReportDoesNotExist)?;
 
 			ensure!(billing_report.state == State::Initialized, Error::<T>::NotExpectedState);
 
-			billing_report.charging_max_batch_index = max_batch_index;
 			billing_report.state = State::ChargingCustomers;
 			ActiveBillingReports::<T>::insert(cluster_id, era, billing_report);
 
@@ -418,10 +1163,18 @@ pub mod pallet {
 			cluster_id: ClusterId,
 			era: DdcEra,
 			batch_index: BatchIndex,
-			payers: Vec<(T::AccountId, CustomerUsage)>,
+			payers: Vec<(T::AccountId, BucketId, CustomerUsage)>,
+			batch_proof: Vec<H256>,
 		) -> DispatchResult {
 			let caller = ensure_signed(origin)?;
-			ensure!(Self::authorised_caller() == Some(caller), Error::<T>::Unauthorised);
+			if !Self::vote_for_proposal(
+				caller,
+				cluster_id,
+				era,
+				Self::proposal_hash(3, (batch_index, &payers)),
+			)? {
+				return Ok(())
+			}
 
 			ensure!(
 				!payers.is_empty() && payers.len() <= MaxBatchSize::get() as usize,
@@ -440,12 +1193,70 @@ pub mod pallet {
 				!billing_report.charging_processed_batches.contains(&batch_index),
 				Error::<T>::BatchIndexAlreadyProcessed
 			);
+			validate_batch_proof::<T>(
+				&billing_report.customers_merkle_root,
+				&merkle::batch_leaf(batch_index, &payers),
+				&batch_proof,
+			)?;
 
 			let mut updated_billing_report = billing_report;
-			for payer in payers {
+			for (customer_id, bucket_id, customer_usage) in payers {
+				updated_billing_report.charged_usage = updated_billing_report
+					.charged_usage
+					.checked_add(&customer_usage)
+					.ok_or(Error::<T>::ArithmeticOverflow)?;
+
+				// the usage is charged per bucket of the customer in the cluster. The batch records
+				// the owner of the bucket in the era, so a bucket transferred since then is
+				// charged to that owner's deposit instead of failing the batch. The usage of a
+				// bucket unknown to the cluster is not charged
+				let charged_bucket_id = match T::BucketVisitor::ensure_bucket_owner(
+					&cluster_id,
+					bucket_id,
+					&customer_id,
+				) {
+					Ok(()) => Some(bucket_id),
+					Err(BucketVisitorError::NotBucketOwner) => {
+						Self::deposit_event(Event::<T>::BucketNotVerified {
+							cluster_id,
+							era,
+							batch_index,
+							customer_id: customer_id.clone(),
+							bucket_id,
+							error: Error::<T>::NotBucketOwner.into(),
+						});
+						None
+					},
+					Err(e) => {
+						Self::deposit_event(Event::<T>::BucketSkipped {
+							cluster_id,
+							era,
+							batch_index,
+							customer_id,
+							bucket_id,
+							error: Into::<Error<T>>::into(e).into(),
+						});
+						continue
+					},
+				};
+
+				// a bucket charged from its own allocation keeps its debts apart from the deposit
+				let budgeted_bucket_id = charged_bucket_id.filter(|bucket_id| {
+					matches!(
+						T::CustomerCharger::get_payment_source(Some(*bucket_id), &customer_id),
+						PaymentSource::BucketBudget(_)
+					)
+				});
+
+				let charged_usage = ChargedCustomers::<T>::get((cluster_id, era), &customer_id)
+					.map(|record| record.usage)
+					.unwrap_or_default();
 				let mut customer_charge = get_customer_charge::<T>(
 					cluster_id,
-					&payer.1,
+					&customer_id,
+					bucket_id,
+					&customer_usage,
+					&charged_usage,
 					updated_billing_report.start_era,
 					updated_billing_report.end_era,
 				)?;
@@ -458,10 +1269,35 @@ pub mod pallet {
 				})()
 				.ok_or(Error::<T>::ArithmeticOverflow)?;
 
-				let customer_id = payer.0.clone();
-				let amount_actually_charged = match T::CustomerCharger::charge_content_owner(
-					customer_id.clone(),
-					updated_billing_report.vault.clone(),
+				// outstanding debt from previous eras is collected before the current usage
+				let debt_repaid = Self::collect_customer_debt(
+					cluster_id,
+					era,
+					batch_index,
+					&customer_id,
+					bucket_id,
+					budgeted_bucket_id,
+					&updated_billing_report.vault,
+				)?;
+				// the repaid debt funds the rewards owed in the cluster first
+				let owed_rewards_funded = Self::fund_owed_rewards(
+					cluster_id,
+					era,
+					&updated_billing_report.vault,
+					debt_repaid,
+				)?;
+				let debt_repaid_charge = split_debt_repayment(
+					debt_repaid - owed_rewards_funded,
+					&customer_charge,
+					total_customer_charge,
+				);
+
+				let amount_actually_charged = match Self::charge_customer(
+					cluster_id,
+					era,
+					charged_bucket_id,
+					&customer_id,
+					&updated_billing_report.vault,
 					total_customer_charge,
 				) {
 					Ok(actually_charged) => actually_charged,
@@ -471,6 +1307,7 @@ pub mod pallet {
 							era,
 							batch_index,
 							customer_id: customer_id.clone(),
+							bucket_id,
 							amount: total_customer_charge,
 							error: e,
 						});
@@ -478,26 +1315,36 @@ pub mod pallet {
 					},
 				};
 
+				Self::record_customer_charge(
+					cluster_id,
+					era,
+					&customer_id,
+					budgeted_bucket_id,
+					&customer_usage,
+					amount_actually_charged,
+					debt_repaid,
+					total_customer_charge.saturating_sub(amount_actually_charged),
+				)?;
+
 				if amount_actually_charged < total_customer_charge {
 					// debt
-					let mut customer_debt =
-						DebtorCustomers::<T>::try_get(cluster_id, customer_id.clone())
-							.unwrap_or_else(|_| Zero::zero());
-
 					let debt = total_customer_charge
 						.checked_sub(amount_actually_charged)
 						.ok_or(Error::<T>::ArithmeticOverflow)?;
 
-					customer_debt =
-						customer_debt.checked_add(debt).ok_or(Error::<T>::ArithmeticOverflow)?;
+					let customer_debt =
+						Self::get_debt(cluster_id, &customer_id, budgeted_bucket_id)
+							.checked_add(debt)
+							.ok_or(Error::<T>::ArithmeticOverflow)?;
 
-					DebtorCustomers::<T>::insert(cluster_id, customer_id.clone(), customer_debt);
+					Self::set_debt(cluster_id, &customer_id, budgeted_bucket_id, customer_debt);
 
 					Self::deposit_event(Event::<T>::Indebted {
 						cluster_id,
 						era,
 						batch_index,
 						customer_id: customer_id.clone(),
+						bucket_id,
 						amount: debt,
 					});
 
@@ -506,6 +1353,7 @@ pub mod pallet {
 						era,
 						batch_index,
 						customer_id,
+						bucket_id,
 						charged: amount_actually_charged,
 						expected_to_charge: total_customer_charge,
 					});
@@ -525,10 +1373,28 @@ pub mod pallet {
 						era,
 						batch_index,
 						customer_id,
+						bucket_id,
 						amount: total_customer_charge,
 					});
 				}
 
+				customer_charge.storage = customer_charge
+					.storage
+					.checked_add(debt_repaid_charge.storage)
+					.ok_or(Error::<T>::ArithmeticOverflow)?;
+				customer_charge.transfer = customer_charge
+					.transfer
+					.checked_add(debt_repaid_charge.transfer)
+					.ok_or(Error::<T>::ArithmeticOverflow)?;
+				customer_charge.puts = customer_charge
+					.puts
+					.checked_add(debt_repaid_charge.puts)
+					.ok_or(Error::<T>::ArithmeticOverflow)?;
+				customer_charge.gets = customer_charge
+					.gets
+					.checked_add(debt_repaid_charge.gets)
+					.ok_or(Error::<T>::ArithmeticOverflow)?;
+
 				updated_billing_report.total_customer_charge.storage = updated_billing_report
 					.total_customer_charge
 					.storage
@@ -572,7 +1438,9 @@ pub mod pallet {
 			era: DdcEra,
 		) -> DispatchResult {
 			let caller = ensure_signed(origin)?;
-			ensure!(Self::authorised_caller() == Some(caller), Error::<T>::Unauthorised);
+			if !Self::vote_for_proposal(caller, cluster_id, era, Self::proposal_hash(4, ()))? {
+				return Ok(())
+			}
 
 			let mut billing_report = ActiveBillingReports::<T>::try_get(cluster_id, era)
 				.map_err(|_| Error::<T>::BillingReportDoesNotExist)?;
@@ -582,6 +1450,10 @@ pub mod pallet {
 				&billing_report.charging_processed_batches,
 				&billing_report.charging_max_batch_index,
 			)?;
+			ensure!(
+				billing_report.charged_usage == billing_report.total_customer_usage,
+				Error::<T>::UsageMismatch
+			);
 
 			Self::deposit_event(Event::<T>::ChargingFinished { cluster_id, era });
 
@@ -632,7 +1504,7 @@ pub mod pallet {
 			}
 
 			if validators_fee > 0 {
-				charge_validator_fees::<T>(validators_fee, &billing_report.vault)?;
+				charge_validator_fees::<T>(cluster_id, era, validators_fee, &billing_report.vault)?;
 				Self::deposit_event(Event::<T>::ValidatorFeesCollected {
 					cluster_id,
 					era,
@@ -640,6 +1512,12 @@ pub mod pallet {
 				});
 			}
 
+			BillingReportFunds::<T>::mutate(cluster_id, era, |funds| {
+				funds.treasury_fee = treasury_fee;
+				funds.validators_fee = validators_fee;
+				funds.cluster_reserve_fee = cluster_reserve_fee;
+			});
+
 			// 1 - (X + Y + Z) > 0, 0 < X + Y + Z < 1
 			let total_left_from_one =
 				(fees.treasury_share + fees.validators_share + fees.cluster_reserve_share)
@@ -669,13 +1547,11 @@ pub mod pallet {
 			origin: OriginFor<T>,
 			cluster_id: ClusterId,
 			era: DdcEra,
-			max_batch_index: BatchIndex,
-			total_node_usage: NodeUsage,
 		) -> DispatchResult {
 			let caller = ensure_signed(origin)?;
-			ensure!(Self::authorised_caller() == Some(caller), Error::<T>::Unauthorised);
-
-			ensure!(max_batch_index < MaxBatchesCount::get(), Error::<T>::BatchIndexOverflow);
+			if !Self::vote_for_proposal(caller, cluster_id, era, Self::proposal_hash(5, ()))? {
+				return Ok(())
+			}
 
 			let mut billing_report = ActiveBillingReports::<T>::try_get(cluster_id, era)
 				.map_err(|_| Error::<T>::BillingReportDoesNotExist)?;
@@ -685,8 +1561,6 @@ pub mod pallet {
 				Error::<T>::NotExpectedState
 			);
 
-			billing_report.total_node_usage = total_node_usage;
-			billing_report.rewarding_max_batch_index = max_batch_index;
 			billing_report.state = State::RewardingProviders;
 			ActiveBillingReports::<T>::insert(cluster_id, era, billing_report);
 
@@ -702,10 +1576,18 @@ pub mod pallet {
 			cluster_id: ClusterId,
 			era: DdcEra,
 			batch_index: BatchIndex,
-			payees: Vec<(T::AccountId, NodeUsage)>,
+			payees: Vec<(T::AccountId, NodePubKey, NodeUsage)>,
+			batch_proof: Vec<H256>,
 		) -> DispatchResult {
 			let caller = ensure_signed(origin)?;
-			ensure!(Self::authorised_caller() == Some(caller), Error::<T>::Unauthorised);
+			if !Self::vote_for_proposal(
+				caller,
+				cluster_id,
+				era,
+				Self::proposal_hash(6, (batch_index, &payees)),
+			)? {
+				return Ok(())
+			}
 
 			ensure!(
 				!payees.is_empty() && payees.len() <= MaxBatchSize::get() as usize,
@@ -727,12 +1609,22 @@ pub mod pallet {
 				!billing_report.rewarding_processed_batches.contains(&batch_index),
 				Error::<T>::BatchIndexAlreadyProcessed
 			);
+			validate_batch_proof::<T>(
+				&billing_report.nodes_merkle_root,
+				&merkle::batch_leaf(batch_index, &payees),
+				&batch_proof,
+			)?;
 
 			let max_dust = MaxDust::get().saturated_into::<BalanceOf<T>>();
 			let mut updated_billing_report = billing_report.clone();
-			for payee in payees {
+			for (node_provider_id, node_pub_key, node_usage) in payees {
+				updated_billing_report.rewarded_usage = updated_billing_report
+					.rewarded_usage
+					.checked_add(&node_usage)
+					.ok_or(Error::<T>::ArithmeticOverflow)?;
+
 				let node_reward = get_node_reward(
-					&payee.1,
+					&node_usage,
 					&billing_report.total_node_usage,
 					&billing_report.total_customer_charge,
 				)
@@ -746,17 +1638,56 @@ pub mod pallet {
 				})()
 				.ok_or(Error::<T>::ArithmeticOverflow)?;
 
-				let node_provider_id = payee.0;
+				// rewards owed from previous eras are paid out of the repaid debts of the cluster
+				Self::pay_owed_reward(cluster_id, &node_provider_id, &node_pub_key)?;
+
 				let mut reward_ = amount_to_reward;
 				let mut reward: BalanceOf<T> = amount_to_reward.saturated_into::<BalanceOf<T>>();
+				let mut owed_reward: u128 = Zero::zero();
 				if amount_to_reward > 0 {
 					let vault_balance = <T as pallet::Config>::Currency::free_balance(
 						&updated_billing_report.vault,
-					) - <T as pallet::Config>::Currency::minimum_balance();
+					)
+					.saturating_sub(<T as pallet::Config>::Currency::minimum_balance());
 
 					// 10000000000001 > 10000000000000 but is still ok
 					if reward > vault_balance {
 						if reward - vault_balance > max_dust {
+							owed_reward = (reward - vault_balance).saturated_into::<u128>();
+							OwingProviders::<T>::try_mutate(
+								cluster_id,
+								&node_provider_id,
+								|owed| -> DispatchResult {
+									*owed = Some(
+										owed.unwrap_or_else(Zero::zero)
+											.checked_add(owed_reward)
+											.ok_or(Error::<T>::ArithmeticOverflow)?,
+									);
+									Ok(())
+								},
+							)?;
+							OwingNodes::<T>::try_mutate(
+								(cluster_id, &node_provider_id),
+								&node_pub_key,
+								|owed| -> DispatchResult {
+									*owed = Some(
+										owed.unwrap_or_else(Zero::zero)
+											.checked_add(owed_reward)
+											.ok_or(Error::<T>::ArithmeticOverflow)?,
+									);
+									Ok(())
+								},
+							)?;
+							TotalOwedRewards::<T>::try_mutate(
+								cluster_id,
+								|total| -> DispatchResult {
+									*total = total
+										.checked_add(owed_reward)
+										.ok_or(Error::<T>::ArithmeticOverflow)?;
+									Ok(())
+								},
+							)?;
+
 							Self::deposit_event(Event::<T>::NotDistributedReward {
 								cluster_id,
 								era,
@@ -770,11 +1701,38 @@ pub mod pallet {
 						reward = vault_balance;
 					}
 
-					<T as pallet::Config>::Currency::transfer(
+					// delegators of the provider node are paid their share of the reward directly
+					let mut provider_reward = reward;
+					for (delegator_id, delegator_reward) in Self::pay_node_reward(
 						&updated_billing_report.vault,
 						&node_provider_id,
+						&node_pub_key,
 						reward,
-						ExistenceRequirement::AllowDeath,
+					)? {
+						provider_reward -= delegator_reward;
+						Self::record_provider_reward(
+							cluster_id,
+							era,
+							&delegator_id,
+							delegator_reward.saturated_into::<u128>(),
+							Zero::zero(),
+						)?;
+
+						Self::deposit_event(Event::<T>::DelegatorRewarded {
+							cluster_id,
+							era,
+							node_provider_id: node_provider_id.clone(),
+							delegator_id,
+							rewarded: delegator_reward.saturated_into::<u128>(),
+						});
+					}
+
+					Self::record_provider_reward(
+						cluster_id,
+						era,
+						&node_provider_id,
+						provider_reward.saturated_into::<u128>(),
+						owed_reward,
 					)?;
 
 					reward_ = reward.saturated_into::<u128>();
@@ -813,7 +1771,9 @@ pub mod pallet {
 			era: DdcEra,
 		) -> DispatchResult {
 			let caller = ensure_signed(origin)?;
-			ensure!(Self::authorised_caller() == Some(caller), Error::<T>::Unauthorised);
+			if !Self::vote_for_proposal(caller, cluster_id, era, Self::proposal_hash(7, ()))? {
+				return Ok(())
+			}
 
 			let mut billing_report = ActiveBillingReports::<T>::try_get(cluster_id, era)
 				.map_err(|_| Error::<T>::BillingReportDoesNotExist)?;
@@ -827,6 +1787,10 @@ pub mod pallet {
 				&billing_report.rewarding_processed_batches,
 				&billing_report.rewarding_max_batch_index,
 			)?;
+			ensure!(
+				billing_report.rewarded_usage == billing_report.total_node_usage,
+				Error::<T>::UsageMismatch
+			);
 
 			let expected_amount_to_reward = (|| -> Option<u128> {
 				billing_report
@@ -850,6 +1814,11 @@ pub mod pallet {
 
 			billing_report.state = State::ProvidersRewarded;
 			ActiveBillingReports::<T>::insert(cluster_id, era, billing_report);
+			DisputePeriodEnds::<T>::insert(
+				cluster_id,
+				era,
+				<frame_system::Pallet<T>>::block_number() + T::DisputePeriod::get(),
+			);
 
 			Self::deposit_event(Event::<T>::RewardingFinished { cluster_id, era });
 
@@ -864,171 +1833,817 @@ pub mod pallet {
 			era: DdcEra,
 		) -> DispatchResult {
 			let caller = ensure_signed(origin)?;
-			ensure!(Self::authorised_caller() == Some(caller), Error::<T>::Unauthorised);
+			if !Self::vote_for_proposal(caller, cluster_id, era, Self::proposal_hash(8, ()))? {
+				return Ok(())
+			}
 
 			let mut billing_report = ActiveBillingReports::<T>::try_get(cluster_id, era)
 				.map_err(|_| Error::<T>::BillingReportDoesNotExist)?;
 
 			ensure!(billing_report.state == State::ProvidersRewarded, Error::<T>::NotExpectedState);
+			ensure!(
+				!BillingReportDisputes::<T>::contains_key(cluster_id, era),
+				Error::<T>::BillingReportDisputed
+			);
+			ensure!(
+				!BillingReportRollbacks::<T>::contains_key(cluster_id, era),
+				Error::<T>::RollbackInProgress
+			);
+			ensure!(
+				Self::dispute_period_ends(cluster_id, era)
+					.map_or(true, |ends| <frame_system::Pallet<T>>::block_number() >= ends),
+				Error::<T>::DisputePeriodNotElapsed
+			);
 
 			billing_report.charging_processed_batches.clear();
 			billing_report.rewarding_processed_batches.clear();
 			billing_report.state = State::Finalized;
 
 			ActiveBillingReports::<T>::insert(cluster_id, era, billing_report);
+			LastFinalizedEras::<T>::mutate(cluster_id, |last_era| {
+				*last_era = Some(last_era.map_or(era, |last_era| last_era.max(era)))
+			});
+			DisputePeriodEnds::<T>::remove(cluster_id, era);
+			let _ = BillingReportVotes::<T>::clear_prefix((cluster_id, era), u32::MAX, None);
+			BillingReportFunds::<T>::remove(cluster_id, era);
+			// the charge and reward records are removed on idle once the report is pruned
+			Self::schedule_pruning(
+				cluster_id,
+				era,
+				<frame_system::Pallet<T>>::block_number() + T::BillingReportRetentionPeriod::get(),
+			);
 			Self::deposit_event(Event::<T>::BillingReportFinalized { cluster_id, era });
 
 			Ok(())
 		}
-	}
 
-	fn charge_treasury_fees<T: Config>(
-		treasury_fee: u128,
-		vault: &T::AccountId,
-		treasury_vault: &T::AccountId,
-	) -> DispatchResult {
-		let amount_to_deduct = treasury_fee.saturated_into::<BalanceOf<T>>();
-		<T as pallet::Config>::Currency::transfer(
-			vault,
-			treasury_vault,
-			amount_to_deduct,
-			ExistenceRequirement::AllowDeath,
-		)
-	}
+		/// Dispute a billing report with rewarded providers before it is finalized.
+		///
+		/// Any account (e.g. a customer, node provider or validator) can open a dispute within
+		/// `DisputePeriod` blocks after the providers are rewarded. `DisputeBond` is reserved from
+		/// the disputer and the report can not be finalized until governance settles the dispute.
+		#[pallet::call_index(9)]
+		#[pallet::weight(T::WeightInfo::dispute_billing_report())]
+		pub fn dispute_billing_report(
+			origin: OriginFor<T>,
+			cluster_id: ClusterId,
+			era: DdcEra,
+		) -> DispatchResult {
+			let disputer = ensure_signed(origin)?;
 
-	fn charge_cluster_reserve_fees<T: Config>(
-		cluster_reserve_fee: u128,
-		vault: &T::AccountId,
-		reserve_vault: &T::AccountId,
-	) -> DispatchResult {
-		let amount_to_deduct = cluster_reserve_fee.saturated_into::<BalanceOf<T>>();
-		<T as pallet::Config>::Currency::transfer(
-			vault,
-			reserve_vault,
-			amount_to_deduct,
-			ExistenceRequirement::AllowDeath,
-		)
-	}
+			let billing_report = ActiveBillingReports::<T>::try_get(cluster_id, era)
+				.map_err(|_| Error::<T>::BillingReportDoesNotExist)?;
+
+			ensure!(billing_report.state == State::ProvidersRewarded, Error::<T>::NotExpectedState);
+			ensure!(
+				!BillingReportDisputes::<T>::contains_key(cluster_id, era),
+				Error::<T>::DisputeAlreadyOpened
+			);
+			ensure!(
+				Self::dispute_period_ends(cluster_id, era)
+					.map_or(false, |ends| <frame_system::Pallet<T>>::block_number() < ends),
+				Error::<T>::DisputePeriodElapsed
+			);
 
-	fn get_current_exposure_ratios<T: Config>(
-	) -> Result<Vec<(T::AccountId, Perquintill)>, DispatchError> {
-		let mut total_score = 0;
-		let mut individual_scores: Vec<(T::AccountId, u64)> = Vec::new();
-		for staker_id in T::NominatorsAndValidatorsList::iter() {
-			let s = T::NominatorsAndValidatorsList::get_score(&staker_id)
-				.map_err(|_| Error::<T>::ScoreRetrievalError)?;
-			let score = T::VoteScoreToU64::convert(s);
-			total_score += score;
+			let bond = T::DisputeBond::get();
+			<T as pallet::Config>::Currency::reserve(&disputer, bond)?;
+			BillingReportDisputes::<T>::insert(
+				cluster_id,
+				era,
+				Dispute { disputer: disputer.clone(), bond },
+			);
 
-			individual_scores.push((staker_id, score));
-		}
+			Self::deposit_event(Event::<T>::BillingReportDisputed {
+				cluster_id,
+				era,
+				disputer,
+				bond,
+			});
 
-		let mut result = Vec::new();
-		for (staker_id, score) in individual_scores {
-			let ratio = Perquintill::from_rational(score, total_score);
-			result.push((staker_id, ratio));
+			Ok(())
 		}
 
-		Ok(result)
-	}
+		/// Settle an open dispute against a billing report.
+		#[pallet::call_index(10)]
+		#[pallet::weight(T::WeightInfo::settle_dispute())]
+		pub fn settle_dispute(
+			origin: OriginFor<T>,
+			cluster_id: ClusterId,
+			era: DdcEra,
+			resolution: DisputeResolution,
+		) -> DispatchResult {
+			ensure_root(origin)?; // requires Governance approval
 
-	fn charge_validator_fees<T: Config>(
-		validators_fee: u128,
-		vault: &T::AccountId,
-	) -> DispatchResult {
-		let stakers = get_current_exposure_ratios::<T>()?;
+			let dispute = BillingReportDisputes::<T>::take(cluster_id, era)
+				.ok_or(Error::<T>::DisputeDoesNotExist)?;
+
+			match resolution.clone() {
+				DisputeResolution::Rejected => {
+					let treasury = T::TreasuryVisitor::get_account_id();
+					<T as pallet::Config>::Currency::repatriate_reserved(
+						&dispute.disputer,
+						&treasury,
+						dispute.bond,
+						BalanceStatus::Free,
+					)?;
+				},
+				DisputeResolution::Adjusted { total_customer_charge, total_node_usage } => {
+					let mut billing_report = ActiveBillingReports::<T>::try_get(cluster_id, era)
+						.map_err(|_| Error::<T>::BillingReportDoesNotExist)?;
+
+					let current_charge = (|| -> Option<u128> {
+						billing_report
+							.total_customer_charge
+							.transfer
+							.checked_add(billing_report.total_customer_charge.storage)?
+							.checked_add(billing_report.total_customer_charge.puts)?
+							.checked_add(billing_report.total_customer_charge.gets)
+					})()
+					.ok_or(Error::<T>::ArithmeticOverflow)?;
+					let corrected_charge = (|| -> Option<u128> {
+						total_customer_charge
+							.transfer
+							.checked_add(total_customer_charge.storage)?
+							.checked_add(total_customer_charge.puts)?
+							.checked_add(total_customer_charge.gets)
+					})()
+					.ok_or(Error::<T>::ArithmeticOverflow)?;
+					// the funds are already paid out, so the charges can only be lowered
+					ensure!(corrected_charge <= current_charge, Error::<T>::BadRequest);
 
-		for (staker_id, ratio) in stakers.iter() {
-			let amount_to_deduct = *ratio * validators_fee;
+					billing_report.total_customer_charge = total_customer_charge;
+					billing_report.total_node_usage = total_node_usage;
+					ActiveBillingReports::<T>::insert(cluster_id, era, &billing_report);
+					// the report is settled, it can not be disputed again
+					DisputePeriodEnds::<T>::remove(cluster_id, era);
 
-			<T as pallet::Config>::Currency::transfer(
-				vault,
-				staker_id,
-				amount_to_deduct.saturated_into::<BalanceOf<T>>(),
-				ExistenceRequirement::AllowDeath,
-			)?;
+					if corrected_charge < current_charge {
+						Self::begin_rollback(
+							cluster_id,
+							era,
+							&billing_report,
+							RollbackReason::Adjusted,
+							Perquintill::from_rational(
+								current_charge - corrected_charge,
+								current_charge,
+							),
+						)?;
+					}
+					<T as pallet::Config>::Currency::unreserve(&dispute.disputer, dispute.bond);
+				},
+				DisputeResolution::Reverted => {
+					let mut billing_report = ActiveBillingReports::<T>::try_get(cluster_id, era)
+						.map_err(|_| Error::<T>::BillingReportDoesNotExist)?;
+
+					// nothing can be done with the report from now on, it is only rolled back
+					billing_report.state = State::Aborted;
+					ActiveBillingReports::<T>::insert(cluster_id, era, &billing_report);
+					DisputePeriodEnds::<T>::remove(cluster_id, era);
+
+					Self::begin_rollback(
+						cluster_id,
+						era,
+						&billing_report,
+						RollbackReason::Reverted,
+						Perquintill::one(),
+					)?;
+					<T as pallet::Config>::Currency::unreserve(&dispute.disputer, dispute.bond);
+				},
+			}
+
+			Self::deposit_event(Event::<T>::DisputeSettled {
+				cluster_id,
+				era,
+				disputer: dispute.disputer,
+				resolution,
+			});
+
+			Ok(())
 		}
 
-		Ok(())
-	}
+		/// Pays out the reward owed to the caller for the node from previous eras, as far as the
+		/// debts repaid by the customers of the cluster allow. The delegators of the node are paid
+		/// their share of the reward.
+		///
+		/// The dispatch origin for this call must be _Signed_ by the node provider.
+		///
+		/// Emits `OwedRewardPaid`, `OwedDelegatorRewardPaid`.
+		#[pallet::call_index(11)]
+		#[pallet::weight(T::WeightInfo::claim_owed_reward())]
+		pub fn claim_owed_reward(
+			origin: OriginFor<T>,
+			cluster_id: ClusterId,
+			node_pub_key: NodePubKey,
+		) -> DispatchResult {
+			let node_provider_id = ensure_signed(origin)?;
 
-	fn get_node_reward(
-		node_usage: &NodeUsage,
-		total_nodes_usage: &NodeUsage,
-		total_customer_charge: &CustomerCharge,
-	) -> Option<NodeReward> {
-		let mut node_reward = NodeReward::default();
+			ensure!(
+				!Self::owed_node_reward(cluster_id, &node_provider_id, &node_pub_key).is_zero(),
+				Error::<T>::NoOwedReward
+			);
 
-		let mut ratio = Perquintill::from_rational(
-			node_usage.transferred_bytes as u128,
-			total_nodes_usage.transferred_bytes as u128,
-		);
+			let paid = Self::pay_owed_reward(cluster_id, &node_provider_id, &node_pub_key)?;
+			ensure!(!paid.is_zero(), Error::<T>::NotEnoughVaultBalance);
 
-		// ratio multiplied by X will be > 0, < X no overflow
-		node_reward.transfer = ratio * total_customer_charge.transfer;
+			Ok(())
+		}
 
-		ratio = Perquintill::from_rational(
-			node_usage.stored_bytes as u128,
-			total_nodes_usage.stored_bytes as u128,
-		);
-		node_reward.storage = ratio * total_customer_charge.storage;
+		/// Pays out the share of the staker in the validators fees collected for the `era` of the
+		/// cluster. The share is proportional to the score of the staker relative to the total
+		/// score, both snapshotted by `snapshot_validator_scores` after the fees were collected.
+		///
+		/// The dispatch origin for this call must be _Signed_, any account can pay out the fees
+		/// of any staker.
+		///
+		/// Emits `ValidatorFeesClaimed`.
+		#[pallet::call_index(12)]
+		#[pallet::weight(T::WeightInfo::claim_validator_fees())]
+		pub fn claim_validator_fees(
+			origin: OriginFor<T>,
+			staker_id: T::AccountId,
+			cluster_id: ClusterId,
+			era: DdcEra,
+		) -> DispatchResult {
+			ensure_signed(origin)?;
 
-		ratio =
-			Perquintill::from_rational(node_usage.number_of_puts, total_nodes_usage.number_of_puts);
-		node_reward.puts = ratio * total_customer_charge.puts;
+			let mut pot = ValidatorFeesPots::<T>::try_get(cluster_id, era)
+				.map_err(|_| Error::<T>::NoValidatorFees)?;
+			ensure!(pot.snapshotted, Error::<T>::ValidatorScoresNotSnapshotted);
+			ensure!(
+				!ValidatorFeesClaims::<T>::contains_key((cluster_id, era), &staker_id),
+				Error::<T>::ValidatorFeesAlreadyClaimed
+			);
 
-		ratio =
-			Perquintill::from_rational(node_usage.number_of_gets, total_nodes_usage.number_of_gets);
-		node_reward.gets = ratio * total_customer_charge.gets;
+			let score = ValidatorFeesScores::<T>::get((cluster_id, era), &staker_id)
+				.ok_or(Error::<T>::NoValidatorFees)?;
+			// no staker had a score when the pot was snapshotted
+			ensure!(!pot.total_score.is_zero(), Error::<T>::NoValidatorFees);
+			let ratio = Perquintill::from_rational(score, pot.total_score);
+			let remaining_fees = pot
+				.total_fees
+				.checked_sub(pot.claimed_fees)
+				.ok_or(Error::<T>::ArithmeticOverflow)?;
+			let amount = (ratio * pot.total_fees).min(remaining_fees);
+			ensure!(!amount.is_zero(), Error::<T>::NoValidatorFees);
 
-		Some(node_reward)
-	}
+			<T as pallet::Config>::Currency::transfer(
+				&Self::validator_fees_pot_account_id(),
+				&staker_id,
+				amount.saturated_into::<BalanceOf<T>>(),
+				ExistenceRequirement::AllowDeath,
+			)?;
 
-	fn get_customer_charge<T: Config>(
-		cluster_id: ClusterId,
-		usage: &CustomerUsage,
-		start_era: i64,
-		end_era: i64,
-	) -> Result<CustomerCharge, Error<T>> {
-		let mut total = CustomerCharge::default();
+			pot.claimed_fees =
+				pot.claimed_fees.checked_add(amount).ok_or(Error::<T>::ArithmeticOverflow)?;
+			ValidatorFeesPots::<T>::insert(cluster_id, era, pot);
+			ValidatorFeesClaims::<T>::insert((cluster_id, era), &staker_id, amount);
 
-		let pricing = T::ClusterVisitor::get_pricing_params(&cluster_id)
-			.map_err(|_| Error::<T>::NotExpectedClusterState)?;
+			Self::deposit_event(Event::<T>::ValidatorFeesClaimed {
+				cluster_id,
+				era,
+				staker_id,
+				amount,
+			});
 
-		total.transfer = (|| -> Option<u128> {
-			(usage.transferred_bytes as u128)
-				.checked_mul(pricing.unit_per_mb_streamed)?
-				.checked_div(byte_unit::MEBIBYTE)
-		})()
-		.ok_or(Error::<T>::ArithmeticOverflow)?;
+			Ok(())
+		}
 
-		// Calculate the duration of the period in seconds
-		let duration_seconds = end_era - start_era;
-		let seconds_in_month = 30.44 * 24.0 * 3600.0;
-		let fraction_of_month =
-			Perquintill::from_rational(duration_seconds as u64, seconds_in_month as u64);
+		/// Abort a billing report stuck before its providers are rewarded and roll it back.
+		///
+		/// The report is moved to the `Aborted` state and the fees paid with it are clawed back.
+		/// The rewards paid with the report are then clawed back and the charges refunded by
+		/// `roll_back_billing_report`. Once every customer is refunded in full, the report is
+		/// removed so that the DAC can begin it again. If the clawed back funds fall short, the
+		/// report stays aborted and the era is never billed again.
+		///
+		/// Emits `BillingReportAborted`.
+		#[pallet::call_index(13)]
+		#[pallet::weight(T::WeightInfo::abort_billing_report())]
+		pub fn abort_billing_report(
+			origin: OriginFor<T>,
+			cluster_id: ClusterId,
+			era: DdcEra,
+		) -> DispatchResult {
+			ensure_root(origin)?; // requires Governance approval
 
-		total.storage = fraction_of_month *
-			(|| -> Option<u128> {
-				(usage.stored_bytes as u128)
-					.checked_mul(pricing.unit_per_mb_stored)?
-					.checked_div(byte_unit::MEBIBYTE)
-			})()
-			.ok_or(Error::<T>::ArithmeticOverflow)?;
+			let mut billing_report = ActiveBillingReports::<T>::try_get(cluster_id, era)
+				.map_err(|_| Error::<T>::BillingReportDoesNotExist)?;
+			// rewarded providers are settled by disputes instead
+			ensure!(
+				matches!(
+					billing_report.state,
+					State::Initialized |
+						State::ChargingCustomers | State::CustomersChargedWithFees |
+						State::RewardingProviders
+				),
+				Error::<T>::NotExpectedState
+			);
+			ensure!(
+				!BillingReportRollbacks::<T>::contains_key(cluster_id, era),
+				Error::<T>::RollbackInProgress
+			);
 
-		total.gets = (usage.number_of_gets as u128)
-			.checked_mul(pricing.unit_per_get_request)
-			.ok_or(Error::<T>::ArithmeticOverflow)?;
+			let state = billing_report.state.clone();
+			billing_report.state = State::Aborted;
+			ActiveBillingReports::<T>::insert(cluster_id, era, &billing_report);
 
-		total.puts = (usage.number_of_puts as u128)
-			.checked_mul(pricing.unit_per_put_request)
-			.ok_or(Error::<T>::ArithmeticOverflow)?;
+			Self::begin_rollback(
+				cluster_id,
+				era,
+				&billing_report,
+				RollbackReason::Aborted,
+				Perquintill::one(),
+			)?;
 
-		Ok(total)
-	}
+			Self::deposit_event(Event::<T>::BillingReportAborted { cluster_id, era, state });
 
-	fn validate_batches<T: Config>(
-		batches: &BoundedBTreeSet<BatchIndex, MaxBatchesCount>,
+			Ok(())
+		}
+
+		/// Roll back a billing report after its dispute is settled with the `Adjusted` or
+		/// `Reverted` resolution or after it is aborted.
+		///
+		/// The fees paid with the report are clawed back when the dispute is settled or the
+		/// report is aborted. Every call processes up to `max_records` of the records of the
+		/// report, so the call is repeated until all of them are processed. The rewards paid to
+		/// the node providers and their delegators are clawed back first from their transferable
+		/// balance, cancelling the rewards left owed to them in the same share. Locked or reserved
+		/// funds are never clawed back, the amount not recovered is recorded with the rollback. The
+		/// charges are then refunded to the payment sources they were taken from, in full unless
+		/// the clawed back funds fall short. The debts incurred with the report are cancelled and
+		/// the debts repaid with it are restored in the same share.
+		///
+		/// The dispatch origin for this call must be _Signed_, any account can roll back the
+		/// report.
+		///
+		/// Emits `RewardClawedBack` for every node provider or delegator, `ChargeRefunded` for
+		/// every customer and `BillingReportRolledBack` once the report is rolled back.
+		#[pallet::call_index(14)]
+		#[pallet::weight(T::WeightInfo::roll_back_billing_report(*max_records))]
+		pub fn roll_back_billing_report(
+			origin: OriginFor<T>,
+			cluster_id: ClusterId,
+			era: DdcEra,
+			max_records: u32,
+		) -> DispatchResult {
+			ensure_signed(origin)?;
+			ensure!(max_records <= MaxBatchSize::get() as u32, Error::<T>::BatchSizeIsOutOfBounds);
+
+			let mut rollback = BillingReportRollbacks::<T>::try_get(cluster_id, era)
+				.map_err(|_| Error::<T>::NotExpectedState)?;
+			let mut billing_report = ActiveBillingReports::<T>::try_get(cluster_id, era)
+				.map_err(|_| Error::<T>::BillingReportDoesNotExist)?;
+			let mut remaining = max_records as usize;
+
+			// the rewards are clawed back first, as they fund the refunds
+			if rollback.refund_ratio.is_none() {
+				let records: Vec<(T::AccountId, ProviderRewardRecord)> =
+					RewardedProviders::<T>::iter_prefix((cluster_id, era))
+						.take(remaining)
+						.collect();
+				remaining -= records.len();
+				for (account_id, record) in records {
+					RewardedProviders::<T>::remove((cluster_id, era), &account_id);
+					Self::claw_back_reward(
+						cluster_id,
+						era,
+						&mut billing_report,
+						&mut rollback,
+						account_id,
+						record,
+					);
+				}
+
+				if RewardedProviders::<T>::iter_prefix((cluster_id, era)).next().is_none() {
+					let shortfall = rollback.refundable.saturating_sub(rollback.available);
+					// rounding of the clawed back shares is tolerated
+					rollback.refund_ratio = Some(if shortfall > MaxDust::get() {
+						Perquintill::from_rational(rollback.available, rollback.refundable)
+					} else {
+						Perquintill::one()
+					});
+				}
+			}
+
+			if let Some(refund_ratio) = rollback.refund_ratio {
+				let charges: Vec<((T::AccountId, PaymentSource), PaymentSourceCharge)> =
+					ChargedPaymentSources::<T>::iter_prefix((cluster_id, era))
+						.take(remaining)
+						.collect();
+				remaining -= charges.len();
+				for ((customer_id, source), charge) in charges {
+					ChargedPaymentSources::<T>::remove((cluster_id, era), (&customer_id, source));
+					Self::refund_payment_source(
+						cluster_id,
+						era,
+						&billing_report.vault,
+						customer_id,
+						source,
+						charge,
+						rollback.share,
+						refund_ratio,
+					)?;
+				}
+			}
+
+			// the debts are rolled back once the charges are refunded
+			if let Some(refund_ratio) = rollback.refund_ratio.filter(|_| {
+				ChargedPaymentSources::<T>::iter_prefix((cluster_id, era)).next().is_none()
+			}) {
+				let records: Vec<(T::AccountId, CustomerChargeRecord)> =
+					ChargedCustomers::<T>::iter_prefix((cluster_id, era)).take(remaining).collect();
+				remaining -= records.len();
+				for (customer_id, record) in records {
+					ChargedCustomers::<T>::remove((cluster_id, era), &customer_id);
+					Self::roll_back_customer_debt(
+						cluster_id,
+						era,
+						customer_id,
+						record,
+						rollback.share,
+						refund_ratio,
+					);
+				}
+
+				let records: Vec<((T::AccountId, BucketId), BucketDebtRecord)> =
+					ChargedBuckets::<T>::iter_prefix((cluster_id, era)).take(remaining).collect();
+				for ((owner_id, bucket_id), record) in records {
+					ChargedBuckets::<T>::remove((cluster_id, era), (&owner_id, bucket_id));
+					Self::roll_back_bucket_debt(
+						cluster_id,
+						era,
+						&owner_id,
+						bucket_id,
+						record,
+						rollback.share,
+						refund_ratio,
+					);
+				}
+
+				if ChargedCustomers::<T>::iter_prefix((cluster_id, era)).next().is_none() &&
+					ChargedBuckets::<T>::iter_prefix((cluster_id, era)).next().is_none()
+				{
+					// a reverted report stays aborted, so that the era is never billed again, as
+					// does an aborted report which could not be refunded in full
+					if rollback.reason != RollbackReason::Adjusted {
+						let _ = BillingReportVotes::<T>::clear_prefix(
+							(cluster_id, era),
+							u32::MAX,
+							None,
+						);
+						let _ =
+							ExecutedProposals::<T>::clear_prefix((cluster_id, era), u32::MAX, None);
+					}
+					// an aborted report refunded in full is removed to be billed again
+					if rollback.reason == RollbackReason::Aborted && refund_ratio.is_one() {
+						ActiveBillingReports::<T>::remove(cluster_id, era);
+						DisputePeriodEnds::<T>::remove(cluster_id, era);
+					} else {
+						ActiveBillingReports::<T>::insert(cluster_id, era, billing_report);
+					}
+					BillingReportRollbacks::<T>::remove(cluster_id, era);
+					BillingReportFunds::<T>::remove(cluster_id, era);
+
+					Self::deposit_event(Event::<T>::BillingReportRolledBack {
+						cluster_id,
+						era,
+						refund_ratio,
+					});
+					return Ok(())
+				}
+			}
+
+			ActiveBillingReports::<T>::insert(cluster_id, era, billing_report);
+			BillingReportRollbacks::<T>::insert(cluster_id, era, rollback);
+
+			Ok(())
+		}
+
+		/// Snapshot the scores in `NominatorsAndValidatorsList` of up to `max_stakers` stakers
+		/// for the validators fees pot of the `era` of the cluster. Each call continues from the
+		/// last snapshotted staker, once every staker is snapshotted the fees can be claimed with
+		/// `claim_validator_fees`.
+		///
+		/// The dispatch origin for this call must be _Signed_, any account can snapshot the scores.
+		///
+		/// Emits `ValidatorScoresSnapshotted` once the scores of all the stakers are snapshotted.
+		#[pallet::call_index(15)]
+		#[pallet::weight(T::WeightInfo::snapshot_validator_scores(*max_stakers))]
+		pub fn snapshot_validator_scores(
+			origin: OriginFor<T>,
+			cluster_id: ClusterId,
+			era: DdcEra,
+			max_stakers: u32,
+		) -> DispatchResult {
+			ensure_signed(origin)?;
+			ensure!(
+				max_stakers > 0 && max_stakers <= MaxBatchSize::get() as u32,
+				Error::<T>::BatchSizeIsOutOfBounds
+			);
+
+			let mut pot = ValidatorFeesPots::<T>::try_get(cluster_id, era)
+				.map_err(|_| Error::<T>::NoValidatorFees)?;
+			ensure!(!pot.snapshotted, Error::<T>::ValidatorScoresAlreadySnapshotted);
+
+			// the snapshot starts over when the last snapshotted staker left the list, the
+			// stakers already snapshotted are skipped
+			let mut stakers = ValidatorScoresSnapshotCursors::<T>::get(cluster_id, era)
+				.and_then(|last_staker| {
+					T::NominatorsAndValidatorsList::iter_from(&last_staker).ok()
+				})
+				.unwrap_or_else(T::NominatorsAndValidatorsList::iter);
+
+			let mut last_staker = None;
+			for staker_id in stakers.by_ref().take(max_stakers as usize) {
+				if !ValidatorFeesScores::<T>::contains_key((cluster_id, era), &staker_id) {
+					let score = T::NominatorsAndValidatorsList::get_score(&staker_id)
+						.map_err(|_| Error::<T>::ScoreRetrievalError)?;
+					let score = T::VoteScoreToU64::convert(score);
+					pot.total_score = pot.total_score.saturating_add(score);
+					ValidatorFeesScores::<T>::insert((cluster_id, era), &staker_id, score);
+				}
+				last_staker = Some(staker_id);
+			}
+
+			if stakers.next().is_none() {
+				pot.snapshotted = true;
+				ValidatorScoresSnapshotCursors::<T>::remove(cluster_id, era);
+
+				Self::deposit_event(Event::<T>::ValidatorScoresSnapshotted {
+					cluster_id,
+					era,
+					total_score: pot.total_score,
+				});
+			} else if let Some(last_staker) = last_staker {
+				ValidatorScoresSnapshotCursors::<T>::insert(cluster_id, era, last_staker);
+			}
+			ValidatorFeesPots::<T>::insert(cluster_id, era, pot);
+
+			Ok(())
+		}
+	}
+
+	fn charge_treasury_fees<T: Config>(
+		treasury_fee: u128,
+		vault: &T::AccountId,
+		treasury_vault: &T::AccountId,
+	) -> DispatchResult {
+		let amount_to_deduct = treasury_fee.saturated_into::<BalanceOf<T>>();
+		<T as pallet::Config>::Currency::transfer(
+			vault,
+			treasury_vault,
+			amount_to_deduct,
+			ExistenceRequirement::AllowDeath,
+		)
+	}
+
+	fn charge_cluster_reserve_fees<T: Config>(
+		cluster_reserve_fee: u128,
+		vault: &T::AccountId,
+		reserve_vault: &T::AccountId,
+	) -> DispatchResult {
+		let amount_to_deduct = cluster_reserve_fee.saturated_into::<BalanceOf<T>>();
+		<T as pallet::Config>::Currency::transfer(
+			vault,
+			reserve_vault,
+			amount_to_deduct,
+			ExistenceRequirement::AllowDeath,
+		)
+	}
+
+	// Moves the validators fees to the pot the stakers claim them from once their scores are
+	// snapshotted, see `snapshot_validator_scores`
+	fn charge_validator_fees<T: Config>(
+		cluster_id: ClusterId,
+		era: DdcEra,
+		validators_fee: u128,
+		vault: &T::AccountId,
+	) -> DispatchResult {
+		<T as pallet::Config>::Currency::transfer(
+			vault,
+			&Pallet::<T>::validator_fees_pot_account_id(),
+			validators_fee.saturated_into::<BalanceOf<T>>(),
+			ExistenceRequirement::AllowDeath,
+		)?;
+
+		ValidatorFeesPots::<T>::mutate(cluster_id, era, |pot| {
+			let pot = pot.get_or_insert_with(ValidatorFeesPot::default);
+			pot.total_fees = pot.total_fees.saturating_add(validators_fee);
+		});
+
+		Ok(())
+	}
+
+	fn get_node_reward(
+		node_usage: &NodeUsage,
+		total_nodes_usage: &NodeUsage,
+		total_customer_charge: &CustomerCharge,
+	) -> Option<NodeReward> {
+		let mut node_reward = NodeReward::default();
+
+		let mut ratio = Perquintill::from_rational(
+			node_usage.transferred_bytes as u128,
+			total_nodes_usage.transferred_bytes as u128,
+		);
+
+		// ratio multiplied by X will be > 0, < X no overflow
+		node_reward.transfer = ratio * total_customer_charge.transfer;
+
+		ratio = Perquintill::from_rational(
+			node_usage.stored_bytes as u128,
+			total_nodes_usage.stored_bytes as u128,
+		);
+		node_reward.storage = ratio * total_customer_charge.storage;
+
+		ratio =
+			Perquintill::from_rational(node_usage.number_of_puts, total_nodes_usage.number_of_puts);
+		node_reward.puts = ratio * total_customer_charge.puts;
+
+		ratio =
+			Perquintill::from_rational(node_usage.number_of_gets, total_nodes_usage.number_of_gets);
+		node_reward.gets = ratio * total_customer_charge.gets;
+
+		Some(node_reward)
+	}
+
+	/// Charge for the usage of the customer bucket. The pricing tiers apply to the usage of all
+	/// the customer buckets, so the bucket is charged for its usage on top of `charged_usage`
+	/// already charged with the report.
+	fn get_customer_charge<T: Config>(
+		cluster_id: ClusterId,
+		customer_id: &T::AccountId,
+		bucket_id: BucketId,
+		usage: &CustomerUsage,
+		charged_usage: &CustomerUsage,
+		start_era: i64,
+		end_era: i64,
+	) -> Result<CustomerCharge, Error<T>> {
+		let mut total = CustomerCharge::default();
+
+		let pricing = T::ClusterVisitor::get_pricing_params(&cluster_id)
+			.map_err(|_| Error::<T>::NotExpectedClusterState)?;
+		let tiers = &pricing.pricing_tiers;
+
+		total.transfer = get_marginal_amount(
+			charged_usage.transferred_bytes,
+			usage.transferred_bytes,
+			|bytes| {
+				get_tiered_amount(bytes, pricing.unit_per_mb_streamed, &tiers.streamed)?
+					.checked_div(byte_unit::MEBIBYTE)
+			},
+		)
+		.ok_or(Error::<T>::ArithmeticOverflow)?;
+
+		// Storage is charged by byte-seconds, `unit_per_mb_stored` is the price per MB for the
+		// whole billing period of the cluster
+		let duration_seconds = end_era
+			.checked_sub(start_era)
+			.and_then(|duration| u64::try_from(duration).ok())
+			.ok_or(Error::<T>::ArithmeticOverflow)?;
+		total.storage =
+			get_marginal_amount(charged_usage.stored_bytes, usage.stored_bytes, |bytes| {
+				let stored_amount =
+					get_tiered_amount(bytes, pricing.unit_per_mb_stored, &tiers.stored)?;
+				get_storage_charge::<T>(stored_amount, duration_seconds, pricing.billing_period)
+					.ok()
+			})
+			.ok_or(Error::<T>::ArithmeticOverflow)?;
+
+		// `unit_per_mb_stored` prices the default replication of the cluster, so the storage is
+		// scaled by the replicas the bucket asks for and discounted for cold data
+		// the usage of a removed bucket is charged at the defaults of the cluster
+		let metadata = T::BucketVisitor::get_bucket_metadata(bucket_id).unwrap_or_default();
+		let capabilities = T::ClusterVisitor::get_capabilities(&cluster_id)
+			.map_err(|_| Error::<T>::NotExpectedClusterState)?;
+		if let Some(replication_factor) = metadata.replication_factor {
+			if capabilities.default_replication_factor > 0 {
+				total.storage = total
+					.storage
+					.checked_mul(replication_factor as u128)
+					.and_then(|storage| {
+						storage.checked_div(capabilities.default_replication_factor as u128)
+					})
+					.ok_or(Error::<T>::ArithmeticOverflow)?;
+			}
+		}
+		if metadata.storage_class == StorageClass::Cold {
+			if let Some(cold_storage_discount) = capabilities.cold_storage_discount {
+				total.storage = total.storage.saturating_sub(cold_storage_discount * total.storage);
+			}
+		}
+
+		total.gets =
+			get_marginal_amount(charged_usage.number_of_gets, usage.number_of_gets, |gets| {
+				get_tiered_amount(gets, pricing.unit_per_get_request, &tiers.gets)
+			})
+			.ok_or(Error::<T>::ArithmeticOverflow)?;
+
+		total.puts =
+			get_marginal_amount(charged_usage.number_of_puts, usage.number_of_puts, |puts| {
+				get_tiered_amount(puts, pricing.unit_per_put_request, &tiers.puts)
+			})
+			.ok_or(Error::<T>::ArithmeticOverflow)?;
+
+		let discount = T::ClusterVisitor::get_customer_discount(&cluster_id, customer_id);
+		if !discount.is_zero() {
+			for charge in
+				[&mut total.transfer, &mut total.storage, &mut total.gets, &mut total.puts]
+			{
+				*charge = charge.saturating_sub(discount * *charge);
+			}
+		}
+
+		Ok(total)
+	}
+
+	/// Amount for `quantity` units on top of the `charged_quantity` units already charged, so
+	/// the amounts of the charges sum up to the amount for their total quantity.
+	fn get_marginal_amount(
+		charged_quantity: u64,
+		quantity: u64,
+		amount: impl Fn(u64) -> Option<u128>,
+	) -> Option<u128> {
+		amount(charged_quantity.checked_add(quantity)?)?.checked_sub(amount(charged_quantity)?)
+	}
+
+	/// Amount for `quantity` units, priced by `base_price` up to the first tier threshold and
+	/// by the unit price of the reached tier above each threshold. Tiers are expected to be
+	/// sorted by ascending threshold.
+	fn get_tiered_amount(quantity: u64, base_price: u128, tiers: &[PricingTier]) -> Option<u128> {
+		let mut amount: u128 = 0;
+		let mut price = base_price;
+		let mut priced_quantity: u64 = 0;
+
+		for tier in tiers {
+			if quantity <= tier.threshold {
+				break
+			}
+			let tier_quantity = tier.threshold.checked_sub(priced_quantity)?;
+			amount = amount.checked_add((tier_quantity as u128).checked_mul(price)?)?;
+			priced_quantity = tier.threshold;
+			price = tier.unit_price;
+		}
+
+		let rest_quantity = quantity.checked_sub(priced_quantity)?;
+		amount.checked_add((rest_quantity as u128).checked_mul(price)?)
+	}
+
+	/// Charge for keeping the stored data during `duration_seconds`, computed with integer
+	/// arithmetic only: `stored_amount * duration_seconds / (MB * billing_period)`, rounded
+	/// down, where `stored_amount` is the stored bytes multiplied by their price per MB.
+	fn get_storage_charge<T: Config>(
+		stored_amount: u128,
+		duration_seconds: u64,
+		billing_period: u64,
+	) -> Result<u128, Error<T>> {
+		let period_mb_seconds = byte_unit::MEBIBYTE
+			.checked_mul(billing_period as u128)
+			.filter(|period| !period.is_zero())
+			.ok_or(Error::<T>::ArithmeticOverflow)?;
+
+		multiply_by_rational_with_rounding(
+			stored_amount,
+			duration_seconds as u128,
+			period_mb_seconds,
+			Rounding::Down,
+		)
+		.ok_or(Error::<T>::ArithmeticOverflow)
+	}
+
+	fn get_missing_batches(
+		max_batch_index: BatchIndex,
+		processed_batches: &BoundedBTreeSet<BatchIndex, MaxBatchesCount>,
+	) -> Vec<BatchIndex> {
+		(0..=max_batch_index)
+			.filter(|index| !processed_batches.contains(index))
+			.collect()
+	}
+
+	/// Attributes the repaid debt to the charge components in proportion to the current usage
+	/// charge of the customer, so the recovered funds are rewarded to providers like any other
+	/// charge. Without current usage the whole amount is attributed to storage.
+	fn split_debt_repayment(
+		debt_repaid: u128,
+		usage_charge: &CustomerCharge,
+		total_usage_charge: u128,
+	) -> CustomerCharge {
+		if total_usage_charge.is_zero() {
+			return CustomerCharge { storage: debt_repaid, ..Default::default() }
+		}
+
+		let transfer =
+			Perquintill::from_rational(usage_charge.transfer, total_usage_charge) * debt_repaid;
+		let puts = Perquintill::from_rational(usage_charge.puts, total_usage_charge) * debt_repaid;
+		let gets = Perquintill::from_rational(usage_charge.gets, total_usage_charge) * debt_repaid;
+		// the rounding remainder goes to storage so the components sum up to the repaid amount
+		let storage =
+			debt_repaid.saturating_sub(transfer).saturating_sub(puts).saturating_sub(gets);
+
+		CustomerCharge { transfer, storage, puts, gets }
+	}
+
+	fn validate_batches<T: Config>(
+		batches: &BoundedBTreeSet<BatchIndex, MaxBatchesCount>,
 		max_batch_index: &BatchIndex,
 	) -> DispatchResult {
 		// Check if the Vec contains all integers between 1 and rewarding_max_batch_index
@@ -1043,20 +2658,29 @@ pub mod pallet {
 		Ok(())
 	}
 
+	fn validate_batch_proof<T: Config>(
+		root: &H256,
+		batch_leaf: &H256,
+		batch_proof: &[H256],
+	) -> DispatchResult {
+		ensure!(
+			batch_proof.len() <= MaxBatchProofLength::get() as usize &&
+				merkle::verify_proof(root, batch_leaf, batch_proof),
+			Error::<T>::BatchProofVerificationFailed
+		);
+
+		Ok(())
+	}
+
 	#[pallet::genesis_config]
 	pub struct GenesisConfig<T: Config> {
 		pub feeder_account: Option<T::AccountId>,
-		pub authorised_caller: Option<T::AccountId>,
 		pub debtor_customers: Vec<(ClusterId, T::AccountId, u128)>,
 	}
 
 	impl<T: Config> Default for GenesisConfig<T> {
 		fn default() -> Self {
-			GenesisConfig {
-				feeder_account: None,
-				authorised_caller: Default::default(),
-				debtor_customers: Default::default(),
-			}
+			GenesisConfig { feeder_account: None, debtor_customers: Default::default() }
 		}
 	}
 
@@ -1079,7 +2703,6 @@ pub mod pallet {
 				}
 			}
 
-			AuthorisedCaller::<T>::set(self.authorised_caller.clone());
 			for (cluster_id, customer_id, debt) in &self.debtor_customers {
 				DebtorCustomers::<T>::insert(cluster_id, customer_id, debt);
 			}
@@ -1091,6 +2714,17 @@ pub mod pallet {
 			T::PalletId::get().into_account_truncating()
 		}
 
+		/// Account holding the validators fees until they are claimed by the stakers.
+		pub fn validator_fees_pot_account_id() -> T::AccountId {
+			T::PalletId::get().into_sub_account_truncating(*b"validators")
+		}
+
+		/// Account holding the debts repaid by the customers of the cluster until they are paid
+		/// out as the rewards owed to its node providers.
+		pub fn owed_rewards_account_id(cluster_id: ClusterId) -> T::AccountId {
+			T::PalletId::get().into_sub_account_truncating((*b"owed", blake2_128(&cluster_id[..])))
+		}
+
 		pub fn sub_account_id(cluster_id: ClusterId, era: DdcEra) -> T::AccountId {
 			let mut bytes = Vec::new();
 			bytes.extend_from_slice(&cluster_id[..]);
@@ -1102,5 +2736,1117 @@ pub mod pallet {
 			// be fulfilled with trailing zeros.
 			T::PalletId::get().into_sub_account_truncating(hash)
 		}
+
+		/// Hash identifying a billing report proposal, i.e. a call together with its parameters.
+		pub fn proposal_hash<Params: Encode>(call_index: u8, params: Params) -> H256 {
+			H256((call_index, params).using_encoded(blake2_256))
+		}
+
+		/// Progress of the billing report of the cluster for the era. Reports pruned after the
+		/// retention period are reported as finalized from their archived summary.
+		pub fn billing_report_info(
+			cluster_id: ClusterId,
+			era: DdcEra,
+		) -> Option<BillingReportInfo> {
+			ActiveBillingReports::<T>::get(cluster_id, era)
+				.map(Self::to_billing_report_info)
+				.or_else(|| {
+					ArchivedBillingReports::<T>::get(cluster_id, era).map(|summary| {
+						BillingReportInfo {
+							state: State::Finalized,
+							start_era: summary.start_era,
+							end_era: summary.end_era,
+							total_customer_charge: summary.total_customer_charge,
+							total_distributed_reward: summary.total_distributed_reward,
+							total_node_usage: summary.total_node_usage,
+							charging_max_batch_index: summary.charging_max_batch_index,
+							charging_missing_batches: Vec::new(),
+							rewarding_max_batch_index: summary.rewarding_max_batch_index,
+							rewarding_missing_batches: Vec::new(),
+						}
+					})
+				})
+		}
+
+		/// Progress of all the active billing reports of the cluster, by era.
+		pub fn billing_reports_info(cluster_id: ClusterId) -> Vec<(DdcEra, BillingReportInfo)> {
+			ActiveBillingReports::<T>::iter_prefix(cluster_id)
+				.map(|(era, report)| (era, Self::to_billing_report_info(report)))
+				.collect()
+		}
+
+		/// Outstanding debts of the customers in the cluster.
+		pub fn customers_debts(cluster_id: ClusterId) -> Vec<(T::AccountId, u128)> {
+			DebtorCustomers::<T>::iter_prefix(cluster_id).collect()
+		}
+
+		/// Adds the charge of the customer to the records of the billing report.
+		#[allow(clippy::too_many_arguments)]
+		fn record_customer_charge(
+			cluster_id: ClusterId,
+			era: DdcEra,
+			customer_id: &T::AccountId,
+			budgeted_bucket_id: Option<BucketId>,
+			usage: &CustomerUsage,
+			charged: u128,
+			debt_repaid: u128,
+			debt_incurred: u128,
+		) -> DispatchResult {
+			BillingReportFunds::<T>::try_mutate(cluster_id, era, |funds| -> DispatchResult {
+				funds.collected = funds
+					.collected
+					.checked_add(charged)
+					.and_then(|collected| collected.checked_add(debt_repaid))
+					.ok_or(Error::<T>::ArithmeticOverflow)?;
+				Ok(())
+			})?;
+
+			// the debts of a budgeted bucket are rolled back to the bucket
+			let (debt_repaid, debt_incurred) = match budgeted_bucket_id {
+				Some(bucket_id) => {
+					ChargedBuckets::<T>::try_mutate(
+						(cluster_id, era),
+						(customer_id, bucket_id),
+						|record| -> DispatchResult {
+							let record = record.get_or_insert_with(Default::default);
+							record.debt_repaid = record
+								.debt_repaid
+								.checked_add(debt_repaid)
+								.ok_or(Error::<T>::ArithmeticOverflow)?;
+							record.debt_incurred = record
+								.debt_incurred
+								.checked_add(debt_incurred)
+								.ok_or(Error::<T>::ArithmeticOverflow)?;
+							Ok(())
+						},
+					)?;
+					(Zero::zero(), Zero::zero())
+				},
+				None => (debt_repaid, debt_incurred),
+			};
+
+			ChargedCustomers::<T>::try_mutate(
+				(cluster_id, era),
+				customer_id,
+				|record| -> DispatchResult {
+					let record = record.get_or_insert_with(Default::default);
+					record.charged = record
+						.charged
+						.checked_add(charged)
+						.ok_or(Error::<T>::ArithmeticOverflow)?;
+					record.debt_repaid = record
+						.debt_repaid
+						.checked_add(debt_repaid)
+						.ok_or(Error::<T>::ArithmeticOverflow)?;
+					record.debt_incurred = record
+						.debt_incurred
+						.checked_add(debt_incurred)
+						.ok_or(Error::<T>::ArithmeticOverflow)?;
+					record.usage =
+						record.usage.checked_add(usage).ok_or(Error::<T>::ArithmeticOverflow)?;
+					Ok(())
+				},
+			)?;
+
+			Ok(())
+		}
+
+		/// Adds the reward paid to the node provider or delegator and the reward left owed to
+		/// the records of the billing report.
+		fn record_provider_reward(
+			cluster_id: ClusterId,
+			era: DdcEra,
+			account_id: &T::AccountId,
+			rewarded: u128,
+			owed: u128,
+		) -> DispatchResult {
+			RewardedProviders::<T>::try_mutate(
+				(cluster_id, era),
+				account_id,
+				|record| -> DispatchResult {
+					let record = record.get_or_insert_with(Default::default);
+					record.rewarded = record
+						.rewarded
+						.checked_add(rewarded)
+						.ok_or(Error::<T>::ArithmeticOverflow)?;
+					record.owed =
+						record.owed.checked_add(owed).ok_or(Error::<T>::ArithmeticOverflow)?;
+					Ok(())
+				},
+			)
+		}
+
+		/// Refunds the share of the charge taken from the payment source of the customer with a
+		/// rolled back billing report to the same source. Only `refund_ratio` of the share is
+		/// refunded if the report funds fall short. A charge the source can not take back, e.g.
+		/// of a bucket removed since, is refunded to the customer account instead.
+		#[allow(clippy::too_many_arguments)]
+		fn refund_payment_source(
+			cluster_id: ClusterId,
+			era: DdcEra,
+			vault: &T::AccountId,
+			customer_id: T::AccountId,
+			source: PaymentSource,
+			charge: PaymentSourceCharge,
+			share: Perquintill,
+			refund_ratio: Perquintill,
+		) -> DispatchResult {
+			let refunded = refund_ratio * (share * charge.charged);
+			// a partial charge in the asset can be worth less than a native unit
+			let asset_refunded = refund_ratio * (share * charge.asset_charged);
+			if refunded.is_zero() && asset_refunded.is_zero() {
+				return Ok(())
+			}
+
+			let result = frame_support::storage::with_storage_layer(|| -> DispatchResult {
+				match source {
+					PaymentSource::Asset(_) => {
+						// the cluster reserve paid the vault for the asset and takes it back
+						let reserve = T::ClusterVisitor::get_reserve_account_id(&cluster_id)
+							.map_err(|_| Error::<T>::NotExpectedClusterState)?;
+						<T as pallet::Config>::Currency::transfer(
+							vault,
+							&reserve,
+							refunded.saturated_into::<BalanceOf<T>>(),
+							ExistenceRequirement::AllowDeath,
+						)?;
+						T::CustomerCharger::refund_content_owner(
+							source,
+							customer_id.clone(),
+							reserve,
+							asset_refunded,
+						)
+					},
+					PaymentSource::Deposit | PaymentSource::BucketBudget(_) =>
+						T::CustomerCharger::refund_content_owner(
+							source,
+							customer_id.clone(),
+							vault.clone(),
+							refunded,
+						),
+				}
+			});
+			if let Err(error) = result {
+				<T as pallet::Config>::Currency::transfer(
+					vault,
+					&customer_id,
+					refunded.saturated_into::<BalanceOf<T>>(),
+					ExistenceRequirement::AllowDeath,
+				)?;
+				Self::deposit_event(Event::<T>::RefundError {
+					cluster_id,
+					era,
+					customer_id: customer_id.clone(),
+					source,
+					amount: refunded,
+					error,
+				});
+			}
+
+			ChargedCustomers::<T>::mutate((cluster_id, era), &customer_id, |record| {
+				if let Some(record) = record {
+					record.refunded = record.refunded.saturating_add(refunded);
+				}
+			});
+
+			Ok(())
+		}
+
+		/// Rolls back the debt changes made with the billing report for the customer in the
+		/// share of the report rolled back, once the charges of the customer are refunded.
+		fn roll_back_customer_debt(
+			cluster_id: ClusterId,
+			era: DdcEra,
+			customer_id: T::AccountId,
+			record: CustomerChargeRecord,
+			share: Perquintill,
+			refund_ratio: Perquintill,
+		) {
+			// the debt repaid may have been incurred with the report itself, so it is restored
+			// before the debt incurred is removed
+			let debt = DebtorCustomers::<T>::get(cluster_id, &customer_id)
+				.unwrap_or_default()
+				.saturating_add(refund_ratio * (share * record.debt_repaid))
+				.saturating_sub(share * record.debt_incurred);
+			if debt.is_zero() {
+				DebtorCustomers::<T>::remove(cluster_id, &customer_id);
+			} else {
+				DebtorCustomers::<T>::insert(cluster_id, &customer_id, debt);
+			}
+
+			Self::deposit_event(Event::<T>::ChargeRefunded {
+				cluster_id,
+				era,
+				customer_id,
+				refunded: record.refunded,
+				debt,
+			});
+		}
+
+		/// Rolls back the debt changes made with the billing report for the bucket charged from
+		/// its own allocation in the share of the report rolled back.
+		fn roll_back_bucket_debt(
+			cluster_id: ClusterId,
+			era: DdcEra,
+			owner_id: &T::AccountId,
+			bucket_id: BucketId,
+			record: BucketDebtRecord,
+			share: Perquintill,
+			refund_ratio: Perquintill,
+		) {
+			let debt = DebtorBuckets::<T>::get(cluster_id, bucket_id)
+				.unwrap_or_default()
+				.saturating_add(refund_ratio * (share * record.debt_repaid))
+				.saturating_sub(share * record.debt_incurred);
+			Self::set_bucket_debt(cluster_id, owner_id, bucket_id, debt);
+
+			Self::deposit_event(Event::<T>::BucketDebtRolledBack {
+				cluster_id,
+				era,
+				bucket_id,
+				debt,
+			});
+		}
+
+		/// Claws back the share of the fees paid out of the billing report funds and records the
+		/// rollback of the report, which is carried out by `roll_back_billing_report`.
+		fn begin_rollback(
+			cluster_id: ClusterId,
+			era: DdcEra,
+			billing_report: &BillingReport<T>,
+			reason: RollbackReason,
+			share: Perquintill,
+		) -> DispatchResult {
+			let funds = BillingReportFunds::<T>::get(cluster_id, era);
+			let vault = &billing_report.vault;
+
+			let mut expected: u128 = 0;
+			let mut claw_back = |account_id: &T::AccountId, amount: u128| {
+				expected = expected.saturating_add(amount);
+				Self::claw_back(account_id, vault, amount)
+			};
+
+			let treasury_fee =
+				claw_back(&T::TreasuryVisitor::get_account_id(), share * funds.treasury_fee);
+			let cluster_reserve_fee = if funds.cluster_reserve_fee.is_zero() {
+				Zero::zero()
+			} else {
+				claw_back(
+					&T::ClusterVisitor::get_reserve_account_id(&cluster_id)
+						.map_err(|_| Error::<T>::NotExpectedClusterState)?,
+					share * funds.cluster_reserve_fee,
+				)
+			};
+			// the validators fees claimed by the stakers are not clawed back
+			let validators_fee = match ValidatorFeesPots::<T>::get(cluster_id, era) {
+				Some(mut pot) => {
+					let unclaimed = pot.total_fees.saturating_sub(pot.claimed_fees);
+					let clawed_back = claw_back(
+						&Self::validator_fees_pot_account_id(),
+						(share * funds.validators_fee).min(unclaimed),
+					);
+					pot.total_fees = pot.total_fees.saturating_sub(clawed_back);
+					ValidatorFeesPots::<T>::insert(cluster_id, era, pot);
+					clawed_back
+				},
+				None => Zero::zero(),
+			};
+			// the repaid debts are restored to the customers, so their funding of the owed
+			// rewards is clawed back as far as the owed rewards are not paid yet
+			let owed_rewards_funded = claw_back(
+				&Self::owed_rewards_account_id(cluster_id),
+				share * funds.owed_rewards_funded,
+			);
+			let clawed_back = treasury_fee
+				.saturating_add(validators_fee)
+				.saturating_add(cluster_reserve_fee)
+				.saturating_add(owed_rewards_funded);
+
+			if !(treasury_fee.is_zero() &&
+				validators_fee.is_zero() &&
+				cluster_reserve_fee.is_zero() &&
+				owed_rewards_funded.is_zero())
+			{
+				Self::deposit_event(Event::<T>::FeesClawedBack {
+					cluster_id,
+					era,
+					treasury_fee,
+					validators_fee,
+					cluster_reserve_fee,
+					owed_rewards_funded,
+				});
+			}
+
+			// the funds of the report not paid out as fees or rewards are still in the vault
+			let left_in_vault = funds
+				.collected
+				.saturating_sub(funds.treasury_fee)
+				.saturating_sub(funds.validators_fee)
+				.saturating_sub(funds.cluster_reserve_fee)
+				.saturating_sub(funds.owed_rewards_funded)
+				.saturating_sub(billing_report.total_distributed_reward);
+			BillingReportRollbacks::<T>::insert(
+				cluster_id,
+				era,
+				BillingReportRollback {
+					reason,
+					share,
+					refundable: share * funds.collected,
+					available: (share * left_in_vault).saturating_add(clawed_back),
+					refund_ratio: None,
+					unrecovered: expected.saturating_sub(clawed_back),
+				},
+			);
+
+			Ok(())
+		}
+
+		/// Claws back the share of the reward paid to the node provider or delegator with the
+		/// billing report being rolled back and cancels the share of the reward left owed.
+		fn claw_back_reward(
+			cluster_id: ClusterId,
+			era: DdcEra,
+			billing_report: &mut BillingReport<T>,
+			rollback: &mut BillingReportRollback,
+			account_id: T::AccountId,
+			record: ProviderRewardRecord,
+		) {
+			let expected = rollback.share * record.rewarded;
+			let clawed_back = Self::claw_back(&account_id, &billing_report.vault, expected);
+			rollback.available = rollback.available.saturating_add(clawed_back);
+			rollback.unrecovered =
+				rollback.unrecovered.saturating_add(expected.saturating_sub(clawed_back));
+			billing_report.total_distributed_reward =
+				billing_report.total_distributed_reward.saturating_sub(clawed_back);
+
+			let owed = rollback.share * record.owed;
+			if !owed.is_zero() {
+				let cancelled =
+					OwingProviders::<T>::mutate_exists(cluster_id, &account_id, |owed_reward| {
+						let cancelled = owed_reward.unwrap_or_default().min(owed);
+						*owed_reward = owed_reward.map(|o| o - cancelled).filter(|o| !o.is_zero());
+						cancelled
+					});
+				TotalOwedRewards::<T>::mutate(cluster_id, |total| {
+					*total = total.saturating_sub(cancelled)
+				});
+
+				// the owed rewards of the nodes can not exceed the reward owed to the provider
+				let mut excess = OwingNodes::<T>::iter_prefix_values((cluster_id, &account_id))
+					.fold(0u128, |total, owed| total.saturating_add(owed))
+					.saturating_sub(
+						OwingProviders::<T>::get(cluster_id, &account_id).unwrap_or_default(),
+					);
+				let nodes_owed =
+					OwingNodes::<T>::iter_prefix((cluster_id, &account_id)).collect::<Vec<_>>();
+				for (node_pub_key, node_owed) in nodes_owed {
+					if excess.is_zero() {
+						break
+					}
+					let cancelled = node_owed.min(excess);
+					excess -= cancelled;
+					if cancelled == node_owed {
+						OwingNodes::<T>::remove((cluster_id, &account_id), &node_pub_key);
+					} else {
+						OwingNodes::<T>::insert(
+							(cluster_id, &account_id),
+							&node_pub_key,
+							node_owed - cancelled,
+						);
+					}
+				}
+			}
+
+			Self::deposit_event(Event::<T>::RewardClawedBack {
+				cluster_id,
+				era,
+				account_id,
+				expected,
+				clawed_back,
+			});
+		}
+
+		/// Moves up to `amount` of the transferable balance of the account back to the vault,
+		/// keeping the account alive. The locked and reserved funds of the account, such as its
+		/// bond or delegated stake, are never clawed back. Returns the clawed back amount, which
+		/// may be lower if the account can not cover it.
+		fn claw_back(account_id: &T::AccountId, vault: &T::AccountId, amount: u128) -> u128 {
+			let transferable = <T as pallet::Config>::Currency::free_balance(account_id)
+				.saturating_sub(<T as pallet::Config>::Currency::minimum_balance())
+				.saturated_into::<u128>();
+			let amount = amount.min(transferable);
+			if amount.is_zero() {
+				return Zero::zero()
+			}
+
+			match <T as pallet::Config>::Currency::withdraw(
+				account_id,
+				amount.saturated_into::<BalanceOf<T>>(),
+				WithdrawReasons::TRANSFER,
+				ExistenceRequirement::KeepAlive,
+			) {
+				Ok(imbalance) => {
+					<T as pallet::Config>::Currency::resolve_creating(vault, imbalance);
+					amount
+				},
+				// the balance of the account is locked
+				Err(_) => Zero::zero(),
+			}
+		}
+
+		/// Appends the finalized billing report to the pruning queue.
+		pub(crate) fn schedule_pruning(
+			cluster_id: ClusterId,
+			era: DdcEra,
+			prunable_at: BlockNumberFor<T>,
+		) {
+			PruningQueueRange::<T>::mutate(|(_, tail)| {
+				PruningQueue::<T>::insert(*tail, (cluster_id, era, prunable_at));
+				*tail += 1;
+			});
+		}
+
+		/// Removes up to `limit` of the charge and reward records of the finalized billing report.
+		/// Returns the number of the records removed and whether all of them are removed.
+		pub(crate) fn prune_billing_report_records(
+			cluster_id: ClusterId,
+			era: DdcEra,
+			limit: u32,
+		) -> (u32, bool) {
+			let clears: [fn((ClusterId, DdcEra), u32) -> MultiRemovalResults; 4] = [
+				|prefix, limit| ChargedCustomers::<T>::clear_prefix(prefix, limit, None),
+				|prefix, limit| ChargedPaymentSources::<T>::clear_prefix(prefix, limit, None),
+				|prefix, limit| ChargedBuckets::<T>::clear_prefix(prefix, limit, None),
+				|prefix, limit| RewardedProviders::<T>::clear_prefix(prefix, limit, None),
+			];
+
+			let mut removed: u32 = 0;
+			for clear in clears {
+				let limit = limit.saturating_sub(removed);
+				if limit.is_zero() {
+					return (removed, false)
+				}
+				let result = clear((cluster_id, era), limit);
+				removed = removed.saturating_add(result.loops);
+				if result.maybe_cursor.is_some() {
+					return (removed, false)
+				}
+			}
+
+			(removed, true)
+		}
+
+		/// Replaces the finalized billing report with its summary in the archive.
+		pub(crate) fn prune_billing_report(cluster_id: ClusterId, era: DdcEra) {
+			let _ = ExecutedProposals::<T>::clear_prefix((cluster_id, era), u32::MAX, None);
+			if let Some(report) = ActiveBillingReports::<T>::take(cluster_id, era) {
+				let content_hash = H256(report.using_encoded(blake2_256));
+				ArchivedBillingReports::<T>::insert(
+					cluster_id,
+					era,
+					BillingReportSummary {
+						start_era: report.start_era,
+						end_era: report.end_era,
+						total_customer_charge: report.total_customer_charge,
+						total_distributed_reward: report.total_distributed_reward,
+						total_node_usage: report.total_node_usage,
+						charging_max_batch_index: report.charging_max_batch_index,
+						rewarding_max_batch_index: report.rewarding_max_batch_index,
+						content_hash,
+					},
+				);
+
+				Self::deposit_event(Event::<T>::BillingReportArchived {
+					cluster_id,
+					era,
+					content_hash,
+				});
+			}
+		}
+
+		fn to_billing_report_info(report: BillingReport<T>) -> BillingReportInfo {
+			let charging_missing_batches = match report.state {
+				State::NotInitialized | State::Initialized => Vec::new(),
+				_ => get_missing_batches(
+					report.charging_max_batch_index,
+					&report.charging_processed_batches,
+				),
+			};
+			let rewarding_missing_batches = match report.state {
+				State::RewardingProviders | State::ProvidersRewarded | State::Finalized =>
+					get_missing_batches(
+						report.rewarding_max_batch_index,
+						&report.rewarding_processed_batches,
+					),
+				_ => Vec::new(),
+			};
+
+			BillingReportInfo {
+				state: report.state,
+				start_era: report.start_era,
+				end_era: report.end_era,
+				total_customer_charge: report.total_customer_charge,
+				total_distributed_reward: report.total_distributed_reward,
+				total_node_usage: report.total_node_usage,
+				charging_max_batch_index: report.charging_max_batch_index,
+				charging_missing_batches,
+				rewarding_max_batch_index: report.rewarding_max_batch_index,
+				rewarding_missing_batches,
+			}
+		}
+
+		/// Charges the outstanding debt of the customer in the cluster to the report vault, or
+		/// the debt of the budgeted bucket to its allocation. Returns the repaid amount, which
+		/// may be lower than the debt if the customer's ledger can not cover it. A failed charge
+		/// is reported with `ChargeError` for the bucket being charged and leaves the debt
+		/// outstanding.
+		#[allow(clippy::too_many_arguments)]
+		fn collect_customer_debt(
+			cluster_id: ClusterId,
+			era: DdcEra,
+			batch_index: BatchIndex,
+			customer_id: &T::AccountId,
+			bucket_id: BucketId,
+			budgeted_bucket_id: Option<BucketId>,
+			vault: &T::AccountId,
+		) -> Result<u128, DispatchError> {
+			let customer_debt = Self::get_debt(cluster_id, customer_id, budgeted_bucket_id);
+			if customer_debt.is_zero() {
+				return Ok(Zero::zero())
+			}
+
+			let repaid = match Self::charge_customer(
+				cluster_id,
+				era,
+				budgeted_bucket_id,
+				customer_id,
+				vault,
+				customer_debt,
+			) {
+				Ok(charged) => charged.min(customer_debt),
+				Err(e) => {
+					Self::deposit_event(Event::<T>::ChargeError {
+						cluster_id,
+						era,
+						batch_index,
+						customer_id: customer_id.clone(),
+						bucket_id,
+						amount: customer_debt,
+						error: e,
+					});
+					Zero::zero()
+				},
+			};
+
+			if repaid.is_zero() {
+				return Ok(Zero::zero())
+			}
+
+			let remaining_debt =
+				customer_debt.checked_sub(repaid).ok_or(Error::<T>::ArithmeticOverflow)?;
+
+			Self::set_debt(cluster_id, customer_id, budgeted_bucket_id, remaining_debt);
+			if remaining_debt.is_zero() {
+				Self::deposit_event(Event::<T>::DebtRepaid {
+					cluster_id,
+					era,
+					batch_index,
+					customer_id: customer_id.clone(),
+					repaid,
+				});
+			} else {
+				Self::deposit_event(Event::<T>::DebtPartiallyRepaid {
+					cluster_id,
+					era,
+					batch_index,
+					customer_id: customer_id.clone(),
+					repaid,
+					remaining_debt,
+				});
+			}
+
+			Ok(repaid)
+		}
+
+		/// Outstanding debt of the customer in the cluster, or of the bucket charged from its
+		/// own allocation.
+		fn get_debt(
+			cluster_id: ClusterId,
+			customer_id: &T::AccountId,
+			budgeted_bucket_id: Option<BucketId>,
+		) -> u128 {
+			match budgeted_bucket_id {
+				Some(bucket_id) => DebtorBuckets::<T>::get(cluster_id, bucket_id),
+				None => DebtorCustomers::<T>::get(cluster_id, customer_id),
+			}
+			.unwrap_or_else(Zero::zero)
+		}
+
+		fn set_debt(
+			cluster_id: ClusterId,
+			customer_id: &T::AccountId,
+			budgeted_bucket_id: Option<BucketId>,
+			debt: u128,
+		) {
+			match (budgeted_bucket_id, debt.is_zero()) {
+				(Some(bucket_id), _) =>
+					Self::set_bucket_debt(cluster_id, customer_id, bucket_id, debt),
+				(None, true) => DebtorCustomers::<T>::remove(cluster_id, customer_id),
+				(None, false) => DebtorCustomers::<T>::insert(cluster_id, customer_id, debt),
+			}
+		}
+
+		/// Sets the debt of the bucket charged from its own allocation, keeping the total debt
+		/// of the buckets of its owner in the cluster.
+		fn set_bucket_debt(
+			cluster_id: ClusterId,
+			owner_id: &T::AccountId,
+			bucket_id: BucketId,
+			debt: u128,
+		) {
+			let previous_debt = DebtorBuckets::<T>::get(cluster_id, bucket_id).unwrap_or_default();
+			if debt.is_zero() {
+				DebtorBuckets::<T>::remove(cluster_id, bucket_id);
+			} else {
+				DebtorBuckets::<T>::insert(cluster_id, bucket_id, debt);
+			}
+
+			DebtorBucketOwners::<T>::mutate_exists(cluster_id, owner_id, |total| {
+				let updated =
+					total.unwrap_or_default().saturating_sub(previous_debt).saturating_add(debt);
+				*total = (!updated.is_zero()).then_some(updated);
+			});
+		}
+
+		/// Charges the customer the amount priced in the native currency to the vault. Customers
+		/// paying with an asset the cluster accepts are charged its equivalent at the cluster
+		/// rate, rounded up. The asset goes to the cluster reserve, which pays the native amount
+		/// into the vault, so providers are rewarded in the native currency either way. If the
+		/// reserve is short of the native amount, the asset charge is reverted and the native
+		/// deposit of the customer is charged instead. The charge is recorded by payment source to
+		/// be refunded to it if the report is rolled back.
+		fn charge_customer(
+			cluster_id: ClusterId,
+			era: DdcEra,
+			bucket_id: Option<BucketId>,
+			customer_id: &T::AccountId,
+			vault: &T::AccountId,
+			amount: u128,
+		) -> Result<u128, DispatchError> {
+			let source = T::CustomerCharger::get_payment_source(bucket_id, customer_id);
+			if let PaymentSource::Asset(asset_id) = source {
+				if let Some(rate) = T::ClusterVisitor::get_asset_rate(&cluster_id, asset_id) {
+					match frame_support::storage::with_storage_layer(|| {
+						Self::charge_customer_asset(
+							cluster_id,
+							era,
+							customer_id,
+							vault,
+							asset_id,
+							rate,
+							amount,
+						)
+					}) {
+						Err(e) if e == Error::<T>::ReserveBalanceTooLow.into() => {
+							Self::deposit_event(Event::<T>::ReserveBalanceTooLow {
+								cluster_id,
+								era,
+								customer_id: customer_id.clone(),
+								asset_id,
+								amount,
+							});
+						},
+						result => return result,
+					}
+				}
+			}
+
+			frame_support::storage::with_storage_layer(|| -> Result<u128, DispatchError> {
+				let charged = T::CustomerCharger::charge_content_owner(
+					bucket_id,
+					customer_id.clone(),
+					vault.clone(),
+					amount,
+				)?;
+				// an asset the cluster does not accept leaves the native deposit charged
+				let source = match source {
+					PaymentSource::Asset(_) => PaymentSource::Deposit,
+					_ => source,
+				};
+				Self::record_payment_source_charge(
+					cluster_id,
+					era,
+					customer_id,
+					source,
+					charged,
+					Zero::zero(),
+				)?;
+
+				Ok(charged)
+			})
+		}
+
+		/// Charges the customer the asset equivalent of the native amount to the cluster reserve,
+		/// which pays the native amount into the vault. Fails with `ReserveBalanceTooLow` if the
+		/// reserve can not pay it.
+		fn charge_customer_asset(
+			cluster_id: ClusterId,
+			era: DdcEra,
+			customer_id: &T::AccountId,
+			vault: &T::AccountId,
+			asset_id: AssetId,
+			rate: FixedU128,
+			amount: u128,
+		) -> Result<u128, DispatchError> {
+			if amount.is_zero() {
+				// a zero charge leaves the asset deposit untouched
+				return Ok(Zero::zero())
+			}
+
+			let reserve = T::ClusterVisitor::get_reserve_account_id(&cluster_id)
+				.map_err(|_| Error::<T>::NotExpectedClusterState)?;
+			let asset_amount = multiply_by_rational_with_rounding(
+				amount,
+				rate.into_inner(),
+				FixedU128::DIV,
+				Rounding::Up,
+			)
+			.ok_or(Error::<T>::ArithmeticOverflow)?;
+
+			let asset_charged = T::CustomerCharger::charge_content_owner_asset(
+				customer_id.clone(),
+				reserve.clone(),
+				asset_id,
+				asset_amount,
+			)?;
+			// the native equivalent of a partial charge is rounded down
+			let charged = if asset_charged >= asset_amount {
+				amount
+			} else {
+				multiply_by_rational_with_rounding(
+					asset_charged,
+					amount,
+					asset_amount,
+					Rounding::Down,
+				)
+				.ok_or(Error::<T>::ArithmeticOverflow)?
+			};
+
+			<T as pallet::Config>::Currency::transfer(
+				&reserve,
+				vault,
+				charged.saturated_into::<BalanceOf<T>>(),
+				ExistenceRequirement::KeepAlive,
+			)
+			.map_err(|_| Error::<T>::ReserveBalanceTooLow)?;
+
+			Self::record_payment_source_charge(
+				cluster_id,
+				era,
+				customer_id,
+				PaymentSource::Asset(asset_id),
+				charged,
+				asset_charged,
+			)?;
+
+			Ok(charged)
+		}
+
+		fn record_payment_source_charge(
+			cluster_id: ClusterId,
+			era: DdcEra,
+			customer_id: &T::AccountId,
+			source: PaymentSource,
+			charged: u128,
+			asset_charged: u128,
+		) -> DispatchResult {
+			if charged.is_zero() && asset_charged.is_zero() {
+				return Ok(())
+			}
+
+			ChargedPaymentSources::<T>::try_mutate(
+				(cluster_id, era),
+				(customer_id, source),
+				|record| -> DispatchResult {
+					let record = record.get_or_insert_with(Default::default);
+					record.charged = record
+						.charged
+						.checked_add(charged)
+						.ok_or(Error::<T>::ArithmeticOverflow)?;
+					record.asset_charged = record
+						.asset_charged
+						.checked_add(asset_charged)
+						.ok_or(Error::<T>::ArithmeticOverflow)?;
+					Ok(())
+				},
+			)
+		}
+
+		/// The reward owed to the node provider in the cluster for the node, along with the part
+		/// of it owed before the rewards were tracked per node.
+		fn owed_node_reward_parts(
+			cluster_id: ClusterId,
+			node_provider_id: &T::AccountId,
+			node_pub_key: &NodePubKey,
+		) -> (u128, u128) {
+			let owed_reward =
+				OwingProviders::<T>::get(cluster_id, node_provider_id).unwrap_or_default();
+			let nodes_owed = OwingNodes::<T>::iter_prefix_values((cluster_id, node_provider_id))
+				.fold(0u128, |total, owed| total.saturating_add(owed));
+			// the reward owed before it was tracked per node is paid with the node claimed first
+			let unattributed = owed_reward.saturating_sub(nodes_owed);
+			let node_owed = OwingNodes::<T>::get((cluster_id, node_provider_id), node_pub_key)
+				.unwrap_or_default();
+
+			(node_owed.saturating_add(unattributed).min(owed_reward), unattributed)
+		}
+
+		/// The reward owed to the node provider in the cluster for the node.
+		fn owed_node_reward(
+			cluster_id: ClusterId,
+			node_provider_id: &T::AccountId,
+			node_pub_key: &NodePubKey,
+		) -> u128 {
+			Self::owed_node_reward_parts(cluster_id, node_provider_id, node_pub_key).0
+		}
+
+		/// Transfers the reward owed to the node provider in the cluster for the node from the
+		/// debts repaid by the customers of the cluster, sharing it with the delegators of the
+		/// node. Returns the paid amount, which may be lower than the owed reward if not enough
+		/// debts are repaid yet.
+		fn pay_owed_reward(
+			cluster_id: ClusterId,
+			node_provider_id: &T::AccountId,
+			node_pub_key: &NodePubKey,
+		) -> Result<u128, DispatchError> {
+			let (node_owed, unattributed) =
+				Self::owed_node_reward_parts(cluster_id, node_provider_id, node_pub_key);
+			if node_owed.is_zero() {
+				return Ok(Zero::zero())
+			}
+			let owed_reward =
+				OwingProviders::<T>::get(cluster_id, node_provider_id).unwrap_or_default();
+
+			let owed_rewards_account = Self::owed_rewards_account_id(cluster_id);
+			let funds = <T as pallet::Config>::Currency::free_balance(&owed_rewards_account)
+				.saturated_into::<u128>();
+			let paid = node_owed.min(funds);
+			if paid.is_zero() {
+				return Ok(Zero::zero())
+			}
+
+			for (delegator_id, delegator_reward) in Self::pay_node_reward(
+				&owed_rewards_account,
+				node_provider_id,
+				node_pub_key,
+				paid.saturated_into::<BalanceOf<T>>(),
+			)? {
+				Self::deposit_event(Event::<T>::OwedDelegatorRewardPaid {
+					cluster_id,
+					node_provider_id: node_provider_id.clone(),
+					delegator_id,
+					paid: delegator_reward.saturated_into::<u128>(),
+				});
+			}
+
+			let remaining = owed_reward.checked_sub(paid).ok_or(Error::<T>::ArithmeticOverflow)?;
+			if remaining.is_zero() {
+				OwingProviders::<T>::remove(cluster_id, node_provider_id);
+			} else {
+				OwingProviders::<T>::insert(cluster_id, node_provider_id, remaining);
+			}
+			// the reward owed before it was tracked per node is paid first
+			let node_paid = paid.saturating_sub(unattributed);
+			OwingNodes::<T>::mutate_exists((cluster_id, node_provider_id), node_pub_key, |owed| {
+				*owed = owed.map(|o| o.saturating_sub(node_paid)).filter(|o| !o.is_zero())
+			});
+			TotalOwedRewards::<T>::mutate(cluster_id, |total| *total = total.saturating_sub(paid));
+
+			Self::deposit_event(Event::<T>::OwedRewardPaid {
+				cluster_id,
+				node_provider_id: node_provider_id.clone(),
+				paid,
+				remaining,
+			});
+
+			Ok(paid)
+		}
+
+		/// Transfers the reward of the node from the account to the delegators of the node, their
+		/// shares less the commission of the node provider, and the rest to the node provider.
+		/// Returns the rewards paid to the delegators.
+		fn pay_node_reward(
+			from: &T::AccountId,
+			node_provider_id: &T::AccountId,
+			node_pub_key: &NodePubKey,
+			reward: BalanceOf<T>,
+		) -> Result<Vec<(T::AccountId, BalanceOf<T>)>, DispatchError> {
+			let mut provider_reward = reward;
+			let mut delegators_rewards = Vec::new();
+			for (delegator_id, delegator_reward) in
+				T::DelegationVisitor::get_delegators_rewards(node_provider_id, node_pub_key, reward)
+			{
+				let delegator_reward = delegator_reward.min(provider_reward);
+				<T as pallet::Config>::Currency::transfer(
+					from,
+					&delegator_id,
+					delegator_reward,
+					ExistenceRequirement::AllowDeath,
+				)?;
+				provider_reward -= delegator_reward;
+				delegators_rewards.push((delegator_id, delegator_reward));
+			}
+
+			<T as pallet::Config>::Currency::transfer(
+				from,
+				node_provider_id,
+				provider_reward,
+				ExistenceRequirement::AllowDeath,
+			)?;
+
+			Ok(delegators_rewards)
+		}
+
+		/// Moves the debt repaid by a customer of the cluster to the funds of the rewards owed in
+		/// the cluster, as far as they are not funded yet. Returns the moved amount, the rest of
+		/// the repaid debt is rewarded with the billing report.
+		fn fund_owed_rewards(
+			cluster_id: ClusterId,
+			era: DdcEra,
+			vault: &T::AccountId,
+			debt_repaid: u128,
+		) -> Result<u128, DispatchError> {
+			let owed_rewards_account = Self::owed_rewards_account_id(cluster_id);
+			let funds = <T as pallet::Config>::Currency::free_balance(&owed_rewards_account)
+				.saturated_into::<u128>();
+			let funded =
+				TotalOwedRewards::<T>::get(cluster_id).saturating_sub(funds).min(debt_repaid);
+			// the funds account can not be created with less than the existential deposit
+			if funded.is_zero() ||
+				(funds.is_zero() &&
+					funded <
+						<T as pallet::Config>::Currency::minimum_balance()
+							.saturated_into::<u128>())
+			{
+				return Ok(Zero::zero())
+			}
+
+			<T as pallet::Config>::Currency::transfer(
+				vault,
+				&owed_rewards_account,
+				funded.saturated_into::<BalanceOf<T>>(),
+				ExistenceRequirement::AllowDeath,
+			)?;
+			BillingReportFunds::<T>::try_mutate(
+				cluster_id,
+				era,
+				|report_funds| -> DispatchResult {
+					report_funds.owed_rewards_funded = report_funds
+						.owed_rewards_funded
+						.checked_add(funded)
+						.ok_or(Error::<T>::ArithmeticOverflow)?;
+					Ok(())
+				},
+			)?;
+
+			Ok(funded)
+		}
+
+		/// Number of DAC validators votes required for a billing report proposal to execute,
+		/// which is a majority of the active DAC validators unless set by governance. The threshold
+		/// set by governance is capped by the number of the active DAC validators.
+		pub fn validator_threshold() -> u32 {
+			Self::threshold_of(T::ValidatorVisitor::get_active_validators().len())
+		}
+
+		fn threshold_of(validators_count: usize) -> u32 {
+			let majority = (validators_count / 2 + 1).saturated_into();
+			ValidatorThreshold::<T>::get().map_or(majority, |threshold| {
+				threshold.min(validators_count.saturated_into()).max(1)
+			})
+		}
+
+		/// Records the vote of a DAC validator for the billing report proposal. Returns `true`
+		/// once the proposal is backed by `validator_threshold` validators and can be applied.
+		fn vote_for_proposal(
+			validator: T::AccountId,
+			cluster_id: ClusterId,
+			era: DdcEra,
+			proposal_hash: H256,
+		) -> Result<bool, DispatchError> {
+			let validators = T::ValidatorVisitor::get_active_validators();
+			ensure!(validators.contains(&validator), Error::<T>::Unauthorised);
+			ensure!(
+				!ExecutedProposals::<T>::contains_key((cluster_id, era), proposal_hash) &&
+					!ArchivedBillingReports::<T>::contains_key(cluster_id, era),
+				Error::<T>::ProposalAlreadyExecuted
+			);
+
+			let mut votes =
+				BillingReportVotes::<T>::get((cluster_id, era), proposal_hash).unwrap_or_default();
+			ensure!(!votes.contains(&validator), Error::<T>::AlreadyVoted);
+			// votes of the validators that have left the active set do not count
+			votes.retain(|voter| validators.contains(voter));
+			votes.try_push(validator.clone()).map_err(|_| Error::<T>::BoundedVecOverflow)?;
+
+			if votes.len() >= Self::threshold_of(validators.len()) as usize {
+				BillingReportVotes::<T>::remove((cluster_id, era), proposal_hash);
+				ExecutedProposals::<T>::insert((cluster_id, era), proposal_hash, ());
+				Ok(true)
+			} else {
+				BillingReportVotes::<T>::insert((cluster_id, era), proposal_hash, votes);
+				Self::deposit_event(Event::<T>::BillingReportVoted {
+					cluster_id,
+					era,
+					validator,
+					proposal_hash,
+				});
+				Ok(false)
+			}
+		}
+	}
+
+	impl<T> From<BucketVisitorError> for Error<T> {
+		fn from(error: BucketVisitorError) -> Self {
+			match error {
+				BucketVisitorError::NoBucketWithId => Error::<T>::NoBucketWithId,
+				BucketVisitorError::NotBucketOwner => Error::<T>::NotBucketOwner,
+				BucketVisitorError::IncorrectClusterId => Error::<T>::IncorrectBucketClusterId,
+			}
+		}
+	}
+
+	impl<T: Config> PayoutVisitorType<T> for Pallet<T> {
+		fn get_customer_debt(cluster_id: &ClusterId, customer_id: &T::AccountId) -> u128 {
+			DebtorCustomers::<T>::get(cluster_id, customer_id).unwrap_or_else(Zero::zero)
+		}
+
+		fn get_bucket_debt(cluster_id: &ClusterId, bucket_id: BucketId) -> u128 {
+			DebtorBuckets::<T>::get(cluster_id, bucket_id).unwrap_or_else(Zero::zero)
+		}
+
+		fn get_buckets_debt(cluster_id: &ClusterId, owner_id: &T::AccountId) -> u128 {
+			DebtorBucketOwners::<T>::get(cluster_id, owner_id).unwrap_or_else(Zero::zero)
+		}
+
+		fn has_open_billing_report(cluster_id: &ClusterId) -> bool {
+			// the reports kept active are bounded by the retention period of the finalized ones
+			ActiveBillingReports::<T>::iter_prefix(cluster_id).any(|(era, report)| {
+				match report.state {
+					State::Finalized => false,
+					State::Aborted => BillingReportRollbacks::<T>::contains_key(cluster_id, era),
+					_ => true,
+				}
+			})
+		}
+
+		fn get_last_finalized_era(cluster_id: &ClusterId) -> Option<DdcEra> {
+			LastFinalizedEras::<T>::get(cluster_id)
+		}
+
+		#[cfg(feature = "runtime-benchmarks")]
+		fn set_last_finalized_era(cluster_id: &ClusterId, era: DdcEra) {
+			LastFinalizedEras::<T>::insert(cluster_id, era);
+		}
 	}
 }
@@ -0,0 +1,73 @@
+//! Binary Merkle tree used to commit to DAC usage batches of a billing report.
+//!
+//! A leaf is the `blake2_256` hash of a SCALE-encoded `(BatchIndex, batch)` pair, so a batch can
+//! neither be altered nor moved to another index once its root is committed. Inner nodes hash the
+//! concatenation of their two children ordered by value, which keeps proofs free of position
+//! flags. A node without a sibling is promoted to the next layer unchanged.
+//!
+//! Leaves and inner nodes are hashed with distinct prefixes, so an inner node can not be passed
+//! off as a batch leaf and the other way round.
+
+use codec::Encode;
+use sp_core::H256;
+use sp_io::hashing::blake2_256;
+use sp_std::prelude::*;
+
+use crate::BatchIndex;
+
+const LEAF_PREFIX: u8 = 0;
+const NODE_PREFIX: u8 = 1;
+
+/// Hash of a usage batch as it is committed to in the Merkle tree.
+pub fn batch_leaf<Item: Encode>(batch_index: BatchIndex, batch: &[Item]) -> H256 {
+	H256((LEAF_PREFIX, batch_index, batch).using_encoded(blake2_256))
+}
+
+/// Root of the tree built over `leaves`. An empty set of leaves commits to the zero hash.
+pub fn merkle_root(leaves: &[H256]) -> H256 {
+	let mut layer = leaves.to_vec();
+	while layer.len() > 1 {
+		layer = next_layer(&layer);
+	}
+	layer.first().copied().unwrap_or_default()
+}
+
+/// Sibling hashes from the leaf at `position` up to the root.
+pub fn merkle_proof(leaves: &[H256], position: usize) -> Vec<H256> {
+	let mut proof = Vec::new();
+	let mut layer = leaves.to_vec();
+	let mut position = position;
+	while layer.len() > 1 {
+		if let Some(sibling) = layer.get(position ^ 1) {
+			proof.push(*sibling);
+		}
+		layer = next_layer(&layer);
+		position /= 2;
+	}
+	proof
+}
+
+/// Checks that `leaf` is included in the tree committed to by `root`.
+pub fn verify_proof(root: &H256, leaf: &H256, proof: &[H256]) -> bool {
+	proof.iter().fold(*leaf, |node, sibling| hash_pair(&node, sibling)) == *root
+}
+
+fn next_layer(layer: &[H256]) -> Vec<H256> {
+	layer
+		.chunks(2)
+		.map(|pair| match pair {
+			[left, right] => hash_pair(left, right),
+			[single] => *single,
+			_ => unreachable!("chunks of two always yield one or two elements; qed"),
+		})
+		.collect()
+}
+
+fn hash_pair(a: &H256, b: &H256) -> H256 {
+	let (first, second) = if a <= b { (a, b) } else { (b, a) };
+	let mut data = [0u8; 65];
+	data[0] = NODE_PREFIX;
+	data[1..33].copy_from_slice(first.as_bytes());
+	data[33..].copy_from_slice(second.as_bytes());
+	H256(blake2_256(&data))
+}
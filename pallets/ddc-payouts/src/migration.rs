@@ -0,0 +1,308 @@
+#[cfg(feature = "try-runtime")]
+use frame_support::ensure;
+use frame_support::{
+	storage_alias,
+	traits::{Get, GetStorageVersion, OnRuntimeUpgrade, StorageVersion},
+	weights::Weight,
+};
+use log::{error, info};
+use sp_std::collections::btree_map::BTreeMap;
+
+use super::*;
+
+const LOG_TARGET: &str = "ddc-payouts";
+
+pub mod v0 {
+	use frame_support::pallet_prelude::*;
+
+	use super::*;
+
+	#[derive(Clone, Encode, Decode, RuntimeDebug, TypeInfo, PartialEq)]
+	#[scale_info(skip_type_params(T))]
+	pub struct BillingReport<T: Config> {
+		pub state: State,
+		pub vault: T::AccountId,
+		pub start_era: i64,
+		pub end_era: i64,
+		pub total_customer_charge: CustomerCharge,
+		pub total_distributed_reward: u128,
+		pub total_node_usage: NodeUsage,
+		// stage 1
+		pub charging_max_batch_index: BatchIndex,
+		pub charging_processed_batches: BoundedBTreeSet<BatchIndex, MaxBatchesCount>,
+		// stage 2
+		pub rewarding_max_batch_index: BatchIndex,
+		pub rewarding_processed_batches: BoundedBTreeSet<BatchIndex, MaxBatchesCount>,
+	}
+
+	#[storage_alias]
+	pub(super) type ActiveBillingReports<T: Config> = StorageDoubleMap<
+		crate::Pallet<T>,
+		Blake2_128Concat,
+		ClusterId,
+		Blake2_128Concat,
+		DdcEra,
+		BillingReport<T>,
+	>;
+
+	#[storage_alias]
+	pub(super) type AuthorisedCaller<T: Config> =
+		StorageValue<crate::Pallet<T>, <T as frame_system::Config>::AccountId>;
+}
+
+// Migrate to billing reports with committed usage driven by the DAC validators quorum
+pub fn migrate_to_v1<T: Config>() -> Weight {
+	let on_chain_version = Pallet::<T>::on_chain_storage_version();
+	if on_chain_version == 0 {
+		let (count, open_count) = v0::ActiveBillingReports::<T>::iter_values()
+			.fold((0u64, 0u64), |(count, open), report| {
+				(count + 1, open + u64::from(report.state != State::Finalized))
+			});
+
+		// Reports opened before the upgrade have no commitments to prove their batches against,
+		// so the upgrade is held back until every report is finalized.
+		if open_count > 0 {
+			error!(
+				target: LOG_TARGET,
+				" >>> DDC Payouts storage not updated! {} billing reports are not finalized", open_count
+			);
+			return T::DbWeight::get().reads(count + 1)
+		}
+
+		info!(
+			target: LOG_TARGET,
+			" >>> Updating DDC Payouts storage. Migrating {} billing reports...", count
+		);
+
+		ActiveBillingReports::<T>::translate::<v0::BillingReport<T>, _>(
+			|cluster_id: ClusterId, era: DdcEra, report: v0::BillingReport<T>| {
+				info!(
+					target: LOG_TARGET,
+					"     Migrating billing report for cluster {:?} and era {:?}...", cluster_id, era
+				);
+
+				// only finalized reports are migrated, so they are left without commitments
+				Some(BillingReport {
+					state: report.state,
+					vault: report.vault,
+					start_era: report.start_era,
+					end_era: report.end_era,
+					total_customer_charge: report.total_customer_charge,
+					total_distributed_reward: report.total_distributed_reward,
+					total_node_usage: report.total_node_usage,
+					customers_merkle_root: H256::default(),
+					nodes_merkle_root: H256::default(),
+					total_customer_usage: CustomerUsage::default(),
+					charging_max_batch_index: report.charging_max_batch_index,
+					charging_processed_batches: report.charging_processed_batches,
+					charged_usage: CustomerUsage::default(),
+					rewarding_max_batch_index: report.rewarding_max_batch_index,
+					rewarding_processed_batches: report.rewarding_processed_batches,
+					rewarded_usage: NodeUsage::default(),
+				})
+			},
+		);
+
+		// The single authorised caller is superseded by the DAC validators quorum.
+		v0::AuthorisedCaller::<T>::kill();
+
+		// Update storage version.
+		StorageVersion::new(1).put::<Pallet<T>>();
+		info!(
+			target: LOG_TARGET,
+			" <<< DDC Payouts storage updated! Migrated {} billing reports ✅", count
+		);
+
+		T::DbWeight::get().reads_writes(count + 1, count + 2)
+	} else {
+		info!(target: LOG_TARGET, " >>> Unused migration!");
+		T::DbWeight::get().reads(1)
+	}
+}
+
+pub struct MigrateToV1<T>(sp_std::marker::PhantomData<T>);
+impl<T: Config> OnRuntimeUpgrade for MigrateToV1<T> {
+	fn on_runtime_upgrade() -> Weight {
+		migrate_to_v1::<T>()
+	}
+
+	#[cfg(feature = "try-runtime")]
+	fn pre_upgrade() -> Result<Vec<u8>, &'static str> {
+		ensure!(
+			v0::ActiveBillingReports::<T>::iter_values()
+				.all(|report| report.state == State::Finalized),
+			"every billing report should be finalized before the upgrade"
+		);
+		let prev_count = v0::ActiveBillingReports::<T>::iter().count();
+
+		Ok((prev_count as u64).encode())
+	}
+
+	#[cfg(feature = "try-runtime")]
+	fn post_upgrade(prev_state: Vec<u8>) -> Result<(), &'static str> {
+		let prev_count: u64 =
+			Decode::decode(&mut &prev_state[..]).expect("pre_upgrade provides a valid state; qed");
+
+		let post_count = ActiveBillingReports::<T>::iter().count() as u64;
+		ensure!(
+			prev_count == post_count,
+			"the billing report count before and after the migration should be the same"
+		);
+
+		ensure!(
+			!v0::AuthorisedCaller::<T>::exists(),
+			"the authorised caller should be removed by the migration"
+		);
+
+		let current_version = Pallet::<T>::current_storage_version();
+		let on_chain_version = Pallet::<T>::on_chain_storage_version();
+
+		frame_support::ensure!(current_version >= 1, "must_upgrade");
+		ensure!(on_chain_version == 1, "after migration, the on_chain_version should be 1");
+		Ok(())
+	}
+}
+
+// Schedule pruning of the billing reports finalized before the upgrade
+pub fn migrate_to_v2<T: Config>() -> Weight {
+	let on_chain_version = Pallet::<T>::on_chain_storage_version();
+	if on_chain_version == 1 {
+		info!(
+			target: LOG_TARGET,
+			" >>> Updating DDC Payouts storage. Scheduling pruning of finalized billing reports..."
+		);
+
+		let prunable_at =
+			<frame_system::Pallet<T>>::block_number() + T::BillingReportRetentionPeriod::get();
+		let mut reads = 0u64;
+		let mut count = 0u64;
+		for (cluster_id, era, report) in ActiveBillingReports::<T>::iter() {
+			reads += 1;
+			if report.state == State::Finalized {
+				Pallet::<T>::schedule_pruning(cluster_id, era, prunable_at);
+				count += 1;
+			}
+		}
+
+		// Update storage version.
+		StorageVersion::new(2).put::<Pallet<T>>();
+		info!(
+			target: LOG_TARGET,
+			" <<< DDC Payouts storage updated! Scheduled pruning of {} billing reports ✅", count
+		);
+
+		T::DbWeight::get().reads_writes(reads + 2, 2 * count + 1)
+	} else {
+		info!(target: LOG_TARGET, " >>> Unused migration!");
+		T::DbWeight::get().reads(1)
+	}
+}
+
+pub struct MigrateToV2<T>(sp_std::marker::PhantomData<T>);
+impl<T: Config> OnRuntimeUpgrade for MigrateToV2<T> {
+	fn on_runtime_upgrade() -> Weight {
+		migrate_to_v2::<T>()
+	}
+
+	#[cfg(feature = "try-runtime")]
+	fn pre_upgrade() -> Result<Vec<u8>, &'static str> {
+		let finalized_count = ActiveBillingReports::<T>::iter_values()
+			.filter(|report| report.state == State::Finalized)
+			.count();
+
+		Ok((finalized_count as u64).encode())
+	}
+
+	#[cfg(feature = "try-runtime")]
+	fn post_upgrade(prev_state: Vec<u8>) -> Result<(), &'static str> {
+		let finalized_count: u64 =
+			Decode::decode(&mut &prev_state[..]).expect("pre_upgrade provides a valid state; qed");
+
+		let (head, tail) = PruningQueueRange::<T>::get();
+		ensure!(
+			tail - head == finalized_count,
+			"every finalized billing report should be scheduled for pruning"
+		);
+
+		let current_version = Pallet::<T>::current_storage_version();
+		let on_chain_version = Pallet::<T>::on_chain_storage_version();
+
+		frame_support::ensure!(current_version >= 2, "must_upgrade");
+		ensure!(on_chain_version == 2, "after migration, the on_chain_version should be 2");
+		Ok(())
+	}
+}
+
+// Sum up the rewards owed to the node providers of every cluster
+pub fn migrate_to_v3<T: Config>() -> Weight {
+	let on_chain_version = Pallet::<T>::on_chain_storage_version();
+	if on_chain_version == 2 {
+		info!(
+			target: LOG_TARGET,
+			" >>> Updating DDC Payouts storage. Summing up owed rewards of the clusters..."
+		);
+
+		let mut reads = 0u64;
+		let mut totals = BTreeMap::<ClusterId, u128>::new();
+		for (cluster_id, _, owed_reward) in OwingProviders::<T>::iter() {
+			reads += 1;
+			let total = totals.entry(cluster_id).or_default();
+			*total = total.saturating_add(owed_reward);
+		}
+
+		let count = totals.len() as u64;
+		for (cluster_id, total) in totals {
+			TotalOwedRewards::<T>::insert(cluster_id, total);
+		}
+
+		// Update storage version.
+		StorageVersion::new(3).put::<Pallet<T>>();
+		info!(
+			target: LOG_TARGET,
+			" <<< DDC Payouts storage updated! Summed up owed rewards of {} clusters ✅", count
+		);
+
+		T::DbWeight::get().reads_writes(reads + 1, count + 1)
+	} else {
+		info!(target: LOG_TARGET, " >>> Unused migration!");
+		T::DbWeight::get().reads(1)
+	}
+}
+
+pub struct MigrateToV3<T>(sp_std::marker::PhantomData<T>);
+impl<T: Config> OnRuntimeUpgrade for MigrateToV3<T> {
+	fn on_runtime_upgrade() -> Weight {
+		migrate_to_v3::<T>()
+	}
+
+	#[cfg(feature = "try-runtime")]
+	fn pre_upgrade() -> Result<Vec<u8>, &'static str> {
+		let total_owed = OwingProviders::<T>::iter_values()
+			.fold(0u128, |total, owed_reward| total.saturating_add(owed_reward));
+
+		Ok(total_owed.encode())
+	}
+
+	#[cfg(feature = "try-runtime")]
+	fn post_upgrade(prev_state: Vec<u8>) -> Result<(), &'static str> {
+		let total_owed: u128 =
+			Decode::decode(&mut &prev_state[..]).expect("pre_upgrade provides a valid state; qed");
+
+		ensure!(
+			TotalOwedRewards::<T>::iter_values()
+				.fold(0u128, |total, owed_reward| total.saturating_add(owed_reward)) ==
+				total_owed,
+			"the owed rewards of the clusters should sum up to the rewards owed to the providers"
+		);
+
+		let current_version = Pallet::<T>::current_storage_version();
+		let on_chain_version = Pallet::<T>::on_chain_storage_version();
+
+		frame_support::ensure!(current_version == 3, "must_upgrade");
+		ensure!(
+			current_version == on_chain_version,
+			"after migration, the current_version and on_chain_version should be the same"
+		);
+		Ok(())
+	}
+}
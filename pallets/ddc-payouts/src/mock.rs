@@ -5,11 +5,14 @@
 use ddc_primitives::{
 	traits::{
 		cluster::{ClusterCreator, ClusterVisitor, ClusterVisitorError},
-		customer::{CustomerCharger, CustomerDepositor},
+		customer::{BucketVisitor, BucketVisitorError, CustomerCharger, CustomerDepositor},
 		pallet::PalletVisitor,
+		staking::DelegationVisitor,
+		validator::ValidatorVisitor,
 	},
-	ClusterBondingParams, ClusterFeesParams, ClusterGovParams, ClusterParams, ClusterPricingParams,
-	NodeType, DOLLARS,
+	AssetId, BucketId, BucketMetadata, ClusterBondingParams, ClusterCapabilities,
+	ClusterFeesParams, ClusterGovParams, ClusterParams, ClusterPricingParams, ClusterPricingTiers,
+	NodePubKey, NodeType, PaymentSource, PricingTier, DOLLARS,
 };
 use frame_election_provider_support::SortedListProvider;
 use frame_support::{
@@ -23,7 +26,7 @@ use sp_core::H256;
 use sp_io::TestExternalities;
 use sp_runtime::{
 	traits::{BlakeTwo256, Identity, IdentityLookup},
-	BuildStorage, DispatchError, Perquintill,
+	BuildStorage, DispatchError, DispatchResult, FixedU128, Perquintill,
 };
 use sp_std::prelude::*;
 
@@ -53,6 +56,7 @@ pub static MAX_DUST: u16 = 100;
 
 parameter_types! {
 	pub static ExistentialDeposit: Balance = 1;
+	pub static BucketsMetadata: Vec<(BucketId, BucketMetadata)> = Vec::new();
 }
 
 #[derive(Default, Clone)]
@@ -114,6 +118,17 @@ impl pallet_balances::Config for Test {
 
 parameter_types! {
 	pub const PayoutsPalletId: PalletId = PalletId(*b"payouts_");
+	pub static DisputePeriod: BlockNumber = 0;
+	pub const DisputeBond: Balance = 100;
+	pub static BillingReportRetentionPeriod: BlockNumber = 100;
+	pub static CustomerDiscount: Perquintill = Perquintill::zero();
+	pub static PaymentAsset: Option<AssetId> = None;
+	pub static AssetRate: Option<FixedU128> = None;
+	pub static AssetDeposit: u128 = 0;
+	pub static BucketAllocations: Vec<(BucketId, u128)> = Vec::new();
+	pub static DelegatorShare: Option<(AccountId, Perquintill)> = None;
+	pub static DacValidators: Vec<AccountId> =
+		vec![DAC_VALIDATOR1_ACCOUNT_ID, DAC_VALIDATOR2_ACCOUNT_ID, DAC_VALIDATOR3_ACCOUNT_ID];
 }
 
 impl crate::pallet::Config for Test {
@@ -122,22 +137,55 @@ impl crate::pallet::Config for Test {
 	type Currency = Balances;
 	type CustomerCharger = TestCustomerCharger;
 	type CustomerDepositor = TestCustomerDepositor;
+	type BucketVisitor = TestBucketVisitor;
 	type ClusterVisitor = TestClusterVisitor;
 	type TreasuryVisitor = TestTreasuryVisitor;
 	type NominatorsAndValidatorsList = TestValidatorVisitor<Self>;
 	type ClusterCreator = TestClusterCreator;
+	type ValidatorVisitor = TestDacValidatorVisitor;
+	type DelegationVisitor = TestDelegationVisitor;
 
 	type VoteScoreToU64 = Identity;
 	type WeightInfo = ();
+	type DisputePeriod = DisputePeriod;
+	type DisputeBond = DisputeBond;
+	type BillingReportRetentionPeriod = BillingReportRetentionPeriod;
+}
+
+pub struct TestDelegationVisitor;
+impl DelegationVisitor<Test, Balance> for TestDelegationVisitor {
+	fn get_delegators_rewards(
+		_provider_id: &AccountId,
+		_node_pub_key: &NodePubKey,
+		reward: Balance,
+	) -> Vec<(AccountId, Balance)> {
+		DelegatorShare::get()
+			.map(|(delegator_id, share)| vec![(delegator_id, share * reward)])
+			.unwrap_or_default()
+	}
 }
 
 pub struct TestCustomerCharger;
 impl<T: Config> CustomerCharger<T> for TestCustomerCharger {
 	fn charge_content_owner(
+		bucket_id: Option<BucketId>,
 		content_owner: T::AccountId,
 		billing_vault: T::AccountId,
 		amount: u128,
 	) -> Result<u128, DispatchError> {
+		// budgeted buckets are charged up to their allocation
+		if let Some(bucket_id) = bucket_id.filter(|id| bucket_allocation(*id).is_some()) {
+			let charged = amount.min(bucket_allocation(bucket_id).unwrap_or_default());
+			set_bucket_allocation(bucket_id, bucket_allocation(bucket_id).unwrap() - charged);
+			<T as pallet::Config>::Currency::transfer(
+				&content_owner,
+				&billing_vault,
+				charged.saturated_into::<BalanceOf<T>>(),
+				ExistenceRequirement::AllowDeath,
+			)?;
+			return Ok(charged)
+		}
+
 		let mut amount_to_charge = amount;
 		let mut temp = ACCOUNT_ID_1.to_ne_bytes();
 		let account_1 = T::AccountId::decode(&mut &temp[..]).unwrap();
@@ -177,6 +225,71 @@ impl<T: Config> CustomerCharger<T> for TestCustomerCharger {
 		)?;
 		Ok(amount_to_charge)
 	}
+
+	fn get_payment_source(
+		bucket_id: Option<BucketId>,
+		_content_owner: &T::AccountId,
+	) -> PaymentSource {
+		match bucket_id.filter(|id| bucket_allocation(*id).is_some()) {
+			Some(bucket_id) => PaymentSource::BucketBudget(bucket_id),
+			None => PaymentAsset::get().map_or(PaymentSource::Deposit, PaymentSource::Asset),
+		}
+	}
+
+	fn charge_content_owner_asset(
+		_content_owner: T::AccountId,
+		_payee: T::AccountId,
+		_asset_id: AssetId,
+		amount: u128,
+	) -> Result<u128, DispatchError> {
+		let charged = amount.min(AssetDeposit::get());
+		AssetDeposit::set(AssetDeposit::get() - charged);
+		Ok(charged)
+	}
+
+	fn refund_content_owner(
+		source: PaymentSource,
+		content_owner: T::AccountId,
+		payer: T::AccountId,
+		amount: u128,
+	) -> DispatchResult {
+		match source {
+			PaymentSource::Asset(_) => {
+				AssetDeposit::set(AssetDeposit::get() + amount);
+				Ok(())
+			},
+			PaymentSource::BucketBudget(bucket_id) => {
+				let allocation = bucket_allocation(bucket_id).ok_or(DispatchError::BadOrigin)?;
+				set_bucket_allocation(bucket_id, allocation + amount);
+				<T as pallet::Config>::Currency::transfer(
+					&payer,
+					&content_owner,
+					amount.saturated_into::<BalanceOf<T>>(),
+					ExistenceRequirement::AllowDeath,
+				)
+			},
+			_ => <T as pallet::Config>::Currency::transfer(
+				&payer,
+				&content_owner,
+				amount.saturated_into::<BalanceOf<T>>(),
+				ExistenceRequirement::AllowDeath,
+			),
+		}
+	}
+}
+
+pub fn bucket_allocation(bucket_id: BucketId) -> Option<u128> {
+	BucketAllocations::get()
+		.into_iter()
+		.find(|(id, _)| *id == bucket_id)
+		.map(|(_, allocation)| allocation)
+}
+
+pub fn set_bucket_allocation(bucket_id: BucketId, allocation: u128) {
+	let mut allocations = BucketAllocations::get();
+	allocations.retain(|(id, _)| *id != bucket_id);
+	allocations.push((bucket_id, allocation));
+	BucketAllocations::set(allocations);
 }
 
 pub const ACCOUNT_ID_1: AccountId = 1;
@@ -209,12 +322,49 @@ impl<T: Config> CustomerDepositor<T> for TestCustomerDepositor {
 	}
 }
 
+// The account `n` owns the buckets `n`, `n + BUCKETS_OFFSET`, `n + 2 * BUCKETS_OFFSET`, ...
+pub const BUCKETS_OFFSET: BucketId = 1_000_000;
+// Buckets starting from this id belong to another cluster
+pub const FOREIGN_BUCKETS_START: BucketId = 1_000 * BUCKETS_OFFSET;
+
+pub struct TestBucketVisitor;
+impl<T: Config> BucketVisitor<T> for TestBucketVisitor {
+	fn ensure_bucket_owner(
+		_cluster_id: &ClusterId,
+		bucket_id: BucketId,
+		owner_id: &T::AccountId,
+	) -> Result<(), BucketVisitorError> {
+		ensure!(bucket_id != 0, BucketVisitorError::NoBucketWithId);
+
+		let temp = ((bucket_id % BUCKETS_OFFSET) as AccountId).to_ne_bytes();
+		let bucket_owner = T::AccountId::decode(&mut &temp[..]).unwrap();
+		ensure!(bucket_owner == *owner_id, BucketVisitorError::NotBucketOwner);
+		ensure!(bucket_id < FOREIGN_BUCKETS_START, BucketVisitorError::IncorrectClusterId);
+
+		Ok(())
+	}
+
+	fn get_bucket_metadata(bucket_id: BucketId) -> Result<BucketMetadata, BucketVisitorError> {
+		ensure!(bucket_id != 0, BucketVisitorError::NoBucketWithId);
+
+		Ok(BucketsMetadata::get()
+			.into_iter()
+			.find(|(id, _)| *id == bucket_id)
+			.map(|(_, metadata)| metadata)
+			.unwrap_or_default())
+	}
+}
+
 pub const RESERVE_ACCOUNT_ID: AccountId = 999;
 pub const TREASURY_ACCOUNT_ID: AccountId = 888;
 pub const VALIDATOR1_ACCOUNT_ID: AccountId = 111;
 pub const VALIDATOR2_ACCOUNT_ID: AccountId = 222;
 pub const VALIDATOR3_ACCOUNT_ID: AccountId = 333;
 
+pub const DAC_VALIDATOR1_ACCOUNT_ID: AccountId = 2;
+pub const DAC_VALIDATOR2_ACCOUNT_ID: AccountId = 123;
+pub const DAC_VALIDATOR3_ACCOUNT_ID: AccountId = 456;
+
 pub const VALIDATOR1_SCORE: u64 = 30;
 pub const VALIDATOR2_SCORE: u64 = 45;
 pub const VALIDATOR3_SCORE: u64 = 25;
@@ -233,54 +383,106 @@ pub const STREAM_ZERO_CLUSTER_ID: ClusterId = ClusterId::repeat_byte(7u8);
 pub const PUT_ZERO_CLUSTER_ID: ClusterId = ClusterId::repeat_byte(8u8);
 pub const GET_ZERO_CLUSTER_ID: ClusterId = ClusterId::repeat_byte(9u8);
 pub const STORAGE_STREAM_ZERO_CLUSTER_ID: ClusterId = ClusterId::repeat_byte(11u8);
-pub const PRICING_PARAMS: ClusterPricingParams = ClusterPricingParams {
-	unit_per_mb_streamed: 2_000_000,
-	unit_per_mb_stored: 3_000_000,
-	unit_per_put_request: 4_000_000,
-	unit_per_get_request: 5_000_000,
-};
+pub const TIERED_CLUSTER_ID: ClusterId = ClusterId::repeat_byte(13u8);
+pub const BILLING_PERIOD: u64 = 2_630_016; // 30.44 days
+pub fn pricing_params() -> ClusterPricingParams {
+	ClusterPricingParams {
+		unit_per_mb_streamed: 2_000_000,
+		unit_per_mb_stored: 3_000_000,
+		unit_per_put_request: 4_000_000,
+		unit_per_get_request: 5_000_000,
+		billing_period: BILLING_PERIOD,
+		pricing_tiers: Default::default(),
+	}
+}
 
-pub const PRICING_PARAMS_STREAM_ZERO: ClusterPricingParams = ClusterPricingParams {
-	unit_per_mb_streamed: 0,
-	unit_per_mb_stored: 3_000_000,
-	unit_per_put_request: 4_000_000,
-	unit_per_get_request: 5_000_000,
-};
+pub fn pricing_params_stream_zero() -> ClusterPricingParams {
+	ClusterPricingParams {
+		unit_per_mb_streamed: 0,
+		unit_per_mb_stored: 3_000_000,
+		unit_per_put_request: 4_000_000,
+		unit_per_get_request: 5_000_000,
+		billing_period: BILLING_PERIOD,
+		pricing_tiers: Default::default(),
+	}
+}
 
-pub const PRICING_PARAMS_STORAGE_ZERO: ClusterPricingParams = ClusterPricingParams {
-	unit_per_mb_streamed: 2_000_000,
-	unit_per_mb_stored: 0,
-	unit_per_put_request: 4_000_000,
-	unit_per_get_request: 5_000_000,
-};
+pub fn pricing_params_storage_zero() -> ClusterPricingParams {
+	ClusterPricingParams {
+		unit_per_mb_streamed: 2_000_000,
+		unit_per_mb_stored: 0,
+		unit_per_put_request: 4_000_000,
+		unit_per_get_request: 5_000_000,
+		billing_period: BILLING_PERIOD,
+		pricing_tiers: Default::default(),
+	}
+}
 
-pub const PRICING_PARAMS_GET_ZERO: ClusterPricingParams = ClusterPricingParams {
-	unit_per_mb_streamed: 2_000_000,
-	unit_per_mb_stored: 3_000_000,
-	unit_per_put_request: 4_000_000,
-	unit_per_get_request: 0,
-};
+pub fn pricing_params_get_zero() -> ClusterPricingParams {
+	ClusterPricingParams {
+		unit_per_mb_streamed: 2_000_000,
+		unit_per_mb_stored: 3_000_000,
+		unit_per_put_request: 4_000_000,
+		unit_per_get_request: 0,
+		billing_period: BILLING_PERIOD,
+		pricing_tiers: Default::default(),
+	}
+}
 
-pub const PRICING_PARAMS_PUT_ZERO: ClusterPricingParams = ClusterPricingParams {
-	unit_per_mb_streamed: 2_000_000,
-	unit_per_mb_stored: 3_000_000,
-	unit_per_put_request: 0,
-	unit_per_get_request: 5_000_000,
-};
+pub fn pricing_params_put_zero() -> ClusterPricingParams {
+	ClusterPricingParams {
+		unit_per_mb_streamed: 2_000_000,
+		unit_per_mb_stored: 3_000_000,
+		unit_per_put_request: 0,
+		unit_per_get_request: 5_000_000,
+		billing_period: BILLING_PERIOD,
+		pricing_tiers: Default::default(),
+	}
+}
 
-pub const PRICING_PARAMS_ONE: ClusterPricingParams = ClusterPricingParams {
-	unit_per_mb_streamed: 10_000_000_000,
-	unit_per_mb_stored: 10_000_000_000,
-	unit_per_put_request: 10_000_000_000,
-	unit_per_get_request: 10_000_000_000,
-};
+pub fn pricing_params_one() -> ClusterPricingParams {
+	ClusterPricingParams {
+		unit_per_mb_streamed: 10_000_000_000,
+		unit_per_mb_stored: 10_000_000_000,
+		unit_per_put_request: 10_000_000_000,
+		unit_per_get_request: 10_000_000_000,
+		billing_period: BILLING_PERIOD,
+		pricing_tiers: Default::default(),
+	}
+}
 
-pub const PRICING_PARAMS_CERE: ClusterPricingParams = ClusterPricingParams {
-	unit_per_mb_streamed: DOLLARS,
-	unit_per_mb_stored: DOLLARS,
-	unit_per_put_request: DOLLARS,
-	unit_per_get_request: DOLLARS,
-};
+pub fn pricing_params_cere() -> ClusterPricingParams {
+	ClusterPricingParams {
+		unit_per_mb_streamed: DOLLARS,
+		unit_per_mb_stored: DOLLARS,
+		unit_per_put_request: DOLLARS,
+		unit_per_get_request: DOLLARS,
+		billing_period: BILLING_PERIOD,
+		pricing_tiers: Default::default(),
+	}
+}
+
+pub fn get_pricing_tiered() -> ClusterPricingParams {
+	let hundred_mb = 100 * byte_unit::MEBIBYTE as u64;
+	ClusterPricingParams {
+		pricing_tiers: ClusterPricingTiers {
+			streamed: vec![PricingTier { threshold: hundred_mb, unit_price: 1_000_000 }]
+				.try_into()
+				.unwrap(),
+			stored: vec![PricingTier { threshold: hundred_mb, unit_price: 1_500_000 }]
+				.try_into()
+				.unwrap(),
+			puts: vec![PricingTier { threshold: 10, unit_price: 2_000_000 }].try_into().unwrap(),
+			gets: vec![
+				PricingTier { threshold: 10, unit_price: 2_500_000 },
+				PricingTier { threshold: 100, unit_price: 1_000_000 },
+			]
+			.try_into()
+			.unwrap(),
+		},
+		..pricing_params()
+	}
+}
 
 pub const PRICING_FEES: ClusterFeesParams = ClusterFeesParams {
 	treasury_share: Perquintill::from_percent(1),
@@ -300,6 +502,21 @@ pub const PRICING_FEES_ZERO: ClusterFeesParams = ClusterFeesParams {
 	cluster_reserve_share: Perquintill::from_percent(0),
 };
 
+pub struct TestDacValidatorVisitor;
+impl<T: frame_system::Config> ValidatorVisitor<T> for TestDacValidatorVisitor {
+	fn get_active_validators() -> Vec<T::AccountId> {
+		DacValidators::get()
+			.iter()
+			.map(|validator| T::AccountId::decode(&mut &validator.to_ne_bytes()[..]).unwrap())
+			.collect()
+	}
+
+	#[cfg(feature = "runtime-benchmarks")]
+	fn setup_validators(_validators: Vec<T::AccountId>) {
+		unimplemented!()
+	}
+}
+
 pub struct TestTreasuryVisitor;
 impl<T: frame_system::Config> PalletVisitor<T> for TestTreasuryVisitor {
 	fn get_account_id() -> T::AccountId {
@@ -330,9 +547,11 @@ impl<T: frame_system::Config> SortedListProvider<T::AccountId> for TestValidator
 		)
 	}
 	fn iter_from(
-		_start: &T::AccountId,
+		start: &T::AccountId,
 	) -> Result<Box<dyn Iterator<Item = T::AccountId>>, Self::Error> {
-		unimplemented!()
+		let mut stakers = Self::iter();
+		stakers.position(|staker_id| staker_id == *start).ok_or(())?;
+		Ok(stakers)
 	}
 	fn count() -> u32 {
 		3
@@ -394,19 +613,21 @@ pub fn get_fees(cluster_id: &ClusterId) -> ClusterFeesParams {
 
 pub fn get_pricing(cluster_id: &ClusterId) -> ClusterPricingParams {
 	if *cluster_id == ONE_CLUSTER_ID || *cluster_id == NO_FEE_CLUSTER_ID {
-		PRICING_PARAMS_ONE
+		pricing_params_one()
 	} else if *cluster_id == CERE_CLUSTER_ID {
-		PRICING_PARAMS_CERE
+		pricing_params_cere()
 	} else if *cluster_id == STORAGE_ZERO_CLUSTER_ID {
-		PRICING_PARAMS_STORAGE_ZERO
+		pricing_params_storage_zero()
 	} else if *cluster_id == STREAM_ZERO_CLUSTER_ID {
-		PRICING_PARAMS_STREAM_ZERO
+		pricing_params_stream_zero()
 	} else if *cluster_id == PUT_ZERO_CLUSTER_ID {
-		PRICING_PARAMS_PUT_ZERO
+		pricing_params_put_zero()
 	} else if *cluster_id == GET_ZERO_CLUSTER_ID {
-		PRICING_PARAMS_GET_ZERO
+		pricing_params_get_zero()
+	} else if *cluster_id == TIERED_CLUSTER_ID {
+		get_pricing_tiered()
 	} else {
-		PRICING_PARAMS
+		pricing_params()
 	}
 }
 
@@ -444,6 +665,10 @@ impl<T: Config> ClusterVisitor<T> for TestClusterVisitor {
 		Ok(get_fees(cluster_id))
 	}
 
+	fn get_customer_discount(_cluster_id: &ClusterId, _customer_id: &T::AccountId) -> Perquintill {
+		CustomerDiscount::get()
+	}
+
 	fn get_reserve_account_id(
 		_cluster_id: &ClusterId,
 	) -> Result<T::AccountId, ClusterVisitorError> {
@@ -456,6 +681,21 @@ impl<T: Config> ClusterVisitor<T> for TestClusterVisitor {
 	) -> Result<ClusterBondingParams<BlockNumberFor<T>>, ClusterVisitorError> {
 		unimplemented!()
 	}
+
+	fn get_capabilities(
+		_cluster_id: &ClusterId,
+	) -> Result<ClusterCapabilities, ClusterVisitorError> {
+		Ok(ClusterCapabilities {
+			default_replication_factor: 2,
+			max_replication_factor: 4,
+			regions: Default::default(),
+			cold_storage_discount: Some(Perquintill::from_percent(40)),
+		})
+	}
+
+	fn get_asset_rate(_cluster_id: &ClusterId, _asset_id: AssetId) -> Option<FixedU128> {
+		AssetRate::get()
+	}
 }
 
 pub(crate) type TestRuntimeCall = <Test as frame_system::Config>::RuntimeCall;
@@ -484,7 +724,6 @@ impl ExtBuilder {
 		let _payout_genesis = pallet_ddc_payouts::GenesisConfig::<Test> {
 			feeder_account: None,
 			debtor_customers: Default::default(),
-			authorised_caller: None,
 		}
 		.assimilate_storage(&mut storage);
 
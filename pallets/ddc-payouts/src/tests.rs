@@ -1,33 +1,53 @@
 //! Tests for the module.
 
 use chrono::{DateTime, NaiveDate, NaiveTime, Utc};
-use ddc_primitives::ClusterId;
+use ddc_primitives::{
+	BucketId, BucketMetadata, ClusterId, NodePubKey, StorageClass, StorageNodePubKey,
+};
 use frame_support::{assert_noop, assert_ok, error::BadOrigin, traits::Randomness};
 use sp_core::H256;
-use sp_runtime::Perquintill;
+use sp_runtime::{
+	helpers_128bit::multiply_by_rational_with_rounding, FixedPointNumber, FixedU128, Perquintill,
+	Rounding,
+};
 
 use super::{mock::*, *};
 
 #[test]
-fn set_authorised_caller_works() {
+fn set_validator_threshold_works() {
 	ExtBuilder.build_and_execute(|| {
 		System::set_block_number(1);
 
 		let root_account = 1u128;
-		let dac_account = 2u128;
+		let threshold = 2;
 
 		assert_noop!(
-			DdcPayouts::set_authorised_caller(RuntimeOrigin::signed(root_account), dac_account),
+			DdcPayouts::set_validator_threshold(RuntimeOrigin::signed(root_account), threshold),
 			BadOrigin
 		);
 
-		assert_ok!(DdcPayouts::set_authorised_caller(RuntimeOrigin::root(), dac_account));
+		assert_noop!(
+			DdcPayouts::set_validator_threshold(RuntimeOrigin::root(), 0),
+			Error::<Test>::InvalidThreshold
+		);
 
-		System::assert_last_event(
-			Event::AuthorisedCaller { authorised_caller: dac_account }.into(),
+		// the threshold can not exceed the number of DAC validators
+		assert_noop!(
+			DdcPayouts::set_validator_threshold(RuntimeOrigin::root(), 4),
+			Error::<Test>::InvalidThreshold
 		);
 
-		assert_eq!(DdcPayouts::authorised_caller().unwrap(), dac_account);
+		// a majority of DAC validators by default
+		assert_eq!(DdcPayouts::validator_threshold(), 2);
+
+		assert_ok!(DdcPayouts::set_validator_threshold(RuntimeOrigin::root(), 3));
+		assert_eq!(DdcPayouts::validator_threshold(), 3);
+
+		assert_ok!(DdcPayouts::set_validator_threshold(RuntimeOrigin::root(), threshold));
+
+		System::assert_last_event(Event::ValidatorThresholdChanged { threshold }.into());
+
+		assert_eq!(DdcPayouts::validator_threshold(), threshold);
 	})
 }
 
@@ -44,8 +64,10 @@ fn begin_billing_report_fails_for_unauthorised() {
 		let start_era: i64 =
 			DateTime::<Utc>::from_naive_utc_and_offset(start_date.and_time(time), Utc).timestamp();
 		let end_era: i64 = start_era + (30.44 * 24.0 * 3600.0) as i64;
+		let customers_merkle_root = H256::default();
+		let nodes_merkle_root = H256::default();
 
-		assert_ok!(DdcPayouts::set_authorised_caller(RuntimeOrigin::root(), dac_account));
+		assert_ok!(DdcPayouts::set_validator_threshold(RuntimeOrigin::root(), 1));
 
 		assert_noop!(
 			DdcPayouts::begin_billing_report(
@@ -54,6 +76,12 @@ fn begin_billing_report_fails_for_unauthorised() {
 				era,
 				start_era,
 				end_era,
+				customers_merkle_root,
+				nodes_merkle_root,
+				0,
+				0,
+				CustomerUsage::default(),
+				NodeUsage::default(),
 			),
 			Error::<Test>::Unauthorised
 		);
@@ -65,6 +93,12 @@ fn begin_billing_report_fails_for_unauthorised() {
 				era,
 				start_era,
 				end_era,
+				customers_merkle_root,
+				nodes_merkle_root,
+				0,
+				0,
+				CustomerUsage::default(),
+				NodeUsage::default(),
 			),
 			Error::<Test>::Unauthorised
 		);
@@ -85,8 +119,10 @@ fn begin_billing_report_works() {
 		let start_era: i64 =
 			DateTime::<Utc>::from_naive_utc_and_offset(start_date.and_time(time), Utc).timestamp();
 		let end_era: i64 = start_era + (30.44 * 24.0 * 3600.0) as i64;
+		let customers_merkle_root = H256::repeat_byte(1);
+		let nodes_merkle_root = H256::repeat_byte(2);
 
-		assert_ok!(DdcPayouts::set_authorised_caller(RuntimeOrigin::root(), dac_account));
+		assert_ok!(DdcPayouts::set_validator_threshold(RuntimeOrigin::root(), 1));
 
 		assert_ok!(DdcPayouts::begin_billing_report(
 			RuntimeOrigin::signed(dac_account),
@@ -94,6 +130,12 @@ fn begin_billing_report_works() {
 			era,
 			start_era,
 			end_era,
+			customers_merkle_root,
+			nodes_merkle_root,
+			0,
+			0,
+			CustomerUsage::default(),
+			NodeUsage::default(),
 		));
 
 		System::assert_last_event(Event::BillingReportInitialized { cluster_id, era }.into());
@@ -102,12 +144,348 @@ fn begin_billing_report_works() {
 		assert_eq!(report.state, State::Initialized);
 		assert_eq!(report.start_era, start_era);
 		assert_eq!(report.end_era, end_era);
+		assert_eq!(report.customers_merkle_root, customers_merkle_root);
+		assert_eq!(report.nodes_merkle_root, nodes_merkle_root);
+	})
+}
+
+#[test]
+fn billing_report_proposals_require_validators_quorum() {
+	ExtBuilder.build_and_execute(|| {
+		System::set_block_number(1);
+
+		let cluster_id = ClusterId::from([12; 20]);
+		let era = 100;
+		let start_date = NaiveDate::from_ymd_opt(2023, 4, 1).unwrap(); // April 1st
+
+		let time = NaiveTime::from_hms_opt(0, 0, 0).unwrap(); // Midnight
+		let start_era: i64 =
+			DateTime::<Utc>::from_naive_utc_and_offset(start_date.and_time(time), Utc).timestamp();
+		let end_era: i64 = start_era + (30.44 * 24.0 * 3600.0) as i64;
+		let customers_merkle_root = H256::repeat_byte(1);
+		let nodes_merkle_root = H256::repeat_byte(2);
+		let proposal_hash = DdcPayouts::proposal_hash(
+			1,
+			(
+				start_era,
+				end_era,
+				customers_merkle_root,
+				nodes_merkle_root,
+				0u16,
+				0u16,
+				&CustomerUsage::default(),
+				&NodeUsage::default(),
+			),
+		);
+
+		assert_ok!(DdcPayouts::set_validator_threshold(RuntimeOrigin::root(), 2));
+
+		assert_ok!(DdcPayouts::begin_billing_report(
+			RuntimeOrigin::signed(DAC_VALIDATOR1_ACCOUNT_ID),
+			cluster_id,
+			era,
+			start_era,
+			end_era,
+			customers_merkle_root,
+			nodes_merkle_root,
+			0,
+			0,
+			CustomerUsage::default(),
+			NodeUsage::default(),
+		));
+
+		System::assert_last_event(
+			Event::BillingReportVoted {
+				cluster_id,
+				era,
+				validator: DAC_VALIDATOR1_ACCOUNT_ID,
+				proposal_hash,
+			}
+			.into(),
+		);
+		assert!(DdcPayouts::active_billing_reports(cluster_id, era).is_none());
+		assert_eq!(
+			DdcPayouts::billing_report_votes((cluster_id, era), proposal_hash)
+				.map(|votes| votes.into_inner()),
+			Some(vec![DAC_VALIDATOR1_ACCOUNT_ID])
+		);
+
+		assert_noop!(
+			DdcPayouts::begin_billing_report(
+				RuntimeOrigin::signed(DAC_VALIDATOR1_ACCOUNT_ID),
+				cluster_id,
+				era,
+				start_era,
+				end_era,
+				customers_merkle_root,
+				nodes_merkle_root,
+				0,
+				0,
+				CustomerUsage::default(),
+				NodeUsage::default(),
+			),
+			Error::<Test>::AlreadyVoted
+		);
+
+		// vote for other parameters is not counted towards the proposal
+		assert_ok!(DdcPayouts::begin_billing_report(
+			RuntimeOrigin::signed(DAC_VALIDATOR2_ACCOUNT_ID),
+			cluster_id,
+			era,
+			start_era,
+			end_era,
+			H256::repeat_byte(3),
+			nodes_merkle_root,
+			0,
+			0,
+			CustomerUsage::default(),
+			NodeUsage::default(),
+		));
+		assert!(DdcPayouts::active_billing_reports(cluster_id, era).is_none());
+
+		assert_ok!(DdcPayouts::begin_billing_report(
+			RuntimeOrigin::signed(DAC_VALIDATOR3_ACCOUNT_ID),
+			cluster_id,
+			era,
+			start_era,
+			end_era,
+			customers_merkle_root,
+			nodes_merkle_root,
+			0,
+			0,
+			CustomerUsage::default(),
+			NodeUsage::default(),
+		));
+
+		System::assert_last_event(Event::BillingReportInitialized { cluster_id, era }.into());
+		let report = DdcPayouts::active_billing_reports(cluster_id, era).unwrap();
+		assert_eq!(report.customers_merkle_root, customers_merkle_root);
+		assert_eq!(DdcPayouts::billing_report_votes((cluster_id, era), proposal_hash), None);
+		assert_eq!(DdcPayouts::executed_proposals((cluster_id, era), proposal_hash), Some(()));
+
+		// votes for the executed proposal are rejected instead of being stored
+		assert_noop!(
+			DdcPayouts::begin_billing_report(
+				RuntimeOrigin::signed(DAC_VALIDATOR2_ACCOUNT_ID),
+				cluster_id,
+				era,
+				start_era,
+				end_era,
+				customers_merkle_root,
+				nodes_merkle_root,
+				0,
+				0,
+				CustomerUsage::default(),
+				NodeUsage::default(),
+			),
+			Error::<Test>::ProposalAlreadyExecuted
+		);
+	})
+}
+
+#[test]
+fn billing_report_proposals_count_votes_of_active_validators_only() {
+	ExtBuilder.build_and_execute(|| {
+		System::set_block_number(1);
+
+		let cluster_id = ClusterId::from([12; 20]);
+		let era = 100;
+		let start_era: i64 = 1_000_000_000;
+		let end_era: i64 = start_era + (30.44 * 24.0 * 3600.0) as i64;
+		let customers_merkle_root = H256::repeat_byte(1);
+		let nodes_merkle_root = H256::repeat_byte(2);
+		let proposal_hash = DdcPayouts::proposal_hash(
+			1,
+			(
+				start_era,
+				end_era,
+				customers_merkle_root,
+				nodes_merkle_root,
+				0u16,
+				0u16,
+				&CustomerUsage::default(),
+				&NodeUsage::default(),
+			),
+		);
+
+		assert_ok!(DdcPayouts::set_validator_threshold(RuntimeOrigin::root(), 3));
+
+		assert_ok!(DdcPayouts::begin_billing_report(
+			RuntimeOrigin::signed(DAC_VALIDATOR3_ACCOUNT_ID),
+			cluster_id,
+			era,
+			start_era,
+			end_era,
+			customers_merkle_root,
+			nodes_merkle_root,
+			0,
+			0,
+			CustomerUsage::default(),
+			NodeUsage::default(),
+		));
+
+		// the threshold set by governance is capped by the active validators
+		DacValidators::set(vec![DAC_VALIDATOR1_ACCOUNT_ID, DAC_VALIDATOR2_ACCOUNT_ID]);
+		assert_eq!(DdcPayouts::validator_threshold(), 2);
+
+		// the vote of the validator that has left the active set is dropped
+		assert_ok!(DdcPayouts::begin_billing_report(
+			RuntimeOrigin::signed(DAC_VALIDATOR1_ACCOUNT_ID),
+			cluster_id,
+			era,
+			start_era,
+			end_era,
+			customers_merkle_root,
+			nodes_merkle_root,
+			0,
+			0,
+			CustomerUsage::default(),
+			NodeUsage::default(),
+		));
+		assert!(DdcPayouts::active_billing_reports(cluster_id, era).is_none());
+		assert_eq!(
+			DdcPayouts::billing_report_votes((cluster_id, era), proposal_hash)
+				.map(|votes| votes.into_inner()),
+			Some(vec![DAC_VALIDATOR1_ACCOUNT_ID])
+		);
+
+		assert_ok!(DdcPayouts::begin_billing_report(
+			RuntimeOrigin::signed(DAC_VALIDATOR2_ACCOUNT_ID),
+			cluster_id,
+			era,
+			start_era,
+			end_era,
+			customers_merkle_root,
+			nodes_merkle_root,
+			0,
+			0,
+			CustomerUsage::default(),
+			NodeUsage::default(),
+		));
+		System::assert_last_event(Event::BillingReportInitialized { cluster_id, era }.into());
+	})
+}
+
+#[test]
+fn billing_report_proposals_require_validators_majority_by_default() {
+	ExtBuilder.build_and_execute(|| {
+		System::set_block_number(1);
+
+		let cluster_id = ClusterId::from([12; 20]);
+		let era = 100;
+		let start_era: i64 = 1_000_000_000;
+		let end_era: i64 = start_era + (30.44 * 24.0 * 3600.0) as i64;
+		let customers_merkle_root = H256::repeat_byte(1);
+		let nodes_merkle_root = H256::repeat_byte(2);
+
+		assert_ok!(DdcPayouts::begin_billing_report(
+			RuntimeOrigin::signed(DAC_VALIDATOR1_ACCOUNT_ID),
+			cluster_id,
+			era,
+			start_era,
+			end_era,
+			customers_merkle_root,
+			nodes_merkle_root,
+			0,
+			0,
+			CustomerUsage::default(),
+			NodeUsage::default(),
+		));
+		assert!(DdcPayouts::active_billing_reports(cluster_id, era).is_none());
+
+		assert_ok!(DdcPayouts::begin_billing_report(
+			RuntimeOrigin::signed(DAC_VALIDATOR2_ACCOUNT_ID),
+			cluster_id,
+			era,
+			start_era,
+			end_era,
+			customers_merkle_root,
+			nodes_merkle_root,
+			0,
+			0,
+			CustomerUsage::default(),
+			NodeUsage::default(),
+		));
+		System::assert_last_event(Event::BillingReportInitialized { cluster_id, era }.into());
+
+		assert_noop!(
+			DdcPayouts::begin_billing_report(
+				RuntimeOrigin::signed(DAC_VALIDATOR3_ACCOUNT_ID),
+				cluster_id,
+				era,
+				start_era,
+				end_era,
+				customers_merkle_root,
+				nodes_merkle_root,
+				0,
+				0,
+				CustomerUsage::default(),
+				NodeUsage::default(),
+			),
+			Error::<Test>::ProposalAlreadyExecuted
+		);
+	})
+}
+
+#[test]
+fn late_votes_are_rejected_after_billing_report_is_finalized() {
+	ExtBuilder.build_and_execute(|| {
+		System::set_block_number(1);
+		BillingReportRetentionPeriod::set(10);
+
+		let cluster_id = ClusterId::from([12; 20]);
+		let era = 100;
+		let proposal_hash = DdcPayouts::proposal_hash(8, ());
+
+		run_billing_report_until_providers_rewarded(DAC_VALIDATOR1_ACCOUNT_ID, cluster_id, era);
+		assert_ok!(DdcPayouts::set_validator_threshold(RuntimeOrigin::root(), 2));
+
+		assert_ok!(DdcPayouts::end_billing_report(
+			RuntimeOrigin::signed(DAC_VALIDATOR1_ACCOUNT_ID),
+			cluster_id,
+			era,
+		));
+		assert_eq!(
+			DdcPayouts::active_billing_reports(cluster_id, era).unwrap().state,
+			State::ProvidersRewarded
+		);
+
+		assert_ok!(DdcPayouts::end_billing_report(
+			RuntimeOrigin::signed(DAC_VALIDATOR2_ACCOUNT_ID),
+			cluster_id,
+			era,
+		));
+		System::assert_last_event(Event::BillingReportFinalized { cluster_id, era }.into());
+
+		assert_noop!(
+			DdcPayouts::end_billing_report(
+				RuntimeOrigin::signed(DAC_VALIDATOR3_ACCOUNT_ID),
+				cluster_id,
+				era
+			),
+			Error::<Test>::ProposalAlreadyExecuted
+		);
+		assert_eq!(DdcPayouts::billing_report_votes((cluster_id, era), proposal_hash), None);
+
+		// the executed proposals are dropped with the report, the archive rejects the votes
+		DdcPayouts::on_idle(11, Weight::MAX);
+		assert!(DdcPayouts::archived_billing_reports(cluster_id, era).is_some());
+		assert_eq!(DdcPayouts::executed_proposals((cluster_id, era), proposal_hash), None);
+		assert_noop!(
+			DdcPayouts::end_billing_report(
+				RuntimeOrigin::signed(DAC_VALIDATOR3_ACCOUNT_ID),
+				cluster_id,
+				era
+			),
+			Error::<Test>::ProposalAlreadyExecuted
+		);
 	})
 }
 
 #[test]
 fn begin_charging_customers_fails_uninitialised() {
 	ExtBuilder.build_and_execute(|| {
+		let root_account = 1u128;
 		let dac_account = 2u128;
 		let cluster_id = ClusterId::from([12; 20]);
 		let era = 100;
@@ -115,32 +493,25 @@ fn begin_charging_customers_fails_uninitialised() {
 
 		assert_noop!(
 			DdcPayouts::begin_charging_customers(
-				RuntimeOrigin::signed(dac_account),
+				RuntimeOrigin::signed(root_account),
 				cluster_id,
 				era,
-				max_batch_index,
 			),
 			Error::<Test>::Unauthorised
 		);
 
 		assert_noop!(
-			DdcPayouts::begin_charging_customers(
-				RuntimeOrigin::root(),
-				cluster_id,
-				era,
-				max_batch_index,
-			),
+			DdcPayouts::begin_charging_customers(RuntimeOrigin::root(), cluster_id, era),
 			BadOrigin
 		);
 
-		assert_ok!(DdcPayouts::set_authorised_caller(RuntimeOrigin::root(), dac_account));
+		assert_ok!(DdcPayouts::set_validator_threshold(RuntimeOrigin::root(), 1));
 
 		assert_noop!(
 			DdcPayouts::begin_charging_customers(
 				RuntimeOrigin::signed(dac_account),
 				cluster_id,
 				era,
-				max_batch_index,
 			),
 			Error::<Test>::BillingReportDoesNotExist
 		);
@@ -162,8 +533,10 @@ fn begin_charging_customers_works() {
 		let start_era: i64 =
 			DateTime::<Utc>::from_naive_utc_and_offset(start_date.and_time(time), Utc).timestamp();
 		let end_era: i64 = start_era + (30.44 * 24.0 * 3600.0) as i64;
+		let customers_merkle_root = H256::default();
+		let nodes_merkle_root = H256::default();
 
-		assert_ok!(DdcPayouts::set_authorised_caller(RuntimeOrigin::root(), dac_account));
+		assert_ok!(DdcPayouts::set_validator_threshold(RuntimeOrigin::root(), 1));
 
 		assert_ok!(DdcPayouts::begin_billing_report(
 			RuntimeOrigin::signed(dac_account),
@@ -171,13 +544,18 @@ fn begin_charging_customers_works() {
 			era,
 			start_era,
 			end_era,
+			customers_merkle_root,
+			nodes_merkle_root,
+			max_batch_index,
+			0,
+			CustomerUsage::default(),
+			NodeUsage::default(),
 		));
 
 		assert_ok!(DdcPayouts::begin_charging_customers(
 			RuntimeOrigin::signed(dac_account),
 			cluster_id,
 			era,
-			max_batch_index,
 		));
 
 		System::assert_last_event(Event::ChargingStarted { cluster_id, era }.into());
@@ -199,14 +577,17 @@ fn send_charging_customers_batch_fails_uninitialised() {
 		let era = 100;
 		let max_batch_index = 2;
 		let batch_index = 1;
-		let payers1 = vec![(user1, CustomerUsage::default())];
-		let payers2 = vec![(user2, CustomerUsage::default())];
+		let payers1 = vec![(user1, user1 as BucketId, CustomerUsage::default())];
+		let payers2 = vec![(user2, user2 as BucketId, CustomerUsage::default())];
 		let start_date = NaiveDate::from_ymd_opt(2023, 4, 1).unwrap(); // April 1st
 
 		let time = NaiveTime::from_hms_opt(0, 0, 0).unwrap(); // Midnight
 		let start_era: i64 =
 			DateTime::<Utc>::from_naive_utc_and_offset(start_date.and_time(time), Utc).timestamp();
 		let end_era: i64 = start_era + (30.44 * 24.0 * 3600.0) as i64;
+		let (customers_merkle_root, customers_proofs) =
+			get_batches_commitment(&[(batch_index, &payers1)]);
+		let nodes_merkle_root = H256::default();
 
 		assert_noop!(
 			DdcPayouts::send_charging_customers_batch(
@@ -215,6 +596,7 @@ fn send_charging_customers_batch_fails_uninitialised() {
 				era,
 				batch_index,
 				payers1.clone(),
+				customers_proofs[0].clone(),
 			),
 			Error::<Test>::Unauthorised
 		);
@@ -226,11 +608,12 @@ fn send_charging_customers_batch_fails_uninitialised() {
 				era,
 				batch_index,
 				payers1.clone(),
+				customers_proofs[0].clone(),
 			),
 			BadOrigin
 		);
 
-		assert_ok!(DdcPayouts::set_authorised_caller(RuntimeOrigin::root(), dac_account));
+		assert_ok!(DdcPayouts::set_validator_threshold(RuntimeOrigin::root(), 1));
 
 		assert_noop!(
 			DdcPayouts::send_charging_customers_batch(
@@ -239,6 +622,7 @@ fn send_charging_customers_batch_fails_uninitialised() {
 				era,
 				batch_index,
 				payers1.clone(),
+				customers_proofs[0].clone(),
 			),
 			Error::<Test>::BillingReportDoesNotExist
 		);
@@ -249,6 +633,12 @@ fn send_charging_customers_batch_fails_uninitialised() {
 			era,
 			start_era,
 			end_era,
+			customers_merkle_root,
+			nodes_merkle_root,
+			max_batch_index,
+			0,
+			CustomerUsage::default(),
+			NodeUsage::default(),
 		));
 
 		assert_noop!(
@@ -258,6 +648,7 @@ fn send_charging_customers_batch_fails_uninitialised() {
 				era,
 				batch_index,
 				payers1.clone(),
+				customers_proofs[0].clone(),
 			),
 			Error::<Test>::NotExpectedState
 		);
@@ -266,7 +657,6 @@ fn send_charging_customers_batch_fails_uninitialised() {
 			RuntimeOrigin::signed(dac_account),
 			cluster_id,
 			era,
-			max_batch_index,
 		));
 
 		assert_ok!(DdcPayouts::send_charging_customers_batch(
@@ -275,6 +665,7 @@ fn send_charging_customers_batch_fails_uninitialised() {
 			era,
 			batch_index,
 			payers1.clone(),
+			customers_proofs[0].clone(),
 		));
 
 		assert_noop!(
@@ -284,8 +675,9 @@ fn send_charging_customers_batch_fails_uninitialised() {
 				era,
 				batch_index,
 				payers1,
+				customers_proofs[0].clone(),
 			),
-			Error::<Test>::BatchIndexAlreadyProcessed
+			Error::<Test>::ProposalAlreadyExecuted
 		);
 
 		assert_noop!(
@@ -295,88 +687,235 @@ fn send_charging_customers_batch_fails_uninitialised() {
 				era,
 				batch_index,
 				payers2,
+				vec![],
 			),
 			Error::<Test>::BatchIndexAlreadyProcessed
 		);
 	})
 }
 
-fn calculate_charge_parts_for_day(cluster_id: ClusterId, usage: CustomerUsage) -> CustomerCharge {
-	let pricing_params = get_pricing(&cluster_id);
-
-	// Calculate the duration of the period in seconds
-	let duration_seconds = 1.0 * 24.0 * 3600.0;
-	let seconds_in_month = 30.44 * 24.0 * 3600.0;
-	let fraction_of_month =
-		Perquintill::from_rational(duration_seconds as u64, seconds_in_month as u64);
-
-	let storage = fraction_of_month *
-		(|| -> Option<u128> {
-			(usage.stored_bytes as u128)
-				.checked_mul(pricing_params.unit_per_mb_stored)?
-				.checked_div(byte_unit::MEBIBYTE)
-		})()
-		.unwrap();
+#[test]
+fn send_charging_customers_batch_fails_invalid_proof() {
+	ExtBuilder.build_and_execute(|| {
+		let dac_account = 2u128;
+		let user1 = 3u128;
+		let user2 = 4u128;
+		let cluster_id = ClusterId::from([12; 20]);
+		let era = 100;
+		let max_batch_index = 1;
+		let batch_index = 0;
+		let payers1 = vec![(user1, user1 as BucketId, CustomerUsage::default())];
+		let payers2 = vec![(user2, user2 as BucketId, CustomerUsage::default())];
+		let start_date = NaiveDate::from_ymd_opt(2023, 4, 1).unwrap(); // April 1st
 
-	CustomerCharge {
-		transfer: pricing_params.unit_per_mb_streamed * (usage.transferred_bytes as u128) /
-			byte_unit::MEBIBYTE,
-		storage,
-		puts: pricing_params.unit_per_put_request * (usage.number_of_puts as u128),
-		gets: pricing_params.unit_per_get_request * (usage.number_of_gets as u128),
-	}
-}
+		let time = NaiveTime::from_hms_opt(0, 0, 0).unwrap(); // Midnight
+		let start_era: i64 =
+			DateTime::<Utc>::from_naive_utc_and_offset(start_date.and_time(time), Utc).timestamp();
+		let end_era: i64 = start_era + (30.44 * 24.0 * 3600.0) as i64;
+		let (customers_merkle_root, customers_proofs) =
+			get_batches_commitment(&[(batch_index, &payers1), (batch_index + 1, &payers2)]);
+		let nodes_merkle_root = H256::default();
 
-fn calculate_charge_for_day(cluster_id: ClusterId, usage: CustomerUsage) -> u128 {
-	let charge = calculate_charge_parts_for_day(cluster_id, usage);
-	charge.transfer + charge.storage + charge.puts + charge.gets
-}
+		assert_ok!(DdcPayouts::set_validator_threshold(RuntimeOrigin::root(), 1));
+		assert_ok!(DdcPayouts::begin_billing_report(
+			RuntimeOrigin::signed(dac_account),
+			cluster_id,
+			era,
+			start_era,
+			end_era,
+			customers_merkle_root,
+			nodes_merkle_root,
+			max_batch_index,
+			0,
+			CustomerUsage::default(),
+			NodeUsage::default(),
+		));
+		assert_ok!(DdcPayouts::begin_charging_customers(
+			RuntimeOrigin::signed(dac_account),
+			cluster_id,
+			era,
+		));
 
-fn calculate_charge_parts_for_month(cluster_id: ClusterId, usage: CustomerUsage) -> CustomerCharge {
-	let pricing_params = get_pricing(&cluster_id);
+		// usage that was not committed to
+		assert_noop!(
+			DdcPayouts::send_charging_customers_batch(
+				RuntimeOrigin::signed(dac_account),
+				cluster_id,
+				era,
+				batch_index,
+				vec![(
+					user1,
+					user1 as BucketId,
+					CustomerUsage { stored_bytes: 1, ..Default::default() }
+				)],
+				customers_proofs[0].clone(),
+			),
+			Error::<Test>::BatchProofVerificationFailed
+		);
 
-	let fraction_of_month = Perquintill::one();
-	let storage = fraction_of_month *
-		(|| -> Option<u128> {
-			(usage.stored_bytes as u128)
-				.checked_mul(pricing_params.unit_per_mb_stored)?
-				.checked_div(byte_unit::MEBIBYTE)
-		})()
-		.unwrap();
+		// committed batch sent under another index
+		assert_noop!(
+			DdcPayouts::send_charging_customers_batch(
+				RuntimeOrigin::signed(dac_account),
+				cluster_id,
+				era,
+				batch_index,
+				payers2.clone(),
+				customers_proofs[1].clone(),
+			),
+			Error::<Test>::BatchProofVerificationFailed
+		);
 
-	CustomerCharge {
-		transfer: pricing_params.unit_per_mb_streamed * (usage.transferred_bytes as u128) /
-			byte_unit::MEBIBYTE,
-		storage,
-		puts: pricing_params.unit_per_put_request * (usage.number_of_puts as u128),
-		gets: pricing_params.unit_per_get_request * (usage.number_of_gets as u128),
+		// proof of another batch
+		assert_noop!(
+			DdcPayouts::send_charging_customers_batch(
+				RuntimeOrigin::signed(dac_account),
+				cluster_id,
+				era,
+				batch_index,
+				payers1.clone(),
+				customers_proofs[1].clone(),
+			),
+			Error::<Test>::BatchProofVerificationFailed
+		);
+
+		assert_ok!(DdcPayouts::send_charging_customers_batch(
+			RuntimeOrigin::signed(dac_account),
+			cluster_id,
+			era,
+			batch_index,
+			payers1,
+			customers_proofs[0].clone(),
+		));
+		assert_ok!(DdcPayouts::send_charging_customers_batch(
+			RuntimeOrigin::signed(dac_account),
+			cluster_id,
+			era,
+			batch_index + 1,
+			payers2,
+			customers_proofs[1].clone(),
+		));
+	})
+}
+
+#[test]
+fn merkle_proofs_verify_against_root() {
+	for leaves_count in 1..=9u16 {
+		let batches: Vec<Vec<(u128, NodeUsage)>> = (0..leaves_count)
+			.map(|i| vec![(i.into(), NodeUsage { stored_bytes: i.into(), ..Default::default() })])
+			.collect();
+		let (root, proofs) = get_sequential_batches_commitment(&batches);
+
+		for (batch_index, batch) in batches.iter().enumerate() {
+			let leaf = merkle::batch_leaf(batch_index as BatchIndex, batch);
+			assert!(merkle::verify_proof(&root, &leaf, &proofs[batch_index]));
+
+			let moved_leaf = merkle::batch_leaf(batch_index as BatchIndex + 1, batch);
+			assert!(!merkle::verify_proof(&root, &moved_leaf, &proofs[batch_index]));
+		}
 	}
+
+	assert_eq!(merkle::merkle_root(&[]), H256::default());
 }
 
-fn calculate_charge_parts_for_hour(cluster_id: ClusterId, usage: CustomerUsage) -> CustomerCharge {
+fn node_key(node_provider_id: AccountId) -> NodePubKey {
+	let mut key = [0u8; 32];
+	key[..16].copy_from_slice(&node_provider_id.to_le_bytes());
+	NodePubKey::StoragePubKey(StorageNodePubKey::new(key))
+}
+
+fn get_batches_commitment<Item: Encode>(
+	batches: &[(BatchIndex, &Vec<Item>)],
+) -> (H256, Vec<Vec<H256>>) {
+	let leaves: Vec<H256> = batches
+		.iter()
+		.map(|(batch_index, batch)| merkle::batch_leaf(*batch_index, batch))
+		.collect();
+	let proofs = (0..leaves.len())
+		.map(|position| merkle::merkle_proof(&leaves, position))
+		.collect();
+
+	(merkle::merkle_root(&leaves), proofs)
+}
+
+fn get_sequential_batches_commitment<Item: Encode>(
+	batches: &[Vec<Item>],
+) -> (H256, Vec<Vec<H256>>) {
+	get_batches_commitment(
+		&batches
+			.iter()
+			.enumerate()
+			.map(|(batch_index, batch)| (batch_index as BatchIndex, batch))
+			.collect::<Vec<_>>(),
+	)
+}
+
+fn combined_usage(usage1: &CustomerUsage, usage2: &CustomerUsage) -> CustomerUsage {
+	CustomerUsage {
+		transferred_bytes: usage1.transferred_bytes + usage2.transferred_bytes,
+		stored_bytes: usage1.stored_bytes + usage2.stored_bytes,
+		number_of_puts: usage1.number_of_puts + usage2.number_of_puts,
+		number_of_gets: usage1.number_of_gets + usage2.number_of_gets,
+	}
+}
+
+fn customers_usage<'a>(
+	payers: impl IntoIterator<Item = &'a (AccountId, BucketId, CustomerUsage)>,
+) -> CustomerUsage {
+	payers
+		.into_iter()
+		.fold(CustomerUsage::default(), |total, (_, _, usage)| combined_usage(&total, usage))
+}
+
+fn calculate_storage_charge(
+	cluster_id: ClusterId,
+	stored_bytes: u64,
+	duration_seconds: u64,
+) -> u128 {
 	let pricing_params = get_pricing(&cluster_id);
 
-	let duration_seconds = 1.0 * 1.0 * 3600.0;
-	let seconds_in_month = 30.44 * 24.0 * 3600.0;
-	let fraction_of_hour =
-		Perquintill::from_rational(duration_seconds as u64, seconds_in_month as u64);
-	let storage = fraction_of_hour *
-		(|| -> Option<u128> {
-			(usage.stored_bytes as u128)
-				.checked_mul(pricing_params.unit_per_mb_stored)?
-				.checked_div(byte_unit::MEBIBYTE)
-		})()
-		.unwrap();
+	multiply_by_rational_with_rounding(
+		(stored_bytes as u128) * (duration_seconds as u128),
+		pricing_params.unit_per_mb_stored,
+		byte_unit::MEBIBYTE * (pricing_params.billing_period as u128),
+		Rounding::Down,
+	)
+	.unwrap()
+}
+
+fn calculate_charge_parts(
+	cluster_id: ClusterId,
+	usage: CustomerUsage,
+	duration_seconds: u64,
+) -> CustomerCharge {
+	let pricing_params = get_pricing(&cluster_id);
 
 	CustomerCharge {
 		transfer: pricing_params.unit_per_mb_streamed * (usage.transferred_bytes as u128) /
 			byte_unit::MEBIBYTE,
-		storage,
+		storage: calculate_storage_charge(cluster_id, usage.stored_bytes, duration_seconds),
 		puts: pricing_params.unit_per_put_request * (usage.number_of_puts as u128),
 		gets: pricing_params.unit_per_get_request * (usage.number_of_gets as u128),
 	}
 }
 
+fn calculate_charge_parts_for_day(cluster_id: ClusterId, usage: CustomerUsage) -> CustomerCharge {
+	calculate_charge_parts(cluster_id, usage, 24 * 3600)
+}
+
+fn calculate_charge_for_day(cluster_id: ClusterId, usage: CustomerUsage) -> u128 {
+	let charge = calculate_charge_parts_for_day(cluster_id, usage);
+	charge.transfer + charge.storage + charge.puts + charge.gets
+}
+
+fn calculate_charge_parts_for_month(cluster_id: ClusterId, usage: CustomerUsage) -> CustomerCharge {
+	calculate_charge_parts(cluster_id, usage, BILLING_PERIOD)
+}
+
+fn calculate_charge_parts_for_hour(cluster_id: ClusterId, usage: CustomerUsage) -> CustomerCharge {
+	calculate_charge_parts(cluster_id, usage, 3600)
+}
+
 fn calculate_charge_for_month(cluster_id: ClusterId, usage: CustomerUsage) -> u128 {
 	let charge = calculate_charge_parts_for_month(cluster_id, usage);
 	charge.transfer + charge.storage + charge.puts + charge.gets
@@ -429,29 +968,40 @@ fn send_charging_customers_batch_works() {
 			number_of_puts: 3456345,
 			number_of_gets: 242334563456423,
 		};
-		let payers1 = vec![(user2_debtor, usage2.clone()), (user4, usage4.clone())];
-		let payers2 = vec![(user1, usage1.clone())];
-		let payers3 = vec![(user3_debtor, usage3.clone())];
+		let payers1 = vec![
+			(user2_debtor, user2_debtor as BucketId, usage2.clone()),
+			(user4, user4 as BucketId, usage4.clone()),
+		];
+		let payers2 = vec![(user1, user1 as BucketId, usage1.clone())];
+		let payers3 = vec![(user3_debtor, user3_debtor as BucketId, usage3.clone())];
 		let start_date = NaiveDate::from_ymd_opt(2023, 4, 1).unwrap(); // April 1st
 		let time = NaiveTime::from_hms_opt(0, 0, 0).unwrap(); // Midnight
 		let start_era: i64 =
 			DateTime::<Utc>::from_naive_utc_and_offset(start_date.and_time(time), Utc).timestamp();
 		let end_era: i64 = start_era + (30.44 * 24.0 * 3600.0) as i64;
+		let (customers_merkle_root, customers_proofs) =
+			get_batches_commitment(&[(0, &payers1), (1, &payers2), (2, &payers3)]);
+		let nodes_merkle_root = H256::default();
 
-		assert_ok!(DdcPayouts::set_authorised_caller(RuntimeOrigin::root(), dac_account));
+		assert_ok!(DdcPayouts::set_validator_threshold(RuntimeOrigin::root(), 1));
 		assert_ok!(DdcPayouts::begin_billing_report(
 			RuntimeOrigin::signed(dac_account),
 			cluster_id,
 			era,
 			start_era,
 			end_era,
+			customers_merkle_root,
+			nodes_merkle_root,
+			max_batch_index,
+			0,
+			CustomerUsage::default(),
+			NodeUsage::default(),
 		));
 
 		assert_ok!(DdcPayouts::begin_charging_customers(
 			RuntimeOrigin::signed(dac_account),
 			cluster_id,
 			era,
-			max_batch_index,
 		));
 		assert_eq!(System::events().len(), 3);
 
@@ -462,6 +1012,7 @@ fn send_charging_customers_batch_works() {
 			era,
 			batch_index,
 			payers1,
+			customers_proofs[0].clone(),
 		));
 
 		let usage4_charge = calculate_charge_for_month(cluster_id, usage4.clone());
@@ -489,6 +1040,7 @@ fn send_charging_customers_batch_works() {
 				cluster_id,
 				era,
 				customer_id: user2_debtor,
+				bucket_id: user2_debtor as BucketId,
 				batch_index,
 				charged: USER2_BALANCE,
 				expected_to_charge: expected_charge2,
@@ -501,6 +1053,7 @@ fn send_charging_customers_batch_works() {
 				cluster_id,
 				era,
 				customer_id: user2_debtor,
+				bucket_id: user2_debtor as BucketId,
 				batch_index,
 				amount: debt,
 			}
@@ -511,6 +1064,7 @@ fn send_charging_customers_batch_works() {
 				cluster_id,
 				era,
 				customer_id: user4,
+				bucket_id: user4 as BucketId,
 				batch_index,
 				amount: usage4_charge,
 			}
@@ -528,6 +1082,7 @@ fn send_charging_customers_batch_works() {
 			era,
 			batch_index,
 			payers2,
+			customers_proofs[1].clone(),
 		));
 
 		System::assert_last_event(
@@ -536,6 +1091,7 @@ fn send_charging_customers_batch_works() {
 				era,
 				batch_index,
 				customer_id: user1,
+				bucket_id: user1 as BucketId,
 				amount: calculate_charge_for_month(cluster_id, usage1.clone()),
 			}
 			.into(),
@@ -575,6 +1131,7 @@ fn send_charging_customers_batch_works() {
 			era,
 			batch_index,
 			payers3,
+			customers_proofs[2].clone(),
 		));
 
 		let user3_charge = calculate_charge_for_month(cluster_id, usage3.clone());
@@ -610,6 +1167,7 @@ fn send_charging_customers_batch_works() {
 				cluster_id,
 				era,
 				customer_id: user3_debtor,
+				bucket_id: user3_debtor as BucketId,
 				batch_index,
 				amount: user3_debt,
 			}
@@ -622,6 +1180,7 @@ fn send_charging_customers_batch_works() {
 				era,
 				batch_index,
 				customer_id: user3_debtor,
+				bucket_id: user3_debtor as BucketId,
 				charged: PARTIAL_CHARGE,
 				expected_to_charge: user3_charge,
 			}
@@ -631,223 +1190,269 @@ fn send_charging_customers_batch_works() {
 }
 
 #[test]
-fn end_charging_customers_works_small_usage_1_hour() {
+fn send_charging_customers_batch_applies_pricing_tiers_and_discount() {
 	ExtBuilder.build_and_execute(|| {
 		System::set_block_number(1);
 
 		let dac_account = 123u128;
-		let user6 = 6u128;
-		let user7 = 7u128;
-		let cluster_id = HIGH_FEES_CLUSTER_ID;
+		let user1 = 1u128;
+		let cluster_id = TIERED_CLUSTER_ID;
 		let era = 100;
 		let max_batch_index = 0;
 		let batch_index = 0;
-		let usage6 = CustomerUsage {
-			transferred_bytes: 0,
-			stored_bytes: 474_957,
-			number_of_puts: 0,
-			number_of_gets: 0,
-		};
-		let usage7 = CustomerUsage {
-			transferred_bytes: 474_957,
-			stored_bytes: 0,
-			number_of_puts: 0,
-			number_of_gets: 0,
+		let usage1 = CustomerUsage {
+			transferred_bytes: 150 * byte_unit::MEBIBYTE as u64,
+			stored_bytes: 200 * byte_unit::MEBIBYTE as u64,
+			number_of_puts: 15,
+			number_of_gets: 150,
 		};
-		let payers1 = vec![(user6, usage6.clone()), (user7, usage7.clone())];
-		let start_date = NaiveDate::from_ymd_opt(2023, 4, 1).unwrap(); // April 1st
-		let time = NaiveTime::from_hms_opt(0, 0, 0).unwrap(); // Midnight
-		let start_era: i64 =
-			DateTime::<Utc>::from_naive_utc_and_offset(start_date.and_time(time), Utc).timestamp();
-		let end_era: i64 = start_era + (1.0 * 1.0 * 3600.0) as i64; // 1 hour
+		let payers = vec![(user1, user1 as BucketId, usage1)];
+		let start_era: i64 = 1_000_000_000;
+		let end_era: i64 = start_era + BILLING_PERIOD as i64;
+		let (customers_merkle_root, customers_proofs) =
+			get_sequential_batches_commitment(&[payers.clone()]);
+
+		CustomerDiscount::set(Perquintill::from_percent(20));
 
-		assert_ok!(DdcPayouts::set_authorised_caller(RuntimeOrigin::root(), dac_account));
+		assert_ok!(DdcPayouts::set_validator_threshold(RuntimeOrigin::root(), 1));
 		assert_ok!(DdcPayouts::begin_billing_report(
 			RuntimeOrigin::signed(dac_account),
 			cluster_id,
 			era,
 			start_era,
 			end_era,
+			customers_merkle_root,
+			H256::default(),
+			max_batch_index,
+			0,
+			CustomerUsage::default(),
+			NodeUsage::default(),
 		));
-
 		assert_ok!(DdcPayouts::begin_charging_customers(
 			RuntimeOrigin::signed(dac_account),
 			cluster_id,
 			era,
-			max_batch_index,
 		));
-		assert_eq!(System::events().len(), 3);
-
-		// batch 1
 		assert_ok!(DdcPayouts::send_charging_customers_batch(
 			RuntimeOrigin::signed(dac_account),
 			cluster_id,
 			era,
 			batch_index,
-			payers1,
+			payers,
+			customers_proofs[0].clone(),
 		));
 
-		let report_before = DdcPayouts::active_billing_reports(cluster_id, era).unwrap();
-		let usage6_charge = calculate_charge_for_hour(cluster_id, usage6.clone());
-		let usage7_charge = calculate_charge_for_hour(cluster_id, usage7.clone());
-		let charge6 = calculate_charge_parts_for_hour(cluster_id, usage6);
-		let charge7 = calculate_charge_parts_for_hour(cluster_id, usage7);
-		assert_eq!(charge7.puts + charge6.puts, report_before.total_customer_charge.puts);
-		assert_eq!(charge7.gets + charge6.gets, report_before.total_customer_charge.gets);
-		assert_eq!(charge7.storage + charge6.storage, report_before.total_customer_charge.storage);
-		assert_eq!(
-			charge7.transfer + charge6.transfer,
-			report_before.total_customer_charge.transfer
-		);
-
-		System::assert_has_event(
-			Event::Charged {
-				cluster_id,
-				era,
-				customer_id: user6,
-				batch_index,
-				amount: usage6_charge,
-			}
-			.into(),
-		);
-
-		System::assert_has_event(
+		// transfer: 100 MB * 2_000_000 + 50 MB * 1_000_000
+		// storage: 100 MB * 3_000_000 + 100 MB * 1_500_000 for the whole billing period
+		// puts: 10 * 4_000_000 + 5 * 2_000_000
+		// gets: 10 * 5_000_000 + 90 * 2_500_000 + 50 * 1_000_000
+		// and 20% discount for every part
+		let expected_charge = CustomerCharge {
+			transfer: 200_000_000,
+			storage: 360_000_000,
+			puts: 40_000_000,
+			gets: 260_000_000,
+		};
+		let report = DdcPayouts::active_billing_reports(cluster_id, era).unwrap();
+		assert_eq!(report.total_customer_charge, expected_charge);
+		System::assert_last_event(
 			Event::Charged {
 				cluster_id,
 				era,
-				customer_id: user7,
 				batch_index,
-				amount: usage7_charge,
+				customer_id: user1,
+				bucket_id: user1 as BucketId,
+				amount: 860_000_000,
 			}
 			.into(),
 		);
+	})
+}
 
-		let mut balance = Balances::free_balance(DdcPayouts::account_id());
-		let charge = usage7_charge + usage6_charge;
-		assert_eq!(balance - Balances::minimum_balance(), charge);
-
-		balance = Balances::free_balance(TREASURY_ACCOUNT_ID);
-		assert_eq!(balance, 0);
-
-		balance = Balances::free_balance(RESERVE_ACCOUNT_ID);
-		assert_eq!(balance, 0);
-
-		balance = Balances::free_balance(VALIDATOR1_ACCOUNT_ID);
-		assert_eq!(balance, 0);
-
-		balance = Balances::free_balance(VALIDATOR2_ACCOUNT_ID);
-		assert_eq!(balance, 0);
+#[test]
+fn send_charging_customers_batch_applies_pricing_tiers_to_customer_usage() {
+	ExtBuilder.build_and_execute(|| {
+		System::set_block_number(1);
 
-		balance = Balances::free_balance(VALIDATOR3_ACCOUNT_ID);
-		assert_eq!(balance, 0);
+		let dac_account = 123u128;
+		let user1 = 1u128;
+		let bucket1: BucketId = 1;
+		let bucket2: BucketId = 1 + BUCKETS_OFFSET;
+		let cluster_id = TIERED_CLUSTER_ID;
+		let era = 100;
+		let max_batch_index = 1;
+		let usage1 = CustomerUsage {
+			transferred_bytes: 75 * byte_unit::MEBIBYTE as u64,
+			stored_bytes: 100 * byte_unit::MEBIBYTE as u64,
+			number_of_puts: 8,
+			number_of_gets: 75,
+		};
+		let usage2 = CustomerUsage {
+			transferred_bytes: 75 * byte_unit::MEBIBYTE as u64,
+			stored_bytes: 100 * byte_unit::MEBIBYTE as u64,
+			number_of_puts: 7,
+			number_of_gets: 75,
+		};
+		// buckets of the customer are charged in different batches
+		let batches = [vec![(user1, bucket1, usage1)], vec![(user1, bucket2, usage2)]];
+		let start_era: i64 = 1_000_000_000;
+		let end_era: i64 = start_era + BILLING_PERIOD as i64;
+		let (customers_merkle_root, customers_proofs) = get_sequential_batches_commitment(&batches);
 
-		assert_ok!(DdcPayouts::end_charging_customers(
+		assert_ok!(DdcPayouts::set_validator_threshold(RuntimeOrigin::root(), 1));
+		assert_ok!(DdcPayouts::begin_billing_report(
 			RuntimeOrigin::signed(dac_account),
 			cluster_id,
 			era,
+			start_era,
+			end_era,
+			customers_merkle_root,
+			H256::default(),
+			max_batch_index,
+			0,
+			CustomerUsage::default(),
+			NodeUsage::default(),
 		));
+		assert_ok!(DdcPayouts::begin_charging_customers(
+			RuntimeOrigin::signed(dac_account),
+			cluster_id,
+			era,
+		));
+		for (batch_index, batch) in batches.into_iter().enumerate() {
+			assert_ok!(DdcPayouts::send_charging_customers_batch(
+				RuntimeOrigin::signed(dac_account),
+				cluster_id,
+				era,
+				batch_index as BatchIndex,
+				batch,
+				customers_proofs[batch_index].clone(),
+			));
+		}
 
-		System::assert_has_event(Event::ChargingFinished { cluster_id, era }.into());
-		let report_after = DdcPayouts::active_billing_reports(cluster_id, era).unwrap();
-		assert_eq!(report_after.state, State::CustomersChargedWithFees);
-
-		let fees = get_fees(&cluster_id);
-		let total_left_from_one =
-			(fees.treasury_share + fees.validators_share + fees.cluster_reserve_share)
-				.left_from_one();
-
-		assert_eq!(
-			total_left_from_one,
-			Perquintill::one() -
-				(PRICING_FEES_HIGH.treasury_share +
-					PRICING_FEES_HIGH.validators_share +
-					PRICING_FEES_HIGH.cluster_reserve_share)
-		);
-		assert_eq!(fees.treasury_share, PRICING_FEES_HIGH.treasury_share);
-		assert_eq!(fees.validators_share, PRICING_FEES_HIGH.validators_share);
-		assert_eq!(fees.cluster_reserve_share, PRICING_FEES_HIGH.cluster_reserve_share);
-
-		balance = Balances::free_balance(TREASURY_ACCOUNT_ID);
-		assert_eq!(balance, get_fees(&cluster_id).treasury_share * charge);
-		assert!(balance > 0);
-
-		balance = Balances::free_balance(RESERVE_ACCOUNT_ID);
-		assert_eq!(balance, get_fees(&cluster_id).cluster_reserve_share * charge);
-		assert!(balance > 0);
-
-		balance = Balances::free_balance(VALIDATOR1_ACCOUNT_ID);
-		let mut ratio = Perquintill::from_rational(
-			VALIDATOR1_SCORE,
-			VALIDATOR1_SCORE + VALIDATOR2_SCORE + VALIDATOR3_SCORE,
-		);
-		assert_eq!(balance, get_fees(&cluster_id).validators_share * ratio * charge);
-		assert!(balance > 0);
-
-		balance = Balances::free_balance(VALIDATOR2_ACCOUNT_ID);
-		ratio = Perquintill::from_rational(
-			VALIDATOR2_SCORE,
-			VALIDATOR1_SCORE + VALIDATOR2_SCORE + VALIDATOR3_SCORE,
-		);
-		assert_eq!(balance, get_fees(&cluster_id).validators_share * ratio * charge);
-		assert!(balance > 0);
-
-		balance = Balances::free_balance(VALIDATOR3_ACCOUNT_ID);
-		ratio = Perquintill::from_rational(
-			VALIDATOR3_SCORE,
-			VALIDATOR1_SCORE + VALIDATOR2_SCORE + VALIDATOR3_SCORE,
-		);
-		assert_eq!(balance, get_fees(&cluster_id).validators_share * ratio * charge);
-		assert!(balance > 0);
+		// first bucket, below the thresholds except for gets:
+		// 75 MB * 2_000_000 + 100 MB * 3_000_000 + 8 * 4_000_000 + 10 * 5_000_000 + 65 * 2_500_000
+		// second bucket, priced on top of the first one:
+		// 25 MB * 2_000_000 + 50 MB * 1_000_000 + 100 MB * 1_500_000 + 2 * 4_000_000 + 5 *
+		// 2_000_000 + 25 * 2_500_000 + 50 * 1_000_000
+		for (batch_index, bucket_id, amount) in
+			[(0, bucket1, 694_500_000), (1, bucket2, 380_500_000)]
+		{
+			System::assert_has_event(
+				Event::Charged {
+					cluster_id,
+					era,
+					batch_index,
+					customer_id: user1,
+					bucket_id,
+					amount,
+				}
+				.into(),
+			);
+		}
 
+		// same as the charge for the total usage of the customer in a single bucket
+		let report = DdcPayouts::active_billing_reports(cluster_id, era).unwrap();
 		assert_eq!(
-			report_after.total_customer_charge.transfer,
-			total_left_from_one * report_before.total_customer_charge.transfer
-		);
-		assert!(report_after.total_customer_charge.transfer > 0);
-		assert_eq!(
-			report_after.total_customer_charge.storage,
-			total_left_from_one * report_before.total_customer_charge.storage
-		);
-		assert!(report_after.total_customer_charge.storage > 0);
-		assert_eq!(
-			report_after.total_customer_charge.puts,
-			total_left_from_one * report_before.total_customer_charge.puts
-		);
-		assert_eq!(
-			report_after.total_customer_charge.gets,
-			total_left_from_one * report_before.total_customer_charge.gets
+			report.total_customer_charge,
+			CustomerCharge {
+				transfer: 250_000_000,
+				storage: 450_000_000,
+				puts: 50_000_000,
+				gets: 325_000_000,
+			}
 		);
 	})
 }
 
 #[test]
-fn send_charging_customers_batch_works_for_day() {
+fn billing_report_info_reports_missing_batches() {
 	ExtBuilder.build_and_execute(|| {
 		System::set_block_number(1);
 
 		let dac_account = 123u128;
-		let user1 = 1u128;
-		let user2_debtor = 2u128;
-		let user3_debtor = 3u128;
-		let user4 = 4u128;
 		let cluster_id = ClusterId::from([12; 20]);
 		let era = 100;
-		let max_batch_index = 3;
-		let mut batch_index = 0;
-		let usage1 = CustomerUsage {
-			// should pass without debt
-			transferred_bytes: 23452345,
-			stored_bytes: 3345234523,
-			number_of_puts: 4456456345234523,
-			number_of_gets: 523423,
-		};
-		let usage2 = CustomerUsage {
-			// should fail as not enough balance
+		let max_batch_index = 2;
+		let usage = CustomerUsage {
 			transferred_bytes: 1,
 			stored_bytes: 2,
 			number_of_puts: 3,
 			number_of_gets: 4,
 		};
+		let batches = [
+			vec![(1u128, 1, usage.clone())],
+			vec![(4u128, 4, usage.clone())],
+			vec![(5u128, 5, usage.clone())],
+		];
+		let start_era: i64 = 1_000_000_000;
+		let end_era: i64 = start_era + BILLING_PERIOD as i64;
+		let (customers_merkle_root, customers_proofs) = get_sequential_batches_commitment(&batches);
+
+		assert_eq!(DdcPayouts::billing_report_info(cluster_id, era), None);
+
+		assert_ok!(DdcPayouts::set_validator_threshold(RuntimeOrigin::root(), 1));
+		assert_ok!(DdcPayouts::begin_billing_report(
+			RuntimeOrigin::signed(dac_account),
+			cluster_id,
+			era,
+			start_era,
+			end_era,
+			customers_merkle_root,
+			H256::default(),
+			max_batch_index,
+			0,
+			CustomerUsage::default(),
+			NodeUsage::default(),
+		));
+
+		let report_info = DdcPayouts::billing_report_info(cluster_id, era).unwrap();
+		assert_eq!(report_info.state, State::Initialized);
+		assert!(report_info.charging_missing_batches.is_empty());
+
+		assert_ok!(DdcPayouts::begin_charging_customers(
+			RuntimeOrigin::signed(dac_account),
+			cluster_id,
+			era,
+		));
+		assert_ok!(DdcPayouts::send_charging_customers_batch(
+			RuntimeOrigin::signed(dac_account),
+			cluster_id,
+			era,
+			1,
+			batches[1].clone(),
+			customers_proofs[1].clone(),
+		));
+
+		let report_info = DdcPayouts::billing_report_info(cluster_id, era).unwrap();
+		assert_eq!(report_info.state, State::ChargingCustomers);
+		assert_eq!(report_info.charging_max_batch_index, max_batch_index);
+		assert_eq!(report_info.charging_missing_batches, vec![0, 2]);
+		assert!(report_info.rewarding_missing_batches.is_empty());
+		assert_eq!(
+			report_info.total_customer_charge,
+			calculate_charge_parts_for_month(cluster_id, usage)
+		);
+		assert_eq!(DdcPayouts::billing_reports_info(cluster_id), vec![(era, report_info)]);
+
+		DebtorCustomers::<Test>::insert(cluster_id, 4u128, 10);
+		assert_eq!(DdcPayouts::customers_debts(cluster_id), vec![(4u128, 10)]);
+	})
+}
+
+#[test]
+fn send_charging_customers_batch_collects_debt_first() {
+	ExtBuilder.build_and_execute(|| {
+		System::set_block_number(1);
+
+		let dac_account = 123u128;
+		let user3_debtor = 3u128;
+		let user4_debtor = 4u128;
+		let cluster_id = ClusterId::from([12; 20]);
+		let era = 100;
+		let max_batch_index = 1;
+		let mut batch_index = 0;
+		let user3_debt = 10_000_000;
+		let user4_debt = 5_000_000;
 		let usage3 = CustomerUsage {
 			// should pass but with debt (partial charge)
 			transferred_bytes: 1,
@@ -862,80 +1467,60 @@ fn send_charging_customers_batch_works_for_day() {
 			number_of_puts: 3456345,
 			number_of_gets: 242334563456423,
 		};
-		let payers1 = vec![(user2_debtor, usage2.clone()), (user4, usage4.clone())];
-		let payers2 = vec![(user1, usage1.clone())];
-		let payers3 = vec![(user3_debtor, usage3.clone())];
+		let payers1 = vec![(user4_debtor, user4_debtor as BucketId, usage4.clone())];
+		let payers2 = vec![(user3_debtor, user3_debtor as BucketId, usage3.clone())];
 		let start_date = NaiveDate::from_ymd_opt(2023, 4, 1).unwrap(); // April 1st
 		let time = NaiveTime::from_hms_opt(0, 0, 0).unwrap(); // Midnight
 		let start_era: i64 =
 			DateTime::<Utc>::from_naive_utc_and_offset(start_date.and_time(time), Utc).timestamp();
-		let end_era: i64 = start_era + (1.0 * 24.0 * 3600.0) as i64;
+		let end_era: i64 = start_era + (30.44 * 24.0 * 3600.0) as i64;
+		let (customers_merkle_root, customers_proofs) =
+			get_sequential_batches_commitment(&[payers1.clone(), payers2.clone()]);
+		let nodes_merkle_root = H256::default();
 
-		assert_ok!(DdcPayouts::set_authorised_caller(RuntimeOrigin::root(), dac_account));
+		// debts from previous eras
+		DebtorCustomers::<Test>::insert(cluster_id, user3_debtor, user3_debt);
+		DebtorCustomers::<Test>::insert(cluster_id, user4_debtor, user4_debt);
+
+		assert_ok!(DdcPayouts::set_validator_threshold(RuntimeOrigin::root(), 1));
 		assert_ok!(DdcPayouts::begin_billing_report(
 			RuntimeOrigin::signed(dac_account),
 			cluster_id,
 			era,
 			start_era,
 			end_era,
+			customers_merkle_root,
+			nodes_merkle_root,
+			max_batch_index,
+			0,
+			CustomerUsage::default(),
+			NodeUsage::default(),
 		));
-
 		assert_ok!(DdcPayouts::begin_charging_customers(
 			RuntimeOrigin::signed(dac_account),
 			cluster_id,
 			era,
-			max_batch_index,
 		));
-		assert_eq!(System::events().len(), 3);
 
-		// batch 1
+		// batch 1: debt is fully repaid before the current usage is charged
 		assert_ok!(DdcPayouts::send_charging_customers_batch(
 			RuntimeOrigin::signed(dac_account),
 			cluster_id,
 			era,
 			batch_index,
 			payers1,
+			customers_proofs[0].clone(),
 		));
 
-		let usage4_charge = calculate_charge_for_day(cluster_id, usage4.clone());
-		let user2_debt = DdcPayouts::debtor_customers(cluster_id, user2_debtor).unwrap();
-		let expected_charge2 = calculate_charge_for_day(cluster_id, usage2.clone());
-		let mut debt = expected_charge2 - USER2_BALANCE;
-		assert_eq!(user2_debt, debt);
-
-		let ratio = Perquintill::from_rational(USER2_BALANCE, expected_charge2);
-		let mut charge2 = calculate_charge_parts_for_day(cluster_id, usage2);
-		charge2.storage = ratio * charge2.storage;
-		charge2.transfer = ratio * charge2.transfer;
-		charge2.gets = ratio * charge2.gets;
-		charge2.puts = ratio * charge2.puts;
-
-		let mut report = DdcPayouts::active_billing_reports(cluster_id, era).unwrap();
-		let charge4 = calculate_charge_parts_for_day(cluster_id, usage4);
-		assert_eq!(charge2.puts + charge4.puts, report.total_customer_charge.puts);
-		assert_eq!(charge2.gets + charge4.gets, report.total_customer_charge.gets);
-		assert_eq!(charge2.storage + charge4.storage, report.total_customer_charge.storage);
-		assert_eq!(charge2.transfer + charge4.transfer, report.total_customer_charge.transfer);
-
+		let usage4_charge = calculate_charge_for_month(cluster_id, usage4);
+		assert_eq!(DdcPayouts::debtor_customers(cluster_id, user4_debtor), None);
 		System::assert_has_event(
-			Event::ChargeFailed {
-				cluster_id,
-				era,
-				customer_id: user2_debtor,
-				batch_index,
-				charged: USER2_BALANCE,
-				expected_to_charge: expected_charge2,
-			}
-			.into(),
-		);
-
-		System::assert_has_event(
-			Event::Indebted {
+			Event::DebtRepaid {
 				cluster_id,
 				era,
-				customer_id: user2_debtor,
 				batch_index,
-				amount: debt,
+				customer_id: user4_debtor,
+				repaid: user4_debt,
 			}
 			.into(),
 		);
@@ -943,17 +1528,23 @@ fn send_charging_customers_batch_works_for_day() {
 			Event::Charged {
 				cluster_id,
 				era,
-				customer_id: user4,
 				batch_index,
+				customer_id: user4_debtor,
+				bucket_id: user4_debtor as BucketId,
 				amount: usage4_charge,
 			}
 			.into(),
 		);
 
-		assert_eq!(System::events().len(), 5 + 3 + 1); // 1 for Currency::transfer
+		// repaid debt is rewarded to providers along with the current charge
+		let report = DdcPayouts::active_billing_reports(cluster_id, era).unwrap();
+		let total_charge = report.total_customer_charge;
+		assert_eq!(
+			total_charge.transfer + total_charge.storage + total_charge.puts + total_charge.gets,
+			usage4_charge + user4_debt
+		);
 
-		// batch 2
-		let mut before_total_customer_charge = report.total_customer_charge;
+		// batch 2: debt is partially repaid and the current usage adds up to the rest
 		batch_index += 1;
 		assert_ok!(DdcPayouts::send_charging_customers_batch(
 			RuntimeOrigin::signed(dac_account),
@@ -961,416 +1552,339 @@ fn send_charging_customers_batch_works_for_day() {
 			era,
 			batch_index,
 			payers2,
+			customers_proofs[1].clone(),
 		));
 
-		System::assert_last_event(
-			Event::Charged {
+		let remaining_debt = user3_debt - PARTIAL_CHARGE;
+		let usage3_charge = calculate_charge_for_month(cluster_id, usage3);
+		System::assert_has_event(
+			Event::DebtPartiallyRepaid {
 				cluster_id,
 				era,
 				batch_index,
-				customer_id: user1,
-				amount: calculate_charge_for_day(cluster_id, usage1.clone()),
+				customer_id: user3_debtor,
+				repaid: PARTIAL_CHARGE,
+				remaining_debt,
 			}
 			.into(),
 		);
-
-		report = DdcPayouts::active_billing_reports(cluster_id, era).unwrap();
-		let charge1 = calculate_charge_parts_for_day(cluster_id, usage1);
-		assert_eq!(
-			charge1.puts + before_total_customer_charge.puts,
-			report.total_customer_charge.puts
-		);
-		assert_eq!(
-			charge1.gets + before_total_customer_charge.gets,
-			report.total_customer_charge.gets
-		);
-		assert_eq!(
-			charge1.storage + before_total_customer_charge.storage,
-			report.total_customer_charge.storage
+		System::assert_has_event(
+			Event::Indebted {
+				cluster_id,
+				era,
+				batch_index,
+				customer_id: user3_debtor,
+				bucket_id: user3_debtor as BucketId,
+				amount: usage3_charge - PARTIAL_CHARGE,
+			}
+			.into(),
 		);
 		assert_eq!(
-			charge1.transfer + before_total_customer_charge.transfer,
-			report.total_customer_charge.transfer
+			DdcPayouts::debtor_customers(cluster_id, user3_debtor),
+			Some(remaining_debt + usage3_charge - PARTIAL_CHARGE)
 		);
+	})
+}
 
-		assert_eq!(report.state, State::ChargingCustomers);
-		let user1_debt = DdcPayouts::debtor_customers(cluster_id, user1);
-		assert_eq!(user1_debt, None);
+#[test]
+fn send_charging_customers_batch_reports_failed_debt_collection() {
+	ExtBuilder.build_and_execute(|| {
+		System::set_block_number(1);
 
-		let balance_before = Balances::free_balance(DdcPayouts::account_id());
+		let dac_account = 123u128;
+		let user5_debtor = 5u128;
+		let cluster_id = ClusterId::from([12; 20]);
+		let era = 100;
+		let max_batch_index = 0;
+		let batch_index = 0;
+		// too low for the customer charger to accept
+		let user5_debt = 1_000;
+		let usage5 = CustomerUsage {
+			transferred_bytes: 467457,
+			stored_bytes: 45674567456,
+			number_of_puts: 3456345,
+			number_of_gets: 242334563456423,
+		};
+		let payers = vec![(user5_debtor, user5_debtor as BucketId, usage5.clone())];
+		let start_date = NaiveDate::from_ymd_opt(2023, 4, 1).unwrap(); // April 1st
+		let time = NaiveTime::from_hms_opt(0, 0, 0).unwrap(); // Midnight
+		let start_era: i64 =
+			DateTime::<Utc>::from_naive_utc_and_offset(start_date.and_time(time), Utc).timestamp();
+		let end_era: i64 = start_era + (30.44 * 24.0 * 3600.0) as i64;
+		let (customers_merkle_root, customers_proofs) =
+			get_batches_commitment(&[(batch_index, &payers)]);
 
-		// batch 3
-		batch_index += 1;
-		before_total_customer_charge = report.total_customer_charge.clone();
+		DebtorCustomers::<Test>::insert(cluster_id, user5_debtor, user5_debt);
+
+		assert_ok!(DdcPayouts::set_validator_threshold(RuntimeOrigin::root(), 1));
+		assert_ok!(DdcPayouts::begin_billing_report(
+			RuntimeOrigin::signed(dac_account),
+			cluster_id,
+			era,
+			start_era,
+			end_era,
+			customers_merkle_root,
+			H256::default(),
+			max_batch_index,
+			0,
+			CustomerUsage::default(),
+			NodeUsage::default(),
+		));
+		assert_ok!(DdcPayouts::begin_charging_customers(
+			RuntimeOrigin::signed(dac_account),
+			cluster_id,
+			era,
+		));
 		assert_ok!(DdcPayouts::send_charging_customers_batch(
 			RuntimeOrigin::signed(dac_account),
 			cluster_id,
 			era,
 			batch_index,
-			payers3,
+			payers,
+			customers_proofs[0].clone(),
 		));
 
-		let user3_charge = calculate_charge_for_day(cluster_id, usage3.clone());
-		let charge3 = calculate_charge_parts_for_day(cluster_id, usage3);
-		let ratio = Perquintill::from_rational(PARTIAL_CHARGE, user3_charge);
-		report = DdcPayouts::active_billing_reports(cluster_id, era).unwrap();
-		assert_eq!(
-			ratio * charge3.puts + before_total_customer_charge.puts,
-			report.total_customer_charge.puts
-		);
-		assert_eq!(
-			ratio * charge3.gets + before_total_customer_charge.gets,
-			report.total_customer_charge.gets
-		);
-		assert_eq!(
-			ratio * charge3.storage + before_total_customer_charge.storage,
-			report.total_customer_charge.storage
-		);
-		assert_eq!(
-			ratio * charge3.transfer + before_total_customer_charge.transfer,
-			report.total_customer_charge.transfer
-		);
-
-		let balance = Balances::free_balance(DdcPayouts::account_id());
-		assert_eq!(balance, balance_before + PARTIAL_CHARGE);
-
-		let user3_debt = DdcPayouts::debtor_customers(cluster_id, user3_debtor).unwrap();
-		debt = user3_charge - PARTIAL_CHARGE;
-		assert_eq!(user3_debt, debt);
-
+		// the debt stays outstanding and the current usage is charged regardless
 		System::assert_has_event(
-			Event::Indebted {
+			Event::ChargeError {
 				cluster_id,
 				era,
-				customer_id: user3_debtor,
 				batch_index,
-				amount: user3_debt,
+				customer_id: user5_debtor,
+				bucket_id: user5_debtor as BucketId,
+				amount: user5_debt,
+				error: BadOrigin.into(),
 			}
 			.into(),
 		);
-
 		System::assert_last_event(
-			Event::ChargeFailed {
+			Event::Charged {
 				cluster_id,
 				era,
 				batch_index,
-				customer_id: user3_debtor,
-				charged: PARTIAL_CHARGE,
-				expected_to_charge: user3_charge,
+				customer_id: user5_debtor,
+				bucket_id: user5_debtor as BucketId,
+				amount: calculate_charge_for_month(cluster_id, usage5),
 			}
 			.into(),
 		);
+		assert_eq!(DdcPayouts::debtor_customers(cluster_id, user5_debtor), Some(user5_debt));
 	})
 }
 
 #[test]
-fn send_charging_customers_batch_works_for_day_free_storage() {
+fn send_charging_customers_batch_funds_owed_rewards_with_repaid_debt() {
 	ExtBuilder.build_and_execute(|| {
 		System::set_block_number(1);
 
 		let dac_account = 123u128;
-		let user1 = 1u128;
-		let user2_debtor = 2u128;
-		let user3_debtor = 3u128;
-		let user4 = 4u128;
-		let cluster_id = STORAGE_ZERO_CLUSTER_ID;
+		let user5_debtor = 5u128;
+		let cluster_id = ClusterId::from([12; 20]);
 		let era = 100;
-		let max_batch_index = 3;
-		let mut batch_index = 0;
-		let usage1 = CustomerUsage {
-			// should pass without debt
-			transferred_bytes: 23452345,
-			stored_bytes: 3345234523,
-			number_of_puts: 4456456345234523,
-			number_of_gets: 523423,
-		};
-		let usage2 = CustomerUsage {
-			// should fail as not enough balance
-			transferred_bytes: 1,
-			stored_bytes: 2,
-			number_of_puts: 3,
-			number_of_gets: 4,
-		};
-		let usage3 = CustomerUsage {
-			// should pass but with debt (partial charge)
-			transferred_bytes: 1,
-			stored_bytes: 2,
-			number_of_puts: 3,
-			number_of_gets: 4,
-		};
-		let usage4 = CustomerUsage {
-			// should pass without debt
+		let max_batch_index = 0;
+		let batch_index = 0;
+		let user5_debt = 10_000_000;
+		let owed_rewards = 4_000_000;
+		let usage5 = CustomerUsage {
 			transferred_bytes: 467457,
 			stored_bytes: 45674567456,
 			number_of_puts: 3456345,
 			number_of_gets: 242334563456423,
 		};
-		let payers1 = vec![(user2_debtor, usage2.clone()), (user4, usage4.clone())];
-		let payers2 = vec![(user1, usage1.clone())];
-		let payers3 = vec![(user3_debtor, usage3.clone())];
+		let payers = vec![(user5_debtor, user5_debtor as BucketId, usage5.clone())];
 		let start_date = NaiveDate::from_ymd_opt(2023, 4, 1).unwrap(); // April 1st
 		let time = NaiveTime::from_hms_opt(0, 0, 0).unwrap(); // Midnight
 		let start_era: i64 =
 			DateTime::<Utc>::from_naive_utc_and_offset(start_date.and_time(time), Utc).timestamp();
-		let end_era: i64 = start_era + (1.0 * 24.0 * 3600.0) as i64;
+		let end_era: i64 = start_era + (30.44 * 24.0 * 3600.0) as i64;
+		let (customers_merkle_root, customers_proofs) =
+			get_batches_commitment(&[(batch_index, &payers)]);
 
-		assert_ok!(DdcPayouts::set_authorised_caller(RuntimeOrigin::root(), dac_account));
+		DebtorCustomers::<Test>::insert(cluster_id, user5_debtor, user5_debt);
+		TotalOwedRewards::<Test>::insert(cluster_id, owed_rewards);
+
+		assert_ok!(DdcPayouts::set_validator_threshold(RuntimeOrigin::root(), 1));
 		assert_ok!(DdcPayouts::begin_billing_report(
 			RuntimeOrigin::signed(dac_account),
 			cluster_id,
 			era,
 			start_era,
 			end_era,
+			customers_merkle_root,
+			H256::default(),
+			max_batch_index,
+			0,
+			CustomerUsage::default(),
+			NodeUsage::default(),
 		));
-
 		assert_ok!(DdcPayouts::begin_charging_customers(
 			RuntimeOrigin::signed(dac_account),
 			cluster_id,
 			era,
-			max_batch_index,
 		));
-		assert_eq!(System::events().len(), 3);
-
-		// batch 1
-		assert_ok!(DdcPayouts::send_charging_customers_batch(
-			RuntimeOrigin::signed(dac_account),
-			cluster_id,
-			era,
-			batch_index,
-			payers1,
-		));
-
-		let usage4_charge = calculate_charge_for_day(cluster_id, usage4.clone());
-		let user2_debt = DdcPayouts::debtor_customers(cluster_id, user2_debtor).unwrap();
-		let expected_charge2 = calculate_charge_for_day(cluster_id, usage2.clone());
-		let mut debt = expected_charge2 - USER2_BALANCE;
-		assert_eq!(user2_debt, debt);
-
-		let ratio = Perquintill::from_rational(USER2_BALANCE, expected_charge2);
-		let mut charge2 = calculate_charge_parts_for_day(cluster_id, usage2);
-		charge2.storage = ratio * charge2.storage;
-		charge2.transfer = ratio * charge2.transfer;
-		charge2.gets = ratio * charge2.gets;
-		charge2.puts = ratio * charge2.puts;
-
-		let mut report = DdcPayouts::active_billing_reports(cluster_id, era).unwrap();
-		let charge4 = calculate_charge_parts_for_day(cluster_id, usage4);
-		assert_eq!(charge2.puts + charge4.puts, report.total_customer_charge.puts);
-		assert_eq!(charge2.gets + charge4.gets, report.total_customer_charge.gets);
-		assert_eq!(charge2.storage + charge4.storage, report.total_customer_charge.storage);
-		assert_eq!(charge2.transfer + charge4.transfer, report.total_customer_charge.transfer);
-
-		System::assert_has_event(
-			Event::ChargeFailed {
-				cluster_id,
-				era,
-				customer_id: user2_debtor,
-				batch_index,
-				charged: USER2_BALANCE,
-				expected_to_charge: expected_charge2,
-			}
-			.into(),
-		);
-
-		System::assert_has_event(
-			Event::Indebted {
-				cluster_id,
-				era,
-				customer_id: user2_debtor,
-				batch_index,
-				amount: debt,
-			}
-			.into(),
-		);
-		System::assert_last_event(
-			Event::Charged {
-				cluster_id,
-				era,
-				customer_id: user4,
-				batch_index,
-				amount: usage4_charge,
-			}
-			.into(),
-		);
-
-		assert_eq!(System::events().len(), 5 + 3 + 1); // 1 for Currency::transfer
-
-		// batch 2
-		let mut before_total_customer_charge = report.total_customer_charge;
-		batch_index += 1;
 		assert_ok!(DdcPayouts::send_charging_customers_batch(
 			RuntimeOrigin::signed(dac_account),
 			cluster_id,
 			era,
 			batch_index,
-			payers2,
+			payers,
+			customers_proofs[0].clone(),
 		));
 
-		System::assert_last_event(
-			Event::Charged {
-				cluster_id,
-				era,
-				batch_index,
-				customer_id: user1,
-				amount: calculate_charge_for_day(cluster_id, usage1.clone()),
-			}
-			.into(),
-		);
-
-		report = DdcPayouts::active_billing_reports(cluster_id, era).unwrap();
-		let charge1 = calculate_charge_parts_for_day(cluster_id, usage1);
-		assert_eq!(
-			charge1.puts + before_total_customer_charge.puts,
-			report.total_customer_charge.puts
-		);
-		assert_eq!(
-			charge1.gets + before_total_customer_charge.gets,
-			report.total_customer_charge.gets
-		);
+		// the repaid debt funds the owed rewards first, the rest goes to the report
+		assert_eq!(DdcPayouts::debtor_customers(cluster_id, user5_debtor), None);
 		assert_eq!(
-			charge1.storage + before_total_customer_charge.storage,
-			report.total_customer_charge.storage
+			Balances::free_balance(DdcPayouts::owed_rewards_account_id(cluster_id)),
+			owed_rewards
 		);
 		assert_eq!(
-			charge1.transfer + before_total_customer_charge.transfer,
-			report.total_customer_charge.transfer
+			DdcPayouts::billing_report_funds(cluster_id, era).owed_rewards_funded,
+			owed_rewards
 		);
 
-		assert_eq!(report.state, State::ChargingCustomers);
-		let user1_debt = DdcPayouts::debtor_customers(cluster_id, user1);
-		assert_eq!(user1_debt, None);
-
-		let balance_before = Balances::free_balance(DdcPayouts::account_id());
-
-		// batch 3
-		batch_index += 1;
-		before_total_customer_charge = report.total_customer_charge.clone();
-		assert_ok!(DdcPayouts::send_charging_customers_batch(
-			RuntimeOrigin::signed(dac_account),
-			cluster_id,
-			era,
-			batch_index,
-			payers3,
-		));
-
-		let user3_charge = calculate_charge_for_day(cluster_id, usage3.clone());
-		let charge3 = calculate_charge_parts_for_day(cluster_id, usage3);
-		let ratio = Perquintill::from_rational(PARTIAL_CHARGE, user3_charge);
-		report = DdcPayouts::active_billing_reports(cluster_id, era).unwrap();
-		assert_eq!(
-			ratio * charge3.puts + before_total_customer_charge.puts,
-			report.total_customer_charge.puts
-		);
-		assert_eq!(
-			ratio * charge3.gets + before_total_customer_charge.gets,
-			report.total_customer_charge.gets
-		);
-		assert_eq!(
-			ratio * charge3.storage + before_total_customer_charge.storage,
-			report.total_customer_charge.storage
-		);
+		let report = DdcPayouts::active_billing_reports(cluster_id, era).unwrap();
+		let total_charged = report.total_customer_charge.transfer +
+			report.total_customer_charge.storage +
+			report.total_customer_charge.puts +
+			report.total_customer_charge.gets;
 		assert_eq!(
-			ratio * charge3.transfer + before_total_customer_charge.transfer,
-			report.total_customer_charge.transfer
+			total_charged,
+			calculate_charge_for_month(cluster_id, usage5) + user5_debt - owed_rewards
 		);
+	})
+}
 
-		let balance = Balances::free_balance(DdcPayouts::account_id());
-		assert_eq!(balance, balance_before + PARTIAL_CHARGE);
-
-		let user3_debt = DdcPayouts::debtor_customers(cluster_id, user3_debtor).unwrap();
-		debt = user3_charge - PARTIAL_CHARGE;
-		assert_eq!(user3_debt, debt);
+#[test]
+fn send_charging_customers_batch_charges_storage_by_byte_seconds() {
+	ExtBuilder.build_and_execute(|| {
+		System::set_block_number(1);
 
-		System::assert_has_event(
-			Event::Indebted {
+		let dac_account = 123u128;
+		let user6 = 6u128;
+		let user7 = 7u128;
+		let cluster_id = ClusterId::from([12; 20]);
+		let max_batch_index = 0;
+		let batch_index = 0;
+		let start_era: i64 = 1_680_307_200; // April 1st 2023
+		let storage_usage = |stored_bytes| CustomerUsage { stored_bytes, ..Default::default() };
+
+		assert_ok!(DdcPayouts::set_validator_threshold(RuntimeOrigin::root(), 1));
+
+		// (era, duration, [(customer, stored bytes, expected charge)]) with 3_000_000 per MB for
+		// the billing period of 2_630_016 seconds, rounded down
+		for (era, duration, expected) in [
+			(100, BILLING_PERIOD as i64, [(user6, 1, 2), (user7, 349_525, 999_999)]),
+			(101, 3600, [(user6, 1_048_576, 4_106), (user7, 10_485_760, 41_064)]),
+		] {
+			let payers: Vec<_> = expected
+				.iter()
+				.map(|(customer, stored_bytes, _)| {
+					(*customer, *customer as BucketId, storage_usage(*stored_bytes))
+				})
+				.collect();
+			let (customers_merkle_root, customers_proofs) =
+				get_batches_commitment(&[(batch_index, &payers)]);
+
+			assert_ok!(DdcPayouts::begin_billing_report(
+				RuntimeOrigin::signed(dac_account),
 				cluster_id,
 				era,
-				customer_id: user3_debtor,
-				batch_index,
-				amount: user3_debt,
-			}
-			.into(),
-		);
-
-		System::assert_last_event(
-			Event::ChargeFailed {
+				start_era,
+				start_era + duration,
+				customers_merkle_root,
+				H256::default(),
+				max_batch_index,
+				0,
+				CustomerUsage::default(),
+				NodeUsage::default(),
+			));
+			assert_ok!(DdcPayouts::begin_charging_customers(
+				RuntimeOrigin::signed(dac_account),
+				cluster_id,
+				era,
+			));
+			assert_ok!(DdcPayouts::send_charging_customers_batch(
+				RuntimeOrigin::signed(dac_account),
 				cluster_id,
 				era,
 				batch_index,
-				customer_id: user3_debtor,
-				charged: PARTIAL_CHARGE,
-				expected_to_charge: user3_charge,
+				payers,
+				customers_proofs[0].clone(),
+			));
+
+			for (customer_id, _, amount) in expected {
+				System::assert_has_event(
+					Event::Charged {
+						cluster_id,
+						era,
+						batch_index,
+						customer_id,
+						bucket_id: customer_id as BucketId,
+						amount,
+					}
+					.into(),
+				);
 			}
-			.into(),
-		);
+		}
 	})
 }
 
 #[test]
-fn send_charging_customers_batch_works_for_day_free_stream() {
+fn end_charging_customers_works_small_usage_1_hour() {
 	ExtBuilder.build_and_execute(|| {
 		System::set_block_number(1);
 
 		let dac_account = 123u128;
-		let user1 = 1u128;
-		let user2_debtor = 2u128;
-		let user3_debtor = 3u128;
-		let user4 = 4u128;
-		let cluster_id = STREAM_ZERO_CLUSTER_ID;
+		let user6 = 6u128;
+		let user7 = 7u128;
+		let cluster_id = HIGH_FEES_CLUSTER_ID;
 		let era = 100;
-		let max_batch_index = 3;
-		let mut batch_index = 0;
-		let usage1 = CustomerUsage {
-			// should pass without debt
-			transferred_bytes: 23452345,
-			stored_bytes: 3345234523,
-			number_of_puts: 4456456345234523,
-			number_of_gets: 523423,
-		};
-		let usage2 = CustomerUsage {
-			// should fail as not enough balance
-			transferred_bytes: 1,
-			stored_bytes: 2,
-			number_of_puts: 3,
-			number_of_gets: 4,
-		};
-		let usage3 = CustomerUsage {
-			// should pass but with debt (partial charge)
-			transferred_bytes: 1,
-			stored_bytes: 2,
-			number_of_puts: 3,
-			number_of_gets: 4,
+		let max_batch_index = 0;
+		let batch_index = 0;
+		let usage6 = CustomerUsage {
+			transferred_bytes: 0,
+			stored_bytes: 474_957,
+			number_of_puts: 0,
+			number_of_gets: 0,
 		};
-		let usage4 = CustomerUsage {
-			// should pass without debt
-			transferred_bytes: 467457,
-			stored_bytes: 45674567456,
-			number_of_puts: 3456345,
-			number_of_gets: 242334563456423,
+		let usage7 = CustomerUsage {
+			transferred_bytes: 474_957,
+			stored_bytes: 0,
+			number_of_puts: 0,
+			number_of_gets: 0,
 		};
-		let payers1 = vec![(user2_debtor, usage2.clone()), (user4, usage4.clone())];
-		let payers2 = vec![(user1, usage1.clone())];
-		let payers3 = vec![(user3_debtor, usage3.clone())];
+		let payers1 = vec![
+			(user6, user6 as BucketId, usage6.clone()),
+			(user7, user7 as BucketId, usage7.clone()),
+		];
 		let start_date = NaiveDate::from_ymd_opt(2023, 4, 1).unwrap(); // April 1st
 		let time = NaiveTime::from_hms_opt(0, 0, 0).unwrap(); // Midnight
 		let start_era: i64 =
 			DateTime::<Utc>::from_naive_utc_and_offset(start_date.and_time(time), Utc).timestamp();
-		let end_era: i64 = start_era + (1.0 * 24.0 * 3600.0) as i64;
+		let end_era: i64 = start_era + (1.0 * 1.0 * 3600.0) as i64; // 1 hour
+		let (customers_merkle_root, customers_proofs) =
+			get_batches_commitment(&[(batch_index, &payers1)]);
+		let nodes_merkle_root = H256::default();
 
-		assert_ok!(DdcPayouts::set_authorised_caller(RuntimeOrigin::root(), dac_account));
+		assert_ok!(DdcPayouts::set_validator_threshold(RuntimeOrigin::root(), 1));
 		assert_ok!(DdcPayouts::begin_billing_report(
 			RuntimeOrigin::signed(dac_account),
 			cluster_id,
 			era,
 			start_era,
 			end_era,
+			customers_merkle_root,
+			nodes_merkle_root,
+			max_batch_index,
+			0,
+			customers_usage(&payers1),
+			NodeUsage::default(),
 		));
 
 		assert_ok!(DdcPayouts::begin_charging_customers(
 			RuntimeOrigin::signed(dac_account),
 			cluster_id,
 			era,
-			max_batch_index,
 		));
 		assert_eq!(System::events().len(), 3);
 
@@ -1381,16 +1895,256 @@ fn send_charging_customers_batch_works_for_day_free_stream() {
 			era,
 			batch_index,
 			payers1,
+			customers_proofs[0].clone(),
 		));
 
-		let usage4_charge = calculate_charge_for_day(cluster_id, usage4.clone());
-		let user2_debt = DdcPayouts::debtor_customers(cluster_id, user2_debtor).unwrap();
-		let expected_charge2 = calculate_charge_for_day(cluster_id, usage2.clone());
-		let mut debt = expected_charge2 - USER2_BALANCE;
-		assert_eq!(user2_debt, debt);
+		let report_before = DdcPayouts::active_billing_reports(cluster_id, era).unwrap();
+		let usage6_charge = calculate_charge_for_hour(cluster_id, usage6.clone());
+		let usage7_charge = calculate_charge_for_hour(cluster_id, usage7.clone());
+		let charge6 = calculate_charge_parts_for_hour(cluster_id, usage6);
+		let charge7 = calculate_charge_parts_for_hour(cluster_id, usage7);
+		assert_eq!(charge7.puts + charge6.puts, report_before.total_customer_charge.puts);
+		assert_eq!(charge7.gets + charge6.gets, report_before.total_customer_charge.gets);
+		assert_eq!(charge7.storage + charge6.storage, report_before.total_customer_charge.storage);
+		assert_eq!(
+			charge7.transfer + charge6.transfer,
+			report_before.total_customer_charge.transfer
+		);
 
-		let ratio = Perquintill::from_rational(USER2_BALANCE, expected_charge2);
-		let mut charge2 = calculate_charge_parts_for_day(cluster_id, usage2);
+		System::assert_has_event(
+			Event::Charged {
+				cluster_id,
+				era,
+				customer_id: user6,
+				bucket_id: user6 as BucketId,
+				batch_index,
+				amount: usage6_charge,
+			}
+			.into(),
+		);
+
+		System::assert_has_event(
+			Event::Charged {
+				cluster_id,
+				era,
+				customer_id: user7,
+				bucket_id: user7 as BucketId,
+				batch_index,
+				amount: usage7_charge,
+			}
+			.into(),
+		);
+
+		let mut balance = Balances::free_balance(DdcPayouts::account_id());
+		let charge = usage7_charge + usage6_charge;
+		assert_eq!(balance - Balances::minimum_balance(), charge);
+
+		balance = Balances::free_balance(TREASURY_ACCOUNT_ID);
+		assert_eq!(balance, 0);
+
+		balance = Balances::free_balance(RESERVE_ACCOUNT_ID);
+		assert_eq!(balance, 0);
+
+		balance = Balances::free_balance(VALIDATOR1_ACCOUNT_ID);
+		assert_eq!(balance, 0);
+
+		balance = Balances::free_balance(VALIDATOR2_ACCOUNT_ID);
+		assert_eq!(balance, 0);
+
+		balance = Balances::free_balance(VALIDATOR3_ACCOUNT_ID);
+		assert_eq!(balance, 0);
+
+		assert_ok!(DdcPayouts::end_charging_customers(
+			RuntimeOrigin::signed(dac_account),
+			cluster_id,
+			era,
+		));
+
+		System::assert_has_event(Event::ChargingFinished { cluster_id, era }.into());
+		let report_after = DdcPayouts::active_billing_reports(cluster_id, era).unwrap();
+		assert_eq!(report_after.state, State::CustomersChargedWithFees);
+
+		let fees = get_fees(&cluster_id);
+		let total_left_from_one =
+			(fees.treasury_share + fees.validators_share + fees.cluster_reserve_share)
+				.left_from_one();
+
+		assert_eq!(
+			total_left_from_one,
+			Perquintill::one() -
+				(PRICING_FEES_HIGH.treasury_share +
+					PRICING_FEES_HIGH.validators_share +
+					PRICING_FEES_HIGH.cluster_reserve_share)
+		);
+		assert_eq!(fees.treasury_share, PRICING_FEES_HIGH.treasury_share);
+		assert_eq!(fees.validators_share, PRICING_FEES_HIGH.validators_share);
+		assert_eq!(fees.cluster_reserve_share, PRICING_FEES_HIGH.cluster_reserve_share);
+
+		balance = Balances::free_balance(TREASURY_ACCOUNT_ID);
+		assert_eq!(balance, get_fees(&cluster_id).treasury_share * charge);
+		assert!(balance > 0);
+
+		balance = Balances::free_balance(RESERVE_ACCOUNT_ID);
+		assert_eq!(balance, get_fees(&cluster_id).cluster_reserve_share * charge);
+		assert!(balance > 0);
+
+		assert_ok!(DdcPayouts::snapshot_validator_scores(
+			RuntimeOrigin::signed(dac_account),
+			cluster_id,
+			era,
+			10,
+		));
+		for staker_id in [VALIDATOR1_ACCOUNT_ID, VALIDATOR2_ACCOUNT_ID, VALIDATOR3_ACCOUNT_ID] {
+			assert_ok!(DdcPayouts::claim_validator_fees(
+				RuntimeOrigin::signed(dac_account),
+				staker_id,
+				cluster_id,
+				era,
+			));
+		}
+
+		balance = Balances::free_balance(VALIDATOR1_ACCOUNT_ID);
+		let mut ratio = Perquintill::from_rational(
+			VALIDATOR1_SCORE,
+			VALIDATOR1_SCORE + VALIDATOR2_SCORE + VALIDATOR3_SCORE,
+		);
+		assert_eq!(balance, get_fees(&cluster_id).validators_share * ratio * charge);
+		assert!(balance > 0);
+
+		balance = Balances::free_balance(VALIDATOR2_ACCOUNT_ID);
+		ratio = Perquintill::from_rational(
+			VALIDATOR2_SCORE,
+			VALIDATOR1_SCORE + VALIDATOR2_SCORE + VALIDATOR3_SCORE,
+		);
+		assert_eq!(balance, get_fees(&cluster_id).validators_share * ratio * charge);
+		assert!(balance > 0);
+
+		balance = Balances::free_balance(VALIDATOR3_ACCOUNT_ID);
+		ratio = Perquintill::from_rational(
+			VALIDATOR3_SCORE,
+			VALIDATOR1_SCORE + VALIDATOR2_SCORE + VALIDATOR3_SCORE,
+		);
+		assert_eq!(balance, get_fees(&cluster_id).validators_share * ratio * charge);
+		assert!(balance > 0);
+
+		assert_eq!(
+			report_after.total_customer_charge.transfer,
+			total_left_from_one * report_before.total_customer_charge.transfer
+		);
+		assert!(report_after.total_customer_charge.transfer > 0);
+		assert_eq!(
+			report_after.total_customer_charge.storage,
+			total_left_from_one * report_before.total_customer_charge.storage
+		);
+		assert!(report_after.total_customer_charge.storage > 0);
+		assert_eq!(
+			report_after.total_customer_charge.puts,
+			total_left_from_one * report_before.total_customer_charge.puts
+		);
+		assert_eq!(
+			report_after.total_customer_charge.gets,
+			total_left_from_one * report_before.total_customer_charge.gets
+		);
+	})
+}
+
+#[test]
+fn send_charging_customers_batch_works_for_day() {
+	ExtBuilder.build_and_execute(|| {
+		System::set_block_number(1);
+
+		let dac_account = 123u128;
+		let user1 = 1u128;
+		let user2_debtor = 2u128;
+		let user3_debtor = 3u128;
+		let user4 = 4u128;
+		let cluster_id = ClusterId::from([12; 20]);
+		let era = 100;
+		let max_batch_index = 3;
+		let mut batch_index = 0;
+		let usage1 = CustomerUsage {
+			// should pass without debt
+			transferred_bytes: 23452345,
+			stored_bytes: 3345234523,
+			number_of_puts: 4456456345234523,
+			number_of_gets: 523423,
+		};
+		let usage2 = CustomerUsage {
+			// should fail as not enough balance
+			transferred_bytes: 1,
+			stored_bytes: 2,
+			number_of_puts: 3,
+			number_of_gets: 4,
+		};
+		let usage3 = CustomerUsage {
+			// should pass but with debt (partial charge)
+			transferred_bytes: 1,
+			stored_bytes: 2,
+			number_of_puts: 3,
+			number_of_gets: 4,
+		};
+		let usage4 = CustomerUsage {
+			// should pass without debt
+			transferred_bytes: 467457,
+			stored_bytes: 45674567456,
+			number_of_puts: 3456345,
+			number_of_gets: 242334563456423,
+		};
+		let payers1 = vec![
+			(user2_debtor, user2_debtor as BucketId, usage2.clone()),
+			(user4, user4 as BucketId, usage4.clone()),
+		];
+		let payers2 = vec![(user1, user1 as BucketId, usage1.clone())];
+		let payers3 = vec![(user3_debtor, user3_debtor as BucketId, usage3.clone())];
+		let start_date = NaiveDate::from_ymd_opt(2023, 4, 1).unwrap(); // April 1st
+		let time = NaiveTime::from_hms_opt(0, 0, 0).unwrap(); // Midnight
+		let start_era: i64 =
+			DateTime::<Utc>::from_naive_utc_and_offset(start_date.and_time(time), Utc).timestamp();
+		let end_era: i64 = start_era + (1.0 * 24.0 * 3600.0) as i64;
+		let (customers_merkle_root, customers_proofs) =
+			get_batches_commitment(&[(0, &payers1), (1, &payers2), (2, &payers3)]);
+		let nodes_merkle_root = H256::default();
+
+		assert_ok!(DdcPayouts::set_validator_threshold(RuntimeOrigin::root(), 1));
+		assert_ok!(DdcPayouts::begin_billing_report(
+			RuntimeOrigin::signed(dac_account),
+			cluster_id,
+			era,
+			start_era,
+			end_era,
+			customers_merkle_root,
+			nodes_merkle_root,
+			max_batch_index,
+			0,
+			CustomerUsage::default(),
+			NodeUsage::default(),
+		));
+
+		assert_ok!(DdcPayouts::begin_charging_customers(
+			RuntimeOrigin::signed(dac_account),
+			cluster_id,
+			era,
+		));
+		assert_eq!(System::events().len(), 3);
+
+		// batch 1
+		assert_ok!(DdcPayouts::send_charging_customers_batch(
+			RuntimeOrigin::signed(dac_account),
+			cluster_id,
+			era,
+			batch_index,
+			payers1,
+			customers_proofs[0].clone(),
+		));
+
+		let usage4_charge = calculate_charge_for_day(cluster_id, usage4.clone());
+		let user2_debt = DdcPayouts::debtor_customers(cluster_id, user2_debtor).unwrap();
+		let expected_charge2 = calculate_charge_for_day(cluster_id, usage2.clone());
+		let mut debt = expected_charge2 - USER2_BALANCE;
+		assert_eq!(user2_debt, debt);
+
+		let ratio = Perquintill::from_rational(USER2_BALANCE, expected_charge2);
+		let mut charge2 = calculate_charge_parts_for_day(cluster_id, usage2);
 		charge2.storage = ratio * charge2.storage;
 		charge2.transfer = ratio * charge2.transfer;
 		charge2.gets = ratio * charge2.gets;
@@ -1408,6 +2162,7 @@ fn send_charging_customers_batch_works_for_day_free_stream() {
 				cluster_id,
 				era,
 				customer_id: user2_debtor,
+				bucket_id: user2_debtor as BucketId,
 				batch_index,
 				charged: USER2_BALANCE,
 				expected_to_charge: expected_charge2,
@@ -1420,6 +2175,7 @@ fn send_charging_customers_batch_works_for_day_free_stream() {
 				cluster_id,
 				era,
 				customer_id: user2_debtor,
+				bucket_id: user2_debtor as BucketId,
 				batch_index,
 				amount: debt,
 			}
@@ -1430,6 +2186,7 @@ fn send_charging_customers_batch_works_for_day_free_stream() {
 				cluster_id,
 				era,
 				customer_id: user4,
+				bucket_id: user4 as BucketId,
 				batch_index,
 				amount: usage4_charge,
 			}
@@ -1447,6 +2204,7 @@ fn send_charging_customers_batch_works_for_day_free_stream() {
 			era,
 			batch_index,
 			payers2,
+			customers_proofs[1].clone(),
 		));
 
 		System::assert_last_event(
@@ -1455,6 +2213,7 @@ fn send_charging_customers_batch_works_for_day_free_stream() {
 				era,
 				batch_index,
 				customer_id: user1,
+				bucket_id: user1 as BucketId,
 				amount: calculate_charge_for_day(cluster_id, usage1.clone()),
 			}
 			.into(),
@@ -1494,6 +2253,7 @@ fn send_charging_customers_batch_works_for_day_free_stream() {
 			era,
 			batch_index,
 			payers3,
+			customers_proofs[2].clone(),
 		));
 
 		let user3_charge = calculate_charge_for_day(cluster_id, usage3.clone());
@@ -1529,6 +2289,7 @@ fn send_charging_customers_batch_works_for_day_free_stream() {
 				cluster_id,
 				era,
 				customer_id: user3_debtor,
+				bucket_id: user3_debtor as BucketId,
 				batch_index,
 				amount: user3_debt,
 			}
@@ -1541,6 +2302,7 @@ fn send_charging_customers_batch_works_for_day_free_stream() {
 				era,
 				batch_index,
 				customer_id: user3_debtor,
+				bucket_id: user3_debtor as BucketId,
 				charged: PARTIAL_CHARGE,
 				expected_to_charge: user3_charge,
 			}
@@ -1550,7 +2312,7 @@ fn send_charging_customers_batch_works_for_day_free_stream() {
 }
 
 #[test]
-fn send_charging_customers_batch_works_for_day_free_get() {
+fn send_charging_customers_batch_works_for_day_free_storage() {
 	ExtBuilder.build_and_execute(|| {
 		System::set_block_number(1);
 
@@ -1559,7 +2321,7 @@ fn send_charging_customers_batch_works_for_day_free_get() {
 		let user2_debtor = 2u128;
 		let user3_debtor = 3u128;
 		let user4 = 4u128;
-		let cluster_id = GET_ZERO_CLUSTER_ID;
+		let cluster_id = STORAGE_ZERO_CLUSTER_ID;
 		let era = 100;
 		let max_batch_index = 3;
 		let mut batch_index = 0;
@@ -1591,29 +2353,40 @@ fn send_charging_customers_batch_works_for_day_free_get() {
 			number_of_puts: 3456345,
 			number_of_gets: 242334563456423,
 		};
-		let payers1 = vec![(user2_debtor, usage2.clone()), (user4, usage4.clone())];
-		let payers2 = vec![(user1, usage1.clone())];
-		let payers3 = vec![(user3_debtor, usage3.clone())];
+		let payers1 = vec![
+			(user2_debtor, user2_debtor as BucketId, usage2.clone()),
+			(user4, user4 as BucketId, usage4.clone()),
+		];
+		let payers2 = vec![(user1, user1 as BucketId, usage1.clone())];
+		let payers3 = vec![(user3_debtor, user3_debtor as BucketId, usage3.clone())];
 		let start_date = NaiveDate::from_ymd_opt(2023, 4, 1).unwrap(); // April 1st
 		let time = NaiveTime::from_hms_opt(0, 0, 0).unwrap(); // Midnight
 		let start_era: i64 =
 			DateTime::<Utc>::from_naive_utc_and_offset(start_date.and_time(time), Utc).timestamp();
 		let end_era: i64 = start_era + (1.0 * 24.0 * 3600.0) as i64;
+		let (customers_merkle_root, customers_proofs) =
+			get_batches_commitment(&[(0, &payers1), (1, &payers2), (2, &payers3)]);
+		let nodes_merkle_root = H256::default();
 
-		assert_ok!(DdcPayouts::set_authorised_caller(RuntimeOrigin::root(), dac_account));
+		assert_ok!(DdcPayouts::set_validator_threshold(RuntimeOrigin::root(), 1));
 		assert_ok!(DdcPayouts::begin_billing_report(
 			RuntimeOrigin::signed(dac_account),
 			cluster_id,
 			era,
 			start_era,
 			end_era,
+			customers_merkle_root,
+			nodes_merkle_root,
+			max_batch_index,
+			0,
+			CustomerUsage::default(),
+			NodeUsage::default(),
 		));
 
 		assert_ok!(DdcPayouts::begin_charging_customers(
 			RuntimeOrigin::signed(dac_account),
 			cluster_id,
 			era,
-			max_batch_index,
 		));
 		assert_eq!(System::events().len(), 3);
 
@@ -1624,6 +2397,7 @@ fn send_charging_customers_batch_works_for_day_free_get() {
 			era,
 			batch_index,
 			payers1,
+			customers_proofs[0].clone(),
 		));
 
 		let usage4_charge = calculate_charge_for_day(cluster_id, usage4.clone());
@@ -1651,6 +2425,7 @@ fn send_charging_customers_batch_works_for_day_free_get() {
 				cluster_id,
 				era,
 				customer_id: user2_debtor,
+				bucket_id: user2_debtor as BucketId,
 				batch_index,
 				charged: USER2_BALANCE,
 				expected_to_charge: expected_charge2,
@@ -1663,6 +2438,7 @@ fn send_charging_customers_batch_works_for_day_free_get() {
 				cluster_id,
 				era,
 				customer_id: user2_debtor,
+				bucket_id: user2_debtor as BucketId,
 				batch_index,
 				amount: debt,
 			}
@@ -1673,6 +2449,7 @@ fn send_charging_customers_batch_works_for_day_free_get() {
 				cluster_id,
 				era,
 				customer_id: user4,
+				bucket_id: user4 as BucketId,
 				batch_index,
 				amount: usage4_charge,
 			}
@@ -1690,6 +2467,7 @@ fn send_charging_customers_batch_works_for_day_free_get() {
 			era,
 			batch_index,
 			payers2,
+			customers_proofs[1].clone(),
 		));
 
 		System::assert_last_event(
@@ -1698,6 +2476,7 @@ fn send_charging_customers_batch_works_for_day_free_get() {
 				era,
 				batch_index,
 				customer_id: user1,
+				bucket_id: user1 as BucketId,
 				amount: calculate_charge_for_day(cluster_id, usage1.clone()),
 			}
 			.into(),
@@ -1737,6 +2516,7 @@ fn send_charging_customers_batch_works_for_day_free_get() {
 			era,
 			batch_index,
 			payers3,
+			customers_proofs[2].clone(),
 		));
 
 		let user3_charge = calculate_charge_for_day(cluster_id, usage3.clone());
@@ -1772,6 +2552,7 @@ fn send_charging_customers_batch_works_for_day_free_get() {
 				cluster_id,
 				era,
 				customer_id: user3_debtor,
+				bucket_id: user3_debtor as BucketId,
 				batch_index,
 				amount: user3_debt,
 			}
@@ -1784,6 +2565,7 @@ fn send_charging_customers_batch_works_for_day_free_get() {
 				era,
 				batch_index,
 				customer_id: user3_debtor,
+				bucket_id: user3_debtor as BucketId,
 				charged: PARTIAL_CHARGE,
 				expected_to_charge: user3_charge,
 			}
@@ -1793,7 +2575,7 @@ fn send_charging_customers_batch_works_for_day_free_get() {
 }
 
 #[test]
-fn send_charging_customers_batch_works_for_day_free_put() {
+fn send_charging_customers_batch_works_for_day_free_stream() {
 	ExtBuilder.build_and_execute(|| {
 		System::set_block_number(1);
 
@@ -1802,7 +2584,7 @@ fn send_charging_customers_batch_works_for_day_free_put() {
 		let user2_debtor = 2u128;
 		let user3_debtor = 3u128;
 		let user4 = 4u128;
-		let cluster_id = PUT_ZERO_CLUSTER_ID;
+		let cluster_id = STREAM_ZERO_CLUSTER_ID;
 		let era = 100;
 		let max_batch_index = 3;
 		let mut batch_index = 0;
@@ -1834,29 +2616,40 @@ fn send_charging_customers_batch_works_for_day_free_put() {
 			number_of_puts: 3456345,
 			number_of_gets: 242334563456423,
 		};
-		let payers1 = vec![(user2_debtor, usage2.clone()), (user4, usage4.clone())];
-		let payers2 = vec![(user1, usage1.clone())];
-		let payers3 = vec![(user3_debtor, usage3.clone())];
+		let payers1 = vec![
+			(user2_debtor, user2_debtor as BucketId, usage2.clone()),
+			(user4, user4 as BucketId, usage4.clone()),
+		];
+		let payers2 = vec![(user1, user1 as BucketId, usage1.clone())];
+		let payers3 = vec![(user3_debtor, user3_debtor as BucketId, usage3.clone())];
 		let start_date = NaiveDate::from_ymd_opt(2023, 4, 1).unwrap(); // April 1st
 		let time = NaiveTime::from_hms_opt(0, 0, 0).unwrap(); // Midnight
 		let start_era: i64 =
 			DateTime::<Utc>::from_naive_utc_and_offset(start_date.and_time(time), Utc).timestamp();
 		let end_era: i64 = start_era + (1.0 * 24.0 * 3600.0) as i64;
+		let (customers_merkle_root, customers_proofs) =
+			get_batches_commitment(&[(0, &payers1), (1, &payers2), (2, &payers3)]);
+		let nodes_merkle_root = H256::default();
 
-		assert_ok!(DdcPayouts::set_authorised_caller(RuntimeOrigin::root(), dac_account));
+		assert_ok!(DdcPayouts::set_validator_threshold(RuntimeOrigin::root(), 1));
 		assert_ok!(DdcPayouts::begin_billing_report(
 			RuntimeOrigin::signed(dac_account),
 			cluster_id,
 			era,
 			start_era,
 			end_era,
+			customers_merkle_root,
+			nodes_merkle_root,
+			max_batch_index,
+			0,
+			CustomerUsage::default(),
+			NodeUsage::default(),
 		));
 
 		assert_ok!(DdcPayouts::begin_charging_customers(
 			RuntimeOrigin::signed(dac_account),
 			cluster_id,
 			era,
-			max_batch_index,
 		));
 		assert_eq!(System::events().len(), 3);
 
@@ -1867,6 +2660,7 @@ fn send_charging_customers_batch_works_for_day_free_put() {
 			era,
 			batch_index,
 			payers1,
+			customers_proofs[0].clone(),
 		));
 
 		let usage4_charge = calculate_charge_for_day(cluster_id, usage4.clone());
@@ -1894,6 +2688,7 @@ fn send_charging_customers_batch_works_for_day_free_put() {
 				cluster_id,
 				era,
 				customer_id: user2_debtor,
+				bucket_id: user2_debtor as BucketId,
 				batch_index,
 				charged: USER2_BALANCE,
 				expected_to_charge: expected_charge2,
@@ -1906,6 +2701,7 @@ fn send_charging_customers_batch_works_for_day_free_put() {
 				cluster_id,
 				era,
 				customer_id: user2_debtor,
+				bucket_id: user2_debtor as BucketId,
 				batch_index,
 				amount: debt,
 			}
@@ -1916,6 +2712,7 @@ fn send_charging_customers_batch_works_for_day_free_put() {
 				cluster_id,
 				era,
 				customer_id: user4,
+				bucket_id: user4 as BucketId,
 				batch_index,
 				amount: usage4_charge,
 			}
@@ -1933,6 +2730,7 @@ fn send_charging_customers_batch_works_for_day_free_put() {
 			era,
 			batch_index,
 			payers2,
+			customers_proofs[1].clone(),
 		));
 
 		System::assert_last_event(
@@ -1941,6 +2739,7 @@ fn send_charging_customers_batch_works_for_day_free_put() {
 				era,
 				batch_index,
 				customer_id: user1,
+				bucket_id: user1 as BucketId,
 				amount: calculate_charge_for_day(cluster_id, usage1.clone()),
 			}
 			.into(),
@@ -1980,6 +2779,7 @@ fn send_charging_customers_batch_works_for_day_free_put() {
 			era,
 			batch_index,
 			payers3,
+			customers_proofs[2].clone(),
 		));
 
 		let user3_charge = calculate_charge_for_day(cluster_id, usage3.clone());
@@ -2015,6 +2815,7 @@ fn send_charging_customers_batch_works_for_day_free_put() {
 				cluster_id,
 				era,
 				customer_id: user3_debtor,
+				bucket_id: user3_debtor as BucketId,
 				batch_index,
 				amount: user3_debt,
 			}
@@ -2027,6 +2828,7 @@ fn send_charging_customers_batch_works_for_day_free_put() {
 				era,
 				batch_index,
 				customer_id: user3_debtor,
+				bucket_id: user3_debtor as BucketId,
 				charged: PARTIAL_CHARGE,
 				expected_to_charge: user3_charge,
 			}
@@ -2036,7 +2838,7 @@ fn send_charging_customers_batch_works_for_day_free_put() {
 }
 
 #[test]
-fn send_charging_customers_batch_works_for_day_free_storage_stream() {
+fn send_charging_customers_batch_works_for_day_free_get() {
 	ExtBuilder.build_and_execute(|| {
 		System::set_block_number(1);
 
@@ -2045,7 +2847,7 @@ fn send_charging_customers_batch_works_for_day_free_storage_stream() {
 		let user2_debtor = 2u128;
 		let user3_debtor = 3u128;
 		let user4 = 4u128;
-		let cluster_id = STORAGE_STREAM_ZERO_CLUSTER_ID;
+		let cluster_id = GET_ZERO_CLUSTER_ID;
 		let era = 100;
 		let max_batch_index = 3;
 		let mut batch_index = 0;
@@ -2077,29 +2879,40 @@ fn send_charging_customers_batch_works_for_day_free_storage_stream() {
 			number_of_puts: 3456345,
 			number_of_gets: 242334563456423,
 		};
-		let payers1 = vec![(user2_debtor, usage2.clone()), (user4, usage4.clone())];
-		let payers2 = vec![(user1, usage1.clone())];
-		let payers3 = vec![(user3_debtor, usage3.clone())];
+		let payers1 = vec![
+			(user2_debtor, user2_debtor as BucketId, usage2.clone()),
+			(user4, user4 as BucketId, usage4.clone()),
+		];
+		let payers2 = vec![(user1, user1 as BucketId, usage1.clone())];
+		let payers3 = vec![(user3_debtor, user3_debtor as BucketId, usage3.clone())];
 		let start_date = NaiveDate::from_ymd_opt(2023, 4, 1).unwrap(); // April 1st
 		let time = NaiveTime::from_hms_opt(0, 0, 0).unwrap(); // Midnight
 		let start_era: i64 =
 			DateTime::<Utc>::from_naive_utc_and_offset(start_date.and_time(time), Utc).timestamp();
 		let end_era: i64 = start_era + (1.0 * 24.0 * 3600.0) as i64;
+		let (customers_merkle_root, customers_proofs) =
+			get_batches_commitment(&[(0, &payers1), (1, &payers2), (2, &payers3)]);
+		let nodes_merkle_root = H256::default();
 
-		assert_ok!(DdcPayouts::set_authorised_caller(RuntimeOrigin::root(), dac_account));
+		assert_ok!(DdcPayouts::set_validator_threshold(RuntimeOrigin::root(), 1));
 		assert_ok!(DdcPayouts::begin_billing_report(
 			RuntimeOrigin::signed(dac_account),
 			cluster_id,
 			era,
 			start_era,
 			end_era,
+			customers_merkle_root,
+			nodes_merkle_root,
+			max_batch_index,
+			0,
+			CustomerUsage::default(),
+			NodeUsage::default(),
 		));
 
 		assert_ok!(DdcPayouts::begin_charging_customers(
 			RuntimeOrigin::signed(dac_account),
 			cluster_id,
 			era,
-			max_batch_index,
 		));
 		assert_eq!(System::events().len(), 3);
 
@@ -2110,6 +2923,7 @@ fn send_charging_customers_batch_works_for_day_free_storage_stream() {
 			era,
 			batch_index,
 			payers1,
+			customers_proofs[0].clone(),
 		));
 
 		let usage4_charge = calculate_charge_for_day(cluster_id, usage4.clone());
@@ -2137,6 +2951,7 @@ fn send_charging_customers_batch_works_for_day_free_storage_stream() {
 				cluster_id,
 				era,
 				customer_id: user2_debtor,
+				bucket_id: user2_debtor as BucketId,
 				batch_index,
 				charged: USER2_BALANCE,
 				expected_to_charge: expected_charge2,
@@ -2149,6 +2964,7 @@ fn send_charging_customers_batch_works_for_day_free_storage_stream() {
 				cluster_id,
 				era,
 				customer_id: user2_debtor,
+				bucket_id: user2_debtor as BucketId,
 				batch_index,
 				amount: debt,
 			}
@@ -2159,6 +2975,7 @@ fn send_charging_customers_batch_works_for_day_free_storage_stream() {
 				cluster_id,
 				era,
 				customer_id: user4,
+				bucket_id: user4 as BucketId,
 				batch_index,
 				amount: usage4_charge,
 			}
@@ -2176,6 +2993,7 @@ fn send_charging_customers_batch_works_for_day_free_storage_stream() {
 			era,
 			batch_index,
 			payers2,
+			customers_proofs[1].clone(),
 		));
 
 		System::assert_last_event(
@@ -2184,6 +3002,7 @@ fn send_charging_customers_batch_works_for_day_free_storage_stream() {
 				era,
 				batch_index,
 				customer_id: user1,
+				bucket_id: user1 as BucketId,
 				amount: calculate_charge_for_day(cluster_id, usage1.clone()),
 			}
 			.into(),
@@ -2223,6 +3042,7 @@ fn send_charging_customers_batch_works_for_day_free_storage_stream() {
 			era,
 			batch_index,
 			payers3,
+			customers_proofs[2].clone(),
 		));
 
 		let user3_charge = calculate_charge_for_day(cluster_id, usage3.clone());
@@ -2258,6 +3078,7 @@ fn send_charging_customers_batch_works_for_day_free_storage_stream() {
 				cluster_id,
 				era,
 				customer_id: user3_debtor,
+				bucket_id: user3_debtor as BucketId,
 				batch_index,
 				amount: user3_debt,
 			}
@@ -2270,6 +3091,7 @@ fn send_charging_customers_batch_works_for_day_free_storage_stream() {
 				era,
 				batch_index,
 				customer_id: user3_debtor,
+				bucket_id: user3_debtor as BucketId,
 				charged: PARTIAL_CHARGE,
 				expected_to_charge: user3_charge,
 			}
@@ -2279,473 +3101,656 @@ fn send_charging_customers_batch_works_for_day_free_storage_stream() {
 }
 
 #[test]
-fn send_charging_customers_batch_works_zero_fees() {
+fn send_charging_customers_batch_works_for_day_free_put() {
 	ExtBuilder.build_and_execute(|| {
 		System::set_block_number(1);
 
 		let dac_account = 123u128;
-		let user5 = 5u128;
-		let cluster_id = ONE_CLUSTER_ID;
+		let user1 = 1u128;
+		let user2_debtor = 2u128;
+		let user3_debtor = 3u128;
+		let user4 = 4u128;
+		let cluster_id = PUT_ZERO_CLUSTER_ID;
 		let era = 100;
-		let max_batch_index = 0;
-		let batch_index = 0;
-		let usage5 = CustomerUsage {
+		let max_batch_index = 3;
+		let mut batch_index = 0;
+		let usage1 = CustomerUsage {
 			// should pass without debt
-			transferred_bytes: 1024,
-			stored_bytes: 1024,
-			number_of_puts: 1,
-			number_of_gets: 1,
+			transferred_bytes: 23452345,
+			stored_bytes: 3345234523,
+			number_of_puts: 4456456345234523,
+			number_of_gets: 523423,
 		};
-		let payers5 = vec![(user5, usage5.clone())];
-		let start_date = NaiveDate::from_ymd_opt(2023, 4, 1).unwrap(); // April 1st
-		let time = NaiveTime::from_hms_opt(0, 0, 0).unwrap(); // Midnight
-		let start_era: i64 =
-			DateTime::<Utc>::from_naive_utc_and_offset(start_date.and_time(time), Utc).timestamp();
-		let end_era: i64 = start_era + (30.44 * 24.0 * 3600.0) as i64;
+		let usage2 = CustomerUsage {
+			// should fail as not enough balance
+			transferred_bytes: 1,
+			stored_bytes: 2,
+			number_of_puts: 3,
+			number_of_gets: 4,
+		};
+		let usage3 = CustomerUsage {
+			// should pass but with debt (partial charge)
+			transferred_bytes: 1,
+			stored_bytes: 2,
+			number_of_puts: 3,
+			number_of_gets: 4,
+		};
+		let usage4 = CustomerUsage {
+			// should pass without debt
+			transferred_bytes: 467457,
+			stored_bytes: 45674567456,
+			number_of_puts: 3456345,
+			number_of_gets: 242334563456423,
+		};
+		let payers1 = vec![
+			(user2_debtor, user2_debtor as BucketId, usage2.clone()),
+			(user4, user4 as BucketId, usage4.clone()),
+		];
+		let payers2 = vec![(user1, user1 as BucketId, usage1.clone())];
+		let payers3 = vec![(user3_debtor, user3_debtor as BucketId, usage3.clone())];
+		let start_date = NaiveDate::from_ymd_opt(2023, 4, 1).unwrap(); // April 1st
+		let time = NaiveTime::from_hms_opt(0, 0, 0).unwrap(); // Midnight
+		let start_era: i64 =
+			DateTime::<Utc>::from_naive_utc_and_offset(start_date.and_time(time), Utc).timestamp();
+		let end_era: i64 = start_era + (1.0 * 24.0 * 3600.0) as i64;
+		let (customers_merkle_root, customers_proofs) =
+			get_batches_commitment(&[(0, &payers1), (1, &payers2), (2, &payers3)]);
+		let nodes_merkle_root = H256::default();
 
-		assert_ok!(DdcPayouts::set_authorised_caller(RuntimeOrigin::root(), dac_account));
+		assert_ok!(DdcPayouts::set_validator_threshold(RuntimeOrigin::root(), 1));
 		assert_ok!(DdcPayouts::begin_billing_report(
 			RuntimeOrigin::signed(dac_account),
 			cluster_id,
 			era,
 			start_era,
 			end_era,
+			customers_merkle_root,
+			nodes_merkle_root,
+			max_batch_index,
+			0,
+			CustomerUsage::default(),
+			NodeUsage::default(),
 		));
 
 		assert_ok!(DdcPayouts::begin_charging_customers(
 			RuntimeOrigin::signed(dac_account),
 			cluster_id,
 			era,
-			max_batch_index,
 		));
 		assert_eq!(System::events().len(), 3);
 
 		// batch 1
+		assert_ok!(DdcPayouts::send_charging_customers_batch(
+			RuntimeOrigin::signed(dac_account),
+			cluster_id,
+			era,
+			batch_index,
+			payers1,
+			customers_proofs[0].clone(),
+		));
+
+		let usage4_charge = calculate_charge_for_day(cluster_id, usage4.clone());
+		let user2_debt = DdcPayouts::debtor_customers(cluster_id, user2_debtor).unwrap();
+		let expected_charge2 = calculate_charge_for_day(cluster_id, usage2.clone());
+		let mut debt = expected_charge2 - USER2_BALANCE;
+		assert_eq!(user2_debt, debt);
+
+		let ratio = Perquintill::from_rational(USER2_BALANCE, expected_charge2);
+		let mut charge2 = calculate_charge_parts_for_day(cluster_id, usage2);
+		charge2.storage = ratio * charge2.storage;
+		charge2.transfer = ratio * charge2.transfer;
+		charge2.gets = ratio * charge2.gets;
+		charge2.puts = ratio * charge2.puts;
+
 		let mut report = DdcPayouts::active_billing_reports(cluster_id, era).unwrap();
-		let before_total_customer_charge = report.total_customer_charge;
-		let balance_before = Balances::free_balance(DdcPayouts::account_id());
+		let charge4 = calculate_charge_parts_for_day(cluster_id, usage4);
+		assert_eq!(charge2.puts + charge4.puts, report.total_customer_charge.puts);
+		assert_eq!(charge2.gets + charge4.gets, report.total_customer_charge.gets);
+		assert_eq!(charge2.storage + charge4.storage, report.total_customer_charge.storage);
+		assert_eq!(charge2.transfer + charge4.transfer, report.total_customer_charge.transfer);
+
+		System::assert_has_event(
+			Event::ChargeFailed {
+				cluster_id,
+				era,
+				customer_id: user2_debtor,
+				bucket_id: user2_debtor as BucketId,
+				batch_index,
+				charged: USER2_BALANCE,
+				expected_to_charge: expected_charge2,
+			}
+			.into(),
+		);
+
+		System::assert_has_event(
+			Event::Indebted {
+				cluster_id,
+				era,
+				customer_id: user2_debtor,
+				bucket_id: user2_debtor as BucketId,
+				batch_index,
+				amount: debt,
+			}
+			.into(),
+		);
+		System::assert_last_event(
+			Event::Charged {
+				cluster_id,
+				era,
+				customer_id: user4,
+				bucket_id: user4 as BucketId,
+				batch_index,
+				amount: usage4_charge,
+			}
+			.into(),
+		);
+
+		assert_eq!(System::events().len(), 5 + 3 + 1); // 1 for Currency::transfer
+
+		// batch 2
+		let mut before_total_customer_charge = report.total_customer_charge;
+		batch_index += 1;
 		assert_ok!(DdcPayouts::send_charging_customers_batch(
 			RuntimeOrigin::signed(dac_account),
 			cluster_id,
 			era,
 			batch_index,
-			payers5,
+			payers2,
+			customers_proofs[1].clone(),
 		));
 
-		let usage5_charge = calculate_charge_for_month(cluster_id, usage5.clone());
-		let charge5 = calculate_charge_parts_for_month(cluster_id, usage5);
-		let balance = Balances::free_balance(DdcPayouts::account_id());
+		System::assert_last_event(
+			Event::Charged {
+				cluster_id,
+				era,
+				batch_index,
+				customer_id: user1,
+				bucket_id: user1 as BucketId,
+				amount: calculate_charge_for_day(cluster_id, usage1.clone()),
+			}
+			.into(),
+		);
+
 		report = DdcPayouts::active_billing_reports(cluster_id, era).unwrap();
-		assert_eq!(balance, usage5_charge + balance_before);
+		let charge1 = calculate_charge_parts_for_day(cluster_id, usage1);
 		assert_eq!(
-			charge5.puts + before_total_customer_charge.puts,
+			charge1.puts + before_total_customer_charge.puts,
 			report.total_customer_charge.puts
 		);
 		assert_eq!(
-			charge5.gets + before_total_customer_charge.gets,
+			charge1.gets + before_total_customer_charge.gets,
 			report.total_customer_charge.gets
 		);
 		assert_eq!(
-			charge5.storage + before_total_customer_charge.storage,
+			charge1.storage + before_total_customer_charge.storage,
 			report.total_customer_charge.storage
 		);
 		assert_eq!(
-			charge5.transfer + before_total_customer_charge.transfer,
+			charge1.transfer + before_total_customer_charge.transfer,
 			report.total_customer_charge.transfer
 		);
-	})
-}
-
-#[test]
-fn end_charging_customers_fails_uninitialised() {
-	ExtBuilder.build_and_execute(|| {
-		let root_account = 100u128;
-		let dac_account = 123u128;
-		let user1 = 1u128;
-		let cluster_id = ClusterId::from([12; 20]);
-		let era = 100;
-		let max_batch_index = 2;
-		let batch_index = 1;
-		let payers = vec![(user1, CustomerUsage::default())];
-		let start_date = NaiveDate::from_ymd_opt(2023, 4, 1).unwrap(); // April 1st
-		let time = NaiveTime::from_hms_opt(0, 0, 0).unwrap(); // Midnight
-		let start_era: i64 =
-			DateTime::<Utc>::from_naive_utc_and_offset(start_date.and_time(time), Utc).timestamp();
-		let end_era: i64 = start_era + (30.44 * 24.0 * 3600.0) as i64;
-
-		assert_noop!(
-			DdcPayouts::end_charging_customers(
-				RuntimeOrigin::signed(root_account),
-				cluster_id,
-				era,
-			),
-			Error::<Test>::Unauthorised
-		);
-
-		assert_noop!(
-			DdcPayouts::end_charging_customers(RuntimeOrigin::root(), cluster_id, era,),
-			BadOrigin
-		);
 
-		assert_ok!(DdcPayouts::set_authorised_caller(RuntimeOrigin::root(), dac_account));
+		assert_eq!(report.state, State::ChargingCustomers);
+		let user1_debt = DdcPayouts::debtor_customers(cluster_id, user1);
+		assert_eq!(user1_debt, None);
 
-		assert_noop!(
-			DdcPayouts::end_charging_customers(RuntimeOrigin::signed(dac_account), cluster_id, era,),
-			Error::<Test>::BillingReportDoesNotExist
-		);
+		let balance_before = Balances::free_balance(DdcPayouts::account_id());
 
-		assert_ok!(DdcPayouts::begin_billing_report(
+		// batch 3
+		batch_index += 1;
+		before_total_customer_charge = report.total_customer_charge.clone();
+		assert_ok!(DdcPayouts::send_charging_customers_batch(
 			RuntimeOrigin::signed(dac_account),
 			cluster_id,
 			era,
-			start_era,
-			end_era,
+			batch_index,
+			payers3,
+			customers_proofs[2].clone(),
 		));
 
-		assert_noop!(
-			DdcPayouts::end_charging_customers(RuntimeOrigin::signed(dac_account), cluster_id, era,),
-			Error::<Test>::NotExpectedState
+		let user3_charge = calculate_charge_for_day(cluster_id, usage3.clone());
+		let charge3 = calculate_charge_parts_for_day(cluster_id, usage3);
+		let ratio = Perquintill::from_rational(PARTIAL_CHARGE, user3_charge);
+		report = DdcPayouts::active_billing_reports(cluster_id, era).unwrap();
+		assert_eq!(
+			ratio * charge3.puts + before_total_customer_charge.puts,
+			report.total_customer_charge.puts
+		);
+		assert_eq!(
+			ratio * charge3.gets + before_total_customer_charge.gets,
+			report.total_customer_charge.gets
+		);
+		assert_eq!(
+			ratio * charge3.storage + before_total_customer_charge.storage,
+			report.total_customer_charge.storage
+		);
+		assert_eq!(
+			ratio * charge3.transfer + before_total_customer_charge.transfer,
+			report.total_customer_charge.transfer
 		);
 
-		assert_ok!(DdcPayouts::begin_charging_customers(
-			RuntimeOrigin::signed(dac_account),
-			cluster_id,
-			era,
-			max_batch_index,
-		));
+		let balance = Balances::free_balance(DdcPayouts::account_id());
+		assert_eq!(balance, balance_before + PARTIAL_CHARGE);
 
-		assert_noop!(
-			DdcPayouts::end_charging_customers(RuntimeOrigin::signed(dac_account), cluster_id, era,),
-			Error::<Test>::BatchesMissed
-		);
+		let user3_debt = DdcPayouts::debtor_customers(cluster_id, user3_debtor).unwrap();
+		debt = user3_charge - PARTIAL_CHARGE;
+		assert_eq!(user3_debt, debt);
 
-		assert_ok!(DdcPayouts::send_charging_customers_batch(
-			RuntimeOrigin::signed(dac_account),
-			cluster_id,
-			era,
-			batch_index,
-			payers,
-		));
+		System::assert_has_event(
+			Event::Indebted {
+				cluster_id,
+				era,
+				customer_id: user3_debtor,
+				bucket_id: user3_debtor as BucketId,
+				batch_index,
+				amount: user3_debt,
+			}
+			.into(),
+		);
 
-		assert_noop!(
-			DdcPayouts::end_charging_customers(RuntimeOrigin::signed(dac_account), cluster_id, era,),
-			Error::<Test>::BatchesMissed
+		System::assert_last_event(
+			Event::ChargeFailed {
+				cluster_id,
+				era,
+				batch_index,
+				customer_id: user3_debtor,
+				bucket_id: user3_debtor as BucketId,
+				charged: PARTIAL_CHARGE,
+				expected_to_charge: user3_charge,
+			}
+			.into(),
 		);
 	})
 }
 
 #[test]
-fn end_charging_customers_works() {
+fn send_charging_customers_batch_works_for_day_free_storage_stream() {
 	ExtBuilder.build_and_execute(|| {
 		System::set_block_number(1);
 
 		let dac_account = 123u128;
 		let user1 = 1u128;
-		let cluster_id = ClusterId::from([12; 20]);
+		let user2_debtor = 2u128;
+		let user3_debtor = 3u128;
+		let user4 = 4u128;
+		let cluster_id = STORAGE_STREAM_ZERO_CLUSTER_ID;
 		let era = 100;
-		let max_batch_index = 0;
-		let batch_index = 0;
+		let max_batch_index = 3;
+		let mut batch_index = 0;
 		let usage1 = CustomerUsage {
+			// should pass without debt
 			transferred_bytes: 23452345,
 			stored_bytes: 3345234523,
 			number_of_puts: 4456456345234523,
 			number_of_gets: 523423,
 		};
-		let payers = vec![(user1, usage1.clone())];
-		let start_date = NaiveDate::from_ymd_opt(2023, 4, 1).unwrap(); // April 1st
-
-		let time = NaiveTime::from_hms_opt(0, 0, 0).unwrap(); // Midnight
-		let start_era: i64 =
-			DateTime::<Utc>::from_naive_utc_and_offset(start_date.and_time(time), Utc).timestamp();
-		let end_era: i64 = start_era + (30.44 * 24.0 * 3600.0) as i64;
-
-		assert_ok!(DdcPayouts::set_authorised_caller(RuntimeOrigin::root(), dac_account));
+		let usage2 = CustomerUsage {
+			// should fail as not enough balance
+			transferred_bytes: 1,
+			stored_bytes: 2,
+			number_of_puts: 3,
+			number_of_gets: 4,
+		};
+		let usage3 = CustomerUsage {
+			// should pass but with debt (partial charge)
+			transferred_bytes: 1,
+			stored_bytes: 2,
+			number_of_puts: 3,
+			number_of_gets: 4,
+		};
+		let usage4 = CustomerUsage {
+			// should pass without debt
+			transferred_bytes: 467457,
+			stored_bytes: 45674567456,
+			number_of_puts: 3456345,
+			number_of_gets: 242334563456423,
+		};
+		let payers1 = vec![
+			(user2_debtor, user2_debtor as BucketId, usage2.clone()),
+			(user4, user4 as BucketId, usage4.clone()),
+		];
+		let payers2 = vec![(user1, user1 as BucketId, usage1.clone())];
+		let payers3 = vec![(user3_debtor, user3_debtor as BucketId, usage3.clone())];
+		let start_date = NaiveDate::from_ymd_opt(2023, 4, 1).unwrap(); // April 1st
+		let time = NaiveTime::from_hms_opt(0, 0, 0).unwrap(); // Midnight
+		let start_era: i64 =
+			DateTime::<Utc>::from_naive_utc_and_offset(start_date.and_time(time), Utc).timestamp();
+		let end_era: i64 = start_era + (1.0 * 24.0 * 3600.0) as i64;
+		let (customers_merkle_root, customers_proofs) =
+			get_batches_commitment(&[(0, &payers1), (1, &payers2), (2, &payers3)]);
+		let nodes_merkle_root = H256::default();
 
+		assert_ok!(DdcPayouts::set_validator_threshold(RuntimeOrigin::root(), 1));
 		assert_ok!(DdcPayouts::begin_billing_report(
 			RuntimeOrigin::signed(dac_account),
 			cluster_id,
 			era,
 			start_era,
 			end_era,
+			customers_merkle_root,
+			nodes_merkle_root,
+			max_batch_index,
+			0,
+			CustomerUsage::default(),
+			NodeUsage::default(),
 		));
 
 		assert_ok!(DdcPayouts::begin_charging_customers(
 			RuntimeOrigin::signed(dac_account),
 			cluster_id,
 			era,
-			max_batch_index,
 		));
+		assert_eq!(System::events().len(), 3);
 
+		// batch 1
 		assert_ok!(DdcPayouts::send_charging_customers_batch(
 			RuntimeOrigin::signed(dac_account),
 			cluster_id,
 			era,
 			batch_index,
-			payers,
+			payers1,
+			customers_proofs[0].clone(),
 		));
 
-		let report_before = DdcPayouts::active_billing_reports(cluster_id, era).unwrap();
-		let charge = calculate_charge_for_month(cluster_id, usage1);
-		System::assert_last_event(
-			Event::Charged { cluster_id, era, batch_index, customer_id: user1, amount: charge }
-				.into(),
-		);
-
-		let mut balance = Balances::free_balance(DdcPayouts::account_id());
-		assert_eq!(balance - Balances::minimum_balance(), charge);
-		assert_eq!(System::events().len(), 4 + 1); // 1 for Currency::transfer
-
-		assert_ok!(DdcPayouts::end_charging_customers(
-			RuntimeOrigin::signed(dac_account),
-			cluster_id,
-			era,
-		));
+		let usage4_charge = calculate_charge_for_day(cluster_id, usage4.clone());
+		let user2_debt = DdcPayouts::debtor_customers(cluster_id, user2_debtor).unwrap();
+		let expected_charge2 = calculate_charge_for_day(cluster_id, usage2.clone());
+		let mut debt = expected_charge2 - USER2_BALANCE;
+		assert_eq!(user2_debt, debt);
 
-		System::assert_has_event(Event::ChargingFinished { cluster_id, era }.into());
+		let ratio = Perquintill::from_rational(USER2_BALANCE, expected_charge2);
+		let mut charge2 = calculate_charge_parts_for_day(cluster_id, usage2);
+		charge2.storage = ratio * charge2.storage;
+		charge2.transfer = ratio * charge2.transfer;
+		charge2.gets = ratio * charge2.gets;
+		charge2.puts = ratio * charge2.puts;
 
-		let treasury_fee = get_fees(&cluster_id).treasury_share * charge;
-		let reserve_fee = get_fees(&cluster_id).cluster_reserve_share * charge;
-		let validator_fee = get_fees(&cluster_id).validators_share * charge;
+		let mut report = DdcPayouts::active_billing_reports(cluster_id, era).unwrap();
+		let charge4 = calculate_charge_parts_for_day(cluster_id, usage4);
+		assert_eq!(charge2.puts + charge4.puts, report.total_customer_charge.puts);
+		assert_eq!(charge2.gets + charge4.gets, report.total_customer_charge.gets);
+		assert_eq!(charge2.storage + charge4.storage, report.total_customer_charge.storage);
+		assert_eq!(charge2.transfer + charge4.transfer, report.total_customer_charge.transfer);
 
 		System::assert_has_event(
-			Event::TreasuryFeesCollected { cluster_id, era, amount: treasury_fee }.into(),
+			Event::ChargeFailed {
+				cluster_id,
+				era,
+				customer_id: user2_debtor,
+				bucket_id: user2_debtor as BucketId,
+				batch_index,
+				charged: USER2_BALANCE,
+				expected_to_charge: expected_charge2,
+			}
+			.into(),
 		);
 
 		System::assert_has_event(
-			Event::ClusterReserveFeesCollected { cluster_id, era, amount: reserve_fee }.into(),
+			Event::Indebted {
+				cluster_id,
+				era,
+				customer_id: user2_debtor,
+				bucket_id: user2_debtor as BucketId,
+				batch_index,
+				amount: debt,
+			}
+			.into(),
 		);
-
-		System::assert_has_event(
-			Event::ValidatorFeesCollected { cluster_id, era, amount: validator_fee }.into(),
+		System::assert_last_event(
+			Event::Charged {
+				cluster_id,
+				era,
+				customer_id: user4,
+				bucket_id: user4 as BucketId,
+				batch_index,
+				amount: usage4_charge,
+			}
+			.into(),
 		);
 
-		let transfers = 3 + 3 + 3 * 3; // for Currency::transfer
-		assert_eq!(System::events().len(), 5 + 1 + 3 + transfers);
-
-		let report_after = DdcPayouts::active_billing_reports(cluster_id, era).unwrap();
-		assert_eq!(report_after.state, State::CustomersChargedWithFees);
-
-		let total_left_from_one = (get_fees(&cluster_id).treasury_share +
-			get_fees(&cluster_id).validators_share +
-			get_fees(&cluster_id).cluster_reserve_share)
-			.left_from_one();
-
-		balance = Balances::free_balance(TREASURY_ACCOUNT_ID);
-		assert_eq!(balance, get_fees(&cluster_id).treasury_share * charge);
+		assert_eq!(System::events().len(), 5 + 3 + 1); // 1 for Currency::transfer
 
-		balance = Balances::free_balance(RESERVE_ACCOUNT_ID);
-		assert_eq!(balance, get_fees(&cluster_id).cluster_reserve_share * charge);
+		// batch 2
+		let mut before_total_customer_charge = report.total_customer_charge;
+		batch_index += 1;
+		assert_ok!(DdcPayouts::send_charging_customers_batch(
+			RuntimeOrigin::signed(dac_account),
+			cluster_id,
+			era,
+			batch_index,
+			payers2,
+			customers_proofs[1].clone(),
+		));
 
-		balance = Balances::free_balance(VALIDATOR1_ACCOUNT_ID);
-		let mut ratio = Perquintill::from_rational(
-			VALIDATOR1_SCORE,
-			VALIDATOR1_SCORE + VALIDATOR2_SCORE + VALIDATOR3_SCORE,
+		System::assert_last_event(
+			Event::Charged {
+				cluster_id,
+				era,
+				batch_index,
+				customer_id: user1,
+				bucket_id: user1 as BucketId,
+				amount: calculate_charge_for_day(cluster_id, usage1.clone()),
+			}
+			.into(),
 		);
-		assert_eq!(balance, get_fees(&cluster_id).validators_share * ratio * charge);
 
-		balance = Balances::free_balance(VALIDATOR2_ACCOUNT_ID);
-		ratio = Perquintill::from_rational(
-			VALIDATOR2_SCORE,
-			VALIDATOR1_SCORE + VALIDATOR2_SCORE + VALIDATOR3_SCORE,
+		report = DdcPayouts::active_billing_reports(cluster_id, era).unwrap();
+		let charge1 = calculate_charge_parts_for_day(cluster_id, usage1);
+		assert_eq!(
+			charge1.puts + before_total_customer_charge.puts,
+			report.total_customer_charge.puts
 		);
-		assert_eq!(balance, get_fees(&cluster_id).validators_share * ratio * charge);
-
-		balance = Balances::free_balance(VALIDATOR3_ACCOUNT_ID);
-		ratio = Perquintill::from_rational(
-			VALIDATOR3_SCORE,
-			VALIDATOR1_SCORE + VALIDATOR2_SCORE + VALIDATOR3_SCORE,
+		assert_eq!(
+			charge1.gets + before_total_customer_charge.gets,
+			report.total_customer_charge.gets
 		);
-		assert_eq!(balance, get_fees(&cluster_id).validators_share * ratio * charge);
+		assert_eq!(
+			charge1.storage + before_total_customer_charge.storage,
+			report.total_customer_charge.storage
+		);
+		assert_eq!(
+			charge1.transfer + before_total_customer_charge.transfer,
+			report.total_customer_charge.transfer
+		);
+
+		assert_eq!(report.state, State::ChargingCustomers);
+		let user1_debt = DdcPayouts::debtor_customers(cluster_id, user1);
+		assert_eq!(user1_debt, None);
+
+		let balance_before = Balances::free_balance(DdcPayouts::account_id());
+
+		// batch 3
+		batch_index += 1;
+		before_total_customer_charge = report.total_customer_charge.clone();
+		assert_ok!(DdcPayouts::send_charging_customers_batch(
+			RuntimeOrigin::signed(dac_account),
+			cluster_id,
+			era,
+			batch_index,
+			payers3,
+			customers_proofs[2].clone(),
+		));
 
+		let user3_charge = calculate_charge_for_day(cluster_id, usage3.clone());
+		let charge3 = calculate_charge_parts_for_day(cluster_id, usage3);
+		let ratio = Perquintill::from_rational(PARTIAL_CHARGE, user3_charge);
+		report = DdcPayouts::active_billing_reports(cluster_id, era).unwrap();
 		assert_eq!(
-			report_after.total_customer_charge.transfer,
-			total_left_from_one * report_before.total_customer_charge.transfer
+			ratio * charge3.puts + before_total_customer_charge.puts,
+			report.total_customer_charge.puts
 		);
 		assert_eq!(
-			report_after.total_customer_charge.storage,
-			total_left_from_one * report_before.total_customer_charge.storage
+			ratio * charge3.gets + before_total_customer_charge.gets,
+			report.total_customer_charge.gets
 		);
 		assert_eq!(
-			report_after.total_customer_charge.puts,
-			total_left_from_one * report_before.total_customer_charge.puts
+			ratio * charge3.storage + before_total_customer_charge.storage,
+			report.total_customer_charge.storage
 		);
 		assert_eq!(
-			report_after.total_customer_charge.gets,
-			total_left_from_one * report_before.total_customer_charge.gets
+			ratio * charge3.transfer + before_total_customer_charge.transfer,
+			report.total_customer_charge.transfer
+		);
+
+		let balance = Balances::free_balance(DdcPayouts::account_id());
+		assert_eq!(balance, balance_before + PARTIAL_CHARGE);
+
+		let user3_debt = DdcPayouts::debtor_customers(cluster_id, user3_debtor).unwrap();
+		debt = user3_charge - PARTIAL_CHARGE;
+		assert_eq!(user3_debt, debt);
+
+		System::assert_has_event(
+			Event::Indebted {
+				cluster_id,
+				era,
+				customer_id: user3_debtor,
+				bucket_id: user3_debtor as BucketId,
+				batch_index,
+				amount: user3_debt,
+			}
+			.into(),
+		);
+
+		System::assert_last_event(
+			Event::ChargeFailed {
+				cluster_id,
+				era,
+				batch_index,
+				customer_id: user3_debtor,
+				bucket_id: user3_debtor as BucketId,
+				charged: PARTIAL_CHARGE,
+				expected_to_charge: user3_charge,
+			}
+			.into(),
 		);
 	})
 }
 
 #[test]
-fn end_charging_customers_works_zero_fees() {
+fn send_charging_customers_batch_works_zero_fees() {
 	ExtBuilder.build_and_execute(|| {
 		System::set_block_number(1);
 
 		let dac_account = 123u128;
-		let user1 = 1u128;
-		let cluster_id = ClusterId::zero();
+		let user5 = 5u128;
+		let cluster_id = ONE_CLUSTER_ID;
 		let era = 100;
 		let max_batch_index = 0;
 		let batch_index = 0;
-		let usage1 = CustomerUsage {
-			transferred_bytes: 23452345,
-			stored_bytes: 3345234523,
+		let usage5 = CustomerUsage {
+			// should pass without debt
+			transferred_bytes: 1024,
+			stored_bytes: 1024,
 			number_of_puts: 1,
 			number_of_gets: 1,
 		};
-		let payers = vec![(user1, usage1.clone())];
+		let payers5 = vec![(user5, user5 as BucketId, usage5.clone())];
 		let start_date = NaiveDate::from_ymd_opt(2023, 4, 1).unwrap(); // April 1st
-
 		let time = NaiveTime::from_hms_opt(0, 0, 0).unwrap(); // Midnight
 		let start_era: i64 =
 			DateTime::<Utc>::from_naive_utc_and_offset(start_date.and_time(time), Utc).timestamp();
 		let end_era: i64 = start_era + (30.44 * 24.0 * 3600.0) as i64;
+		let (customers_merkle_root, customers_proofs) =
+			get_batches_commitment(&[(batch_index, &payers5)]);
+		let nodes_merkle_root = H256::default();
 
-		assert_ok!(DdcPayouts::set_authorised_caller(RuntimeOrigin::root(), dac_account));
-
+		assert_ok!(DdcPayouts::set_validator_threshold(RuntimeOrigin::root(), 1));
 		assert_ok!(DdcPayouts::begin_billing_report(
 			RuntimeOrigin::signed(dac_account),
 			cluster_id,
 			era,
 			start_era,
 			end_era,
+			customers_merkle_root,
+			nodes_merkle_root,
+			max_batch_index,
+			0,
+			CustomerUsage::default(),
+			NodeUsage::default(),
 		));
 
 		assert_ok!(DdcPayouts::begin_charging_customers(
 			RuntimeOrigin::signed(dac_account),
 			cluster_id,
 			era,
-			max_batch_index,
 		));
+		assert_eq!(System::events().len(), 3);
 
+		// batch 1
+		let mut report = DdcPayouts::active_billing_reports(cluster_id, era).unwrap();
+		let before_total_customer_charge = report.total_customer_charge;
+		let balance_before = Balances::free_balance(DdcPayouts::account_id());
 		assert_ok!(DdcPayouts::send_charging_customers_batch(
 			RuntimeOrigin::signed(dac_account),
 			cluster_id,
 			era,
 			batch_index,
-			payers,
-		));
-
-		let report_before = DdcPayouts::active_billing_reports(cluster_id, era).unwrap();
-		let charge = calculate_charge_for_month(cluster_id, usage1);
-		System::assert_last_event(
-			Event::Charged { cluster_id, era, customer_id: user1, batch_index, amount: charge }
-				.into(),
-		);
-
-		let mut balance = Balances::free_balance(DdcPayouts::account_id());
-		assert_eq!(balance - Balances::minimum_balance(), charge);
-		assert_eq!(System::events().len(), 4 + 1); // 1 for Currency::transfer
-
-		assert_ok!(DdcPayouts::end_charging_customers(
-			RuntimeOrigin::signed(dac_account),
-			cluster_id,
-			era,
+			payers5,
+			customers_proofs[0].clone(),
 		));
 
-		System::assert_has_event(Event::ChargingFinished { cluster_id, era }.into());
-		assert_eq!(System::events().len(), 5 + 1);
-
-		let report_after = DdcPayouts::active_billing_reports(cluster_id, era).unwrap();
-		assert_eq!(report_after.state, State::CustomersChargedWithFees);
-
-		let fees = get_fees(&cluster_id);
-
-		let total_left_from_one =
-			(fees.treasury_share + fees.validators_share + fees.cluster_reserve_share)
-				.left_from_one();
-
-		assert_eq!(total_left_from_one, Perquintill::one());
-
-		assert_eq!(fees.treasury_share, Perquintill::zero());
-		assert_eq!(fees.validators_share, Perquintill::zero());
-		assert_eq!(fees.cluster_reserve_share, Perquintill::zero());
-
-		balance = Balances::free_balance(TREASURY_ACCOUNT_ID);
-		assert_eq!(balance, 0);
-
-		balance = Balances::free_balance(RESERVE_ACCOUNT_ID);
-		assert_eq!(balance, 0);
-
-		balance = Balances::free_balance(VALIDATOR1_ACCOUNT_ID);
-		assert_eq!(balance, 0);
-
-		balance = Balances::free_balance(VALIDATOR2_ACCOUNT_ID);
-		assert_eq!(balance, 0);
-
-		balance = Balances::free_balance(VALIDATOR3_ACCOUNT_ID);
-		assert_eq!(balance, 0);
-
+		let usage5_charge = calculate_charge_for_month(cluster_id, usage5.clone());
+		let charge5 = calculate_charge_parts_for_month(cluster_id, usage5);
+		let balance = Balances::free_balance(DdcPayouts::account_id());
+		report = DdcPayouts::active_billing_reports(cluster_id, era).unwrap();
+		assert_eq!(balance, usage5_charge + balance_before);
 		assert_eq!(
-			report_after.total_customer_charge.transfer,
-			total_left_from_one * report_before.total_customer_charge.transfer
+			charge5.puts + before_total_customer_charge.puts,
+			report.total_customer_charge.puts
 		);
 		assert_eq!(
-			report_after.total_customer_charge.storage,
-			total_left_from_one * report_before.total_customer_charge.storage
+			charge5.gets + before_total_customer_charge.gets,
+			report.total_customer_charge.gets
 		);
 		assert_eq!(
-			report_after.total_customer_charge.puts,
-			total_left_from_one * report_before.total_customer_charge.puts
+			charge5.storage + before_total_customer_charge.storage,
+			report.total_customer_charge.storage
 		);
 		assert_eq!(
-			report_after.total_customer_charge.gets,
-			total_left_from_one * report_before.total_customer_charge.gets
+			charge5.transfer + before_total_customer_charge.transfer,
+			report.total_customer_charge.transfer
 		);
 	})
 }
 
 #[test]
-fn begin_rewarding_providers_fails_uninitialised() {
+fn end_charging_customers_fails_uninitialised() {
 	ExtBuilder.build_and_execute(|| {
-		let root_account = 1u128;
-		let dac_account = 2u128;
-		let user1 = 3u128;
+		let root_account = 100u128;
+		let dac_account = 123u128;
+		let user1 = 1u128;
 		let cluster_id = ClusterId::from([12; 20]);
 		let era = 100;
 		let max_batch_index = 2;
 		let batch_index = 1;
-		let payers = vec![(user1, CustomerUsage::default())];
-		let node_usage = NodeUsage::default();
+		let payers = vec![(user1, user1 as BucketId, CustomerUsage::default())];
 		let start_date = NaiveDate::from_ymd_opt(2023, 4, 1).unwrap(); // April 1st
-
 		let time = NaiveTime::from_hms_opt(0, 0, 0).unwrap(); // Midnight
 		let start_era: i64 =
 			DateTime::<Utc>::from_naive_utc_and_offset(start_date.and_time(time), Utc).timestamp();
 		let end_era: i64 = start_era + (30.44 * 24.0 * 3600.0) as i64;
+		let (customers_merkle_root, customers_proofs) =
+			get_batches_commitment(&[(batch_index, &payers)]);
+		let nodes_merkle_root = H256::default();
 
 		assert_noop!(
-			DdcPayouts::begin_rewarding_providers(
+			DdcPayouts::end_charging_customers(
 				RuntimeOrigin::signed(root_account),
 				cluster_id,
 				era,
-				max_batch_index,
-				node_usage.clone(),
 			),
 			Error::<Test>::Unauthorised
 		);
 
 		assert_noop!(
-			DdcPayouts::begin_rewarding_providers(
-				RuntimeOrigin::root(),
-				cluster_id,
-				era,
-				max_batch_index,
-				node_usage.clone(),
-			),
+			DdcPayouts::end_charging_customers(RuntimeOrigin::root(), cluster_id, era,),
 			BadOrigin
 		);
 
-		assert_ok!(DdcPayouts::set_authorised_caller(RuntimeOrigin::root(), dac_account));
+		assert_ok!(DdcPayouts::set_validator_threshold(RuntimeOrigin::root(), 1));
 
 		assert_noop!(
-			DdcPayouts::begin_rewarding_providers(
-				RuntimeOrigin::signed(dac_account),
-				cluster_id,
-				era,
-				max_batch_index,
-				node_usage.clone(),
-			),
+			DdcPayouts::end_charging_customers(RuntimeOrigin::signed(dac_account), cluster_id, era,),
 			Error::<Test>::BillingReportDoesNotExist
 		);
 
@@ -2755,16 +3760,16 @@ fn begin_rewarding_providers_fails_uninitialised() {
 			era,
 			start_era,
 			end_era,
+			customers_merkle_root,
+			nodes_merkle_root,
+			max_batch_index,
+			0,
+			CustomerUsage::default(),
+			NodeUsage::default(),
 		));
 
 		assert_noop!(
-			DdcPayouts::begin_rewarding_providers(
-				RuntimeOrigin::signed(dac_account),
-				cluster_id,
-				era,
-				max_batch_index,
-				node_usage.clone(),
-			),
+			DdcPayouts::end_charging_customers(RuntimeOrigin::signed(dac_account), cluster_id, era,),
 			Error::<Test>::NotExpectedState
 		);
 
@@ -2772,18 +3777,11 @@ fn begin_rewarding_providers_fails_uninitialised() {
 			RuntimeOrigin::signed(dac_account),
 			cluster_id,
 			era,
-			max_batch_index,
 		));
 
 		assert_noop!(
-			DdcPayouts::begin_rewarding_providers(
-				RuntimeOrigin::signed(dac_account),
-				cluster_id,
-				era,
-				max_batch_index,
-				node_usage.clone(),
-			),
-			Error::<Test>::NotExpectedState
+			DdcPayouts::end_charging_customers(RuntimeOrigin::signed(dac_account), cluster_id, era,),
+			Error::<Test>::BatchesMissed
 		);
 
 		assert_ok!(DdcPayouts::send_charging_customers_batch(
@@ -2791,43 +3789,19 @@ fn begin_rewarding_providers_fails_uninitialised() {
 			cluster_id,
 			era,
 			batch_index,
-			payers.clone(),
-		));
-
-		assert_noop!(
-			DdcPayouts::begin_rewarding_providers(
-				RuntimeOrigin::signed(dac_account),
-				cluster_id,
-				era,
-				max_batch_index,
-				node_usage.clone(),
-			),
-			Error::<Test>::NotExpectedState
-		);
-
-		assert_ok!(DdcPayouts::send_charging_customers_batch(
-			RuntimeOrigin::signed(dac_account),
-			cluster_id,
-			era,
-			batch_index + 1,
 			payers,
+			customers_proofs[0].clone(),
 		));
 
 		assert_noop!(
-			DdcPayouts::begin_rewarding_providers(
-				RuntimeOrigin::signed(dac_account),
-				cluster_id,
-				era,
-				max_batch_index,
-				node_usage,
-			),
-			Error::<Test>::NotExpectedState
+			DdcPayouts::end_charging_customers(RuntimeOrigin::signed(dac_account), cluster_id, era,),
+			Error::<Test>::BatchesMissed
 		);
 	})
 }
 
 #[test]
-fn begin_rewarding_providers_works() {
+fn end_charging_customers_works() {
 	ExtBuilder.build_and_execute(|| {
 		System::set_block_number(1);
 
@@ -2837,16 +3811,24 @@ fn begin_rewarding_providers_works() {
 		let era = 100;
 		let max_batch_index = 0;
 		let batch_index = 0;
-		let total_node_usage = NodeUsage::default();
-		let payers = vec![(user1, CustomerUsage::default())];
+		let usage1 = CustomerUsage {
+			transferred_bytes: 23452345,
+			stored_bytes: 3345234523,
+			number_of_puts: 4456456345234523,
+			number_of_gets: 523423,
+		};
+		let payers = vec![(user1, user1 as BucketId, usage1.clone())];
 		let start_date = NaiveDate::from_ymd_opt(2023, 4, 1).unwrap(); // April 1st
 
 		let time = NaiveTime::from_hms_opt(0, 0, 0).unwrap(); // Midnight
 		let start_era: i64 =
 			DateTime::<Utc>::from_naive_utc_and_offset(start_date.and_time(time), Utc).timestamp();
 		let end_era: i64 = start_era + (30.44 * 24.0 * 3600.0) as i64;
+		let (customers_merkle_root, customers_proofs) =
+			get_batches_commitment(&[(batch_index, &payers)]);
+		let nodes_merkle_root = H256::default();
 
-		assert_ok!(DdcPayouts::set_authorised_caller(RuntimeOrigin::root(), dac_account));
+		assert_ok!(DdcPayouts::set_validator_threshold(RuntimeOrigin::root(), 1));
 
 		assert_ok!(DdcPayouts::begin_billing_report(
 			RuntimeOrigin::signed(dac_account),
@@ -2854,16 +3836,18 @@ fn begin_rewarding_providers_works() {
 			era,
 			start_era,
 			end_era,
+			customers_merkle_root,
+			nodes_merkle_root,
+			max_batch_index,
+			0,
+			customers_usage(&payers),
+			NodeUsage::default(),
 		));
 
-		let mut report = DdcPayouts::active_billing_reports(cluster_id, era).unwrap();
-		assert_eq!(report.state, State::Initialized);
-
 		assert_ok!(DdcPayouts::begin_charging_customers(
 			RuntimeOrigin::signed(dac_account),
 			cluster_id,
 			era,
-			max_batch_index,
 		));
 
 		assert_ok!(DdcPayouts::send_charging_customers_batch(
@@ -2872,253 +3856,288 @@ fn begin_rewarding_providers_works() {
 			era,
 			batch_index,
 			payers,
+			customers_proofs[0].clone(),
 		));
 
-		assert_ok!(DdcPayouts::end_charging_customers(
-			RuntimeOrigin::signed(dac_account),
-			cluster_id,
-			era,
-		));
+		let report_before = DdcPayouts::active_billing_reports(cluster_id, era).unwrap();
+		let charge = calculate_charge_for_month(cluster_id, usage1);
+		System::assert_last_event(
+			Event::Charged {
+				cluster_id,
+				era,
+				batch_index,
+				customer_id: user1,
+				bucket_id: user1 as BucketId,
+				amount: charge,
+			}
+			.into(),
+		);
 
-		assert_ok!(DdcPayouts::begin_rewarding_providers(
+		let mut balance = Balances::free_balance(DdcPayouts::account_id());
+		assert_eq!(balance - Balances::minimum_balance(), charge);
+		assert_eq!(System::events().len(), 4 + 1); // 1 for Currency::transfer
+
+		assert_ok!(DdcPayouts::end_charging_customers(
 			RuntimeOrigin::signed(dac_account),
 			cluster_id,
 			era,
-			max_batch_index,
-			total_node_usage,
 		));
 
-		System::assert_last_event(Event::RewardingStarted { cluster_id, era }.into());
-
-		report = DdcPayouts::active_billing_reports(cluster_id, era).unwrap();
-		assert_eq!(report.state, State::RewardingProviders);
-	})
-}
+		System::assert_has_event(Event::ChargingFinished { cluster_id, era }.into());
 
-#[test]
-fn send_rewarding_providers_batch_fails_uninitialised() {
-	ExtBuilder.build_and_execute(|| {
-		let root_account = 1u128;
-		let dac_account = 2u128;
-		let user1 = 3u128;
-		let user2 = 4u128;
-		let node1 = 33u128;
-		let cluster_id = ClusterId::from([12; 20]);
-		let era = 100;
-		let max_batch_index = 1;
-		let batch_index = 0;
-		let payers1 = vec![(user1, CustomerUsage::default())];
-		let payers2 = vec![(user2, CustomerUsage::default())];
-		let payees = vec![(node1, NodeUsage::default())];
-		let start_date = NaiveDate::from_ymd_opt(2023, 4, 1).unwrap(); // April 1st
+		let treasury_fee = get_fees(&cluster_id).treasury_share * charge;
+		let reserve_fee = get_fees(&cluster_id).cluster_reserve_share * charge;
+		let validator_fee = get_fees(&cluster_id).validators_share * charge;
 
-		let time = NaiveTime::from_hms_opt(0, 0, 0).unwrap(); // Midnight
-		let start_era: i64 =
-			DateTime::<Utc>::from_naive_utc_and_offset(start_date.and_time(time), Utc).timestamp();
-		let end_era: i64 = start_era + (30.44 * 24.0 * 3600.0) as i64;
+		System::assert_has_event(
+			Event::TreasuryFeesCollected { cluster_id, era, amount: treasury_fee }.into(),
+		);
 
-		assert_noop!(
-			DdcPayouts::send_rewarding_providers_batch(
-				RuntimeOrigin::signed(root_account),
-				cluster_id,
-				era,
-				batch_index,
-				payees.clone(),
-			),
-			Error::<Test>::Unauthorised
+		System::assert_has_event(
+			Event::ClusterReserveFeesCollected { cluster_id, era, amount: reserve_fee }.into(),
 		);
 
-		assert_noop!(
-			DdcPayouts::send_rewarding_providers_batch(
-				RuntimeOrigin::root(),
-				cluster_id,
-				era,
-				batch_index,
-				payees.clone(),
-			),
-			BadOrigin
+		System::assert_has_event(
+			Event::ValidatorFeesCollected { cluster_id, era, amount: validator_fee }.into(),
 		);
 
-		assert_ok!(DdcPayouts::set_authorised_caller(RuntimeOrigin::root(), dac_account));
+		let transfers = 3 + 3 + 3; // for Currency::transfer
+		assert_eq!(System::events().len(), 5 + 1 + 3 + transfers);
+
+		// validators fees are kept in the pot until the stakers claim them
+		assert_eq!(
+			DdcPayouts::validator_fees_pots(cluster_id, era),
+			Some(ValidatorFeesPot {
+				total_fees: validator_fee,
+				total_score: 0,
+				claimed_fees: 0,
+				snapshotted: false,
+			})
+		);
+		balance = Balances::free_balance(DdcPayouts::validator_fees_pot_account_id());
+		assert_eq!(balance, validator_fee);
+		balance = Balances::free_balance(VALIDATOR1_ACCOUNT_ID);
+		assert_eq!(balance, 0);
 
+		// the fees are claimed once the scores of all the stakers are snapshotted
 		assert_noop!(
-			DdcPayouts::send_rewarding_providers_batch(
-				RuntimeOrigin::signed(dac_account),
+			DdcPayouts::claim_validator_fees(
+				RuntimeOrigin::signed(user1),
+				VALIDATOR1_ACCOUNT_ID,
 				cluster_id,
 				era,
-				batch_index,
-				payees.clone(),
 			),
-			Error::<Test>::BillingReportDoesNotExist
+			Error::<Test>::ValidatorScoresNotSnapshotted
 		);
 
-		assert_ok!(DdcPayouts::begin_billing_report(
-			RuntimeOrigin::signed(dac_account),
+		assert_ok!(DdcPayouts::snapshot_validator_scores(
+			RuntimeOrigin::signed(user1),
 			cluster_id,
 			era,
-			start_era,
-			end_era,
+			2,
 		));
+		assert_eq!(
+			DdcPayouts::validator_scores_snapshot_cursors(cluster_id, era),
+			Some(VALIDATOR2_ACCOUNT_ID)
+		);
+		assert_eq!(
+			DdcPayouts::validator_fees_scores((cluster_id, era), VALIDATOR2_ACCOUNT_ID),
+			Some(VALIDATOR2_SCORE)
+		);
+		assert!(!DdcPayouts::validator_fees_pots(cluster_id, era).unwrap().snapshotted);
 
-		assert_noop!(
-			DdcPayouts::send_rewarding_providers_batch(
-				RuntimeOrigin::signed(dac_account),
-				cluster_id,
-				era,
-				batch_index,
-				payees.clone(),
-			),
-			Error::<Test>::NotExpectedState
-		);
-
-		assert_ok!(DdcPayouts::begin_charging_customers(
-			RuntimeOrigin::signed(dac_account),
+		assert_ok!(DdcPayouts::snapshot_validator_scores(
+			RuntimeOrigin::signed(user1),
 			cluster_id,
 			era,
-			max_batch_index,
+			2,
 		));
+		System::assert_last_event(
+			Event::ValidatorScoresSnapshotted {
+				cluster_id,
+				era,
+				total_score: VALIDATOR1_SCORE + VALIDATOR2_SCORE + VALIDATOR3_SCORE,
+			}
+			.into(),
+		);
+		assert_eq!(
+			DdcPayouts::validator_fees_pots(cluster_id, era),
+			Some(ValidatorFeesPot {
+				total_fees: validator_fee,
+				total_score: VALIDATOR1_SCORE + VALIDATOR2_SCORE + VALIDATOR3_SCORE,
+				claimed_fees: 0,
+				snapshotted: true,
+			})
+		);
+		assert_eq!(DdcPayouts::validator_scores_snapshot_cursors(cluster_id, era), None);
+		assert_noop!(
+			DdcPayouts::snapshot_validator_scores(RuntimeOrigin::signed(user1), cluster_id, era, 2),
+			Error::<Test>::ValidatorScoresAlreadySnapshotted
+		);
+
+		for staker_id in [VALIDATOR1_ACCOUNT_ID, VALIDATOR2_ACCOUNT_ID, VALIDATOR3_ACCOUNT_ID] {
+			assert_ok!(DdcPayouts::claim_validator_fees(
+				RuntimeOrigin::signed(user1),
+				staker_id,
+				cluster_id,
+				era,
+			));
+		}
+
+		System::assert_last_event(
+			Event::ValidatorFeesClaimed {
+				cluster_id,
+				era,
+				staker_id: VALIDATOR3_ACCOUNT_ID,
+				amount: DdcPayouts::validator_fees_claims((cluster_id, era), VALIDATOR3_ACCOUNT_ID)
+					.unwrap(),
+			}
+			.into(),
+		);
 
 		assert_noop!(
-			DdcPayouts::send_rewarding_providers_batch(
-				RuntimeOrigin::signed(dac_account),
+			DdcPayouts::claim_validator_fees(
+				RuntimeOrigin::signed(user1),
+				VALIDATOR1_ACCOUNT_ID,
 				cluster_id,
 				era,
-				batch_index,
-				payees.clone(),
 			),
-			Error::<Test>::NotExpectedState
+			Error::<Test>::ValidatorFeesAlreadyClaimed
 		);
 
-		assert_ok!(DdcPayouts::send_charging_customers_batch(
-			RuntimeOrigin::signed(dac_account),
-			cluster_id,
-			era,
-			batch_index,
-			payers1,
-		));
-
 		assert_noop!(
-			DdcPayouts::send_rewarding_providers_batch(
-				RuntimeOrigin::signed(dac_account),
+			DdcPayouts::claim_validator_fees(
+				RuntimeOrigin::signed(user1),
+				VALIDATOR1_ACCOUNT_ID,
 				cluster_id,
-				era,
-				batch_index,
-				payees.clone(),
+				era + 1,
 			),
-			Error::<Test>::NotExpectedState
+			Error::<Test>::NoValidatorFees
 		);
 
-		assert_ok!(DdcPayouts::send_charging_customers_batch(
-			RuntimeOrigin::signed(dac_account),
+		let report_after = DdcPayouts::active_billing_reports(cluster_id, era).unwrap();
+		assert_eq!(report_after.state, State::CustomersChargedWithFees);
+
+		let total_left_from_one = (get_fees(&cluster_id).treasury_share +
+			get_fees(&cluster_id).validators_share +
+			get_fees(&cluster_id).cluster_reserve_share)
+			.left_from_one();
+
+		balance = Balances::free_balance(TREASURY_ACCOUNT_ID);
+		assert_eq!(balance, get_fees(&cluster_id).treasury_share * charge);
+
+		balance = Balances::free_balance(RESERVE_ACCOUNT_ID);
+		assert_eq!(balance, get_fees(&cluster_id).cluster_reserve_share * charge);
+
+		balance = Balances::free_balance(VALIDATOR1_ACCOUNT_ID);
+		let mut ratio = Perquintill::from_rational(
+			VALIDATOR1_SCORE,
+			VALIDATOR1_SCORE + VALIDATOR2_SCORE + VALIDATOR3_SCORE,
+		);
+		assert_eq!(balance, get_fees(&cluster_id).validators_share * ratio * charge);
+
+		balance = Balances::free_balance(VALIDATOR2_ACCOUNT_ID);
+		ratio = Perquintill::from_rational(
+			VALIDATOR2_SCORE,
+			VALIDATOR1_SCORE + VALIDATOR2_SCORE + VALIDATOR3_SCORE,
+		);
+		assert_eq!(balance, get_fees(&cluster_id).validators_share * ratio * charge);
+
+		balance = Balances::free_balance(VALIDATOR3_ACCOUNT_ID);
+		ratio = Perquintill::from_rational(
+			VALIDATOR3_SCORE,
+			VALIDATOR1_SCORE + VALIDATOR2_SCORE + VALIDATOR3_SCORE,
+		);
+		assert_eq!(balance, get_fees(&cluster_id).validators_share * ratio * charge);
+
+		assert_eq!(
+			report_after.total_customer_charge.transfer,
+			total_left_from_one * report_before.total_customer_charge.transfer
+		);
+		assert_eq!(
+			report_after.total_customer_charge.storage,
+			total_left_from_one * report_before.total_customer_charge.storage
+		);
+		assert_eq!(
+			report_after.total_customer_charge.puts,
+			total_left_from_one * report_before.total_customer_charge.puts
+		);
+		assert_eq!(
+			report_after.total_customer_charge.gets,
+			total_left_from_one * report_before.total_customer_charge.gets
+		);
+	})
+}
+
+#[test]
+fn claim_validator_fees_fails_without_staker_scores() {
+	ExtBuilder.build_and_execute(|| {
+		let cluster_id = ClusterId::from([12; 20]);
+		let era = 100;
+
+		ValidatorFeesPots::<Test>::insert(
 			cluster_id,
 			era,
-			batch_index + 1,
-			payers2,
-		));
+			ValidatorFeesPot {
+				total_fees: 100,
+				total_score: 0,
+				claimed_fees: 0,
+				snapshotted: true,
+			},
+		);
+		ValidatorFeesScores::<Test>::insert((cluster_id, era), VALIDATOR1_ACCOUNT_ID, 0);
 
 		assert_noop!(
-			DdcPayouts::send_rewarding_providers_batch(
-				RuntimeOrigin::signed(dac_account),
+			DdcPayouts::claim_validator_fees(
+				RuntimeOrigin::signed(VALIDATOR1_ACCOUNT_ID),
+				VALIDATOR1_ACCOUNT_ID,
 				cluster_id,
 				era,
-				batch_index,
-				payees.clone(),
 			),
-			Error::<Test>::NotExpectedState
+			Error::<Test>::NoValidatorFees
 		);
 
-		assert_ok!(DdcPayouts::end_charging_customers(
-			RuntimeOrigin::signed(dac_account),
-			cluster_id,
-			era,
-		));
-
 		assert_noop!(
-			DdcPayouts::send_rewarding_providers_batch(
-				RuntimeOrigin::signed(dac_account),
+			DdcPayouts::claim_validator_fees(
+				RuntimeOrigin::signed(VALIDATOR2_ACCOUNT_ID),
+				VALIDATOR2_ACCOUNT_ID,
 				cluster_id,
 				era,
-				batch_index,
-				payees,
 			),
-			Error::<Test>::NotExpectedState
+			Error::<Test>::NoValidatorFees
 		);
 	})
 }
 
 #[test]
-fn send_rewarding_providers_batch_works() {
+fn end_charging_customers_works_zero_fees() {
 	ExtBuilder.build_and_execute(|| {
 		System::set_block_number(1);
 
 		let dac_account = 123u128;
 		let user1 = 1u128;
-		let node1 = 10u128;
-		let node2 = 11u128;
-		let node3 = 12u128;
-		let cluster_id = ClusterId::from([12; 20]);
+		let cluster_id = ClusterId::zero();
 		let era = 100;
 		let max_batch_index = 0;
-		let max_node_batch_index = 1;
 		let batch_index = 0;
-		let batch_node_index = 0;
 		let usage1 = CustomerUsage {
 			transferred_bytes: 23452345,
 			stored_bytes: 3345234523,
-			number_of_puts: 4456456345234523,
-			number_of_gets: 523423,
-		};
-
-		let node_usage1 = NodeUsage {
-			// Storage 1
-			transferred_bytes: usage1.transferred_bytes * 2 / 3,
-			stored_bytes: 0,
-			number_of_puts: usage1.number_of_puts * 2 / 3,
-			number_of_gets: usage1.number_of_gets * 2 / 3,
-		};
-
-		let node_usage2 = NodeUsage {
-			// Storage 2
-			transferred_bytes: 0,
-			stored_bytes: usage1.stored_bytes * 2,
-			number_of_puts: 0,
-			number_of_gets: 0,
-		};
-
-		let node_usage3 = NodeUsage {
-			// Storage 1 + Storage 2
-			transferred_bytes: usage1.transferred_bytes * 2,
-			stored_bytes: usage1.stored_bytes * 3,
-			number_of_puts: usage1.number_of_puts * 2,
-			number_of_gets: usage1.number_of_gets * 2,
-		};
-
-		let total_nodes_usage = NodeUsage {
-			transferred_bytes: node_usage1.transferred_bytes +
-				node_usage2.transferred_bytes +
-				node_usage3.transferred_bytes,
-			stored_bytes: node_usage1.stored_bytes +
-				node_usage2.stored_bytes +
-				node_usage3.stored_bytes,
-			number_of_puts: node_usage1.number_of_puts +
-				node_usage2.number_of_puts +
-				node_usage3.number_of_puts,
-			number_of_gets: node_usage1.number_of_gets +
-				node_usage2.number_of_gets +
-				node_usage3.number_of_gets,
+			number_of_puts: 1,
+			number_of_gets: 1,
 		};
-
-		let payers = vec![(user1, usage1)];
-		let payees1 = vec![(node1, node_usage1.clone()), (node2, node_usage2.clone())];
-		let payees2 = vec![(node3, node_usage3.clone())];
+		let payers = vec![(user1, user1 as BucketId, usage1.clone())];
 		let start_date = NaiveDate::from_ymd_opt(2023, 4, 1).unwrap(); // April 1st
 
 		let time = NaiveTime::from_hms_opt(0, 0, 0).unwrap(); // Midnight
 		let start_era: i64 =
 			DateTime::<Utc>::from_naive_utc_and_offset(start_date.and_time(time), Utc).timestamp();
 		let end_era: i64 = start_era + (30.44 * 24.0 * 3600.0) as i64;
+		let (customers_merkle_root, customers_proofs) =
+			get_batches_commitment(&[(batch_index, &payers)]);
+		let nodes_merkle_root = H256::default();
 
-		assert_ok!(DdcPayouts::set_authorised_caller(RuntimeOrigin::root(), dac_account));
+		assert_ok!(DdcPayouts::set_validator_threshold(RuntimeOrigin::root(), 1));
 
 		assert_ok!(DdcPayouts::begin_billing_report(
 			RuntimeOrigin::signed(dac_account),
@@ -3126,13 +4145,18 @@ fn send_rewarding_providers_batch_works() {
 			era,
 			start_era,
 			end_era,
+			customers_merkle_root,
+			nodes_merkle_root,
+			max_batch_index,
+			0,
+			customers_usage(&payers),
+			NodeUsage::default(),
 		));
 
 		assert_ok!(DdcPayouts::begin_charging_customers(
 			RuntimeOrigin::signed(dac_account),
 			cluster_id,
 			era,
-			max_batch_index,
 		));
 
 		assert_ok!(DdcPayouts::send_charging_customers_batch(
@@ -3141,20 +4165,65 @@ fn send_rewarding_providers_batch_works() {
 			era,
 			batch_index,
 			payers,
+			customers_proofs[0].clone(),
 		));
 
 		let report_before = DdcPayouts::active_billing_reports(cluster_id, era).unwrap();
+		let charge = calculate_charge_for_month(cluster_id, usage1);
+		System::assert_last_event(
+			Event::Charged {
+				cluster_id,
+				era,
+				customer_id: user1,
+				bucket_id: user1 as BucketId,
+				batch_index,
+				amount: charge,
+			}
+			.into(),
+		);
+
+		let mut balance = Balances::free_balance(DdcPayouts::account_id());
+		assert_eq!(balance - Balances::minimum_balance(), charge);
+		assert_eq!(System::events().len(), 4 + 1); // 1 for Currency::transfer
+
 		assert_ok!(DdcPayouts::end_charging_customers(
 			RuntimeOrigin::signed(dac_account),
 			cluster_id,
 			era,
 		));
 
+		System::assert_has_event(Event::ChargingFinished { cluster_id, era }.into());
+		assert_eq!(System::events().len(), 5 + 1);
+
 		let report_after = DdcPayouts::active_billing_reports(cluster_id, era).unwrap();
-		let total_left_from_one = (get_fees(&cluster_id).treasury_share +
-			get_fees(&cluster_id).validators_share +
-			get_fees(&cluster_id).cluster_reserve_share)
-			.left_from_one();
+		assert_eq!(report_after.state, State::CustomersChargedWithFees);
+
+		let fees = get_fees(&cluster_id);
+
+		let total_left_from_one =
+			(fees.treasury_share + fees.validators_share + fees.cluster_reserve_share)
+				.left_from_one();
+
+		assert_eq!(total_left_from_one, Perquintill::one());
+
+		assert_eq!(fees.treasury_share, Perquintill::zero());
+		assert_eq!(fees.validators_share, Perquintill::zero());
+		assert_eq!(fees.cluster_reserve_share, Perquintill::zero());
+
+		balance = Balances::free_balance(TREASURY_ACCOUNT_ID);
+		assert_eq!(balance, 0);
+
+		balance = Balances::free_balance(RESERVE_ACCOUNT_ID);
+		assert_eq!(balance, 0);
+
+		balance = Balances::free_balance(VALIDATOR1_ACCOUNT_ID);
+		assert_eq!(balance, 0);
+
+		balance = Balances::free_balance(VALIDATOR2_ACCOUNT_ID);
+		assert_eq!(balance, 0);
+
+		balance = Balances::free_balance(VALIDATOR3_ACCOUNT_ID);
+		assert_eq!(balance, 0);
 
 		assert_eq!(
 			report_after.total_customer_charge.transfer,
@@ -3172,464 +4241,410 @@ fn send_rewarding_providers_batch_works() {
 			report_after.total_customer_charge.gets,
 			total_left_from_one * report_before.total_customer_charge.gets
 		);
+	})
+}
 
-		assert_ok!(DdcPayouts::begin_rewarding_providers(
-			RuntimeOrigin::signed(dac_account),
-			cluster_id,
-			era,
-			max_node_batch_index,
-			total_nodes_usage.clone(),
-		));
+#[test]
+fn begin_rewarding_providers_fails_uninitialised() {
+	ExtBuilder.build_and_execute(|| {
+		let root_account = 1u128;
+		let dac_account = 2u128;
+		let user1 = 3u128;
+		let cluster_id = ClusterId::from([12; 20]);
+		let era = 100;
+		let max_batch_index = 2;
+		let batch_index = 1;
+		let payers = vec![(user1, user1 as BucketId, CustomerUsage::default())];
+		let node_usage = NodeUsage::default();
+		let start_date = NaiveDate::from_ymd_opt(2023, 4, 1).unwrap(); // April 1st
 
-		assert_ok!(DdcPayouts::send_rewarding_providers_batch(
-			RuntimeOrigin::signed(dac_account),
-			cluster_id,
-			era,
-			batch_node_index,
-			payees1,
-		));
-
-		let ratio1_transfer = Perquintill::from_rational(
-			node_usage1.transferred_bytes,
-			total_nodes_usage.transferred_bytes,
-		);
-		let mut transfer_charge = ratio1_transfer * report_after.total_customer_charge.transfer;
-
-		let ratio1_storage =
-			Perquintill::from_rational(node_usage1.stored_bytes, total_nodes_usage.stored_bytes);
-		let mut storage_charge = ratio1_storage * report_after.total_customer_charge.storage;
-
-		let ratio1_puts = Perquintill::from_rational(
-			node_usage1.number_of_puts,
-			total_nodes_usage.number_of_puts,
-		);
-		let mut puts_charge = ratio1_puts * report_after.total_customer_charge.puts;
-
-		let ratio1_gets = Perquintill::from_rational(
-			node_usage1.number_of_gets,
-			total_nodes_usage.number_of_gets,
-		);
-		let mut gets_charge = ratio1_gets * report_after.total_customer_charge.gets;
-
-		let balance_node1 = Balances::free_balance(node1);
-		assert_eq!(balance_node1, transfer_charge + storage_charge + puts_charge + gets_charge);
-		let mut report_reward = DdcPayouts::active_billing_reports(cluster_id, era).unwrap();
+		let time = NaiveTime::from_hms_opt(0, 0, 0).unwrap(); // Midnight
+		let start_era: i64 =
+			DateTime::<Utc>::from_naive_utc_and_offset(start_date.and_time(time), Utc).timestamp();
+		let end_era: i64 = start_era + (30.44 * 24.0 * 3600.0) as i64;
+		let (customers_merkle_root, customers_proofs) =
+			get_batches_commitment(&[(batch_index, &payers), (batch_index + 1, &payers)]);
+		let nodes_merkle_root = H256::default();
 
-		System::assert_has_event(
-			Event::Rewarded {
+		assert_noop!(
+			DdcPayouts::begin_rewarding_providers(
+				RuntimeOrigin::signed(root_account),
 				cluster_id,
 				era,
-				node_provider_id: node1,
-				batch_index: batch_node_index,
-				rewarded: balance_node1,
-				expected_to_reward: balance_node1,
-			}
-			.into(),
-		);
-
-		let ratio2_transfer = Perquintill::from_rational(
-			node_usage2.transferred_bytes,
-			total_nodes_usage.transferred_bytes,
-		);
-		transfer_charge = ratio2_transfer * report_after.total_customer_charge.transfer;
-
-		let ratio2_storage =
-			Perquintill::from_rational(node_usage2.stored_bytes, total_nodes_usage.stored_bytes);
-		storage_charge = ratio2_storage * report_after.total_customer_charge.storage;
-
-		let ratio2_puts = Perquintill::from_rational(
-			node_usage2.number_of_puts,
-			total_nodes_usage.number_of_puts,
+			),
+			Error::<Test>::Unauthorised
 		);
-		puts_charge = ratio2_puts * report_after.total_customer_charge.puts;
 
-		let ratio2_gets = Perquintill::from_rational(
-			node_usage2.number_of_gets,
-			total_nodes_usage.number_of_gets,
+		assert_noop!(
+			DdcPayouts::begin_rewarding_providers(RuntimeOrigin::root(), cluster_id, era),
+			BadOrigin
 		);
-		gets_charge = ratio2_gets * report_after.total_customer_charge.gets;
 
-		let balance_node2 = Balances::free_balance(node2);
-		assert_eq!(balance_node2, transfer_charge + storage_charge + puts_charge + gets_charge);
-		assert_eq!(report_reward.total_distributed_reward, balance_node1 + balance_node2);
+		assert_ok!(DdcPayouts::set_validator_threshold(RuntimeOrigin::root(), 1));
 
-		System::assert_has_event(
-			Event::Rewarded {
+		assert_noop!(
+			DdcPayouts::begin_rewarding_providers(
+				RuntimeOrigin::signed(dac_account),
 				cluster_id,
 				era,
-				node_provider_id: node2,
-				batch_index: batch_node_index,
-				rewarded: balance_node2,
-				expected_to_reward: balance_node2,
-			}
-			.into(),
+			),
+			Error::<Test>::BillingReportDoesNotExist
 		);
 
-		// batch 2
-		assert_ok!(DdcPayouts::send_rewarding_providers_batch(
+		assert_ok!(DdcPayouts::begin_billing_report(
 			RuntimeOrigin::signed(dac_account),
 			cluster_id,
 			era,
-			batch_node_index + 1,
-			payees2,
+			start_era,
+			end_era,
+			customers_merkle_root,
+			nodes_merkle_root,
+			max_batch_index,
+			max_batch_index,
+			CustomerUsage::default(),
+			node_usage,
 		));
 
-		let ratio3_transfer = Perquintill::from_rational(
-			node_usage3.transferred_bytes,
-			total_nodes_usage.transferred_bytes,
+		assert_noop!(
+			DdcPayouts::begin_rewarding_providers(
+				RuntimeOrigin::signed(dac_account),
+				cluster_id,
+				era,
+			),
+			Error::<Test>::NotExpectedState
 		);
-		transfer_charge = ratio3_transfer * report_after.total_customer_charge.transfer;
-
-		let ratio3_storage =
-			Perquintill::from_rational(node_usage3.stored_bytes, total_nodes_usage.stored_bytes);
-		storage_charge = ratio3_storage * report_after.total_customer_charge.storage;
 
-		let ratio3_puts = Perquintill::from_rational(
-			node_usage3.number_of_puts,
-			total_nodes_usage.number_of_puts,
-		);
-		puts_charge = ratio3_puts * report_after.total_customer_charge.puts;
+		assert_ok!(DdcPayouts::begin_charging_customers(
+			RuntimeOrigin::signed(dac_account),
+			cluster_id,
+			era,
+		));
 
-		let ratio3_gets = Perquintill::from_rational(
-			node_usage3.number_of_gets,
-			total_nodes_usage.number_of_gets,
+		assert_noop!(
+			DdcPayouts::begin_rewarding_providers(
+				RuntimeOrigin::signed(dac_account),
+				cluster_id,
+				era,
+			),
+			Error::<Test>::NotExpectedState
 		);
-		gets_charge = ratio3_gets * report_after.total_customer_charge.gets;
 
-		report_reward = DdcPayouts::active_billing_reports(cluster_id, era).unwrap();
-		let balance_node3 = Balances::free_balance(node3);
-		assert_eq!(balance_node3, transfer_charge + storage_charge + puts_charge + gets_charge);
+		assert_ok!(DdcPayouts::send_charging_customers_batch(
+			RuntimeOrigin::signed(dac_account),
+			cluster_id,
+			era,
+			batch_index,
+			payers.clone(),
+			customers_proofs[0].clone(),
+		));
 
-		System::assert_has_event(
-			Event::Rewarded {
+		assert_noop!(
+			DdcPayouts::begin_rewarding_providers(
+				RuntimeOrigin::signed(dac_account),
 				cluster_id,
 				era,
-				node_provider_id: node3,
-				batch_index: batch_node_index + 1,
-				rewarded: balance_node3,
-				expected_to_reward: balance_node3,
-			}
-			.into(),
-		);
-
-		assert_eq!(
-			report_reward.total_distributed_reward,
-			balance_node1 + balance_node2 + balance_node3
+			),
+			Error::<Test>::NotExpectedState
 		);
 
-		let expected_amount_to_reward = report_reward.total_customer_charge.transfer +
-			report_reward.total_customer_charge.storage +
-			report_reward.total_customer_charge.puts +
-			report_reward.total_customer_charge.gets;
-
-		assert!(expected_amount_to_reward - report_reward.total_distributed_reward <= 20000);
-
-		assert_ok!(DdcPayouts::end_rewarding_providers(
+		assert_ok!(DdcPayouts::send_charging_customers_batch(
 			RuntimeOrigin::signed(dac_account),
 			cluster_id,
 			era,
+			batch_index + 1,
+			payers,
+			customers_proofs[1].clone(),
 		));
+
+		assert_noop!(
+			DdcPayouts::begin_rewarding_providers(
+				RuntimeOrigin::signed(dac_account),
+				cluster_id,
+				era,
+			),
+			Error::<Test>::NotExpectedState
+		);
 	})
 }
 
 #[test]
-fn send_rewarding_providers_batch_100_nodes_small_usage_works() {
+fn begin_rewarding_providers_works() {
 	ExtBuilder.build_and_execute(|| {
 		System::set_block_number(1);
 
-		let num_nodes = 100;
-		let num_users = 5;
 		let dac_account = 123u128;
-		let bank = 1u128;
-		let cluster_id = ONE_CLUSTER_ID;
+		let user1 = 1u128;
+		let cluster_id = ClusterId::from([12; 20]);
 		let era = 100;
-		let user_batch_size = 10;
-		let node_batch_size = 10;
-		let mut batch_user_index = 0;
-		let mut batch_node_index = 0;
+		let max_batch_index = 0;
+		let batch_index = 0;
+		let total_node_usage = NodeUsage::default();
+		let payers = vec![(user1, user1 as BucketId, CustomerUsage::default())];
 		let start_date = NaiveDate::from_ymd_opt(2023, 4, 1).unwrap(); // April 1st
 
 		let time = NaiveTime::from_hms_opt(0, 0, 0).unwrap(); // Midnight
 		let start_era: i64 =
 			DateTime::<Utc>::from_naive_utc_and_offset(start_date.and_time(time), Utc).timestamp();
 		let end_era: i64 = start_era + (30.44 * 24.0 * 3600.0) as i64;
-		let usage1 = CustomerUsage {
-			transferred_bytes: 1024,
-			stored_bytes: 1024,
-			number_of_puts: 1,
-			number_of_gets: 1,
-		};
+		let (customers_merkle_root, customers_proofs) =
+			get_batches_commitment(&[(batch_index, &payers)]);
+		let nodes_merkle_root = H256::default();
 
-		let node_usage1 = NodeUsage {
-			// CDN
-			transferred_bytes: Perquintill::from_float(0.75) * usage1.transferred_bytes,
-			stored_bytes: 0,
-			number_of_puts: Perquintill::from_float(0.75) * usage1.number_of_puts,
-			number_of_gets: Perquintill::from_float(0.75) * usage1.number_of_gets,
-		};
+		assert_ok!(DdcPayouts::set_validator_threshold(RuntimeOrigin::root(), 1));
 
-		let node_usage2 = NodeUsage {
-			// Storage
-			transferred_bytes: 0,
-			stored_bytes: usage1.stored_bytes * 2,
-			number_of_puts: 0,
-			number_of_gets: 0,
-		};
+		assert_ok!(DdcPayouts::begin_billing_report(
+			RuntimeOrigin::signed(dac_account),
+			cluster_id,
+			era,
+			start_era,
+			end_era,
+			customers_merkle_root,
+			nodes_merkle_root,
+			max_batch_index,
+			max_batch_index,
+			CustomerUsage::default(),
+			total_node_usage,
+		));
 
-		let node_usage3 = NodeUsage {
-			// CDN + Storage
-			transferred_bytes: usage1.transferred_bytes * 2,
-			stored_bytes: usage1.stored_bytes * 3,
-			number_of_puts: usage1.number_of_puts * 2,
-			number_of_gets: usage1.number_of_gets * 2,
-		};
+		let mut report = DdcPayouts::active_billing_reports(cluster_id, era).unwrap();
+		assert_eq!(report.state, State::Initialized);
 
-		let mut payees: Vec<Vec<(u128, NodeUsage)>> = Vec::new();
-		let mut node_batch: Vec<(u128, NodeUsage)> = Vec::new();
-		let mut total_nodes_usage = NodeUsage::default();
-		for i in 10..10 + num_nodes {
-			let node_usage = match i % 3 {
-				0 => node_usage1.clone(),
-				1 => node_usage2.clone(),
-				2 => node_usage3.clone(),
-				_ => unreachable!(),
-			};
-			total_nodes_usage.transferred_bytes += node_usage.transferred_bytes;
-			total_nodes_usage.stored_bytes += node_usage.stored_bytes;
-			total_nodes_usage.number_of_puts += node_usage.number_of_puts;
-			total_nodes_usage.number_of_gets += node_usage.number_of_gets;
+		assert_ok!(DdcPayouts::begin_charging_customers(
+			RuntimeOrigin::signed(dac_account),
+			cluster_id,
+			era,
+		));
 
-			node_batch.push((i, node_usage));
-			if node_batch.len() == node_batch_size {
-				payees.push(node_batch.clone());
-				node_batch.clear();
-			}
-		}
-		if !node_batch.is_empty() {
-			payees.push(node_batch.clone());
-		}
+		assert_ok!(DdcPayouts::send_charging_customers_batch(
+			RuntimeOrigin::signed(dac_account),
+			cluster_id,
+			era,
+			batch_index,
+			payers,
+			customers_proofs[0].clone(),
+		));
 
-		let mut total_charge = 0u128;
-		let mut payers: Vec<Vec<(u128, CustomerUsage)>> = Vec::new();
-		let mut user_batch: Vec<(u128, CustomerUsage)> = Vec::new();
-		for user_id in 1000..1000 + num_users {
-			let ratio = match user_id % 5 {
-				0 => Perquintill::one(),
-				1 => Perquintill::from_float(0.5),
-				2 => Perquintill::from_float(2f64),
-				3 => Perquintill::from_float(0.25),
-				4 => Perquintill::from_float(0.001),
-				_ => unreachable!(),
-			};
+		assert_ok!(DdcPayouts::end_charging_customers(
+			RuntimeOrigin::signed(dac_account),
+			cluster_id,
+			era,
+		));
 
-			let mut user_usage = usage1.clone();
-			user_usage.transferred_bytes = ratio * user_usage.transferred_bytes;
-			user_usage.stored_bytes = ratio * user_usage.stored_bytes;
-			user_usage.number_of_puts = ratio * user_usage.number_of_puts;
-			user_usage.number_of_gets = ratio * user_usage.number_of_gets;
+		assert_ok!(DdcPayouts::begin_rewarding_providers(
+			RuntimeOrigin::signed(dac_account),
+			cluster_id,
+			era,
+		));
 
-			let expected_charge = calculate_charge_for_month(cluster_id, user_usage.clone());
-			Balances::transfer(
-				RuntimeOrigin::signed(bank),
-				user_id,
-				(expected_charge * 2).max(Balances::minimum_balance()),
-			)
-			.unwrap();
-			total_charge += expected_charge;
+		System::assert_last_event(Event::RewardingStarted { cluster_id, era }.into());
 
-			user_batch.push((user_id, user_usage));
-			if user_batch.len() == user_batch_size {
-				payers.push(user_batch.clone());
-				user_batch.clear();
-			}
-		}
-		if !user_batch.is_empty() {
-			payers.push(user_batch.clone());
-		}
+		report = DdcPayouts::active_billing_reports(cluster_id, era).unwrap();
+		assert_eq!(report.state, State::RewardingProviders);
+	})
+}
+
+#[test]
+fn send_rewarding_providers_batch_fails_uninitialised() {
+	ExtBuilder.build_and_execute(|| {
+		let root_account = 1u128;
+		let dac_account = 2u128;
+		let user1 = 3u128;
+		let user2 = 4u128;
+		let node1 = 33u128;
+		let cluster_id = ClusterId::from([12; 20]);
+		let era = 100;
+		let max_batch_index = 1;
+		let batch_index = 0;
+		let payers1 = vec![(user1, user1 as BucketId, CustomerUsage::default())];
+		let payers2 = vec![(user2, user2 as BucketId, CustomerUsage::default())];
+		let payees = vec![(node1, node_key(node1), NodeUsage::default())];
+		let start_date = NaiveDate::from_ymd_opt(2023, 4, 1).unwrap(); // April 1st
+
+		let time = NaiveTime::from_hms_opt(0, 0, 0).unwrap(); // Midnight
+		let start_era: i64 =
+			DateTime::<Utc>::from_naive_utc_and_offset(start_date.and_time(time), Utc).timestamp();
+		let end_era: i64 = start_era + (30.44 * 24.0 * 3600.0) as i64;
+		let (customers_merkle_root, customers_proofs) =
+			get_batches_commitment(&[(batch_index, &payers1), (batch_index + 1, &payers2)]);
+		let (nodes_merkle_root, nodes_proofs) = get_batches_commitment(&[(batch_index, &payees)]);
+
+		assert_noop!(
+			DdcPayouts::send_rewarding_providers_batch(
+				RuntimeOrigin::signed(root_account),
+				cluster_id,
+				era,
+				batch_index,
+				payees.clone(),
+				nodes_proofs[0].clone(),
+			),
+			Error::<Test>::Unauthorised
+		);
+
+		assert_noop!(
+			DdcPayouts::send_rewarding_providers_batch(
+				RuntimeOrigin::root(),
+				cluster_id,
+				era,
+				batch_index,
+				payees.clone(),
+				nodes_proofs[0].clone(),
+			),
+			BadOrigin
+		);
+
+		assert_ok!(DdcPayouts::set_validator_threshold(RuntimeOrigin::root(), 1));
+
+		assert_noop!(
+			DdcPayouts::send_rewarding_providers_batch(
+				RuntimeOrigin::signed(dac_account),
+				cluster_id,
+				era,
+				batch_index,
+				payees.clone(),
+				nodes_proofs[0].clone(),
+			),
+			Error::<Test>::BillingReportDoesNotExist
+		);
 
-		assert_ok!(DdcPayouts::set_authorised_caller(RuntimeOrigin::root(), dac_account));
 		assert_ok!(DdcPayouts::begin_billing_report(
 			RuntimeOrigin::signed(dac_account),
 			cluster_id,
 			era,
 			start_era,
 			end_era,
+			customers_merkle_root,
+			nodes_merkle_root,
+			max_batch_index,
+			0,
+			CustomerUsage::default(),
+			NodeUsage::default(),
 		));
+
+		assert_noop!(
+			DdcPayouts::send_rewarding_providers_batch(
+				RuntimeOrigin::signed(dac_account),
+				cluster_id,
+				era,
+				batch_index,
+				payees.clone(),
+				nodes_proofs[0].clone(),
+			),
+			Error::<Test>::NotExpectedState
+		);
+
 		assert_ok!(DdcPayouts::begin_charging_customers(
 			RuntimeOrigin::signed(dac_account),
 			cluster_id,
 			era,
-			(payers.len() - 1) as u16,
 		));
 
-		for batch in payers.iter() {
-			assert_ok!(DdcPayouts::send_charging_customers_batch(
+		assert_noop!(
+			DdcPayouts::send_rewarding_providers_batch(
 				RuntimeOrigin::signed(dac_account),
 				cluster_id,
 				era,
-				batch_user_index,
-				batch.to_vec(),
-			));
-
-			for (customer_id, usage) in batch.iter() {
-				let charge = calculate_charge_for_month(cluster_id, usage.clone());
-
-				System::assert_has_event(
-					Event::Charged {
-						cluster_id,
-						era,
-						customer_id: *customer_id,
-						batch_index: batch_user_index,
-						amount: charge,
-					}
-					.into(),
-				);
-			}
-			batch_user_index += 1;
-		}
-
-		let report_before = DdcPayouts::active_billing_reports(cluster_id, era).unwrap();
-		let balance1 = Balances::free_balance(report_before.vault);
-		let balance2 = Balances::free_balance(DdcPayouts::account_id());
-		assert_eq!(balance1, balance2);
-		assert_eq!(report_before.vault, DdcPayouts::account_id());
-		assert_eq!(balance1 - Balances::minimum_balance(), total_charge);
+				batch_index,
+				payees.clone(),
+				nodes_proofs[0].clone(),
+			),
+			Error::<Test>::NotExpectedState
+		);
 
-		assert_ok!(DdcPayouts::end_charging_customers(
+		assert_ok!(DdcPayouts::send_charging_customers_batch(
 			RuntimeOrigin::signed(dac_account),
 			cluster_id,
 			era,
+			batch_index,
+			payers1,
+			customers_proofs[0].clone(),
 		));
 
-		let report_after = DdcPayouts::active_billing_reports(cluster_id, era).unwrap();
-		let total_left_from_one = (get_fees(&cluster_id).treasury_share +
-			get_fees(&cluster_id).validators_share +
-			get_fees(&cluster_id).cluster_reserve_share)
-			.left_from_one();
-
-		let total_charge = report_after.total_customer_charge.transfer +
-			report_before.total_customer_charge.storage +
-			report_before.total_customer_charge.puts +
-			report_before.total_customer_charge.gets;
-		let balance_after = Balances::free_balance(DdcPayouts::account_id());
-		assert_eq!(total_charge, balance_after - Balances::minimum_balance());
-
-		assert_eq!(
-			report_after.total_customer_charge.transfer,
-			total_left_from_one * report_before.total_customer_charge.transfer
-		);
-		assert_eq!(
-			report_after.total_customer_charge.storage,
-			total_left_from_one * report_before.total_customer_charge.storage
-		);
-		assert_eq!(
-			report_after.total_customer_charge.puts,
-			total_left_from_one * report_before.total_customer_charge.puts
-		);
-		assert_eq!(
-			report_after.total_customer_charge.gets,
-			total_left_from_one * report_before.total_customer_charge.gets
+		assert_noop!(
+			DdcPayouts::send_rewarding_providers_batch(
+				RuntimeOrigin::signed(dac_account),
+				cluster_id,
+				era,
+				batch_index,
+				payees.clone(),
+				nodes_proofs[0].clone(),
+			),
+			Error::<Test>::NotExpectedState
 		);
 
-		assert_ok!(DdcPayouts::begin_rewarding_providers(
+		assert_ok!(DdcPayouts::send_charging_customers_batch(
 			RuntimeOrigin::signed(dac_account),
 			cluster_id,
 			era,
-			(payees.len() - 1) as u16,
-			total_nodes_usage.clone(),
+			batch_index + 1,
+			payers2,
+			customers_proofs[1].clone(),
 		));
 
-		for batch in payees.iter() {
-			let before_batch = Balances::free_balance(DdcPayouts::account_id());
-			assert_ok!(DdcPayouts::send_rewarding_providers_batch(
+		assert_noop!(
+			DdcPayouts::send_rewarding_providers_batch(
 				RuntimeOrigin::signed(dac_account),
 				cluster_id,
 				era,
-				batch_node_index,
-				batch.to_vec(),
-			));
+				batch_index,
+				payees.clone(),
+				nodes_proofs[0].clone(),
+			),
+			Error::<Test>::NotExpectedState
+		);
 
-			let mut batch_charge = 0;
-			for (node1, node_usage1) in batch.iter() {
-				let ratio1_transfer = Perquintill::from_rational(
-					node_usage1.transferred_bytes,
-					total_nodes_usage.transferred_bytes,
-				);
-				let transfer_charge = ratio1_transfer * report_after.total_customer_charge.transfer;
+		assert_ok!(DdcPayouts::end_charging_customers(
+			RuntimeOrigin::signed(dac_account),
+			cluster_id,
+			era,
+		));
 
-				let ratio1_storage = Perquintill::from_rational(
-					node_usage1.stored_bytes,
-					total_nodes_usage.stored_bytes,
-				);
-				let storage_charge = ratio1_storage * report_after.total_customer_charge.storage;
+		assert_noop!(
+			DdcPayouts::send_rewarding_providers_batch(
+				RuntimeOrigin::signed(dac_account),
+				cluster_id,
+				era,
+				batch_index,
+				payees,
+				nodes_proofs[0].clone(),
+			),
+			Error::<Test>::NotExpectedState
+		);
+	})
+}
 
-				let ratio1_puts = Perquintill::from_rational(
-					node_usage1.number_of_puts,
-					total_nodes_usage.number_of_puts,
-				);
-				let puts_charge = ratio1_puts * report_after.total_customer_charge.puts;
+#[test]
+fn send_rewarding_providers_batch_works() {
+	ExtBuilder.build_and_execute(|| {
+		System::set_block_number(1);
 
-				let ratio1_gets = Perquintill::from_rational(
-					node_usage1.number_of_gets,
-					total_nodes_usage.number_of_gets,
-				);
-				let gets_charge = ratio1_gets * report_after.total_customer_charge.gets;
-
-				let balance_node1 = Balances::free_balance(node1);
-				assert!(
-					(transfer_charge + storage_charge + puts_charge + gets_charge) - balance_node1 <
-						MAX_DUST.into()
-				);
-
-				batch_charge += transfer_charge + storage_charge + puts_charge + gets_charge;
-			}
-			let after_batch = Balances::free_balance(DdcPayouts::account_id());
-			assert!(batch_charge + after_batch - before_batch < MAX_DUST.into());
-
-			batch_node_index += 1;
-		}
-		assert!(Balances::free_balance(DdcPayouts::account_id()) < MAX_DUST.into());
-	})
-}
-
-#[test]
-fn send_rewarding_providers_batch_100_nodes_large_usage_works() {
-	ExtBuilder.build_and_execute(|| {
-		System::set_block_number(1);
-
-		let start_date = NaiveDate::from_ymd_opt(2023, 4, 1).unwrap(); // April 1st
-
-		let time = NaiveTime::from_hms_opt(0, 0, 0).unwrap(); // Midnight
-		let start_era: i64 =
-			DateTime::<Utc>::from_naive_utc_and_offset(start_date.and_time(time), Utc).timestamp();
-		let end_era: i64 = start_era + (30.44 * 24.0 * 3600.0) as i64;
-		let num_nodes = 100;
-		let num_users = 5;
 		let dac_account = 123u128;
-		let bank = 1u128;
-		let cluster_id = ONE_CLUSTER_ID;
+		let user1 = 1u128;
+		let node1 = 10u128;
+		let node2 = 11u128;
+		let node3 = 12u128;
+		let cluster_id = ClusterId::from([12; 20]);
 		let era = 100;
-		let user_batch_size = 10;
-		let node_batch_size = 10;
-		let mut batch_user_index = 0;
-		let mut batch_node_index = 0;
+		let max_batch_index = 0;
+		let max_node_batch_index = 1;
+		let batch_index = 0;
+		let batch_node_index = 0;
 		let usage1 = CustomerUsage {
-			transferred_bytes: 1024,
-			stored_bytes: 1024,
-			number_of_puts: 1,
-			number_of_gets: 1,
+			transferred_bytes: 23452345,
+			stored_bytes: 3345234523,
+			number_of_puts: 4456456345234523,
+			number_of_gets: 523423,
 		};
 
 		let node_usage1 = NodeUsage {
-			// CDN
-			transferred_bytes: Perquintill::from_float(0.75) * usage1.transferred_bytes,
+			// Storage 1
+			transferred_bytes: usage1.transferred_bytes * 2 / 3,
 			stored_bytes: 0,
-			number_of_puts: Perquintill::from_float(0.75) * usage1.number_of_puts,
-			number_of_gets: Perquintill::from_float(0.75) * usage1.number_of_gets,
+			number_of_puts: usage1.number_of_puts * 2 / 3,
+			number_of_gets: usage1.number_of_gets * 2 / 3,
 		};
 
 		let node_usage2 = NodeUsage {
-			// Storage
+			// Storage 2
 			transferred_bytes: 0,
 			stored_bytes: usage1.stored_bytes * 2,
 			number_of_puts: 0,
@@ -3637,137 +4652,79 @@ fn send_rewarding_providers_batch_100_nodes_large_usage_works() {
 		};
 
 		let node_usage3 = NodeUsage {
-			// CDN + Storage
+			// Storage 1 + Storage 2
 			transferred_bytes: usage1.transferred_bytes * 2,
 			stored_bytes: usage1.stored_bytes * 3,
 			number_of_puts: usage1.number_of_puts * 2,
 			number_of_gets: usage1.number_of_gets * 2,
 		};
 
-		let mut payees: Vec<Vec<(u128, NodeUsage)>> = Vec::new();
-		let mut node_batch: Vec<(u128, NodeUsage)> = Vec::new();
-		let mut total_nodes_usage = NodeUsage::default();
-		for i in 10..10 + num_nodes {
-			let ratio = match i % 5 {
-				0 => Perquintill::from_float(1_000_000.0),
-				1 => Perquintill::from_float(10_000_000.0),
-				2 => Perquintill::from_float(100_000_000.0),
-				3 => Perquintill::from_float(1_000_000_000.0),
-				4 => Perquintill::from_float(10_000_000_000.0),
-				_ => unreachable!(),
-			};
-			let mut node_usage = match i % 3 {
-				0 => node_usage1.clone(),
-				1 => node_usage2.clone(),
-				2 => node_usage3.clone(),
-				_ => unreachable!(),
-			};
-			node_usage.transferred_bytes = ratio * node_usage.transferred_bytes;
-			node_usage.stored_bytes = ratio * node_usage.stored_bytes;
-			node_usage.number_of_puts = ratio * node_usage.number_of_puts;
-			node_usage.number_of_gets = ratio * node_usage.number_of_gets;
-
-			total_nodes_usage.transferred_bytes += node_usage.transferred_bytes;
-			total_nodes_usage.stored_bytes += node_usage.stored_bytes;
-			total_nodes_usage.number_of_puts += node_usage.number_of_puts;
-			total_nodes_usage.number_of_gets += node_usage.number_of_gets;
-
-			node_batch.push((i, node_usage));
-			if node_batch.len() == node_batch_size {
-				payees.push(node_batch.clone());
-				node_batch.clear();
-			}
-		}
-		if !node_batch.is_empty() {
-			payees.push(node_batch.clone());
-		}
-
-		let mut total_charge = 0u128;
-		let mut payers: Vec<Vec<(u128, CustomerUsage)>> = Vec::new();
-		let mut user_batch: Vec<(u128, CustomerUsage)> = Vec::new();
-		for user_id in 1000..1000 + num_users {
-			let ratio = match user_id % 5 {
-				0 => Perquintill::from_float(1_000_000.0),
-				1 => Perquintill::from_float(10_000_000.0),
-				2 => Perquintill::from_float(100_000_000.0),
-				3 => Perquintill::from_float(1_000_000_000.0),
-				4 => Perquintill::from_float(10_000_000_000.0),
-				_ => unreachable!(),
-			};
+		let total_nodes_usage = NodeUsage {
+			transferred_bytes: node_usage1.transferred_bytes +
+				node_usage2.transferred_bytes +
+				node_usage3.transferred_bytes,
+			stored_bytes: node_usage1.stored_bytes +
+				node_usage2.stored_bytes +
+				node_usage3.stored_bytes,
+			number_of_puts: node_usage1.number_of_puts +
+				node_usage2.number_of_puts +
+				node_usage3.number_of_puts,
+			number_of_gets: node_usage1.number_of_gets +
+				node_usage2.number_of_gets +
+				node_usage3.number_of_gets,
+		};
 
-			let mut user_usage = usage1.clone();
-			user_usage.transferred_bytes = ratio * user_usage.transferred_bytes;
-			user_usage.stored_bytes = ratio * user_usage.stored_bytes;
-			user_usage.number_of_puts = ratio * user_usage.number_of_puts;
-			user_usage.number_of_gets = ratio * user_usage.number_of_gets;
+		let payers = vec![(user1, user1 as BucketId, usage1)];
+		let payees1 = vec![
+			(node1, node_key(node1), node_usage1.clone()),
+			(node2, node_key(node2), node_usage2.clone()),
+		];
+		let payees2 = vec![(node3, node_key(node3), node_usage3.clone())];
+		let start_date = NaiveDate::from_ymd_opt(2023, 4, 1).unwrap(); // April 1st
 
-			let expected_charge = calculate_charge_for_month(cluster_id, user_usage.clone());
-			Balances::transfer(
-				RuntimeOrigin::signed(bank),
-				user_id,
-				(expected_charge * 2).max(Balances::minimum_balance()),
-			)
-			.unwrap();
-			total_charge += expected_charge;
+		let time = NaiveTime::from_hms_opt(0, 0, 0).unwrap(); // Midnight
+		let start_era: i64 =
+			DateTime::<Utc>::from_naive_utc_and_offset(start_date.and_time(time), Utc).timestamp();
+		let end_era: i64 = start_era + (30.44 * 24.0 * 3600.0) as i64;
+		let (customers_merkle_root, customers_proofs) =
+			get_batches_commitment(&[(batch_index, &payers)]);
+		let (nodes_merkle_root, nodes_proofs) = get_batches_commitment(&[
+			(batch_node_index, &payees1),
+			(batch_node_index + 1, &payees2),
+		]);
 
-			user_batch.push((user_id, user_usage));
-			if user_batch.len() == user_batch_size {
-				payers.push(user_batch.clone());
-				user_batch.clear();
-			}
-		}
-		if !user_batch.is_empty() {
-			payers.push(user_batch.clone());
-		}
+		assert_ok!(DdcPayouts::set_validator_threshold(RuntimeOrigin::root(), 1));
 
-		assert_ok!(DdcPayouts::set_authorised_caller(RuntimeOrigin::root(), dac_account));
 		assert_ok!(DdcPayouts::begin_billing_report(
 			RuntimeOrigin::signed(dac_account),
 			cluster_id,
 			era,
 			start_era,
 			end_era,
+			customers_merkle_root,
+			nodes_merkle_root,
+			max_batch_index,
+			max_node_batch_index,
+			customers_usage(&payers),
+			total_nodes_usage.clone(),
 		));
+
 		assert_ok!(DdcPayouts::begin_charging_customers(
 			RuntimeOrigin::signed(dac_account),
 			cluster_id,
 			era,
-			(payers.len() - 1) as u16,
 		));
 
-		for batch in payers.iter() {
-			assert_ok!(DdcPayouts::send_charging_customers_batch(
-				RuntimeOrigin::signed(dac_account),
-				cluster_id,
-				era,
-				batch_user_index,
-				batch.to_vec(),
-			));
-
-			for (customer_id, usage) in batch.iter() {
-				let charge = calculate_charge_for_month(cluster_id, usage.clone());
-
-				System::assert_has_event(
-					Event::Charged {
-						cluster_id,
-						era,
-						customer_id: *customer_id,
-						batch_index: batch_user_index,
-						amount: charge,
-					}
-					.into(),
-				);
-			}
-			batch_user_index += 1;
-		}
+		assert_ok!(DdcPayouts::send_charging_customers_batch(
+			RuntimeOrigin::signed(dac_account),
+			cluster_id,
+			era,
+			batch_index,
+			payers,
+			customers_proofs[0].clone(),
+		));
 
 		let report_before = DdcPayouts::active_billing_reports(cluster_id, era).unwrap();
-		let balance1 = Balances::free_balance(report_before.vault);
-		let balance2 = Balances::free_balance(DdcPayouts::account_id());
-		assert_eq!(balance1, balance2);
-		assert_eq!(report_before.vault, DdcPayouts::account_id());
-		assert_eq!(balance1 - Balances::minimum_balance(), total_charge);
-
 		assert_ok!(DdcPayouts::end_charging_customers(
 			RuntimeOrigin::signed(dac_account),
 			cluster_id,
@@ -3780,13 +4737,6 @@ fn send_rewarding_providers_batch_100_nodes_large_usage_works() {
 			get_fees(&cluster_id).cluster_reserve_share)
 			.left_from_one();
 
-		let total_charge = report_after.total_customer_charge.transfer +
-			report_before.total_customer_charge.storage +
-			report_before.total_customer_charge.puts +
-			report_before.total_customer_charge.gets;
-		let balance_after = Balances::free_balance(DdcPayouts::account_id());
-		assert_eq!(total_charge, balance_after - Balances::minimum_balance());
-
 		assert_eq!(
 			report_after.total_customer_charge.transfer,
 			total_left_from_one * report_before.total_customer_charge.transfer
@@ -3808,74 +4758,166 @@ fn send_rewarding_providers_batch_100_nodes_large_usage_works() {
 			RuntimeOrigin::signed(dac_account),
 			cluster_id,
 			era,
-			(payees.len() - 1) as u16,
-			total_nodes_usage.clone(),
 		));
 
-		for batch in payees.iter() {
-			let before_batch = Balances::free_balance(DdcPayouts::account_id());
-			assert_ok!(DdcPayouts::send_rewarding_providers_batch(
-				RuntimeOrigin::signed(dac_account),
-				cluster_id,
-				era,
-				batch_node_index,
-				batch.to_vec(),
-			));
+		assert_ok!(DdcPayouts::send_rewarding_providers_batch(
+			RuntimeOrigin::signed(dac_account),
+			cluster_id,
+			era,
+			batch_node_index,
+			payees1,
+			nodes_proofs[0].clone(),
+		));
 
-			let mut batch_charge = 0;
-			for (node1, node_usage1) in batch.iter() {
-				let ratio1_transfer = Perquintill::from_rational(
-					node_usage1.transferred_bytes,
-					total_nodes_usage.transferred_bytes,
-				);
-				let transfer_charge = ratio1_transfer * report_after.total_customer_charge.transfer;
+		let ratio1_transfer = Perquintill::from_rational(
+			node_usage1.transferred_bytes,
+			total_nodes_usage.transferred_bytes,
+		);
+		let mut transfer_charge = ratio1_transfer * report_after.total_customer_charge.transfer;
 
-				let ratio1_storage = Perquintill::from_rational(
-					node_usage1.stored_bytes,
-					total_nodes_usage.stored_bytes,
-				);
-				let storage_charge = ratio1_storage * report_after.total_customer_charge.storage;
+		let ratio1_storage =
+			Perquintill::from_rational(node_usage1.stored_bytes, total_nodes_usage.stored_bytes);
+		let mut storage_charge = ratio1_storage * report_after.total_customer_charge.storage;
 
-				let ratio1_puts = Perquintill::from_rational(
-					node_usage1.number_of_puts,
-					total_nodes_usage.number_of_puts,
-				);
-				let puts_charge = ratio1_puts * report_after.total_customer_charge.puts;
+		let ratio1_puts = Perquintill::from_rational(
+			node_usage1.number_of_puts,
+			total_nodes_usage.number_of_puts,
+		);
+		let mut puts_charge = ratio1_puts * report_after.total_customer_charge.puts;
 
-				let ratio1_gets = Perquintill::from_rational(
-					node_usage1.number_of_gets,
-					total_nodes_usage.number_of_gets,
-				);
-				let gets_charge = ratio1_gets * report_after.total_customer_charge.gets;
+		let ratio1_gets = Perquintill::from_rational(
+			node_usage1.number_of_gets,
+			total_nodes_usage.number_of_gets,
+		);
+		let mut gets_charge = ratio1_gets * report_after.total_customer_charge.gets;
 
-				let balance_node1 = Balances::free_balance(node1);
-				assert!(
-					(transfer_charge + storage_charge + puts_charge + gets_charge) - balance_node1 <
-						MAX_DUST.into()
-				);
+		let balance_node1 = Balances::free_balance(node1);
+		assert_eq!(balance_node1, transfer_charge + storage_charge + puts_charge + gets_charge);
+		let mut report_reward = DdcPayouts::active_billing_reports(cluster_id, era).unwrap();
 
-				batch_charge += transfer_charge + storage_charge + puts_charge + gets_charge;
+		System::assert_has_event(
+			Event::Rewarded {
+				cluster_id,
+				era,
+				node_provider_id: node1,
+				batch_index: batch_node_index,
+				rewarded: balance_node1,
+				expected_to_reward: balance_node1,
 			}
-			let after_batch = Balances::free_balance(DdcPayouts::account_id());
-			assert!(batch_charge + after_batch - before_batch < MAX_DUST.into());
+			.into(),
+		);
 
-			batch_node_index += 1;
-		}
-		assert!(Balances::free_balance(DdcPayouts::account_id()) < MAX_DUST.into());
+		let ratio2_transfer = Perquintill::from_rational(
+			node_usage2.transferred_bytes,
+			total_nodes_usage.transferred_bytes,
+		);
+		transfer_charge = ratio2_transfer * report_after.total_customer_charge.transfer;
+
+		let ratio2_storage =
+			Perquintill::from_rational(node_usage2.stored_bytes, total_nodes_usage.stored_bytes);
+		storage_charge = ratio2_storage * report_after.total_customer_charge.storage;
+
+		let ratio2_puts = Perquintill::from_rational(
+			node_usage2.number_of_puts,
+			total_nodes_usage.number_of_puts,
+		);
+		puts_charge = ratio2_puts * report_after.total_customer_charge.puts;
+
+		let ratio2_gets = Perquintill::from_rational(
+			node_usage2.number_of_gets,
+			total_nodes_usage.number_of_gets,
+		);
+		gets_charge = ratio2_gets * report_after.total_customer_charge.gets;
+
+		let balance_node2 = Balances::free_balance(node2);
+		assert_eq!(balance_node2, transfer_charge + storage_charge + puts_charge + gets_charge);
+		assert_eq!(report_reward.total_distributed_reward, balance_node1 + balance_node2);
+
+		System::assert_has_event(
+			Event::Rewarded {
+				cluster_id,
+				era,
+				node_provider_id: node2,
+				batch_index: batch_node_index,
+				rewarded: balance_node2,
+				expected_to_reward: balance_node2,
+			}
+			.into(),
+		);
+
+		// batch 2
+		assert_ok!(DdcPayouts::send_rewarding_providers_batch(
+			RuntimeOrigin::signed(dac_account),
+			cluster_id,
+			era,
+			batch_node_index + 1,
+			payees2,
+			nodes_proofs[1].clone(),
+		));
+
+		let ratio3_transfer = Perquintill::from_rational(
+			node_usage3.transferred_bytes,
+			total_nodes_usage.transferred_bytes,
+		);
+		transfer_charge = ratio3_transfer * report_after.total_customer_charge.transfer;
+
+		let ratio3_storage =
+			Perquintill::from_rational(node_usage3.stored_bytes, total_nodes_usage.stored_bytes);
+		storage_charge = ratio3_storage * report_after.total_customer_charge.storage;
+
+		let ratio3_puts = Perquintill::from_rational(
+			node_usage3.number_of_puts,
+			total_nodes_usage.number_of_puts,
+		);
+		puts_charge = ratio3_puts * report_after.total_customer_charge.puts;
+
+		let ratio3_gets = Perquintill::from_rational(
+			node_usage3.number_of_gets,
+			total_nodes_usage.number_of_gets,
+		);
+		gets_charge = ratio3_gets * report_after.total_customer_charge.gets;
+
+		report_reward = DdcPayouts::active_billing_reports(cluster_id, era).unwrap();
+		let balance_node3 = Balances::free_balance(node3);
+		assert_eq!(balance_node3, transfer_charge + storage_charge + puts_charge + gets_charge);
+
+		System::assert_has_event(
+			Event::Rewarded {
+				cluster_id,
+				era,
+				node_provider_id: node3,
+				batch_index: batch_node_index + 1,
+				rewarded: balance_node3,
+				expected_to_reward: balance_node3,
+			}
+			.into(),
+		);
+
+		assert_eq!(
+			report_reward.total_distributed_reward,
+			balance_node1 + balance_node2 + balance_node3
+		);
+
+		let expected_amount_to_reward = report_reward.total_customer_charge.transfer +
+			report_reward.total_customer_charge.storage +
+			report_reward.total_customer_charge.puts +
+			report_reward.total_customer_charge.gets;
+
+		assert!(expected_amount_to_reward - report_reward.total_distributed_reward <= 20000);
+
+		assert_ok!(DdcPayouts::end_rewarding_providers(
+			RuntimeOrigin::signed(dac_account),
+			cluster_id,
+			era,
+		));
 	})
 }
 
 #[test]
-fn send_rewarding_providers_batch_100_nodes_small_large_usage_works() {
+fn send_rewarding_providers_batch_100_nodes_small_usage_works() {
 	ExtBuilder.build_and_execute(|| {
 		System::set_block_number(1);
 
-		let start_date = NaiveDate::from_ymd_opt(2023, 4, 1).unwrap(); // April 1st
-
-		let time = NaiveTime::from_hms_opt(0, 0, 0).unwrap(); // Midnight
-		let start_era: i64 =
-			DateTime::<Utc>::from_naive_utc_and_offset(start_date.and_time(time), Utc).timestamp();
-		let end_era: i64 = start_era + (30.44 * 24.0 * 3600.0) as i64;
 		let num_nodes = 100;
 		let num_users = 5;
 		let dac_account = 123u128;
@@ -3886,6 +4928,12 @@ fn send_rewarding_providers_batch_100_nodes_small_large_usage_works() {
 		let node_batch_size = 10;
 		let mut batch_user_index = 0;
 		let mut batch_node_index = 0;
+		let start_date = NaiveDate::from_ymd_opt(2023, 4, 1).unwrap(); // April 1st
+
+		let time = NaiveTime::from_hms_opt(0, 0, 0).unwrap(); // Midnight
+		let start_era: i64 =
+			DateTime::<Utc>::from_naive_utc_and_offset(start_date.and_time(time), Utc).timestamp();
+		let end_era: i64 = start_era + (30.44 * 24.0 * 3600.0) as i64;
 		let usage1 = CustomerUsage {
 			transferred_bytes: 1024,
 			stored_bytes: 1024,
@@ -3917,35 +4965,22 @@ fn send_rewarding_providers_batch_100_nodes_small_large_usage_works() {
 			number_of_gets: usage1.number_of_gets * 2,
 		};
 
-		let mut payees: Vec<Vec<(u128, NodeUsage)>> = Vec::new();
-		let mut node_batch: Vec<(u128, NodeUsage)> = Vec::new();
+		let mut payees: Vec<Vec<(u128, NodePubKey, NodeUsage)>> = Vec::new();
+		let mut node_batch: Vec<(u128, NodePubKey, NodeUsage)> = Vec::new();
 		let mut total_nodes_usage = NodeUsage::default();
 		for i in 10..10 + num_nodes {
-			let ratio = match i % 5 {
-				0 => Perquintill::from_float(1_000_000.0),
-				1 => Perquintill::from_float(0.5),
-				2 => Perquintill::from_float(100_000_000.0),
-				3 => Perquintill::from_float(0.25),
-				4 => Perquintill::from_float(10_000_000_000.0),
-				_ => unreachable!(),
-			};
-			let mut node_usage = match i % 3 {
+			let node_usage = match i % 3 {
 				0 => node_usage1.clone(),
 				1 => node_usage2.clone(),
 				2 => node_usage3.clone(),
 				_ => unreachable!(),
 			};
-			node_usage.transferred_bytes = ratio * node_usage.transferred_bytes;
-			node_usage.stored_bytes = ratio * node_usage.stored_bytes;
-			node_usage.number_of_puts = ratio * node_usage.number_of_puts;
-			node_usage.number_of_gets = ratio * node_usage.number_of_gets;
-
 			total_nodes_usage.transferred_bytes += node_usage.transferred_bytes;
 			total_nodes_usage.stored_bytes += node_usage.stored_bytes;
 			total_nodes_usage.number_of_puts += node_usage.number_of_puts;
 			total_nodes_usage.number_of_gets += node_usage.number_of_gets;
 
-			node_batch.push((i, node_usage));
+			node_batch.push((i, node_key(i), node_usage));
 			if node_batch.len() == node_batch_size {
 				payees.push(node_batch.clone());
 				node_batch.clear();
@@ -3956,15 +4991,15 @@ fn send_rewarding_providers_batch_100_nodes_small_large_usage_works() {
 		}
 
 		let mut total_charge = 0u128;
-		let mut payers: Vec<Vec<(u128, CustomerUsage)>> = Vec::new();
-		let mut user_batch: Vec<(u128, CustomerUsage)> = Vec::new();
+		let mut payers: Vec<Vec<(u128, BucketId, CustomerUsage)>> = Vec::new();
+		let mut user_batch: Vec<(u128, BucketId, CustomerUsage)> = Vec::new();
 		for user_id in 1000..1000 + num_users {
 			let ratio = match user_id % 5 {
-				0 => Perquintill::from_float(1_000_000.0),
-				1 => Perquintill::from_float(10_000_000.0),
-				2 => Perquintill::from_float(100_000_000.0),
-				3 => Perquintill::from_float(1_000_000_000.0),
-				4 => Perquintill::from_float(10_000_000_000.0),
+				0 => Perquintill::one(),
+				1 => Perquintill::from_float(0.5),
+				2 => Perquintill::from_float(2f64),
+				3 => Perquintill::from_float(0.25),
+				4 => Perquintill::from_float(0.001),
 				_ => unreachable!(),
 			};
 
@@ -3983,7 +5018,7 @@ fn send_rewarding_providers_batch_100_nodes_small_large_usage_works() {
 			.unwrap();
 			total_charge += expected_charge;
 
-			user_batch.push((user_id, user_usage));
+			user_batch.push((user_id, user_id as BucketId, user_usage));
 			if user_batch.len() == user_batch_size {
 				payers.push(user_batch.clone());
 				user_batch.clear();
@@ -3993,880 +5028,3931 @@ fn send_rewarding_providers_batch_100_nodes_small_large_usage_works() {
 			payers.push(user_batch.clone());
 		}
 
-		assert_ok!(DdcPayouts::set_authorised_caller(RuntimeOrigin::root(), dac_account));
-		assert_ok!(DdcPayouts::begin_billing_report(
-			RuntimeOrigin::signed(dac_account),
+		let (customers_merkle_root, customers_proofs) = get_sequential_batches_commitment(&payers);
+		let (nodes_merkle_root, nodes_proofs) = get_sequential_batches_commitment(&payees);
+
+		assert_ok!(DdcPayouts::set_validator_threshold(RuntimeOrigin::root(), 1));
+		assert_ok!(DdcPayouts::begin_billing_report(
+			RuntimeOrigin::signed(dac_account),
+			cluster_id,
+			era,
+			start_era,
+			end_era,
+			customers_merkle_root,
+			nodes_merkle_root,
+			(payers.len() - 1) as u16,
+			(payees.len() - 1) as u16,
+			customers_usage(payers.iter().flatten()),
+			total_nodes_usage.clone(),
+		));
+		assert_ok!(DdcPayouts::begin_charging_customers(
+			RuntimeOrigin::signed(dac_account),
+			cluster_id,
+			era,
+		));
+
+		for batch in payers.iter() {
+			assert_ok!(DdcPayouts::send_charging_customers_batch(
+				RuntimeOrigin::signed(dac_account),
+				cluster_id,
+				era,
+				batch_user_index,
+				batch.to_vec(),
+				customers_proofs[batch_user_index as usize].clone(),
+			));
+
+			for (customer_id, bucket_id, usage) in batch.iter() {
+				let charge = calculate_charge_for_month(cluster_id, usage.clone());
+
+				System::assert_has_event(
+					Event::Charged {
+						cluster_id,
+						era,
+						customer_id: *customer_id,
+						bucket_id: *bucket_id,
+						batch_index: batch_user_index,
+						amount: charge,
+					}
+					.into(),
+				);
+			}
+			batch_user_index += 1;
+		}
+
+		let report_before = DdcPayouts::active_billing_reports(cluster_id, era).unwrap();
+		let balance1 = Balances::free_balance(report_before.vault);
+		let balance2 = Balances::free_balance(DdcPayouts::account_id());
+		assert_eq!(balance1, balance2);
+		assert_eq!(report_before.vault, DdcPayouts::account_id());
+		assert_eq!(balance1 - Balances::minimum_balance(), total_charge);
+
+		assert_ok!(DdcPayouts::end_charging_customers(
+			RuntimeOrigin::signed(dac_account),
+			cluster_id,
+			era,
+		));
+
+		let report_after = DdcPayouts::active_billing_reports(cluster_id, era).unwrap();
+		let total_left_from_one = (get_fees(&cluster_id).treasury_share +
+			get_fees(&cluster_id).validators_share +
+			get_fees(&cluster_id).cluster_reserve_share)
+			.left_from_one();
+
+		let total_charge = report_after.total_customer_charge.transfer +
+			report_before.total_customer_charge.storage +
+			report_before.total_customer_charge.puts +
+			report_before.total_customer_charge.gets;
+		let balance_after = Balances::free_balance(DdcPayouts::account_id());
+		assert_eq!(total_charge, balance_after - Balances::minimum_balance());
+
+		assert_eq!(
+			report_after.total_customer_charge.transfer,
+			total_left_from_one * report_before.total_customer_charge.transfer
+		);
+		assert_eq!(
+			report_after.total_customer_charge.storage,
+			total_left_from_one * report_before.total_customer_charge.storage
+		);
+		assert_eq!(
+			report_after.total_customer_charge.puts,
+			total_left_from_one * report_before.total_customer_charge.puts
+		);
+		assert_eq!(
+			report_after.total_customer_charge.gets,
+			total_left_from_one * report_before.total_customer_charge.gets
+		);
+
+		assert_ok!(DdcPayouts::begin_rewarding_providers(
+			RuntimeOrigin::signed(dac_account),
+			cluster_id,
+			era,
+		));
+
+		for batch in payees.iter() {
+			let before_batch = Balances::free_balance(DdcPayouts::account_id());
+			assert_ok!(DdcPayouts::send_rewarding_providers_batch(
+				RuntimeOrigin::signed(dac_account),
+				cluster_id,
+				era,
+				batch_node_index,
+				batch.to_vec(),
+				nodes_proofs[batch_node_index as usize].clone(),
+			));
+
+			let mut batch_charge = 0;
+			for (node1, _, node_usage1) in batch.iter() {
+				let ratio1_transfer = Perquintill::from_rational(
+					node_usage1.transferred_bytes,
+					total_nodes_usage.transferred_bytes,
+				);
+				let transfer_charge = ratio1_transfer * report_after.total_customer_charge.transfer;
+
+				let ratio1_storage = Perquintill::from_rational(
+					node_usage1.stored_bytes,
+					total_nodes_usage.stored_bytes,
+				);
+				let storage_charge = ratio1_storage * report_after.total_customer_charge.storage;
+
+				let ratio1_puts = Perquintill::from_rational(
+					node_usage1.number_of_puts,
+					total_nodes_usage.number_of_puts,
+				);
+				let puts_charge = ratio1_puts * report_after.total_customer_charge.puts;
+
+				let ratio1_gets = Perquintill::from_rational(
+					node_usage1.number_of_gets,
+					total_nodes_usage.number_of_gets,
+				);
+				let gets_charge = ratio1_gets * report_after.total_customer_charge.gets;
+
+				let balance_node1 = Balances::free_balance(node1);
+				assert!(
+					(transfer_charge + storage_charge + puts_charge + gets_charge) - balance_node1 <
+						MAX_DUST.into()
+				);
+
+				batch_charge += transfer_charge + storage_charge + puts_charge + gets_charge;
+			}
+			let after_batch = Balances::free_balance(DdcPayouts::account_id());
+			assert!(batch_charge + after_batch - before_batch < MAX_DUST.into());
+
+			batch_node_index += 1;
+		}
+		assert!(Balances::free_balance(DdcPayouts::account_id()) < MAX_DUST.into());
+	})
+}
+
+#[test]
+fn send_rewarding_providers_batch_100_nodes_large_usage_works() {
+	ExtBuilder.build_and_execute(|| {
+		System::set_block_number(1);
+
+		let start_date = NaiveDate::from_ymd_opt(2023, 4, 1).unwrap(); // April 1st
+
+		let time = NaiveTime::from_hms_opt(0, 0, 0).unwrap(); // Midnight
+		let start_era: i64 =
+			DateTime::<Utc>::from_naive_utc_and_offset(start_date.and_time(time), Utc).timestamp();
+		let end_era: i64 = start_era + (30.44 * 24.0 * 3600.0) as i64;
+		let num_nodes = 100;
+		let num_users = 5;
+		let dac_account = 123u128;
+		let bank = 1u128;
+		let cluster_id = ONE_CLUSTER_ID;
+		let era = 100;
+		let user_batch_size = 10;
+		let node_batch_size = 10;
+		let mut batch_user_index = 0;
+		let mut batch_node_index = 0;
+		let usage1 = CustomerUsage {
+			transferred_bytes: 1024,
+			stored_bytes: 1024,
+			number_of_puts: 1,
+			number_of_gets: 1,
+		};
+
+		let node_usage1 = NodeUsage {
+			// CDN
+			transferred_bytes: Perquintill::from_float(0.75) * usage1.transferred_bytes,
+			stored_bytes: 0,
+			number_of_puts: Perquintill::from_float(0.75) * usage1.number_of_puts,
+			number_of_gets: Perquintill::from_float(0.75) * usage1.number_of_gets,
+		};
+
+		let node_usage2 = NodeUsage {
+			// Storage
+			transferred_bytes: 0,
+			stored_bytes: usage1.stored_bytes * 2,
+			number_of_puts: 0,
+			number_of_gets: 0,
+		};
+
+		let node_usage3 = NodeUsage {
+			// CDN + Storage
+			transferred_bytes: usage1.transferred_bytes * 2,
+			stored_bytes: usage1.stored_bytes * 3,
+			number_of_puts: usage1.number_of_puts * 2,
+			number_of_gets: usage1.number_of_gets * 2,
+		};
+
+		let mut payees: Vec<Vec<(u128, NodePubKey, NodeUsage)>> = Vec::new();
+		let mut node_batch: Vec<(u128, NodePubKey, NodeUsage)> = Vec::new();
+		let mut total_nodes_usage = NodeUsage::default();
+		for i in 10..10 + num_nodes {
+			let ratio = match i % 5 {
+				0 => Perquintill::from_float(1_000_000.0),
+				1 => Perquintill::from_float(10_000_000.0),
+				2 => Perquintill::from_float(100_000_000.0),
+				3 => Perquintill::from_float(1_000_000_000.0),
+				4 => Perquintill::from_float(10_000_000_000.0),
+				_ => unreachable!(),
+			};
+			let mut node_usage = match i % 3 {
+				0 => node_usage1.clone(),
+				1 => node_usage2.clone(),
+				2 => node_usage3.clone(),
+				_ => unreachable!(),
+			};
+			node_usage.transferred_bytes = ratio * node_usage.transferred_bytes;
+			node_usage.stored_bytes = ratio * node_usage.stored_bytes;
+			node_usage.number_of_puts = ratio * node_usage.number_of_puts;
+			node_usage.number_of_gets = ratio * node_usage.number_of_gets;
+
+			total_nodes_usage.transferred_bytes += node_usage.transferred_bytes;
+			total_nodes_usage.stored_bytes += node_usage.stored_bytes;
+			total_nodes_usage.number_of_puts += node_usage.number_of_puts;
+			total_nodes_usage.number_of_gets += node_usage.number_of_gets;
+
+			node_batch.push((i, node_key(i), node_usage));
+			if node_batch.len() == node_batch_size {
+				payees.push(node_batch.clone());
+				node_batch.clear();
+			}
+		}
+		if !node_batch.is_empty() {
+			payees.push(node_batch.clone());
+		}
+
+		let mut total_charge = 0u128;
+		let mut payers: Vec<Vec<(u128, BucketId, CustomerUsage)>> = Vec::new();
+		let mut user_batch: Vec<(u128, BucketId, CustomerUsage)> = Vec::new();
+		for user_id in 1000..1000 + num_users {
+			let ratio = match user_id % 5 {
+				0 => Perquintill::from_float(1_000_000.0),
+				1 => Perquintill::from_float(10_000_000.0),
+				2 => Perquintill::from_float(100_000_000.0),
+				3 => Perquintill::from_float(1_000_000_000.0),
+				4 => Perquintill::from_float(10_000_000_000.0),
+				_ => unreachable!(),
+			};
+
+			let mut user_usage = usage1.clone();
+			user_usage.transferred_bytes = ratio * user_usage.transferred_bytes;
+			user_usage.stored_bytes = ratio * user_usage.stored_bytes;
+			user_usage.number_of_puts = ratio * user_usage.number_of_puts;
+			user_usage.number_of_gets = ratio * user_usage.number_of_gets;
+
+			let expected_charge = calculate_charge_for_month(cluster_id, user_usage.clone());
+			Balances::transfer(
+				RuntimeOrigin::signed(bank),
+				user_id,
+				(expected_charge * 2).max(Balances::minimum_balance()),
+			)
+			.unwrap();
+			total_charge += expected_charge;
+
+			user_batch.push((user_id, user_id as BucketId, user_usage));
+			if user_batch.len() == user_batch_size {
+				payers.push(user_batch.clone());
+				user_batch.clear();
+			}
+		}
+		if !user_batch.is_empty() {
+			payers.push(user_batch.clone());
+		}
+
+		let (customers_merkle_root, customers_proofs) = get_sequential_batches_commitment(&payers);
+		let (nodes_merkle_root, nodes_proofs) = get_sequential_batches_commitment(&payees);
+
+		assert_ok!(DdcPayouts::set_validator_threshold(RuntimeOrigin::root(), 1));
+		assert_ok!(DdcPayouts::begin_billing_report(
+			RuntimeOrigin::signed(dac_account),
+			cluster_id,
+			era,
+			start_era,
+			end_era,
+			customers_merkle_root,
+			nodes_merkle_root,
+			(payers.len() - 1) as u16,
+			(payees.len() - 1) as u16,
+			customers_usage(payers.iter().flatten()),
+			total_nodes_usage.clone(),
+		));
+		assert_ok!(DdcPayouts::begin_charging_customers(
+			RuntimeOrigin::signed(dac_account),
+			cluster_id,
+			era,
+		));
+
+		for batch in payers.iter() {
+			assert_ok!(DdcPayouts::send_charging_customers_batch(
+				RuntimeOrigin::signed(dac_account),
+				cluster_id,
+				era,
+				batch_user_index,
+				batch.to_vec(),
+				customers_proofs[batch_user_index as usize].clone(),
+			));
+
+			for (customer_id, bucket_id, usage) in batch.iter() {
+				let charge = calculate_charge_for_month(cluster_id, usage.clone());
+
+				System::assert_has_event(
+					Event::Charged {
+						cluster_id,
+						era,
+						customer_id: *customer_id,
+						bucket_id: *bucket_id,
+						batch_index: batch_user_index,
+						amount: charge,
+					}
+					.into(),
+				);
+			}
+			batch_user_index += 1;
+		}
+
+		let report_before = DdcPayouts::active_billing_reports(cluster_id, era).unwrap();
+		let balance1 = Balances::free_balance(report_before.vault);
+		let balance2 = Balances::free_balance(DdcPayouts::account_id());
+		assert_eq!(balance1, balance2);
+		assert_eq!(report_before.vault, DdcPayouts::account_id());
+		assert_eq!(balance1 - Balances::minimum_balance(), total_charge);
+
+		assert_ok!(DdcPayouts::end_charging_customers(
+			RuntimeOrigin::signed(dac_account),
+			cluster_id,
+			era,
+		));
+
+		let report_after = DdcPayouts::active_billing_reports(cluster_id, era).unwrap();
+		let total_left_from_one = (get_fees(&cluster_id).treasury_share +
+			get_fees(&cluster_id).validators_share +
+			get_fees(&cluster_id).cluster_reserve_share)
+			.left_from_one();
+
+		let total_charge = report_after.total_customer_charge.transfer +
+			report_before.total_customer_charge.storage +
+			report_before.total_customer_charge.puts +
+			report_before.total_customer_charge.gets;
+		let balance_after = Balances::free_balance(DdcPayouts::account_id());
+		assert_eq!(total_charge, balance_after - Balances::minimum_balance());
+
+		assert_eq!(
+			report_after.total_customer_charge.transfer,
+			total_left_from_one * report_before.total_customer_charge.transfer
+		);
+		assert_eq!(
+			report_after.total_customer_charge.storage,
+			total_left_from_one * report_before.total_customer_charge.storage
+		);
+		assert_eq!(
+			report_after.total_customer_charge.puts,
+			total_left_from_one * report_before.total_customer_charge.puts
+		);
+		assert_eq!(
+			report_after.total_customer_charge.gets,
+			total_left_from_one * report_before.total_customer_charge.gets
+		);
+
+		assert_ok!(DdcPayouts::begin_rewarding_providers(
+			RuntimeOrigin::signed(dac_account),
+			cluster_id,
+			era,
+		));
+
+		for batch in payees.iter() {
+			let before_batch = Balances::free_balance(DdcPayouts::account_id());
+			assert_ok!(DdcPayouts::send_rewarding_providers_batch(
+				RuntimeOrigin::signed(dac_account),
+				cluster_id,
+				era,
+				batch_node_index,
+				batch.to_vec(),
+				nodes_proofs[batch_node_index as usize].clone(),
+			));
+
+			let mut batch_charge = 0;
+			for (node1, _, node_usage1) in batch.iter() {
+				let ratio1_transfer = Perquintill::from_rational(
+					node_usage1.transferred_bytes,
+					total_nodes_usage.transferred_bytes,
+				);
+				let transfer_charge = ratio1_transfer * report_after.total_customer_charge.transfer;
+
+				let ratio1_storage = Perquintill::from_rational(
+					node_usage1.stored_bytes,
+					total_nodes_usage.stored_bytes,
+				);
+				let storage_charge = ratio1_storage * report_after.total_customer_charge.storage;
+
+				let ratio1_puts = Perquintill::from_rational(
+					node_usage1.number_of_puts,
+					total_nodes_usage.number_of_puts,
+				);
+				let puts_charge = ratio1_puts * report_after.total_customer_charge.puts;
+
+				let ratio1_gets = Perquintill::from_rational(
+					node_usage1.number_of_gets,
+					total_nodes_usage.number_of_gets,
+				);
+				let gets_charge = ratio1_gets * report_after.total_customer_charge.gets;
+
+				let balance_node1 = Balances::free_balance(node1);
+				assert!(
+					(transfer_charge + storage_charge + puts_charge + gets_charge) - balance_node1 <
+						MAX_DUST.into()
+				);
+
+				batch_charge += transfer_charge + storage_charge + puts_charge + gets_charge;
+			}
+			let after_batch = Balances::free_balance(DdcPayouts::account_id());
+			assert!(batch_charge + after_batch - before_batch < MAX_DUST.into());
+
+			batch_node_index += 1;
+		}
+		assert!(Balances::free_balance(DdcPayouts::account_id()) < MAX_DUST.into());
+	})
+}
+
+#[test]
+fn send_rewarding_providers_batch_100_nodes_small_large_usage_works() {
+	ExtBuilder.build_and_execute(|| {
+		System::set_block_number(1);
+
+		let start_date = NaiveDate::from_ymd_opt(2023, 4, 1).unwrap(); // April 1st
+
+		let time = NaiveTime::from_hms_opt(0, 0, 0).unwrap(); // Midnight
+		let start_era: i64 =
+			DateTime::<Utc>::from_naive_utc_and_offset(start_date.and_time(time), Utc).timestamp();
+		let end_era: i64 = start_era + (30.44 * 24.0 * 3600.0) as i64;
+		let num_nodes = 100;
+		let num_users = 5;
+		let dac_account = 123u128;
+		let bank = 1u128;
+		let cluster_id = ONE_CLUSTER_ID;
+		let era = 100;
+		let user_batch_size = 10;
+		let node_batch_size = 10;
+		let mut batch_user_index = 0;
+		let mut batch_node_index = 0;
+		let usage1 = CustomerUsage {
+			transferred_bytes: 1024,
+			stored_bytes: 1024,
+			number_of_puts: 1,
+			number_of_gets: 1,
+		};
+
+		let node_usage1 = NodeUsage {
+			// CDN
+			transferred_bytes: Perquintill::from_float(0.75) * usage1.transferred_bytes,
+			stored_bytes: 0,
+			number_of_puts: Perquintill::from_float(0.75) * usage1.number_of_puts,
+			number_of_gets: Perquintill::from_float(0.75) * usage1.number_of_gets,
+		};
+
+		let node_usage2 = NodeUsage {
+			// Storage
+			transferred_bytes: 0,
+			stored_bytes: usage1.stored_bytes * 2,
+			number_of_puts: 0,
+			number_of_gets: 0,
+		};
+
+		let node_usage3 = NodeUsage {
+			// CDN + Storage
+			transferred_bytes: usage1.transferred_bytes * 2,
+			stored_bytes: usage1.stored_bytes * 3,
+			number_of_puts: usage1.number_of_puts * 2,
+			number_of_gets: usage1.number_of_gets * 2,
+		};
+
+		let mut payees: Vec<Vec<(u128, NodePubKey, NodeUsage)>> = Vec::new();
+		let mut node_batch: Vec<(u128, NodePubKey, NodeUsage)> = Vec::new();
+		let mut total_nodes_usage = NodeUsage::default();
+		for i in 10..10 + num_nodes {
+			let ratio = match i % 5 {
+				0 => Perquintill::from_float(1_000_000.0),
+				1 => Perquintill::from_float(0.5),
+				2 => Perquintill::from_float(100_000_000.0),
+				3 => Perquintill::from_float(0.25),
+				4 => Perquintill::from_float(10_000_000_000.0),
+				_ => unreachable!(),
+			};
+			let mut node_usage = match i % 3 {
+				0 => node_usage1.clone(),
+				1 => node_usage2.clone(),
+				2 => node_usage3.clone(),
+				_ => unreachable!(),
+			};
+			node_usage.transferred_bytes = ratio * node_usage.transferred_bytes;
+			node_usage.stored_bytes = ratio * node_usage.stored_bytes;
+			node_usage.number_of_puts = ratio * node_usage.number_of_puts;
+			node_usage.number_of_gets = ratio * node_usage.number_of_gets;
+
+			total_nodes_usage.transferred_bytes += node_usage.transferred_bytes;
+			total_nodes_usage.stored_bytes += node_usage.stored_bytes;
+			total_nodes_usage.number_of_puts += node_usage.number_of_puts;
+			total_nodes_usage.number_of_gets += node_usage.number_of_gets;
+
+			node_batch.push((i, node_key(i), node_usage));
+			if node_batch.len() == node_batch_size {
+				payees.push(node_batch.clone());
+				node_batch.clear();
+			}
+		}
+		if !node_batch.is_empty() {
+			payees.push(node_batch.clone());
+		}
+
+		let mut total_charge = 0u128;
+		let mut payers: Vec<Vec<(u128, BucketId, CustomerUsage)>> = Vec::new();
+		let mut user_batch: Vec<(u128, BucketId, CustomerUsage)> = Vec::new();
+		for user_id in 1000..1000 + num_users {
+			let ratio = match user_id % 5 {
+				0 => Perquintill::from_float(1_000_000.0),
+				1 => Perquintill::from_float(10_000_000.0),
+				2 => Perquintill::from_float(100_000_000.0),
+				3 => Perquintill::from_float(1_000_000_000.0),
+				4 => Perquintill::from_float(10_000_000_000.0),
+				_ => unreachable!(),
+			};
+
+			let mut user_usage = usage1.clone();
+			user_usage.transferred_bytes = ratio * user_usage.transferred_bytes;
+			user_usage.stored_bytes = ratio * user_usage.stored_bytes;
+			user_usage.number_of_puts = ratio * user_usage.number_of_puts;
+			user_usage.number_of_gets = ratio * user_usage.number_of_gets;
+
+			let expected_charge = calculate_charge_for_month(cluster_id, user_usage.clone());
+			Balances::transfer(
+				RuntimeOrigin::signed(bank),
+				user_id,
+				(expected_charge * 2).max(Balances::minimum_balance()),
+			)
+			.unwrap();
+			total_charge += expected_charge;
+
+			user_batch.push((user_id, user_id as BucketId, user_usage));
+			if user_batch.len() == user_batch_size {
+				payers.push(user_batch.clone());
+				user_batch.clear();
+			}
+		}
+		if !user_batch.is_empty() {
+			payers.push(user_batch.clone());
+		}
+
+		let (customers_merkle_root, customers_proofs) = get_sequential_batches_commitment(&payers);
+		let (nodes_merkle_root, nodes_proofs) = get_sequential_batches_commitment(&payees);
+
+		assert_ok!(DdcPayouts::set_validator_threshold(RuntimeOrigin::root(), 1));
+		assert_ok!(DdcPayouts::begin_billing_report(
+			RuntimeOrigin::signed(dac_account),
+			cluster_id,
+			era,
+			start_era,
+			end_era,
+			customers_merkle_root,
+			nodes_merkle_root,
+			(payers.len() - 1) as u16,
+			(payees.len() - 1) as u16,
+			customers_usage(payers.iter().flatten()),
+			total_nodes_usage.clone(),
+		));
+		assert_ok!(DdcPayouts::begin_charging_customers(
+			RuntimeOrigin::signed(dac_account),
+			cluster_id,
+			era,
+		));
+
+		for batch in payers.iter() {
+			assert_ok!(DdcPayouts::send_charging_customers_batch(
+				RuntimeOrigin::signed(dac_account),
+				cluster_id,
+				era,
+				batch_user_index,
+				batch.to_vec(),
+				customers_proofs[batch_user_index as usize].clone(),
+			));
+
+			for (customer_id, bucket_id, usage) in batch.iter() {
+				let charge = calculate_charge_for_month(cluster_id, usage.clone());
+
+				System::assert_has_event(
+					Event::Charged {
+						cluster_id,
+						era,
+						customer_id: *customer_id,
+						bucket_id: *bucket_id,
+						batch_index: batch_user_index,
+						amount: charge,
+					}
+					.into(),
+				);
+			}
+			batch_user_index += 1;
+		}
+
+		let report_before = DdcPayouts::active_billing_reports(cluster_id, era).unwrap();
+		let balance1 = Balances::free_balance(report_before.vault);
+		let balance2 = Balances::free_balance(DdcPayouts::account_id());
+		assert_eq!(balance1, balance2);
+		assert_eq!(report_before.vault, DdcPayouts::account_id());
+		assert_eq!(balance1 - Balances::minimum_balance(), total_charge);
+
+		assert_ok!(DdcPayouts::end_charging_customers(
+			RuntimeOrigin::signed(dac_account),
+			cluster_id,
+			era,
+		));
+
+		let report_after = DdcPayouts::active_billing_reports(cluster_id, era).unwrap();
+		let total_left_from_one = (get_fees(&cluster_id).treasury_share +
+			get_fees(&cluster_id).validators_share +
+			get_fees(&cluster_id).cluster_reserve_share)
+			.left_from_one();
+
+		let total_charge = report_after.total_customer_charge.transfer +
+			report_before.total_customer_charge.storage +
+			report_before.total_customer_charge.puts +
+			report_before.total_customer_charge.gets;
+		let balance_after = Balances::free_balance(DdcPayouts::account_id());
+		assert_eq!(total_charge, balance_after - Balances::minimum_balance());
+
+		assert_eq!(
+			report_after.total_customer_charge.transfer,
+			total_left_from_one * report_before.total_customer_charge.transfer
+		);
+		assert_eq!(
+			report_after.total_customer_charge.storage,
+			total_left_from_one * report_before.total_customer_charge.storage
+		);
+		assert_eq!(
+			report_after.total_customer_charge.puts,
+			total_left_from_one * report_before.total_customer_charge.puts
+		);
+		assert_eq!(
+			report_after.total_customer_charge.gets,
+			total_left_from_one * report_before.total_customer_charge.gets
+		);
+
+		assert_ok!(DdcPayouts::begin_rewarding_providers(
+			RuntimeOrigin::signed(dac_account),
+			cluster_id,
+			era,
+		));
+
+		for batch in payees.iter() {
+			let before_batch = Balances::free_balance(DdcPayouts::account_id());
+			assert_ok!(DdcPayouts::send_rewarding_providers_batch(
+				RuntimeOrigin::signed(dac_account),
+				cluster_id,
+				era,
+				batch_node_index,
+				batch.to_vec(),
+				nodes_proofs[batch_node_index as usize].clone(),
+			));
+
+			let mut batch_charge = 0;
+			for (node1, _, node_usage1) in batch.iter() {
+				let ratio1_transfer = Perquintill::from_rational(
+					node_usage1.transferred_bytes,
+					total_nodes_usage.transferred_bytes,
+				);
+				let transfer_charge = ratio1_transfer * report_after.total_customer_charge.transfer;
+
+				let ratio1_storage = Perquintill::from_rational(
+					node_usage1.stored_bytes,
+					total_nodes_usage.stored_bytes,
+				);
+				let storage_charge = ratio1_storage * report_after.total_customer_charge.storage;
+
+				let ratio1_puts = Perquintill::from_rational(
+					node_usage1.number_of_puts,
+					total_nodes_usage.number_of_puts,
+				);
+				let puts_charge = ratio1_puts * report_after.total_customer_charge.puts;
+
+				let ratio1_gets = Perquintill::from_rational(
+					node_usage1.number_of_gets,
+					total_nodes_usage.number_of_gets,
+				);
+				let gets_charge = ratio1_gets * report_after.total_customer_charge.gets;
+
+				let balance_node1 = Balances::free_balance(node1);
+				assert!(
+					(transfer_charge + storage_charge + puts_charge + gets_charge) - balance_node1 <
+						MAX_DUST.into()
+				);
+
+				batch_charge += transfer_charge + storage_charge + puts_charge + gets_charge;
+			}
+			let after_batch = Balances::free_balance(DdcPayouts::account_id());
+			assert!(batch_charge + after_batch - before_batch < MAX_DUST.into());
+
+			batch_node_index += 1;
+		}
+		assert!(Balances::free_balance(DdcPayouts::account_id()) < MAX_DUST.into());
+	})
+}
+
+fn generate_random_u64<T: Randomness<H256, BlockNumber>>(_: &T, min: u64, max: u64) -> u64 {
+	let (random_seed, _) = T::random_seed();
+	let random_raw = u64::from_be_bytes(random_seed.as_bytes()[0..8].try_into().unwrap());
+
+	min.saturating_add(random_raw % (max.saturating_sub(min).saturating_add(1)))
+}
+
+#[test]
+fn send_rewarding_providers_batch_100_nodes_random_usage_works() {
+	ExtBuilder.build_and_execute(|| {
+		System::set_block_number(1);
+
+		let start_date = NaiveDate::from_ymd_opt(2023, 4, 1).unwrap(); // April 1st
+
+		let time = NaiveTime::from_hms_opt(0, 0, 0).unwrap(); // Midnight
+		let start_era: i64 =
+			DateTime::<Utc>::from_naive_utc_and_offset(start_date.and_time(time), Utc).timestamp();
+		let end_era: i64 = start_era + (30.44 * 24.0 * 3600.0) as i64;
+		let mock_randomness = MockRandomness::default();
+		let min: u64 = 1024;
+		let max: u64 = 1024 * 1024;
+		let num_nodes = 100;
+		let num_users = 100;
+		let dac_account = 123u128;
+		let bank = 1u128;
+		let cluster_id = CERE_CLUSTER_ID;
+		let era = 100;
+		let user_batch_size = 10;
+		let node_batch_size = 10;
+		let mut batch_user_index = 0;
+		let mut batch_node_index = 0;
+		let mut payees: Vec<Vec<(u128, NodePubKey, NodeUsage)>> = Vec::new();
+		let mut node_batch: Vec<(u128, NodePubKey, NodeUsage)> = Vec::new();
+		let mut total_nodes_usage = NodeUsage::default();
+		for i in 10..10 + num_nodes {
+			let node_usage = NodeUsage {
+				transferred_bytes: generate_random_u64(&mock_randomness, min, max),
+				stored_bytes: generate_random_u64(&mock_randomness, min, max),
+				number_of_puts: generate_random_u64(&mock_randomness, min, max),
+				number_of_gets: generate_random_u64(&mock_randomness, min, max),
+			};
+
+			total_nodes_usage.transferred_bytes += node_usage.transferred_bytes;
+			total_nodes_usage.stored_bytes += node_usage.stored_bytes;
+			total_nodes_usage.number_of_puts += node_usage.number_of_puts;
+			total_nodes_usage.number_of_gets += node_usage.number_of_gets;
+
+			node_batch.push((i, node_key(i), node_usage));
+			if node_batch.len() == node_batch_size {
+				payees.push(node_batch.clone());
+				node_batch.clear();
+			}
+		}
+		if !node_batch.is_empty() {
+			payees.push(node_batch.clone());
+		}
+
+		let mut total_charge = 0u128;
+		let mut payers: Vec<Vec<(u128, BucketId, CustomerUsage)>> = Vec::new();
+		let mut user_batch: Vec<(u128, BucketId, CustomerUsage)> = Vec::new();
+		for user_id in 1000..1000 + num_users {
+			let user_usage = CustomerUsage {
+				transferred_bytes: generate_random_u64(&mock_randomness, min, max),
+				stored_bytes: generate_random_u64(&mock_randomness, min, max),
+				number_of_puts: generate_random_u64(&mock_randomness, min, max),
+				number_of_gets: generate_random_u64(&mock_randomness, min, max),
+			};
+
+			let expected_charge = calculate_charge_for_month(cluster_id, user_usage.clone());
+			Balances::transfer(
+				RuntimeOrigin::signed(bank),
+				user_id,
+				(expected_charge * 2).max(Balances::minimum_balance()),
+			)
+			.unwrap();
+			total_charge += expected_charge;
+
+			user_batch.push((user_id, user_id as BucketId, user_usage));
+			if user_batch.len() == user_batch_size {
+				payers.push(user_batch.clone());
+				user_batch.clear();
+			}
+		}
+		if !user_batch.is_empty() {
+			payers.push(user_batch.clone());
+		}
+
+		let (customers_merkle_root, customers_proofs) = get_sequential_batches_commitment(&payers);
+		let (nodes_merkle_root, nodes_proofs) = get_sequential_batches_commitment(&payees);
+
+		assert_ok!(DdcPayouts::set_validator_threshold(RuntimeOrigin::root(), 1));
+		assert_ok!(DdcPayouts::begin_billing_report(
+			RuntimeOrigin::signed(dac_account),
+			cluster_id,
+			era,
+			start_era,
+			end_era,
+			customers_merkle_root,
+			nodes_merkle_root,
+			(payers.len() - 1) as u16,
+			(payees.len() - 1) as u16,
+			customers_usage(payers.iter().flatten()),
+			total_nodes_usage.clone(),
+		));
+		assert_ok!(DdcPayouts::begin_charging_customers(
+			RuntimeOrigin::signed(dac_account),
+			cluster_id,
+			era,
+		));
+
+		for batch in payers.iter() {
+			assert_ok!(DdcPayouts::send_charging_customers_batch(
+				RuntimeOrigin::signed(dac_account),
+				cluster_id,
+				era,
+				batch_user_index,
+				batch.to_vec(),
+				customers_proofs[batch_user_index as usize].clone(),
+			));
+
+			for (customer_id, bucket_id, usage) in batch.iter() {
+				let charge = calculate_charge_for_month(cluster_id, usage.clone());
+
+				System::assert_has_event(
+					Event::Charged {
+						cluster_id,
+						era,
+						customer_id: *customer_id,
+						bucket_id: *bucket_id,
+						batch_index: batch_user_index,
+						amount: charge,
+					}
+					.into(),
+				);
+			}
+			batch_user_index += 1;
+		}
+
+		let report_before = DdcPayouts::active_billing_reports(cluster_id, era).unwrap();
+		let balance1 = Balances::free_balance(report_before.vault);
+		let balance2 = Balances::free_balance(DdcPayouts::account_id());
+		assert_eq!(balance1, balance2);
+		assert_eq!(report_before.vault, DdcPayouts::account_id());
+		assert_eq!(balance1 - Balances::minimum_balance(), total_charge);
+
+		assert_ok!(DdcPayouts::end_charging_customers(
+			RuntimeOrigin::signed(dac_account),
+			cluster_id,
+			era,
+		));
+
+		let report_after = DdcPayouts::active_billing_reports(cluster_id, era).unwrap();
+		let total_left_from_one = (get_fees(&cluster_id).treasury_share +
+			get_fees(&cluster_id).validators_share +
+			get_fees(&cluster_id).cluster_reserve_share)
+			.left_from_one();
+
+		let total_charge = report_after.total_customer_charge.transfer +
+			report_before.total_customer_charge.storage +
+			report_before.total_customer_charge.puts +
+			report_before.total_customer_charge.gets;
+		let balance_after = Balances::free_balance(DdcPayouts::account_id());
+		assert_eq!(total_charge, balance_after - Balances::minimum_balance());
+
+		assert_eq!(
+			report_after.total_customer_charge.transfer,
+			total_left_from_one * report_before.total_customer_charge.transfer
+		);
+		assert_eq!(
+			report_after.total_customer_charge.storage,
+			total_left_from_one * report_before.total_customer_charge.storage
+		);
+		assert_eq!(
+			report_after.total_customer_charge.puts,
+			total_left_from_one * report_before.total_customer_charge.puts
+		);
+		assert_eq!(
+			report_after.total_customer_charge.gets,
+			total_left_from_one * report_before.total_customer_charge.gets
+		);
+
+		assert_ok!(DdcPayouts::begin_rewarding_providers(
+			RuntimeOrigin::signed(dac_account),
+			cluster_id,
+			era,
+		));
+
+		for batch in payees.iter() {
+			let before_batch = Balances::free_balance(DdcPayouts::account_id());
+			assert_ok!(DdcPayouts::send_rewarding_providers_batch(
+				RuntimeOrigin::signed(dac_account),
+				cluster_id,
+				era,
+				batch_node_index,
+				batch.to_vec(),
+				nodes_proofs[batch_node_index as usize].clone(),
+			));
+
+			let mut batch_charge = 0;
+			for (node1, _, node_usage1) in batch.iter() {
+				let ratio1_transfer = Perquintill::from_rational(
+					node_usage1.transferred_bytes,
+					total_nodes_usage.transferred_bytes,
+				);
+				let transfer_charge = ratio1_transfer * report_after.total_customer_charge.transfer;
+
+				let ratio1_storage = Perquintill::from_rational(
+					node_usage1.stored_bytes,
+					total_nodes_usage.stored_bytes,
+				);
+				let storage_charge = ratio1_storage * report_after.total_customer_charge.storage;
+
+				let ratio1_puts = Perquintill::from_rational(
+					node_usage1.number_of_puts,
+					total_nodes_usage.number_of_puts,
+				);
+				let puts_charge = ratio1_puts * report_after.total_customer_charge.puts;
+
+				let ratio1_gets = Perquintill::from_rational(
+					node_usage1.number_of_gets,
+					total_nodes_usage.number_of_gets,
+				);
+				let gets_charge = ratio1_gets * report_after.total_customer_charge.gets;
+
+				let balance_node1 = Balances::free_balance(node1);
+				assert!(
+					(transfer_charge + storage_charge + puts_charge + gets_charge) - balance_node1 <
+						MAX_DUST.into()
+				);
+
+				batch_charge += transfer_charge + storage_charge + puts_charge + gets_charge;
+			}
+			let after_batch = Balances::free_balance(DdcPayouts::account_id());
+			assert!(batch_charge + after_batch - before_batch < MAX_DUST.into());
+
+			batch_node_index += 1;
+		}
+		assert!(Balances::free_balance(DdcPayouts::account_id()) < MAX_DUST.into());
+	})
+}
+
+#[test]
+fn send_rewarding_providers_batch_defers_owed_rewards() {
+	ExtBuilder.build_and_execute(|| {
+		System::set_block_number(1);
+
+		let dac_account = 123u128;
+		let bank = 1u128;
+		let node1 = 10u128;
+		let cluster_id = ClusterId::from([12; 20]);
+		let era = 100;
+		let next_era = 101;
+		let batch_index = 0;
+		let reward = 100_000_000;
+		let node_usage = NodeUsage {
+			transferred_bytes: 0,
+			stored_bytes: 10,
+			number_of_puts: 0,
+			number_of_gets: 0,
+		};
+		let payees = vec![(node1, node_key(node1), node_usage.clone())];
+		let (nodes_merkle_root, nodes_proofs) = get_batches_commitment(&[(batch_index, &payees)]);
+		let vault = DdcPayouts::account_id();
+
+		for report_era in [era, next_era] {
+			ActiveBillingReports::<Test>::insert(
+				cluster_id,
+				report_era,
+				BillingReport::<Test> {
+					state: State::RewardingProviders,
+					vault,
+					total_customer_charge: CustomerCharge { storage: reward, ..Default::default() },
+					total_node_usage: node_usage.clone(),
+					nodes_merkle_root,
+					..Default::default()
+				},
+			);
+		}
+
+		assert_ok!(DdcPayouts::set_validator_threshold(RuntimeOrigin::root(), 1));
+
+		// vault runs short, the rest of the reward is owed to the provider
+		assert_ok!(Balances::transfer(RuntimeOrigin::signed(bank), vault, 40_000_000));
+		assert_ok!(DdcPayouts::send_rewarding_providers_batch(
+			RuntimeOrigin::signed(dac_account),
+			cluster_id,
+			era,
+			batch_index,
+			payees.clone(),
+			nodes_proofs[0].clone(),
+		));
+
+		System::assert_last_event(
+			Event::Rewarded {
+				cluster_id,
+				era,
+				batch_index,
+				node_provider_id: node1,
+				rewarded: 40_000_000,
+				expected_to_reward: reward,
+			}
+			.into(),
+		);
+		assert_eq!(DdcPayouts::owing_providers(cluster_id, node1), Some(60_000_000));
+		assert_eq!(DdcPayouts::owing_nodes((cluster_id, node1), node_key(node1)), Some(60_000_000));
+
+		assert_noop!(
+			DdcPayouts::claim_owed_reward(
+				RuntimeOrigin::signed(node1),
+				cluster_id,
+				node_key(node1)
+			),
+			Error::<Test>::NotEnoughVaultBalance
+		);
+		assert_noop!(
+			DdcPayouts::claim_owed_reward(RuntimeOrigin::signed(bank), cluster_id, node_key(bank)),
+			Error::<Test>::NoOwedReward
+		);
+
+		assert_eq!(DdcPayouts::total_owed_rewards(cluster_id), 60_000_000);
+
+		// owed reward is not paid out of the vault of another report
+		assert_ok!(Balances::transfer(RuntimeOrigin::signed(bank), vault, reward));
+		assert_ok!(DdcPayouts::send_rewarding_providers_batch(
+			RuntimeOrigin::signed(dac_account),
+			cluster_id,
+			next_era,
+			batch_index,
+			payees,
+			nodes_proofs[0].clone(),
+		));
+
+		System::assert_last_event(
+			Event::Rewarded {
+				cluster_id,
+				era: next_era,
+				batch_index,
+				node_provider_id: node1,
+				rewarded: reward,
+				expected_to_reward: reward,
+			}
+			.into(),
+		);
+		assert_eq!(DdcPayouts::owing_providers(cluster_id, node1), Some(60_000_000));
+
+		// provider claims the owed reward once repaid debts fund it
+		let owed_rewards_account = DdcPayouts::owed_rewards_account_id(cluster_id);
+		assert_ok!(Balances::transfer(
+			RuntimeOrigin::signed(bank),
+			owed_rewards_account,
+			10_000_000
+		));
+		assert_ok!(DdcPayouts::claim_owed_reward(
+			RuntimeOrigin::signed(node1),
+			cluster_id,
+			node_key(node1)
+		));
+
+		System::assert_last_event(
+			Event::OwedRewardPaid {
+				cluster_id,
+				node_provider_id: node1,
+				paid: 10_000_000,
+				remaining: 50_000_000,
+			}
+			.into(),
+		);
+		assert_eq!(DdcPayouts::owing_providers(cluster_id, node1), Some(50_000_000));
+		assert_eq!(DdcPayouts::total_owed_rewards(cluster_id), 50_000_000);
+		assert_eq!(Balances::free_balance(node1), 150_000_000);
+
+		// delegators of the node are paid their share of the owed reward
+		let delegator = 77u128;
+		assert_ok!(Balances::transfer(
+			RuntimeOrigin::signed(bank),
+			owed_rewards_account,
+			10_000_000
+		));
+		DelegatorShare::set(Some((delegator, Perquintill::from_percent(30))));
+		assert_ok!(DdcPayouts::claim_owed_reward(
+			RuntimeOrigin::signed(node1),
+			cluster_id,
+			node_key(node1)
+		));
+		DelegatorShare::set(None);
+
+		System::assert_has_event(
+			Event::OwedDelegatorRewardPaid {
+				cluster_id,
+				node_provider_id: node1,
+				delegator_id: delegator,
+				paid: 3_000_000,
+			}
+			.into(),
+		);
+		System::assert_last_event(
+			Event::OwedRewardPaid {
+				cluster_id,
+				node_provider_id: node1,
+				paid: 10_000_000,
+				remaining: 40_000_000,
+			}
+			.into(),
+		);
+		assert_eq!(Balances::free_balance(delegator), 3_000_000);
+		assert_eq!(Balances::free_balance(node1), 157_000_000);
+	})
+}
+
+#[test]
+fn claim_owed_reward_pays_the_reward_owed_for_the_node() {
+	ExtBuilder.build_and_execute(|| {
+		System::set_block_number(1);
+
+		let bank = 1u128;
+		let provider = 10u128;
+		let delegator = 77u128;
+		let cluster_id = ClusterId::from([12; 20]);
+		let node_a = node_key(provider);
+		let node_b = node_key(11u128);
+
+		// part of the reward was owed before the rewards were tracked per node
+		OwingProviders::<Test>::insert(cluster_id, provider, 100_000_000);
+		OwingNodes::<Test>::insert((cluster_id, provider), node_a.clone(), 30_000_000);
+		OwingNodes::<Test>::insert((cluster_id, provider), node_b.clone(), 50_000_000);
+		TotalOwedRewards::<Test>::insert(cluster_id, 100_000_000);
+		assert_ok!(Balances::transfer(
+			RuntimeOrigin::signed(bank),
+			DdcPayouts::owed_rewards_account_id(cluster_id),
+			100_000_000
+		));
+
+		// the delegators of the node claimed are paid their share only
+		let provider_balance_before = Balances::free_balance(provider);
+		DelegatorShare::set(Some((delegator, Perquintill::from_percent(30))));
+		assert_ok!(DdcPayouts::claim_owed_reward(
+			RuntimeOrigin::signed(provider),
+			cluster_id,
+			node_a.clone()
+		));
+		DelegatorShare::set(None);
+
+		System::assert_last_event(
+			Event::OwedRewardPaid {
+				cluster_id,
+				node_provider_id: provider,
+				paid: 50_000_000,
+				remaining: 50_000_000,
+			}
+			.into(),
+		);
+		assert_eq!(Balances::free_balance(delegator), 15_000_000);
+		assert_eq!(Balances::free_balance(provider) - provider_balance_before, 35_000_000);
+		assert_eq!(DdcPayouts::owing_nodes((cluster_id, provider), node_a.clone()), None);
+		assert_eq!(
+			DdcPayouts::owing_nodes((cluster_id, provider), node_b.clone()),
+			Some(50_000_000)
+		);
+
+		assert_noop!(
+			DdcPayouts::claim_owed_reward(RuntimeOrigin::signed(provider), cluster_id, node_a),
+			Error::<Test>::NoOwedReward
+		);
+
+		assert_ok!(DdcPayouts::claim_owed_reward(
+			RuntimeOrigin::signed(provider),
+			cluster_id,
+			node_b.clone()
+		));
+
+		System::assert_last_event(
+			Event::OwedRewardPaid {
+				cluster_id,
+				node_provider_id: provider,
+				paid: 50_000_000,
+				remaining: 0,
+			}
+			.into(),
+		);
+		assert_eq!(Balances::free_balance(delegator), 15_000_000);
+		assert_eq!(Balances::free_balance(provider) - provider_balance_before, 85_000_000);
+		assert_eq!(DdcPayouts::owing_nodes((cluster_id, provider), node_b), None);
+		assert_eq!(DdcPayouts::owing_providers(cluster_id, provider), None);
+		assert_eq!(DdcPayouts::total_owed_rewards(cluster_id), 0);
+	})
+}
+
+#[test]
+fn send_rewarding_providers_batch_pays_delegators() {
+	ExtBuilder.build_and_execute(|| {
+		System::set_block_number(1);
+
+		let dac_account = 123u128;
+		let bank = 1u128;
+		let node1 = 10u128;
+		let delegator = 77u128;
+		let cluster_id = ClusterId::from([12; 20]);
+		let era = 100;
+		let batch_index = 0;
+		let reward = 100_000_000;
+		let node_usage = NodeUsage {
+			transferred_bytes: 0,
+			stored_bytes: 10,
+			number_of_puts: 0,
+			number_of_gets: 0,
+		};
+		let payees = vec![(node1, node_key(node1), node_usage.clone())];
+		let (nodes_merkle_root, nodes_proofs) = get_batches_commitment(&[(batch_index, &payees)]);
+		let vault = DdcPayouts::account_id();
+
+		ActiveBillingReports::<Test>::insert(
+			cluster_id,
+			era,
+			BillingReport::<Test> {
+				state: State::RewardingProviders,
+				vault,
+				total_customer_charge: CustomerCharge { storage: reward, ..Default::default() },
+				total_node_usage: node_usage,
+				nodes_merkle_root,
+				..Default::default()
+			},
+		);
+
+		assert_ok!(DdcPayouts::set_validator_threshold(RuntimeOrigin::root(), 1));
+		assert_ok!(Balances::transfer(RuntimeOrigin::signed(bank), vault, reward + 1));
+		DelegatorShare::set(Some((delegator, Perquintill::from_percent(30))));
+		let provider_balance_before = Balances::free_balance(node1);
+
+		assert_ok!(DdcPayouts::send_rewarding_providers_batch(
+			RuntimeOrigin::signed(dac_account),
+			cluster_id,
+			era,
+			batch_index,
+			payees,
+			nodes_proofs[0].clone(),
+		));
+		DelegatorShare::set(None);
+
+		System::assert_has_event(
+			Event::DelegatorRewarded {
+				cluster_id,
+				era,
+				node_provider_id: node1,
+				delegator_id: delegator,
+				rewarded: 30_000_000,
+			}
+			.into(),
+		);
+		System::assert_last_event(
+			Event::Rewarded {
+				cluster_id,
+				era,
+				batch_index,
+				node_provider_id: node1,
+				rewarded: reward,
+				expected_to_reward: reward,
+			}
+			.into(),
+		);
+		assert_eq!(Balances::free_balance(delegator), 30_000_000);
+		assert_eq!(Balances::free_balance(node1) - provider_balance_before, 70_000_000);
+	})
+}
+
+#[test]
+fn end_rewarding_providers_fails_uninitialised() {
+	ExtBuilder.build_and_execute(|| {
+		let root_account = 1u128;
+		let dac_account = 2u128;
+		let user1 = 3u128;
+		let user2 = 4u128;
+		let node1 = 33u128;
+		let cluster_id = ClusterId::from([12; 20]);
+		let era = 100;
+		let max_batch_index = 1;
+		let batch_index = 0;
+		let payers1 = vec![(user1, user1 as BucketId, CustomerUsage::default())];
+		let payers2 = vec![(user2, user2 as BucketId, CustomerUsage::default())];
+		let payees = vec![(node1, node_key(node1), NodeUsage::default())];
+		let total_node_usage = NodeUsage::default();
+		let start_date = NaiveDate::from_ymd_opt(2023, 4, 1).unwrap(); // April 1st
+
+		let time = NaiveTime::from_hms_opt(0, 0, 0).unwrap(); // Midnight
+		let start_era: i64 =
+			DateTime::<Utc>::from_naive_utc_and_offset(start_date.and_time(time), Utc).timestamp();
+		let end_era: i64 = start_era + (30.44 * 24.0 * 3600.0) as i64;
+		let (customers_merkle_root, customers_proofs) =
+			get_batches_commitment(&[(batch_index, &payers1), (batch_index + 1, &payers2)]);
+		let (nodes_merkle_root, nodes_proofs) = get_batches_commitment(&[(batch_index, &payees)]);
+
+		assert_noop!(
+			DdcPayouts::end_rewarding_providers(
+				RuntimeOrigin::signed(root_account),
+				cluster_id,
+				era,
+			),
+			Error::<Test>::Unauthorised
+		);
+
+		assert_noop!(
+			DdcPayouts::end_rewarding_providers(RuntimeOrigin::root(), cluster_id, era,),
+			BadOrigin
+		);
+
+		assert_ok!(DdcPayouts::set_validator_threshold(RuntimeOrigin::root(), 1));
+
+		assert_noop!(
+			DdcPayouts::end_rewarding_providers(
+				RuntimeOrigin::signed(dac_account),
+				cluster_id,
+				era,
+			),
+			Error::<Test>::BillingReportDoesNotExist
+		);
+
+		assert_ok!(DdcPayouts::begin_billing_report(
+			RuntimeOrigin::signed(dac_account),
+			cluster_id,
+			era,
+			start_era,
+			end_era,
+			customers_merkle_root,
+			nodes_merkle_root,
+			max_batch_index,
+			max_batch_index,
+			CustomerUsage::default(),
+			total_node_usage,
+		));
+
+		assert_noop!(
+			DdcPayouts::end_rewarding_providers(
+				RuntimeOrigin::signed(dac_account),
+				cluster_id,
+				era,
+			),
+			Error::<Test>::NotExpectedState
+		);
+
+		assert_ok!(DdcPayouts::begin_charging_customers(
+			RuntimeOrigin::signed(dac_account),
+			cluster_id,
+			era,
+		));
+
+		assert_noop!(
+			DdcPayouts::end_rewarding_providers(
+				RuntimeOrigin::signed(dac_account),
+				cluster_id,
+				era,
+			),
+			Error::<Test>::NotExpectedState
+		);
+
+		assert_ok!(DdcPayouts::send_charging_customers_batch(
+			RuntimeOrigin::signed(dac_account),
+			cluster_id,
+			era,
+			batch_index,
+			payers1,
+			customers_proofs[0].clone(),
+		));
+
+		assert_noop!(
+			DdcPayouts::end_rewarding_providers(
+				RuntimeOrigin::signed(dac_account),
+				cluster_id,
+				era,
+			),
+			Error::<Test>::NotExpectedState
+		);
+
+		assert_ok!(DdcPayouts::send_charging_customers_batch(
+			RuntimeOrigin::signed(dac_account),
+			cluster_id,
+			era,
+			batch_index + 1,
+			payers2,
+			customers_proofs[1].clone(),
+		));
+
+		assert_noop!(
+			DdcPayouts::end_rewarding_providers(
+				RuntimeOrigin::signed(dac_account),
+				cluster_id,
+				era,
+			),
+			Error::<Test>::NotExpectedState
+		);
+
+		assert_ok!(DdcPayouts::end_charging_customers(
+			RuntimeOrigin::signed(dac_account),
+			cluster_id,
+			era,
+		));
+
+		assert_noop!(
+			DdcPayouts::end_rewarding_providers(
+				RuntimeOrigin::signed(dac_account),
+				cluster_id,
+				era,
+			),
+			Error::<Test>::NotExpectedState
+		);
+
+		assert_ok!(DdcPayouts::begin_rewarding_providers(
+			RuntimeOrigin::signed(dac_account),
+			cluster_id,
+			era,
+		));
+
+		assert_noop!(
+			DdcPayouts::end_rewarding_providers(
+				RuntimeOrigin::signed(dac_account),
+				cluster_id,
+				era,
+			),
+			Error::<Test>::BatchesMissed
+		);
+
+		assert_ok!(DdcPayouts::send_rewarding_providers_batch(
+			RuntimeOrigin::signed(dac_account),
+			cluster_id,
+			era,
+			batch_index,
+			payees,
+			nodes_proofs[0].clone(),
+		));
+
+		assert_noop!(
+			DdcPayouts::end_rewarding_providers(
+				RuntimeOrigin::signed(dac_account),
+				cluster_id,
+				era,
+			),
+			Error::<Test>::BatchesMissed
+		);
+	})
+}
+
+#[test]
+fn end_rewarding_providers_works() {
+	ExtBuilder.build_and_execute(|| {
+		System::set_block_number(1);
+
+		let start_date = NaiveDate::from_ymd_opt(2023, 4, 1).unwrap(); // April 1st
+
+		let time = NaiveTime::from_hms_opt(0, 0, 0).unwrap(); // Midnight
+		let start_era: i64 =
+			DateTime::<Utc>::from_naive_utc_and_offset(start_date.and_time(time), Utc).timestamp();
+		let end_era: i64 = start_era + (30.44 * 24.0 * 3600.0) as i64;
+		let dac_account = 2u128;
+		let user1 = 1u128;
+		let node1 = 33u128;
+		let cluster_id = ClusterId::from([12; 20]);
+		let era = 100;
+		let max_batch_index = 0;
+		let batch_index = 0;
+		let usage1 = CustomerUsage {
+			transferred_bytes: 23452345,
+			stored_bytes: 3345234523,
+			number_of_puts: 4456456345234523,
+			number_of_gets: 523423,
+		};
+
+		let node_usage1 = NodeUsage {
+			// CDN + Storage
+			transferred_bytes: usage1.transferred_bytes * 2 / 3,
+			stored_bytes: usage1.stored_bytes * 2 / 3,
+			number_of_puts: usage1.number_of_puts * 2 / 3,
+			number_of_gets: usage1.number_of_gets * 2 / 3,
+		};
+		let total_node_usage = node_usage1.clone();
+		let payers = vec![(user1, user1 as BucketId, usage1)];
+		let payees = vec![(node1, node_key(node1), node_usage1)];
+
+		let (customers_merkle_root, customers_proofs) =
+			get_batches_commitment(&[(batch_index, &payers)]);
+		let (nodes_merkle_root, nodes_proofs) = get_batches_commitment(&[(batch_index, &payees)]);
+
+		assert_ok!(DdcPayouts::set_validator_threshold(RuntimeOrigin::root(), 1));
+
+		assert_ok!(DdcPayouts::begin_billing_report(
+			RuntimeOrigin::signed(dac_account),
+			cluster_id,
+			era,
+			start_era,
+			end_era,
+			customers_merkle_root,
+			nodes_merkle_root,
+			max_batch_index,
+			max_batch_index,
+			customers_usage(&payers),
+			total_node_usage,
+		));
+
+		let mut report = DdcPayouts::active_billing_reports(cluster_id, era).unwrap();
+		assert_eq!(report.state, State::Initialized);
+
+		assert_ok!(DdcPayouts::begin_charging_customers(
+			RuntimeOrigin::signed(dac_account),
+			cluster_id,
+			era,
+		));
+
+		assert_ok!(DdcPayouts::send_charging_customers_batch(
+			RuntimeOrigin::signed(dac_account),
+			cluster_id,
+			era,
+			batch_index,
+			payers,
+			customers_proofs[0].clone(),
+		));
+
+		assert_ok!(DdcPayouts::end_charging_customers(
+			RuntimeOrigin::signed(dac_account),
+			cluster_id,
+			era,
+		));
+
+		assert_ok!(DdcPayouts::begin_rewarding_providers(
+			RuntimeOrigin::signed(dac_account),
+			cluster_id,
+			era,
+		));
+
+		assert_ok!(DdcPayouts::send_rewarding_providers_batch(
+			RuntimeOrigin::signed(dac_account),
+			cluster_id,
+			era,
+			batch_index,
+			payees,
+			nodes_proofs[0].clone(),
+		));
+
+		assert_ok!(DdcPayouts::end_rewarding_providers(
+			RuntimeOrigin::signed(dac_account),
+			cluster_id,
+			era,
+		));
+
+		System::assert_last_event(Event::RewardingFinished { cluster_id, era }.into());
+
+		report = DdcPayouts::active_billing_reports(cluster_id, era).unwrap();
+		assert_eq!(report.state, State::ProvidersRewarded);
+	})
+}
+
+#[test]
+fn end_charging_and_rewarding_require_committed_usage() {
+	ExtBuilder.build_and_execute(|| {
+		System::set_block_number(1);
+
+		let dac_account = 2u128;
+		let user1 = 1u128;
+		let node1 = 33u128;
+		let cluster_id = ClusterId::from([12; 20]);
+		let start_era: i64 = 1_000_000_000;
+		let end_era: i64 = start_era + (30.44 * 24.0 * 3600.0) as i64;
+		let batch_index = 0;
+		let usage1 = CustomerUsage {
+			transferred_bytes: 1024,
+			stored_bytes: 1024,
+			number_of_puts: 1,
+			number_of_gets: 1,
+		};
+		let node_usage1 = NodeUsage {
+			transferred_bytes: 1024,
+			stored_bytes: 1024,
+			number_of_puts: 1,
+			number_of_gets: 1,
+		};
+		let payers = vec![(user1, user1 as BucketId, usage1.clone())];
+		let payees = vec![(node1, node_key(node1), node_usage1.clone())];
+		let (customers_merkle_root, customers_proofs) =
+			get_batches_commitment(&[(batch_index, &payers)]);
+		let (nodes_merkle_root, nodes_proofs) = get_batches_commitment(&[(batch_index, &payees)]);
+
+		assert_ok!(DdcPayouts::set_validator_threshold(RuntimeOrigin::root(), 1));
+
+		assert_noop!(
+			DdcPayouts::begin_billing_report(
+				RuntimeOrigin::signed(dac_account),
+				cluster_id,
+				100,
+				start_era,
+				end_era,
+				customers_merkle_root,
+				nodes_merkle_root,
+				MaxBatchesCount::get(),
+				0,
+				usage1.clone(),
+				node_usage1.clone(),
+			),
+			Error::<Test>::BatchIndexOverflow
+		);
+
+		// the batches sent fall short of the committed customers usage
+		let era = 100;
+		assert_ok!(DdcPayouts::begin_billing_report(
+			RuntimeOrigin::signed(dac_account),
+			cluster_id,
+			era,
+			start_era,
+			end_era,
+			customers_merkle_root,
+			nodes_merkle_root,
+			0,
+			0,
+			combined_usage(&usage1, &usage1),
+			node_usage1.clone(),
+		));
+		assert_ok!(DdcPayouts::begin_charging_customers(
+			RuntimeOrigin::signed(dac_account),
+			cluster_id,
+			era
+		));
+		assert_ok!(DdcPayouts::send_charging_customers_batch(
+			RuntimeOrigin::signed(dac_account),
+			cluster_id,
+			era,
+			batch_index,
+			payers.clone(),
+			customers_proofs[0].clone(),
+		));
+		assert_noop!(
+			DdcPayouts::end_charging_customers(RuntimeOrigin::signed(dac_account), cluster_id, era),
+			Error::<Test>::UsageMismatch
+		);
+
+		// the batches sent fall short of the committed nodes usage
+		let era = 101;
+		assert_ok!(DdcPayouts::begin_billing_report(
+			RuntimeOrigin::signed(dac_account),
+			cluster_id,
+			era,
+			start_era,
+			end_era,
+			customers_merkle_root,
+			nodes_merkle_root,
+			0,
+			0,
+			usage1,
+			NodeUsage {
+				transferred_bytes: 2048,
+				stored_bytes: 2048,
+				number_of_puts: 2,
+				number_of_gets: 2,
+			},
+		));
+		assert_ok!(DdcPayouts::begin_charging_customers(
+			RuntimeOrigin::signed(dac_account),
+			cluster_id,
+			era
+		));
+		assert_ok!(DdcPayouts::send_charging_customers_batch(
+			RuntimeOrigin::signed(dac_account),
+			cluster_id,
+			era,
+			batch_index,
+			payers,
+			customers_proofs[0].clone(),
+		));
+		assert_ok!(DdcPayouts::end_charging_customers(
+			RuntimeOrigin::signed(dac_account),
+			cluster_id,
+			era
+		));
+		assert_ok!(DdcPayouts::begin_rewarding_providers(
+			RuntimeOrigin::signed(dac_account),
+			cluster_id,
+			era
+		));
+		assert_ok!(DdcPayouts::send_rewarding_providers_batch(
+			RuntimeOrigin::signed(dac_account),
+			cluster_id,
+			era,
+			batch_index,
+			payees,
+			nodes_proofs[0].clone(),
+		));
+		assert_noop!(
+			DdcPayouts::end_rewarding_providers(
+				RuntimeOrigin::signed(dac_account),
+				cluster_id,
+				era
+			),
+			Error::<Test>::UsageMismatch
+		);
+	})
+}
+
+#[test]
+fn end_billing_report_fails_uninitialised() {
+	ExtBuilder.build_and_execute(|| {
+		let start_date = NaiveDate::from_ymd_opt(2023, 4, 1).unwrap(); // April 1st
+
+		let time = NaiveTime::from_hms_opt(0, 0, 0).unwrap(); // Midnight
+		let start_era: i64 =
+			DateTime::<Utc>::from_naive_utc_and_offset(start_date.and_time(time), Utc).timestamp();
+		let end_era: i64 = start_era + (30.44 * 24.0 * 3600.0) as i64;
+		let root_account = 1u128;
+		let dac_account = 2u128;
+		let user1 = 3u128;
+		let user2 = 4u128;
+		let node1 = 33u128;
+		let cluster_id = ClusterId::from([12; 20]);
+		let era = 100;
+		let max_batch_index = 1;
+		let batch_index = 0;
+		let payers1 = vec![(user1, user1 as BucketId, CustomerUsage::default())];
+		let payers2 = vec![(user2, user2 as BucketId, CustomerUsage::default())];
+		let payees = vec![(node1, node_key(node1), NodeUsage::default())];
+		let total_node_usage = NodeUsage::default();
+		let (customers_merkle_root, customers_proofs) =
+			get_batches_commitment(&[(batch_index, &payers1), (batch_index + 1, &payers2)]);
+		let (nodes_merkle_root, nodes_proofs) =
+			get_batches_commitment(&[(batch_index, &payees), (batch_index + 1, &payees)]);
+
+		assert_noop!(
+			DdcPayouts::end_billing_report(RuntimeOrigin::signed(root_account), cluster_id, era,),
+			Error::<Test>::Unauthorised
+		);
+
+		assert_noop!(
+			DdcPayouts::end_billing_report(RuntimeOrigin::root(), cluster_id, era,),
+			BadOrigin
+		);
+
+		assert_ok!(DdcPayouts::set_validator_threshold(RuntimeOrigin::root(), 1));
+
+		assert_noop!(
+			DdcPayouts::end_billing_report(RuntimeOrigin::signed(dac_account), cluster_id, era,),
+			Error::<Test>::BillingReportDoesNotExist
+		);
+
+		assert_ok!(DdcPayouts::begin_billing_report(
+			RuntimeOrigin::signed(dac_account),
+			cluster_id,
+			era,
+			start_era,
+			end_era,
+			customers_merkle_root,
+			nodes_merkle_root,
+			max_batch_index,
+			max_batch_index,
+			CustomerUsage::default(),
+			total_node_usage,
+		));
+
+		assert_noop!(
+			DdcPayouts::end_billing_report(RuntimeOrigin::signed(dac_account), cluster_id, era,),
+			Error::<Test>::NotExpectedState
+		);
+
+		assert_ok!(DdcPayouts::begin_charging_customers(
+			RuntimeOrigin::signed(dac_account),
+			cluster_id,
+			era,
+		));
+
+		assert_noop!(
+			DdcPayouts::end_billing_report(RuntimeOrigin::signed(dac_account), cluster_id, era,),
+			Error::<Test>::NotExpectedState
+		);
+
+		assert_ok!(DdcPayouts::send_charging_customers_batch(
+			RuntimeOrigin::signed(dac_account),
+			cluster_id,
+			era,
+			batch_index,
+			payers1,
+			customers_proofs[0].clone(),
+		));
+
+		assert_noop!(
+			DdcPayouts::end_billing_report(RuntimeOrigin::signed(dac_account), cluster_id, era,),
+			Error::<Test>::NotExpectedState
+		);
+
+		assert_ok!(DdcPayouts::send_charging_customers_batch(
+			RuntimeOrigin::signed(dac_account),
+			cluster_id,
+			era,
+			batch_index + 1,
+			payers2,
+			customers_proofs[1].clone(),
+		));
+
+		assert_noop!(
+			DdcPayouts::end_billing_report(RuntimeOrigin::signed(dac_account), cluster_id, era,),
+			Error::<Test>::NotExpectedState
+		);
+
+		assert_ok!(DdcPayouts::end_charging_customers(
+			RuntimeOrigin::signed(dac_account),
+			cluster_id,
+			era,
+		));
+
+		assert_noop!(
+			DdcPayouts::end_billing_report(RuntimeOrigin::signed(dac_account), cluster_id, era,),
+			Error::<Test>::NotExpectedState
+		);
+
+		assert_ok!(DdcPayouts::begin_rewarding_providers(
+			RuntimeOrigin::signed(dac_account),
+			cluster_id,
+			era,
+		));
+
+		assert_noop!(
+			DdcPayouts::end_billing_report(RuntimeOrigin::signed(dac_account), cluster_id, era,),
+			Error::<Test>::NotExpectedState
+		);
+
+		assert_ok!(DdcPayouts::send_rewarding_providers_batch(
+			RuntimeOrigin::signed(dac_account),
+			cluster_id,
+			era,
+			batch_index,
+			payees.clone(),
+			nodes_proofs[0].clone(),
+		));
+
+		assert_noop!(
+			DdcPayouts::end_billing_report(RuntimeOrigin::signed(dac_account), cluster_id, era,),
+			Error::<Test>::NotExpectedState
+		);
+
+		assert_ok!(DdcPayouts::send_rewarding_providers_batch(
+			RuntimeOrigin::signed(dac_account),
+			cluster_id,
+			era,
+			batch_index + 1,
+			payees,
+			nodes_proofs[1].clone(),
+		));
+
+		assert_noop!(
+			DdcPayouts::end_billing_report(RuntimeOrigin::signed(dac_account), cluster_id, era,),
+			Error::<Test>::NotExpectedState
+		);
+	})
+}
+
+#[test]
+fn end_billing_report_works() {
+	ExtBuilder.build_and_execute(|| {
+		System::set_block_number(1);
+
+		let start_date = NaiveDate::from_ymd_opt(2023, 4, 1).unwrap(); // April 1st
+
+		let time = NaiveTime::from_hms_opt(0, 0, 0).unwrap(); // Midnight
+		let start_era: i64 =
+			DateTime::<Utc>::from_naive_utc_and_offset(start_date.and_time(time), Utc).timestamp();
+		let end_era: i64 = start_era + (30.44 * 24.0 * 3600.0) as i64;
+		let dac_account = 2u128;
+		let user1 = 3u128;
+		let node1 = 33u128;
+		let cluster_id = ClusterId::from([12; 20]);
+		let era = 100;
+		let max_batch_index = 0;
+		let batch_index = 0;
+		let total_node_usage = NodeUsage::default();
+		let payers = vec![(user1, user1 as BucketId, CustomerUsage::default())];
+		let payees = vec![(node1, node_key(node1), NodeUsage::default())];
+		let (customers_merkle_root, customers_proofs) =
+			get_batches_commitment(&[(batch_index, &payers)]);
+		let (nodes_merkle_root, nodes_proofs) = get_batches_commitment(&[(batch_index, &payees)]);
+
+		assert_ok!(DdcPayouts::set_validator_threshold(RuntimeOrigin::root(), 1));
+
+		assert_ok!(DdcPayouts::begin_billing_report(
+			RuntimeOrigin::signed(dac_account),
+			cluster_id,
+			era,
+			start_era,
+			end_era,
+			customers_merkle_root,
+			nodes_merkle_root,
+			max_batch_index,
+			max_batch_index,
+			CustomerUsage::default(),
+			total_node_usage,
+		));
+
+		let report = DdcPayouts::active_billing_reports(cluster_id, era).unwrap();
+		assert_eq!(report.state, State::Initialized);
+
+		assert_ok!(DdcPayouts::begin_charging_customers(
+			RuntimeOrigin::signed(dac_account),
+			cluster_id,
+			era,
+		));
+
+		assert_ok!(DdcPayouts::send_charging_customers_batch(
+			RuntimeOrigin::signed(dac_account),
+			cluster_id,
+			era,
+			batch_index,
+			payers,
+			customers_proofs[0].clone(),
+		));
+
+		assert_ok!(DdcPayouts::end_charging_customers(
+			RuntimeOrigin::signed(dac_account),
+			cluster_id,
+			era,
+		));
+
+		assert_ok!(DdcPayouts::begin_rewarding_providers(
+			RuntimeOrigin::signed(dac_account),
+			cluster_id,
+			era,
+		));
+
+		assert_ok!(DdcPayouts::send_rewarding_providers_batch(
+			RuntimeOrigin::signed(dac_account),
+			cluster_id,
+			era,
+			batch_index,
+			payees,
+			nodes_proofs[0].clone(),
+		));
+
+		assert_ok!(DdcPayouts::end_rewarding_providers(
+			RuntimeOrigin::signed(dac_account),
+			cluster_id,
+			era,
+		));
+
+		assert_ok!(DdcPayouts::end_billing_report(
+			RuntimeOrigin::signed(dac_account),
+			cluster_id,
+			era,
+		));
+
+		System::assert_last_event(Event::BillingReportFinalized { cluster_id, era }.into());
+
+		let report_end = DdcPayouts::active_billing_reports(cluster_id, era).unwrap();
+		assert!(report_end.rewarding_processed_batches.is_empty());
+		assert!(report_end.charging_processed_batches.is_empty());
+		assert_eq!(report_end.state, State::Finalized);
+	})
+}
+
+fn run_billing_report_until_providers_rewarded(
+	dac_account: AccountId,
+	cluster_id: ClusterId,
+	era: DdcEra,
+) {
+	let user1 = 3u128;
+	let node1 = 33u128;
+	run_charged_billing_report_until_providers_rewarded(
+		dac_account,
+		cluster_id,
+		era,
+		vec![(user1, user1 as BucketId, CustomerUsage::default())],
+		vec![(node1, node_key(node1), NodeUsage::default())],
+	);
+}
+
+fn run_charged_billing_report_until_providers_rewarded(
+	dac_account: AccountId,
+	cluster_id: ClusterId,
+	era: DdcEra,
+	payers: Vec<(AccountId, BucketId, CustomerUsage)>,
+	payees: Vec<(AccountId, NodePubKey, NodeUsage)>,
+) {
+	let start_date = NaiveDate::from_ymd_opt(2023, 4, 1).unwrap(); // April 1st
+	let time = NaiveTime::from_hms_opt(0, 0, 0).unwrap(); // Midnight
+	let start_era: i64 =
+		DateTime::<Utc>::from_naive_utc_and_offset(start_date.and_time(time), Utc).timestamp();
+	let end_era: i64 = start_era + (30.44 * 24.0 * 3600.0) as i64;
+	let max_batch_index = 0;
+	let batch_index = 0;
+	let total_node_usage =
+		payees.iter().fold(NodeUsage::default(), |total, (_, _, usage)| NodeUsage {
+			transferred_bytes: total.transferred_bytes + usage.transferred_bytes,
+			stored_bytes: total.stored_bytes + usage.stored_bytes,
+			number_of_puts: total.number_of_puts + usage.number_of_puts,
+			number_of_gets: total.number_of_gets + usage.number_of_gets,
+		});
+	let (customers_merkle_root, customers_proofs) =
+		get_batches_commitment(&[(batch_index, &payers)]);
+	let (nodes_merkle_root, nodes_proofs) = get_batches_commitment(&[(batch_index, &payees)]);
+
+	assert_ok!(DdcPayouts::set_validator_threshold(RuntimeOrigin::root(), 1));
+	assert_ok!(DdcPayouts::begin_billing_report(
+		RuntimeOrigin::signed(dac_account),
+		cluster_id,
+		era,
+		start_era,
+		end_era,
+		customers_merkle_root,
+		nodes_merkle_root,
+		max_batch_index,
+		max_batch_index,
+		customers_usage(&payers),
+		total_node_usage,
+	));
+	assert_ok!(DdcPayouts::begin_charging_customers(
+		RuntimeOrigin::signed(dac_account),
+		cluster_id,
+		era,
+	));
+	assert_ok!(DdcPayouts::send_charging_customers_batch(
+		RuntimeOrigin::signed(dac_account),
+		cluster_id,
+		era,
+		batch_index,
+		payers,
+		customers_proofs[0].clone(),
+	));
+	assert_ok!(DdcPayouts::end_charging_customers(
+		RuntimeOrigin::signed(dac_account),
+		cluster_id,
+		era,
+	));
+	assert_ok!(DdcPayouts::begin_rewarding_providers(
+		RuntimeOrigin::signed(dac_account),
+		cluster_id,
+		era,
+	));
+	assert_ok!(DdcPayouts::send_rewarding_providers_batch(
+		RuntimeOrigin::signed(dac_account),
+		cluster_id,
+		era,
+		batch_index,
+		payees,
+		nodes_proofs[0].clone(),
+	));
+	assert_ok!(DdcPayouts::end_rewarding_providers(
+		RuntimeOrigin::signed(dac_account),
+		cluster_id,
+		era,
+	));
+}
+
+#[test]
+fn dispute_billing_report_works() {
+	ExtBuilder.build_and_execute(|| {
+		System::set_block_number(1);
+		DisputePeriod::set(10);
+
+		let dac_account = 2u128;
+		let disputer1 = 4u128;
+		let disputer2 = 5u128;
+		let cluster_id = ClusterId::from([12; 20]);
+		let era = 100;
+
+		assert_noop!(
+			DdcPayouts::dispute_billing_report(RuntimeOrigin::signed(disputer1), cluster_id, era),
+			Error::<Test>::BillingReportDoesNotExist
+		);
+
+		run_billing_report_until_providers_rewarded(dac_account, cluster_id, era);
+		assert_eq!(DdcPayouts::dispute_period_ends(cluster_id, era), Some(11));
+
+		assert_ok!(DdcPayouts::dispute_billing_report(
+			RuntimeOrigin::signed(disputer1),
+			cluster_id,
+			era
+		));
+
+		System::assert_last_event(
+			Event::BillingReportDisputed {
+				cluster_id,
+				era,
+				disputer: disputer1,
+				bond: DisputeBond::get(),
+			}
+			.into(),
+		);
+		assert_eq!(Balances::reserved_balance(disputer1), DisputeBond::get());
+
+		assert_noop!(
+			DdcPayouts::dispute_billing_report(RuntimeOrigin::signed(disputer2), cluster_id, era),
+			Error::<Test>::DisputeAlreadyOpened
+		);
+
+		assert_noop!(
+			DdcPayouts::end_billing_report(RuntimeOrigin::signed(dac_account), cluster_id, era),
+			Error::<Test>::BillingReportDisputed
+		);
+
+		assert_noop!(
+			DdcPayouts::settle_dispute(
+				RuntimeOrigin::signed(dac_account),
+				cluster_id,
+				era,
+				DisputeResolution::Rejected
+			),
+			BadOrigin
+		);
+
+		let treasury_balance_before = Balances::free_balance(TREASURY_ACCOUNT_ID);
+		assert_ok!(DdcPayouts::settle_dispute(
+			RuntimeOrigin::root(),
+			cluster_id,
+			era,
+			DisputeResolution::Rejected
+		));
+
+		System::assert_last_event(
+			Event::DisputeSettled {
+				cluster_id,
+				era,
+				disputer: disputer1,
+				resolution: DisputeResolution::Rejected,
+			}
+			.into(),
+		);
+		assert_eq!(Balances::reserved_balance(disputer1), 0);
+		assert_eq!(
+			Balances::free_balance(TREASURY_ACCOUNT_ID),
+			treasury_balance_before + DisputeBond::get()
+		);
+
+		assert_noop!(
+			DdcPayouts::settle_dispute(
+				RuntimeOrigin::root(),
+				cluster_id,
+				era,
+				DisputeResolution::Rejected
+			),
+			Error::<Test>::DisputeDoesNotExist
+		);
+
+		assert_noop!(
+			DdcPayouts::end_billing_report(RuntimeOrigin::signed(dac_account), cluster_id, era),
+			Error::<Test>::DisputePeriodNotElapsed
+		);
+
+		System::set_block_number(11);
+
+		assert_noop!(
+			DdcPayouts::dispute_billing_report(RuntimeOrigin::signed(disputer2), cluster_id, era),
+			Error::<Test>::DisputePeriodElapsed
+		);
+
+		assert_ok!(DdcPayouts::end_billing_report(
+			RuntimeOrigin::signed(dac_account),
+			cluster_id,
+			era,
+		));
+		assert_eq!(DdcPayouts::dispute_period_ends(cluster_id, era), None);
+	})
+}
+
+#[test]
+fn settle_dispute_reverts_report_and_refunds_customers() {
+	ExtBuilder.build_and_execute(|| {
+		System::set_block_number(1);
+		DisputePeriod::set(10);
+
+		let dac_account = 2u128;
+		let disputer = 5u128;
+		let user = 4u128;
+		let node = 10u128;
+		let delegator = 77u128;
+		let cluster_id = ClusterId::from([12; 20]);
+		let era = 100;
+		let usage = CustomerUsage {
+			transferred_bytes: 467457,
+			stored_bytes: 45674567456,
+			number_of_puts: 3456345,
+			number_of_gets: 242334563456423,
+		};
+		let node_usage = NodeUsage {
+			transferred_bytes: 1,
+			stored_bytes: 1,
+			number_of_puts: 1,
+			number_of_gets: 1,
+		};
+		let charge = calculate_charge_for_month(cluster_id, usage.clone());
+		let existential_deposit = Balances::minimum_balance();
+		// the accounts the fees and rewards are clawed back from are kept alive
+		for account_id in [
+			TREASURY_ACCOUNT_ID,
+			RESERVE_ACCOUNT_ID,
+			DdcPayouts::validator_fees_pot_account_id(),
+			node,
+			delegator,
+		] {
+			Balances::make_free_balance_be(&account_id, existential_deposit);
+		}
+		let user_balance_before = Balances::free_balance(user);
+		let treasury_balance_before = Balances::free_balance(TREASURY_ACCOUNT_ID);
+		let reserve_balance_before = Balances::free_balance(RESERVE_ACCOUNT_ID);
+
+		DelegatorShare::set(Some((delegator, Perquintill::from_percent(30))));
+		run_charged_billing_report_until_providers_rewarded(
+			dac_account,
+			cluster_id,
+			era,
+			vec![(user, user as BucketId, usage)],
+			vec![(node, node_key(node), node_usage)],
+		);
+		DelegatorShare::set(None);
+
+		let fees = get_fees(&cluster_id);
+		let treasury_fee = fees.treasury_share * charge;
+		let validators_fee = fees.validators_share * charge;
+		let cluster_reserve_fee = fees.cluster_reserve_share * charge;
+		let provider_reward = Balances::free_balance(node) - existential_deposit;
+		let delegator_reward = Balances::free_balance(delegator) - existential_deposit;
+		assert_eq!(Balances::free_balance(user), user_balance_before - charge);
+		assert!(provider_reward > 0 && delegator_reward > 0);
+		assert_eq!(
+			DdcPayouts::billing_report_funds(cluster_id, era),
+			CollectedFunds {
+				collected: charge,
+				treasury_fee,
+				validators_fee,
+				cluster_reserve_fee,
+				owed_rewards_funded: 0,
+			}
+		);
+		assert_eq!(
+			DdcPayouts::rewarded_providers((cluster_id, era), node),
+			Some(ProviderRewardRecord { rewarded: provider_reward, owed: 0 })
+		);
+
+		assert_ok!(DdcPayouts::dispute_billing_report(
+			RuntimeOrigin::signed(disputer),
+			cluster_id,
+			era
+		));
+		assert_ok!(DdcPayouts::settle_dispute(
+			RuntimeOrigin::root(),
+			cluster_id,
+			era,
+			DisputeResolution::Reverted
+		));
+
+		// the fees are clawed back straight away and nothing else can be done with the report
+		System::assert_has_event(
+			Event::FeesClawedBack {
+				cluster_id,
+				era,
+				treasury_fee,
+				validators_fee,
+				cluster_reserve_fee,
+				owed_rewards_funded: 0,
+			}
+			.into(),
+		);
+		assert_eq!(Balances::free_balance(TREASURY_ACCOUNT_ID), treasury_balance_before);
+		assert_eq!(Balances::free_balance(RESERVE_ACCOUNT_ID), reserve_balance_before);
+		assert_eq!(DdcPayouts::validator_fees_pots(cluster_id, era).unwrap().total_fees, 0);
+		assert_eq!(Balances::reserved_balance(disputer), 0);
+		assert_eq!(DdcPayouts::dispute_period_ends(cluster_id, era), None);
+		assert_eq!(
+			DdcPayouts::active_billing_reports(cluster_id, era).unwrap().state,
+			State::Aborted
+		);
+		assert_noop!(
+			DdcPayouts::end_billing_report(RuntimeOrigin::signed(dac_account), cluster_id, era),
+			Error::<Test>::NotExpectedState
+		);
+		assert_noop!(
+			DdcPayouts::roll_back_billing_report(
+				RuntimeOrigin::signed(disputer),
+				cluster_id,
+				era,
+				MaxBatchSize::get() as u32 + 1
+			),
+			Error::<Test>::BatchSizeIsOutOfBounds
+		);
+
+		// the rewards are clawed back before the customers are refunded
+		assert_ok!(DdcPayouts::roll_back_billing_report(
+			RuntimeOrigin::signed(disputer),
+			cluster_id,
+			era,
+			2
+		));
+		System::assert_has_event(
+			Event::RewardClawedBack {
+				cluster_id,
+				era,
+				account_id: node,
+				expected: provider_reward,
+				clawed_back: provider_reward,
+			}
+			.into(),
+		);
+		System::assert_has_event(
+			Event::RewardClawedBack {
+				cluster_id,
+				era,
+				account_id: delegator,
+				expected: delegator_reward,
+				clawed_back: delegator_reward,
+			}
+			.into(),
+		);
+		assert_eq!(Balances::free_balance(node), existential_deposit);
+		assert_eq!(Balances::free_balance(delegator), existential_deposit);
+		assert_eq!(
+			DdcPayouts::billing_report_rollbacks(cluster_id, era).unwrap().refund_ratio,
+			Some(Perquintill::one())
+		);
+		assert_eq!(Balances::free_balance(user), user_balance_before - charge);
+
+		assert_ok!(DdcPayouts::roll_back_billing_report(
+			RuntimeOrigin::signed(disputer),
+			cluster_id,
+			era,
+			2
+		));
+		System::assert_has_event(
+			Event::ChargeRefunded { cluster_id, era, customer_id: user, refunded: charge, debt: 0 }
+				.into(),
+		);
+		System::assert_last_event(
+			Event::BillingReportRolledBack { cluster_id, era, refund_ratio: Perquintill::one() }
+				.into(),
+		);
+		assert_eq!(Balances::free_balance(user), user_balance_before);
+		assert!(DdcPayouts::billing_report_rollbacks(cluster_id, era).is_none());
+		assert_noop!(
+			DdcPayouts::roll_back_billing_report(
+				RuntimeOrigin::signed(disputer),
+				cluster_id,
+				era,
+				2
+			),
+			Error::<Test>::NotExpectedState
+		);
+
+		// the era of the cluster can not be billed again
+		let report = DdcPayouts::active_billing_reports(cluster_id, era).unwrap();
+		assert_eq!(report.state, State::Aborted);
+		assert_eq!(report.total_distributed_reward, 0);
+		assert_noop!(
+			DdcPayouts::begin_billing_report(
+				RuntimeOrigin::signed(dac_account),
+				cluster_id,
+				era,
+				1,
+				2,
+				H256::default(),
+				H256::default(),
+				0,
+				0,
+				CustomerUsage::default(),
+				NodeUsage::default(),
+			),
+			Error::<Test>::NotExpectedState
+		);
+	})
+}
+
+#[test]
+fn roll_back_billing_report_keeps_locked_and_reserved_funds() {
+	ExtBuilder.build_and_execute(|| {
+		System::set_block_number(1);
+		DisputePeriod::set(10);
+
+		let dac_account = 2u128;
+		let disputer = 5u128;
+		let user = 4u128;
+		let node = 10u128;
+		let cluster_id = ClusterId::from([12; 20]);
+		let era = 100;
+		let usage = CustomerUsage {
+			transferred_bytes: 467457,
+			stored_bytes: 45674567456,
+			number_of_puts: 3456345,
+			number_of_gets: 242334563456423,
+		};
+		let node_usage = NodeUsage {
+			transferred_bytes: 1,
+			stored_bytes: 1,
+			number_of_puts: 1,
+			number_of_gets: 1,
+		};
+		let charge = calculate_charge_for_month(cluster_id, usage.clone());
+		let existential_deposit = Balances::minimum_balance();
+		for account_id in [
+			TREASURY_ACCOUNT_ID,
+			RESERVE_ACCOUNT_ID,
+			DdcPayouts::validator_fees_pot_account_id(),
+			node,
+		] {
+			Balances::make_free_balance_be(&account_id, existential_deposit);
+		}
+
+		run_charged_billing_report_until_providers_rewarded(
+			dac_account,
 			cluster_id,
 			era,
-			start_era,
-			end_era,
+			vec![(user, user as BucketId, usage)],
+			vec![(node, node_key(node), node_usage)],
+		);
+		let provider_reward = Balances::free_balance(node) - existential_deposit;
+
+		// the provider delegates a part of the reward and bonds the rest of its balance
+		let delegated = provider_reward / 2;
+		assert_ok!(Balances::reserve(&node, delegated));
+		let bonded = Balances::total_balance(&node);
+		Balances::set_lock(*b"ddcstake", &node, bonded, WithdrawReasons::all());
+
+		assert_ok!(DdcPayouts::dispute_billing_report(
+			RuntimeOrigin::signed(disputer),
+			cluster_id,
+			era
 		));
-		assert_ok!(DdcPayouts::begin_charging_customers(
-			RuntimeOrigin::signed(dac_account),
+		assert_ok!(DdcPayouts::settle_dispute(
+			RuntimeOrigin::root(),
 			cluster_id,
 			era,
-			(payers.len() - 1) as u16,
+			DisputeResolution::Reverted
+		));
+		assert_ok!(DdcPayouts::roll_back_billing_report(
+			RuntimeOrigin::signed(disputer),
+			cluster_id,
+			era,
+			1
 		));
 
-		for batch in payers.iter() {
-			assert_ok!(DdcPayouts::send_charging_customers_batch(
-				RuntimeOrigin::signed(dac_account),
+		System::assert_has_event(
+			Event::RewardClawedBack {
 				cluster_id,
 				era,
-				batch_user_index,
-				batch.to_vec(),
-			));
-
-			for (customer_id, usage) in batch.iter() {
-				let charge = calculate_charge_for_month(cluster_id, usage.clone());
-
-				System::assert_has_event(
-					Event::Charged {
-						cluster_id,
-						era,
-						customer_id: *customer_id,
-						batch_index: batch_user_index,
-						amount: charge,
-					}
-					.into(),
-				);
+				account_id: node,
+				expected: provider_reward,
+				clawed_back: 0,
 			}
-			batch_user_index += 1;
-		}
+			.into(),
+		);
+		let rollback = DdcPayouts::billing_report_rollbacks(cluster_id, era).unwrap();
+		assert_eq!(rollback.unrecovered, provider_reward);
+		assert_eq!(
+			rollback.refund_ratio,
+			Some(Perquintill::from_rational(charge - provider_reward, charge))
+		);
+		assert_eq!(Balances::reserved_balance(node), delegated);
+		assert_eq!(Balances::total_balance(&node), bonded);
+		assert_eq!(Balances::locks(node)[0].amount, bonded);
+	})
+}
 
-		let report_before = DdcPayouts::active_billing_reports(cluster_id, era).unwrap();
-		let balance1 = Balances::free_balance(report_before.vault);
-		let balance2 = Balances::free_balance(DdcPayouts::account_id());
-		assert_eq!(balance1, balance2);
-		assert_eq!(report_before.vault, DdcPayouts::account_id());
-		assert_eq!(balance1 - Balances::minimum_balance(), total_charge);
+#[test]
+fn settle_dispute_adjusts_report_and_refunds_difference() {
+	ExtBuilder.build_and_execute(|| {
+		System::set_block_number(1);
+		DisputePeriod::set(10);
 
-		assert_ok!(DdcPayouts::end_charging_customers(
-			RuntimeOrigin::signed(dac_account),
+		let dac_account = 2u128;
+		let disputer = 5u128;
+		let user = 4u128;
+		let node = 10u128;
+		let cluster_id = ClusterId::from([12; 20]);
+		let era = 100;
+		let usage = CustomerUsage {
+			transferred_bytes: 467457,
+			stored_bytes: 45674567456,
+			number_of_puts: 3456345,
+			number_of_gets: 242334563456423,
+		};
+		let node_usage = NodeUsage {
+			transferred_bytes: 1,
+			stored_bytes: 1,
+			number_of_puts: 1,
+			number_of_gets: 1,
+		};
+		let charge = calculate_charge_for_month(cluster_id, usage.clone());
+		let user_balance_before = Balances::free_balance(user);
+		let treasury_balance_before = Balances::free_balance(TREASURY_ACCOUNT_ID);
+
+		run_charged_billing_report_until_providers_rewarded(
+			dac_account,
 			cluster_id,
 			era,
+			vec![(user, user as BucketId, usage)],
+			vec![(node, node_key(node), node_usage.clone())],
+		);
+		let provider_reward = Balances::free_balance(node);
+		let report = DdcPayouts::active_billing_reports(cluster_id, era).unwrap();
+
+		assert_ok!(DdcPayouts::dispute_billing_report(
+			RuntimeOrigin::signed(disputer),
+			cluster_id,
+			era
 		));
 
-		let report_after = DdcPayouts::active_billing_reports(cluster_id, era).unwrap();
-		let total_left_from_one = (get_fees(&cluster_id).treasury_share +
-			get_fees(&cluster_id).validators_share +
-			get_fees(&cluster_id).cluster_reserve_share)
-			.left_from_one();
+		// the charges can only be lowered, the funds are already paid out
+		let mut total_customer_charge = report.total_customer_charge.clone();
+		total_customer_charge.gets += 1;
+		assert_noop!(
+			DdcPayouts::settle_dispute(
+				RuntimeOrigin::root(),
+				cluster_id,
+				era,
+				DisputeResolution::Adjusted {
+					total_customer_charge,
+					total_node_usage: node_usage.clone(),
+				}
+			),
+			Error::<Test>::BadRequest
+		);
 
-		let total_charge = report_after.total_customer_charge.transfer +
-			report_before.total_customer_charge.storage +
-			report_before.total_customer_charge.puts +
-			report_before.total_customer_charge.gets;
-		let balance_after = Balances::free_balance(DdcPayouts::account_id());
-		assert_eq!(total_charge, balance_after - Balances::minimum_balance());
+		// a quarter of the charges is refunded
+		let total_customer_charge = CustomerCharge {
+			transfer: report.total_customer_charge.transfer / 4 * 3,
+			storage: report.total_customer_charge.storage / 4 * 3,
+			puts: report.total_customer_charge.puts / 4 * 3,
+			gets: report.total_customer_charge.gets / 4 * 3,
+		};
+		let current_charge = report.total_customer_charge.transfer +
+			report.total_customer_charge.storage +
+			report.total_customer_charge.puts +
+			report.total_customer_charge.gets;
+		let corrected_charge = total_customer_charge.transfer +
+			total_customer_charge.storage +
+			total_customer_charge.puts +
+			total_customer_charge.gets;
+		let share = Perquintill::from_rational(current_charge - corrected_charge, current_charge);
+		assert_ok!(DdcPayouts::settle_dispute(
+			RuntimeOrigin::root(),
+			cluster_id,
+			era,
+			DisputeResolution::Adjusted {
+				total_customer_charge: total_customer_charge.clone(),
+				total_node_usage: node_usage.clone(),
+			}
+		));
 
+		let treasury_fee = get_fees(&cluster_id).treasury_share * charge;
 		assert_eq!(
-			report_after.total_customer_charge.transfer,
-			total_left_from_one * report_before.total_customer_charge.transfer
+			Balances::free_balance(TREASURY_ACCOUNT_ID),
+			treasury_balance_before + treasury_fee - share * treasury_fee
 		);
-		assert_eq!(
-			report_after.total_customer_charge.storage,
-			total_left_from_one * report_before.total_customer_charge.storage
+		assert_eq!(Balances::reserved_balance(disputer), 0);
+		// the report can neither be disputed again nor finalized before it is rolled back
+		assert_noop!(
+			DdcPayouts::dispute_billing_report(RuntimeOrigin::signed(disputer), cluster_id, era),
+			Error::<Test>::DisputePeriodElapsed
 		);
-		assert_eq!(
-			report_after.total_customer_charge.puts,
-			total_left_from_one * report_before.total_customer_charge.puts
+		assert_noop!(
+			DdcPayouts::end_billing_report(RuntimeOrigin::signed(dac_account), cluster_id, era),
+			Error::<Test>::RollbackInProgress
 		);
-		assert_eq!(
-			report_after.total_customer_charge.gets,
-			total_left_from_one * report_before.total_customer_charge.gets
+
+		assert_ok!(DdcPayouts::roll_back_billing_report(
+			RuntimeOrigin::signed(disputer),
+			cluster_id,
+			era,
+			MaxBatchSize::get() as u32
+		));
+		System::assert_has_event(
+			Event::RewardClawedBack {
+				cluster_id,
+				era,
+				account_id: node,
+				expected: share * provider_reward,
+				clawed_back: share * provider_reward,
+			}
+			.into(),
+		);
+		System::assert_last_event(
+			Event::BillingReportRolledBack { cluster_id, era, refund_ratio: Perquintill::one() }
+				.into(),
 		);
+		assert_eq!(Balances::free_balance(node), provider_reward - share * provider_reward);
+		assert_eq!(Balances::free_balance(user), user_balance_before - charge + share * charge);
 
-		assert_ok!(DdcPayouts::begin_rewarding_providers(
+		let report = DdcPayouts::active_billing_reports(cluster_id, era).unwrap();
+		assert_eq!(report.state, State::ProvidersRewarded);
+		assert_eq!(report.total_customer_charge, total_customer_charge);
+		assert_eq!(report.total_distributed_reward, provider_reward - share * provider_reward);
+		assert_ok!(DdcPayouts::end_billing_report(
 			RuntimeOrigin::signed(dac_account),
 			cluster_id,
 			era,
-			(payees.len() - 1) as u16,
-			total_nodes_usage.clone(),
 		));
+	})
+}
 
-		for batch in payees.iter() {
-			let before_batch = Balances::free_balance(DdcPayouts::account_id());
-			assert_ok!(DdcPayouts::send_rewarding_providers_batch(
-				RuntimeOrigin::signed(dac_account),
-				cluster_id,
-				era,
-				batch_node_index,
-				batch.to_vec(),
-			));
+#[test]
+fn finalized_billing_reports_are_pruned_after_retention_period() {
+	ExtBuilder.build_and_execute(|| {
+		System::set_block_number(1);
+		BillingReportRetentionPeriod::set(10);
 
-			let mut batch_charge = 0;
-			for (node1, node_usage1) in batch.iter() {
-				let ratio1_transfer = Perquintill::from_rational(
-					node_usage1.transferred_bytes,
-					total_nodes_usage.transferred_bytes,
-				);
-				let transfer_charge = ratio1_transfer * report_after.total_customer_charge.transfer;
+		let dac_account = 2u128;
+		let cluster_id = ClusterId::from([12; 20]);
+		let first_era = 100;
+		let second_era = 101;
 
-				let ratio1_storage = Perquintill::from_rational(
-					node_usage1.stored_bytes,
-					total_nodes_usage.stored_bytes,
-				);
-				let storage_charge = ratio1_storage * report_after.total_customer_charge.storage;
+		run_billing_report_until_providers_rewarded(dac_account, cluster_id, first_era);
+		assert_ok!(DdcPayouts::end_billing_report(
+			RuntimeOrigin::signed(dac_account),
+			cluster_id,
+			first_era,
+		));
 
-				let ratio1_puts = Perquintill::from_rational(
-					node_usage1.number_of_puts,
-					total_nodes_usage.number_of_puts,
-				);
-				let puts_charge = ratio1_puts * report_after.total_customer_charge.puts;
+		System::set_block_number(5);
+		run_billing_report_until_providers_rewarded(dac_account, cluster_id, second_era);
+		assert_ok!(DdcPayouts::end_billing_report(
+			RuntimeOrigin::signed(dac_account),
+			cluster_id,
+			second_era,
+		));
+		assert_eq!(DdcPayouts::pruning_queue_range(), (0, 2));
 
-				let ratio1_gets = Perquintill::from_rational(
-					node_usage1.number_of_gets,
-					total_nodes_usage.number_of_gets,
-				);
-				let gets_charge = ratio1_gets * report_after.total_customer_charge.gets;
+		let first_report = DdcPayouts::active_billing_reports(cluster_id, first_era).unwrap();
+		let first_info = DdcPayouts::billing_report_info(cluster_id, first_era).unwrap();
 
-				let balance_node1 = Balances::free_balance(node1);
-				assert!(
-					(transfer_charge + storage_charge + puts_charge + gets_charge) - balance_node1 <
-						MAX_DUST.into()
-				);
+		// the retention period is not over yet
+		DdcPayouts::on_idle(10, Weight::MAX);
+		assert!(DdcPayouts::active_billing_reports(cluster_id, first_era).is_some());
+		assert_eq!(DdcPayouts::pruning_queue_range(), (0, 2));
 
-				batch_charge += transfer_charge + storage_charge + puts_charge + gets_charge;
-			}
-			let after_batch = Balances::free_balance(DdcPayouts::account_id());
-			assert!(batch_charge + after_batch - before_batch < MAX_DUST.into());
+		// not enough weight to prune a report
+		DdcPayouts::on_idle(11, <Test as frame_system::Config>::DbWeight::get().reads(1));
+		assert!(DdcPayouts::active_billing_reports(cluster_id, first_era).is_some());
 
-			batch_node_index += 1;
-		}
-		assert!(Balances::free_balance(DdcPayouts::account_id()) < MAX_DUST.into());
-	})
-}
+		DdcPayouts::on_idle(11, Weight::MAX);
+		assert!(DdcPayouts::active_billing_reports(cluster_id, first_era).is_none());
+		assert!(DdcPayouts::active_billing_reports(cluster_id, second_era).is_some());
+		assert_eq!(DdcPayouts::pruning_queue_range(), (1, 2));
+		assert!(DdcPayouts::pruning_queue(0).is_none());
 
-fn generate_random_u64<T: Randomness<H256, BlockNumber>>(_: &T, min: u64, max: u64) -> u64 {
-	let (random_seed, _) = T::random_seed();
-	let random_raw = u64::from_be_bytes(random_seed.as_bytes()[0..8].try_into().unwrap());
+		let content_hash = H256(first_report.using_encoded(sp_io::hashing::blake2_256));
+		System::assert_last_event(
+			Event::BillingReportArchived { cluster_id, era: first_era, content_hash }.into(),
+		);
+		assert_eq!(
+			DdcPayouts::archived_billing_reports(cluster_id, first_era),
+			Some(BillingReportSummary {
+				start_era: first_report.start_era,
+				end_era: first_report.end_era,
+				total_customer_charge: first_report.total_customer_charge,
+				total_distributed_reward: first_report.total_distributed_reward,
+				total_node_usage: first_report.total_node_usage,
+				charging_max_batch_index: first_report.charging_max_batch_index,
+				rewarding_max_batch_index: first_report.rewarding_max_batch_index,
+				content_hash,
+			})
+		);
+		assert_eq!(DdcPayouts::billing_report_info(cluster_id, first_era), Some(first_info));
+
+		// an archived report can not be opened again
+		assert_noop!(
+			DdcPayouts::begin_billing_report(
+				RuntimeOrigin::signed(dac_account),
+				cluster_id,
+				first_era,
+				1,
+				2,
+				H256::default(),
+				H256::default(),
+				0,
+				0,
+				CustomerUsage::default(),
+				NodeUsage::default(),
+			),
+			Error::<Test>::NotExpectedState
+		);
 
-	min.saturating_add(random_raw % (max.saturating_sub(min).saturating_add(1)))
+		DdcPayouts::on_idle(15, Weight::MAX);
+		assert!(DdcPayouts::active_billing_reports(cluster_id, second_era).is_none());
+		assert!(DdcPayouts::archived_billing_reports(cluster_id, second_era).is_some());
+		assert_eq!(DdcPayouts::pruning_queue_range(), (2, 2));
+	})
 }
 
 #[test]
-fn send_rewarding_providers_batch_100_nodes_random_usage_works() {
+fn billing_report_records_are_pruned_page_by_page() {
 	ExtBuilder.build_and_execute(|| {
 		System::set_block_number(1);
+		BillingReportRetentionPeriod::set(10);
 
-		let start_date = NaiveDate::from_ymd_opt(2023, 4, 1).unwrap(); // April 1st
-
-		let time = NaiveTime::from_hms_opt(0, 0, 0).unwrap(); // Midnight
-		let start_era: i64 =
-			DateTime::<Utc>::from_naive_utc_and_offset(start_date.and_time(time), Utc).timestamp();
-		let end_era: i64 = start_era + (30.44 * 24.0 * 3600.0) as i64;
-		let mock_randomness = MockRandomness::default();
-		let min: u64 = 1024;
-		let max: u64 = 1024 * 1024;
-		let num_nodes = 100;
-		let num_users = 100;
-		let dac_account = 123u128;
-		let bank = 1u128;
-		let cluster_id = CERE_CLUSTER_ID;
-		let era = 100;
-		let user_batch_size = 10;
-		let node_batch_size = 10;
-		let mut batch_user_index = 0;
-		let mut batch_node_index = 0;
-		let mut payees: Vec<Vec<(u128, NodeUsage)>> = Vec::new();
-		let mut node_batch: Vec<(u128, NodeUsage)> = Vec::new();
-		let mut total_nodes_usage = NodeUsage::default();
-		for i in 10..10 + num_nodes {
-			let node_usage = NodeUsage {
-				transferred_bytes: generate_random_u64(&mock_randomness, min, max),
-				stored_bytes: generate_random_u64(&mock_randomness, min, max),
-				number_of_puts: generate_random_u64(&mock_randomness, min, max),
-				number_of_gets: generate_random_u64(&mock_randomness, min, max),
-			};
-
-			total_nodes_usage.transferred_bytes += node_usage.transferred_bytes;
-			total_nodes_usage.stored_bytes += node_usage.stored_bytes;
-			total_nodes_usage.number_of_puts += node_usage.number_of_puts;
-			total_nodes_usage.number_of_gets += node_usage.number_of_gets;
-
-			node_batch.push((i, node_usage));
-			if node_batch.len() == node_batch_size {
-				payees.push(node_batch.clone());
-				node_batch.clear();
-			}
-		}
-		if !node_batch.is_empty() {
-			payees.push(node_batch.clone());
-		}
-
-		let mut total_charge = 0u128;
-		let mut payers: Vec<Vec<(u128, CustomerUsage)>> = Vec::new();
-		let mut user_batch: Vec<(u128, CustomerUsage)> = Vec::new();
-		for user_id in 1000..1000 + num_users {
-			let user_usage = CustomerUsage {
-				transferred_bytes: generate_random_u64(&mock_randomness, min, max),
-				stored_bytes: generate_random_u64(&mock_randomness, min, max),
-				number_of_puts: generate_random_u64(&mock_randomness, min, max),
-				number_of_gets: generate_random_u64(&mock_randomness, min, max),
-			};
-
-			let expected_charge = calculate_charge_for_month(cluster_id, user_usage.clone());
-			Balances::transfer(
-				RuntimeOrigin::signed(bank),
-				user_id,
-				(expected_charge * 2).max(Balances::minimum_balance()),
-			)
-			.unwrap();
-			total_charge += expected_charge;
+		let dac_account = 2u128;
+		let cluster_id = ClusterId::from([12; 20]);
+		let era = 100;
 
-			user_batch.push((user_id, user_usage));
-			if user_batch.len() == user_batch_size {
-				payers.push(user_batch.clone());
-				user_batch.clear();
-			}
-		}
-		if !user_batch.is_empty() {
-			payers.push(user_batch.clone());
+		run_billing_report_until_providers_rewarded(dac_account, cluster_id, era);
+		for account_id in 100..103u128 {
+			ChargedCustomers::<Test>::insert(
+				(cluster_id, era),
+				account_id,
+				CustomerChargeRecord::default(),
+			);
+			RewardedProviders::<Test>::insert(
+				(cluster_id, era),
+				account_id,
+				ProviderRewardRecord::default(),
+			);
+			ChargedPaymentSources::<Test>::insert(
+				(cluster_id, era),
+				(account_id, PaymentSource::Deposit),
+				PaymentSourceCharge::default(),
+			);
+			ChargedBuckets::<Test>::insert(
+				(cluster_id, era),
+				(account_id, account_id as BucketId),
+				BucketDebtRecord::default(),
+			);
 		}
+		assert_ok!(DdcPayouts::end_billing_report(
+			RuntimeOrigin::signed(dac_account),
+			cluster_id,
+			era,
+		));
+
+		// the records are kept until the report is pruned
+		let records = || {
+			ChargedCustomers::<Test>::iter_prefix((cluster_id, era)).count() +
+				ChargedPaymentSources::<Test>::iter_prefix((cluster_id, era)).count() +
+				ChargedBuckets::<Test>::iter_prefix((cluster_id, era)).count() +
+				RewardedProviders::<Test>::iter_prefix((cluster_id, era)).count()
+		};
+		let records_before = records();
+		assert!(records_before >= 12);
+
+		// enough weight to remove two records per block
+		let db_weight = <Test as frame_system::Config>::DbWeight::get();
+		let weight = db_weight
+			.reads(1)
+			.saturating_add(<() as WeightInfo>::prune_billing_report())
+			.saturating_add(db_weight.writes(1))
+			.saturating_add(db_weight.reads_writes(2, 2));
+		DdcPayouts::on_idle(11, weight);
+		assert_eq!(records(), records_before - 2);
+		assert!(DdcPayouts::active_billing_reports(cluster_id, era).is_some());
+		assert_eq!(DdcPayouts::pruning_queue_range(), (0, 1));
+
+		DdcPayouts::on_idle(11, Weight::MAX);
+		assert_eq!(records(), 0);
+		assert!(DdcPayouts::active_billing_reports(cluster_id, era).is_none());
+		assert!(DdcPayouts::archived_billing_reports(cluster_id, era).is_some());
+		assert_eq!(DdcPayouts::pruning_queue_range(), (1, 1));
+	})
+}
+
+#[test]
+fn abort_billing_report_refunds_customers_and_rolls_back() {
+	ExtBuilder.build_and_execute(|| {
+		System::set_block_number(1);
+
+		let dac_account = 123u128;
+		let user3_debtor = 3u128;
+		let user4_debtor = 4u128;
+		let cluster_id = ClusterId::from([12; 20]);
+		let era = 100;
+		let rewarded_era = 101;
+		let max_batch_index = 1;
+		let user3_debt = 10_000_000;
+		let user4_debt = 5_000_000;
+		let usage3 = CustomerUsage {
+			// should pass but with debt (partial charge)
+			transferred_bytes: 1,
+			stored_bytes: 2,
+			number_of_puts: 3,
+			number_of_gets: 4,
+		};
+		let usage4 = CustomerUsage {
+			// should pass without debt
+			transferred_bytes: 467457,
+			stored_bytes: 45674567456,
+			number_of_puts: 3456345,
+			number_of_gets: 242334563456423,
+		};
+		let payers1 = vec![(user4_debtor, user4_debtor as BucketId, usage4.clone())];
+		let payers2 = vec![(user3_debtor, user3_debtor as BucketId, usage3.clone())];
+		let start_date = NaiveDate::from_ymd_opt(2023, 4, 1).unwrap(); // April 1st
+		let time = NaiveTime::from_hms_opt(0, 0, 0).unwrap(); // Midnight
+		let start_era: i64 =
+			DateTime::<Utc>::from_naive_utc_and_offset(start_date.and_time(time), Utc).timestamp();
+		let end_era: i64 = start_era + (30.44 * 24.0 * 3600.0) as i64;
+		let (customers_merkle_root, customers_proofs) =
+			get_sequential_batches_commitment(&[payers1.clone(), payers2.clone()]);
+		let nodes_merkle_root = H256::default();
 
-		assert_ok!(DdcPayouts::set_authorised_caller(RuntimeOrigin::root(), dac_account));
+		// debts from previous eras
+		DebtorCustomers::<Test>::insert(cluster_id, user3_debtor, user3_debt);
+		DebtorCustomers::<Test>::insert(cluster_id, user4_debtor, user4_debt);
+		let user3_balance_before = Balances::free_balance(user3_debtor);
+		let user4_balance_before = Balances::free_balance(user4_debtor);
+
+		assert_ok!(DdcPayouts::set_validator_threshold(RuntimeOrigin::root(), 1));
 		assert_ok!(DdcPayouts::begin_billing_report(
 			RuntimeOrigin::signed(dac_account),
 			cluster_id,
 			era,
 			start_era,
 			end_era,
+			customers_merkle_root,
+			nodes_merkle_root,
+			max_batch_index,
+			0,
+			customers_usage(payers1.iter().chain(&payers2)),
+			NodeUsage::default(),
 		));
 		assert_ok!(DdcPayouts::begin_charging_customers(
 			RuntimeOrigin::signed(dac_account),
 			cluster_id,
 			era,
-			(payers.len() - 1) as u16,
 		));
-
-		for batch in payers.iter() {
+		for (batch_index, payers) in [payers1, payers2].into_iter().enumerate() {
 			assert_ok!(DdcPayouts::send_charging_customers_batch(
 				RuntimeOrigin::signed(dac_account),
 				cluster_id,
 				era,
-				batch_user_index,
-				batch.to_vec(),
+				batch_index as BatchIndex,
+				payers,
+				customers_proofs[batch_index].clone(),
 			));
-
-			for (customer_id, usage) in batch.iter() {
-				let charge = calculate_charge_for_month(cluster_id, usage.clone());
-
-				System::assert_has_event(
-					Event::Charged {
-						cluster_id,
-						era,
-						customer_id: *customer_id,
-						batch_index: batch_user_index,
-						amount: charge,
-					}
-					.into(),
-				);
-			}
-			batch_user_index += 1;
 		}
 
-		let report_before = DdcPayouts::active_billing_reports(cluster_id, era).unwrap();
-		let balance1 = Balances::free_balance(report_before.vault);
-		let balance2 = Balances::free_balance(DdcPayouts::account_id());
-		assert_eq!(balance1, balance2);
-		assert_eq!(report_before.vault, DdcPayouts::account_id());
-		assert_eq!(balance1 - Balances::minimum_balance(), total_charge);
-
-		assert_ok!(DdcPayouts::end_charging_customers(
-			RuntimeOrigin::signed(dac_account),
-			cluster_id,
-			era,
-		));
-
-		let report_after = DdcPayouts::active_billing_reports(cluster_id, era).unwrap();
-		let total_left_from_one = (get_fees(&cluster_id).treasury_share +
-			get_fees(&cluster_id).validators_share +
-			get_fees(&cluster_id).cluster_reserve_share)
-			.left_from_one();
-
-		let total_charge = report_after.total_customer_charge.transfer +
-			report_before.total_customer_charge.storage +
-			report_before.total_customer_charge.puts +
-			report_before.total_customer_charge.gets;
-		let balance_after = Balances::free_balance(DdcPayouts::account_id());
-		assert_eq!(total_charge, balance_after - Balances::minimum_balance());
-
+		let usage3_charge = calculate_charge_for_month(cluster_id, usage3.clone());
+		let usage4_charge = calculate_charge_for_month(cluster_id, usage4.clone());
 		assert_eq!(
-			report_after.total_customer_charge.transfer,
-			total_left_from_one * report_before.total_customer_charge.transfer
+			DdcPayouts::charged_customers((cluster_id, era), user3_debtor),
+			Some(CustomerChargeRecord {
+				charged: PARTIAL_CHARGE,
+				debt_repaid: PARTIAL_CHARGE,
+				debt_incurred: usage3_charge - PARTIAL_CHARGE,
+				usage: usage3,
+				refunded: 0,
+			})
 		);
 		assert_eq!(
-			report_after.total_customer_charge.storage,
-			total_left_from_one * report_before.total_customer_charge.storage
+			DdcPayouts::charged_customers((cluster_id, era), user4_debtor),
+			Some(CustomerChargeRecord {
+				charged: usage4_charge,
+				debt_repaid: user4_debt,
+				debt_incurred: 0,
+				usage: usage4,
+				refunded: 0,
+			})
 		);
+
 		assert_eq!(
-			report_after.total_customer_charge.puts,
-			total_left_from_one * report_before.total_customer_charge.puts
+			DdcPayouts::charged_payment_sources(
+				(cluster_id, era),
+				(user3_debtor, PaymentSource::Deposit)
+			),
+			Some(PaymentSourceCharge { charged: 2 * PARTIAL_CHARGE, asset_charged: 0 })
 		);
 		assert_eq!(
-			report_after.total_customer_charge.gets,
-			total_left_from_one * report_before.total_customer_charge.gets
+			DdcPayouts::charged_payment_sources(
+				(cluster_id, era),
+				(user4_debtor, PaymentSource::Deposit)
+			),
+			Some(PaymentSourceCharge { charged: usage4_charge + user4_debt, asset_charged: 0 })
 		);
 
-		assert_ok!(DdcPayouts::begin_rewarding_providers(
+		assert_noop!(
+			DdcPayouts::abort_billing_report(RuntimeOrigin::signed(dac_account), cluster_id, era),
+			BadOrigin
+		);
+		assert_noop!(
+			DdcPayouts::abort_billing_report(RuntimeOrigin::root(), cluster_id, era + 10),
+			Error::<Test>::BillingReportDoesNotExist
+		);
+
+		assert_ok!(DdcPayouts::abort_billing_report(RuntimeOrigin::root(), cluster_id, era));
+		System::assert_last_event(
+			Event::BillingReportAborted { cluster_id, era, state: State::ChargingCustomers }.into(),
+		);
+		let report = DdcPayouts::active_billing_reports(cluster_id, era).unwrap();
+		assert_eq!(report.state, State::Aborted);
+		assert!(DdcPayouts::billing_report_rollbacks(cluster_id, era).is_some());
+		assert_noop!(
+			DdcPayouts::end_charging_customers(RuntimeOrigin::signed(dac_account), cluster_id, era),
+			Error::<Test>::NotExpectedState
+		);
+		assert_noop!(
+			DdcPayouts::abort_billing_report(RuntimeOrigin::root(), cluster_id, era),
+			Error::<Test>::NotExpectedState
+		);
+
+		// the first call refunds a single payment source
+		assert_ok!(DdcPayouts::roll_back_billing_report(
 			RuntimeOrigin::signed(dac_account),
 			cluster_id,
 			era,
-			(payees.len() - 1) as u16,
-			total_nodes_usage.clone(),
+			1
 		));
+		assert_eq!(
+			DdcPayouts::billing_report_rollbacks(cluster_id, era).unwrap().refund_ratio,
+			Some(Perquintill::one())
+		);
+		assert_eq!(ChargedPaymentSources::<Test>::iter_prefix((cluster_id, era)).count(), 1);
+		assert_eq!(ChargedCustomers::<Test>::iter_prefix((cluster_id, era)).count(), 2);
 
-		for batch in payees.iter() {
-			let before_batch = Balances::free_balance(DdcPayouts::account_id());
-			assert_ok!(DdcPayouts::send_rewarding_providers_batch(
-				RuntimeOrigin::signed(dac_account),
+		assert_ok!(DdcPayouts::roll_back_billing_report(
+			RuntimeOrigin::signed(dac_account),
+			cluster_id,
+			era,
+			3
+		));
+		System::assert_last_event(
+			Event::BillingReportRolledBack { cluster_id, era, refund_ratio: Perquintill::one() }
+				.into(),
+		);
+		System::assert_has_event(
+			Event::ChargeRefunded {
 				cluster_id,
 				era,
-				batch_node_index,
-				batch.to_vec(),
-			));
-
-			let mut batch_charge = 0;
-			for (node1, node_usage1) in batch.iter() {
-				let ratio1_transfer = Perquintill::from_rational(
-					node_usage1.transferred_bytes,
-					total_nodes_usage.transferred_bytes,
-				);
-				let transfer_charge = ratio1_transfer * report_after.total_customer_charge.transfer;
-
-				let ratio1_storage = Perquintill::from_rational(
-					node_usage1.stored_bytes,
-					total_nodes_usage.stored_bytes,
-				);
-				let storage_charge = ratio1_storage * report_after.total_customer_charge.storage;
-
-				let ratio1_puts = Perquintill::from_rational(
-					node_usage1.number_of_puts,
-					total_nodes_usage.number_of_puts,
-				);
-				let puts_charge = ratio1_puts * report_after.total_customer_charge.puts;
-
-				let ratio1_gets = Perquintill::from_rational(
-					node_usage1.number_of_gets,
-					total_nodes_usage.number_of_gets,
-				);
-				let gets_charge = ratio1_gets * report_after.total_customer_charge.gets;
+				customer_id: user3_debtor,
+				refunded: 2 * PARTIAL_CHARGE,
+				debt: user3_debt,
+			}
+			.into(),
+		);
+		System::assert_has_event(
+			Event::ChargeRefunded {
+				cluster_id,
+				era,
+				customer_id: user4_debtor,
+				refunded: usage4_charge + user4_debt,
+				debt: user4_debt,
+			}
+			.into(),
+		);
 
-				let balance_node1 = Balances::free_balance(node1);
-				assert!(
-					(transfer_charge + storage_charge + puts_charge + gets_charge) - balance_node1 <
-						MAX_DUST.into()
-				);
+		assert!(DdcPayouts::active_billing_reports(cluster_id, era).is_none());
+		assert!(DdcPayouts::billing_report_rollbacks(cluster_id, era).is_none());
+		assert_eq!(ChargedCustomers::<Test>::iter_prefix((cluster_id, era)).count(), 0);
+		assert_eq!(Balances::free_balance(user3_debtor), user3_balance_before);
+		assert_eq!(Balances::free_balance(user4_debtor), user4_balance_before);
+		assert_eq!(DdcPayouts::debtor_customers(cluster_id, user3_debtor), Some(user3_debt));
+		assert_eq!(DdcPayouts::debtor_customers(cluster_id, user4_debtor), Some(user4_debt));
 
-				batch_charge += transfer_charge + storage_charge + puts_charge + gets_charge;
-			}
-			let after_batch = Balances::free_balance(DdcPayouts::account_id());
-			assert!(batch_charge + after_batch - before_batch < MAX_DUST.into());
+		// the DAC can begin the report again
+		assert_ok!(DdcPayouts::begin_billing_report(
+			RuntimeOrigin::signed(dac_account),
+			cluster_id,
+			era,
+			start_era,
+			end_era,
+			customers_merkle_root,
+			nodes_merkle_root,
+			max_batch_index,
+			0,
+			CustomerUsage::default(),
+			NodeUsage::default(),
+		));
 
-			batch_node_index += 1;
-		}
-		assert!(Balances::free_balance(DdcPayouts::account_id()) < MAX_DUST.into());
+		// rewarded providers are settled by disputes instead
+		run_billing_report_until_providers_rewarded(dac_account, cluster_id, rewarded_era);
+		assert_noop!(
+			DdcPayouts::abort_billing_report(RuntimeOrigin::root(), cluster_id, rewarded_era),
+			Error::<Test>::NotExpectedState
+		);
 	})
 }
 
 #[test]
-fn end_rewarding_providers_fails_uninitialised() {
+fn abort_billing_report_keeps_report_refunded_in_part() {
 	ExtBuilder.build_and_execute(|| {
-		let root_account = 1u128;
-		let dac_account = 2u128;
-		let user1 = 3u128;
-		let user2 = 4u128;
-		let node1 = 33u128;
+		System::set_block_number(1);
+
+		let dac_account = 123u128;
+		let user1 = 1u128;
 		let cluster_id = ClusterId::from([12; 20]);
 		let era = 100;
-		let max_batch_index = 1;
+		let max_batch_index = 0;
 		let batch_index = 0;
-		let payers1 = vec![(user1, CustomerUsage::default())];
-		let payers2 = vec![(user2, CustomerUsage::default())];
-		let payees = vec![(node1, NodeUsage::default())];
-		let total_node_usage = NodeUsage::default();
+		let usage1 = CustomerUsage {
+			transferred_bytes: 23452345,
+			stored_bytes: 3345234523,
+			number_of_puts: 4456456345234523,
+			number_of_gets: 523423,
+		};
+		let payers = vec![(user1, user1 as BucketId, usage1.clone())];
 		let start_date = NaiveDate::from_ymd_opt(2023, 4, 1).unwrap(); // April 1st
-
 		let time = NaiveTime::from_hms_opt(0, 0, 0).unwrap(); // Midnight
 		let start_era: i64 =
 			DateTime::<Utc>::from_naive_utc_and_offset(start_date.and_time(time), Utc).timestamp();
 		let end_era: i64 = start_era + (30.44 * 24.0 * 3600.0) as i64;
+		let (customers_merkle_root, customers_proofs) =
+			get_batches_commitment(&[(batch_index, &payers)]);
+		let nodes_merkle_root = H256::default();
+		let user1_balance_before = Balances::free_balance(user1);
+		// the accounts the fees are clawed back from are kept alive
+		for account_id in
+			[TREASURY_ACCOUNT_ID, RESERVE_ACCOUNT_ID, DdcPayouts::validator_fees_pot_account_id()]
+		{
+			Balances::make_free_balance_be(&account_id, Balances::minimum_balance());
+		}
 
-		assert_noop!(
-			DdcPayouts::end_rewarding_providers(
-				RuntimeOrigin::signed(root_account),
-				cluster_id,
-				era,
-			),
-			Error::<Test>::Unauthorised
-		);
-
-		assert_noop!(
-			DdcPayouts::end_rewarding_providers(RuntimeOrigin::root(), cluster_id, era,),
-			BadOrigin
-		);
-
-		assert_ok!(DdcPayouts::set_authorised_caller(RuntimeOrigin::root(), dac_account));
-
-		assert_noop!(
-			DdcPayouts::end_rewarding_providers(
-				RuntimeOrigin::signed(dac_account),
-				cluster_id,
-				era,
-			),
-			Error::<Test>::BillingReportDoesNotExist
-		);
-
+		assert_ok!(DdcPayouts::set_validator_threshold(RuntimeOrigin::root(), 1));
 		assert_ok!(DdcPayouts::begin_billing_report(
 			RuntimeOrigin::signed(dac_account),
 			cluster_id,
 			era,
 			start_era,
 			end_era,
+			customers_merkle_root,
+			nodes_merkle_root,
+			max_batch_index,
+			0,
+			customers_usage(&payers),
+			NodeUsage::default(),
 		));
-
-		assert_noop!(
-			DdcPayouts::end_rewarding_providers(
-				RuntimeOrigin::signed(dac_account),
-				cluster_id,
-				era,
-			),
-			Error::<Test>::NotExpectedState
-		);
-
 		assert_ok!(DdcPayouts::begin_charging_customers(
 			RuntimeOrigin::signed(dac_account),
 			cluster_id,
 			era,
-			max_batch_index,
 		));
-
-		assert_noop!(
-			DdcPayouts::end_rewarding_providers(
-				RuntimeOrigin::signed(dac_account),
-				cluster_id,
-				era,
-			),
-			Error::<Test>::NotExpectedState
-		);
-
 		assert_ok!(DdcPayouts::send_charging_customers_batch(
 			RuntimeOrigin::signed(dac_account),
 			cluster_id,
 			era,
 			batch_index,
-			payers1,
+			payers,
+			customers_proofs[0].clone(),
 		));
-
-		assert_noop!(
-			DdcPayouts::end_rewarding_providers(
-				RuntimeOrigin::signed(dac_account),
-				cluster_id,
-				era,
-			),
-			Error::<Test>::NotExpectedState
-		);
-
-		assert_ok!(DdcPayouts::send_charging_customers_batch(
+		assert_ok!(DdcPayouts::end_charging_customers(
 			RuntimeOrigin::signed(dac_account),
 			cluster_id,
 			era,
-			batch_index + 1,
-			payers2,
 		));
+		let charge = calculate_charge_for_month(cluster_id, usage1);
 
-		assert_noop!(
-			DdcPayouts::end_rewarding_providers(
-				RuntimeOrigin::signed(dac_account),
+		// the validators fees claimed by the stakers can not be clawed back
+		assert_ok!(DdcPayouts::snapshot_validator_scores(
+			RuntimeOrigin::signed(user1),
+			cluster_id,
+			era,
+			MaxBatchSize::get() as u32,
+		));
+		for staker_id in [VALIDATOR1_ACCOUNT_ID, VALIDATOR2_ACCOUNT_ID, VALIDATOR3_ACCOUNT_ID] {
+			assert_ok!(DdcPayouts::claim_validator_fees(
+				RuntimeOrigin::signed(user1),
+				staker_id,
 				cluster_id,
 				era,
-			),
-			Error::<Test>::NotExpectedState
-		);
+			));
+		}
+		let pot = DdcPayouts::validator_fees_pots(cluster_id, era).unwrap();
+		let claimed_fees = pot.claimed_fees;
 
-		assert_ok!(DdcPayouts::end_charging_customers(
+		assert_ok!(DdcPayouts::abort_billing_report(RuntimeOrigin::root(), cluster_id, era));
+		System::assert_last_event(
+			Event::BillingReportAborted { cluster_id, era, state: State::CustomersChargedWithFees }
+				.into(),
+		);
+		assert_ok!(DdcPayouts::roll_back_billing_report(
 			RuntimeOrigin::signed(dac_account),
 			cluster_id,
 			era,
+			MaxBatchSize::get() as u32,
 		));
 
+		let refund_ratio = Perquintill::from_rational(charge - claimed_fees, charge);
+		assert!(refund_ratio < Perquintill::one());
+		System::assert_last_event(
+			Event::BillingReportRolledBack { cluster_id, era, refund_ratio }.into(),
+		);
+		assert_eq!(
+			Balances::free_balance(user1),
+			user1_balance_before - charge + refund_ratio * charge
+		);
+
+		// the report stays aborted, so that the era is not charged again
+		let report = DdcPayouts::active_billing_reports(cluster_id, era).unwrap();
+		assert_eq!(report.state, State::Aborted);
+		assert!(DdcPayouts::billing_report_rollbacks(cluster_id, era).is_none());
 		assert_noop!(
-			DdcPayouts::end_rewarding_providers(
+			DdcPayouts::begin_billing_report(
 				RuntimeOrigin::signed(dac_account),
 				cluster_id,
 				era,
+				start_era,
+				end_era,
+				customers_merkle_root,
+				nodes_merkle_root,
+				max_batch_index,
+				0,
+				CustomerUsage::default(),
+				NodeUsage::default(),
 			),
 			Error::<Test>::NotExpectedState
 		);
+		assert_noop!(
+			DdcPayouts::abort_billing_report(RuntimeOrigin::root(), cluster_id, era),
+			Error::<Test>::NotExpectedState
+		);
+	})
+}
 
-		assert_ok!(DdcPayouts::begin_rewarding_providers(
+#[test]
+fn has_open_billing_report_works() {
+	ExtBuilder.build_and_execute(|| {
+		System::set_block_number(1);
+		DisputePeriod::set(10);
+
+		let dac_account = 123u128;
+		let disputer = 4u128;
+		let cluster_id = ClusterId::from([12; 20]);
+		let era = 100;
+
+		assert!(!<DdcPayouts as PayoutVisitorType<Test>>::has_open_billing_report(&cluster_id));
+		assert_eq!(
+			<DdcPayouts as PayoutVisitorType<Test>>::get_last_finalized_era(&cluster_id),
+			None
+		);
+
+		run_billing_report_until_providers_rewarded(dac_account, cluster_id, era);
+		assert!(<DdcPayouts as PayoutVisitorType<Test>>::has_open_billing_report(&cluster_id));
+
+		// an aborted report is open until it is rolled back
+		assert_ok!(DdcPayouts::dispute_billing_report(
+			RuntimeOrigin::signed(disputer),
+			cluster_id,
+			era,
+		));
+		assert_ok!(DdcPayouts::settle_dispute(
+			RuntimeOrigin::root(),
+			cluster_id,
+			era,
+			DisputeResolution::Reverted,
+		));
+		assert!(<DdcPayouts as PayoutVisitorType<Test>>::has_open_billing_report(&cluster_id));
+		assert_ok!(DdcPayouts::roll_back_billing_report(
 			RuntimeOrigin::signed(dac_account),
 			cluster_id,
 			era,
-			max_batch_index,
-			total_node_usage,
+			MaxBatchSize::get() as u32,
 		));
-
-		assert_noop!(
-			DdcPayouts::end_rewarding_providers(
-				RuntimeOrigin::signed(dac_account),
-				cluster_id,
-				era,
-			),
-			Error::<Test>::BatchesMissed
+		assert_eq!(
+			DdcPayouts::active_billing_reports(cluster_id, era).unwrap().state,
+			State::Aborted
 		);
+		assert!(!<DdcPayouts as PayoutVisitorType<Test>>::has_open_billing_report(&cluster_id));
 
-		assert_ok!(DdcPayouts::send_rewarding_providers_batch(
+		// a finalized report is closed
+		run_billing_report_until_providers_rewarded(dac_account, cluster_id, era + 1);
+		assert!(<DdcPayouts as PayoutVisitorType<Test>>::has_open_billing_report(&cluster_id));
+		System::set_block_number(20);
+		assert_ok!(DdcPayouts::end_billing_report(
 			RuntimeOrigin::signed(dac_account),
 			cluster_id,
-			era,
-			batch_index,
-			payees,
+			era + 1,
 		));
-
-		assert_noop!(
-			DdcPayouts::end_rewarding_providers(
-				RuntimeOrigin::signed(dac_account),
-				cluster_id,
-				era,
-			),
-			Error::<Test>::BatchesMissed
+		assert!(!<DdcPayouts as PayoutVisitorType<Test>>::has_open_billing_report(&cluster_id));
+		assert_eq!(
+			<DdcPayouts as PayoutVisitorType<Test>>::get_last_finalized_era(&cluster_id),
+			Some(era + 1)
 		);
+		assert!(!<DdcPayouts as PayoutVisitorType<Test>>::has_open_billing_report(
+			&ClusterId::from([13; 20])
+		));
 	})
 }
 
 #[test]
-fn end_rewarding_providers_works() {
+fn send_charging_customers_batch_charges_per_bucket() {
 	ExtBuilder.build_and_execute(|| {
 		System::set_block_number(1);
 
-		let start_date = NaiveDate::from_ymd_opt(2023, 4, 1).unwrap(); // April 1st
-
-		let time = NaiveTime::from_hms_opt(0, 0, 0).unwrap(); // Midnight
-		let start_era: i64 =
-			DateTime::<Utc>::from_naive_utc_and_offset(start_date.and_time(time), Utc).timestamp();
-		let end_era: i64 = start_era + (30.44 * 24.0 * 3600.0) as i64;
-		let dac_account = 2u128;
-		let user1 = 1u128;
-		let node1 = 33u128;
+		let dac_account = 123u128;
+		let user4 = 4u128;
+		let bucket1: BucketId = 4;
+		let bucket2: BucketId = 4 + BUCKETS_OFFSET;
 		let cluster_id = ClusterId::from([12; 20]);
 		let era = 100;
-		let max_batch_index = 0;
-		let batch_index = 0;
+		let max_batch_index = 3;
 		let usage1 = CustomerUsage {
-			transferred_bytes: 23452345,
-			stored_bytes: 3345234523,
-			number_of_puts: 4456456345234523,
-			number_of_gets: 523423,
+			transferred_bytes: 467457,
+			stored_bytes: 45674567456,
+			number_of_puts: 3456345,
+			number_of_gets: 242334563456423,
 		};
-
-		let node_usage1 = NodeUsage {
-			// CDN + Storage
-			transferred_bytes: usage1.transferred_bytes * 2 / 3,
-			stored_bytes: usage1.stored_bytes * 2 / 3,
-			number_of_puts: usage1.number_of_puts * 2 / 3,
-			number_of_gets: usage1.number_of_gets * 2 / 3,
+		let usage2 = CustomerUsage {
+			transferred_bytes: 1024,
+			stored_bytes: 34567456,
+			number_of_puts: 3456,
+			number_of_gets: 2423345,
 		};
-		let total_node_usage = node_usage1.clone();
-		let payers = vec![(user1, usage1)];
-		let payees = vec![(node1, node_usage1)];
-
-		assert_ok!(DdcPayouts::set_authorised_caller(RuntimeOrigin::root(), dac_account));
+		let batches = [
+			vec![(user4, bucket1, usage1.clone()), (user4, bucket2, usage2.clone())],
+			// bucket of another customer
+			vec![(user4, 5, usage1.clone())],
+			// bucket in another cluster
+			vec![(user4, FOREIGN_BUCKETS_START + 4, usage1.clone())],
+			vec![(user4, 0, usage1.clone())],
+		];
+		let start_date = NaiveDate::from_ymd_opt(2023, 4, 1).unwrap(); // April 1st
+		let time = NaiveTime::from_hms_opt(0, 0, 0).unwrap(); // Midnight
+		let start_era: i64 =
+			DateTime::<Utc>::from_naive_utc_and_offset(start_date.and_time(time), Utc).timestamp();
+		let end_era: i64 = start_era + (30.44 * 24.0 * 3600.0) as i64;
+		let (customers_merkle_root, customers_proofs) = get_sequential_batches_commitment(&batches);
 
+		assert_ok!(DdcPayouts::set_validator_threshold(RuntimeOrigin::root(), 1));
 		assert_ok!(DdcPayouts::begin_billing_report(
 			RuntimeOrigin::signed(dac_account),
 			cluster_id,
 			era,
 			start_era,
 			end_era,
+			customers_merkle_root,
+			H256::default(),
+			max_batch_index,
+			0,
+			CustomerUsage::default(),
+			NodeUsage::default(),
 		));
-
-		let mut report = DdcPayouts::active_billing_reports(cluster_id, era).unwrap();
-		assert_eq!(report.state, State::Initialized);
-
 		assert_ok!(DdcPayouts::begin_charging_customers(
 			RuntimeOrigin::signed(dac_account),
 			cluster_id,
 			era,
-			max_batch_index,
 		));
 
+		let balance_before = Balances::free_balance(user4);
 		assert_ok!(DdcPayouts::send_charging_customers_batch(
 			RuntimeOrigin::signed(dac_account),
 			cluster_id,
 			era,
-			batch_index,
-			payers,
+			0,
+			batches[0].clone(),
+			customers_proofs[0].clone(),
 		));
 
-		assert_ok!(DdcPayouts::end_charging_customers(
-			RuntimeOrigin::signed(dac_account),
-			cluster_id,
-			era,
-		));
+		// the second bucket is charged on top of the usage of the first one
+		let charge1 = calculate_charge_for_month(cluster_id, usage1.clone());
+		let charged_usage = combined_usage(&usage1, &usage2);
+		let charge2 = calculate_charge_for_month(cluster_id, charged_usage.clone()) - charge1;
+		for (bucket_id, amount) in [(bucket1, charge1), (bucket2, charge2)] {
+			System::assert_has_event(
+				Event::Charged {
+					cluster_id,
+					era,
+					batch_index: 0,
+					customer_id: user4,
+					bucket_id,
+					amount,
+				}
+				.into(),
+			);
+		}
+		assert_eq!(Balances::free_balance(user4), balance_before - charge1 - charge2);
+		let mut total_charged = charge1 + charge2;
 
-		assert_ok!(DdcPayouts::begin_rewarding_providers(
+		// buckets transferred since the era are charged to the recorded owner
+		let (_, bucket_id, usage) = batches[1][0].clone();
+		let balance_before = Balances::free_balance(user4);
+		assert_ok!(DdcPayouts::send_charging_customers_batch(
 			RuntimeOrigin::signed(dac_account),
 			cluster_id,
 			era,
-			max_batch_index,
-			total_node_usage,
+			1,
+			batches[1].clone(),
+			customers_proofs[1].clone(),
 		));
 
-		assert_ok!(DdcPayouts::send_rewarding_providers_batch(
-			RuntimeOrigin::signed(dac_account),
-			cluster_id,
-			era,
-			batch_index,
-			payees,
-		));
+		let charge = calculate_charge_for_month(cluster_id, combined_usage(&charged_usage, &usage)) -
+			calculate_charge_for_month(cluster_id, charged_usage.clone());
+		System::assert_has_event(
+			Event::BucketNotVerified {
+				cluster_id,
+				era,
+				batch_index: 1,
+				customer_id: user4,
+				bucket_id,
+				error: Error::<Test>::NotBucketOwner.into(),
+			}
+			.into(),
+		);
+		System::assert_last_event(
+			Event::Charged {
+				cluster_id,
+				era,
+				batch_index: 1,
+				customer_id: user4,
+				bucket_id,
+				amount: charge,
+			}
+			.into(),
+		);
+		assert_eq!(Balances::free_balance(user4), balance_before - charge);
+		total_charged += charge;
 
-		assert_ok!(DdcPayouts::end_rewarding_providers(
-			RuntimeOrigin::signed(dac_account),
-			cluster_id,
-			era,
-		));
+		// the usage of buckets unknown to the cluster is not charged
+		for (batch_index, error) in
+			[(2, Error::<Test>::IncorrectBucketClusterId), (3, Error::<Test>::NoBucketWithId)]
+		{
+			let (_, bucket_id, _) = batches[batch_index as usize][0].clone();
+			let balance_before = Balances::free_balance(user4);
+			assert_ok!(DdcPayouts::send_charging_customers_batch(
+				RuntimeOrigin::signed(dac_account),
+				cluster_id,
+				era,
+				batch_index,
+				batches[batch_index as usize].clone(),
+				customers_proofs[batch_index as usize].clone(),
+			));
 
-		System::assert_last_event(Event::RewardingFinished { cluster_id, era }.into());
+			System::assert_last_event(
+				Event::BucketSkipped {
+					cluster_id,
+					era,
+					batch_index,
+					customer_id: user4,
+					bucket_id,
+					error: error.into(),
+				}
+				.into(),
+			);
+			assert_eq!(Balances::free_balance(user4), balance_before);
+		}
 
-		report = DdcPayouts::active_billing_reports(cluster_id, era).unwrap();
-		assert_eq!(report.state, State::ProvidersRewarded);
+		let report = DdcPayouts::active_billing_reports(cluster_id, era).unwrap();
+		assert_eq!(report.state, State::ChargingCustomers);
+		let total_charge = report.total_customer_charge;
+		assert_eq!(
+			total_charge.transfer + total_charge.storage + total_charge.puts + total_charge.gets,
+			total_charged
+		);
 	})
 }
 
 #[test]
-fn end_billing_report_fails_uninitialised() {
+fn send_charging_customers_batch_keeps_debt_of_budgeted_bucket() {
 	ExtBuilder.build_and_execute(|| {
-		let start_date = NaiveDate::from_ymd_opt(2023, 4, 1).unwrap(); // April 1st
+		System::set_block_number(1);
 
+		let dac_account = 123u128;
+		let user4 = 4u128;
+		let budgeted_bucket: BucketId = 4;
+		let shared_bucket: BucketId = 4 + BUCKETS_OFFSET;
+		let cluster_id = ClusterId::from([12; 20]);
+		let era = 100;
+		let max_batch_index = 2;
+		let allocation = 1_000;
+		let usage = CustomerUsage {
+			transferred_bytes: 467457,
+			stored_bytes: 45674567456,
+			number_of_puts: 3456345,
+			number_of_gets: 242334563456423,
+		};
+		let batches = [
+			vec![(user4, budgeted_bucket, usage.clone())],
+			vec![(user4, shared_bucket, usage.clone())],
+			vec![(user4, budgeted_bucket, usage.clone())],
+		];
+		let start_date = NaiveDate::from_ymd_opt(2023, 4, 1).unwrap(); // April 1st
 		let time = NaiveTime::from_hms_opt(0, 0, 0).unwrap(); // Midnight
 		let start_era: i64 =
 			DateTime::<Utc>::from_naive_utc_and_offset(start_date.and_time(time), Utc).timestamp();
 		let end_era: i64 = start_era + (30.44 * 24.0 * 3600.0) as i64;
-		let root_account = 1u128;
-		let dac_account = 2u128;
-		let user1 = 3u128;
-		let user2 = 4u128;
-		let node1 = 33u128;
-		let cluster_id = ClusterId::from([12; 20]);
-		let era = 100;
-		let max_batch_index = 1;
-		let batch_index = 0;
-		let payers1 = vec![(user1, CustomerUsage::default())];
-		let payers2 = vec![(user2, CustomerUsage::default())];
-		let payees = vec![(node1, NodeUsage::default())];
-		let total_node_usage = NodeUsage::default();
-
-		assert_noop!(
-			DdcPayouts::end_billing_report(RuntimeOrigin::signed(root_account), cluster_id, era,),
-			Error::<Test>::Unauthorised
-		);
-
-		assert_noop!(
-			DdcPayouts::end_billing_report(RuntimeOrigin::root(), cluster_id, era,),
-			BadOrigin
-		);
-
-		assert_ok!(DdcPayouts::set_authorised_caller(RuntimeOrigin::root(), dac_account));
-
-		assert_noop!(
-			DdcPayouts::end_billing_report(RuntimeOrigin::signed(dac_account), cluster_id, era,),
-			Error::<Test>::BillingReportDoesNotExist
-		);
+		let (customers_merkle_root, customers_proofs) = get_sequential_batches_commitment(&batches);
+		set_bucket_allocation(budgeted_bucket, allocation);
 
+		assert_ok!(DdcPayouts::set_validator_threshold(RuntimeOrigin::root(), 1));
 		assert_ok!(DdcPayouts::begin_billing_report(
 			RuntimeOrigin::signed(dac_account),
 			cluster_id,
 			era,
 			start_era,
 			end_era,
+			customers_merkle_root,
+			H256::default(),
+			max_batch_index,
+			0,
+			CustomerUsage::default(),
+			NodeUsage::default(),
 		));
-
-		assert_noop!(
-			DdcPayouts::end_billing_report(RuntimeOrigin::signed(dac_account), cluster_id, era,),
-			Error::<Test>::NotExpectedState
-		);
-
 		assert_ok!(DdcPayouts::begin_charging_customers(
 			RuntimeOrigin::signed(dac_account),
 			cluster_id,
 			era,
-			max_batch_index,
 		));
 
-		assert_noop!(
-			DdcPayouts::end_billing_report(RuntimeOrigin::signed(dac_account), cluster_id, era,),
-			Error::<Test>::NotExpectedState
-		);
-
+		// the shortfall of the budgeted bucket is its own debt
 		assert_ok!(DdcPayouts::send_charging_customers_batch(
 			RuntimeOrigin::signed(dac_account),
 			cluster_id,
 			era,
-			batch_index,
-			payers1,
+			0,
+			batches[0].clone(),
+			customers_proofs[0].clone(),
 		));
-
-		assert_noop!(
-			DdcPayouts::end_billing_report(RuntimeOrigin::signed(dac_account), cluster_id, era,),
-			Error::<Test>::NotExpectedState
+		let charge1 = calculate_charge_for_month(cluster_id, usage.clone());
+		let bucket_debt = charge1 - allocation;
+		System::assert_has_event(
+			Event::Indebted {
+				cluster_id,
+				era,
+				batch_index: 0,
+				customer_id: user4,
+				bucket_id: budgeted_bucket,
+				amount: bucket_debt,
+			}
+			.into(),
 		);
+		assert_eq!(DdcPayouts::debtor_buckets(cluster_id, budgeted_bucket), Some(bucket_debt));
+		assert_eq!(DdcPayouts::debtor_bucket_owners(cluster_id, user4), Some(bucket_debt));
+		assert_eq!(DdcPayouts::debtor_customers(cluster_id, user4), None);
+		assert_eq!(bucket_allocation(budgeted_bucket), Some(0));
 
+		// the shared deposit is not charged for the debt of the budgeted bucket
+		let balance_before = Balances::free_balance(user4);
 		assert_ok!(DdcPayouts::send_charging_customers_batch(
 			RuntimeOrigin::signed(dac_account),
 			cluster_id,
 			era,
-			batch_index + 1,
-			payers2,
+			1,
+			batches[1].clone(),
+			customers_proofs[1].clone(),
 		));
-
-		assert_noop!(
-			DdcPayouts::end_billing_report(RuntimeOrigin::signed(dac_account), cluster_id, era,),
-			Error::<Test>::NotExpectedState
+		let usage2 = combined_usage(&usage, &usage);
+		let charge2 = calculate_charge_for_month(cluster_id, usage2.clone()) - charge1;
+		assert_eq!(Balances::free_balance(user4), balance_before - charge2);
+		assert_eq!(DdcPayouts::debtor_buckets(cluster_id, budgeted_bucket), Some(bucket_debt));
+
+		// the debt is collected from the allocation once it is topped up
+		let top_up = bucket_debt + 10 * charge1;
+		set_bucket_allocation(budgeted_bucket, top_up);
+		assert_ok!(DdcPayouts::send_charging_customers_batch(
+			RuntimeOrigin::signed(dac_account),
+			cluster_id,
+			era,
+			2,
+			batches[2].clone(),
+			customers_proofs[2].clone(),
+		));
+		let charge3 = calculate_charge_for_month(cluster_id, combined_usage(&usage2, &usage)) -
+			calculate_charge_for_month(cluster_id, usage2);
+		System::assert_has_event(
+			Event::DebtRepaid {
+				cluster_id,
+				era,
+				batch_index: 2,
+				customer_id: user4,
+				repaid: bucket_debt,
+			}
+			.into(),
+		);
+		assert_eq!(DdcPayouts::debtor_buckets(cluster_id, budgeted_bucket), None);
+		assert_eq!(DdcPayouts::debtor_bucket_owners(cluster_id, user4), None);
+		assert_eq!(bucket_allocation(budgeted_bucket), Some(top_up - bucket_debt - charge3));
+		assert_eq!(
+			DdcPayouts::charged_buckets((cluster_id, era), (user4, budgeted_bucket)),
+			Some(BucketDebtRecord { debt_repaid: bucket_debt, debt_incurred: bucket_debt })
+		);
+		assert_eq!(
+			DdcPayouts::charged_payment_sources(
+				(cluster_id, era),
+				(user4, PaymentSource::BucketBudget(budgeted_bucket))
+			),
+			Some(PaymentSourceCharge {
+				charged: allocation + bucket_debt + charge3,
+				asset_charged: 0
+			})
 		);
 
-		assert_ok!(DdcPayouts::end_charging_customers(
+		// the allocation and the debt of the bucket are restored with the report rolled back
+		assert_ok!(DdcPayouts::abort_billing_report(RuntimeOrigin::root(), cluster_id, era));
+		assert_ok!(DdcPayouts::roll_back_billing_report(
 			RuntimeOrigin::signed(dac_account),
 			cluster_id,
 			era,
+			MaxBatchSize::get() as u32,
 		));
-
-		assert_noop!(
-			DdcPayouts::end_billing_report(RuntimeOrigin::signed(dac_account), cluster_id, era,),
-			Error::<Test>::NotExpectedState
+		System::assert_has_event(
+			Event::BucketDebtRolledBack { cluster_id, era, bucket_id: budgeted_bucket, debt: 0 }
+				.into(),
 		);
+		assert_eq!(bucket_allocation(budgeted_bucket), Some(top_up + allocation));
+		assert_eq!(DdcPayouts::debtor_buckets(cluster_id, budgeted_bucket), None);
+		assert_eq!(DdcPayouts::debtor_bucket_owners(cluster_id, user4), None);
+		assert_eq!(ChargedBuckets::<Test>::iter_prefix((cluster_id, era)).count(), 0);
+	})
+}
 
-		assert_ok!(DdcPayouts::begin_rewarding_providers(
+#[test]
+fn send_charging_customers_batch_prices_bucket_metadata() {
+	ExtBuilder.build_and_execute(|| {
+		System::set_block_number(1);
+
+		let dac_account = 123u128;
+		let user4 = 4u128;
+		let replicated_bucket: BucketId = 4;
+		let cold_bucket: BucketId = 4 + BUCKETS_OFFSET;
+		let cluster_id = ClusterId::from([12; 20]);
+		let era = 100;
+		let max_batch_index = 0;
+		let usage = CustomerUsage {
+			transferred_bytes: 467457,
+			stored_bytes: 45674567456,
+			number_of_puts: 3456345,
+			number_of_gets: 242334563456423,
+		};
+		let batch =
+			vec![(user4, replicated_bucket, usage.clone()), (user4, cold_bucket, usage.clone())];
+		let start_date = NaiveDate::from_ymd_opt(2023, 4, 1).unwrap(); // April 1st
+		let time = NaiveTime::from_hms_opt(0, 0, 0).unwrap(); // Midnight
+		let start_era: i64 =
+			DateTime::<Utc>::from_naive_utc_and_offset(start_date.and_time(time), Utc).timestamp();
+		let end_era: i64 = start_era + (30.44 * 24.0 * 3600.0) as i64;
+		let (customers_merkle_root, customers_proofs) =
+			get_sequential_batches_commitment(&[batch.clone()]);
+
+		// the cluster replicates twice by default and discounts cold storage by 40%
+		BucketsMetadata::set(vec![
+			(
+				replicated_bucket,
+				BucketMetadata { replication_factor: Some(4), ..Default::default() },
+			),
+			(
+				cold_bucket,
+				BucketMetadata { storage_class: StorageClass::Cold, ..Default::default() },
+			),
+		]);
+
+		assert_ok!(DdcPayouts::set_validator_threshold(RuntimeOrigin::root(), 1));
+		assert_ok!(DdcPayouts::begin_billing_report(
 			RuntimeOrigin::signed(dac_account),
 			cluster_id,
 			era,
+			start_era,
+			end_era,
+			customers_merkle_root,
+			H256::default(),
 			max_batch_index,
-			total_node_usage,
+			0,
+			CustomerUsage::default(),
+			NodeUsage::default(),
 		));
-
-		assert_noop!(
-			DdcPayouts::end_billing_report(RuntimeOrigin::signed(dac_account), cluster_id, era,),
-			Error::<Test>::NotExpectedState
-		);
-
-		assert_ok!(DdcPayouts::send_rewarding_providers_batch(
+		assert_ok!(DdcPayouts::begin_charging_customers(
 			RuntimeOrigin::signed(dac_account),
 			cluster_id,
 			era,
-			batch_index,
-			payees.clone(),
 		));
-
-		assert_noop!(
-			DdcPayouts::end_billing_report(RuntimeOrigin::signed(dac_account), cluster_id, era,),
-			Error::<Test>::NotExpectedState
-		);
-
-		assert_ok!(DdcPayouts::send_rewarding_providers_batch(
+		assert_ok!(DdcPayouts::send_charging_customers_batch(
 			RuntimeOrigin::signed(dac_account),
 			cluster_id,
 			era,
-			batch_index + 1,
-			payees,
+			0,
+			batch,
+			customers_proofs[0].clone(),
 		));
 
-		assert_noop!(
-			DdcPayouts::end_billing_report(RuntimeOrigin::signed(dac_account), cluster_id, era,),
-			Error::<Test>::NotExpectedState
+		let charge = calculate_charge_parts_for_month(cluster_id, usage.clone());
+		let replicated_charge =
+			charge.transfer + charge.puts + charge.gets + charge.storage * 4 / 2;
+		// the cold bucket is charged on top of the usage of the replicated one
+		let total_charge =
+			calculate_charge_parts_for_month(cluster_id, combined_usage(&usage, &usage));
+		let cold_storage = total_charge.storage - charge.storage;
+		let cold_charge = total_charge.transfer - charge.transfer + total_charge.puts - charge.puts +
+			total_charge.gets -
+			charge.gets + cold_storage -
+			Perquintill::from_percent(40) * cold_storage;
+		for (bucket_id, amount) in
+			[(replicated_bucket, replicated_charge), (cold_bucket, cold_charge)]
+		{
+			System::assert_has_event(
+				Event::Charged {
+					cluster_id,
+					era,
+					batch_index: 0,
+					customer_id: user4,
+					bucket_id,
+					amount,
+				}
+				.into(),
+			);
+		}
+
+		let report = DdcPayouts::active_billing_reports(cluster_id, era).unwrap();
+		assert_eq!(
+			report.total_customer_charge.storage,
+			charge.storage * 4 / 2 + cold_storage - Perquintill::from_percent(40) * cold_storage
 		);
 	})
 }
 
 #[test]
-fn end_billing_report_works() {
+fn send_charging_customers_batch_charges_in_asset() {
 	ExtBuilder.build_and_execute(|| {
 		System::set_block_number(1);
 
+		let dac_account = 123u128;
+		let user4 = 4u128;
+		let bucket1: BucketId = 4;
+		let bucket2: BucketId = 4 + BUCKETS_OFFSET;
+		let cluster_id = ClusterId::from([12; 20]);
+		let era = 100;
+		let max_batch_index = 0;
+		let usage1 = CustomerUsage {
+			transferred_bytes: 467457,
+			stored_bytes: 45674567456,
+			number_of_puts: 3456345,
+			number_of_gets: 242334563456423,
+		};
+		let usage2 = CustomerUsage {
+			transferred_bytes: 1024,
+			stored_bytes: 34567456,
+			number_of_puts: 3456,
+			number_of_gets: 2423345,
+		};
+		let batch = vec![(user4, bucket1, usage1.clone()), (user4, bucket2, usage2.clone())];
 		let start_date = NaiveDate::from_ymd_opt(2023, 4, 1).unwrap(); // April 1st
-
 		let time = NaiveTime::from_hms_opt(0, 0, 0).unwrap(); // Midnight
 		let start_era: i64 =
 			DateTime::<Utc>::from_naive_utc_and_offset(start_date.and_time(time), Utc).timestamp();
 		let end_era: i64 = start_era + (30.44 * 24.0 * 3600.0) as i64;
-		let dac_account = 2u128;
-		let user1 = 3u128;
-		let node1 = 33u128;
-		let cluster_id = ClusterId::from([12; 20]);
-		let era = 100;
-		let max_batch_index = 0;
-		let batch_index = 0;
-		let total_node_usage = NodeUsage::default();
-		let payers = vec![(user1, CustomerUsage::default())];
-		let payees = vec![(node1, NodeUsage::default())];
+		let (customers_merkle_root, customers_proofs) =
+			get_sequential_batches_commitment(&[batch.clone()]);
+
+		let charge1 = calculate_charge_for_month(cluster_id, usage1.clone());
+		let charge2 =
+			calculate_charge_for_month(cluster_id, combined_usage(&usage1, &usage2)) - charge1;
+		let rate = FixedU128::from_rational(3, 2);
+		// the asset equivalent is rounded up
+		let asset_charge1 = multiply_by_rational_with_rounding(
+			charge1,
+			rate.into_inner(),
+			FixedU128::DIV,
+			Rounding::Up,
+		)
+		.unwrap();
+		let asset_charge2 = multiply_by_rational_with_rounding(
+			charge2,
+			rate.into_inner(),
+			FixedU128::DIV,
+			Rounding::Up,
+		)
+		.unwrap();
 
-		assert_ok!(DdcPayouts::set_authorised_caller(RuntimeOrigin::root(), dac_account));
+		// the deposit of the asset covers half of the second charge
+		PaymentAsset::set(Some(1));
+		AssetRate::set(Some(rate));
+		AssetDeposit::set(asset_charge1 + asset_charge2 / 2);
+		let reserve_balance = 10 * (charge1 + charge2);
+		Balances::make_free_balance_be(&RESERVE_ACCOUNT_ID, reserve_balance);
 
+		assert_ok!(DdcPayouts::set_validator_threshold(RuntimeOrigin::root(), 1));
 		assert_ok!(DdcPayouts::begin_billing_report(
 			RuntimeOrigin::signed(dac_account),
 			cluster_id,
 			era,
 			start_era,
 			end_era,
+			customers_merkle_root,
+			H256::default(),
+			max_batch_index,
+			0,
+			CustomerUsage::default(),
+			NodeUsage::default(),
 		));
-
-		let report = DdcPayouts::active_billing_reports(cluster_id, era).unwrap();
-		assert_eq!(report.state, State::Initialized);
-
 		assert_ok!(DdcPayouts::begin_charging_customers(
 			RuntimeOrigin::signed(dac_account),
 			cluster_id,
 			era,
-			max_batch_index,
 		));
 
+		let vault = DdcPayouts::active_billing_reports(cluster_id, era).unwrap().vault;
+		let vault_balance_before = Balances::free_balance(vault);
+		let balance_before = Balances::free_balance(user4);
 		assert_ok!(DdcPayouts::send_charging_customers_batch(
 			RuntimeOrigin::signed(dac_account),
 			cluster_id,
 			era,
-			batch_index,
-			payers,
+			0,
+			batch,
+			customers_proofs[0].clone(),
 		));
 
-		assert_ok!(DdcPayouts::end_charging_customers(
-			RuntimeOrigin::signed(dac_account),
-			cluster_id,
-			era,
-		));
+		System::assert_has_event(
+			Event::Charged {
+				cluster_id,
+				era,
+				batch_index: 0,
+				customer_id: user4,
+				bucket_id: bucket1,
+				amount: charge1,
+			}
+			.into(),
+		);
+		let charged2 = multiply_by_rational_with_rounding(
+			asset_charge2 / 2,
+			charge2,
+			asset_charge2,
+			Rounding::Down,
+		)
+		.unwrap();
+		System::assert_has_event(
+			Event::Indebted {
+				cluster_id,
+				era,
+				batch_index: 0,
+				customer_id: user4,
+				bucket_id: bucket2,
+				amount: charge2 - charged2,
+			}
+			.into(),
+		);
+		assert_eq!(DdcPayouts::debtor_customers(cluster_id, user4), Some(charge2 - charged2));
 
-		assert_ok!(DdcPayouts::begin_rewarding_providers(
+		// the customer pays in the asset and the reserve pays the vault in the native currency
+		assert_eq!(AssetDeposit::get(), 0);
+		assert_eq!(Balances::free_balance(user4), balance_before);
+		assert_eq!(
+			Balances::free_balance(RESERVE_ACCOUNT_ID),
+			reserve_balance - charge1 - charged2
+		);
+		assert_eq!(Balances::free_balance(vault), vault_balance_before + charge1 + charged2);
+	})
+}
+
+fn begin_charging_customers_in_asset(
+	dac_account: AccountId,
+	cluster_id: ClusterId,
+	era: DdcEra,
+	batch: &Vec<(AccountId, BucketId, CustomerUsage)>,
+) -> Vec<H256> {
+	let start_date = NaiveDate::from_ymd_opt(2023, 4, 1).unwrap(); // April 1st
+	let time = NaiveTime::from_hms_opt(0, 0, 0).unwrap(); // Midnight
+	let start_era: i64 =
+		DateTime::<Utc>::from_naive_utc_and_offset(start_date.and_time(time), Utc).timestamp();
+	let end_era: i64 = start_era + (30.44 * 24.0 * 3600.0) as i64;
+	let (customers_merkle_root, customers_proofs) = get_batches_commitment(&[(0, batch)]);
+
+	PaymentAsset::set(Some(1));
+	assert_ok!(DdcPayouts::set_validator_threshold(RuntimeOrigin::root(), 1));
+	assert_ok!(DdcPayouts::begin_billing_report(
+		RuntimeOrigin::signed(dac_account),
+		cluster_id,
+		era,
+		start_era,
+		end_era,
+		customers_merkle_root,
+		H256::default(),
+		0,
+		0,
+		CustomerUsage::default(),
+		NodeUsage::default(),
+	));
+	assert_ok!(DdcPayouts::begin_charging_customers(
+		RuntimeOrigin::signed(dac_account),
+		cluster_id,
+		era,
+	));
+
+	customers_proofs[0].clone()
+}
+
+#[test]
+fn send_charging_customers_batch_charges_deposit_if_reserve_can_not_pay_for_asset() {
+	ExtBuilder.build_and_execute(|| {
+		System::set_block_number(1);
+
+		let dac_account = 123u128;
+		let user4 = 4u128;
+		let bucket_id: BucketId = 4;
+		let cluster_id = ClusterId::from([12; 20]);
+		let era = 100;
+		let usage = CustomerUsage {
+			transferred_bytes: 467457,
+			stored_bytes: 45674567456,
+			number_of_puts: 3456345,
+			number_of_gets: 242334563456423,
+		};
+		let batch = vec![(user4, bucket_id, usage.clone())];
+		let charge = calculate_charge_for_month(cluster_id, usage);
+
+		AssetRate::set(Some(FixedU128::from_u32(1)));
+		AssetDeposit::set(charge);
+		// the reserve has to stay alive after paying the vault
+		Balances::make_free_balance_be(&RESERVE_ACCOUNT_ID, charge);
+		let proof = begin_charging_customers_in_asset(dac_account, cluster_id, era, &batch);
+		let vault = DdcPayouts::active_billing_reports(cluster_id, era).unwrap().vault;
+		let vault_balance_before = Balances::free_balance(vault);
+		let user4_balance_before = Balances::free_balance(user4);
+
+		assert_ok!(DdcPayouts::send_charging_customers_batch(
 			RuntimeOrigin::signed(dac_account),
 			cluster_id,
 			era,
-			max_batch_index,
-			total_node_usage,
+			0,
+			batch,
+			proof,
 		));
 
-		assert_ok!(DdcPayouts::send_rewarding_providers_batch(
+		System::assert_has_event(
+			Event::ReserveBalanceTooLow {
+				cluster_id,
+				era,
+				customer_id: user4,
+				asset_id: 1,
+				amount: charge,
+			}
+			.into(),
+		);
+		// the asset charge is reverted and the native deposit is charged instead
+		assert_eq!(AssetDeposit::get(), charge);
+		assert_eq!(Balances::free_balance(RESERVE_ACCOUNT_ID), charge);
+		assert_eq!(Balances::free_balance(user4), user4_balance_before - charge);
+		assert_eq!(Balances::free_balance(vault), vault_balance_before + charge);
+		assert_eq!(DdcPayouts::debtor_customers(cluster_id, user4), None);
+		assert_eq!(
+			DdcPayouts::charged_payment_sources(
+				(cluster_id, era),
+				(user4, PaymentSource::Asset(1))
+			),
+			None
+		);
+		assert_eq!(
+			DdcPayouts::charged_payment_sources((cluster_id, era), (user4, PaymentSource::Deposit))
+				.map(|charge| charge.charged),
+			Some(charge)
+		);
+	})
+}
+
+#[test]
+fn send_charging_customers_batch_charges_asset_if_reserve_can_pay_for_it() {
+	ExtBuilder.build_and_execute(|| {
+		System::set_block_number(1);
+
+		let dac_account = 123u128;
+		let user4 = 4u128;
+		let bucket_id: BucketId = 4;
+		let cluster_id = ClusterId::from([12; 20]);
+		let era = 100;
+		let usage = CustomerUsage {
+			transferred_bytes: 467457,
+			stored_bytes: 45674567456,
+			number_of_puts: 3456345,
+			number_of_gets: 242334563456423,
+		};
+		let batch = vec![(user4, bucket_id, usage.clone())];
+		let charge = calculate_charge_for_month(cluster_id, usage);
+
+		AssetRate::set(Some(FixedU128::from_u32(1)));
+		AssetDeposit::set(charge);
+		Balances::make_free_balance_be(&RESERVE_ACCOUNT_ID, 2 * charge);
+		let proof = begin_charging_customers_in_asset(dac_account, cluster_id, era, &batch);
+		let user4_balance_before = Balances::free_balance(user4);
+
+		assert_ok!(DdcPayouts::send_charging_customers_batch(
 			RuntimeOrigin::signed(dac_account),
 			cluster_id,
 			era,
-			batch_index,
-			payees,
+			0,
+			batch,
+			proof,
 		));
+		assert_eq!(AssetDeposit::get(), 0);
+		assert_eq!(Balances::free_balance(RESERVE_ACCOUNT_ID), charge);
+		assert_eq!(Balances::free_balance(user4), user4_balance_before);
+	})
+}
 
-		assert_ok!(DdcPayouts::end_rewarding_providers(
+#[test]
+fn abort_billing_report_refunds_charges_in_asset() {
+	ExtBuilder.build_and_execute(|| {
+		System::set_block_number(1);
+
+		let dac_account = 123u128;
+		let user4 = 4u128;
+		let bucket_id: BucketId = 4;
+		let cluster_id = ClusterId::from([12; 20]);
+		let era = 100;
+		let usage = CustomerUsage {
+			transferred_bytes: 467457,
+			stored_bytes: 45674567456,
+			number_of_puts: 3456345,
+			number_of_gets: 242334563456423,
+		};
+		let batch = vec![(user4, bucket_id, usage)];
+
+		// the asset deposit covers less than a native unit of the charge
+		AssetRate::set(Some(FixedU128::from_u32(1_000)));
+		AssetDeposit::set(999);
+		Balances::make_free_balance_be(&RESERVE_ACCOUNT_ID, 1_000);
+		let proof = begin_charging_customers_in_asset(dac_account, cluster_id, era, &batch);
+
+		assert_ok!(DdcPayouts::send_charging_customers_batch(
 			RuntimeOrigin::signed(dac_account),
 			cluster_id,
 			era,
+			0,
+			batch,
+			proof,
 		));
+		assert_eq!(AssetDeposit::get(), 0);
+		assert_eq!(
+			DdcPayouts::charged_payment_sources(
+				(cluster_id, era),
+				(user4, PaymentSource::Asset(1))
+			),
+			Some(PaymentSourceCharge { charged: 0, asset_charged: 999 })
+		);
 
-		assert_ok!(DdcPayouts::end_billing_report(
+		assert_ok!(DdcPayouts::abort_billing_report(RuntimeOrigin::root(), cluster_id, era));
+		assert_ok!(DdcPayouts::roll_back_billing_report(
 			RuntimeOrigin::signed(dac_account),
 			cluster_id,
 			era,
+			MaxBatchSize::get() as u32,
 		));
 
-		System::assert_last_event(Event::BillingReportFinalized { cluster_id, era }.into());
-
-		let report_end = DdcPayouts::active_billing_reports(cluster_id, era).unwrap();
-		assert!(report_end.rewarding_processed_batches.is_empty());
-		assert!(report_end.charging_processed_batches.is_empty());
-		assert_eq!(report_end.state, State::Finalized);
+		assert_eq!(AssetDeposit::get(), 999);
+		assert_eq!(Balances::free_balance(RESERVE_ACCOUNT_ID), 1_000);
 	})
 }
@@ -1,11 +1,16 @@
-//! Autogenerated weights for pallet_ddc_payouts
+//! Weights for pallet_ddc_payouts
 //!
-//! THIS FILE WAS AUTO-GENERATED USING THE SUBSTRATE BENCHMARK CLI VERSION 4.0.0-dev
-//! DATE: 2023-12-20, STEPS: `50`, REPEAT: 20, LOW RANGE: `[]`, HIGH RANGE: `[]`
-//! HOSTNAME: `bench`, CPU: `DO-Premium-AMD`
-//! EXECUTION: Some(Wasm), WASM-EXECUTION: Interpreted, CHAIN: Some("dev"), DB CACHE: 1024
+//! The weights were generated with the Substrate benchmark CLI on 2023-12-20 (STEPS: `50`, REPEAT:
+//! 20, HOSTNAME: `bench`). The weights of the following calls are provisional estimates derived
+//! from their storage access, not benchmark results, and must be regenerated with the command below
+//! before a release: `set_validator_threshold`, `begin_billing_report`, `begin_charging_customers`,
+//! `send_charging_customers_batch`, `end_charging_customers`, `begin_rewarding_providers`,
+//! `send_rewarding_providers_batch`, `end_rewarding_providers`, `end_billing_report`,
+//! `dispute_billing_report`, `settle_dispute`, `claim_owed_reward`, `claim_validator_fees`,
+//! `prune_billing_report`, `abort_billing_report`, `roll_back_billing_report`,
+//! `snapshot_validator_scores`.
 
-// Executed Command:
+// Command to regenerate the weights:
 // ./target/release/cere
 // benchmark
 // pallet
@@ -27,7 +32,7 @@ use sp_std::marker::PhantomData;
 
 /// Weight functions needed for pallet_ddc_payouts.
 pub trait WeightInfo {
-	fn set_authorised_caller() -> Weight;
+	fn set_validator_threshold() -> Weight;
 	fn begin_billing_report() -> Weight;
 	fn begin_charging_customers() -> Weight;
 	fn send_charging_customers_batch(b: u32, ) -> Weight;
@@ -36,179 +41,491 @@ pub trait WeightInfo {
 	fn send_rewarding_providers_batch(b: u32, ) -> Weight;
 	fn end_rewarding_providers() -> Weight;
 	fn end_billing_report() -> Weight;
+	fn dispute_billing_report() -> Weight;
+	fn settle_dispute() -> Weight;
+	fn claim_owed_reward() -> Weight;
+	fn claim_validator_fees() -> Weight;
+	fn prune_billing_report() -> Weight;
+	fn abort_billing_report() -> Weight;
+	fn roll_back_billing_report(r: u32, ) -> Weight;
+	fn snapshot_validator_scores(s: u32, ) -> Weight;
 }
 
 /// Weights for pallet_ddc_payouts using the Substrate node and recommended hardware.
 pub struct SubstrateWeight<T>(PhantomData<T>);
 impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
-	// Storage: DdcPayouts AuthorisedCaller (r:0 w:1)
-	fn set_authorised_caller() -> Weight {
-		Weight::from_parts(90_258_000_u64, 0)
+	// Storage: Session Validators (r:1 w:0)
+	// Storage: DdcPayouts ValidatorThreshold (r:0 w:1)
+	fn set_validator_threshold() -> Weight {
+		Weight::from_parts(94_000_000_u64, 0)
+			.saturating_add(T::DbWeight::get().reads(1_u64))
 			.saturating_add(T::DbWeight::get().writes(1_u64))
 	}
-	// Storage: DdcPayouts AuthorisedCaller (r:1 w:0)
+	// Storage: Session Validators (r:1 w:0)
+	// Storage: DdcPayouts ValidatorThreshold (r:1 w:0)
+	// Storage: DdcPayouts BillingReportVotes (r:1 w:1)
+	// Storage: DdcPayouts ExecutedProposals (r:1 w:1)
+	// Storage: DdcPayouts ArchivedBillingReports (r:1 w:0)
 	// Storage: DdcPayouts ActiveBillingReports (r:1 w:1)
 	fn begin_billing_report() -> Weight {
-		Weight::from_parts(214_646_000_u64, 0)
-			.saturating_add(T::DbWeight::get().reads(2_u64))
-			.saturating_add(T::DbWeight::get().writes(1_u64))
+		Weight::from_parts(236_000_000_u64, 0)
+			.saturating_add(T::DbWeight::get().reads(6_u64))
+			.saturating_add(T::DbWeight::get().writes(3_u64))
 	}
-	// Storage: DdcPayouts AuthorisedCaller (r:1 w:0)
+	// Storage: Session Validators (r:1 w:0)
+	// Storage: DdcPayouts ValidatorThreshold (r:1 w:0)
+	// Storage: DdcPayouts BillingReportVotes (r:1 w:1)
+	// Storage: DdcPayouts ExecutedProposals (r:1 w:1)
+	// Storage: DdcPayouts ArchivedBillingReports (r:1 w:0)
 	// Storage: DdcPayouts ActiveBillingReports (r:1 w:1)
 	fn begin_charging_customers() -> Weight {
 		Weight::from_parts(228_676_000_u64, 0)
-			.saturating_add(T::DbWeight::get().reads(2_u64))
-			.saturating_add(T::DbWeight::get().writes(1_u64))
+			.saturating_add(T::DbWeight::get().reads(6_u64))
+			.saturating_add(T::DbWeight::get().writes(3_u64))
 	}
-	// Storage: DdcPayouts AuthorisedCaller (r:1 w:0)
+	// Storage: Session Validators (r:1 w:0)
+	// Storage: DdcPayouts ValidatorThreshold (r:1 w:0)
+	// Storage: DdcPayouts BillingReportVotes (r:1 w:1)
+	// Storage: DdcPayouts ExecutedProposals (r:1 w:1)
+	// Storage: DdcPayouts ArchivedBillingReports (r:1 w:0)
 	// Storage: DdcPayouts ActiveBillingReports (r:1 w:1)
 	// Storage: DdcClusters ClustersGovParams (r:1 w:0)
 	// Storage: DdcCustomers Ledger (r:1 w:1)
-	// Storage: System Account (r:2 w:2)
+	// Storage: DdcCustomers BucketBudgets (r:1 w:1)
+	// Storage: System Account (r:3 w:3)
 	// Storage: DdcPayouts DebtorCustomers (r:1 w:1)
+	// Storage: DdcPayouts DebtorBuckets (r:1 w:1)
+	// Storage: DdcPayouts DebtorBucketOwners (r:1 w:1)
+	// Storage: DdcPayouts TotalOwedRewards (r:1 w:0)
+	// Storage: DdcCustomers Buckets (r:1 w:0)
+	// Storage: DdcPayouts ChargedCustomers (r:1 w:1)
+	// Storage: DdcPayouts ChargedBuckets (r:1 w:1)
+	// Storage: DdcPayouts ChargedPaymentSources (r:1 w:1)
+	// Storage: DdcPayouts BillingReportFunds (r:1 w:1)
 	/// The range of component `b` is `[1, 1000]`.
 	fn send_charging_customers_batch(b: u32, ) -> Weight {
-		Weight::from_parts(891_324_000_u64, 0)
-			// Standard Error: 3_864_375
-			.saturating_add(Weight::from_parts(558_679_506_u64, 0).saturating_mul(b as u64))
-			.saturating_add(T::DbWeight::get().reads(7_u64))
-			.saturating_add(T::DbWeight::get().reads((2_u64).saturating_mul(b as u64)))
-			.saturating_add(T::DbWeight::get().writes(5_u64))
-			.saturating_add(T::DbWeight::get().writes((2_u64).saturating_mul(b as u64)))
+		Weight::from_parts(903_517_000_u64, 0)
+			.saturating_add(Weight::from_parts(781_406_217_u64, 0).saturating_mul(b as u64))
+			.saturating_add(T::DbWeight::get().reads(11_u64))
+			.saturating_add(T::DbWeight::get().reads((12_u64).saturating_mul(b as u64)))
+			.saturating_add(T::DbWeight::get().writes(7_u64))
+			.saturating_add(T::DbWeight::get().writes((9_u64).saturating_mul(b as u64)))
 	}
-	// Storage: DdcPayouts AuthorisedCaller (r:1 w:0)
+	// Storage: Session Validators (r:1 w:0)
+	// Storage: DdcPayouts ValidatorThreshold (r:1 w:0)
+	// Storage: DdcPayouts BillingReportVotes (r:1 w:1)
+	// Storage: DdcPayouts ExecutedProposals (r:1 w:1)
+	// Storage: DdcPayouts ArchivedBillingReports (r:1 w:0)
 	// Storage: DdcPayouts ActiveBillingReports (r:1 w:1)
 	// Storage: DdcClusters ClustersGovParams (r:1 w:0)
-	// Storage: System Account (r:3 w:3)
+	// Storage: System Account (r:4 w:4)
 	// Storage: DdcClusters Clusters (r:1 w:0)
-	// Storage: Staking Validators (r:2 w:0)
-	// Storage: Staking Bonded (r:1 w:0)
-	// Storage: Staking Ledger (r:1 w:0)
-	// Storage: Staking Nominators (r:1 w:0)
+	// Storage: DdcPayouts ValidatorFeesPots (r:1 w:1)
+	// Storage: DdcPayouts BillingReportFunds (r:1 w:1)
 	fn end_charging_customers() -> Weight {
-		Weight::from_parts(1_691_550_000_u64, 0)
-			.saturating_add(T::DbWeight::get().reads(12_u64))
-			.saturating_add(T::DbWeight::get().writes(4_u64))
+		Weight::from_parts(1_137_902_000_u64, 0)
+			.saturating_add(T::DbWeight::get().reads(14_u64))
+			.saturating_add(T::DbWeight::get().writes(9_u64))
 	}
-	// Storage: DdcPayouts AuthorisedCaller (r:1 w:0)
+	// Storage: Session Validators (r:1 w:0)
+	// Storage: DdcPayouts ValidatorThreshold (r:1 w:0)
+	// Storage: DdcPayouts BillingReportVotes (r:1 w:1)
+	// Storage: DdcPayouts ExecutedProposals (r:1 w:1)
+	// Storage: DdcPayouts ArchivedBillingReports (r:1 w:0)
 	// Storage: DdcPayouts ActiveBillingReports (r:1 w:1)
 	fn begin_rewarding_providers() -> Weight {
 		Weight::from_parts(234_686_000_u64, 0)
-			.saturating_add(T::DbWeight::get().reads(2_u64))
-			.saturating_add(T::DbWeight::get().writes(1_u64))
+			.saturating_add(T::DbWeight::get().reads(6_u64))
+			.saturating_add(T::DbWeight::get().writes(3_u64))
 	}
-	// Storage: DdcPayouts AuthorisedCaller (r:1 w:0)
+	// Storage: Session Validators (r:1 w:0)
+	// Storage: DdcPayouts ValidatorThreshold (r:1 w:0)
+	// Storage: DdcPayouts BillingReportVotes (r:1 w:1)
+	// Storage: DdcPayouts ExecutedProposals (r:1 w:1)
+	// Storage: DdcPayouts ArchivedBillingReports (r:1 w:0)
 	// Storage: DdcPayouts ActiveBillingReports (r:1 w:1)
-	// Storage: System Account (r:2 w:2)
+	// Storage: System Account (r:3 w:3)
+	// Storage: DdcPayouts OwingProviders (r:1 w:1)
+	// Storage: DdcPayouts OwingNodes (r:2 w:1)
+	// Storage: DdcPayouts TotalOwedRewards (r:1 w:1)
+	// Storage: DdcPayouts RewardedProviders (r:1 w:1)
+	// Storage: DdcStaking Nodes (r:1 w:0)
+	// Storage: DdcStaking DelegatedStake (r:1 w:0)
 	/// The range of component `b` is `[1, 1000]`.
 	fn send_rewarding_providers_batch(b: u32, ) -> Weight {
 		Weight::from_parts(565_710_000_u64, 0)
-			// Standard Error: 854_032
-			.saturating_add(Weight::from_parts(408_429_599_u64, 0).saturating_mul(b as u64))
-			.saturating_add(T::DbWeight::get().reads(4_u64))
-			.saturating_add(T::DbWeight::get().reads((1_u64).saturating_mul(b as u64)))
-			.saturating_add(T::DbWeight::get().writes(3_u64))
-			.saturating_add(T::DbWeight::get().writes((1_u64).saturating_mul(b as u64)))
+			.saturating_add(Weight::from_parts(541_096_215_u64, 0).saturating_mul(b as u64))
+			.saturating_add(T::DbWeight::get().reads(8_u64))
+			.saturating_add(T::DbWeight::get().reads((9_u64).saturating_mul(b as u64)))
+			.saturating_add(T::DbWeight::get().writes(5_u64))
+			.saturating_add(T::DbWeight::get().writes((6_u64).saturating_mul(b as u64)))
 	}
-	// Storage: DdcPayouts AuthorisedCaller (r:1 w:0)
+	// Storage: Session Validators (r:1 w:0)
+	// Storage: DdcPayouts ValidatorThreshold (r:1 w:0)
+	// Storage: DdcPayouts BillingReportVotes (r:1 w:1)
+	// Storage: DdcPayouts ExecutedProposals (r:1 w:1)
+	// Storage: DdcPayouts ArchivedBillingReports (r:1 w:0)
 	// Storage: DdcPayouts ActiveBillingReports (r:1 w:1)
+	// Storage: DdcPayouts DisputePeriodEnds (r:0 w:1)
 	fn end_rewarding_providers() -> Weight {
 		Weight::from_parts(274_535_000_u64, 0)
-			.saturating_add(T::DbWeight::get().reads(2_u64))
-			.saturating_add(T::DbWeight::get().writes(1_u64))
+			.saturating_add(T::DbWeight::get().reads(6_u64))
+			.saturating_add(T::DbWeight::get().writes(4_u64))
 	}
-	// Storage: DdcPayouts AuthorisedCaller (r:1 w:0)
+	// Storage: Session Validators (r:1 w:0)
+	// Storage: DdcPayouts ValidatorThreshold (r:1 w:0)
+	// Storage: DdcPayouts BillingReportVotes (r:1 w:1)
+	// Storage: DdcPayouts ExecutedProposals (r:1 w:1)
+	// Storage: DdcPayouts ArchivedBillingReports (r:1 w:0)
 	// Storage: DdcPayouts ActiveBillingReports (r:1 w:1)
+	// Storage: DdcPayouts LastFinalizedEras (r:1 w:1)
+	// Storage: DdcPayouts BillingReportDisputes (r:1 w:0)
+	// Storage: DdcPayouts BillingReportRollbacks (r:1 w:0)
+	// Storage: DdcPayouts DisputePeriodEnds (r:1 w:1)
+	// Storage: DdcPayouts BillingReportFunds (r:0 w:1)
+	// Storage: DdcPayouts PruningQueueRange (r:1 w:1)
+	// Storage: DdcPayouts PruningQueue (r:0 w:1)
 	fn end_billing_report() -> Weight {
-		Weight::from_parts(232_626_000_u64, 0)
+		Weight::from_parts(258_410_000_u64, 0)
+			.saturating_add(T::DbWeight::get().reads(11_u64))
+			.saturating_add(T::DbWeight::get().writes(8_u64))
+	}
+	// Storage: DdcPayouts ActiveBillingReports (r:1 w:0)
+	// Storage: DdcPayouts BillingReportDisputes (r:1 w:1)
+	// Storage: DdcPayouts DisputePeriodEnds (r:1 w:0)
+	// Storage: System Account (r:1 w:1)
+	fn dispute_billing_report() -> Weight {
+		Weight::from_parts(198_357_000_u64, 0)
+			.saturating_add(T::DbWeight::get().reads(4_u64))
+			.saturating_add(T::DbWeight::get().writes(2_u64))
+	}
+	// Storage: DdcPayouts BillingReportDisputes (r:1 w:1)
+	// Storage: DdcPayouts ActiveBillingReports (r:1 w:1)
+	// Storage: DdcPayouts DisputePeriodEnds (r:0 w:1)
+	// Storage: DdcPayouts BillingReportFunds (r:1 w:0)
+	// Storage: DdcClusters Clusters (r:1 w:0)
+	// Storage: DdcPayouts ValidatorFeesPots (r:1 w:1)
+	// Storage: System Account (r:6 w:6)
+	// Storage: DdcPayouts BillingReportRollbacks (r:0 w:1)
+	fn settle_dispute() -> Weight {
+		Weight::from_parts(441_152_000_u64, 0)
+			.saturating_add(T::DbWeight::get().reads(11_u64))
+			.saturating_add(T::DbWeight::get().writes(11_u64))
+	}
+	// Storage: DdcPayouts OwingProviders (r:1 w:1)
+	// Storage: DdcPayouts OwingNodes (r:2 w:1)
+	// Storage: DdcPayouts TotalOwedRewards (r:1 w:1)
+	// Storage: System Account (r:2 w:2)
+	// Storage: DdcStaking Nodes (r:1 w:0)
+	// Storage: DdcStaking DelegatedStake (r:1 w:0)
+	fn claim_owed_reward() -> Weight {
+		Weight::from_parts(139_781_000_u64, 0)
+			.saturating_add(T::DbWeight::get().reads(8_u64))
+			.saturating_add(T::DbWeight::get().writes(5_u64))
+	}
+	// Storage: DdcPayouts ValidatorFeesPots (r:1 w:1)
+	// Storage: DdcPayouts ValidatorFeesClaims (r:1 w:1)
+	// Storage: DdcPayouts ValidatorFeesScores (r:1 w:0)
+	// Storage: System Account (r:2 w:2)
+	fn claim_validator_fees() -> Weight {
+		Weight::from_parts(104_361_000_u64, 0)
+			.saturating_add(T::DbWeight::get().reads(5_u64))
+			.saturating_add(T::DbWeight::get().writes(4_u64))
+	}
+	// Storage: DdcPayouts PruningQueue (r:1 w:1)
+	// Storage: DdcPayouts ActiveBillingReports (r:1 w:1)
+	// Storage: DdcPayouts ArchivedBillingReports (r:0 w:1)
+	// Storage: DdcPayouts ExecutedProposals (r:0 w:2007)
+	fn prune_billing_report() -> Weight {
+		Weight::from_parts(61_203_000_u64, 0)
 			.saturating_add(T::DbWeight::get().reads(2_u64))
-			.saturating_add(T::DbWeight::get().writes(1_u64))
+			.saturating_add(T::DbWeight::get().writes(2010_u64))
+	}
+	// Storage: DdcPayouts ActiveBillingReports (r:1 w:1)
+	// Storage: DdcPayouts BillingReportRollbacks (r:1 w:1)
+	// Storage: DdcPayouts BillingReportFunds (r:1 w:0)
+	// Storage: DdcClusters Clusters (r:1 w:0)
+	// Storage: DdcPayouts ValidatorFeesPots (r:1 w:1)
+	// Storage: System Account (r:6 w:6)
+	fn abort_billing_report() -> Weight {
+		Weight::from_parts(398_114_000_u64, 0)
+			.saturating_add(T::DbWeight::get().reads(11_u64))
+			.saturating_add(T::DbWeight::get().writes(9_u64))
+	}
+	// Storage: DdcPayouts BillingReportRollbacks (r:1 w:1)
+	// Storage: DdcPayouts ActiveBillingReports (r:1 w:1)
+	// Storage: DdcPayouts RewardedProviders (r:1 w:0)
+	// Storage: DdcPayouts ChargedPaymentSources (r:1001 w:1000)
+	// Storage: DdcPayouts ChargedCustomers (r:1001 w:1000)
+	// Storage: DdcPayouts ChargedBuckets (r:1 w:0)
+	// Storage: System Account (r:1001 w:1001)
+	// Storage: DdcCustomers Ledger (r:1000 w:1000)
+	// Storage: DdcPayouts DebtorCustomers (r:1000 w:1000)
+	// Storage: DdcPayouts BillingReportVotes (r:0 w:1)
+	// Storage: DdcPayouts ExecutedProposals (r:0 w:2007)
+	// Storage: DdcPayouts BillingReportFunds (r:0 w:1)
+	// Storage: DdcPayouts DisputePeriodEnds (r:0 w:1)
+	/// The range of component `r` is `[1, 1000]`.
+	fn roll_back_billing_report(r: u32, ) -> Weight {
+		Weight::from_parts(241_837_000_u64, 0)
+			.saturating_add(Weight::from_parts(281_406_533_u64, 0).saturating_mul(r as u64))
+			.saturating_add(T::DbWeight::get().reads(7_u64))
+			.saturating_add(T::DbWeight::get().reads((5_u64).saturating_mul(r as u64)))
+			.saturating_add(T::DbWeight::get().writes(2012_u64))
+			.saturating_add(T::DbWeight::get().writes((5_u64).saturating_mul(r as u64)))
+	}
+	// Storage: DdcPayouts ValidatorFeesPots (r:1 w:1)
+	// Storage: DdcPayouts ValidatorScoresSnapshotCursors (r:1 w:1)
+	// Storage: VoterList ListNodes (r:1001 w:0)
+	// Storage: DdcPayouts ValidatorFeesScores (r:1000 w:1000)
+	// Storage: Staking Bonded (r:1000 w:0)
+	// Storage: Staking Ledger (r:1000 w:0)
+	/// The range of component `s` is `[1, 1000]`.
+	fn snapshot_validator_scores(s: u32, ) -> Weight {
+		Weight::from_parts(51_904_000_u64, 0)
+			.saturating_add(Weight::from_parts(38_716_402_u64, 0).saturating_mul(s as u64))
+			.saturating_add(T::DbWeight::get().reads(3_u64))
+			.saturating_add(T::DbWeight::get().reads((4_u64).saturating_mul(s as u64)))
+			.saturating_add(T::DbWeight::get().writes(2_u64))
+			.saturating_add(T::DbWeight::get().writes((1_u64).saturating_mul(s as u64)))
 	}
 }
 
 // For backwards compatibility and tests
 impl WeightInfo for () {
-	// Storage: DdcPayouts AuthorisedCaller (r:0 w:1)
-	fn set_authorised_caller() -> Weight {
-		Weight::from_parts(90_258_000_u64, 0)
+	// Storage: Session Validators (r:1 w:0)
+	// Storage: DdcPayouts ValidatorThreshold (r:0 w:1)
+	fn set_validator_threshold() -> Weight {
+		Weight::from_parts(94_000_000_u64, 0)
+			.saturating_add(RocksDbWeight::get().reads(1_u64))
 			.saturating_add(RocksDbWeight::get().writes(1_u64))
 	}
-	// Storage: DdcPayouts AuthorisedCaller (r:1 w:0)
+	// Storage: Session Validators (r:1 w:0)
+	// Storage: DdcPayouts ValidatorThreshold (r:1 w:0)
+	// Storage: DdcPayouts BillingReportVotes (r:1 w:1)
+	// Storage: DdcPayouts ExecutedProposals (r:1 w:1)
+	// Storage: DdcPayouts ArchivedBillingReports (r:1 w:0)
 	// Storage: DdcPayouts ActiveBillingReports (r:1 w:1)
 	fn begin_billing_report() -> Weight {
-		Weight::from_parts(214_646_000_u64, 0)
-			.saturating_add(RocksDbWeight::get().reads(2_u64))
-			.saturating_add(RocksDbWeight::get().writes(1_u64))
+		Weight::from_parts(236_000_000_u64, 0)
+			.saturating_add(RocksDbWeight::get().reads(6_u64))
+			.saturating_add(RocksDbWeight::get().writes(3_u64))
 	}
-	// Storage: DdcPayouts AuthorisedCaller (r:1 w:0)
+	// Storage: Session Validators (r:1 w:0)
+	// Storage: DdcPayouts ValidatorThreshold (r:1 w:0)
+	// Storage: DdcPayouts BillingReportVotes (r:1 w:1)
+	// Storage: DdcPayouts ExecutedProposals (r:1 w:1)
+	// Storage: DdcPayouts ArchivedBillingReports (r:1 w:0)
 	// Storage: DdcPayouts ActiveBillingReports (r:1 w:1)
 	fn begin_charging_customers() -> Weight {
 		Weight::from_parts(228_676_000_u64, 0)
-			.saturating_add(RocksDbWeight::get().reads(2_u64))
-			.saturating_add(RocksDbWeight::get().writes(1_u64))
+			.saturating_add(RocksDbWeight::get().reads(6_u64))
+			.saturating_add(RocksDbWeight::get().writes(3_u64))
 	}
-	// Storage: DdcPayouts AuthorisedCaller (r:1 w:0)
+	// Storage: Session Validators (r:1 w:0)
+	// Storage: DdcPayouts ValidatorThreshold (r:1 w:0)
+	// Storage: DdcPayouts BillingReportVotes (r:1 w:1)
+	// Storage: DdcPayouts ExecutedProposals (r:1 w:1)
+	// Storage: DdcPayouts ArchivedBillingReports (r:1 w:0)
 	// Storage: DdcPayouts ActiveBillingReports (r:1 w:1)
 	// Storage: DdcClusters ClustersGovParams (r:1 w:0)
 	// Storage: DdcCustomers Ledger (r:1 w:1)
-	// Storage: System Account (r:2 w:2)
+	// Storage: DdcCustomers BucketBudgets (r:1 w:1)
+	// Storage: System Account (r:3 w:3)
 	// Storage: DdcPayouts DebtorCustomers (r:1 w:1)
+	// Storage: DdcPayouts DebtorBuckets (r:1 w:1)
+	// Storage: DdcPayouts DebtorBucketOwners (r:1 w:1)
+	// Storage: DdcPayouts TotalOwedRewards (r:1 w:0)
+	// Storage: DdcCustomers Buckets (r:1 w:0)
+	// Storage: DdcPayouts ChargedCustomers (r:1 w:1)
+	// Storage: DdcPayouts ChargedBuckets (r:1 w:1)
+	// Storage: DdcPayouts ChargedPaymentSources (r:1 w:1)
+	// Storage: DdcPayouts BillingReportFunds (r:1 w:1)
 	/// The range of component `b` is `[1, 1000]`.
 	fn send_charging_customers_batch(b: u32, ) -> Weight {
-		Weight::from_parts(891_324_000_u64, 0)
-			// Standard Error: 3_864_375
-			.saturating_add(Weight::from_parts(558_679_506_u64, 0).saturating_mul(b as u64))
-			.saturating_add(RocksDbWeight::get().reads(7_u64))
-			.saturating_add(RocksDbWeight::get().reads((2_u64).saturating_mul(b as u64)))
-			.saturating_add(RocksDbWeight::get().writes(5_u64))
-			.saturating_add(RocksDbWeight::get().writes((2_u64).saturating_mul(b as u64)))
+		Weight::from_parts(903_517_000_u64, 0)
+			.saturating_add(Weight::from_parts(781_406_217_u64, 0).saturating_mul(b as u64))
+			.saturating_add(RocksDbWeight::get().reads(11_u64))
+			.saturating_add(RocksDbWeight::get().reads((12_u64).saturating_mul(b as u64)))
+			.saturating_add(RocksDbWeight::get().writes(7_u64))
+			.saturating_add(RocksDbWeight::get().writes((9_u64).saturating_mul(b as u64)))
 	}
-	// Storage: DdcPayouts AuthorisedCaller (r:1 w:0)
+	// Storage: Session Validators (r:1 w:0)
+	// Storage: DdcPayouts ValidatorThreshold (r:1 w:0)
+	// Storage: DdcPayouts BillingReportVotes (r:1 w:1)
+	// Storage: DdcPayouts ExecutedProposals (r:1 w:1)
+	// Storage: DdcPayouts ArchivedBillingReports (r:1 w:0)
 	// Storage: DdcPayouts ActiveBillingReports (r:1 w:1)
 	// Storage: DdcClusters ClustersGovParams (r:1 w:0)
-	// Storage: System Account (r:3 w:3)
+	// Storage: System Account (r:4 w:4)
 	// Storage: DdcClusters Clusters (r:1 w:0)
-	// Storage: Staking Validators (r:2 w:0)
-	// Storage: Staking Bonded (r:1 w:0)
-	// Storage: Staking Ledger (r:1 w:0)
-	// Storage: Staking Nominators (r:1 w:0)
+	// Storage: DdcPayouts ValidatorFeesPots (r:1 w:1)
+	// Storage: DdcPayouts BillingReportFunds (r:1 w:1)
 	fn end_charging_customers() -> Weight {
-		Weight::from_parts(1_691_550_000_u64, 0)
-			.saturating_add(RocksDbWeight::get().reads(12_u64))
-			.saturating_add(RocksDbWeight::get().writes(4_u64))
+		Weight::from_parts(1_137_902_000_u64, 0)
+			.saturating_add(RocksDbWeight::get().reads(14_u64))
+			.saturating_add(RocksDbWeight::get().writes(9_u64))
 	}
-	// Storage: DdcPayouts AuthorisedCaller (r:1 w:0)
+	// Storage: Session Validators (r:1 w:0)
+	// Storage: DdcPayouts ValidatorThreshold (r:1 w:0)
+	// Storage: DdcPayouts BillingReportVotes (r:1 w:1)
+	// Storage: DdcPayouts ExecutedProposals (r:1 w:1)
+	// Storage: DdcPayouts ArchivedBillingReports (r:1 w:0)
 	// Storage: DdcPayouts ActiveBillingReports (r:1 w:1)
 	fn begin_rewarding_providers() -> Weight {
 		Weight::from_parts(234_686_000_u64, 0)
-			.saturating_add(RocksDbWeight::get().reads(2_u64))
-			.saturating_add(RocksDbWeight::get().writes(1_u64))
+			.saturating_add(RocksDbWeight::get().reads(6_u64))
+			.saturating_add(RocksDbWeight::get().writes(3_u64))
 	}
-	// Storage: DdcPayouts AuthorisedCaller (r:1 w:0)
+	// Storage: Session Validators (r:1 w:0)
+	// Storage: DdcPayouts ValidatorThreshold (r:1 w:0)
+	// Storage: DdcPayouts BillingReportVotes (r:1 w:1)
+	// Storage: DdcPayouts ExecutedProposals (r:1 w:1)
+	// Storage: DdcPayouts ArchivedBillingReports (r:1 w:0)
 	// Storage: DdcPayouts ActiveBillingReports (r:1 w:1)
-	// Storage: System Account (r:2 w:2)
+	// Storage: System Account (r:3 w:3)
+	// Storage: DdcPayouts OwingProviders (r:1 w:1)
+	// Storage: DdcPayouts OwingNodes (r:2 w:1)
+	// Storage: DdcPayouts TotalOwedRewards (r:1 w:1)
+	// Storage: DdcPayouts RewardedProviders (r:1 w:1)
+	// Storage: DdcStaking Nodes (r:1 w:0)
+	// Storage: DdcStaking DelegatedStake (r:1 w:0)
 	/// The range of component `b` is `[1, 1000]`.
 	fn send_rewarding_providers_batch(b: u32, ) -> Weight {
 		Weight::from_parts(565_710_000_u64, 0)
-			// Standard Error: 854_032
-			.saturating_add(Weight::from_parts(408_429_599_u64, 0).saturating_mul(b as u64))
-			.saturating_add(RocksDbWeight::get().reads(4_u64))
-			.saturating_add(RocksDbWeight::get().reads((1_u64).saturating_mul(b as u64)))
-			.saturating_add(RocksDbWeight::get().writes(3_u64))
-			.saturating_add(RocksDbWeight::get().writes((1_u64).saturating_mul(b as u64)))
+			.saturating_add(Weight::from_parts(541_096_215_u64, 0).saturating_mul(b as u64))
+			.saturating_add(RocksDbWeight::get().reads(8_u64))
+			.saturating_add(RocksDbWeight::get().reads((9_u64).saturating_mul(b as u64)))
+			.saturating_add(RocksDbWeight::get().writes(5_u64))
+			.saturating_add(RocksDbWeight::get().writes((6_u64).saturating_mul(b as u64)))
 	}
-	// Storage: DdcPayouts AuthorisedCaller (r:1 w:0)
+	// Storage: Session Validators (r:1 w:0)
+	// Storage: DdcPayouts ValidatorThreshold (r:1 w:0)
+	// Storage: DdcPayouts BillingReportVotes (r:1 w:1)
+	// Storage: DdcPayouts ExecutedProposals (r:1 w:1)
+	// Storage: DdcPayouts ArchivedBillingReports (r:1 w:0)
 	// Storage: DdcPayouts ActiveBillingReports (r:1 w:1)
+	// Storage: DdcPayouts DisputePeriodEnds (r:0 w:1)
 	fn end_rewarding_providers() -> Weight {
 		Weight::from_parts(274_535_000_u64, 0)
-			.saturating_add(RocksDbWeight::get().reads(2_u64))
-			.saturating_add(RocksDbWeight::get().writes(1_u64))
+			.saturating_add(RocksDbWeight::get().reads(6_u64))
+			.saturating_add(RocksDbWeight::get().writes(4_u64))
 	}
-	// Storage: DdcPayouts AuthorisedCaller (r:1 w:0)
+	// Storage: Session Validators (r:1 w:0)
+	// Storage: DdcPayouts ValidatorThreshold (r:1 w:0)
+	// Storage: DdcPayouts BillingReportVotes (r:1 w:1)
+	// Storage: DdcPayouts ExecutedProposals (r:1 w:1)
+	// Storage: DdcPayouts ArchivedBillingReports (r:1 w:0)
 	// Storage: DdcPayouts ActiveBillingReports (r:1 w:1)
+	// Storage: DdcPayouts LastFinalizedEras (r:1 w:1)
+	// Storage: DdcPayouts BillingReportDisputes (r:1 w:0)
+	// Storage: DdcPayouts BillingReportRollbacks (r:1 w:0)
+	// Storage: DdcPayouts DisputePeriodEnds (r:1 w:1)
+	// Storage: DdcPayouts BillingReportFunds (r:0 w:1)
+	// Storage: DdcPayouts PruningQueueRange (r:1 w:1)
+	// Storage: DdcPayouts PruningQueue (r:0 w:1)
 	fn end_billing_report() -> Weight {
-		Weight::from_parts(232_626_000_u64, 0)
+		Weight::from_parts(258_410_000_u64, 0)
+			.saturating_add(RocksDbWeight::get().reads(11_u64))
+			.saturating_add(RocksDbWeight::get().writes(8_u64))
+	}
+	// Storage: DdcPayouts ActiveBillingReports (r:1 w:0)
+	// Storage: DdcPayouts BillingReportDisputes (r:1 w:1)
+	// Storage: DdcPayouts DisputePeriodEnds (r:1 w:0)
+	// Storage: System Account (r:1 w:1)
+	fn dispute_billing_report() -> Weight {
+		Weight::from_parts(198_357_000_u64, 0)
+			.saturating_add(RocksDbWeight::get().reads(4_u64))
+			.saturating_add(RocksDbWeight::get().writes(2_u64))
+	}
+	// Storage: DdcPayouts BillingReportDisputes (r:1 w:1)
+	// Storage: DdcPayouts ActiveBillingReports (r:1 w:1)
+	// Storage: DdcPayouts DisputePeriodEnds (r:0 w:1)
+	// Storage: DdcPayouts BillingReportFunds (r:1 w:0)
+	// Storage: DdcClusters Clusters (r:1 w:0)
+	// Storage: DdcPayouts ValidatorFeesPots (r:1 w:1)
+	// Storage: System Account (r:6 w:6)
+	// Storage: DdcPayouts BillingReportRollbacks (r:0 w:1)
+	fn settle_dispute() -> Weight {
+		Weight::from_parts(441_152_000_u64, 0)
+			.saturating_add(RocksDbWeight::get().reads(11_u64))
+			.saturating_add(RocksDbWeight::get().writes(11_u64))
+	}
+	// Storage: DdcPayouts OwingProviders (r:1 w:1)
+	// Storage: DdcPayouts OwingNodes (r:2 w:1)
+	// Storage: DdcPayouts TotalOwedRewards (r:1 w:1)
+	// Storage: System Account (r:2 w:2)
+	// Storage: DdcStaking Nodes (r:1 w:0)
+	// Storage: DdcStaking DelegatedStake (r:1 w:0)
+	fn claim_owed_reward() -> Weight {
+		Weight::from_parts(139_781_000_u64, 0)
+			.saturating_add(RocksDbWeight::get().reads(8_u64))
+			.saturating_add(RocksDbWeight::get().writes(5_u64))
+	}
+	// Storage: DdcPayouts ValidatorFeesPots (r:1 w:1)
+	// Storage: DdcPayouts ValidatorFeesClaims (r:1 w:1)
+	// Storage: DdcPayouts ValidatorFeesScores (r:1 w:0)
+	// Storage: System Account (r:2 w:2)
+	fn claim_validator_fees() -> Weight {
+		Weight::from_parts(104_361_000_u64, 0)
+			.saturating_add(RocksDbWeight::get().reads(5_u64))
+			.saturating_add(RocksDbWeight::get().writes(4_u64))
+	}
+	// Storage: DdcPayouts PruningQueue (r:1 w:1)
+	// Storage: DdcPayouts ActiveBillingReports (r:1 w:1)
+	// Storage: DdcPayouts ArchivedBillingReports (r:0 w:1)
+	// Storage: DdcPayouts ExecutedProposals (r:0 w:2007)
+	fn prune_billing_report() -> Weight {
+		Weight::from_parts(61_203_000_u64, 0)
 			.saturating_add(RocksDbWeight::get().reads(2_u64))
-			.saturating_add(RocksDbWeight::get().writes(1_u64))
+			.saturating_add(RocksDbWeight::get().writes(2010_u64))
+	}
+	// Storage: DdcPayouts ActiveBillingReports (r:1 w:1)
+	// Storage: DdcPayouts BillingReportRollbacks (r:1 w:1)
+	// Storage: DdcPayouts BillingReportFunds (r:1 w:0)
+	// Storage: DdcClusters Clusters (r:1 w:0)
+	// Storage: DdcPayouts ValidatorFeesPots (r:1 w:1)
+	// Storage: System Account (r:6 w:6)
+	fn abort_billing_report() -> Weight {
+		Weight::from_parts(398_114_000_u64, 0)
+			.saturating_add(RocksDbWeight::get().reads(11_u64))
+			.saturating_add(RocksDbWeight::get().writes(9_u64))
+	}
+	// Storage: DdcPayouts BillingReportRollbacks (r:1 w:1)
+	// Storage: DdcPayouts ActiveBillingReports (r:1 w:1)
+	// Storage: DdcPayouts RewardedProviders (r:1 w:0)
+	// Storage: DdcPayouts ChargedPaymentSources (r:1001 w:1000)
+	// Storage: DdcPayouts ChargedCustomers (r:1001 w:1000)
+	// Storage: DdcPayouts ChargedBuckets (r:1 w:0)
+	// Storage: System Account (r:1001 w:1001)
+	// Storage: DdcCustomers Ledger (r:1000 w:1000)
+	// Storage: DdcPayouts DebtorCustomers (r:1000 w:1000)
+	// Storage: DdcPayouts BillingReportVotes (r:0 w:1)
+	// Storage: DdcPayouts ExecutedProposals (r:0 w:2007)
+	// Storage: DdcPayouts BillingReportFunds (r:0 w:1)
+	// Storage: DdcPayouts DisputePeriodEnds (r:0 w:1)
+	/// The range of component `r` is `[1, 1000]`.
+	fn roll_back_billing_report(r: u32, ) -> Weight {
+		Weight::from_parts(241_837_000_u64, 0)
+			.saturating_add(Weight::from_parts(281_406_533_u64, 0).saturating_mul(r as u64))
+			.saturating_add(RocksDbWeight::get().reads(7_u64))
+			.saturating_add(RocksDbWeight::get().reads((5_u64).saturating_mul(r as u64)))
+			.saturating_add(RocksDbWeight::get().writes(2012_u64))
+			.saturating_add(RocksDbWeight::get().writes((5_u64).saturating_mul(r as u64)))
+	}
+	// Storage: DdcPayouts ValidatorFeesPots (r:1 w:1)
+	// Storage: DdcPayouts ValidatorScoresSnapshotCursors (r:1 w:1)
+	// Storage: VoterList ListNodes (r:1001 w:0)
+	// Storage: DdcPayouts ValidatorFeesScores (r:1000 w:1000)
+	// Storage: Staking Bonded (r:1000 w:0)
+	// Storage: Staking Ledger (r:1000 w:0)
+	/// The range of component `s` is `[1, 1000]`.
+	fn snapshot_validator_scores(s: u32, ) -> Weight {
+		Weight::from_parts(51_904_000_u64, 0)
+			.saturating_add(Weight::from_parts(38_716_402_u64, 0).saturating_mul(s as u64))
+			.saturating_add(RocksDbWeight::get().reads(3_u64))
+			.saturating_add(RocksDbWeight::get().reads((4_u64).saturating_mul(s as u64)))
+			.saturating_add(RocksDbWeight::get().writes(2_u64))
+			.saturating_add(RocksDbWeight::get().writes((1_u64).saturating_mul(s as u64)))
 	}
 }
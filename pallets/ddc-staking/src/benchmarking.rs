@@ -4,7 +4,7 @@ use ddc_primitives::{NodeParams, NodeType, StorageNodeMode, StorageNodeParams, S
 pub use frame_benchmarking::{
 	account, benchmarks, impl_benchmark_test_suite, whitelist_account, whitelisted_caller,
 };
-use frame_support::traits::Currency;
+use frame_support::traits::{Currency, ReservableCurrency};
 use frame_system::RawOrigin;
 use sp_runtime::traits::StaticLookup;
 use sp_std::prelude::*;
@@ -40,7 +40,7 @@ benchmarks! {
 	}: _(RawOrigin::Signed(stash.clone()), controller_lookup, node.clone(), amount)
 	verify {
 		assert!(Bonded::<T>::contains_key(stash));
-		assert!(Ledger::<T>::contains_key(controller));
+		assert!(Ledger::<T>::contains_key(controller, &node));
 		assert!(Nodes::<T>::contains_key(node));
 	}
 
@@ -48,42 +48,42 @@ benchmarks! {
 		// clean up any existing state.
 		clear_activated_nodes::<T>();
 
-		let (stash, controller, _) = create_stash_controller_node::<T>(0, 100)?;
-		let ledger = Ledger::<T>::get(&controller).ok_or("ledger not created before")?;
+		let (stash, controller, node) = create_stash_controller_node::<T>(0, 100)?;
+		let ledger = Ledger::<T>::get(&controller, &node).ok_or("ledger not created before")?;
 		let original_bonded: BalanceOf<T> = ledger.active;
 		let amount = T::Currency::minimum_balance() * 5u32.into(); // Half of total
 
 		whitelist_account!(controller);
-	}: _(RawOrigin::Signed(controller.clone()), amount)
+	}: _(RawOrigin::Signed(controller.clone()), node.clone(), amount)
 	verify {
-		let ledger = Ledger::<T>::get(&controller).ok_or("ledger not created after")?;
+		let ledger = Ledger::<T>::get(&controller, &node).ok_or("ledger not created after")?;
 		let new_bonded: BalanceOf<T> = ledger.active;
 		assert!(original_bonded > new_bonded);
 	}
 
 	withdraw_unbonded {
-		let (stash, controller, _) = create_stash_controller_node::<T>(0, 100)?;
+		let (stash, controller, node) = create_stash_controller_node::<T>(0, 100)?;
 		let amount = T::Currency::minimum_balance() * 5u32.into(); // Half of total
-		DdcStaking::<T>::unbond(RawOrigin::Signed(controller.clone()).into(), amount)?;
+		DdcStaking::<T>::unbond(RawOrigin::Signed(controller.clone()).into(), node.clone(), amount)?;
 		frame_system::Pallet::<T>::set_block_number(BlockNumberFor::<T>::from(1000u32));
-		let ledger = Ledger::<T>::get(&controller).ok_or("ledger not created before")?;
+		let ledger = Ledger::<T>::get(&controller, &node).ok_or("ledger not created before")?;
 		let original_total: BalanceOf<T> = ledger.total;
 		whitelist_account!(controller);
-	}: _(RawOrigin::Signed(controller.clone()))
+	}: _(RawOrigin::Signed(controller.clone()), node.clone())
 	verify {
-		let ledger = Ledger::<T>::get(&controller).ok_or("ledger not created after")?;
+		let ledger = Ledger::<T>::get(&controller, &node).ok_or("ledger not created after")?;
 		let new_total: BalanceOf<T> = ledger.total;
 		assert!(original_total > new_total);
 	}
 
 	store {
 		let node_pub_key = NodePubKey::StoragePubKey(StorageNodePubKey::new([0; 32]));
-		let (stash, controller, _) = create_stash_controller_node_with_balance::<T>(0, T::ClusterVisitor::get_bond_size(&ClusterId::from([1; 20]), NodeType::Storage).unwrap_or(100u128), node_pub_key)?;
+		let (_, controller, node) = create_stash_controller_node_with_balance::<T>(0, T::ClusterVisitor::get_bond_size(&ClusterId::from([1; 20]), NodeType::Storage).unwrap_or(100u128), node_pub_key)?;
 
 		whitelist_account!(controller);
-	}: _(RawOrigin::Signed(controller), ClusterId::from([1; 20]))
+	}: _(RawOrigin::Signed(controller), node.clone(), ClusterId::from([1; 20]))
 	verify {
-		assert!(Storages::<T>::contains_key(&stash));
+		assert!(Storages::<T>::contains_key(&node));
 	}
 
 
@@ -92,38 +92,187 @@ benchmarks! {
 		clear_activated_nodes::<T>();
 
 		let node_pub_key = NodePubKey::StoragePubKey(StorageNodePubKey::new([0; 32]));
-		let (storage_stash, storage_controller, _) = create_stash_controller_node_with_balance::<T>(0, T::ClusterVisitor::get_bond_size(&ClusterId::from([1; 20]), NodeType::Storage).unwrap_or(10u128), node_pub_key)?;
-		DdcStaking::<T>::store(RawOrigin::Signed(storage_controller.clone()).into(), ClusterId::from([1; 20]))?;
-		assert!(Storages::<T>::contains_key(&storage_stash));
+		let (_, storage_controller, storage_node) = create_stash_controller_node_with_balance::<T>(0, T::ClusterVisitor::get_bond_size(&ClusterId::from([1; 20]), NodeType::Storage).unwrap_or(10u128), node_pub_key)?;
+		DdcStaking::<T>::store(RawOrigin::Signed(storage_controller.clone()).into(), storage_node.clone(), ClusterId::from([1; 20]))?;
+		assert!(Storages::<T>::contains_key(&storage_node));
 		frame_system::Pallet::<T>::set_block_number(BlockNumberFor::<T>::from(1u32));
-		DdcStaking::<T>::chill(RawOrigin::Signed(storage_controller.clone()).into())?;
+		DdcStaking::<T>::chill(RawOrigin::Signed(storage_controller.clone()).into(), storage_node.clone())?;
 		frame_system::Pallet::<T>::set_block_number(BlockNumberFor::<T>::from(1u32) + T::ClusterVisitor::get_chill_delay(&ClusterId::from([1; 20]), NodeType::Storage).unwrap_or_else(|_| BlockNumberFor::<T>::from(10u32)));
 
 		whitelist_account!(storage_controller);
-	}: _(RawOrigin::Signed(storage_controller))
+	}: _(RawOrigin::Signed(storage_controller), storage_node.clone())
 	verify {
-		assert!(!Storages::<T>::contains_key(&storage_stash));
+		assert!(!Storages::<T>::contains_key(&storage_node));
 	}
 
 	set_controller {
-		let (stash, _, _) = create_stash_controller_node::<T>(USER_SEED, 100)?;
+		let (stash, _, node) = create_stash_controller_node::<T>(USER_SEED, 100)?;
 		let new_controller = create_funded_user::<T>("new_controller", USER_SEED, 100);
 		let new_controller_lookup = T::Lookup::unlookup(new_controller.clone());
 		whitelist_account!(stash);
 	}: _(RawOrigin::Signed(stash), new_controller_lookup)
 	verify {
-		assert!(Ledger::<T>::contains_key(&new_controller));
+		assert!(Ledger::<T>::contains_key(&new_controller, &node));
 	}
 
 	set_node {
-		let (stash, _, _) = create_stash_controller_node::<T>(USER_SEED, 100)?;
+		let (stash, _, node) = create_stash_controller_node::<T>(USER_SEED, 100)?;
 		let new_node = NodePubKey::StoragePubKey(StorageNodePubKey::new([1; 32]));
 		whitelist_account!(stash);
-	}: _(RawOrigin::Signed(stash), new_node.clone())
+	}: _(RawOrigin::Signed(stash), node, new_node.clone())
 	verify {
 		assert!(Nodes::<T>::contains_key(&new_node));
 	}
 
+	set_slash_fraction {
+		let fraction = Perbill::from_percent(10);
+	}: _(RawOrigin::Root, Offence::Downtime, fraction)
+	verify {
+		assert_eq!(SlashFractions::<T>::get(Offence::Downtime), fraction);
+	}
+
+	report_offence {
+		let (_, _, node) = create_stash_controller_node::<T>(USER_SEED, 100)?;
+		let validator = create_funded_user::<T>("validator", USER_SEED, 100);
+		T::ValidatorVisitor::setup_validators(vec![validator.clone()]);
+		SlashFractions::<T>::insert(Offence::DataLoss, Perbill::from_percent(10));
+		whitelist_account!(validator);
+	}: _(RawOrigin::Signed(validator), node.clone(), Offence::DataLoss)
+	verify {
+		assert!(UnappliedSlashes::<T>::contains_key(&node, Offence::DataLoss));
+	}
+
+	cancel_slash {
+		let (_, _, node) = create_stash_controller_node::<T>(USER_SEED, 100)?;
+		SlashFractions::<T>::insert(Offence::DataLoss, Perbill::from_percent(10));
+		DdcStaking::<T>::report_offence(RawOrigin::Root.into(), node.clone(), Offence::DataLoss)?;
+	}: _(RawOrigin::Root, node.clone(), Offence::DataLoss)
+	verify {
+		assert!(!UnappliedSlashes::<T>::contains_key(&node, Offence::DataLoss));
+	}
+
+	apply_slash {
+		let (stash, controller, node) = create_stash_controller_node::<T>(USER_SEED, 100)?;
+		let amount = T::Currency::minimum_balance() * 5u32.into(); // Half of total
+		DdcStaking::<T>::unbond(RawOrigin::Signed(controller.clone()).into(), node.clone(), amount)?;
+		// the delegated stake is slashed as well
+		for i in 0 .. MaxNodeDelegators::get() {
+			let delegator = create_funded_user::<T>("delegator", i, 100);
+			DdcStaking::<T>::delegate(RawOrigin::Signed(delegator).into(), node.clone(), amount)?;
+		}
+		SlashFractions::<T>::insert(Offence::DataLoss, Perbill::from_percent(10));
+		DdcStaking::<T>::report_offence(RawOrigin::Root.into(), node.clone(), Offence::DataLoss)?;
+		frame_system::Pallet::<T>::set_block_number(
+			frame_system::Pallet::<T>::block_number() + T::SlashDeferDuration::get()
+		);
+		let original_total = Ledger::<T>::get(&controller, &node).ok_or("ledger not created before")?.total;
+		let caller = create_funded_user::<T>("caller", USER_SEED, 100);
+		whitelist_account!(caller);
+	}: _(RawOrigin::Signed(caller), node.clone(), Offence::DataLoss)
+	verify {
+		assert!(!UnappliedSlashes::<T>::contains_key(&node, Offence::DataLoss));
+		let ledger = Ledger::<T>::get(&controller, &node).ok_or("ledger not created after")?;
+		assert!(original_total > ledger.total);
+		assert_eq!(ledger.stash, stash);
+		assert!(DelegatedStake::<T>::get(&node) < amount * MaxNodeDelegators::get().into());
+	}
+
+	delegate {
+		let (_, _, node) = create_stash_controller_node::<T>(USER_SEED, 100)?;
+		let delegator = create_funded_user::<T>("delegator", USER_SEED, 100);
+		let amount = T::Currency::minimum_balance() * 10u32.into();
+		whitelist_account!(delegator);
+	}: _(RawOrigin::Signed(delegator.clone()), node.clone(), amount)
+	verify {
+		assert!(Delegations::<T>::contains_key(&node, &delegator));
+		assert_eq!(DelegatedStake::<T>::get(&node), amount);
+	}
+
+	undelegate {
+		let (_, _, node) = create_stash_controller_node::<T>(USER_SEED, 100)?;
+		let delegator = create_funded_user::<T>("delegator", USER_SEED, 100);
+		let amount = T::Currency::minimum_balance() * 10u32.into();
+		DdcStaking::<T>::delegate(RawOrigin::Signed(delegator.clone()).into(), node.clone(), amount)?;
+		let half = T::Currency::minimum_balance() * 5u32.into(); // Half of delegated
+		whitelist_account!(delegator);
+	}: _(RawOrigin::Signed(delegator.clone()), node.clone(), half)
+	verify {
+		let delegation = Delegations::<T>::get(&node, &delegator).ok_or("delegation not created")?;
+		assert_eq!(delegation.active, amount - half);
+	}
+
+	withdraw_undelegated {
+		let (_, _, node) = create_stash_controller_node::<T>(USER_SEED, 100)?;
+		let delegator = create_funded_user::<T>("delegator", USER_SEED, 100);
+		let amount = T::Currency::minimum_balance() * 10u32.into();
+		DdcStaking::<T>::delegate(RawOrigin::Signed(delegator.clone()).into(), node.clone(), amount)?;
+		DdcStaking::<T>::undelegate(RawOrigin::Signed(delegator.clone()).into(), node.clone(), amount)?;
+		frame_system::Pallet::<T>::set_block_number(BlockNumberFor::<T>::from(1000u32));
+		whitelist_account!(delegator);
+	}: _(RawOrigin::Signed(delegator.clone()), node.clone())
+	verify {
+		assert!(!Delegations::<T>::contains_key(&node, &delegator));
+		assert!(T::Currency::reserved_balance(&delegator).is_zero());
+	}
+
+	set_commission {
+		let (_, controller, node) = create_stash_controller_node::<T>(USER_SEED, 100)?;
+		let commission = Perbill::from_percent(5);
+		whitelist_account!(controller);
+	}: _(RawOrigin::Signed(controller), node.clone(), commission)
+	verify {
+		assert_eq!(Commissions::<T>::get(&node), commission);
+	}
+
+	bond_node {
+		let (stash, controller, _) = create_stash_controller_node::<T>(USER_SEED, 100)?;
+		let node = NodePubKey::StoragePubKey(StorageNodePubKey::new([1; 32]));
+		let _ = T::NodeCreator::create_node(
+			node.clone(),
+			stash.clone(),
+			NodeParams::StorageParams(StorageNodeParams {
+				mode: StorageNodeMode::Storage,
+				host: vec![1u8; 255],
+				domain: vec![2u8; 256],
+				ssl: true,
+				http_port: 35000u16,
+				grpc_port: 25000u16,
+				p2p_port: 15000u16,
+			})
+		)?;
+		let amount = T::Currency::minimum_balance() * 10u32.into();
+		whitelist_account!(stash);
+	}: _(RawOrigin::Signed(stash.clone()), node.clone(), amount)
+	verify {
+		assert!(Ledger::<T>::contains_key(&controller, &node));
+		assert_eq!(Providers::<T>::get(&stash).len(), 2);
+	}
+
+	rebond {
+		let (_, controller, node) = create_stash_controller_node::<T>(USER_SEED, 100)?;
+		let amount = T::Currency::minimum_balance() * 5u32.into(); // Half of total
+		DdcStaking::<T>::unbond(RawOrigin::Signed(controller.clone()).into(), node.clone(), amount)?;
+		let original_bonded = Ledger::<T>::get(&controller, &node).ok_or("ledger not created before")?.active;
+		whitelist_account!(controller);
+	}: _(RawOrigin::Signed(controller.clone()), node.clone(), amount)
+	verify {
+		let ledger = Ledger::<T>::get(&controller, &node).ok_or("ledger not created after")?;
+		assert_eq!(ledger.active, original_bonded + amount);
+		assert!(ledger.unlocking.is_empty());
+	}
+
+	bond_extra {
+		let (stash, controller, node) = create_stash_controller_node::<T>(USER_SEED, 100)?;
+		let amount = T::Currency::minimum_balance() * 10u32.into();
+		UnderBondedNodes::<T>::insert(&node, frame_system::Pallet::<T>::block_number());
+		let original_bonded = Ledger::<T>::get(&controller, &node).ok_or("ledger not created before")?.active;
+		whitelist_account!(stash);
+	}: _(RawOrigin::Signed(stash.clone()), node.clone(), amount)
+	verify {
+		let ledger = Ledger::<T>::get(&controller, &node).ok_or("ledger not created after")?;
+		assert_eq!(ledger.active, original_bonded + amount);
+	}
+
 	impl_benchmark_test_suite!(
 		DdcStaking,
 		crate::mock::ExtBuilder::default().build(),
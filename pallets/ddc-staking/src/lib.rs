@@ -6,6 +6,32 @@
 //! - [`Call`]
 //! - [`Pallet`]
 //!
+//! ## Nodes
+//!
+//! A provider stash bonds several DDC nodes, further nodes are bonded with `bond_node`, and
+//! the bond, cluster membership, chilling and unbonding are tracked per node. `rebond` moves
+//! the newest unlocking chunks of a node back to its active bond. Nodes left below a raised
+//! cluster bond size are marked as under-bonded and chilled after `UnderBondedGracePeriod`
+//! unless their stake is topped up. Their stakes are checked on idle page by page through the
+//! `ClusterStorages` index.
+//!
+//! ## Delegation
+//!
+//! Token holders delegate stake to a DDC node with `delegate`, `undelegate` and
+//! `withdraw_undelegated`. The delegated stake is reserved, counts towards the cluster bond
+//! size, is slashed pro-rata with the node stake and is unlocked after the cluster unbonding
+//! delay. Providers set the commission they take from the delegators rewards with
+//! `set_commission`, a raised commission takes effect after the cluster unbonding delay and
+//! is applied on idle.
+//!
+//! ## Slashing
+//!
+//! Governance or a majority of the DAC validators report offences with `report_offence`. The
+//! governed `SlashFractions` of the active bond and the unlocking chunks are slashed into the
+//! cluster reserve or the treasury after `SlashDeferDuration`, unless governance cancels the
+//! slash with `cancel_slash`. A due slash failing on idle is left for `apply_slash`, and stakes
+//! with pending slashes can not be withdrawn.
+//!
 //! ## GenesisConfig
 //!
 //! The DDC Staking pallet depends on the [`GenesisConfig`]. The
@@ -23,21 +49,27 @@ pub(crate) mod mock;
 #[cfg(test)]
 mod tests;
 
+pub mod migration;
 pub mod weights;
 use core::fmt::Debug;
 
 use codec::{Decode, Encode, HasCompact};
 use ddc_primitives::traits::{
-	cluster::{ClusterCreator, ClusterVisitor, ClusterVisitorError},
+	cluster::{ClusterCreator, ClusterVisitor, ClusterVisitorError, OnClusterBondingParamsChanged},
 	node::{NodeCreator, NodeVisitor},
-	staking::{StakerCreator, StakingVisitor, StakingVisitorError},
+	pallet::PalletVisitor,
+	staking::{DelegationVisitor, StakerCreator, StakingVisitor, StakingVisitorError},
+	validator::ValidatorVisitor,
 };
 pub use ddc_primitives::{ClusterId, NodePubKey, NodeType};
 use frame_support::{
 	assert_ok,
 	pallet_prelude::*,
 	parameter_types,
-	traits::{Currency, DefensiveSaturating, LockIdentifier, LockableCurrency, WithdrawReasons},
+	traits::{
+		Currency, DefensiveSaturating, Imbalance, LockIdentifier, LockableCurrency,
+		ReservableCurrency, WithdrawReasons,
+	},
 	BoundedVec,
 };
 use frame_system::pallet_prelude::*;
@@ -45,7 +77,7 @@ pub use pallet::*;
 use scale_info::TypeInfo;
 use sp_runtime::{
 	traits::{AtLeast32BitUnsigned, CheckedAdd, CheckedSub, Saturating, StaticLookup, Zero},
-	RuntimeDebug, SaturatedConversion,
+	Perbill, RuntimeDebug, SaturatedConversion,
 };
 use sp_std::prelude::*;
 
@@ -60,6 +92,12 @@ pub type BalanceOf<T> =
 parameter_types! {
 	/// A limit to the number of pending unlocks an account may have in parallel.
 	pub MaxUnlockingChunks: u32 = 32;
+	/// A limit to the number of delegators a DDC node may have.
+	pub MaxNodeDelegators: u32 = 64;
+	/// A limit to the number of DDC nodes a stash account may bond.
+	pub MaxNodesPerStash: u32 = 32;
+	/// A limit to the number of DAC validators votes for an offence of a DDC node.
+	pub MaxOffenceVotes: u32 = 256;
 }
 
 /// Just a Balance/BlockNumber tuple to encode when a chunk of funds will be unlocked.
@@ -143,17 +181,90 @@ impl<
 
 		Self { stash: self.stash, total, active: self.active, chilling: self.chilling, unlocking }
 	}
+
+	/// Re-bond funds that were scheduled for unlocking, taking the newest chunks first.
+	///
+	/// Returns the updated ledger, and the amount actually rebonded.
+	fn rebond(mut self, value: Balance) -> (Self, Balance) {
+		let mut unlocking_balance = Balance::zero();
+
+		while let Some(last) = self.unlocking.last_mut() {
+			if unlocking_balance.saturating_add(last.value) <= value {
+				unlocking_balance = unlocking_balance.saturating_add(last.value);
+				self.active = self.active.saturating_add(last.value);
+				self.unlocking.pop();
+			} else {
+				let diff = value.saturating_sub(unlocking_balance);
+
+				unlocking_balance = unlocking_balance.saturating_add(diff);
+				self.active = self.active.saturating_add(diff);
+				last.value = last.value.saturating_sub(diff);
+			}
+
+			if unlocking_balance >= value {
+				break
+			}
+		}
+
+		(self, unlocking_balance)
+	}
+
+	/// Slash `fraction` of the active funds and of every unlocking chunk, returning the slashed
+	/// amount. Active funds that would be left below `minimum_balance` are slashed entirely.
+	fn slash(&mut self, fraction: Perbill, minimum_balance: Balance) -> Balance {
+		let mut slashed = fraction * self.active;
+		if self.active.saturating_sub(slashed) < minimum_balance {
+			slashed = self.active;
+		}
+		self.active = self.active.saturating_sub(slashed);
+
+		for chunk in self.unlocking.iter_mut() {
+			let chunk_slash = fraction * chunk.value;
+			chunk.value = chunk.value.saturating_sub(chunk_slash);
+			slashed = slashed.saturating_add(chunk_slash);
+		}
+		self.unlocking.retain(|chunk| !chunk.value.is_zero());
+
+		self.total = self.total.saturating_sub(slashed);
+		slashed
+	}
+}
+
+/// Misbehaviour of a DDC node that its stake is slashed for.
+#[derive(PartialEq, Eq, Clone, Copy, Encode, Decode, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub enum Offence {
+	/// The node was not serving its cluster.
+	Downtime,
+	/// The node lost data it was storing.
+	DataLoss,
+	/// The node reported usage that did not happen.
+	FraudulentUsage,
+}
+
+/// A reported slash waiting for its deferral period to end.
+#[derive(PartialEq, Eq, Clone, Encode, Decode, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub struct UnappliedSlash<AccountId, BlockNumber> {
+	/// Fraction of the stake to be slashed.
+	pub fraction: Perbill,
+	/// The DAC validator whose vote completed the quorum of the report, `None` if reported by
+	/// governance.
+	pub reporter: Option<AccountId>,
+	/// Block number from which the slash can be applied.
+	pub apply_at: BlockNumber,
 }
 
 #[frame_support::pallet]
 pub mod pallet {
-	use ddc_primitives::traits::{cluster::ClusterManager, node::NodeVisitorError};
+	use ddc_primitives::{
+		traits::{cluster::ClusterManager, node::NodeVisitorError},
+		ClusterBondingParams,
+	};
 
 	use super::*;
 
 	/// The current storage version.
 	const STORAGE_VERSION: frame_support::traits::StorageVersion =
-		frame_support::traits::StorageVersion::new(0);
+		frame_support::traits::StorageVersion::new(1);
 
 	#[pallet::pallet]
 	#[pallet::storage_version(STORAGE_VERSION)]
@@ -162,7 +273,8 @@ pub mod pallet {
 
 	#[pallet::config]
 	pub trait Config: frame_system::Config {
-		type Currency: LockableCurrency<Self::AccountId, Moment = BlockNumberFor<Self>>;
+		type Currency: LockableCurrency<Self::AccountId, Moment = BlockNumberFor<Self>>
+			+ ReservableCurrency<Self::AccountId>;
 
 		type RuntimeEvent: From<Event<Self>> + IsType<<Self as frame_system::Config>::RuntimeEvent>;
 
@@ -178,6 +290,21 @@ pub mod pallet {
 		type NodeVisitor: NodeVisitor<Self>;
 
 		type NodeCreator: NodeCreator<Self>;
+
+		/// Receives slashes of nodes that are not assigned to a cluster with a reserve account.
+		type TreasuryVisitor: PalletVisitor<Self>;
+
+		/// DAC validators allowed to report offences of DDC nodes.
+		type ValidatorVisitor: ValidatorVisitor<Self>;
+
+		/// Number of blocks a reported slash is deferred for, so governance can cancel it.
+		#[pallet::constant]
+		type SlashDeferDuration: Get<BlockNumberFor<Self>>;
+
+		/// Number of blocks a node left under-bonded by a raised cluster bond size has to top up
+		/// its stake before it is chilled.
+		#[pallet::constant]
+		type UnderBondedGracePeriod: Get<BlockNumberFor<Self>>;
 	}
 
 	/// Map from all locked "stash" accounts to the controller account.
@@ -185,32 +312,162 @@ pub mod pallet {
 	#[pallet::getter(fn bonded)]
 	pub type Bonded<T: Config> = StorageMap<_, Twox64Concat, T::AccountId, T::AccountId>;
 
-	/// Map from all (unlocked) "controller" accounts to the info regarding the staking.
+	/// Map from all (unlocked) "controller" accounts and DDC nodes of their stash to the info
+	/// regarding the staking of the node.
 	#[pallet::storage]
 	#[pallet::getter(fn ledger)]
-	pub type Ledger<T: Config> =
-		StorageMap<_, Blake2_128Concat, T::AccountId, StakingLedger<T::AccountId, BalanceOf<T>, T>>;
-
-	/// The map of (wannabe) Storage nodes participants stash keys to the DDC cluster ID they
-	/// wish to participate into.
+	pub type Ledger<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		T::AccountId,
+		Twox64Concat,
+		NodePubKey,
+		StakingLedger<T::AccountId, BalanceOf<T>, T>,
+	>;
+
+	/// The map of (wannabe) Storage nodes to the DDC cluster ID they wish to participate into.
 	#[pallet::storage]
 	#[pallet::getter(fn storages)]
-	pub type Storages<T: Config> = StorageMap<_, Twox64Concat, T::AccountId, ClusterId>;
+	pub type Storages<T: Config> = StorageMap<_, Twox64Concat, NodePubKey, ClusterId>;
+
+	/// Map from DDC cluster ID to the Storage nodes participating into it, the index of
+	/// `Storages` by cluster.
+	#[pallet::storage]
+	pub type ClusterStorages<T: Config> =
+		StorageDoubleMap<_, Blake2_128Concat, ClusterId, Twox64Concat, NodePubKey, ()>;
 
 	/// Map from DDC node ID to the node operator stash account.
 	#[pallet::storage]
 	#[pallet::getter(fn nodes)]
 	pub type Nodes<T: Config> = StorageMap<_, Twox64Concat, NodePubKey, T::AccountId>;
 
-	/// Map from operator stash account to DDC node ID.
+	/// Map from operator stash account to the DDC node IDs it has bonded.
 	#[pallet::storage]
 	#[pallet::getter(fn providers)]
-	pub type Providers<T: Config> = StorageMap<_, Twox64Concat, T::AccountId, NodePubKey>;
-
-	/// Map of Storage node provider stash accounts that aim to leave a cluster
+	pub type Providers<T: Config> = StorageMap<
+		_,
+		Twox64Concat,
+		T::AccountId,
+		BoundedVec<NodePubKey, MaxNodesPerStash>,
+		ValueQuery,
+	>;
+
+	/// Map of Storage nodes that aim to leave a cluster
 	#[pallet::storage]
 	#[pallet::getter(fn leaving_storages)]
-	pub type LeavingStorages<T: Config> = StorageMap<_, Twox64Concat, T::AccountId, ClusterId>;
+	pub type LeavingStorages<T: Config> = StorageMap<_, Twox64Concat, NodePubKey, ClusterId>;
+
+	/// Map from offence to the fraction of the stake slashed for it.
+	#[pallet::storage]
+	#[pallet::getter(fn slash_fraction)]
+	pub type SlashFractions<T: Config> = StorageMap<_, Twox64Concat, Offence, Perbill, ValueQuery>;
+
+	/// Map of DDC nodes and their reported offences to the slashes not applied yet.
+	#[pallet::storage]
+	#[pallet::getter(fn unapplied_slash)]
+	pub type UnappliedSlashes<T: Config> = StorageDoubleMap<
+		_,
+		Twox64Concat,
+		NodePubKey,
+		Twox64Concat,
+		Offence,
+		UnappliedSlash<T::AccountId, BlockNumberFor<T>>,
+	>;
+
+	/// Votes of DAC validators for offences of DDC nodes that are not reported yet.
+	#[pallet::storage]
+	#[pallet::getter(fn offence_votes)]
+	pub type OffenceVotes<T: Config> = StorageDoubleMap<
+		_,
+		Twox64Concat,
+		NodePubKey,
+		Twox64Concat,
+		Offence,
+		BoundedVec<T::AccountId, MaxOffenceVotes>,
+		ValueQuery,
+	>;
+
+	/// Queue of reported slashes with the block they can be applied from, in block order.
+	#[pallet::storage]
+	#[pallet::getter(fn slash_queue)]
+	pub type SlashQueue<T: Config> =
+		StorageMap<_, Twox64Concat, u64, (NodePubKey, Offence, BlockNumberFor<T>)>;
+
+	/// Head and tail indices of the slash queue.
+	#[pallet::storage]
+	pub type SlashQueueRange<T: Config> = StorageValue<_, (u64, u64), ValueQuery>;
+
+	/// Map from DDC node ID and delegator account to the stake delegated to the node.
+	#[pallet::storage]
+	#[pallet::getter(fn delegation)]
+	pub type Delegations<T: Config> = StorageDoubleMap<
+		_,
+		Twox64Concat,
+		NodePubKey,
+		Blake2_128Concat,
+		T::AccountId,
+		StakingLedger<T::AccountId, BalanceOf<T>, T>,
+	>;
+
+	/// Map from DDC node ID to the number of its delegators.
+	#[pallet::storage]
+	#[pallet::getter(fn node_delegators_count)]
+	pub type NodeDelegatorsCount<T: Config> =
+		StorageMap<_, Twox64Concat, NodePubKey, u32, ValueQuery>;
+
+	/// Map from DDC node ID to the stake actively delegated to it.
+	#[pallet::storage]
+	#[pallet::getter(fn delegated_stake)]
+	pub type DelegatedStake<T: Config> =
+		StorageMap<_, Twox64Concat, NodePubKey, BalanceOf<T>, ValueQuery>;
+
+	/// Map from DDC node ID to the commission its provider takes from the delegators rewards.
+	#[pallet::storage]
+	#[pallet::getter(fn commission)]
+	pub type Commissions<T: Config> = StorageMap<_, Twox64Concat, NodePubKey, Perbill, ValueQuery>;
+
+	/// Map from DDC node ID to the raised commission of its provider and the block it takes
+	/// effect from.
+	#[pallet::storage]
+	#[pallet::getter(fn pending_commission)]
+	pub type PendingCommissions<T: Config> =
+		StorageMap<_, Twox64Concat, NodePubKey, (Perbill, BlockNumberFor<T>)>;
+
+	/// Queue of nodes with a raised commission and the block it takes effect from, in the order
+	/// it was raised.
+	#[pallet::storage]
+	#[pallet::getter(fn commission_queue)]
+	pub type CommissionQueue<T: Config> =
+		StorageMap<_, Twox64Concat, u64, (NodePubKey, BlockNumberFor<T>)>;
+
+	/// Head and tail indices of the commission queue.
+	#[pallet::storage]
+	pub type CommissionQueueRange<T: Config> = StorageValue<_, (u64, u64), ValueQuery>;
+
+	/// Map from under-bonded Storage nodes to the block they are chilled at unless their stake is
+	/// topped up to the bond size of their cluster.
+	#[pallet::storage]
+	#[pallet::getter(fn under_bonded)]
+	pub type UnderBondedNodes<T: Config> =
+		StorageMap<_, Twox64Concat, NodePubKey, BlockNumberFor<T>>;
+
+	/// Queue of under-bonded Storage nodes with the block they are chilled at, in block order.
+	#[pallet::storage]
+	#[pallet::getter(fn under_bonded_queue)]
+	pub type UnderBondedQueue<T: Config> =
+		StorageMap<_, Twox64Concat, u64, (NodePubKey, BlockNumberFor<T>)>;
+
+	/// Head and tail indices of the under-bonded queue.
+	#[pallet::storage]
+	pub type UnderBondedQueueRange<T: Config> = StorageValue<_, (u64, u64), ValueQuery>;
+
+	/// Map from DDC cluster ID to the pending check of its Storage nodes stakes against the
+	/// changed bond size, with the block under-bonded nodes are chilled at and the raw key of the
+	/// last node checked.
+	#[pallet::storage]
+	#[pallet::getter(fn pending_bond_check)]
+	pub type PendingBondChecks<T: Config> =
+		StorageMap<_, Blake2_128Concat, ClusterId, (BlockNumberFor<T>, Option<Vec<u8>>)>;
 
 	#[pallet::genesis_config]
 	pub struct GenesisConfig<T: Config> {
@@ -233,14 +490,23 @@ pub mod pallet {
 					T::Currency::free_balance(stash) >= balance,
 					"Stash do not have enough balance to participate in storage network."
 				);
-				assert_ok!(Pallet::<T>::bond(
-					T::RuntimeOrigin::from(Some(stash.clone()).into()),
-					T::Lookup::unlookup(controller.clone()),
-					node.clone(),
-					balance,
-				));
+				if <Bonded<T>>::contains_key(stash) {
+					assert_ok!(Pallet::<T>::bond_node(
+						T::RuntimeOrigin::from(Some(stash.clone()).into()),
+						node.clone(),
+						balance,
+					));
+				} else {
+					assert_ok!(Pallet::<T>::bond(
+						T::RuntimeOrigin::from(Some(stash.clone()).into()),
+						T::Lookup::unlookup(controller.clone()),
+						node.clone(),
+						balance,
+					));
+				}
 				assert_ok!(Pallet::<T>::store(
 					T::RuntimeOrigin::from(Some(controller.clone()).into()),
+					node.clone(),
 					cluster,
 				));
 			}
@@ -250,31 +516,76 @@ pub mod pallet {
 	#[pallet::event]
 	#[pallet::generate_deposit(pub(crate) fn deposit_event)]
 	pub enum Event<T: Config> {
-		/// An account has bonded this amount. \[stash, amount\]
+		/// An account has bonded this amount for its DDC node. \[stash, node, amount\]
 		///
 		/// NOTE: This event is only emitted when funds are bonded via a dispatchable. Notably,
 		/// it will not be emitted for staking rewards when they are added to stake.
-		Bonded(T::AccountId, BalanceOf<T>),
-		/// An account has unbonded this amount. \[stash, amount\]
-		Unbonded(T::AccountId, BalanceOf<T>),
+		Bonded(T::AccountId, NodePubKey, BalanceOf<T>),
+		/// An account has unbonded this amount of its DDC node stake. \[stash, node, amount\]
+		Unbonded(T::AccountId, NodePubKey, BalanceOf<T>),
 		/// An account has called `withdraw_unbonded` and removed unbonding chunks worth `Balance`
-		/// from the unlocking queue. \[stash, amount\]
-		Withdrawn(T::AccountId, BalanceOf<T>),
-		/// An account has stopped participating as DDC network participant.
-		/// \[stash\]
-		Chilled(T::AccountId),
-		/// An account has declared desire to stop participating in DDC network soon.
-		/// \[stash, cluster, block\]
-		ChillSoon(T::AccountId, ClusterId, BlockNumberFor<T>),
-		/// An account that started participating as DDC network participant.
-		/// \[stash\]
-		Activated(T::AccountId),
-		/// An account that started unbonding tokens below the minimum value set for the cluster
-		/// his DDC node is assigned to \[stash\]
-		LeaveSoon(T::AccountId),
-		/// An account that unbonded tokens below the minimum value set for the cluster his
-		/// DDC node was assigned to \[stash\]
-		Left(T::AccountId),
+		/// from the unlocking queue of its DDC node. \[stash, node, amount\]
+		Withdrawn(T::AccountId, NodePubKey, BalanceOf<T>),
+		/// A DDC node of an account has stopped participating as DDC network participant.
+		/// \[stash, node\]
+		Chilled(T::AccountId, NodePubKey),
+		/// An account has declared desire of its DDC node to stop participating in DDC network
+		/// soon. \[stash, node, cluster, block\]
+		ChillSoon(T::AccountId, NodePubKey, ClusterId, BlockNumberFor<T>),
+		/// A DDC node of an account started participating as DDC network participant.
+		/// \[stash, node\]
+		Activated(T::AccountId, NodePubKey),
+		/// An account that started unbonding tokens of its DDC node below the minimum value set
+		/// for the cluster the node is assigned to \[stash, node\]
+		LeaveSoon(T::AccountId, NodePubKey),
+		/// An account that unbonded tokens of its DDC node below the minimum value set for the
+		/// cluster the node was assigned to \[stash, node\]
+		Left(T::AccountId, NodePubKey),
+		/// An account has rebonded this amount of its DDC node stake scheduled for unlocking.
+		/// \[stash, node, amount\]
+		Rebonded(T::AccountId, NodePubKey, BalanceOf<T>),
+		/// An account has rebonded enough tokens of its DDC node to stay in the cluster the node
+		/// was leaving. \[stash, node, cluster\]
+		LeaveCancelled(T::AccountId, NodePubKey, ClusterId),
+		/// Governance has set the fraction of the stake slashed for an offence.
+		/// \[offence, fraction\]
+		SlashFractionSet(Offence, Perbill),
+		/// An offence of a DDC node has been reported, its slash can be applied from the block.
+		/// \[node, offence, block\]
+		SlashReported(NodePubKey, Offence, BlockNumberFor<T>),
+		/// A DAC validator has voted to report an offence of a DDC node, which is reported once
+		/// a majority of the active DAC validators votes for it. \[validator, node, offence\]
+		OffenceVoted(T::AccountId, NodePubKey, Offence),
+		/// Governance has cancelled the slash of a DDC node. \[node, offence\]
+		SlashCancelled(NodePubKey, Offence),
+		/// A due slash of a DDC node could not be applied when blocks had spare weight, it is left
+		/// for `apply_slash` or `cancel_slash`. \[node, offence, error\]
+		SlashFailed(NodePubKey, Offence, DispatchError),
+		/// An account has been slashed for the offence of its DDC node and the amount moved to the
+		/// beneficiary. \[stash, offence, amount, beneficiary\]
+		Slashed(T::AccountId, Offence, BalanceOf<T>, T::AccountId),
+		/// An account has delegated this amount to a DDC node. \[delegator, node, amount\]
+		Delegated(T::AccountId, NodePubKey, BalanceOf<T>),
+		/// An account has undelegated this amount from a DDC node. \[delegator, node, amount\]
+		Undelegated(T::AccountId, NodePubKey, BalanceOf<T>),
+		/// An account has called `withdraw_undelegated` and removed unlocking chunks worth
+		/// `Balance` of its delegation to a DDC node. \[delegator, node, amount\]
+		DelegationWithdrawn(T::AccountId, NodePubKey, BalanceOf<T>),
+		/// A node provider has set the commission it takes from the rewards of its delegators.
+		/// \[node, commission\]
+		CommissionSet(NodePubKey, Perbill),
+		/// A node provider has raised its commission, which takes effect from the block so the
+		/// delegators can undelegate before. \[node, commission, block\]
+		CommissionScheduled(NodePubKey, Perbill, BlockNumberFor<T>),
+		/// The stake an account has delegated to a DDC node has been slashed for the offence of
+		/// the node. \[delegator, node, offence, amount\]
+		DelegationSlashed(T::AccountId, NodePubKey, Offence, BalanceOf<T>),
+		/// The stake of a DDC node is below the raised bond size of its cluster, the node is
+		/// chilled at the block unless the stake is topped up. \[stash, node, cluster, block\]
+		UnderBonded(T::AccountId, NodePubKey, ClusterId, BlockNumberFor<T>),
+		/// The stake of an under-bonded DDC node is back to the bond size of its cluster.
+		/// \[stash, node\]
+		BondToppedUp(T::AccountId, NodePubKey),
 	}
 
 	#[pallet::error]
@@ -321,6 +632,165 @@ pub mod pallet {
 		/// Action is prohibited for a node provider stash account that is in the process of
 		/// leaving a cluster
 		NodeIsLeaving,
+		/// Origin of the call is neither governance nor an active DAC validator.
+		NotReporter,
+		/// No slash fraction is set for the offence.
+		NoSlashFraction,
+		/// The offence of the node has already been reported and its slash is pending.
+		SlashAlreadyReported,
+		/// No pending slash found for the node and offence.
+		NoUnappliedSlash,
+		/// Action is prohibited while a slash of the node is pending.
+		SlashPending,
+		/// The DAC validator has already voted for the offence.
+		AlreadyVoted,
+		/// The offence has reached the limit of votes (see `MaxOffenceVotes`).
+		TooManyVotes,
+		/// No stake delegated by the account to the node.
+		NoDelegation,
+		/// The node has reached the limit of delegators (see `MaxNodeDelegators`).
+		TooManyDelegators,
+		/// Action is prohibited while the node has delegators.
+		NodeHasDelegators,
+		/// The stash has reached the limit of bonded nodes (see `MaxNodesPerStash`).
+		TooManyNodes,
+		/// No funds of the node are scheduled for unlocking.
+		NoUnlockChunk,
+	}
+
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		fn on_idle(now: BlockNumberFor<T>, remaining_weight: Weight) -> Weight {
+			let read_weight = T::DbWeight::get().reads(1);
+			let mut consumed_weight = read_weight;
+			if remaining_weight.any_lt(consumed_weight) {
+				return Weight::zero()
+			}
+
+			let (mut head, tail) = <SlashQueueRange<T>>::get();
+			let start = head;
+			let apply_weight = T::WeightInfo::apply_slash();
+			while head < tail {
+				// reserve a write for the updated queue range
+				let required_weight = consumed_weight
+					.saturating_add(apply_weight)
+					.saturating_add(T::DbWeight::get().writes(1));
+				if remaining_weight.any_lt(required_weight) {
+					break
+				}
+
+				if let Some((node_pub_key, offence, apply_at)) = <SlashQueue<T>>::get(head) {
+					if apply_at > now {
+						break
+					}
+					<SlashQueue<T>>::remove(head);
+					// a slash cancelled and reported again is applied from its own queue entry
+					let is_due = Self::unapplied_slash(&node_pub_key, offence)
+						.is_some_and(|slash| slash.apply_at == apply_at);
+					if is_due {
+						if let Err(error) = frame_support::storage::with_storage_layer(|| {
+							Self::do_apply_slash(&node_pub_key, offence)
+						}) {
+							Self::deposit_event(Event::<T>::SlashFailed(
+								node_pub_key,
+								offence,
+								error,
+							));
+						}
+					}
+				}
+				consumed_weight = consumed_weight.saturating_add(apply_weight);
+				head += 1;
+			}
+
+			if head != start {
+				<SlashQueueRange<T>>::put((head, tail));
+				consumed_weight = consumed_weight.saturating_add(T::DbWeight::get().writes(1));
+			}
+
+			consumed_weight.saturating_accrue(read_weight);
+			if remaining_weight.any_lt(consumed_weight) {
+				return consumed_weight
+			}
+
+			let (mut head, tail) = <UnderBondedQueueRange<T>>::get();
+			let start = head;
+			let chill_weight = read_weight.saturating_add(T::WeightInfo::chill());
+			while head < tail {
+				// reserve a write for the updated queue range
+				let required_weight = consumed_weight
+					.saturating_add(chill_weight)
+					.saturating_add(T::DbWeight::get().writes(1));
+				if remaining_weight.any_lt(required_weight) {
+					break
+				}
+
+				if let Some((node_pub_key, chill_at)) = <UnderBondedQueue<T>>::get(head) {
+					if chill_at > now {
+						break
+					}
+					<UnderBondedQueue<T>>::remove(head);
+					// a node topped up and under-bonded again is chilled from its own queue entry
+					if Self::under_bonded(&node_pub_key) == Some(chill_at) {
+						Self::chill_under_bonded(&node_pub_key);
+					}
+				}
+				consumed_weight = consumed_weight.saturating_add(chill_weight);
+				head += 1;
+			}
+
+			if head != start {
+				<UnderBondedQueueRange<T>>::put((head, tail));
+				consumed_weight = consumed_weight.saturating_add(T::DbWeight::get().writes(1));
+			}
+
+			consumed_weight.saturating_accrue(read_weight);
+			if remaining_weight.any_lt(consumed_weight) {
+				return consumed_weight
+			}
+
+			let (mut head, tail) = <CommissionQueueRange<T>>::get();
+			let start = head;
+			// the queue entry and the pending commission are read, both removed and the
+			// commission written
+			let commission_weight = T::DbWeight::get().reads_writes(2, 3);
+			while head < tail {
+				// reserve a write for the updated queue range
+				let required_weight = consumed_weight
+					.saturating_add(commission_weight)
+					.saturating_add(T::DbWeight::get().writes(1));
+				if remaining_weight.any_lt(required_weight) {
+					break
+				}
+
+				if let Some((node_pub_key, apply_at)) = <CommissionQueue<T>>::get(head) {
+					if apply_at > now {
+						break
+					}
+					<CommissionQueue<T>>::remove(head);
+					// a commission replaced before it takes effect is applied from its own entry
+					if let Some((commission, block)) = Self::pending_commission(&node_pub_key) {
+						if block == apply_at {
+							<PendingCommissions<T>>::remove(&node_pub_key);
+							<Commissions<T>>::insert(&node_pub_key, commission);
+							Self::deposit_event(Event::<T>::CommissionSet(
+								node_pub_key,
+								commission,
+							));
+						}
+					}
+				}
+				consumed_weight = consumed_weight.saturating_add(commission_weight);
+				head += 1;
+			}
+
+			if head != start {
+				<CommissionQueueRange<T>>::put((head, tail));
+				consumed_weight = consumed_weight.saturating_add(T::DbWeight::get().writes(1));
+			}
+
+			Self::check_pending_bonds(remaining_weight, consumed_weight)
+		}
 	}
 
 	#[pallet::call]
@@ -330,6 +800,8 @@ pub mod pallet {
 		///
 		/// `value` must be more than the `minimum_balance` specified by `T::Currency`.
 		///
+		/// Further DDC nodes of the stash are bonded with [`Call::bond_node`].
+		///
 		/// The dispatch origin for this call must be _Signed_ by the stash account.
 		///
 		/// Emits `Bonded`.
@@ -349,7 +821,7 @@ pub mod pallet {
 
 			let controller = T::Lookup::lookup(controller)?;
 
-			if <Ledger<T>>::contains_key(&controller) {
+			if Self::is_controller(&controller) {
 				Err(Error::<T>::AlreadyPaired)?
 			}
 
@@ -368,25 +840,13 @@ pub mod pallet {
 
 			frame_system::Pallet::<T>::inc_consumers(&stash).map_err(|_| Error::<T>::BadState)?;
 
-			Nodes::<T>::insert(&node, &stash);
-			Providers::<T>::insert(&stash, &node);
-
 			// You're auto-bonded forever, here. We might improve this by only bonding when
 			// you actually store/serve and remove once you unbond __everything__.
 			<Bonded<T>>::insert(&stash, &controller);
 
 			let stash_balance = T::Currency::free_balance(&stash);
 			let value = value.min(stash_balance);
-			Self::deposit_event(Event::<T>::Bonded(stash.clone(), value));
-			let item = StakingLedger {
-				stash,
-				total: value,
-				active: value,
-				chilling: Default::default(),
-				unlocking: Default::default(),
-			};
-			Self::update_ledger(&controller, &item);
-			Ok(())
+			Self::do_bond_node(stash, &controller, node, value)
 		}
 
 		/// Schedule a portion of the stash to be unlocked ready for transfer out after the bond
@@ -412,10 +872,12 @@ pub mod pallet {
 		#[pallet::weight(T::WeightInfo::unbond())]
 		pub fn unbond(
 			origin: OriginFor<T>,
+			node_pub_key: NodePubKey,
 			#[pallet::compact] value: BalanceOf<T>,
 		) -> DispatchResult {
 			let controller = ensure_signed(origin)?;
-			let mut ledger = Self::ledger(&controller).ok_or(Error::<T>::NotController)?;
+			let mut ledger =
+				Self::ledger(&controller, &node_pub_key).ok_or(Error::<T>::NotController)?;
 
 			ensure!(
 				ledger.unlocking.len() < MaxUnlockingChunks::get() as usize,
@@ -435,7 +897,9 @@ pub mod pallet {
 					ledger.active = Zero::zero();
 				}
 
-				let min_active_bond = if let Some(cluster_id) = Self::storages(&ledger.stash) {
+				let stake = Self::node_stake(&ledger, &node_pub_key);
+
+				let min_active_bond = if let Some(cluster_id) = Self::storages(&node_pub_key) {
 					let bond_size =
 						T::ClusterVisitor::get_bond_size(&cluster_id, NodeType::Storage)
 							.map_err(Into::<Error<T>>::into)?;
@@ -448,10 +912,7 @@ pub mod pallet {
 
 				// Make sure that the user maintains enough active bond for their role in the
 				// cluster. If a user runs into this error, they should chill first.
-				ensure!(ledger.active >= min_active_bond, Error::<T>::InsufficientBond);
-
-				let node_pub_key =
-					<Providers<T>>::get(&ledger.stash).ok_or(Error::<T>::BadState)?;
+				ensure!(stake >= min_active_bond, Error::<T>::InsufficientBond);
 
 				let unbonding_delay = if T::NodeVisitor::exists(&node_pub_key) {
 					let node_cluster_id = T::NodeVisitor::get_cluster_id(&node_pub_key)
@@ -467,13 +928,16 @@ pub mod pallet {
 
 						// If provider is trying to unbond after chilling and aims to leave the
 						// cluster eventually, we keep its stake till the end of unbonding period.
-						if ledger.active < min_bond_size.saturated_into::<BalanceOf<T>>() {
+						if stake < min_bond_size.saturated_into::<BalanceOf<T>>() {
 							match node_pub_key {
 								NodePubKey::StoragePubKey(_) =>
-									LeavingStorages::<T>::insert(ledger.stash.clone(), cluster_id),
+									LeavingStorages::<T>::insert(&node_pub_key, cluster_id),
 							};
 
-							Self::deposit_event(Event::<T>::LeaveSoon(ledger.stash.clone()));
+							Self::deposit_event(Event::<T>::LeaveSoon(
+								ledger.stash.clone(),
+								node_pub_key.clone(),
+							));
 						};
 
 						match node_pub_key {
@@ -504,9 +968,9 @@ pub mod pallet {
 						.map_err(|_| Error::<T>::NoMoreChunks)?;
 				};
 
-				Self::update_ledger(&controller, &ledger);
+				Self::update_ledger(&controller, &node_pub_key, &ledger);
 
-				Self::deposit_event(Event::<T>::Unbonded(ledger.stash, value));
+				Self::deposit_event(Event::<T>::Unbonded(ledger.stash, node_pub_key, value));
 			}
 			Ok(())
 		}
@@ -523,24 +987,25 @@ pub mod pallet {
 		/// See also [`Call::unbond`].
 		#[pallet::call_index(2)]
 		#[pallet::weight(T::WeightInfo::withdraw_unbonded())]
-		pub fn withdraw_unbonded(origin: OriginFor<T>) -> DispatchResult {
+		pub fn withdraw_unbonded(origin: OriginFor<T>, node_pub_key: NodePubKey) -> DispatchResult {
 			let controller = ensure_signed(origin)?;
-			let mut ledger = Self::ledger(&controller).ok_or(Error::<T>::NotController)?;
+			let mut ledger =
+				Self::ledger(&controller, &node_pub_key).ok_or(Error::<T>::NotController)?;
 			let (stash, old_total) = (ledger.stash.clone(), ledger.total);
-			let node_pub_key = <Providers<T>>::get(stash.clone()).ok_or(Error::<T>::BadState)?;
+
+			// Keep the funds at stake until the pending slashes of the node are settled.
+			ensure!(!Self::has_unapplied_slash(&node_pub_key), Error::<T>::SlashPending);
 
 			ledger = ledger.consolidate_unlocked(<frame_system::Pallet<T>>::block_number());
 
 			if ledger.unlocking.is_empty() && ledger.active < T::Currency::minimum_balance() {
 				// This account must have called `unbond()` with some value that caused the active
 				// portion to fall below existential deposit + will have no more unlocking chunks
-				// left. We can now safely remove all staking-related information.
-				Self::kill_stash(&stash)?;
-				// Remove the lock.
-				T::Currency::remove_lock(DDC_STAKING_ID, &stash);
+				// left. We can now safely remove all staking-related information of the node.
+				Self::kill_node(&stash, &controller, &node_pub_key)?;
 			} else {
 				// This was the consequence of a partial unbond. just update the ledger and move on.
-				Self::update_ledger(&controller, &ledger);
+				Self::update_ledger(&controller, &node_pub_key, &ledger);
 			};
 
 			// `old_total` should never be less than the new total because
@@ -549,75 +1014,87 @@ pub mod pallet {
 				// Already checked that this won't overflow by entry condition.
 				let value =
 					old_total.checked_sub(&ledger.total).ok_or(Error::<T>::ArithmeticUnderflow)?;
-				Self::deposit_event(Event::<T>::Withdrawn(stash.clone(), value));
+				Self::deposit_event(Event::<T>::Withdrawn(
+					stash.clone(),
+					node_pub_key.clone(),
+					value,
+				));
 
 				// If provider aimed to leave the cluster and the unbonding period ends, remove
 				// the node from the cluster
-				if let Some(cluster_id) = <LeavingStorages<T>>::get(&stash) {
+				if let Some(cluster_id) = <LeavingStorages<T>>::take(&node_pub_key) {
 					// Cluster manager could remove the node from cluster by this moment already, so
 					// it is ok to ignore result.
 					let _ = T::ClusterManager::remove_node(&cluster_id, &node_pub_key);
 
-					<LeavingStorages<T>>::remove(&stash);
-
-					Self::deposit_event(Event::<T>::Left(stash));
+					Self::deposit_event(Event::<T>::Left(stash, node_pub_key));
 				}
 			}
 
 			Ok(())
 		}
 
-		/// Declare the desire to participate in storage network for the origin controller. Also
-		/// works to cancel a previous "chill".
+		/// Declare the desire of the DDC node to participate in storage network for the origin
+		/// controller. Also works to cancel a previous "chill".
 		///
 		/// `cluster` is the ID of the DDC cluster the participant wishes to join.
 		///
 		/// The dispatch origin for this call must be _Signed_ by the controller, not the stash. The
-		/// bond size must be greater than or equal to the `StorageBondSize`.
+		/// bond size together with the stake delegated to the node must be greater than or equal
+		/// to the `StorageBondSize`.
 		#[pallet::call_index(3)]
 		#[pallet::weight(T::WeightInfo::store())]
-		pub fn store(origin: OriginFor<T>, cluster_id: ClusterId) -> DispatchResult {
+		pub fn store(
+			origin: OriginFor<T>,
+			node_pub_key: NodePubKey,
+			cluster_id: ClusterId,
+		) -> DispatchResult {
 			let controller = ensure_signed(origin)?;
 
 			T::ClusterVisitor::ensure_cluster(&cluster_id).map_err(Into::<Error<T>>::into)?;
 
-			let ledger = Self::ledger(&controller).ok_or(Error::<T>::NotController)?;
-			// Retrieve the respective bond size from Cluster Visitor
-			let bond_size = T::ClusterVisitor::get_bond_size(&cluster_id, NodeType::Storage)
-				.map_err(Into::<Error<T>>::into)?;
-			ensure!(
-				ledger.active >= bond_size.saturated_into::<BalanceOf<T>>(),
-				Error::<T>::InsufficientBond
-			);
+			let ledger =
+				Self::ledger(&controller, &node_pub_key).ok_or(Error::<T>::NotController)?;
 			let stash = &ledger.stash;
 
 			// Only Storage node can perform storing (i.e. saving content)
-			let node_pub_key = <Providers<T>>::get(stash).ok_or(Error::<T>::BadState)?;
 			ensure!(
 				matches!(node_pub_key, NodePubKey::StoragePubKey(_)),
 				Error::<T>::StoringProhibited
 			);
 
+			// Retrieve the respective bond size from Cluster Visitor
+			let bond_size = T::ClusterVisitor::get_bond_size(&cluster_id, NodeType::Storage)
+				.map_err(Into::<Error<T>>::into)?;
+			ensure!(
+				Self::node_stake(&ledger, &node_pub_key) >=
+					bond_size.saturated_into::<BalanceOf<T>>(),
+				Error::<T>::InsufficientBond
+			);
+
 			// Is it an attempt to cancel a previous "chill"?
-			if let Some(current_cluster) = Self::storages(stash) {
+			if let Some(current_cluster) = Self::storages(&node_pub_key) {
 				// Switching the cluster is prohibited. The user should chill first.
 				ensure!(current_cluster == cluster_id, Error::<T>::AlreadyInRole);
 				// Cancel previous "chill" attempts
-				Self::reset_chilling(&controller);
+				Self::reset_chilling(&controller, &node_pub_key);
 				return Ok(())
 			} else {
-				// Can't participate in new Storage network if provider hasn't left the previous
+				// Can't participate in new Storage network if the node hasn't left the previous
 				// cluster yet
-				ensure!(!LeavingStorages::<T>::contains_key(stash), Error::<T>::NodeIsLeaving);
+				ensure!(
+					!LeavingStorages::<T>::contains_key(&node_pub_key),
+					Error::<T>::NodeIsLeaving
+				);
 			}
 
-			Self::do_add_storage(stash, cluster_id);
-			Self::deposit_event(Event::<T>::Activated(stash.clone()));
+			Self::do_add_storage(&node_pub_key, cluster_id);
+			Self::deposit_event(Event::<T>::Activated(stash.clone(), node_pub_key));
 
 			Ok(())
 		}
 
-		/// Declare no desire to either participate in DDC network.
+		/// Declare no desire of the DDC node to either participate in DDC network.
 		///
 		/// Only in case the delay for the role _origin_ maintains in the cluster is set to zero in
 		/// cluster settings, it removes the participant immediately. Otherwise, it requires at
@@ -635,13 +1112,14 @@ pub mod pallet {
 		/// Emits `ChillSoon`, `Chill`.
 		#[pallet::call_index(4)]
 		#[pallet::weight(T::WeightInfo::chill())]
-		pub fn chill(origin: OriginFor<T>) -> DispatchResult {
+		pub fn chill(origin: OriginFor<T>, node_pub_key: NodePubKey) -> DispatchResult {
 			let controller = ensure_signed(origin)?;
-			let ledger = Self::ledger(&controller).ok_or(Error::<T>::NotController)?;
+			let ledger =
+				Self::ledger(&controller, &node_pub_key).ok_or(Error::<T>::NotController)?;
 			let current_block = <frame_system::Pallet<T>>::block_number();
 
 			// Extract delay from the cluster settings.
-			let (cluster, delay) = if let Some(cluster) = Self::storages(&ledger.stash) {
+			let (cluster, delay) = if let Some(cluster) = Self::storages(&node_pub_key) {
 				let chill_delay = T::ClusterVisitor::get_chill_delay(&cluster, NodeType::Storage)
 					.map_err(Into::<Error<T>>::into)?;
 				(cluster, chill_delay)
@@ -651,7 +1129,7 @@ pub mod pallet {
 
 			if delay == BlockNumberFor::<T>::from(0u32) {
 				// No delay is set, so we can chill right away.
-				Self::chill_stash(&ledger.stash);
+				Self::chill_node(&ledger.stash, &node_pub_key);
 				return Ok(())
 			}
 
@@ -659,13 +1137,25 @@ pub mod pallet {
 			match ledger.chilling {
 				None => {
 					// No previous declarations of desire to chill. Note it to allow chilling soon.
-					Self::chill_stash_soon(&ledger.stash, &controller, cluster, can_chill_from);
+					Self::chill_node_soon(
+						&ledger.stash,
+						&controller,
+						&node_pub_key,
+						cluster,
+						can_chill_from,
+					);
 					return Ok(())
 				},
 				Some(chilling) if can_chill_from < chilling => {
 					// Time to chill is not reached yet, but it is allowed to chill earlier. Update
 					// to allow chilling sooner.
-					Self::chill_stash_soon(&ledger.stash, &controller, cluster, can_chill_from);
+					Self::chill_node_soon(
+						&ledger.stash,
+						&controller,
+						&node_pub_key,
+						cluster,
+						can_chill_from,
+					);
 					return Ok(())
 				},
 				Some(chilling) if chilling > current_block => Err(Error::<T>::TooEarly)?,
@@ -673,8 +1163,8 @@ pub mod pallet {
 			}
 
 			// It's time to chill.
-			Self::chill_stash(&ledger.stash);
-			Self::reset_chilling(&controller); // for future chilling
+			Self::chill_node(&ledger.stash, &node_pub_key);
+			Self::reset_chilling(&controller, &node_pub_key); // for future chilling
 
 			Ok(())
 		}
@@ -693,46 +1183,63 @@ pub mod pallet {
 			let stash = ensure_signed(origin)?;
 			let old_controller = Self::bonded(&stash).ok_or(Error::<T>::NotStash)?;
 			let controller = T::Lookup::lookup(controller)?;
-			if <Ledger<T>>::contains_key(&controller) {
+			if Self::is_controller(&controller) {
 				Err(Error::<T>::AlreadyPaired)?
 			}
 			if controller != old_controller {
 				<Bonded<T>>::insert(&stash, &controller);
-				if let Some(l) = <Ledger<T>>::take(&old_controller) {
-					<Ledger<T>>::insert(&controller, l);
+				let ledgers = <Ledger<T>>::drain_prefix(&old_controller).collect::<Vec<_>>();
+				for (node_pub_key, l) in ledgers {
+					<Ledger<T>>::insert(&controller, node_pub_key, l);
 				}
 			}
 			Ok(())
 		}
 
-		/// (Re-)set the DDC node of a node operator stash account. Requires to chill first.
+		/// (Re-)set one of the DDC nodes of a node operator stash account, moving the stake of
+		/// `node_pub_key` to `new_node`. Requires to chill the node first.
 		///
 		/// The dispatch origin for this call must be _Signed_ by the stash, not the controller.
 		#[pallet::call_index(6)]
 		#[pallet::weight(T::WeightInfo::set_node())]
-		pub fn set_node(origin: OriginFor<T>, new_node: NodePubKey) -> DispatchResult {
+		pub fn set_node(
+			origin: OriginFor<T>,
+			node_pub_key: NodePubKey,
+			new_node: NodePubKey,
+		) -> DispatchResult {
 			let stash = ensure_signed(origin)?;
 
-			if let Some(existing_node_stash) = Nodes::<T>::get(&new_node) {
-				if existing_node_stash != stash {
-					Err(Error::<T>::AlreadyPaired)?
-				}
+			if Nodes::<T>::contains_key(&new_node) {
+				Err(Error::<T>::AlreadyPaired)?
 			}
 
-			// Remove previously owned node from storage
-			if let Some(current_node) = Providers::<T>::get(&stash) {
-				<Nodes<T>>::remove(current_node);
-			}
+			let controller = Self::bonded(&stash).ok_or(Error::<T>::NotStash)?;
+			let mut nodes = Providers::<T>::get(&stash);
+			let index = nodes
+				.iter()
+				.position(|node| *node == node_pub_key)
+				.ok_or(Error::<T>::NodeHasNoStake)?;
 
-			// Ensure only one node per stash.
-			ensure!(!<Storages<T>>::contains_key(&stash), Error::<T>::AlreadyInRole);
+			// The pending slashes of the node must still reach this stash.
+			ensure!(!Self::has_unapplied_slash(&node_pub_key), Error::<T>::SlashPending);
+			// Delegators have chosen the node, not the stash.
+			ensure!(Self::node_delegators_count(&node_pub_key) == 0, Error::<T>::NodeHasDelegators);
+
+			// Ensure the node has left its cluster.
+			ensure!(!<Storages<T>>::contains_key(&node_pub_key), Error::<T>::AlreadyInRole);
 
 			// Ensure that provider is not about leaving the cluster as it may cause the removal
 			// of an unexpected node after unbonding.
-			ensure!(!<LeavingStorages<T>>::contains_key(&stash), Error::<T>::NodeIsLeaving);
+			ensure!(!<LeavingStorages<T>>::contains_key(&node_pub_key), Error::<T>::NodeIsLeaving);
+
+			let ledger =
+				<Ledger<T>>::take(&controller, &node_pub_key).ok_or(Error::<T>::BadState)?;
+			<Ledger<T>>::insert(&controller, &new_node, ledger);
 
-			<Nodes<T>>::insert(new_node.clone(), stash.clone());
-			<Providers<T>>::insert(stash, new_node);
+			<Nodes<T>>::remove(&node_pub_key);
+			<Nodes<T>>::insert(&new_node, &stash);
+			nodes[index] = new_node;
+			<Providers<T>>::insert(stash, nodes);
 
 			Ok(())
 		}
@@ -742,15 +1249,16 @@ pub mod pallet {
 		/// The dispatch origin for this call must be _Signed_ by the controller.
 		#[pallet::call_index(7)]
 		#[pallet::weight(T::WeightInfo::fast_chill())]
-		pub fn fast_chill(origin: OriginFor<T>) -> DispatchResult {
+		pub fn fast_chill(origin: OriginFor<T>, node_pub_key: NodePubKey) -> DispatchResult {
 			let controller = ensure_signed(origin)?;
 
-			let stash = <Ledger<T>>::get(&controller).ok_or(Error::<T>::NotController)?.stash;
-			let node_pub_key = <Providers<T>>::get(&stash).ok_or(Error::<T>::BadState)?;
+			let stash = <Ledger<T>>::get(&controller, &node_pub_key)
+				.ok_or(Error::<T>::NotController)?
+				.stash;
 			let node_stash = <Nodes<T>>::get(&node_pub_key).ok_or(Error::<T>::BadState)?;
 			ensure!(stash == node_stash, Error::<T>::NotNodeController);
 
-			let cluster_id = <Storages<T>>::get(&stash).ok_or(Error::<T>::NodeHasNoStake)?;
+			let cluster_id = <Storages<T>>::get(&node_pub_key).ok_or(Error::<T>::NodeHasNoStake)?;
 
 			let is_cluster_node = T::ClusterManager::contains_node(&cluster_id, &node_pub_key);
 			ensure!(!is_cluster_node, Error::<T>::FastChillProhibited);
@@ -758,72 +1266,658 @@ pub mod pallet {
 			// block number + 1 => no overflow
 			let can_chill_from =
 				<frame_system::Pallet<T>>::block_number() + BlockNumberFor::<T>::from(1u32);
-			Self::chill_stash_soon(&stash, &controller, cluster_id, can_chill_from);
+			Self::chill_node_soon(&stash, &controller, &node_pub_key, cluster_id, can_chill_from);
+
+			Ok(())
+		}
+
+		/// Set the fraction of the stake slashed for an offence.
+		///
+		/// The dispatch origin for this call must be _Root_.
+		///
+		/// Emits `SlashFractionSet`.
+		#[pallet::call_index(8)]
+		#[pallet::weight(T::WeightInfo::set_slash_fraction())]
+		pub fn set_slash_fraction(
+			origin: OriginFor<T>,
+			offence: Offence,
+			fraction: Perbill,
+		) -> DispatchResult {
+			ensure_root(origin)?;
+
+			<SlashFractions<T>>::insert(offence, fraction);
+			Self::deposit_event(Event::<T>::SlashFractionSet(offence, fraction));
+
+			Ok(())
+		}
+
+		/// Report an offence of a DDC node. The fraction of the active bond and of the unlocking
+		/// chunks set for the offence is slashed after `SlashDeferDuration` blocks, unless
+		/// governance cancels it with [`Call::cancel_slash`] in the meantime. The funds of the
+		/// node stash can not be withdrawn until the slash is settled.
+		///
+		/// The dispatch origin for this call must be _Root_ or _Signed_ by an active DAC
+		/// validator. An offence voted by DAC validators is reported once a majority of the
+		/// active DAC validators votes for it.
+		///
+		/// Emits `OffenceVoted` or `SlashReported`.
+		#[pallet::call_index(9)]
+		#[pallet::weight(T::WeightInfo::report_offence())]
+		pub fn report_offence(
+			origin: OriginFor<T>,
+			node_pub_key: NodePubKey,
+			offence: Offence,
+		) -> DispatchResult {
+			let (reporter, validators) = match frame_system::ensure_signed_or_root(origin)? {
+				Some(validator) => {
+					let validators = T::ValidatorVisitor::get_active_validators();
+					ensure!(validators.contains(&validator), Error::<T>::NotReporter);
+					(Some(validator), validators)
+				},
+				None => (None, Vec::new()),
+			};
+
+			ensure!(<Nodes<T>>::contains_key(&node_pub_key), Error::<T>::NodeHasNoStake);
+			ensure!(
+				!<UnappliedSlashes<T>>::contains_key(&node_pub_key, offence),
+				Error::<T>::SlashAlreadyReported
+			);
+
+			let fraction = Self::slash_fraction(offence);
+			ensure!(!fraction.is_zero(), Error::<T>::NoSlashFraction);
+
+			match &reporter {
+				Some(validator) =>
+					if !Self::vote_for_offence(validator, &validators, &node_pub_key, offence)? {
+						return Ok(())
+					},
+				None => <OffenceVotes<T>>::remove(&node_pub_key, offence),
+			}
+
+			let apply_at = <frame_system::Pallet<T>>::block_number()
+				.saturating_add(T::SlashDeferDuration::get());
+			<UnappliedSlashes<T>>::insert(
+				&node_pub_key,
+				offence,
+				UnappliedSlash { fraction, reporter, apply_at },
+			);
+			<SlashQueueRange<T>>::mutate(|(_, tail)| {
+				<SlashQueue<T>>::insert(*tail, (node_pub_key.clone(), offence, apply_at));
+				*tail += 1;
+			});
+
+			Self::deposit_event(Event::<T>::SlashReported(node_pub_key, offence, apply_at));
+
+			Ok(())
+		}
+
+		/// Cancel a reported slash before it is applied.
+		///
+		/// The dispatch origin for this call must be _Root_.
+		///
+		/// Emits `SlashCancelled`.
+		#[pallet::call_index(10)]
+		#[pallet::weight(T::WeightInfo::cancel_slash())]
+		pub fn cancel_slash(
+			origin: OriginFor<T>,
+			node_pub_key: NodePubKey,
+			offence: Offence,
+		) -> DispatchResult {
+			ensure_root(origin)?;
+
+			<UnappliedSlashes<T>>::take(&node_pub_key, offence)
+				.ok_or(Error::<T>::NoUnappliedSlash)?;
+			Self::deposit_event(Event::<T>::SlashCancelled(node_pub_key, offence));
+
+			Ok(())
+		}
+
+		/// Apply a reported slash whose deferral period is over. Slashes are also applied
+		/// automatically when blocks have spare weight.
+		///
+		/// The dispatch origin for this call must be _Signed_ by any account.
+		///
+		/// Emits `Slashed`.
+		#[pallet::call_index(11)]
+		#[pallet::weight(T::WeightInfo::apply_slash())]
+		pub fn apply_slash(
+			origin: OriginFor<T>,
+			node_pub_key: NodePubKey,
+			offence: Offence,
+		) -> DispatchResult {
+			ensure_signed(origin)?;
+
+			let slash = Self::unapplied_slash(&node_pub_key, offence)
+				.ok_or(Error::<T>::NoUnappliedSlash)?;
+			ensure!(
+				slash.apply_at <= <frame_system::Pallet<T>>::block_number(),
+				Error::<T>::TooEarly
+			);
+
+			Self::do_apply_slash(&node_pub_key, offence)
+		}
+
+		/// Take the origin account as a delegator and reserve `value` of its balance as stake of
+		/// the DDC node. Delegated stake counts towards the cluster bond size of the node provider
+		/// and earns a share of the provider rewards, less the provider commission.
+		///
+		/// The dispatch origin for this call must be _Signed_ by the delegator.
+		///
+		/// Emits `Delegated`.
+		#[pallet::call_index(12)]
+		#[pallet::weight(T::WeightInfo::delegate())]
+		pub fn delegate(
+			origin: OriginFor<T>,
+			node_pub_key: NodePubKey,
+			#[pallet::compact] value: BalanceOf<T>,
+		) -> DispatchResult {
+			let delegator = ensure_signed(origin)?;
+			ensure!(<Nodes<T>>::contains_key(&node_pub_key), Error::<T>::NodeHasNoStake);
+
+			// The balance bonded by the delegator as a stash is locked and can not be reserved.
+			let bonded = Self::bonded(&delegator)
+				.map_or_else(Zero::zero, |controller| Self::bonded_total(&controller));
+			let value = value.min(T::Currency::free_balance(&delegator).saturating_sub(bonded));
+
+			let mut ledger = match Self::delegation(&node_pub_key, &delegator) {
+				Some(ledger) => ledger,
+				None => {
+					// Reject a delegation which is considered to be _dust_.
+					ensure!(value >= T::Currency::minimum_balance(), Error::<T>::InsufficientBond);
+					let delegators_count = Self::node_delegators_count(&node_pub_key);
+					ensure!(
+						delegators_count < MaxNodeDelegators::get(),
+						Error::<T>::TooManyDelegators
+					);
+					<NodeDelegatorsCount<T>>::insert(&node_pub_key, delegators_count + 1);
+					StakingLedger::default_from(delegator.clone())
+				},
+			};
+
+			ledger.active =
+				ledger.active.checked_add(&value).ok_or(Error::<T>::ArithmeticOverflow)?;
+			ledger.total =
+				ledger.total.checked_add(&value).ok_or(Error::<T>::ArithmeticOverflow)?;
+
+			// Delegated funds are reserved, so they never overlap with the locked stake of a
+			// stash.
+			T::Currency::reserve(&delegator, value)?;
+			<Delegations<T>>::insert(&node_pub_key, &delegator, &ledger);
+
+			let delegated_stake = Self::delegated_stake(&node_pub_key)
+				.checked_add(&value)
+				.ok_or(Error::<T>::ArithmeticOverflow)?;
+			<DelegatedStake<T>>::insert(&node_pub_key, delegated_stake);
+
+			Self::deposit_event(Event::<T>::Delegated(delegator, node_pub_key.clone(), value));
+			Self::note_bond_top_up(&node_pub_key);
+
+			Ok(())
+		}
+
+		/// Schedule a portion of the stake delegated to the DDC node to be unlocked after the
+		/// unbonding delay of the node cluster. The stake left below
+		/// T::Currency::minimum_balance() is scheduled as well.
+		///
+		/// The stake of an activated node can not drop below the bond size of its cluster. A
+		/// chilled node left below the bond size leaves its cluster once the unbonding delay ends.
+		///
+		/// The dispatch origin for this call must be _Signed_ by the delegator.
+		///
+		/// Emits `Undelegated`, `LeaveSoon`.
+		///
+		/// See also [`Call::withdraw_undelegated`].
+		#[pallet::call_index(13)]
+		#[pallet::weight(T::WeightInfo::undelegate())]
+		pub fn undelegate(
+			origin: OriginFor<T>,
+			node_pub_key: NodePubKey,
+			#[pallet::compact] value: BalanceOf<T>,
+		) -> DispatchResult {
+			let delegator = ensure_signed(origin)?;
+			let mut ledger =
+				Self::delegation(&node_pub_key, &delegator).ok_or(Error::<T>::NoDelegation)?;
+
+			ensure!(
+				ledger.unlocking.len() < MaxUnlockingChunks::get() as usize,
+				Error::<T>::NoMoreChunks,
+			);
+
+			let mut value = value.min(ledger.active);
+			if value.is_zero() {
+				return Ok(())
+			}
+
+			ledger.active =
+				ledger.active.checked_sub(&value).ok_or(Error::<T>::ArithmeticUnderflow)?;
+
+			// Avoid there being a dust balance left in the staking system.
+			if ledger.active < T::Currency::minimum_balance() {
+				value = value.checked_add(&ledger.active).ok_or(Error::<T>::ArithmeticOverflow)?;
+				ledger.active = Zero::zero();
+			}
+
+			// The stake of the node left after the undelegation, if the node is still bonded.
+			let provider = Self::nodes(&node_pub_key).and_then(|stash| {
+				let controller = Self::bonded(&stash)?;
+				let ledger = Self::ledger(&controller, &node_pub_key)?;
+				let stake = Self::node_stake(&ledger, &node_pub_key).saturating_sub(value);
+				Some((stash, stake))
+			});
+
+			// Make sure that the node maintains enough stake for its role in the cluster. The
+			// node provider should chill first to let the delegators leave.
+			if let (Some(cluster_id), Some((_, stake))) =
+				(Self::storages(&node_pub_key), provider.as_ref())
+			{
+				let bond_size = T::ClusterVisitor::get_bond_size(&cluster_id, NodeType::Storage)
+					.map_err(Into::<Error<T>>::into)?;
+				ensure!(
+					*stake >= bond_size.saturated_into::<BalanceOf<T>>(),
+					Error::<T>::InsufficientBond
+				);
+			}
+
+			let unbonding_delay = match T::NodeVisitor::get_cluster_id(&node_pub_key) {
+				Ok(Some(cluster_id)) => {
+					let bonding_params = T::ClusterVisitor::get_bonding_params(&cluster_id)
+						.map_err(Into::<Error<T>>::into)?;
+
+					// The node left below the bond size leaves the cluster at the end of the
+					// unbonding period, as if its provider unbonded the stake.
+					if let Some((stash, stake)) = provider {
+						let bond_size =
+							bonding_params.storage_bond_size.saturated_into::<BalanceOf<T>>();
+						if stake < bond_size && !<LeavingStorages<T>>::contains_key(&node_pub_key) {
+							<LeavingStorages<T>>::insert(&node_pub_key, cluster_id);
+							Self::deposit_event(Event::<T>::LeaveSoon(stash, node_pub_key.clone()));
+						}
+					}
+
+					bonding_params.storage_unbonding_delay
+				},
+				// If node is not a member of any cluster or was deleted, allow immediate unbonding.
+				_ => BlockNumberFor::<T>::from(0u32),
+			};
+
+			// block number + configuration -> no overflow
+			let block = <frame_system::Pallet<T>>::block_number() + unbonding_delay;
+			if let Some(chunk) = ledger.unlocking.last_mut().filter(|chunk| chunk.block == block) {
+				chunk.value = chunk.value.defensive_saturating_add(value)
+			} else {
+				ledger
+					.unlocking
+					.try_push(UnlockChunk { value, block })
+					.map_err(|_| Error::<T>::NoMoreChunks)?;
+			};
+			<Delegations<T>>::insert(&node_pub_key, &delegator, &ledger);
+
+			<DelegatedStake<T>>::mutate(&node_pub_key, |delegated_stake| {
+				*delegated_stake = delegated_stake.saturating_sub(value)
+			});
+
+			Self::deposit_event(Event::<T>::Undelegated(delegator, node_pub_key, value));
+
+			Ok(())
+		}
+
+		/// Remove any unlocked chunks from the `unlocking` queue of the stake delegated to the DDC
+		/// node, unreserving that balance of the delegator.
+		///
+		/// The dispatch origin for this call must be _Signed_ by the delegator.
+		///
+		/// Emits `DelegationWithdrawn`, `Left`.
+		///
+		/// See also [`Call::undelegate`].
+		#[pallet::call_index(14)]
+		#[pallet::weight(T::WeightInfo::withdraw_undelegated())]
+		pub fn withdraw_undelegated(
+			origin: OriginFor<T>,
+			node_pub_key: NodePubKey,
+		) -> DispatchResult {
+			let delegator = ensure_signed(origin)?;
+			let mut ledger =
+				Self::delegation(&node_pub_key, &delegator).ok_or(Error::<T>::NoDelegation)?;
+			let old_total = ledger.total;
+
+			// Keep the funds at stake until the pending slashes of the node are settled.
+			ensure!(!Self::has_unapplied_slash(&node_pub_key), Error::<T>::SlashPending);
+
+			ledger = ledger.consolidate_unlocked(<frame_system::Pallet<T>>::block_number());
+
+			if ledger.unlocking.is_empty() && ledger.active.is_zero() {
+				<Delegations<T>>::remove(&node_pub_key, &delegator);
+				<NodeDelegatorsCount<T>>::mutate(&node_pub_key, |count| {
+					*count = count.saturating_sub(1)
+				});
+			} else {
+				<Delegations<T>>::insert(&node_pub_key, &delegator, &ledger);
+			}
+
+			let value =
+				old_total.checked_sub(&ledger.total).ok_or(Error::<T>::ArithmeticUnderflow)?;
+			if !value.is_zero() {
+				T::Currency::unreserve(&delegator, value);
+				Self::deposit_event(Event::<T>::DelegationWithdrawn(
+					delegator,
+					node_pub_key.clone(),
+					value,
+				));
+
+				// If the undelegation left the node below the bond size and the unbonding period
+				// ends, remove the node from the cluster it was leaving.
+				Self::leave_if_under_bonded(&node_pub_key);
+			}
+
+			Ok(())
+		}
+
+		/// Set the commission the node provider takes from the rewards of the delegators of the
+		/// DDC node.
+		///
+		/// A lowered commission takes effect immediately. A raised commission takes effect after
+		/// the unbonding delay of the node cluster, so the delegators can undelegate before it
+		/// applies to their rewards.
+		///
+		/// The dispatch origin for this call must be _Signed_ by the controller.
+		///
+		/// Emits `CommissionSet`, `CommissionScheduled`.
+		#[pallet::call_index(15)]
+		#[pallet::weight(T::WeightInfo::set_commission())]
+		pub fn set_commission(
+			origin: OriginFor<T>,
+			node_pub_key: NodePubKey,
+			commission: Perbill,
+		) -> DispatchResult {
+			let controller = ensure_signed(origin)?;
+			ensure!(
+				<Ledger<T>>::contains_key(&controller, &node_pub_key),
+				Error::<T>::NotController
+			);
+
+			let delay = match T::NodeVisitor::get_cluster_id(&node_pub_key) {
+				Ok(Some(cluster_id)) if commission > Self::effective_commission(&node_pub_key) =>
+					T::ClusterVisitor::get_bonding_params(&cluster_id)
+						.map_err(Into::<Error<T>>::into)?
+						.storage_unbonding_delay,
+				// Delegators of a node out of any cluster earn no rewards to be affected.
+				_ => BlockNumberFor::<T>::from(0u32),
+			};
+
+			// A new commission replaces the one pending.
+			<PendingCommissions<T>>::remove(&node_pub_key);
+			if delay.is_zero() {
+				<Commissions<T>>::insert(&node_pub_key, commission);
+				Self::deposit_event(Event::<T>::CommissionSet(node_pub_key, commission));
+			} else {
+				// block number + configuration -> no overflow
+				let block = <frame_system::Pallet<T>>::block_number() + delay;
+				<PendingCommissions<T>>::insert(&node_pub_key, (commission, block));
+				<CommissionQueueRange<T>>::mutate(|(_, tail)| {
+					<CommissionQueue<T>>::insert(*tail, (node_pub_key.clone(), block));
+					*tail += 1;
+				});
+				Self::deposit_event(Event::<T>::CommissionScheduled(
+					node_pub_key,
+					commission,
+					block,
+				));
+			}
+
+			Ok(())
+		}
+
+		/// Lock up `value` of the balance of an already bonded stash as the stake of another DDC
+		/// node. Each node of the stash has its own bond, cluster membership and unlocking queue,
+		/// managed by the controller of the stash.
+		///
+		/// `value` must be more than the `minimum_balance` specified by `T::Currency`.
+		///
+		/// The dispatch origin for this call must be _Signed_ by the stash account.
+		///
+		/// Emits `Bonded`.
+		#[pallet::call_index(16)]
+		#[pallet::weight(T::WeightInfo::bond_node())]
+		pub fn bond_node(
+			origin: OriginFor<T>,
+			node: NodePubKey,
+			#[pallet::compact] value: BalanceOf<T>,
+		) -> DispatchResult {
+			let stash = ensure_signed(origin)?;
+			let controller = Self::bonded(&stash).ok_or(Error::<T>::NotStash)?;
+
+			// Only the balance not bonded by the other nodes of the stash can be bonded.
+			let stash_balance =
+				T::Currency::free_balance(&stash).saturating_sub(Self::bonded_total(&controller));
+			let value = value.min(stash_balance);
+
+			// Reject a bond which is considered to be _dust_.
+			if value < T::Currency::minimum_balance() {
+				Err(Error::<T>::InsufficientBond)?
+			}
+
+			// Reject a bond with a known DDC node.
+			if Nodes::<T>::contains_key(&node) {
+				Err(Error::<T>::AlreadyPaired)?
+			}
+
+			// Checks that the node is registered in the network
+			ensure!(T::NodeVisitor::exists(&node), Error::<T>::NodeIsNotFound);
+
+			Self::do_bond_node(stash, &controller, node, value)
+		}
+
+		/// Rebond a portion of the DDC node stake scheduled to be unlocked, taking the most
+		/// recently unbonded funds first.
+		///
+		/// If the node is leaving its cluster after unbonding below the cluster bond size, the
+		/// leave is cancelled once the stake is back to the bond size. The node stays chilled and
+		/// may call [`Call::store`] to participate again.
+		///
+		/// The dispatch origin for this call must be _Signed_ by the controller.
+		///
+		/// Emits `Rebonded`, `LeaveCancelled`.
+		///
+		/// See also [`Call::unbond`].
+		#[pallet::call_index(17)]
+		#[pallet::weight(T::WeightInfo::rebond())]
+		pub fn rebond(
+			origin: OriginFor<T>,
+			node_pub_key: NodePubKey,
+			#[pallet::compact] value: BalanceOf<T>,
+		) -> DispatchResult {
+			let controller = ensure_signed(origin)?;
+			let ledger =
+				Self::ledger(&controller, &node_pub_key).ok_or(Error::<T>::NotController)?;
+			ensure!(!ledger.unlocking.is_empty(), Error::<T>::NoUnlockChunk);
+
+			let (ledger, rebonded_value) = ledger.rebond(value);
+			// Last check: the new active amount of ledger must be more than ED.
+			ensure!(ledger.active >= T::Currency::minimum_balance(), Error::<T>::InsufficientBond);
+
+			Self::update_ledger(&controller, &node_pub_key, &ledger);
+			Self::deposit_event(Event::<T>::Rebonded(
+				ledger.stash.clone(),
+				node_pub_key.clone(),
+				rebonded_value,
+			));
+			Self::note_bond_top_up(&node_pub_key);
+
+			// The node stays in the cluster it was about to leave if the stake is enough again.
+			if let Some(cluster_id) = Self::leaving_storages(&node_pub_key) {
+				let bond_size = T::ClusterVisitor::get_bond_size(&cluster_id, NodeType::Storage)
+					.map_err(Into::<Error<T>>::into)?;
+				if Self::node_stake(&ledger, &node_pub_key) >=
+					bond_size.saturated_into::<BalanceOf<T>>()
+				{
+					<LeavingStorages<T>>::remove(&node_pub_key);
+					Self::deposit_event(Event::<T>::LeaveCancelled(
+						ledger.stash,
+						node_pub_key,
+						cluster_id,
+					));
+				}
+			}
+
+			Ok(())
+		}
+
+		/// Add some extra amount that have appeared in the stash `free_balance` into the stake of
+		/// the DDC node, e.g. to top up the stake of a node left under-bonded by a raised
+		/// cluster bond size.
+		///
+		/// The dispatch origin for this call must be _Signed_ by the stash, not the controller.
+		///
+		/// Emits `Bonded`, `BondToppedUp`.
+		#[pallet::call_index(18)]
+		#[pallet::weight(T::WeightInfo::bond_extra())]
+		pub fn bond_extra(
+			origin: OriginFor<T>,
+			node_pub_key: NodePubKey,
+			#[pallet::compact] max_additional: BalanceOf<T>,
+		) -> DispatchResult {
+			let stash = ensure_signed(origin)?;
+			let controller = Self::bonded(&stash).ok_or(Error::<T>::NotStash)?;
+			let mut ledger =
+				Self::ledger(&controller, &node_pub_key).ok_or(Error::<T>::NodeHasNoStake)?;
+
+			// Only the balance not bonded by the nodes of the stash can be bonded.
+			let stash_balance =
+				T::Currency::free_balance(&stash).saturating_sub(Self::bonded_total(&controller));
+			let extra = max_additional.min(stash_balance);
+			if extra.is_zero() {
+				return Ok(())
+			}
+
+			ledger.total =
+				ledger.total.checked_add(&extra).ok_or(Error::<T>::ArithmeticOverflow)?;
+			ledger.active =
+				ledger.active.checked_add(&extra).ok_or(Error::<T>::ArithmeticOverflow)?;
+			// Last check: the new active amount of ledger must be more than ED.
+			ensure!(ledger.active >= T::Currency::minimum_balance(), Error::<T>::InsufficientBond);
+
+			Self::update_ledger(&controller, &node_pub_key, &ledger);
+			Self::deposit_event(Event::<T>::Bonded(stash, node_pub_key.clone(), extra));
+			Self::note_bond_top_up(&node_pub_key);
 
 			Ok(())
 		}
 	}
 
 	impl<T: Config> Pallet<T> {
-		/// Update the ledger for a controller.
+		/// Update the ledger of a DDC node for a controller.
 		///
 		/// This will also update the stash lock.
 		fn update_ledger(
 			controller: &T::AccountId,
+			node_pub_key: &NodePubKey,
 			ledger: &StakingLedger<T::AccountId, BalanceOf<T>, T>,
 		) {
-			T::Currency::set_lock(
-				DDC_STAKING_ID,
-				&ledger.stash,
-				ledger.total,
-				WithdrawReasons::all(),
-			);
-			<Ledger<T>>::insert(controller, ledger);
+			<Ledger<T>>::insert(controller, node_pub_key, ledger);
+			Self::update_lock(controller, &ledger.stash);
+		}
+
+		/// Lock the balance of the stash bonded by all of its DDC nodes, or remove the lock if
+		/// the stash has no bonded nodes left.
+		fn update_lock(controller: &T::AccountId, stash: &T::AccountId) {
+			let total = Self::bonded_total(controller);
+			if total.is_zero() {
+				T::Currency::remove_lock(DDC_STAKING_ID, stash);
+			} else {
+				T::Currency::set_lock(DDC_STAKING_ID, stash, total, WithdrawReasons::all());
+			}
+		}
+
+		/// The balance bonded by all DDC nodes of the stash controlled by the controller.
+		fn bonded_total(controller: &T::AccountId) -> BalanceOf<T> {
+			<Ledger<T>>::iter_prefix_values(controller)
+				.fold(BalanceOf::<T>::zero(), |total, ledger| total.saturating_add(ledger.total))
 		}
 
-		/// Chill a stash account.
-		fn chill_stash(stash: &T::AccountId) {
-			let chilled_as_storage = Self::do_remove_storage(stash);
+		/// Whether the account controls a bonded stash.
+		fn is_controller(controller: &T::AccountId) -> bool {
+			<Ledger<T>>::iter_prefix(controller).next().is_some()
+		}
+
+		/// Add a DDC node with `value` bonded to the nodes of a bonded stash.
+		fn do_bond_node(
+			stash: T::AccountId,
+			controller: &T::AccountId,
+			node: NodePubKey,
+			value: BalanceOf<T>,
+		) -> DispatchResult {
+			Providers::<T>::try_mutate(&stash, |nodes| nodes.try_push(node.clone()))
+				.map_err(|_| Error::<T>::TooManyNodes)?;
+			Nodes::<T>::insert(&node, &stash);
+
+			Self::deposit_event(Event::<T>::Bonded(stash.clone(), node.clone(), value));
+			let item = StakingLedger {
+				stash,
+				total: value,
+				active: value,
+				chilling: Default::default(),
+				unlocking: Default::default(),
+			};
+			Self::update_ledger(controller, &node, &item);
+			Ok(())
+		}
+
+		/// Chill a DDC node of a stash account.
+		fn chill_node(stash: &T::AccountId, node_pub_key: &NodePubKey) {
+			let chilled_as_storage = Self::do_remove_storage(node_pub_key);
 			if chilled_as_storage {
-				Self::deposit_event(Event::<T>::Chilled(stash.clone()));
+				Self::deposit_event(Event::<T>::Chilled(stash.clone(), node_pub_key.clone()));
 			}
 		}
 
-		/// Note a desire of a stash account to chill soon.
-		pub fn chill_stash_soon(
+		/// Note a desire of a stash account to chill its DDC node soon.
+		pub fn chill_node_soon(
 			stash: &T::AccountId,
 			controller: &T::AccountId,
+			node_pub_key: &NodePubKey,
 			cluster: ClusterId,
 			can_chill_from: BlockNumberFor<T>,
 		) {
-			Ledger::<T>::mutate(controller, |maybe_ledger| {
+			Ledger::<T>::mutate(controller, node_pub_key, |maybe_ledger| {
 				if let Some(ref mut ledger) = maybe_ledger {
 					ledger.chilling = Some(can_chill_from)
 				}
 			});
-			Self::deposit_event(Event::<T>::ChillSoon(stash.clone(), cluster, can_chill_from));
+			Self::deposit_event(Event::<T>::ChillSoon(
+				stash.clone(),
+				node_pub_key.clone(),
+				cluster,
+				can_chill_from,
+			));
 		}
 
-		/// Remove all associated data of a stash account from the staking system.
+		/// Remove all associated data of a DDC node from the staking system, and of its stash
+		/// account if it was the last node of the stash.
 		///
 		/// Assumes storage is upgraded before calling.
 		///
-		/// This is called:
-		/// - after a `withdraw_unbonded()` call that frees all of a stash's bonded balance.
-		/// - through `reap_stash()` if the balance has fallen to zero (through slashing).
-		fn kill_stash(stash: &T::AccountId) -> DispatchResult {
-			let controller = <Bonded<T>>::get(stash).ok_or(Error::<T>::NotStash)?;
-
-			<Bonded<T>>::remove(stash);
-			<Ledger<T>>::remove(&controller);
-
-			if let Some(node_pub_key) = <Providers<T>>::take(stash) {
-				<Nodes<T>>::remove(node_pub_key);
-			};
-
-			Self::do_remove_storage(stash);
+		/// This is called after a `withdraw_unbonded()` call that frees all of the node's bonded
+		/// balance.
+		fn kill_node(
+			stash: &T::AccountId,
+			controller: &T::AccountId,
+			node_pub_key: &NodePubKey,
+		) -> DispatchResult {
+			<Ledger<T>>::remove(controller, node_pub_key);
+			<Nodes<T>>::remove(node_pub_key);
+			Self::do_remove_storage(node_pub_key);
+
+			let mut nodes = <Providers<T>>::get(stash);
+			nodes.retain(|node| node != node_pub_key);
+			if nodes.is_empty() {
+				<Bonded<T>>::remove(stash);
+				<Providers<T>>::remove(stash);
+				frame_system::Pallet::<T>::dec_consumers(stash);
+			} else {
+				<Providers<T>>::insert(stash, nodes);
+			}
 
-			frame_system::Pallet::<T>::dec_consumers(stash);
+			Self::update_lock(controller, stash);
 
 			Ok(())
 		}
@@ -831,25 +1925,339 @@ pub mod pallet {
 		/// This function will add a storage network participant to the `Storages` storage map.
 		///
 		/// If the storage network participant already exists, their cluster will be updated.
-		pub fn do_add_storage(who: &T::AccountId, cluster: ClusterId) {
-			Storages::<T>::insert(who, cluster);
+		pub fn do_add_storage(node_pub_key: &NodePubKey, cluster: ClusterId) {
+			if let Some(old_cluster) = Storages::<T>::get(node_pub_key) {
+				ClusterStorages::<T>::remove(old_cluster, node_pub_key);
+			}
+			Storages::<T>::insert(node_pub_key, cluster);
+			ClusterStorages::<T>::insert(cluster, node_pub_key, ());
 		}
 
 		/// This function will remove a storage network participant from the `Storages` map.
 		///
-		/// Returns true if `who` was removed from `Storages`, otherwise false.
-		pub fn do_remove_storage(who: &T::AccountId) -> bool {
-			Storages::<T>::take(who).is_some()
+		/// Returns true if `node_pub_key` was removed from `Storages`, otherwise false.
+		pub fn do_remove_storage(node_pub_key: &NodePubKey) -> bool {
+			UnderBondedNodes::<T>::remove(node_pub_key);
+			match Storages::<T>::take(node_pub_key) {
+				Some(cluster) => {
+					ClusterStorages::<T>::remove(cluster, node_pub_key);
+					true
+				},
+				None => false,
+			}
+		}
+
+		/// Stake of the node provider counted towards the cluster bond size, its active bond
+		/// together with the stake delegated to its node.
+		fn node_stake(
+			ledger: &StakingLedger<T::AccountId, BalanceOf<T>, T>,
+			node_pub_key: &NodePubKey,
+		) -> BalanceOf<T> {
+			ledger.active.saturating_add(Self::delegated_stake(node_pub_key))
+		}
+
+		/// The commission the provider of the node takes from the delegators rewards, counting the
+		/// raised commission from the block it takes effect, before it is applied on idle.
+		fn effective_commission(node_pub_key: &NodePubKey) -> Perbill {
+			match Self::pending_commission(node_pub_key) {
+				Some((commission, block)) if block <= <frame_system::Pallet<T>>::block_number() =>
+					commission,
+				_ => Self::commission(node_pub_key),
+			}
+		}
+
+		/// Remove the node leaving its cluster from the cluster once its stake, counting the
+		/// delegated stake, is below the bond size of the cluster.
+		fn leave_if_under_bonded(node_pub_key: &NodePubKey) {
+			let cluster_id = match Self::leaving_storages(node_pub_key) {
+				Some(cluster_id) => cluster_id,
+				None => return,
+			};
+			if Self::has_bond_size(node_pub_key, &cluster_id).unwrap_or(false) {
+				return
+			}
+
+			<LeavingStorages<T>>::remove(node_pub_key);
+			// Cluster manager could remove the node from cluster by this moment already, so it is
+			// ok to ignore result.
+			let _ = T::ClusterManager::remove_node(&cluster_id, node_pub_key);
+			if let Some(stash) = Self::nodes(node_pub_key) {
+				Self::deposit_event(Event::<T>::Left(stash, node_pub_key.clone()));
+			}
+		}
+
+		/// Whether the node has reported offences whose slashes are not applied yet.
+		fn has_unapplied_slash(node_pub_key: &NodePubKey) -> bool {
+			<UnappliedSlashes<T>>::iter_prefix(node_pub_key).next().is_some()
+		}
+
+		/// Records the vote of a DAC validator for the offence of the node. Returns `true` once a
+		/// majority of the active DAC validators has voted for it and the offence can be reported.
+		fn vote_for_offence(
+			validator: &T::AccountId,
+			validators: &[T::AccountId],
+			node_pub_key: &NodePubKey,
+			offence: Offence,
+		) -> Result<bool, DispatchError> {
+			let mut votes = Self::offence_votes(node_pub_key, offence);
+			ensure!(!votes.contains(validator), Error::<T>::AlreadyVoted);
+			votes.try_push(validator.clone()).map_err(|_| Error::<T>::TooManyVotes)?;
+
+			// votes of the validators that have left the active set do not count
+			let active_votes = votes.iter().filter(|voter| validators.contains(voter)).count();
+			if active_votes > validators.len() / 2 {
+				<OffenceVotes<T>>::remove(node_pub_key, offence);
+				Ok(true)
+			} else {
+				<OffenceVotes<T>>::insert(node_pub_key, offence, votes);
+				Self::deposit_event(Event::<T>::OffenceVoted(
+					validator.clone(),
+					node_pub_key.clone(),
+					offence,
+				));
+				Ok(false)
+			}
 		}
 
-		/// Reset the chilling block for a controller.
-		pub fn reset_chilling(controller: &T::AccountId) {
-			Ledger::<T>::mutate(controller, |maybe_ledger| {
+		/// Slash the stake of the node stash and the stake delegated to the node pro-rata for the
+		/// reported offence. The slashed funds go to the reserve of the cluster the node is
+		/// assigned to, or to the treasury.
+		fn do_apply_slash(node_pub_key: &NodePubKey, offence: Offence) -> DispatchResult {
+			let slash = <UnappliedSlashes<T>>::take(node_pub_key, offence)
+				.ok_or(Error::<T>::NoUnappliedSlash)?;
+			let stash = <Nodes<T>>::get(node_pub_key).ok_or(Error::<T>::NodeHasNoStake)?;
+			let controller = <Bonded<T>>::get(&stash).ok_or(Error::<T>::BadState)?;
+			let mut ledger = Self::ledger(&controller, node_pub_key).ok_or(Error::<T>::BadState)?;
+
+			let amount = ledger.slash(slash.fraction, T::Currency::minimum_balance());
+			Self::update_ledger(&controller, node_pub_key, &ledger);
+
+			let cluster_id = T::NodeVisitor::get_cluster_id(node_pub_key)
+				.ok()
+				.flatten()
+				.or_else(|| Self::storages(node_pub_key))
+				.or_else(|| Self::leaving_storages(node_pub_key));
+			let beneficiary = cluster_id
+				.and_then(|cluster_id| T::ClusterVisitor::get_reserve_account_id(&cluster_id).ok())
+				.unwrap_or_else(T::TreasuryVisitor::get_account_id);
+
+			// The lock has already been lowered by the slashed amount.
+			let (mut imbalance, _) = T::Currency::slash(&stash, amount);
+			let slashed = imbalance.peek();
+
+			// The delegations are bounded by `MaxNodeDelegators`.
+			let delegations = <Delegations<T>>::iter_prefix(node_pub_key).collect::<Vec<_>>();
+			let mut delegated_slashed = BalanceOf::<T>::zero();
+			for (delegator, mut delegation) in delegations {
+				let active = delegation.active;
+				let amount = delegation.slash(slash.fraction, T::Currency::minimum_balance());
+				delegated_slashed =
+					delegated_slashed.saturating_add(active.saturating_sub(delegation.active));
+				if delegation.total.is_zero() {
+					<Delegations<T>>::remove(node_pub_key, &delegator);
+					<NodeDelegatorsCount<T>>::mutate(node_pub_key, |count| {
+						*count = count.saturating_sub(1)
+					});
+				} else {
+					<Delegations<T>>::insert(node_pub_key, &delegator, &delegation);
+				}
+
+				let (delegator_imbalance, _) = T::Currency::slash_reserved(&delegator, amount);
+				let delegator_slashed = delegator_imbalance.peek();
+				imbalance.subsume(delegator_imbalance);
+				Self::deposit_event(Event::<T>::DelegationSlashed(
+					delegator,
+					node_pub_key.clone(),
+					offence,
+					delegator_slashed,
+				));
+			}
+			<DelegatedStake<T>>::mutate(node_pub_key, |delegated_stake| {
+				*delegated_stake = delegated_stake.saturating_sub(delegated_slashed)
+			});
+
+			T::Currency::resolve_creating(&beneficiary, imbalance);
+
+			Self::deposit_event(Event::<T>::Slashed(stash, offence, slashed, beneficiary));
+
+			Ok(())
+		}
+
+		/// Reset the chilling block of a DDC node for a controller.
+		pub fn reset_chilling(controller: &T::AccountId, node_pub_key: &NodePubKey) {
+			Ledger::<T>::mutate(controller, node_pub_key, |maybe_ledger| {
 				if let Some(ref mut ledger) = maybe_ledger {
 					ledger.chilling = None
 				}
 			});
 		}
+
+		/// Whether the stake of the node is at least the bond size of the cluster.
+		fn has_bond_size(
+			node_pub_key: &NodePubKey,
+			cluster_id: &ClusterId,
+		) -> Result<bool, Error<T>> {
+			let stash = Self::nodes(node_pub_key).ok_or(Error::<T>::NodeHasNoStake)?;
+			let controller = Self::bonded(&stash).ok_or(Error::<T>::BadState)?;
+			let ledger = Self::ledger(&controller, node_pub_key).ok_or(Error::<T>::BadState)?;
+			let bond_size = T::ClusterVisitor::get_bond_size(cluster_id, NodeType::Storage)
+				.map_err(Into::<Error<T>>::into)?;
+			Ok(Self::node_stake(&ledger, node_pub_key) >=
+				bond_size.saturated_into::<BalanceOf<T>>())
+		}
+
+		/// Clear the under-bonded mark of the node once its stake is back to the bond size of its
+		/// cluster.
+		fn note_bond_top_up(node_pub_key: &NodePubKey) {
+			if !<UnderBondedNodes<T>>::contains_key(node_pub_key) {
+				return
+			}
+			let topped_up = Self::storages(node_pub_key).is_some_and(|cluster_id| {
+				Self::has_bond_size(node_pub_key, &cluster_id).unwrap_or(false)
+			});
+			if !topped_up {
+				return
+			}
+			<UnderBondedNodes<T>>::remove(node_pub_key);
+			if let Some(stash) = Self::nodes(node_pub_key) {
+				Self::deposit_event(Event::<T>::BondToppedUp(stash, node_pub_key.clone()));
+			}
+		}
+
+		/// Chill the node whose grace period to top up its stake has expired, unless the stake
+		/// is back to the bond size of its cluster.
+		fn chill_under_bonded(node_pub_key: &NodePubKey) {
+			<UnderBondedNodes<T>>::remove(node_pub_key);
+			let cluster_id = match Self::storages(node_pub_key) {
+				Some(cluster_id) => cluster_id,
+				None => return,
+			};
+			if Self::has_bond_size(node_pub_key, &cluster_id).unwrap_or(false) {
+				return
+			}
+			if let Some(stash) = Self::nodes(node_pub_key) {
+				Self::chill_node(&stash, node_pub_key);
+				if let Some(controller) = Self::bonded(&stash) {
+					Self::reset_chilling(&controller, node_pub_key);
+				}
+			}
+		}
+
+		/// Check the stake of the Storage node in the cluster against the bond size, marking
+		/// the node as under-bonded until `chill_at` or clearing its mark.
+		fn check_bond(
+			node_pub_key: &NodePubKey,
+			cluster_id: &ClusterId,
+			bond_size: BalanceOf<T>,
+			chill_at: BlockNumberFor<T>,
+		) {
+			let stash = match Self::nodes(node_pub_key) {
+				Some(stash) => stash,
+				None => return,
+			};
+			let stake = Self::bonded(&stash)
+				.and_then(|controller| Self::ledger(&controller, node_pub_key))
+				.map_or_else(Zero::zero, |ledger| Self::node_stake(&ledger, node_pub_key));
+
+			if stake >= bond_size {
+				// The bond size has been lowered back, the node does not need to top up.
+				if <UnderBondedNodes<T>>::take(node_pub_key).is_some() {
+					Self::deposit_event(Event::<T>::BondToppedUp(stash, node_pub_key.clone()));
+				}
+			} else if !<UnderBondedNodes<T>>::contains_key(node_pub_key) {
+				<UnderBondedNodes<T>>::insert(node_pub_key, chill_at);
+				<UnderBondedQueueRange<T>>::mutate(|(_, tail)| {
+					<UnderBondedQueue<T>>::insert(*tail, (node_pub_key.clone(), chill_at));
+					*tail += 1;
+				});
+				Self::deposit_event(Event::<T>::UnderBonded(
+					stash,
+					node_pub_key.clone(),
+					*cluster_id,
+					chill_at,
+				));
+			}
+		}
+
+		/// Check the stakes of the Storage nodes in the clusters with changed bonding params,
+		/// as far as the remaining weight allows. Returns the consumed weight.
+		fn check_pending_bonds(remaining_weight: Weight, mut consumed_weight: Weight) -> Weight {
+			// the node, its stash, controller, ledger, delegated stake and under-bonded mark are
+			// read, the mark and the under-bonded queue written
+			let check_weight = T::DbWeight::get().reads_writes(6, 3);
+			loop {
+				// the pending check and the cluster bond size are read, the check updated
+				let required_weight = consumed_weight
+					.saturating_add(T::DbWeight::get().reads_writes(2, 1))
+					.saturating_add(check_weight);
+				if remaining_weight.any_lt(required_weight) {
+					return consumed_weight
+				}
+				consumed_weight.saturating_accrue(T::DbWeight::get().reads(2));
+
+				let (cluster_id, (chill_at, last_key)) = match <PendingBondChecks<T>>::iter().next()
+				{
+					Some(check) => check,
+					None => return consumed_weight,
+				};
+				let bond_size =
+					match T::ClusterVisitor::get_bond_size(&cluster_id, NodeType::Storage) {
+						Ok(bond_size) => bond_size.saturated_into::<BalanceOf<T>>(),
+						Err(_) => {
+							<PendingBondChecks<T>>::remove(cluster_id);
+							consumed_weight.saturating_accrue(T::DbWeight::get().writes(1));
+							continue
+						},
+					};
+
+				let mut nodes = match last_key {
+					Some(last_key) =>
+						<ClusterStorages<T>>::iter_key_prefix_from(cluster_id, last_key),
+					None => <ClusterStorages<T>>::iter_key_prefix(cluster_id),
+				};
+				let mut done = false;
+				while !remaining_weight.any_lt(
+					consumed_weight
+						.saturating_add(check_weight)
+						.saturating_add(T::DbWeight::get().writes(1)),
+				) {
+					match nodes.next() {
+						Some(node_pub_key) => {
+							Self::check_bond(&node_pub_key, &cluster_id, bond_size, chill_at);
+							consumed_weight.saturating_accrue(check_weight);
+						},
+						None => {
+							done = true;
+							break
+						},
+					}
+				}
+
+				consumed_weight.saturating_accrue(T::DbWeight::get().writes(1));
+				if done {
+					<PendingBondChecks<T>>::remove(cluster_id);
+				} else {
+					<PendingBondChecks<T>>::insert(
+						cluster_id,
+						(chill_at, Some(nodes.last_raw_key().to_vec())),
+					);
+					return consumed_weight
+				}
+			}
+		}
+	}
+
+	impl<T: Config> OnClusterBondingParamsChanged<T> for Pallet<T> {
+		fn on_bonding_params_changed(
+			cluster_id: &ClusterId,
+			_bonding_params: &ClusterBondingParams<BlockNumberFor<T>>,
+		) {
+			let chill_at = <frame_system::Pallet<T>>::block_number()
+				.saturating_add(T::UnderBondedGracePeriod::get());
+			// The stakes of the nodes in the cluster are checked on idle, a check in progress
+			// starts over against the latest bond size.
+			<PendingBondChecks<T>>::insert(cluster_id, (chill_at, None::<Vec<u8>>));
+		}
 	}
 
 	impl<T: Config> StakerCreator<T, BalanceOf<T>> for Pallet<T> {
@@ -860,22 +2268,15 @@ pub mod pallet {
 			value: BalanceOf<T>,
 			cluster_id: ClusterId,
 		) -> DispatchResult {
-			Nodes::<T>::insert(&node, &stash);
-			Providers::<T>::insert(&stash, &node);
+			// An already bonded stash keeps its controller for the new node.
+			let controller = <Bonded<T>>::get(&stash).unwrap_or(controller);
 			<Bonded<T>>::insert(&stash, &controller);
-			let stash_balance = T::Currency::free_balance(&stash);
+			let stash_balance =
+				T::Currency::free_balance(&stash).saturating_sub(Self::bonded_total(&controller));
 			let value = value.min(stash_balance);
-			Self::deposit_event(Event::<T>::Bonded(stash.clone(), value));
-			let item = StakingLedger {
-				stash: stash.clone(),
-				total: value,
-				active: value,
-				chilling: Default::default(),
-				unlocking: Default::default(),
-			};
-			Self::update_ledger(&controller, &item);
+			Self::do_bond_node(stash, &controller, node.clone(), value)?;
 			match node {
-				NodePubKey::StoragePubKey(_node) => Self::do_add_storage(&stash, cluster_id),
+				NodePubKey::StoragePubKey(_) => Self::do_add_storage(&node, cluster_id),
 			}
 
 			Ok(())
@@ -887,13 +2288,24 @@ pub mod pallet {
 			node_pub_key: &NodePubKey,
 			cluster_id: &ClusterId,
 		) -> Result<bool, StakingVisitorError> {
-			let stash =
-				<Nodes<T>>::get(node_pub_key).ok_or(StakingVisitorError::NodeStakeDoesNotExist)?;
-			let maybe_storage_in_cluster = Storages::<T>::get(&stash);
+			ensure!(
+				<Nodes<T>>::contains_key(node_pub_key),
+				StakingVisitorError::NodeStakeDoesNotExist
+			);
+			let maybe_storage_in_cluster = Storages::<T>::get(node_pub_key);
 
 			let has_activated_stake: bool = maybe_storage_in_cluster
 				.is_some_and(|staking_cluster| staking_cluster == *cluster_id);
 
+			// The node stays activated during the grace period after the cluster bond size has
+			// been raised, but it is reported as under-bonded.
+			if has_activated_stake &&
+				!Self::has_bond_size(node_pub_key, cluster_id)
+					.map_err(|_| StakingVisitorError::NodeStakeIsInBadState)?
+			{
+				Err(StakingVisitorError::NodeStakeIsUnderBonded)?
+			}
+
 			Ok(has_activated_stake)
 		}
 
@@ -907,7 +2319,7 @@ pub mod pallet {
 			let controller =
 				<Bonded<T>>::get(&stash).ok_or(StakingVisitorError::NodeStakeIsInBadState)?;
 
-			let is_chilling_attempt = <Ledger<T>>::get(&controller)
+			let is_chilling_attempt = <Ledger<T>>::get(&controller, node_pub_key)
 				.ok_or(StakingVisitorError::NodeStakeIsInBadState)?
 				.chilling
 				.is_some();
@@ -916,6 +2328,42 @@ pub mod pallet {
 		}
 	}
 
+	impl<T: Config> DelegationVisitor<T, BalanceOf<T>> for Pallet<T> {
+		fn get_delegators_rewards(
+			provider_id: &T::AccountId,
+			node_pub_key: &NodePubKey,
+			reward: BalanceOf<T>,
+		) -> Vec<(T::AccountId, BalanceOf<T>)> {
+			// Only the stash bonding the node shares its reward.
+			if Self::nodes(node_pub_key).as_ref() != Some(provider_id) {
+				return Vec::new()
+			}
+			let delegated_stake = Self::delegated_stake(node_pub_key);
+			if delegated_stake.is_zero() {
+				return Vec::new()
+			}
+			let own_stake = Self::bonded(provider_id)
+				.and_then(|controller| Self::ledger(&controller, node_pub_key))
+				.map_or_else(Zero::zero, |ledger| ledger.active);
+			let stake = own_stake.saturating_add(delegated_stake);
+
+			// The delegators share the node reward pro-rata to the stake, less the provider
+			// commission.
+			let delegators_reward =
+				Perbill::from_rational(delegated_stake, stake).mul_floor(reward);
+			let delegators_reward = (Perbill::one() - Self::effective_commission(node_pub_key))
+				.mul_floor(delegators_reward);
+
+			<Delegations<T>>::iter_prefix(node_pub_key)
+				.filter_map(|(delegator, ledger)| {
+					let share = Perbill::from_rational(ledger.active, delegated_stake)
+						.mul_floor(delegators_reward);
+					(!share.is_zero()).then_some((delegator, share))
+				})
+				.collect()
+		}
+	}
+
 	impl<T> From<ClusterVisitorError> for Error<T> {
 		fn from(error: ClusterVisitorError) -> Self {
 			match error {
@@ -0,0 +1,165 @@
+#[cfg(feature = "try-runtime")]
+use frame_support::ensure;
+use frame_support::{
+	storage_alias,
+	traits::{Get, GetStorageVersion, OnRuntimeUpgrade, StorageVersion},
+	weights::Weight,
+};
+use log::info;
+use sp_std::collections::btree_map::BTreeMap;
+
+use super::*;
+
+const LOG_TARGET: &str = "ddc-staking";
+
+pub mod v0 {
+	use super::*;
+
+	#[storage_alias]
+	pub(super) type Ledger<T: Config> = StorageMap<
+		crate::Pallet<T>,
+		Blake2_128Concat,
+		<T as frame_system::Config>::AccountId,
+		StakingLedger<<T as frame_system::Config>::AccountId, BalanceOf<T>, T>,
+	>;
+
+	#[storage_alias]
+	pub(super) type Providers<T: Config> = StorageMap<
+		crate::Pallet<T>,
+		Twox64Concat,
+		<T as frame_system::Config>::AccountId,
+		NodePubKey,
+	>;
+
+	#[storage_alias]
+	pub(super) type Storages<T: Config> = StorageMap<
+		crate::Pallet<T>,
+		Twox64Concat,
+		<T as frame_system::Config>::AccountId,
+		ClusterId,
+	>;
+
+	#[storage_alias]
+	pub(super) type LeavingStorages<T: Config> = StorageMap<
+		crate::Pallet<T>,
+		Twox64Concat,
+		<T as frame_system::Config>::AccountId,
+		ClusterId,
+	>;
+}
+
+// Migrate to multiple nodes per stash
+pub fn migrate_to_v1<T: Config>() -> Weight {
+	let on_chain_version = Pallet::<T>::on_chain_storage_version();
+	if on_chain_version == 0 {
+		// All the maps are keyed by stash or controller before the migration, so they are
+		// drained before inserting the entries under their new keys.
+		let providers = v0::Providers::<T>::drain().collect::<Vec<_>>();
+		let ledgers = v0::Ledger::<T>::drain().collect::<Vec<_>>();
+		let storages = v0::Storages::<T>::drain().collect::<Vec<_>>();
+		let leaving_storages = v0::LeavingStorages::<T>::drain().collect::<Vec<_>>();
+
+		let count = providers.len() as u64;
+		info!(
+			target: LOG_TARGET,
+			" >>> Updating DDC Staking storage. Migrating {} providers...", count
+		);
+
+		let stash_nodes = providers.iter().cloned().collect::<BTreeMap<_, _>>();
+		for (stash, node_pub_key) in providers {
+			Providers::<T>::insert(stash, BoundedVec::truncate_from(vec![node_pub_key]));
+		}
+
+		for (controller, ledger) in ledgers.iter() {
+			if let Some(node_pub_key) = stash_nodes.get(&ledger.stash) {
+				info!(target: LOG_TARGET, "     Migrating ledger of node {:?}...", node_pub_key);
+				Ledger::<T>::insert(controller, node_pub_key, ledger);
+			}
+		}
+
+		for (stash, cluster_id) in storages.iter() {
+			if let Some(node_pub_key) = stash_nodes.get(stash) {
+				Pallet::<T>::do_add_storage(node_pub_key, *cluster_id);
+			}
+		}
+
+		for (stash, cluster_id) in leaving_storages.iter() {
+			if let Some(node_pub_key) = stash_nodes.get(stash) {
+				LeavingStorages::<T>::insert(node_pub_key, cluster_id);
+			}
+		}
+
+		// Update storage version.
+		StorageVersion::new(1).put::<Pallet<T>>();
+		info!(
+			target: LOG_TARGET,
+			" <<< DDC Staking storage updated! Migrated {} providers ✅", count
+		);
+
+		let entries =
+			count + ledgers.len() as u64 + storages.len() as u64 + leaving_storages.len() as u64;
+		// the Storage nodes are indexed by cluster as well
+		T::DbWeight::get().reads_writes(
+			entries + storages.len() as u64 + 1,
+			entries * 2 + storages.len() as u64 + 1,
+		)
+	} else {
+		info!(target: LOG_TARGET, " >>> Unused migration!");
+		T::DbWeight::get().reads(1)
+	}
+}
+
+pub struct MigrateToV1<T>(sp_std::marker::PhantomData<T>);
+impl<T: Config> OnRuntimeUpgrade for MigrateToV1<T> {
+	fn on_runtime_upgrade() -> Weight {
+		migrate_to_v1::<T>()
+	}
+
+	#[cfg(feature = "try-runtime")]
+	fn pre_upgrade() -> Result<Vec<u8>, &'static str> {
+		let prev_providers = v0::Providers::<T>::iter().count() as u64;
+		let prev_ledgers = v0::Ledger::<T>::iter().count() as u64;
+		let prev_storages = v0::Storages::<T>::iter().count() as u64;
+
+		Ok((prev_providers, prev_ledgers, prev_storages).encode())
+	}
+
+	#[cfg(feature = "try-runtime")]
+	fn post_upgrade(prev_state: Vec<u8>) -> Result<(), &'static str> {
+		let (prev_providers, prev_ledgers, prev_storages): (u64, u64, u64) =
+			Decode::decode(&mut &prev_state[..]).expect("pre_upgrade provides a valid state; qed");
+
+		ensure!(
+			prev_providers == Providers::<T>::iter().count() as u64,
+			"the providers count before and after the migration should be the same"
+		);
+		ensure!(
+			prev_ledgers == Ledger::<T>::iter().count() as u64,
+			"the ledgers count before and after the migration should be the same"
+		);
+		ensure!(
+			prev_storages == Storages::<T>::iter().count() as u64,
+			"the storages count before and after the migration should be the same"
+		);
+		ensure!(
+			prev_storages == ClusterStorages::<T>::iter().count() as u64,
+			"the storages should be indexed by cluster after the migration"
+		);
+
+		let current_version = Pallet::<T>::current_storage_version();
+		let on_chain_version = Pallet::<T>::on_chain_storage_version();
+
+		frame_support::ensure!(current_version >= 1, "must_upgrade");
+		ensure!(on_chain_version == 1, "after migration, the on_chain_version should be 1");
+
+		Providers::<T>::iter().try_for_each(|(stash, nodes)| -> Result<(), &'static str> {
+			ensure!(nodes.len() == 1, "At this point all the providers should have a single node");
+			ensure!(
+				nodes.iter().all(|node| Nodes::<T>::get(node) == Some(stash.clone())),
+				"the nodes of the providers should be mapped back to their stash"
+			);
+			Ok(())
+		})?;
+		Ok(())
+	}
+}
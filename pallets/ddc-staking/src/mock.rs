@@ -8,9 +8,11 @@ use ddc_primitives::{
 	traits::{
 		cluster::{ClusterManager, ClusterManagerError, ClusterVisitor, ClusterVisitorError},
 		node::{NodeVisitor, NodeVisitorError},
+		pallet::PalletVisitor,
+		validator::ValidatorVisitor,
 	},
-	ClusterBondingParams, ClusterFeesParams, ClusterGovParams, ClusterParams, ClusterPricingParams,
-	NodeParams, NodePubKey, StorageNodePubKey,
+	AssetId, ClusterBondingParams, ClusterCapabilities, ClusterFeesParams, ClusterGovParams,
+	ClusterParams, ClusterPricingParams, NodeParams, NodePubKey, StorageNodePubKey,
 };
 use frame_support::{
 	construct_runtime,
@@ -25,7 +27,7 @@ use sp_core::H256;
 use sp_io::TestExternalities;
 use sp_runtime::{
 	traits::{BlakeTwo256, IdentityLookup},
-	BuildStorage, Perquintill,
+	BuildStorage, FixedU128, Perquintill,
 };
 use sp_std::collections::btree_map::BTreeMap;
 
@@ -50,8 +52,15 @@ construct_runtime!(
 	}
 );
 
+pub(crate) const TREASURY_ACCOUNT_ID: AccountId = 7;
+pub(crate) const DAC_VALIDATOR_ACCOUNT_ID: AccountId = 5;
+
 parameter_types! {
 	pub static ExistentialDeposit: Balance = 1;
+	pub static DacValidators: Vec<AccountId> = vec![DAC_VALIDATOR_ACCOUNT_ID];
+	pub const SlashDeferDuration: BlockNumber = 10;
+	pub const UnderBondedGracePeriod: BlockNumber = 10;
+	pub static StorageBondSize: u128 = 10;
 }
 
 impl frame_system::Config for Test {
@@ -112,6 +121,10 @@ impl crate::pallet::Config for Test {
 	type NodeVisitor = MockNodeVisitor;
 	type NodeCreator = TestNodeCreator;
 	type ClusterCreator = TestClusterCreator;
+	type TreasuryVisitor = TestTreasuryVisitor;
+	type ValidatorVisitor = TestDacValidatorVisitor;
+	type SlashDeferDuration = SlashDeferDuration;
+	type UnderBondedGracePeriod = UnderBondedGracePeriod;
 }
 
 pub(crate) type DdcStakingCall = crate::Call<Test>;
@@ -150,7 +163,7 @@ impl<T: Config> ClusterVisitor<T> for TestClusterVisitor {
 		_cluster_id: &ClusterId,
 		_node_type: NodeType,
 	) -> Result<u128, ClusterVisitorError> {
-		Ok(10)
+		Ok(StorageBondSize::get())
 	}
 	fn get_chill_delay(
 		_cluster_id: &ClusterId,
@@ -173,6 +186,8 @@ impl<T: Config> ClusterVisitor<T> for TestClusterVisitor {
 			unit_per_mb_streamed: 3,
 			unit_per_put_request: 4,
 			unit_per_get_request: 5,
+			billing_period: 2_630_016,
+			pricing_tiers: Default::default(),
 		})
 	}
 
@@ -184,6 +199,10 @@ impl<T: Config> ClusterVisitor<T> for TestClusterVisitor {
 		})
 	}
 
+	fn get_customer_discount(_cluster_id: &ClusterId, _customer_id: &T::AccountId) -> Perquintill {
+		Perquintill::zero()
+	}
+
 	fn get_reserve_account_id(
 		_cluster_id: &ClusterId,
 	) -> Result<T::AccountId, ClusterVisitorError> {
@@ -212,6 +231,43 @@ impl<T: Config> ClusterVisitor<T> for TestClusterVisitor {
 				.unwrap_or_default(),
 		})
 	}
+
+	fn get_capabilities(
+		_cluster_id: &ClusterId,
+	) -> Result<ClusterCapabilities, ClusterVisitorError> {
+		Ok(ClusterCapabilities::default())
+	}
+
+	fn get_asset_rate(_cluster_id: &ClusterId, _asset_id: AssetId) -> Option<FixedU128> {
+		None
+	}
+}
+
+pub struct TestTreasuryVisitor;
+impl<T: frame_system::Config> PalletVisitor<T> for TestTreasuryVisitor {
+	fn get_account_id() -> T::AccountId {
+		T::AccountId::decode(&mut &TREASURY_ACCOUNT_ID.encode()[..]).unwrap()
+	}
+}
+
+pub struct TestDacValidatorVisitor;
+impl<T: frame_system::Config> ValidatorVisitor<T> for TestDacValidatorVisitor {
+	fn get_active_validators() -> Vec<T::AccountId> {
+		DacValidators::get()
+			.iter()
+			.map(|validator| T::AccountId::decode(&mut &validator.encode()[..]).unwrap())
+			.collect()
+	}
+
+	#[cfg(feature = "runtime-benchmarks")]
+	fn setup_validators(validators: Vec<T::AccountId>) {
+		DacValidators::set(
+			validators
+				.iter()
+				.map(|validator| AccountId::decode(&mut &validator.encode()[..]).unwrap())
+				.collect(),
+		);
+	}
 }
 
 pub struct TestClusterManager;
@@ -397,15 +453,17 @@ fn check_ledgers() {
 }
 
 fn assert_ledger_consistent(controller: AccountId) {
-	// ensures ledger.total == ledger.active + sum(ledger.unlocking).
-	let ledger = DdcStaking::ledger(controller).expect("Not a controller.");
-	let real_total: Balance = ledger.unlocking.iter().fold(ledger.active, |a, c| a + c.value);
-	assert_eq!(real_total, ledger.total);
-	assert!(
-		ledger.active >= Balances::minimum_balance() || ledger.active == 0,
-		"{}: active ledger amount ({}) must be greater than ED {}",
-		controller,
-		ledger.active,
-		Balances::minimum_balance()
-	);
+	assert!(Ledger::<Test>::iter_prefix(controller).next().is_some(), "Not a controller.");
+	// ensures ledger.total == ledger.active + sum(ledger.unlocking) for every node.
+	Ledger::<Test>::iter_prefix_values(controller).for_each(|ledger| {
+		let real_total: Balance = ledger.unlocking.iter().fold(ledger.active, |a, c| a + c.value);
+		assert_eq!(real_total, ledger.total);
+		assert!(
+			ledger.active >= Balances::minimum_balance() || ledger.active == 0,
+			"{}: active ledger amount ({}) must be greater than ED {}",
+			controller,
+			ledger.active,
+			Balances::minimum_balance()
+		);
+	});
 }
@@ -19,6 +19,7 @@ pub fn clear_activated_nodes<T: Config>() {
 	#[allow(unused_must_use)]
 	{
 		Storages::<T>::clear(u32::MAX, None);
+		ClusterStorages::<T>::clear(u32::MAX, None);
 	}
 }
 
@@ -123,6 +124,7 @@ pub fn create_stash_controller_node_with_balance<T: Config>(
 		unit_per_mb_streamed: 10,
 		unit_per_put_request: 10,
 		unit_per_get_request: 10,
+		billing_period: 2_630_016,
 	};
 	T::ClusterCreator::create_new_cluster(
 		cluster_id,
@@ -1,8 +1,13 @@
 //! Tests for the module.
 
-use ddc_primitives::StorageNodePubKey;
-use frame_support::{assert_noop, assert_ok, traits::ReservableCurrency};
+use ddc_primitives::{ClusterBondingParams, StorageNodePubKey};
+use frame_support::{
+	assert_noop, assert_ok,
+	traits::{Hooks, ReservableCurrency},
+	weights::Weight,
+};
 use pallet_balances::Error as BalancesError;
+use sp_runtime::DispatchError;
 
 use super::{mock::*, *};
 
@@ -37,7 +42,7 @@ fn basic_setup_works() {
 
 		// Account 10 controls the stash from account 11, which is 100 units
 		assert_eq!(
-			DdcStaking::ledger(10),
+			DdcStaking::ledger(10, NodePubKey::StoragePubKey(StorageNodePubKey::new([12; 32]))),
 			Some(StakingLedger {
 				stash: 11,
 				total: 100,
@@ -48,7 +53,7 @@ fn basic_setup_works() {
 		);
 		// Account 20 controls the stash from account 21, which is 100 units
 		assert_eq!(
-			DdcStaking::ledger(20),
+			DdcStaking::ledger(20, NodePubKey::StoragePubKey(StorageNodePubKey::new([22; 32]))),
 			Some(StakingLedger {
 				stash: 21,
 				total: 100,
@@ -58,7 +63,10 @@ fn basic_setup_works() {
 			})
 		);
 		// Account 1 does not control any stash
-		assert_eq!(DdcStaking::ledger(1), None);
+		assert_eq!(
+			DdcStaking::ledger(1, NodePubKey::StoragePubKey(StorageNodePubKey::new([12; 32]))),
+			None
+		);
 	});
 }
 
@@ -68,8 +76,10 @@ fn change_controller_works() {
 		// 10 and 11 are bonded as stash controller.
 		assert_eq!(DdcStaking::bonded(11), Some(10));
 
+		let node_pub_key = NodePubKey::StoragePubKey(StorageNodePubKey::new([12; 32]));
+
 		// 10 can control 11 who is initially a validator.
-		assert_ok!(DdcStaking::withdraw_unbonded(RuntimeOrigin::signed(10)));
+		assert_ok!(DdcStaking::withdraw_unbonded(RuntimeOrigin::signed(10), node_pub_key.clone()));
 
 		// Change controller.
 		assert_ok!(DdcStaking::set_controller(RuntimeOrigin::signed(11), 3));
@@ -81,11 +91,19 @@ fn change_controller_works() {
 
 		// 10 is no longer in control.
 		assert_noop!(
-			DdcStaking::store(RuntimeOrigin::signed(10), ClusterId::from([1; 20])),
+			DdcStaking::store(
+				RuntimeOrigin::signed(10),
+				node_pub_key.clone(),
+				ClusterId::from([1; 20])
+			),
 			Error::<Test>::NotController
 		);
 		// 3 is a new controller.
-		assert_ok!(DdcStaking::store(RuntimeOrigin::signed(3), ClusterId::from([1; 20])));
+		assert_ok!(DdcStaking::store(
+			RuntimeOrigin::signed(3),
+			node_pub_key,
+			ClusterId::from([1; 20])
+		));
 	})
 }
 
@@ -104,7 +122,11 @@ fn not_enough_inital_bond_flow() {
 
 		// Not enough tokens bonded to serve
 		assert_noop!(
-			DdcStaking::store(RuntimeOrigin::signed(4), ClusterId::from([1; 20])),
+			DdcStaking::store(
+				RuntimeOrigin::signed(4),
+				NodePubKey::StoragePubKey(StorageNodePubKey::new([5; 32])),
+				ClusterId::from([1; 20])
+			),
 			Error::<Test>::InsufficientBond
 		);
 
@@ -118,7 +140,11 @@ fn not_enough_inital_bond_flow() {
 
 		// Not enough tokens bonded to store
 		assert_noop!(
-			DdcStaking::store(RuntimeOrigin::signed(4), ClusterId::from([1; 20])),
+			DdcStaking::store(
+				RuntimeOrigin::signed(4),
+				NodePubKey::StoragePubKey(StorageNodePubKey::new([5; 32])),
+				ClusterId::from([1; 20])
+			),
 			Error::<Test>::InsufficientBond
 		);
 
@@ -134,12 +160,25 @@ fn not_enough_inital_bond_flow() {
 		);
 
 		// Unbond all bonded amount
-		assert_ok!(DdcStaking::unbond(RuntimeOrigin::signed(4), 5));
-		System::assert_last_event(Event::Unbonded(3, 5).into());
+		assert_ok!(DdcStaking::unbond(
+			RuntimeOrigin::signed(4),
+			NodePubKey::StoragePubKey(StorageNodePubKey::new([5; 32])),
+			5
+		));
+		System::assert_last_event(
+			Event::Unbonded(3, NodePubKey::StoragePubKey(StorageNodePubKey::new([5; 32])), 5)
+				.into(),
+		);
 		System::set_block_number(11);
 		// Withdraw unbonded tokens to clear up the stash controller pair
-		assert_ok!(DdcStaking::withdraw_unbonded(RuntimeOrigin::signed(4)));
-		System::assert_last_event(Event::Withdrawn(3, 5).into());
+		assert_ok!(DdcStaking::withdraw_unbonded(
+			RuntimeOrigin::signed(4),
+			NodePubKey::StoragePubKey(StorageNodePubKey::new([5; 32]))
+		));
+		System::assert_last_event(
+			Event::Withdrawn(3, NodePubKey::StoragePubKey(StorageNodePubKey::new([5; 32])), 5)
+				.into(),
+		);
 
 		// Bond sufficient amount
 		assert_ok!(DdcStaking::bond(
@@ -150,7 +189,11 @@ fn not_enough_inital_bond_flow() {
 		));
 
 		// Serving should work
-		assert_ok!(DdcStaking::store(RuntimeOrigin::signed(4), ClusterId::from([1; 20])));
+		assert_ok!(DdcStaking::store(
+			RuntimeOrigin::signed(4),
+			NodePubKey::StoragePubKey(StorageNodePubKey::new([5; 32])),
+			ClusterId::from([1; 20])
+		));
 	})
 }
 
@@ -167,16 +210,38 @@ fn unbonding_edge_cases_work() {
 			100
 		));
 
-		assert_ok!(DdcStaking::store(RuntimeOrigin::signed(4), ClusterId::from([1; 20])));
+		assert_ok!(DdcStaking::store(
+			RuntimeOrigin::signed(4),
+			NodePubKey::StoragePubKey(StorageNodePubKey::new([5; 32])),
+			ClusterId::from([1; 20])
+		));
 
-		assert_ok!(DdcStaking::unbond(RuntimeOrigin::signed(4), 1));
+		assert_ok!(DdcStaking::unbond(
+			RuntimeOrigin::signed(4),
+			NodePubKey::StoragePubKey(StorageNodePubKey::new([5; 32])),
+			1
+		));
 		while System::block_number() < 33 {
-			assert_ok!(DdcStaking::unbond(RuntimeOrigin::signed(4), 1));
-			System::assert_last_event(Event::Unbonded(3, 1).into());
+			assert_ok!(DdcStaking::unbond(
+				RuntimeOrigin::signed(4),
+				NodePubKey::StoragePubKey(StorageNodePubKey::new([5; 32])),
+				1
+			));
+			System::assert_last_event(
+				Event::Unbonded(3, NodePubKey::StoragePubKey(StorageNodePubKey::new([5; 32])), 1)
+					.into(),
+			);
 			System::set_block_number(System::block_number() + 1);
 		}
 
-		assert_noop!(DdcStaking::unbond(RuntimeOrigin::signed(4), 1), Error::<Test>::NoMoreChunks);
+		assert_noop!(
+			DdcStaking::unbond(
+				RuntimeOrigin::signed(4),
+				NodePubKey::StoragePubKey(StorageNodePubKey::new([5; 32])),
+				1
+			),
+			Error::<Test>::NoMoreChunks
+		);
 	})
 }
 
@@ -187,35 +252,48 @@ fn set_node_works() {
 		// 10 and 11 are bonded as stash controller.
 		assert_eq!(DdcStaking::bonded(11), Some(10));
 
+		let node_pub_key = NodePubKey::StoragePubKey(StorageNodePubKey::new([12; 32]));
+		let new_node = NodePubKey::StoragePubKey(StorageNodePubKey::new([13; 32]));
+
 		// Node is already paired
 		assert_noop!(
 			DdcStaking::set_node(
-				RuntimeOrigin::signed(10),
-				NodePubKey::StoragePubKey(StorageNodePubKey::new([12; 32]))
+				RuntimeOrigin::signed(11),
+				node_pub_key.clone(),
+				NodePubKey::StoragePubKey(StorageNodePubKey::new([22; 32]))
 			),
 			Error::<Test>::AlreadyPaired
 		);
 
+		// Node of another stash cannot be changed
+		assert_noop!(
+			DdcStaking::set_node(RuntimeOrigin::signed(21), node_pub_key.clone(), new_node.clone()),
+			Error::<Test>::NodeHasNoStake
+		);
+
 		// Node cannot be changed
 		assert_noop!(
-			DdcStaking::set_node(
-				RuntimeOrigin::signed(11),
-				NodePubKey::StoragePubKey(StorageNodePubKey::new([12; 32]))
-			),
+			DdcStaking::set_node(RuntimeOrigin::signed(11), node_pub_key.clone(), new_node.clone()),
 			Error::<Test>::AlreadyInRole
 		);
 
 		// Schedule Storage participant removal.
-		assert_ok!(DdcStaking::chill(RuntimeOrigin::signed(10)));
+		assert_ok!(DdcStaking::chill(RuntimeOrigin::signed(10), node_pub_key.clone()));
 		System::set_block_number(11);
 		// Actual Storage participant removal.
-		assert_ok!(DdcStaking::chill(RuntimeOrigin::signed(10)));
+		assert_ok!(DdcStaking::chill(RuntimeOrigin::signed(10), node_pub_key.clone()));
 
 		// Setting node should work
 		assert_ok!(DdcStaking::set_node(
 			RuntimeOrigin::signed(11),
-			NodePubKey::StoragePubKey(StorageNodePubKey::new([13; 32]))
+			node_pub_key.clone(),
+			new_node.clone()
 		));
+		assert_eq!(DdcStaking::nodes(&node_pub_key), None);
+		assert_eq!(DdcStaking::nodes(&new_node), Some(11));
+		assert_eq!(DdcStaking::providers(11).into_inner(), vec![new_node.clone()]);
+		assert_eq!(DdcStaking::ledger(10, node_pub_key), None);
+		assert_eq!(DdcStaking::ledger(10, new_node).unwrap().active, 100);
 	})
 }
 
@@ -242,19 +320,41 @@ fn cancel_previous_chill_works() {
 		));
 
 		// Not enough tokens bonded to serve
-		assert_ok!(DdcStaking::store(RuntimeOrigin::signed(4), cluster_id));
+		assert_ok!(DdcStaking::store(
+			RuntimeOrigin::signed(4),
+			NodePubKey::StoragePubKey(StorageNodePubKey::new([5; 32])),
+			cluster_id
+		));
 
-		assert_ok!(DdcStaking::store(RuntimeOrigin::signed(2), ClusterId::from([1; 20])));
+		assert_ok!(DdcStaking::store(
+			RuntimeOrigin::signed(2),
+			NodePubKey::StoragePubKey(StorageNodePubKey::new([3; 32])),
+			ClusterId::from([1; 20])
+		));
 
 		// Schedule Storage participant removal.
-		assert_ok!(DdcStaking::chill(RuntimeOrigin::signed(4)));
+		assert_ok!(DdcStaking::chill(
+			RuntimeOrigin::signed(4),
+			NodePubKey::StoragePubKey(StorageNodePubKey::new([5; 32]))
+		));
 		// Not enough tokens bonded to serve
-		assert_ok!(DdcStaking::store(RuntimeOrigin::signed(4), cluster_id));
+		assert_ok!(DdcStaking::store(
+			RuntimeOrigin::signed(4),
+			NodePubKey::StoragePubKey(StorageNodePubKey::new([5; 32])),
+			cluster_id
+		));
 
 		// Schedule Storage participant removal.
-		assert_ok!(DdcStaking::chill(RuntimeOrigin::signed(2)));
+		assert_ok!(DdcStaking::chill(
+			RuntimeOrigin::signed(2),
+			NodePubKey::StoragePubKey(StorageNodePubKey::new([3; 32]))
+		));
 		// Not enough tokens bonded to serve
-		assert_ok!(DdcStaking::store(RuntimeOrigin::signed(2), cluster_id));
+		assert_ok!(DdcStaking::store(
+			RuntimeOrigin::signed(2),
+			NodePubKey::StoragePubKey(StorageNodePubKey::new([3; 32])),
+			cluster_id
+		));
 	})
 }
 
@@ -287,9 +387,19 @@ fn staking_should_work() {
 			1500
 		));
 		let events = System::events();
-		assert_eq!(events[events.len() - 2].event, Event::Bonded(3, 1500).into());
-		assert_ok!(DdcStaking::store(RuntimeOrigin::signed(4), ClusterId::from([0; 20])));
-		System::assert_last_event(Event::Activated(3).into());
+		assert_eq!(
+			events[events.len() - 2].event,
+			Event::Bonded(3, NodePubKey::StoragePubKey(StorageNodePubKey::new([5; 32])), 1500)
+				.into()
+		);
+		assert_ok!(DdcStaking::store(
+			RuntimeOrigin::signed(4),
+			NodePubKey::StoragePubKey(StorageNodePubKey::new([5; 32])),
+			ClusterId::from([0; 20])
+		));
+		System::assert_last_event(
+			Event::Activated(3, NodePubKey::StoragePubKey(StorageNodePubKey::new([5; 32]))).into(),
+		);
 
 		// Controller already paired
 		assert_noop!(
@@ -317,7 +427,7 @@ fn staking_should_work() {
 		// participant, 5 is a DDC node.
 		assert_eq!(DdcStaking::bonded(3), Some(4));
 		assert_eq!(
-			DdcStaking::ledger(4),
+			DdcStaking::ledger(4, NodePubKey::StoragePubKey(StorageNodePubKey::new([5; 32]))),
 			Some(StakingLedger {
 				stash: 3,
 				total: 1500,
@@ -326,7 +436,10 @@ fn staking_should_work() {
 				unlocking: Default::default(),
 			})
 		);
-		assert_eq!(DdcStaking::storages(3), Some(ClusterId::from([0; 20])));
+		assert_eq!(
+			DdcStaking::storages(NodePubKey::StoragePubKey(StorageNodePubKey::new([5; 32]))),
+			Some(ClusterId::from([0; 20]))
+		);
 		assert_eq!(
 			DdcStaking::nodes(NodePubKey::StoragePubKey(StorageNodePubKey::new([5; 32]))),
 			Some(3)
@@ -336,15 +449,26 @@ fn staking_should_work() {
 		Timestamp::set_timestamp(System::block_number() * BLOCK_TIME + INIT_TIMESTAMP);
 
 		// Schedule Storage participant removal.
-		assert_ok!(DdcStaking::chill(RuntimeOrigin::signed(4)));
-		System::assert_last_event(Event::ChillSoon(3, ClusterId::from([0; 20]), 11).into());
+		assert_ok!(DdcStaking::chill(
+			RuntimeOrigin::signed(4),
+			NodePubKey::StoragePubKey(StorageNodePubKey::new([5; 32]))
+		));
+		System::assert_last_event(
+			Event::ChillSoon(
+				3,
+				NodePubKey::StoragePubKey(StorageNodePubKey::new([5; 32])),
+				ClusterId::from([0; 20]),
+				11,
+			)
+			.into(),
+		);
 
 		// Removal is scheduled, stashed value of 4 is still lock.
 		let chilling = System::block_number() + 10u64;
 		// TestClusterVisitor::get_chill_delay(&ClusterId::from([1; 20]), NodeType::Storage)
 		// 	.unwrap_or(10_u64);
 		assert_eq!(
-			DdcStaking::ledger(4),
+			DdcStaking::ledger(4, NodePubKey::StoragePubKey(StorageNodePubKey::new([5; 32]))),
 			Some(StakingLedger {
 				stash: 3,
 				total: 1500,
@@ -358,11 +482,20 @@ fn staking_should_work() {
 		assert_ok!(Balances::reserve(&3, 409));
 
 		// Too early to call chill the second time
-		assert_noop!(DdcStaking::chill(RuntimeOrigin::signed(4)), Error::<Test>::TooEarly);
+		assert_noop!(
+			DdcStaking::chill(
+				RuntimeOrigin::signed(4),
+				NodePubKey::StoragePubKey(StorageNodePubKey::new([5; 32]))
+			),
+			Error::<Test>::TooEarly
+		);
 
 		// Fast chill should not be allowed
 		assert_noop!(
-			DdcStaking::fast_chill(RuntimeOrigin::signed(4)),
+			DdcStaking::fast_chill(
+				RuntimeOrigin::signed(4),
+				NodePubKey::StoragePubKey(StorageNodePubKey::new([5; 32]))
+			),
 			Error::<Test>::FastChillProhibited
 		);
 
@@ -374,7 +507,7 @@ fn staking_should_work() {
 
 		// Ledger is not changed until we make another call to `chill`.
 		assert_eq!(
-			DdcStaking::ledger(4),
+			DdcStaking::ledger(4, NodePubKey::StoragePubKey(StorageNodePubKey::new([5; 32]))),
 			Some(StakingLedger {
 				stash: 3,
 				total: 1500,
@@ -385,11 +518,19 @@ fn staking_should_work() {
 		);
 
 		// Actual Storage participant removal.
-		assert_ok!(DdcStaking::chill(RuntimeOrigin::signed(4)));
-		System::assert_last_event(Event::Chilled(3).into());
+		assert_ok!(DdcStaking::chill(
+			RuntimeOrigin::signed(4),
+			NodePubKey::StoragePubKey(StorageNodePubKey::new([5; 32]))
+		));
+		System::assert_last_event(
+			Event::Chilled(3, NodePubKey::StoragePubKey(StorageNodePubKey::new([5; 32]))).into(),
+		);
 
-		// Account 3 is no longer a Storage participant.
-		assert_eq!(DdcStaking::storages(3), None);
+		// Node 5 is no longer a Storage participant.
+		assert_eq!(
+			DdcStaking::storages(NodePubKey::StoragePubKey(StorageNodePubKey::new([5; 32]))),
+			None
+		);
 	});
 }
 
@@ -426,21 +567,30 @@ fn storage_full_unbonding_works() {
 		let events = System::events();
 		assert_eq!(
 			events[events.len() - 2].event,
-			Event::Bonded(provider_stash, storage_bond_size).into()
+			Event::Bonded(provider_stash, node_pub_key.clone(), storage_bond_size).into()
 		);
-		assert_ok!(DdcStaking::store(RuntimeOrigin::signed(provider_controller), cluster_id));
-		System::assert_last_event(Event::Activated(provider_stash).into());
+		assert_ok!(DdcStaking::store(
+			RuntimeOrigin::signed(provider_controller),
+			node_pub_key.clone(),
+			cluster_id
+		));
+		System::assert_last_event(Event::Activated(provider_stash, node_pub_key.clone()).into());
 
-		assert_eq!(DdcStaking::storages(provider_stash), Some(cluster_id));
-		assert_eq!(DdcStaking::nodes(node_pub_key), Some(provider_stash));
+		assert_eq!(DdcStaking::storages(&node_pub_key), Some(cluster_id));
+		assert_eq!(DdcStaking::nodes(&node_pub_key), Some(provider_stash));
 
 		// Set block timestamp.
 		Timestamp::set_timestamp(System::block_number() * BLOCK_TIME + INIT_TIMESTAMP);
 
 		// Schedule Storage participant removal.
-		assert_ok!(DdcStaking::chill(RuntimeOrigin::signed(provider_controller)));
+		assert_ok!(DdcStaking::chill(
+			RuntimeOrigin::signed(provider_controller),
+			node_pub_key.clone()
+		));
 		let chilling = System::block_number() + storage_chill_delay;
-		System::assert_last_event(Event::ChillSoon(provider_stash, cluster_id, chilling).into());
+		System::assert_last_event(
+			Event::ChillSoon(provider_stash, node_pub_key.clone(), cluster_id, chilling).into(),
+		);
 
 		// Set the block number that allows us to chill.
 		while System::block_number() < chilling {
@@ -449,20 +599,26 @@ fn storage_full_unbonding_works() {
 		}
 
 		// Actual Storage participant removal.
-		assert_ok!(DdcStaking::chill(RuntimeOrigin::signed(provider_controller)));
-		System::assert_last_event(Event::Chilled(provider_stash).into());
+		assert_ok!(DdcStaking::chill(
+			RuntimeOrigin::signed(provider_controller),
+			node_pub_key.clone()
+		));
+		System::assert_last_event(Event::Chilled(provider_stash, node_pub_key.clone()).into());
 
 		// Account is no longer a Storage participant.
-		assert_eq!(DdcStaking::storages(provider_stash), None);
+		assert_eq!(DdcStaking::storages(&node_pub_key), None);
 
 		// Start unbonding all tokens
 		assert_ok!(DdcStaking::unbond(
 			RuntimeOrigin::signed(provider_controller),
+			node_pub_key.clone(),
 			storage_bond_size
 		));
-		System::assert_has_event(Event::LeaveSoon(provider_stash).into());
-		assert_eq!(DdcStaking::leaving_storages(provider_stash), Some(cluster_id));
-		System::assert_last_event(Event::Unbonded(provider_stash, storage_bond_size).into());
+		System::assert_has_event(Event::LeaveSoon(provider_stash, node_pub_key.clone()).into());
+		assert_eq!(DdcStaking::leaving_storages(&node_pub_key), Some(cluster_id));
+		System::assert_last_event(
+			Event::Unbonded(provider_stash, node_pub_key.clone(), storage_bond_size).into(),
+		);
 
 		let unbonding = System::block_number() + storage_unbond_delay;
 		// Set the block number that allows us to chill.
@@ -471,10 +627,79 @@ fn storage_full_unbonding_works() {
 			Timestamp::set_timestamp(System::block_number() * BLOCK_TIME + INIT_TIMESTAMP);
 		}
 
-		assert_ok!(DdcStaking::withdraw_unbonded(RuntimeOrigin::signed(provider_controller)));
-		System::assert_has_event(Event::Withdrawn(provider_stash, storage_bond_size).into());
-		assert_eq!(DdcStaking::leaving_storages(provider_stash), None);
-		System::assert_last_event(Event::Left(provider_stash).into());
+		assert_ok!(DdcStaking::withdraw_unbonded(
+			RuntimeOrigin::signed(provider_controller),
+			node_pub_key.clone()
+		));
+		System::assert_has_event(
+			Event::Withdrawn(provider_stash, node_pub_key.clone(), storage_bond_size).into(),
+		);
+		assert_eq!(DdcStaking::leaving_storages(&node_pub_key), None);
+		System::assert_last_event(Event::Left(provider_stash, node_pub_key).into());
+
+		MockNodeVisitor::reset_and_release_lock(lock);
+	});
+}
+
+#[test]
+fn rebond_works() {
+	ExtBuilder::default().build_and_execute(|| {
+		System::set_block_number(1);
+
+		let cluster_id = ClusterId::from([1; 20]);
+		let node_pub_key = NodePubKey::StoragePubKey(StorageNodePubKey::new([5; 32]));
+
+		let lock = MockNodeVisitor::set_and_hold_lock(MockNode {
+			cluster_id: Some(cluster_id),
+			exists: true,
+		});
+
+		assert_ok!(DdcStaking::bond(RuntimeOrigin::signed(3), 4, node_pub_key.clone(), 100));
+		assert_ok!(DdcStaking::store(RuntimeOrigin::signed(4), node_pub_key.clone(), cluster_id));
+
+		// Nothing to rebond.
+		assert_noop!(
+			DdcStaking::rebond(RuntimeOrigin::signed(4), node_pub_key.clone(), 10),
+			Error::<Test>::NoUnlockChunk
+		);
+
+		// Chill and unbond below the bond size to leave the cluster.
+		assert_ok!(DdcStaking::chill(RuntimeOrigin::signed(4), node_pub_key.clone()));
+		System::set_block_number(11);
+		assert_ok!(DdcStaking::chill(RuntimeOrigin::signed(4), node_pub_key.clone()));
+		assert_ok!(DdcStaking::unbond(RuntimeOrigin::signed(4), node_pub_key.clone(), 95));
+		System::set_block_number(12);
+		assert_ok!(DdcStaking::unbond(RuntimeOrigin::signed(4), node_pub_key.clone(), 3));
+		assert_eq!(DdcStaking::leaving_storages(&node_pub_key), Some(cluster_id));
+
+		// Rebonding below the bond size keeps the node leaving.
+		assert_ok!(DdcStaking::rebond(RuntimeOrigin::signed(4), node_pub_key.clone(), 2));
+		System::assert_last_event(Event::Rebonded(3, node_pub_key.clone(), 2).into());
+		assert_eq!(DdcStaking::leaving_storages(&node_pub_key), Some(cluster_id));
+
+		// The newest chunks are rebonded first, the leave is cancelled at the bond size.
+		assert_ok!(DdcStaking::rebond(RuntimeOrigin::signed(4), node_pub_key.clone(), 10));
+		System::assert_has_event(Event::Rebonded(3, node_pub_key.clone(), 10).into());
+		System::assert_last_event(
+			Event::LeaveCancelled(3, node_pub_key.clone(), cluster_id).into(),
+		);
+		assert_eq!(DdcStaking::leaving_storages(&node_pub_key), None);
+		let ledger = DdcStaking::ledger(4, &node_pub_key).unwrap();
+		assert_eq!(ledger.active, 14);
+		assert_eq!(ledger.total, 100);
+		assert_eq!(ledger.unlocking.len(), 1);
+		assert_eq!(ledger.unlocking[0].value, 86);
+		assert_eq!(ledger.unlocking[0].block, 21);
+
+		// The node can participate in the cluster again.
+		assert_ok!(DdcStaking::store(RuntimeOrigin::signed(4), node_pub_key.clone(), cluster_id));
+
+		// Rebonding more than unlocking rebonds everything.
+		assert_ok!(DdcStaking::rebond(RuntimeOrigin::signed(4), node_pub_key.clone(), 1000));
+		System::assert_last_event(Event::Rebonded(3, node_pub_key.clone(), 86).into());
+		let ledger = DdcStaking::ledger(4, &node_pub_key).unwrap();
+		assert_eq!(ledger.active, 100);
+		assert!(ledger.unlocking.is_empty());
 
 		MockNodeVisitor::reset_and_release_lock(lock);
 	});
@@ -502,6 +727,80 @@ fn staking_creator_works() {
 	});
 }
 
+#[test]
+fn multiple_nodes_per_stash_work() {
+	ExtBuilder::default().build_and_execute(|| {
+		System::set_block_number(1);
+
+		let cluster_id = ClusterId::from([1; 20]);
+		let node_pub_key = NodePubKey::StoragePubKey(StorageNodePubKey::new([12; 32]));
+		let second_node = NodePubKey::StoragePubKey(StorageNodePubKey::new([13; 32]));
+		let third_node = NodePubKey::StoragePubKey(StorageNodePubKey::new([14; 32]));
+
+		let _ = Balances::make_free_balance_be(&11, 300);
+
+		// Only a bonded stash can bond more nodes.
+		assert_noop!(
+			DdcStaking::bond_node(RuntimeOrigin::signed(3), second_node.clone(), 100),
+			Error::<Test>::NotStash
+		);
+		assert_noop!(
+			DdcStaking::bond_node(RuntimeOrigin::signed(11), node_pub_key.clone(), 100),
+			Error::<Test>::AlreadyPaired
+		);
+
+		// Account 11 bonds two more nodes, limited by the balance not bonded yet.
+		assert_ok!(DdcStaking::bond_node(RuntimeOrigin::signed(11), second_node.clone(), 100));
+		System::assert_last_event(Event::Bonded(11, second_node.clone(), 100).into());
+		assert_ok!(DdcStaking::bond_node(RuntimeOrigin::signed(11), third_node.clone(), 500));
+		System::assert_last_event(Event::Bonded(11, third_node.clone(), 100).into());
+		assert_eq!(
+			DdcStaking::providers(11).into_inner(),
+			vec![node_pub_key.clone(), second_node.clone(), third_node.clone()]
+		);
+		assert_eq!(DdcStaking::nodes(&third_node), Some(11));
+		assert_eq!(Balances::locks(11)[0].amount, 300);
+		assert_noop!(
+			DdcStaking::bond_node(
+				RuntimeOrigin::signed(11),
+				NodePubKey::StoragePubKey(StorageNodePubKey::new([15; 32])),
+				100
+			),
+			Error::<Test>::InsufficientBond
+		);
+
+		// Each node joins a cluster and chills on its own.
+		assert_ok!(DdcStaking::store(RuntimeOrigin::signed(10), second_node.clone(), cluster_id));
+		System::assert_last_event(Event::Activated(11, second_node.clone()).into());
+		assert_eq!(DdcStaking::storages(&second_node), Some(cluster_id));
+		assert_ok!(DdcStaking::chill(RuntimeOrigin::signed(10), node_pub_key.clone()));
+		System::assert_last_event(
+			Event::ChillSoon(11, node_pub_key.clone(), cluster_id, 11).into(),
+		);
+		assert_eq!(DdcStaking::ledger(10, &node_pub_key).unwrap().chilling, Some(11));
+		assert_eq!(DdcStaking::ledger(10, &second_node).unwrap().chilling, None);
+
+		// Fully unbonding a node removes it from the stash, the other nodes stay bonded.
+		assert_ok!(DdcStaking::unbond(RuntimeOrigin::signed(10), third_node.clone(), 100));
+		assert_ok!(DdcStaking::withdraw_unbonded(RuntimeOrigin::signed(10), third_node.clone()));
+		System::assert_last_event(Event::Withdrawn(11, third_node.clone(), 100).into());
+		assert_eq!(DdcStaking::nodes(&third_node), None);
+		assert_eq!(DdcStaking::ledger(10, &third_node), None);
+		assert_eq!(
+			DdcStaking::providers(11).into_inner(),
+			vec![node_pub_key.clone(), second_node.clone()]
+		);
+		assert_eq!(DdcStaking::bonded(11), Some(10));
+		assert_eq!(Balances::locks(11)[0].amount, 200);
+
+		// The controller moves with all the nodes of the stash.
+		assert_ok!(DdcStaking::set_controller(RuntimeOrigin::signed(11), 3));
+		assert!(DdcStaking::ledger(3, &node_pub_key).is_some());
+		assert!(DdcStaking::ledger(3, &second_node).is_some());
+		assert!(DdcStaking::ledger(10, &second_node).is_none());
+	});
+}
+
 #[test]
 fn staking_visitor_works() {
 	// Verifies initial conditions of mock
@@ -520,7 +819,11 @@ fn staking_visitor_works() {
 			assert!(!result);
 		}
 
-		assert_ok!(DdcStaking::store(RuntimeOrigin::signed(4), ClusterId::from([1; 20])));
+		assert_ok!(DdcStaking::store(
+			RuntimeOrigin::signed(4),
+			NodePubKey::StoragePubKey(StorageNodePubKey::new([5; 32])),
+			ClusterId::from([1; 20])
+		));
 
 		if let Ok(result) =
 			<DdcStaking as StakingVisitor<Test>>::has_activated_stake(&node_pub_key, &cluster_id)
@@ -529,3 +832,743 @@ fn staking_visitor_works() {
 		}
 	});
 }
+
+#[test]
+fn slashing_works() {
+	ExtBuilder::default().build_and_execute(|| {
+		System::set_block_number(1);
+
+		let lock = MockNodeVisitor::set_and_hold_lock(MockNode { cluster_id: None, exists: true });
+
+		let node_pub_key = NodePubKey::StoragePubKey(StorageNodePubKey::new([12; 32]));
+
+		// Account 11 has 40 units unlocking.
+		assert_ok!(DdcStaking::unbond(RuntimeOrigin::signed(10), node_pub_key.clone(), 40));
+
+		// Only governance and DAC validators can report offences.
+		assert_noop!(
+			DdcStaking::report_offence(
+				RuntimeOrigin::signed(1),
+				node_pub_key.clone(),
+				Offence::DataLoss
+			),
+			Error::<Test>::NotReporter
+		);
+		assert_noop!(
+			DdcStaking::report_offence(
+				RuntimeOrigin::root(),
+				node_pub_key.clone(),
+				Offence::DataLoss
+			),
+			Error::<Test>::NoSlashFraction
+		);
+
+		assert_ok!(DdcStaking::set_slash_fraction(
+			RuntimeOrigin::root(),
+			Offence::DataLoss,
+			Perbill::from_percent(10)
+		));
+		System::assert_last_event(
+			Event::SlashFractionSet(Offence::DataLoss, Perbill::from_percent(10)).into(),
+		);
+
+		assert_ok!(DdcStaking::report_offence(
+			RuntimeOrigin::signed(DAC_VALIDATOR_ACCOUNT_ID),
+			node_pub_key.clone(),
+			Offence::DataLoss
+		));
+		let apply_at = 1 + SlashDeferDuration::get();
+		System::assert_last_event(
+			Event::SlashReported(node_pub_key.clone(), Offence::DataLoss, apply_at).into(),
+		);
+		assert_noop!(
+			DdcStaking::report_offence(
+				RuntimeOrigin::root(),
+				node_pub_key.clone(),
+				Offence::DataLoss
+			),
+			Error::<Test>::SlashAlreadyReported
+		);
+
+		// The stake can not leave before the slash is settled.
+		assert_noop!(
+			DdcStaking::withdraw_unbonded(RuntimeOrigin::signed(10), node_pub_key.clone()),
+			Error::<Test>::SlashPending
+		);
+		assert_noop!(
+			DdcStaking::apply_slash(
+				RuntimeOrigin::signed(1),
+				node_pub_key.clone(),
+				Offence::DataLoss
+			),
+			Error::<Test>::TooEarly
+		);
+
+		System::set_block_number(apply_at);
+		assert_ok!(DdcStaking::apply_slash(
+			RuntimeOrigin::signed(1),
+			node_pub_key.clone(),
+			Offence::DataLoss
+		));
+		System::assert_last_event(
+			Event::Slashed(11, Offence::DataLoss, 10, TREASURY_ACCOUNT_ID).into(),
+		);
+
+		// Both the active bond and the unlocking chunk are slashed by 10%.
+		let ledger = DdcStaking::ledger(10, &node_pub_key).unwrap();
+		assert_eq!(ledger.active, 54);
+		assert_eq!(ledger.unlocking[0].value, 36);
+		assert_eq!(ledger.total, 90);
+		assert_eq!(Balances::free_balance(11), 90);
+		assert_eq!(Balances::locks(11)[0].amount, 90);
+		assert_eq!(Balances::free_balance(TREASURY_ACCOUNT_ID), 10);
+
+		assert_noop!(
+			DdcStaking::apply_slash(
+				RuntimeOrigin::signed(1),
+				node_pub_key.clone(),
+				Offence::DataLoss
+			),
+			Error::<Test>::NoUnappliedSlash
+		);
+		assert_ok!(DdcStaking::withdraw_unbonded(RuntimeOrigin::signed(10), node_pub_key.clone()));
+		System::assert_last_event(Event::Withdrawn(11, node_pub_key, 36).into());
+
+		MockNodeVisitor::reset_and_release_lock(lock);
+	});
+}
+
+#[test]
+fn slash_can_be_cancelled_or_applied_on_idle() {
+	ExtBuilder::default().build_and_execute(|| {
+		System::set_block_number(1);
+
+		let lock = MockNodeVisitor::set_and_hold_lock(MockNode { cluster_id: None, exists: true });
+
+		let node_pub_key = NodePubKey::StoragePubKey(StorageNodePubKey::new([22; 32]));
+		assert_ok!(DdcStaking::set_slash_fraction(
+			RuntimeOrigin::root(),
+			Offence::Downtime,
+			Perbill::from_percent(50)
+		));
+		assert_ok!(DdcStaking::report_offence(
+			RuntimeOrigin::root(),
+			node_pub_key.clone(),
+			Offence::Downtime
+		));
+
+		// Only governance can cancel a slash.
+		assert_noop!(
+			DdcStaking::cancel_slash(
+				RuntimeOrigin::signed(1),
+				node_pub_key.clone(),
+				Offence::Downtime
+			),
+			DispatchError::BadOrigin
+		);
+		assert_ok!(DdcStaking::cancel_slash(
+			RuntimeOrigin::root(),
+			node_pub_key.clone(),
+			Offence::Downtime
+		));
+		System::assert_last_event(
+			Event::SlashCancelled(node_pub_key.clone(), Offence::Downtime).into(),
+		);
+		assert_noop!(
+			DdcStaking::cancel_slash(
+				RuntimeOrigin::root(),
+				node_pub_key.clone(),
+				Offence::Downtime
+			),
+			Error::<Test>::NoUnappliedSlash
+		);
+
+		// Cancelled slashes are never applied.
+		System::set_block_number(1 + SlashDeferDuration::get());
+		DdcStaking::on_idle(System::block_number(), Weight::MAX);
+		assert_eq!(DdcStaking::ledger(20, &node_pub_key).unwrap().active, 100);
+
+		assert_ok!(DdcStaking::report_offence(
+			RuntimeOrigin::root(),
+			node_pub_key.clone(),
+			Offence::Downtime
+		));
+		let apply_at = System::block_number() + SlashDeferDuration::get();
+
+		// Pending slashes are not applied before their block.
+		DdcStaking::on_idle(apply_at - 1, Weight::MAX);
+		assert!(DdcStaking::unapplied_slash(&node_pub_key, Offence::Downtime).is_some());
+
+		// Not enough weight to apply the slash.
+		assert_eq!(DdcStaking::on_idle(apply_at, Weight::zero()), Weight::zero());
+		assert!(DdcStaking::unapplied_slash(&node_pub_key, Offence::Downtime).is_some());
+
+		DdcStaking::on_idle(apply_at, Weight::MAX);
+		assert!(DdcStaking::unapplied_slash(&node_pub_key, Offence::Downtime).is_none());
+		assert_eq!(DdcStaking::ledger(20, &node_pub_key).unwrap().active, 50);
+		assert_eq!(Balances::free_balance(TREASURY_ACCOUNT_ID), 50);
+
+		MockNodeVisitor::reset_and_release_lock(lock);
+	});
+}
+
+#[test]
+fn offence_is_reported_by_validators_majority() {
+	ExtBuilder::default().build_and_execute(|| {
+		System::set_block_number(1);
+
+		let lock = MockNodeVisitor::set_and_hold_lock(MockNode { cluster_id: None, exists: true });
+
+		let node_pub_key = NodePubKey::StoragePubKey(StorageNodePubKey::new([12; 32]));
+		DacValidators::set(vec![DAC_VALIDATOR_ACCOUNT_ID, 1, 2]);
+		assert_ok!(DdcStaking::set_slash_fraction(
+			RuntimeOrigin::root(),
+			Offence::DataLoss,
+			Perbill::from_percent(10)
+		));
+
+		assert_ok!(DdcStaking::report_offence(
+			RuntimeOrigin::signed(DAC_VALIDATOR_ACCOUNT_ID),
+			node_pub_key.clone(),
+			Offence::DataLoss
+		));
+		System::assert_last_event(
+			Event::OffenceVoted(DAC_VALIDATOR_ACCOUNT_ID, node_pub_key.clone(), Offence::DataLoss)
+				.into(),
+		);
+		assert!(DdcStaking::unapplied_slash(&node_pub_key, Offence::DataLoss).is_none());
+		assert_noop!(
+			DdcStaking::report_offence(
+				RuntimeOrigin::signed(DAC_VALIDATOR_ACCOUNT_ID),
+				node_pub_key.clone(),
+				Offence::DataLoss
+			),
+			Error::<Test>::AlreadyVoted
+		);
+
+		// Votes of the validators that left the active set do not count.
+		DacValidators::set(vec![1, 2, 3]);
+		assert_ok!(DdcStaking::report_offence(
+			RuntimeOrigin::signed(1),
+			node_pub_key.clone(),
+			Offence::DataLoss
+		));
+		assert!(DdcStaking::unapplied_slash(&node_pub_key, Offence::DataLoss).is_none());
+
+		assert_ok!(DdcStaking::report_offence(
+			RuntimeOrigin::signed(2),
+			node_pub_key.clone(),
+			Offence::DataLoss
+		));
+		let apply_at = 1 + SlashDeferDuration::get();
+		System::assert_last_event(
+			Event::SlashReported(node_pub_key.clone(), Offence::DataLoss, apply_at).into(),
+		);
+		assert_eq!(
+			DdcStaking::unapplied_slash(&node_pub_key, Offence::DataLoss),
+			Some(UnappliedSlash {
+				fraction: Perbill::from_percent(10),
+				reporter: Some(2),
+				apply_at
+			})
+		);
+		assert!(DdcStaking::offence_votes(&node_pub_key, Offence::DataLoss).is_empty());
+		assert_eq!(DdcStaking::slash_queue(0), Some((node_pub_key, Offence::DataLoss, apply_at)));
+
+		MockNodeVisitor::reset_and_release_lock(lock);
+	});
+}
+
+#[test]
+fn failed_slash_is_not_retried_on_idle() {
+	ExtBuilder::default().build_and_execute(|| {
+		System::set_block_number(1);
+
+		let lock = MockNodeVisitor::set_and_hold_lock(MockNode { cluster_id: None, exists: true });
+
+		let node_pub_key = NodePubKey::StoragePubKey(StorageNodePubKey::new([12; 32]));
+		assert_ok!(DdcStaking::set_slash_fraction(
+			RuntimeOrigin::root(),
+			Offence::DataLoss,
+			Perbill::from_percent(10)
+		));
+		assert_ok!(DdcStaking::report_offence(
+			RuntimeOrigin::root(),
+			node_pub_key.clone(),
+			Offence::DataLoss
+		));
+		let apply_at = 1 + SlashDeferDuration::get();
+
+		// The stash of the node has lost its controller.
+		Bonded::<Test>::remove(11);
+		DdcStaking::on_idle(apply_at, Weight::MAX);
+		System::assert_last_event(
+			Event::SlashFailed(
+				node_pub_key.clone(),
+				Offence::DataLoss,
+				Error::<Test>::BadState.into(),
+			)
+			.into(),
+		);
+
+		// The slash is left pending for governance instead of being retried.
+		assert!(DdcStaking::unapplied_slash(&node_pub_key, Offence::DataLoss).is_some());
+		assert_eq!(SlashQueueRange::<Test>::get(), (1, 1));
+		assert_eq!(
+			DdcStaking::on_idle(apply_at + 1, Weight::MAX),
+			<Test as frame_system::Config>::DbWeight::get().reads(1)
+		);
+
+		MockNodeVisitor::reset_and_release_lock(lock);
+	});
+}
+
+#[test]
+fn delegation_counts_towards_bond_size() {
+	ExtBuilder::default().build_and_execute(|| {
+		System::set_block_number(1);
+
+		let cluster_id = ClusterId::from([1; 20]);
+		let lock = MockNodeVisitor::set_and_hold_lock(MockNode {
+			cluster_id: Some(cluster_id),
+			exists: true,
+		});
+
+		let node_pub_key = NodePubKey::StoragePubKey(StorageNodePubKey::new([5; 32]));
+		let unknown_node = NodePubKey::StoragePubKey(StorageNodePubKey::new([6; 32]));
+
+		// Account 3 bonds less than the bond size of the cluster.
+		assert_ok!(DdcStaking::bond(RuntimeOrigin::signed(3), 4, node_pub_key.clone(), 5));
+		assert_noop!(
+			DdcStaking::store(
+				RuntimeOrigin::signed(4),
+				NodePubKey::StoragePubKey(StorageNodePubKey::new([5; 32])),
+				cluster_id
+			),
+			Error::<Test>::InsufficientBond
+		);
+
+		assert_noop!(
+			DdcStaking::delegate(RuntimeOrigin::signed(1), unknown_node, 5),
+			Error::<Test>::NodeHasNoStake
+		);
+		assert_noop!(
+			DdcStaking::delegate(RuntimeOrigin::signed(1), node_pub_key.clone(), 0),
+			Error::<Test>::InsufficientBond
+		);
+		// The balance bonded by a stash can not be delegated.
+		assert_noop!(
+			DdcStaking::delegate(RuntimeOrigin::signed(11), node_pub_key.clone(), 5),
+			Error::<Test>::InsufficientBond
+		);
+
+		// Delegated stake makes up for the missing bond.
+		assert_ok!(DdcStaking::delegate(RuntimeOrigin::signed(1), node_pub_key.clone(), 5));
+		System::assert_last_event(Event::Delegated(1, node_pub_key.clone(), 5).into());
+		assert_eq!(DdcStaking::delegated_stake(&node_pub_key), 5);
+		assert_eq!(DdcStaking::node_delegators_count(&node_pub_key), 1);
+		assert_eq!(Balances::reserved_balance(1), 5);
+		assert!(Balances::locks(1).is_empty());
+		assert_ok!(DdcStaking::store(
+			RuntimeOrigin::signed(4),
+			NodePubKey::StoragePubKey(StorageNodePubKey::new([5; 32])),
+			cluster_id
+		));
+
+		// The provider can not unbond below the bond size counting the delegated stake.
+		assert_noop!(
+			DdcStaking::unbond(
+				RuntimeOrigin::signed(4),
+				NodePubKey::StoragePubKey(StorageNodePubKey::new([5; 32])),
+				1
+			),
+			Error::<Test>::InsufficientBond
+		);
+
+		// Neither can the delegators while the node is activated.
+		assert_noop!(
+			DdcStaking::undelegate(RuntimeOrigin::signed(1), node_pub_key.clone(), 1),
+			Error::<Test>::InsufficientBond
+		);
+
+		// The node leaves the cluster once the stake delegated to the chilled node is unlocked.
+		assert_ok!(DdcStaking::chill(RuntimeOrigin::signed(4), node_pub_key.clone()));
+		System::set_block_number(11);
+		assert_ok!(DdcStaking::chill(RuntimeOrigin::signed(4), node_pub_key.clone()));
+		assert_ok!(DdcStaking::undelegate(RuntimeOrigin::signed(1), node_pub_key.clone(), 5));
+		System::assert_has_event(Event::LeaveSoon(3, node_pub_key.clone()).into());
+		System::assert_last_event(Event::Undelegated(1, node_pub_key.clone(), 5).into());
+		assert_eq!(DdcStaking::delegated_stake(&node_pub_key), 0);
+		assert_eq!(DdcStaking::leaving_storages(&node_pub_key), Some(cluster_id));
+
+		// Delegated stake is unlocked after the unbonding delay of the cluster.
+		assert_ok!(DdcStaking::withdraw_undelegated(
+			RuntimeOrigin::signed(1),
+			node_pub_key.clone()
+		));
+		assert!(DdcStaking::delegation(&node_pub_key, 1).is_some());
+		assert_eq!(Balances::reserved_balance(1), 5);
+
+		System::set_block_number(21);
+		assert_ok!(DdcStaking::withdraw_undelegated(
+			RuntimeOrigin::signed(1),
+			node_pub_key.clone()
+		));
+		System::assert_has_event(Event::DelegationWithdrawn(1, node_pub_key.clone(), 5).into());
+		System::assert_last_event(Event::Left(3, node_pub_key.clone()).into());
+		assert!(DdcStaking::delegation(&node_pub_key, 1).is_none());
+		assert_eq!(DdcStaking::node_delegators_count(&node_pub_key), 0);
+		assert_eq!(DdcStaking::leaving_storages(&node_pub_key), None);
+		assert_eq!(Balances::reserved_balance(1), 0);
+
+		assert_noop!(
+			DdcStaking::undelegate(RuntimeOrigin::signed(1), node_pub_key, 5),
+			Error::<Test>::NoDelegation
+		);
+
+		MockNodeVisitor::reset_and_release_lock(lock);
+	});
+}
+
+#[test]
+fn delegators_share_provider_rewards() {
+	ExtBuilder::default().build_and_execute(|| {
+		let node_pub_key = NodePubKey::StoragePubKey(StorageNodePubKey::new([12; 32]));
+
+		// Account 11 has 100 units bonded and keeps all of its rewards.
+		assert!(<DdcStaking as DelegationVisitor<Test, Balance>>::get_delegators_rewards(
+			&11,
+			&node_pub_key,
+			1000
+		)
+		.is_empty());
+
+		assert_ok!(DdcStaking::delegate(RuntimeOrigin::signed(1), node_pub_key.clone(), 60));
+		assert_ok!(DdcStaking::delegate(RuntimeOrigin::signed(2), node_pub_key.clone(), 40));
+		assert_ok!(DdcStaking::set_commission(
+			RuntimeOrigin::signed(10),
+			node_pub_key.clone(),
+			Perbill::from_percent(10)
+		));
+		System::assert_last_event(
+			Event::CommissionSet(node_pub_key.clone(), Perbill::from_percent(10)).into(),
+		);
+
+		// Half of the reward belongs to the delegators, less 10% of commission.
+		let mut rewards = <DdcStaking as DelegationVisitor<Test, Balance>>::get_delegators_rewards(
+			&11,
+			&node_pub_key,
+			1000,
+		);
+		rewards.sort();
+		assert_eq!(rewards, vec![(1, 270), (2, 180)]);
+
+		// Not the provider stash of the node.
+		assert!(<DdcStaking as DelegationVisitor<Test, Balance>>::get_delegators_rewards(
+			&21,
+			&node_pub_key,
+			1000
+		)
+		.is_empty());
+
+		// Delegators have chosen the node of the provider.
+		assert_noop!(
+			DdcStaking::set_node(
+				RuntimeOrigin::signed(11),
+				node_pub_key,
+				NodePubKey::StoragePubKey(StorageNodePubKey::new([13; 32]))
+			),
+			Error::<Test>::NodeHasDelegators
+		);
+	});
+}
+
+#[test]
+fn delegated_stake_is_slashed_pro_rata() {
+	ExtBuilder::default().build_and_execute(|| {
+		System::set_block_number(1);
+		let node_pub_key = NodePubKey::StoragePubKey(StorageNodePubKey::new([12; 32]));
+
+		assert_ok!(DdcStaking::delegate(RuntimeOrigin::signed(1), node_pub_key.clone(), 60));
+		assert_ok!(DdcStaking::delegate(RuntimeOrigin::signed(2), node_pub_key.clone(), 40));
+		assert_ok!(DdcStaking::undelegate(RuntimeOrigin::signed(2), node_pub_key.clone(), 20));
+		assert_ok!(DdcStaking::set_slash_fraction(
+			RuntimeOrigin::root(),
+			Offence::DataLoss,
+			Perbill::from_percent(10)
+		));
+		assert_ok!(DdcStaking::report_offence(
+			RuntimeOrigin::root(),
+			node_pub_key.clone(),
+			Offence::DataLoss
+		));
+
+		// The delegated stake can not leave before the slash is settled.
+		System::set_block_number(11);
+		assert_noop!(
+			DdcStaking::withdraw_undelegated(RuntimeOrigin::signed(2), node_pub_key.clone()),
+			Error::<Test>::SlashPending
+		);
+
+		assert_ok!(DdcStaking::apply_slash(
+			RuntimeOrigin::signed(3),
+			node_pub_key.clone(),
+			Offence::DataLoss
+		));
+		System::assert_has_event(
+			Event::DelegationSlashed(1, node_pub_key.clone(), Offence::DataLoss, 6).into(),
+		);
+		System::assert_has_event(
+			Event::DelegationSlashed(2, node_pub_key.clone(), Offence::DataLoss, 4).into(),
+		);
+		System::assert_last_event(
+			Event::Slashed(11, Offence::DataLoss, 10, TREASURY_ACCOUNT_ID).into(),
+		);
+
+		// Both the active and the unlocking delegated stake are slashed by 10%.
+		assert_eq!(DdcStaking::delegation(&node_pub_key, 1).unwrap().total, 54);
+		let delegation = DdcStaking::delegation(&node_pub_key, 2).unwrap();
+		assert_eq!(delegation.active, 18);
+		assert_eq!(delegation.unlocking[0].value, 18);
+		assert_eq!(DdcStaking::delegated_stake(&node_pub_key), 72);
+		assert_eq!(Balances::reserved_balance(1), 54);
+		assert_eq!(Balances::reserved_balance(2), 36);
+		assert_eq!(Balances::free_balance(1), 40);
+		assert_eq!(Balances::free_balance(TREASURY_ACCOUNT_ID), 20);
+
+		assert_ok!(DdcStaking::withdraw_undelegated(
+			RuntimeOrigin::signed(2),
+			node_pub_key.clone()
+		));
+		System::assert_last_event(Event::DelegationWithdrawn(2, node_pub_key, 18).into());
+		assert_eq!(Balances::reserved_balance(2), 18);
+	});
+}
+
+#[test]
+fn raised_commission_takes_effect_after_unbonding_delay() {
+	ExtBuilder::default().build_and_execute(|| {
+		System::set_block_number(1);
+
+		let cluster_id = ClusterId::from([1; 20]);
+		let lock = MockNodeVisitor::set_and_hold_lock(MockNode {
+			cluster_id: Some(cluster_id),
+			exists: true,
+		});
+		let node_pub_key = NodePubKey::StoragePubKey(StorageNodePubKey::new([12; 32]));
+
+		assert_ok!(DdcStaking::delegate(RuntimeOrigin::signed(1), node_pub_key.clone(), 60));
+		assert_ok!(DdcStaking::delegate(RuntimeOrigin::signed(2), node_pub_key.clone(), 40));
+		assert_ok!(DdcStaking::set_commission(
+			RuntimeOrigin::signed(10),
+			node_pub_key.clone(),
+			Perbill::from_percent(10)
+		));
+		System::assert_last_event(
+			Event::CommissionScheduled(node_pub_key.clone(), Perbill::from_percent(10), 11).into(),
+		);
+		assert_eq!(DdcStaking::commission(&node_pub_key), Perbill::zero());
+
+		// The delegators keep their rewards until the raised commission takes effect.
+		let mut rewards = <DdcStaking as DelegationVisitor<Test, Balance>>::get_delegators_rewards(
+			&11,
+			&node_pub_key,
+			1000,
+		);
+		rewards.sort();
+		assert_eq!(rewards, vec![(1, 300), (2, 200)]);
+
+		System::set_block_number(11);
+		let mut rewards = <DdcStaking as DelegationVisitor<Test, Balance>>::get_delegators_rewards(
+			&11,
+			&node_pub_key,
+			1000,
+		);
+		rewards.sort();
+		assert_eq!(rewards, vec![(1, 270), (2, 180)]);
+		// The rewards are computed without applying the raised commission.
+		assert_eq!(DdcStaking::commission(&node_pub_key), Perbill::zero());
+		assert_eq!(
+			DdcStaking::pending_commission(&node_pub_key),
+			Some((Perbill::from_percent(10), 11))
+		);
+
+		// The raised commission is applied on idle.
+		DdcStaking::on_idle(System::block_number(), Weight::MAX);
+		System::assert_last_event(
+			Event::CommissionSet(node_pub_key.clone(), Perbill::from_percent(10)).into(),
+		);
+		assert_eq!(DdcStaking::commission(&node_pub_key), Perbill::from_percent(10));
+		assert_eq!(DdcStaking::pending_commission(&node_pub_key), None);
+		assert_eq!(DdcStaking::commission_queue(0), None);
+
+		// A lowered commission takes effect immediately.
+		assert_ok!(DdcStaking::set_commission(
+			RuntimeOrigin::signed(10),
+			node_pub_key.clone(),
+			Perbill::from_percent(5)
+		));
+		System::assert_last_event(
+			Event::CommissionSet(node_pub_key.clone(), Perbill::from_percent(5)).into(),
+		);
+		assert_eq!(DdcStaking::commission(&node_pub_key), Perbill::from_percent(5));
+
+		MockNodeVisitor::reset_and_release_lock(lock);
+	});
+}
+
+#[test]
+fn under_bonded_nodes_are_chilled_after_grace_period() {
+	ExtBuilder::default().build_and_execute(|| {
+		System::set_block_number(1);
+
+		let cluster_id = ClusterId::from([1; 20]);
+		let node_1 = NodePubKey::StoragePubKey(StorageNodePubKey::new([12; 32]));
+		let node_2 = NodePubKey::StoragePubKey(StorageNodePubKey::new([22; 32]));
+		let node_3 = NodePubKey::StoragePubKey(StorageNodePubKey::new([32; 32]));
+		let node_4 = NodePubKey::StoragePubKey(StorageNodePubKey::new([42; 32]));
+
+		let _ = Balances::make_free_balance_be(&11, 200);
+		let _ = Balances::make_free_balance_be(&41, 200);
+
+		// Governance raises the bond size above the stake of all the nodes in the cluster.
+		StorageBondSize::set(150);
+		<DdcStaking as OnClusterBondingParamsChanged<Test>>::on_bonding_params_changed(
+			&cluster_id,
+			&ClusterBondingParams {
+				storage_bond_size: 150,
+				storage_chill_delay: 10,
+				storage_unbonding_delay: 10,
+			},
+		);
+		let chill_at = 1 + UnderBondedGracePeriod::get();
+		assert_eq!(DdcStaking::pending_bond_check(cluster_id), Some((chill_at, None)));
+
+		// The stakes of the nodes are checked on idle.
+		DdcStaking::on_idle(System::block_number(), Weight::MAX);
+		assert_eq!(DdcStaking::pending_bond_check(cluster_id), None);
+		for (stash, node_pub_key) in [(11, &node_1), (21, &node_2), (31, &node_3), (41, &node_4)] {
+			System::assert_has_event(
+				Event::UnderBonded(stash, node_pub_key.clone(), cluster_id, chill_at).into(),
+			);
+			assert_eq!(DdcStaking::under_bonded(node_pub_key), Some(chill_at));
+		}
+		assert!(matches!(
+			<DdcStaking as StakingVisitor<Test>>::has_activated_stake(&node_1, &cluster_id),
+			Err(StakingVisitorError::NodeStakeIsUnderBonded)
+		));
+
+		// Only the stash can bond extra funds.
+		assert_noop!(
+			DdcStaking::bond_extra(RuntimeOrigin::signed(10), node_1.clone(), 50),
+			Error::<Test>::NotStash
+		);
+
+		// Topping up the stake with the stash funds clears the under-bonded node.
+		assert_ok!(DdcStaking::bond_extra(RuntimeOrigin::signed(11), node_1.clone(), 50));
+		System::assert_has_event(Event::Bonded(11, node_1.clone(), 50).into());
+		System::assert_last_event(Event::BondToppedUp(11, node_1.clone()).into());
+		assert_eq!(DdcStaking::under_bonded(&node_1), None);
+		assert_eq!(DdcStaking::ledger(10, &node_1).unwrap().active, 150);
+		assert_eq!(Balances::locks(11)[0].amount, 150);
+		assert!(matches!(
+			<DdcStaking as StakingVisitor<Test>>::has_activated_stake(&node_1, &cluster_id),
+			Ok(true)
+		));
+
+		// The stake delegated to the node counts towards the bond size as well.
+		assert_ok!(DdcStaking::delegate(RuntimeOrigin::signed(3), node_2.clone(), 50));
+		System::assert_last_event(Event::BondToppedUp(21, node_2.clone()).into());
+		assert_eq!(DdcStaking::under_bonded(&node_2), None);
+
+		// Bonding less than the bond size keeps the node under-bonded.
+		assert_ok!(DdcStaking::bond_extra(RuntimeOrigin::signed(41), node_4.clone(), 20));
+		System::assert_last_event(Event::Bonded(41, node_4.clone(), 20).into());
+		assert_eq!(DdcStaking::under_bonded(&node_4), Some(chill_at));
+
+		// The under-bonded nodes are not chilled before the end of the grace period.
+		DdcStaking::on_idle(chill_at - 1, Weight::MAX);
+		assert_eq!(DdcStaking::storages(&node_3), Some(cluster_id));
+
+		// Not enough weight to chill the nodes.
+		assert_eq!(DdcStaking::on_idle(chill_at, Weight::zero()), Weight::zero());
+		assert_eq!(DdcStaking::storages(&node_3), Some(cluster_id));
+
+		DdcStaking::on_idle(chill_at, Weight::MAX);
+		for (stash, node_pub_key) in [(31, &node_3), (41, &node_4)] {
+			System::assert_has_event(Event::Chilled(stash, node_pub_key.clone()).into());
+			assert_eq!(DdcStaking::storages(node_pub_key), None);
+			assert!(!ClusterStorages::<Test>::contains_key(cluster_id, node_pub_key));
+			assert_eq!(DdcStaking::under_bonded(node_pub_key), None);
+		}
+		assert_eq!(DdcStaking::storages(&node_1), Some(cluster_id));
+		assert_eq!(DdcStaking::storages(&node_2), Some(cluster_id));
+
+		// Lowering the bond size back clears the nodes not chilled yet.
+		StorageBondSize::set(200);
+		<DdcStaking as OnClusterBondingParamsChanged<Test>>::on_bonding_params_changed(
+			&cluster_id,
+			&ClusterBondingParams {
+				storage_bond_size: 200,
+				storage_chill_delay: 10,
+				storage_unbonding_delay: 10,
+			},
+		);
+		DdcStaking::on_idle(System::block_number(), Weight::MAX);
+		assert!(DdcStaking::under_bonded(&node_1).is_some());
+		assert!(DdcStaking::under_bonded(&node_2).is_some());
+		StorageBondSize::set(100);
+		<DdcStaking as OnClusterBondingParamsChanged<Test>>::on_bonding_params_changed(
+			&cluster_id,
+			&ClusterBondingParams {
+				storage_bond_size: 100,
+				storage_chill_delay: 10,
+				storage_unbonding_delay: 10,
+			},
+		);
+		DdcStaking::on_idle(System::block_number(), Weight::MAX);
+		for (stash, node_pub_key) in [(11, &node_1), (21, &node_2)] {
+			System::assert_has_event(Event::BondToppedUp(stash, node_pub_key.clone()).into());
+			assert_eq!(DdcStaking::under_bonded(node_pub_key), None);
+		}
+	});
+}
+
+#[test]
+fn bonds_are_checked_on_idle_page_by_page() {
+	ExtBuilder::default().build_and_execute(|| {
+		System::set_block_number(1);
+
+		let cluster_id = ClusterId::from([1; 20]);
+		let nodes = [12, 22, 32, 42]
+			.map(|key| NodePubKey::StoragePubKey(StorageNodePubKey::new([key; 32])));
+		for node_pub_key in nodes.iter() {
+			assert!(ClusterStorages::<Test>::contains_key(cluster_id, node_pub_key));
+		}
+
+		StorageBondSize::set(150);
+		<DdcStaking as OnClusterBondingParamsChanged<Test>>::on_bonding_params_changed(
+			&cluster_id,
+			&ClusterBondingParams {
+				storage_bond_size: 150,
+				storage_chill_delay: 10,
+				storage_unbonding_delay: 10,
+			},
+		);
+
+		// Enough weight to check a single node per block.
+		let weight = <Test as frame_system::Config>::DbWeight::get().reads_writes(10, 5);
+		for checked in 1..=nodes.len() {
+			DdcStaking::on_idle(System::block_number(), weight);
+			let under_bonded = nodes
+				.iter()
+				.filter(|node_pub_key| DdcStaking::under_bonded(node_pub_key).is_some());
+			assert_eq!(under_bonded.count(), checked);
+			assert!(DdcStaking::pending_bond_check(cluster_id).is_some());
+		}
+
+		DdcStaking::on_idle(System::block_number(), weight);
+		assert_eq!(DdcStaking::pending_bond_check(cluster_id), None);
+		assert_eq!(UnderBondedQueueRange::<Test>::get(), (0, 4));
+	});
+}
@@ -1,11 +1,13 @@
-//! Autogenerated weights for pallet_ddc_staking
+//! Weights for pallet_ddc_staking
 //!
-//! THIS FILE WAS AUTO-GENERATED USING THE SUBSTRATE BENCHMARK CLI VERSION 4.0.0-dev
-//! DATE: 2023-12-14, STEPS: `50`, REPEAT: 20, LOW RANGE: `[]`, HIGH RANGE: `[]`
-//! HOSTNAME: `Yahors-MacBook-Pro.local`, CPU: `<UNKNOWN>`
-//! EXECUTION: Some(Wasm), WASM-EXECUTION: Compiled, CHAIN: Some("dev"), DB CACHE: 1024
+//! The weights were generated with the Substrate benchmark CLI on 2023-12-14 (STEPS: `50`, REPEAT:
+//! 20, HOSTNAME: `Yahors-MacBook-Pro.local`). The weights of the following calls are provisional
+//! estimates derived from their storage access, not benchmark results, and must be regenerated with
+//! the command below before a release: `set_slash_fraction`, `report_offence`, `cancel_slash`,
+//! `apply_slash`, `delegate`, `undelegate`, `withdraw_undelegated`, `set_commission`, `bond_node`,
+//! `rebond`, `bond_extra`.
 
-// Executed Command:
+// Command to regenerate the weights:
 // ./target/release/cere
 // benchmark
 // pallet
@@ -35,6 +37,17 @@ pub trait WeightInfo {
 	fn set_controller() -> Weight;
 	fn set_node() -> Weight;
 	fn fast_chill() -> Weight;
+	fn set_slash_fraction() -> Weight;
+	fn report_offence() -> Weight;
+	fn cancel_slash() -> Weight;
+	fn apply_slash() -> Weight;
+	fn delegate() -> Weight;
+	fn undelegate() -> Weight;
+	fn withdraw_undelegated() -> Weight;
+	fn set_commission() -> Weight;
+	fn bond_node() -> Weight;
+	fn rebond() -> Weight;
+	fn bond_extra() -> Weight;
 }
 
 /// Weights for pallet_ddc_staking using the Substrate node and recommended hardware.
@@ -110,6 +123,138 @@ impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
 	fn fast_chill() -> Weight {
 		Weight::from_parts(28_000_000_u64, 0)
 	}
+	// Storage: DdcStaking SlashFractions (r:0 w:1)
+	fn set_slash_fraction() -> Weight {
+		Weight::from_parts(10_000_000_u64, 0)
+			.saturating_add(T::DbWeight::get().writes(1_u64))
+	}
+	// Storage: Session Validators (r:1 w:0)
+	// Storage: DdcStaking Nodes (r:1 w:0)
+	// Storage: DdcStaking UnappliedSlashes (r:1 w:1)
+	// Storage: DdcStaking SlashFractions (r:1 w:0)
+	// Storage: DdcStaking OffenceVotes (r:1 w:1)
+	// Storage: DdcStaking SlashQueueRange (r:1 w:1)
+	// Storage: DdcStaking SlashQueue (r:0 w:1)
+	fn report_offence() -> Weight {
+		Weight::from_parts(31_000_000_u64, 0)
+			.saturating_add(T::DbWeight::get().reads(6_u64))
+			.saturating_add(T::DbWeight::get().writes(4_u64))
+	}
+	// Storage: DdcStaking UnappliedSlashes (r:1 w:1)
+	fn cancel_slash() -> Weight {
+		Weight::from_parts(13_000_000_u64, 0)
+			.saturating_add(T::DbWeight::get().reads(1_u64))
+			.saturating_add(T::DbWeight::get().writes(1_u64))
+	}
+	// Storage: DdcStaking UnappliedSlashes (r:1 w:1)
+	// Storage: DdcStaking Nodes (r:1 w:0)
+	// Storage: DdcStaking Bonded (r:1 w:0)
+	// Storage: DdcStaking Ledger (r:1 w:1)
+	// Storage: Balances Locks (r:1 w:1)
+	// Storage: DdcNodes StorageNodes (r:1 w:0)
+	// Storage: DdcClusters Clusters (r:1 w:0)
+	// Storage: DdcStaking Delegations (r:65 w:64)
+	// Storage: DdcStaking NodeDelegatorsCount (r:1 w:1)
+	// Storage: DdcStaking DelegatedStake (r:1 w:1)
+	// Storage: System Account (r:66 w:66)
+	fn apply_slash() -> Weight {
+		Weight::from_parts(1_212_000_000_u64, 0)
+			.saturating_add(T::DbWeight::get().reads(139_u64))
+			.saturating_add(T::DbWeight::get().writes(134_u64))
+	}
+	// Storage: DdcStaking Nodes (r:1 w:0)
+	// Storage: DdcStaking Bonded (r:1 w:0)
+	// Storage: DdcStaking Ledger (r:1 w:0)
+	// Storage: DdcStaking Delegations (r:1 w:1)
+	// Storage: DdcStaking NodeDelegatorsCount (r:1 w:1)
+	// Storage: DdcStaking DelegatedStake (r:1 w:1)
+	// Storage: System Account (r:1 w:1)
+	// Storage: DdcStaking UnderBondedNodes (r:1 w:0)
+	fn delegate() -> Weight {
+		Weight::from_parts(40_000_000_u64, 0)
+			.saturating_add(T::DbWeight::get().reads(8_u64))
+			.saturating_add(T::DbWeight::get().writes(4_u64))
+	}
+	// Storage: DdcStaking Delegations (r:1 w:1)
+	// Storage: DdcStaking Nodes (r:1 w:0)
+	// Storage: DdcStaking Bonded (r:1 w:0)
+	// Storage: DdcStaking Ledger (r:1 w:0)
+	// Storage: DdcStaking DelegatedStake (r:1 w:1)
+	// Storage: DdcStaking Storages (r:1 w:0)
+	// Storage: DdcNodes StorageNodes (r:1 w:0)
+	// Storage: DdcClusters ClustersGovParams (r:1 w:0)
+	// Storage: DdcStaking LeavingStorages (r:1 w:1)
+	fn undelegate() -> Weight {
+		Weight::from_parts(44_000_000_u64, 0)
+			.saturating_add(T::DbWeight::get().reads(9_u64))
+			.saturating_add(T::DbWeight::get().writes(3_u64))
+	}
+	// Storage: DdcStaking Delegations (r:1 w:1)
+	// Storage: DdcStaking UnappliedSlashes (r:1 w:0)
+	// Storage: DdcStaking NodeDelegatorsCount (r:1 w:1)
+	// Storage: System Account (r:1 w:1)
+	// Storage: DdcStaking LeavingStorages (r:1 w:1)
+	// Storage: DdcStaking Nodes (r:1 w:0)
+	// Storage: DdcStaking Bonded (r:1 w:0)
+	// Storage: DdcStaking Ledger (r:1 w:0)
+	// Storage: DdcStaking DelegatedStake (r:1 w:0)
+	// Storage: DdcClusters ClustersGovParams (r:1 w:0)
+	// Storage: DdcClusters ClustersNodes (r:1 w:1)
+	// Storage: DdcNodes StorageNodes (r:1 w:1)
+	fn withdraw_undelegated() -> Weight {
+		Weight::from_parts(52_000_000_u64, 0)
+			.saturating_add(T::DbWeight::get().reads(12_u64))
+			.saturating_add(T::DbWeight::get().writes(6_u64))
+	}
+	// Storage: DdcStaking Ledger (r:1 w:0)
+	// Storage: DdcNodes StorageNodes (r:1 w:0)
+	// Storage: DdcStaking Commissions (r:1 w:1)
+	// Storage: DdcClusters ClustersGovParams (r:1 w:0)
+	// Storage: DdcStaking PendingCommissions (r:1 w:1)
+	// Storage: DdcStaking CommissionQueueRange (r:1 w:1)
+	// Storage: DdcStaking CommissionQueue (r:0 w:1)
+	fn set_commission() -> Weight {
+		Weight::from_parts(24_000_000_u64, 0)
+			.saturating_add(T::DbWeight::get().reads(6_u64))
+			.saturating_add(T::DbWeight::get().writes(4_u64))
+	}
+	// Storage: DdcStaking Bonded (r:1 w:0)
+	// Storage: DdcStaking Ledger (r:2 w:1)
+	// Storage: DdcStaking Nodes (r:1 w:1)
+	// Storage: DdcStaking Providers (r:1 w:1)
+	// Storage: DdcNodes StorageNodes (r:1 w:0)
+	// Storage: Balances Locks (r:1 w:1)
+	fn bond_node() -> Weight {
+		Weight::from_parts(41_000_000_u64, 0)
+			.saturating_add(T::DbWeight::get().reads(7_u64))
+			.saturating_add(T::DbWeight::get().writes(4_u64))
+	}
+	// Storage: DdcStaking Ledger (r:1 w:1)
+	// Storage: Balances Locks (r:1 w:1)
+	// Storage: System Account (r:1 w:1)
+	// Storage: DdcStaking LeavingStorages (r:1 w:1)
+	// Storage: DdcClusters ClustersGovParams (r:1 w:0)
+	// Storage: DdcStaking DelegatedStake (r:1 w:0)
+	// Storage: DdcStaking UnderBondedNodes (r:1 w:0)
+	fn rebond() -> Weight {
+		Weight::from_parts(36_000_000_u64, 0)
+			.saturating_add(T::DbWeight::get().reads(7_u64))
+			.saturating_add(T::DbWeight::get().writes(4_u64))
+	}
+	// Storage: DdcStaking Bonded (r:1 w:0)
+	// Storage: DdcStaking Ledger (r:1 w:1)
+	// Storage: Balances Locks (r:1 w:1)
+	// Storage: System Account (r:1 w:1)
+	// Storage: DdcStaking UnderBondedNodes (r:1 w:1)
+	// Storage: DdcStaking Storages (r:1 w:0)
+	// Storage: DdcStaking Nodes (r:1 w:0)
+	// Storage: DdcClusters ClustersGovParams (r:1 w:0)
+	// Storage: DdcStaking DelegatedStake (r:1 w:0)
+	fn bond_extra() -> Weight {
+		Weight::from_parts(38_000_000_u64, 0)
+			.saturating_add(T::DbWeight::get().reads(9_u64))
+			.saturating_add(T::DbWeight::get().writes(4_u64))
+	}
 }
 
 // For backwards compatibility and tests
@@ -184,4 +329,136 @@ impl WeightInfo for () {
 	fn fast_chill() -> Weight {
 		Weight::from_parts(28_000_000_u64, 0)
 	}
+	// Storage: DdcStaking SlashFractions (r:0 w:1)
+	fn set_slash_fraction() -> Weight {
+		Weight::from_parts(10_000_000_u64, 0)
+			.saturating_add(RocksDbWeight::get().writes(1_u64))
+	}
+	// Storage: Session Validators (r:1 w:0)
+	// Storage: DdcStaking Nodes (r:1 w:0)
+	// Storage: DdcStaking UnappliedSlashes (r:1 w:1)
+	// Storage: DdcStaking SlashFractions (r:1 w:0)
+	// Storage: DdcStaking OffenceVotes (r:1 w:1)
+	// Storage: DdcStaking SlashQueueRange (r:1 w:1)
+	// Storage: DdcStaking SlashQueue (r:0 w:1)
+	fn report_offence() -> Weight {
+		Weight::from_parts(31_000_000_u64, 0)
+			.saturating_add(RocksDbWeight::get().reads(6_u64))
+			.saturating_add(RocksDbWeight::get().writes(4_u64))
+	}
+	// Storage: DdcStaking UnappliedSlashes (r:1 w:1)
+	fn cancel_slash() -> Weight {
+		Weight::from_parts(13_000_000_u64, 0)
+			.saturating_add(RocksDbWeight::get().reads(1_u64))
+			.saturating_add(RocksDbWeight::get().writes(1_u64))
+	}
+	// Storage: DdcStaking UnappliedSlashes (r:1 w:1)
+	// Storage: DdcStaking Nodes (r:1 w:0)
+	// Storage: DdcStaking Bonded (r:1 w:0)
+	// Storage: DdcStaking Ledger (r:1 w:1)
+	// Storage: Balances Locks (r:1 w:1)
+	// Storage: DdcNodes StorageNodes (r:1 w:0)
+	// Storage: DdcClusters Clusters (r:1 w:0)
+	// Storage: DdcStaking Delegations (r:65 w:64)
+	// Storage: DdcStaking NodeDelegatorsCount (r:1 w:1)
+	// Storage: DdcStaking DelegatedStake (r:1 w:1)
+	// Storage: System Account (r:66 w:66)
+	fn apply_slash() -> Weight {
+		Weight::from_parts(1_212_000_000_u64, 0)
+			.saturating_add(RocksDbWeight::get().reads(139_u64))
+			.saturating_add(RocksDbWeight::get().writes(134_u64))
+	}
+	// Storage: DdcStaking Nodes (r:1 w:0)
+	// Storage: DdcStaking Bonded (r:1 w:0)
+	// Storage: DdcStaking Ledger (r:1 w:0)
+	// Storage: DdcStaking Delegations (r:1 w:1)
+	// Storage: DdcStaking NodeDelegatorsCount (r:1 w:1)
+	// Storage: DdcStaking DelegatedStake (r:1 w:1)
+	// Storage: System Account (r:1 w:1)
+	// Storage: DdcStaking UnderBondedNodes (r:1 w:0)
+	fn delegate() -> Weight {
+		Weight::from_parts(40_000_000_u64, 0)
+			.saturating_add(RocksDbWeight::get().reads(8_u64))
+			.saturating_add(RocksDbWeight::get().writes(4_u64))
+	}
+	// Storage: DdcStaking Delegations (r:1 w:1)
+	// Storage: DdcStaking Nodes (r:1 w:0)
+	// Storage: DdcStaking Bonded (r:1 w:0)
+	// Storage: DdcStaking Ledger (r:1 w:0)
+	// Storage: DdcStaking DelegatedStake (r:1 w:1)
+	// Storage: DdcStaking Storages (r:1 w:0)
+	// Storage: DdcNodes StorageNodes (r:1 w:0)
+	// Storage: DdcClusters ClustersGovParams (r:1 w:0)
+	// Storage: DdcStaking LeavingStorages (r:1 w:1)
+	fn undelegate() -> Weight {
+		Weight::from_parts(44_000_000_u64, 0)
+			.saturating_add(RocksDbWeight::get().reads(9_u64))
+			.saturating_add(RocksDbWeight::get().writes(3_u64))
+	}
+	// Storage: DdcStaking Delegations (r:1 w:1)
+	// Storage: DdcStaking UnappliedSlashes (r:1 w:0)
+	// Storage: DdcStaking NodeDelegatorsCount (r:1 w:1)
+	// Storage: System Account (r:1 w:1)
+	// Storage: DdcStaking LeavingStorages (r:1 w:1)
+	// Storage: DdcStaking Nodes (r:1 w:0)
+	// Storage: DdcStaking Bonded (r:1 w:0)
+	// Storage: DdcStaking Ledger (r:1 w:0)
+	// Storage: DdcStaking DelegatedStake (r:1 w:0)
+	// Storage: DdcClusters ClustersGovParams (r:1 w:0)
+	// Storage: DdcClusters ClustersNodes (r:1 w:1)
+	// Storage: DdcNodes StorageNodes (r:1 w:1)
+	fn withdraw_undelegated() -> Weight {
+		Weight::from_parts(52_000_000_u64, 0)
+			.saturating_add(RocksDbWeight::get().reads(12_u64))
+			.saturating_add(RocksDbWeight::get().writes(6_u64))
+	}
+	// Storage: DdcStaking Ledger (r:1 w:0)
+	// Storage: DdcNodes StorageNodes (r:1 w:0)
+	// Storage: DdcStaking Commissions (r:1 w:1)
+	// Storage: DdcClusters ClustersGovParams (r:1 w:0)
+	// Storage: DdcStaking PendingCommissions (r:1 w:1)
+	// Storage: DdcStaking CommissionQueueRange (r:1 w:1)
+	// Storage: DdcStaking CommissionQueue (r:0 w:1)
+	fn set_commission() -> Weight {
+		Weight::from_parts(24_000_000_u64, 0)
+			.saturating_add(RocksDbWeight::get().reads(6_u64))
+			.saturating_add(RocksDbWeight::get().writes(4_u64))
+	}
+	// Storage: DdcStaking Bonded (r:1 w:0)
+	// Storage: DdcStaking Ledger (r:2 w:1)
+	// Storage: DdcStaking Nodes (r:1 w:1)
+	// Storage: DdcStaking Providers (r:1 w:1)
+	// Storage: DdcNodes StorageNodes (r:1 w:0)
+	// Storage: Balances Locks (r:1 w:1)
+	fn bond_node() -> Weight {
+		Weight::from_parts(41_000_000_u64, 0)
+			.saturating_add(RocksDbWeight::get().reads(7_u64))
+			.saturating_add(RocksDbWeight::get().writes(4_u64))
+	}
+	// Storage: DdcStaking Ledger (r:1 w:1)
+	// Storage: Balances Locks (r:1 w:1)
+	// Storage: System Account (r:1 w:1)
+	// Storage: DdcStaking LeavingStorages (r:1 w:1)
+	// Storage: DdcClusters ClustersGovParams (r:1 w:0)
+	// Storage: DdcStaking DelegatedStake (r:1 w:0)
+	// Storage: DdcStaking UnderBondedNodes (r:1 w:0)
+	fn rebond() -> Weight {
+		Weight::from_parts(36_000_000_u64, 0)
+			.saturating_add(RocksDbWeight::get().reads(7_u64))
+			.saturating_add(RocksDbWeight::get().writes(4_u64))
+	}
+	// Storage: DdcStaking Bonded (r:1 w:0)
+	// Storage: DdcStaking Ledger (r:1 w:1)
+	// Storage: Balances Locks (r:1 w:1)
+	// Storage: System Account (r:1 w:1)
+	// Storage: DdcStaking UnderBondedNodes (r:1 w:1)
+	// Storage: DdcStaking Storages (r:1 w:0)
+	// Storage: DdcStaking Nodes (r:1 w:0)
+	// Storage: DdcClusters ClustersGovParams (r:1 w:0)
+	// Storage: DdcStaking DelegatedStake (r:1 w:0)
+	fn bond_extra() -> Weight {
+		Weight::from_parts(38_000_000_u64, 0)
+			.saturating_add(RocksDbWeight::get().reads(9_u64))
+			.saturating_add(RocksDbWeight::get().writes(4_u64))
+	}
 }
\ No newline at end of file
@@ -1,6 +1,7 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
 use codec::{Decode, Encode};
+use frame_support::{traits::ConstU32, BoundedVec};
 use scale_info::{prelude::vec::Vec, TypeInfo};
 use serde::{Deserialize, Serialize};
 use sp_core::hash::H160;
@@ -14,6 +15,7 @@ pub const DOLLARS: u128 = 100 * CENTS;
 pub type ClusterId = H160;
 pub type DdcEra = u32;
 pub type BucketId = u64;
+pub type AssetId = u32;
 pub type StorageNodePubKey = AccountId32;
 
 // ClusterParams includes Governance non-sensetive parameters only
@@ -38,6 +40,33 @@ pub struct ClusterGovParams<Balance, BlockNumber> {
 	pub unit_per_mb_streamed: u128,
 	pub unit_per_put_request: u128,
 	pub unit_per_get_request: u128,
+	pub billing_period: u64, // seconds of storage priced by `unit_per_mb_stored`
+}
+
+// PricingTier sets the unit price for the usage above its threshold, in bytes for streamed and
+// stored data and in number of requests for puts and gets
+#[derive(
+	Clone, Encode, Decode, RuntimeDebug, TypeInfo, PartialEq, Default, Serialize, Deserialize,
+)]
+pub struct PricingTier {
+	pub threshold: u64,
+	pub unit_price: u128,
+}
+
+pub const MAX_PRICING_TIERS: u32 = 16;
+pub type PricingTiers = BoundedVec<PricingTier, ConstU32<MAX_PRICING_TIERS>>;
+
+// ClusterPricingTiers overrides the flat `unit_per_*` prices above the tiers thresholds, tiers
+// are sorted by ascending threshold. The tiers are kept apart from `ClusterGovParams`, so the
+// gov params of the existing clusters are not migrated
+#[derive(
+	Clone, Encode, Decode, RuntimeDebug, TypeInfo, PartialEq, Default, Serialize, Deserialize,
+)]
+pub struct ClusterPricingTiers {
+	pub streamed: PricingTiers,
+	pub stored: PricingTiers,
+	pub puts: PricingTiers,
+	pub gets: PricingTiers,
 }
 
 #[derive(Clone, Encode, Decode, RuntimeDebug, TypeInfo, PartialEq)]
@@ -46,6 +75,67 @@ pub struct ClusterPricingParams {
 	pub unit_per_mb_streamed: u128,
 	pub unit_per_put_request: u128,
 	pub unit_per_get_request: u128,
+	pub billing_period: u64, // seconds of storage priced by `unit_per_mb_stored`
+	pub pricing_tiers: ClusterPricingTiers,
+}
+
+pub const MAX_REGION_LEN: u32 = 32;
+pub const MAX_CLUSTER_REGIONS: u32 = 16;
+pub type Region = BoundedVec<u8, ConstU32<MAX_REGION_LEN>>;
+
+// ClusterCapabilities limits the metadata of the cluster buckets and prices it, the default
+// capabilities support neither custom replication, nor regions, nor cold storage
+#[derive(
+	Clone, Encode, Decode, RuntimeDebug, TypeInfo, PartialEq, Default, Serialize, Deserialize,
+)]
+pub struct ClusterCapabilities {
+	pub default_replication_factor: u8, // replicas priced by `unit_per_mb_stored`
+	pub max_replication_factor: u8,
+	pub regions: BoundedVec<Region, ConstU32<MAX_CLUSTER_REGIONS>>,
+	pub cold_storage_discount: Option<Perquintill>, // `None` if cold storage is not supported
+}
+
+#[derive(
+	Clone,
+	Copy,
+	Encode,
+	Decode,
+	RuntimeDebug,
+	TypeInfo,
+	PartialEq,
+	Eq,
+	Default,
+	Serialize,
+	Deserialize,
+)]
+pub enum StorageClass {
+	/// Frequently accessed data
+	#[default]
+	Hot = 1,
+	/// Rarely accessed data, stored at a lower price
+	Cold = 2,
+}
+
+// BucketMetadata tells the storage nodes how to place the bucket data
+#[derive(
+	Clone, Encode, Decode, RuntimeDebug, TypeInfo, PartialEq, Eq, Default, Serialize, Deserialize,
+)]
+pub struct BucketMetadata {
+	pub replication_factor: Option<u8>, // `None` for the default replication of the cluster
+	pub region: Option<Region>,
+	pub storage_class: StorageClass,
+	pub ttl: Option<u64>, // seconds the data is retained for
+}
+
+// PaymentSource is where the usage of the content owner is charged from and refunded to
+#[derive(Clone, Copy, Encode, Decode, RuntimeDebug, TypeInfo, PartialEq, Eq)]
+pub enum PaymentSource {
+	/// The deposit of the content owner in the native currency
+	Deposit,
+	/// The allocation of the bucket with a budget
+	BucketBudget(BucketId),
+	/// The deposit of the content owner in the asset
+	Asset(AssetId),
 }
 
 #[derive(Clone, Encode, Decode, RuntimeDebug, TypeInfo, PartialEq)]
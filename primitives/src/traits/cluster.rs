@@ -2,11 +2,11 @@ use codec::{Decode, Encode};
 use frame_support::dispatch::DispatchResult;
 use frame_system::{pallet_prelude::BlockNumberFor, Config};
 use scale_info::TypeInfo;
-use sp_runtime::RuntimeDebug;
+use sp_runtime::{FixedU128, Perquintill, RuntimeDebug};
 
 use crate::{
-	ClusterBondingParams, ClusterFeesParams, ClusterGovParams, ClusterId, ClusterParams,
-	ClusterPricingParams, NodePubKey, NodeType,
+	AssetId, ClusterBondingParams, ClusterCapabilities, ClusterFeesParams, ClusterGovParams,
+	ClusterId, ClusterParams, ClusterPricingParams, NodePubKey, NodeType,
 };
 
 pub trait ClusterVisitor<T: Config> {
@@ -23,6 +23,8 @@ pub trait ClusterVisitor<T: Config> {
 
 	fn get_fees_params(cluster_id: &ClusterId) -> Result<ClusterFeesParams, ClusterVisitorError>;
 
+	fn get_customer_discount(cluster_id: &ClusterId, customer_id: &T::AccountId) -> Perquintill;
+
 	fn get_reserve_account_id(cluster_id: &ClusterId) -> Result<T::AccountId, ClusterVisitorError>;
 
 	fn get_chill_delay(
@@ -38,6 +40,13 @@ pub trait ClusterVisitor<T: Config> {
 	fn get_bonding_params(
 		cluster_id: &ClusterId,
 	) -> Result<ClusterBondingParams<BlockNumberFor<T>>, ClusterVisitorError>;
+
+	fn get_capabilities(cluster_id: &ClusterId)
+		-> Result<ClusterCapabilities, ClusterVisitorError>;
+
+	/// Amount of the asset charged per unit of the native currency in the cluster, `None` if
+	/// the cluster does not accept the asset.
+	fn get_asset_rate(cluster_id: &ClusterId, asset_id: AssetId) -> Option<FixedU128>;
 }
 
 pub trait ClusterCreator<T: Config, Balance> {
@@ -68,6 +77,25 @@ pub trait ClusterManager<T: Config> {
 	) -> Result<(), ClusterManagerError>;
 }
 
+/// Notified when the governance changes the bonding params of a cluster.
+///
+/// The notification is covered by the weight of a single storage write, so the work depending on
+/// the number of nodes in the cluster has to be deferred.
+pub trait OnClusterBondingParamsChanged<T: Config> {
+	fn on_bonding_params_changed(
+		cluster_id: &ClusterId,
+		bonding_params: &ClusterBondingParams<BlockNumberFor<T>>,
+	);
+}
+
+impl<T: Config> OnClusterBondingParamsChanged<T> for () {
+	fn on_bonding_params_changed(
+		_cluster_id: &ClusterId,
+		_bonding_params: &ClusterBondingParams<BlockNumberFor<T>>,
+	) {
+	}
+}
+
 pub enum ClusterManagerError {
 	AttemptToAddNonExistentNode,
 	AttemptToAddAlreadyAssignedNode,
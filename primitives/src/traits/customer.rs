@@ -1,16 +1,70 @@
 use core::u128;
 
-use sp_runtime::DispatchError;
+use codec::{Decode, Encode};
+use scale_info::TypeInfo;
+use sp_runtime::{DispatchError, DispatchResult, RuntimeDebug};
+
+use crate::{AssetId, BucketId, BucketMetadata, ClusterId, PaymentSource};
 
 pub trait CustomerCharger<T: frame_system::Config> {
+	/// Charges the content owner for the usage. When `bucket_id` refers to a bucket with a
+	/// budget, only the bucket's own allocation is charged.
 	fn charge_content_owner(
+		bucket_id: Option<BucketId>,
 		content_owner: T::AccountId,
 		billing_vault: T::AccountId,
 		amount: u128,
 	) -> Result<u128, DispatchError>;
+
+	/// The source the content owner is charged from for the usage of the bucket.
+	fn get_payment_source(
+		bucket_id: Option<BucketId>,
+		content_owner: &T::AccountId,
+	) -> PaymentSource;
+
+	/// Charges the content owner's deposit of the asset, transferring the charged amount of the
+	/// asset to the payee.
+	fn charge_content_owner_asset(
+		content_owner: T::AccountId,
+		payee: T::AccountId,
+		asset_id: AssetId,
+		amount: u128,
+	) -> Result<u128, DispatchError>;
+
+	/// Refunds the content owner a charge taken from the source, transferring the amount from
+	/// the payer. The amount is in the asset for `PaymentSource::Asset`.
+	fn refund_content_owner(
+		source: PaymentSource,
+		content_owner: T::AccountId,
+		payer: T::AccountId,
+		amount: u128,
+	) -> DispatchResult;
 }
 
 pub trait CustomerDepositor<T: frame_system::Config> {
 	fn deposit(customer: T::AccountId, amount: u128) -> Result<(), DispatchError>;
 	fn deposit_extra(customer: T::AccountId, amount: u128) -> Result<(), DispatchError>;
 }
+
+pub trait BucketVisitor<T: frame_system::Config> {
+	fn ensure_bucket_owner(
+		cluster_id: &ClusterId,
+		bucket_id: BucketId,
+		owner_id: &T::AccountId,
+	) -> Result<(), BucketVisitorError>;
+
+	fn get_bucket_metadata(bucket_id: BucketId) -> Result<BucketMetadata, BucketVisitorError>;
+
+	#[cfg(feature = "runtime-benchmarks")]
+	fn create_bucket(
+		cluster_id: &ClusterId,
+		owner_id: T::AccountId,
+	) -> Result<BucketId, DispatchError>;
+}
+
+#[derive(Clone, Encode, Decode, RuntimeDebug, TypeInfo, PartialEq)]
+pub enum BucketVisitorError {
+	NoBucketWithId,
+	NotBucketOwner,
+	IncorrectClusterId,
+}
@@ -2,6 +2,7 @@ pub mod cluster;
 pub mod customer;
 pub mod node;
 pub mod pallet;
+pub mod payout;
 pub mod staking;
 pub mod validator;
 
@@ -9,5 +10,6 @@ pub use cluster::*;
 pub use customer::*;
 pub use node::*;
 pub use pallet::*;
+pub use payout::*;
 pub use staking::*;
 pub use validator::*;
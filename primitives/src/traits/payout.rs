@@ -0,0 +1,23 @@
+use frame_system::Config;
+
+use crate::{BucketId, ClusterId, DdcEra};
+
+pub trait PayoutVisitor<T: Config> {
+	fn get_customer_debt(cluster_id: &ClusterId, customer_id: &T::AccountId) -> u128;
+
+	/// Outstanding debt of the bucket charged from its own allocation.
+	fn get_bucket_debt(cluster_id: &ClusterId, bucket_id: BucketId) -> u128;
+
+	/// Total outstanding debt of the buckets of the owner charged from their own allocation.
+	fn get_buckets_debt(cluster_id: &ClusterId, owner_id: &T::AccountId) -> u128;
+
+	/// Whether a billing report of the cluster is being charged, rewarded, disputed or rolled
+	/// back, so that the buckets of the cluster must keep their owners and cluster.
+	fn has_open_billing_report(cluster_id: &ClusterId) -> bool;
+
+	/// The latest era the cluster has a finalized billing report for.
+	fn get_last_finalized_era(cluster_id: &ClusterId) -> Option<DdcEra>;
+
+	#[cfg(feature = "runtime-benchmarks")]
+	fn set_last_finalized_era(cluster_id: &ClusterId, era: DdcEra);
+}
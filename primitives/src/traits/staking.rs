@@ -1,4 +1,5 @@
 use frame_system::Config;
+use sp_std::prelude::*;
 
 use crate::{ClusterId, NodePubKey};
 
@@ -23,7 +24,18 @@ pub trait StakerCreator<T: Config, Balance> {
 	) -> sp_runtime::DispatchResult;
 }
 
+pub trait DelegationVisitor<T: Config, Balance> {
+	/// Shares of the node reward owed to the delegators of the node, less the commission of the
+	/// node provider, who keeps the rest.
+	fn get_delegators_rewards(
+		provider_id: &T::AccountId,
+		node_pub_key: &NodePubKey,
+		reward: Balance,
+	) -> Vec<(T::AccountId, Balance)>;
+}
+
 pub enum StakingVisitorError {
 	NodeStakeDoesNotExist,
 	NodeStakeIsInBadState,
+	NodeStakeIsUnderBonded,
 }
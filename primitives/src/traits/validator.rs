@@ -3,4 +3,7 @@ use sp_std::prelude::*;
 
 pub trait ValidatorVisitor<T: Config> {
 	fn get_active_validators() -> Vec<T::AccountId>;
+
+	#[cfg(feature = "runtime-benchmarks")]
+	fn setup_validators(validators: Vec<T::AccountId>);
 }
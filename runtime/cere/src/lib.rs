@@ -23,7 +23,10 @@
 #![recursion_limit = "256"]
 
 use codec::{Decode, Encode, MaxEncodedLen};
-use ddc_primitives::traits::pallet::PalletVisitor;
+use ddc_primitives::{
+	traits::{pallet::PalletVisitor, validator::ValidatorVisitor},
+	AssetId, BucketId, ClusterId, DdcEra,
+};
 use frame_election_provider_support::{onchain, BalancingConfig, SequentialPhragmen, VoteWeight};
 use frame_support::{
 	construct_runtime,
@@ -31,9 +34,10 @@ use frame_support::{
 	pallet_prelude::{Get, StorageVersion},
 	parameter_types,
 	traits::{
-		ConstBool, ConstU128, ConstU16, ConstU32, Currency, EitherOfDiverse, EqualPrivilegeOnly,
-		Everything, GetStorageVersion, Imbalance, InstanceFilter, KeyOwnerProofSystem,
-		LockIdentifier, Nothing, OnRuntimeUpgrade, OnUnbalanced, WithdrawReasons,
+		AsEnsureOriginWithArg, ConstBool, ConstU128, ConstU16, ConstU32, Currency, EitherOfDiverse,
+		EqualPrivilegeOnly, Everything, GetStorageVersion, Imbalance, InstanceFilter,
+		KeyOwnerProofSystem, LockIdentifier, Nothing, OnRuntimeUpgrade, OnUnbalanced,
+		WithdrawReasons,
 	},
 	weights::{
 		constants::{
@@ -1330,9 +1334,43 @@ impl frame_support::traits::OnRuntimeUpgrade for InitiateNominationPools {
 	}
 }
 
+parameter_types! {
+	pub const AssetDeposit: Balance = 100 * DOLLARS;
+	pub const AssetAccountDeposit: Balance = deposit(1, 16);
+	pub const ApprovalDeposit: Balance = DOLLARS;
+	pub const AssetsStringLimit: u32 = 50;
+	pub const MetadataDepositBase: Balance = deposit(1, 68);
+	pub const MetadataDepositPerByte: Balance = deposit(0, 1);
+}
+
+impl pallet_assets::Config for Runtime {
+	type RuntimeEvent = RuntimeEvent;
+	type Balance = Balance;
+	type AssetId = AssetId;
+	type AssetIdParameter = codec::Compact<AssetId>;
+	type Currency = Balances;
+	type CreateOrigin = AsEnsureOriginWithArg<EnsureRoot<AccountId>>;
+	type ForceOrigin = EnsureRoot<AccountId>;
+	type AssetDeposit = AssetDeposit;
+	type AssetAccountDeposit = AssetAccountDeposit;
+	type MetadataDepositBase = MetadataDepositBase;
+	type MetadataDepositPerByte = MetadataDepositPerByte;
+	type ApprovalDeposit = ApprovalDeposit;
+	type StringLimit = AssetsStringLimit;
+	type Freezer = ();
+	type Extra = ();
+	type CallbackHandle = ();
+	type WeightInfo = pallet_assets::weights::SubstrateWeight<Runtime>;
+	type RemoveItemsLimit = ConstU32<1000>;
+	#[cfg(feature = "runtime-benchmarks")]
+	type BenchmarkHelper = ();
+}
+
 parameter_types! {
 	pub const DdcCustomersPalletId: PalletId = PalletId(*b"accounts"); // DDC maintainer's stake
 	pub const UnlockingDelay: BlockNumber = 100800_u32; // 1 hour * 24 * 7 = 7 days; (1 hour is 600 blocks)
+	pub const BucketDeposit: Balance = DOLLARS;
+	pub const BucketGracePeriod: BlockNumber = 7 * DAYS;
 }
 
 impl pallet_ddc_customers::Config for Runtime {
@@ -1342,6 +1380,10 @@ impl pallet_ddc_customers::Config for Runtime {
 	type RuntimeEvent = RuntimeEvent;
 	type ClusterVisitor = pallet_ddc_clusters::Pallet<Runtime>;
 	type ClusterCreator = pallet_ddc_clusters::Pallet<Runtime>;
+	type PayoutVisitor = pallet_ddc_payouts::Pallet<Runtime>;
+	type BucketDeposit = BucketDeposit;
+	type BucketGracePeriod = BucketGracePeriod;
+	type Assets = Assets;
 	type WeightInfo = pallet_ddc_customers::weights::SubstrateWeight<Runtime>;
 }
 
@@ -1352,6 +1394,7 @@ impl pallet_ddc_clusters::Config for Runtime {
 	type StakerCreator = pallet_ddc_staking::Pallet<Runtime>;
 	type Currency = Balances;
 	type WeightInfo = pallet_ddc_clusters::weights::SubstrateWeight<Runtime>;
+	type OnBondingParamsChanged = pallet_ddc_staking::Pallet<Runtime>;
 }
 
 impl pallet_ddc_nodes::Config for Runtime {
@@ -1362,6 +1405,9 @@ impl pallet_ddc_nodes::Config for Runtime {
 
 parameter_types! {
 	pub const PayoutsPalletId: PalletId = PalletId(*b"payouts_");
+	pub const PayoutsDisputePeriod: BlockNumber = 2 * DAYS;
+	pub const PayoutsDisputeBond: Balance = 1_000 * DOLLARS;
+	pub const PayoutsBillingReportRetentionPeriod: BlockNumber = 30 * DAYS;
 }
 
 pub struct TreasuryWrapper;
@@ -1371,18 +1417,41 @@ impl<T: frame_system::Config> PalletVisitor<T> for TreasuryWrapper {
 	}
 }
 
+pub struct DdcValidatorsWrapper;
+impl ValidatorVisitor<Runtime> for DdcValidatorsWrapper {
+	fn get_active_validators() -> Vec<AccountId> {
+		Session::validators()
+	}
+
+	#[cfg(feature = "runtime-benchmarks")]
+	fn setup_validators(validators: Vec<AccountId>) {
+		pallet_session::Validators::<Runtime>::put(validators);
+	}
+}
+
 impl pallet_ddc_payouts::Config for Runtime {
 	type RuntimeEvent = RuntimeEvent;
 	type PalletId = PayoutsPalletId;
 	type Currency = Balances;
 	type CustomerCharger = DdcCustomers;
 	type CustomerDepositor = DdcCustomers;
+	type BucketVisitor = DdcCustomers;
 	type ClusterVisitor = DdcClusters;
 	type TreasuryVisitor = TreasuryWrapper;
 	type NominatorsAndValidatorsList = pallet_staking::UseNominatorsAndValidatorsMap<Self>;
 	type ClusterCreator = DdcClusters;
+	type ValidatorVisitor = DdcValidatorsWrapper;
+	type DelegationVisitor = pallet_ddc_staking::Pallet<Runtime>;
 	type WeightInfo = pallet_ddc_payouts::weights::SubstrateWeight<Runtime>;
 	type VoteScoreToU64 = IdentityConvert; // used for UseNominatorsAndValidatorsMap
+	type DisputePeriod = PayoutsDisputePeriod;
+	type DisputeBond = PayoutsDisputeBond;
+	type BillingReportRetentionPeriod = PayoutsBillingReportRetentionPeriod;
+}
+
+parameter_types! {
+	pub const DdcSlashDeferDuration: BlockNumber = 7 * DAYS;
+	pub const DdcUnderBondedGracePeriod: BlockNumber = 7 * DAYS;
 }
 
 impl pallet_ddc_staking::Config for Runtime {
@@ -1394,6 +1463,10 @@ impl pallet_ddc_staking::Config for Runtime {
 	type ClusterManager = pallet_ddc_clusters::Pallet<Runtime>;
 	type NodeVisitor = pallet_ddc_nodes::Pallet<Runtime>;
 	type NodeCreator = pallet_ddc_nodes::Pallet<Runtime>;
+	type TreasuryVisitor = TreasuryWrapper;
+	type ValidatorVisitor = DdcValidatorsWrapper;
+	type SlashDeferDuration = DdcSlashDeferDuration;
+	type UnderBondedGracePeriod = DdcUnderBondedGracePeriod;
 }
 
 construct_runtime!(
@@ -1447,7 +1520,8 @@ construct_runtime!(
 		DdcCustomers: pallet_ddc_customers,
 		DdcNodes: pallet_ddc_nodes,
 		DdcClusters: pallet_ddc_clusters,
-		DdcPayouts: pallet_ddc_payouts
+		DdcPayouts: pallet_ddc_payouts,
+		Assets: pallet_assets,
 	}
 );
 
@@ -1529,6 +1603,12 @@ type Migrations = (
 	pallet_staking::migrations::v13::MigrateToV13<Runtime>,
 	pallet_society::migrations::MigrateToV2<Runtime, (), ()>,
 	pallet_ddc_customers::migration::MigrateToV1<Runtime>,
+	pallet_ddc_customers::migration::MigrateToV2<Runtime>,
+	pallet_ddc_payouts::migration::MigrateToV1<Runtime>,
+	pallet_ddc_payouts::migration::MigrateToV2<Runtime>,
+	pallet_ddc_payouts::migration::MigrateToV3<Runtime>,
+	pallet_ddc_clusters::migration::MigrateToV1<Runtime>,
+	pallet_ddc_staking::migration::MigrateToV1<Runtime>,
 	SetStorageVersions,
 );
 
@@ -1555,6 +1635,7 @@ extern crate frame_benchmarking;
 mod benches {
 	define_benchmarks!(
 		[frame_benchmarking, BaselineBench::<Runtime>]
+		[pallet_assets, Assets]
 		[pallet_babe, Babe]
 		[pallet_bags_list, VoterList]
 		[pallet_balances, Balances]
@@ -1885,6 +1966,46 @@ impl_runtime_apis! {
 		}
 	}
 
+	impl pallet_ddc_customers_runtime_api::DdcCustomersApi<Block, AccountId, Balance, BlockNumber>
+		for Runtime
+	{
+		fn bucket_access(
+			bucket_id: BucketId,
+			account_id: AccountId,
+		) -> Option<pallet_ddc_customers::BucketPermission> {
+			DdcCustomers::bucket_access(bucket_id, &account_id)
+		}
+
+		fn bucket_grants(
+			bucket_id: BucketId,
+		) -> Vec<(AccountId, pallet_ddc_customers::BucketGrant<BlockNumber, Balance>)> {
+			DdcCustomers::bucket_grants(bucket_id)
+		}
+	}
+
+	impl pallet_ddc_payouts_runtime_api::DdcPayoutsApi<Block, AccountId> for Runtime {
+		fn billing_report(
+			cluster_id: ClusterId,
+			era: DdcEra,
+		) -> Option<pallet_ddc_payouts::BillingReportInfo> {
+			DdcPayouts::billing_report_info(cluster_id, era)
+		}
+
+		fn billing_reports(
+			cluster_id: ClusterId,
+		) -> Vec<(DdcEra, pallet_ddc_payouts::BillingReportInfo)> {
+			DdcPayouts::billing_reports_info(cluster_id)
+		}
+
+		fn customer_debt(cluster_id: ClusterId, customer_id: AccountId) -> u128 {
+			DdcPayouts::debtor_customers(cluster_id, customer_id).unwrap_or_default()
+		}
+
+		fn customers_debts(cluster_id: ClusterId) -> Vec<(AccountId, u128)> {
+			DdcPayouts::customers_debts(cluster_id)
+		}
+	}
+
 	#[cfg(feature = "try-runtime")]
 	impl frame_try_runtime::TryRuntime<Block> for Runtime {
 		fn on_runtime_upgrade(checks: frame_try_runtime::UpgradeCheckSelect) -> (Weight, Weight) {